@@ -0,0 +1,20 @@
+// Emits `cfg(js_host)` for targets whose imports are provided by a JS engine
+// (browsers, Web Workers, Node). WASI and native builds use std instead, so code
+// behind the cfg must have a std fallback.
+//
+// Emits `cfg(native_simd)` on x86_64 and aarch64, which get the AVX2/NEON kernels
+// in native_simd.rs in place of simd128.
+
+use std::env;
+
+fn main() {
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if arch == "wasm32" && os != "wasi" {
+        println!("cargo:rustc-cfg=js_host");
+    }
+    if arch == "x86_64" || arch == "aarch64" {
+        println!("cargo:rustc-cfg=native_simd");
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
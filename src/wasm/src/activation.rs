@@ -0,0 +1,349 @@
+// Activation functions applied per call or per layer
+// Elementwise kinds are vectorised four lanes at a time; softmax normalises over the whole buffer.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::error::NeuralError;
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+// Negative-side slope used by LeakyReLU
+pub const LEAKY_RELU_SLOPE: f32 = 0.01;
+
+// sqrt(2 / pi), used by the tanh form of GELU
+const GELU_SCALE: f32 = 0.797_884_6;
+const GELU_CUBIC: f32 = 0.044_715;
+
+// Speed/accuracy trade-off of the SIMD exp, sigmoid and tanh kernels (and GELU,
+// which is built on sigmoid). Maximum errors measured against f64 over a sweep of
+// f32 inputs; exp clamps its argument to [-87.3, 88.3] in both modes:
+//   Accurate  exp      1 ulp
+//             sigmoid  9e-8 absolute, 2.5 ulp for x > -87
+//             tanh     1.5 ulp
+//   Fast      exp      8e-5 relative
+//             sigmoid  2e-5 absolute
+//             tanh     4e-5 absolute (no relative bound near zero)
+// Scalar paths (non-SIMD builds and leftover elements) always use the standard
+// library, so Fast only changes results where the SIMD kernels run.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationAccuracy {
+    Fast = 0,
+    Accurate = 1,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationKind {
+    Linear = 0,
+    ReLU = 1,
+    Sigmoid = 2,
+    Tanh = 3,
+    LeakyReLU = 4,
+    GELU = 5,
+    Softmax = 6,
+}
+
+impl ActivationKind {
+    // Inverse of `kind as u8`, used when decoding serialized models
+    pub fn from_u8(value: u8) -> Option<ActivationKind> {
+        match value {
+            0 => Some(ActivationKind::Linear),
+            1 => Some(ActivationKind::ReLU),
+            2 => Some(ActivationKind::Sigmoid),
+            3 => Some(ActivationKind::Tanh),
+            4 => Some(ActivationKind::LeakyReLU),
+            5 => Some(ActivationKind::GELU),
+            6 => Some(ActivationKind::Softmax),
+            _ => None,
+        }
+    }
+
+    // Name used by model specs and describe()
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ActivationKind::Linear => "linear",
+            ActivationKind::ReLU => "relu",
+            ActivationKind::Sigmoid => "sigmoid",
+            ActivationKind::Tanh => "tanh",
+            ActivationKind::LeakyReLU => "leaky_relu",
+            ActivationKind::GELU => "gelu",
+            ActivationKind::Softmax => "softmax",
+        }
+    }
+
+    // Apply the activation to every element of a buffer in place
+    pub fn apply_slice(self, values: &mut [f32], simd: bool) {
+        self.apply_slice_with(values, simd, ActivationAccuracy::Accurate);
+    }
+
+    pub fn apply_slice_with(self, values: &mut [f32], simd: bool, accuracy: ActivationAccuracy) {
+        match self {
+            ActivationKind::Linear => {}
+            ActivationKind::Softmax => softmax_in_place(values, simd),
+            _ => simd_dispatch!(simd && values.len() >= 4, self.simd_apply(values, accuracy), {
+                // The scalar kernels are always accurate
+                let _ = accuracy;
+                self.scalar_apply_slice(values)
+            }),
+        }
+    }
+
+    fn scalar_apply_slice(self, values: &mut [f32]) {
+        for value in values.iter_mut() {
+            *value = self.scalar_apply(*value);
+        }
+    }
+
+    // Turn dL/d(output) in `grad` into dL/d(input), given the pre-activation values
+    // and the activation outputs of the same slice
+    pub fn backprop_slice(self, pre: &[f32], post: &[f32], grad: &mut [f32]) {
+        match self {
+            ActivationKind::Linear => {}
+            ActivationKind::Softmax => {
+                // Jacobian-vector product: dz_i = y_i · (g_i - Σ_j g_j · y_j)
+                let weighted: f32 = grad.iter().zip(post).map(|(g, y)| g * y).sum();
+                for (g, y) in grad.iter_mut().zip(post) {
+                    *g = y * (*g - weighted);
+                }
+            }
+            _ => {
+                for ((g, &x), &y) in grad.iter_mut().zip(pre).zip(post) {
+                    *g *= self.scalar_derivative(x, y);
+                }
+            }
+        }
+    }
+
+    fn scalar_derivative(self, x: f32, y: f32) -> f32 {
+        match self {
+            ActivationKind::Linear | ActivationKind::Softmax => 1.0,
+            ActivationKind::ReLU => if x > 0.0 { 1.0 } else { 0.0 },
+            ActivationKind::LeakyReLU => if x >= 0.0 { 1.0 } else { LEAKY_RELU_SLOPE },
+            ActivationKind::Sigmoid => y * (1.0 - y),
+            ActivationKind::Tanh => 1.0 - y * y,
+            ActivationKind::GELU => {
+                let t = (GELU_SCALE * (x + GELU_CUBIC * x * x * x)).tanh();
+                0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * GELU_SCALE * (1.0 + 3.0 * GELU_CUBIC * x * x)
+            }
+        }
+    }
+
+    // Scalar evaluation of elementwise kinds; softmax is only defined over a slice
+    pub(crate) fn scalar_apply(self, x: f32) -> f32 {
+        match self {
+            ActivationKind::Linear | ActivationKind::Softmax => x,
+            ActivationKind::ReLU => x.max(0.0),
+            ActivationKind::LeakyReLU => if x >= 0.0 { x } else { x * LEAKY_RELU_SLOPE },
+            ActivationKind::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            ActivationKind::Tanh => x.tanh(),
+            ActivationKind::GELU => {
+                let inner = GELU_SCALE * (x + GELU_CUBIC * x * x * x);
+                0.5 * x * (1.0 + inner.tanh())
+            }
+        }
+    }
+
+    #[cfg(target_feature = "simd128")]
+    fn simd_apply(self, values: &mut [f32], accuracy: ActivationAccuracy) {
+        let mut chunks = values.chunks_exact_mut(4);
+
+        for chunk in &mut chunks {
+            simd::store(chunk, self.simd_lanes(simd::load(chunk), accuracy));
+        }
+
+        // Handle remaining elements with scalar operations
+        for value in chunks.into_remainder() {
+            *value = self.scalar_apply(*value);
+        }
+    }
+
+    #[cfg(target_feature = "simd128")]
+    pub(crate) fn simd_lanes(self, x: v128, accuracy: ActivationAccuracy) -> v128 {
+        match self {
+            ActivationKind::Linear | ActivationKind::Softmax => x,
+            ActivationKind::ReLU => f32x4_max(x, f32x4_splat(0.0)),
+            // slope < 1, so max(x, slope * x) selects x for positives and slope * x for negatives
+            ActivationKind::LeakyReLU => f32x4_max(x, f32x4_mul(x, f32x4_splat(LEAKY_RELU_SLOPE))),
+            ActivationKind::Sigmoid => simd_sigmoid(x, accuracy),
+            ActivationKind::Tanh => simd_tanh(x, accuracy),
+            ActivationKind::GELU => {
+                // 0.5x(1 + tanh(z)) == x * sigmoid(2z)
+                let x3 = f32x4_mul(f32x4_mul(x, x), x);
+                let inner = f32x4_mul(f32x4_splat(GELU_SCALE), f32x4_add(x, f32x4_mul(f32x4_splat(GELU_CUBIC), x3)));
+                f32x4_mul(x, simd_sigmoid(f32x4_add(inner, inner), accuracy))
+            }
+        }
+    }
+}
+
+// e^x via range reduction x = n·ln2 + r and a degree-6 polynomial for e^r (Cephes expf)
+#[cfg(target_feature = "simd128")]
+pub(crate) fn simd_exp(x: v128) -> v128 {
+    let x = f32x4_min(f32x4_max(x, f32x4_splat(-87.3)), f32x4_splat(88.3));
+
+    let n = f32x4_nearest(f32x4_mul(x, f32x4_splat(std::f32::consts::LOG2_E)));
+    let r = f32x4_sub(x, f32x4_mul(n, f32x4_splat(0.693_359_4)));
+    let r = f32x4_sub(r, f32x4_mul(n, f32x4_splat(-2.121_944_4e-4)));
+
+    let mut p = f32x4_splat(1.987_569_1e-4);
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.398_2e-3));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(8.333_452e-3));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(4.166_579_6e-2));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.666_666_5e-1));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(0.5));
+    let r2 = f32x4_mul(r, r);
+    let poly = f32x4_add(f32x4_add(f32x4_mul(p, r2), r), f32x4_splat(1.0));
+
+    // Build 2^n directly in the exponent bits
+    let exponent = i32x4_shl(i32x4_add(i32x4_trunc_sat_f32x4(n), i32x4_splat(127)), 23);
+    f32x4_mul(poly, exponent)
+}
+
+// e^x via the same range reduction and a degree-3 minimax polynomial for e^r
+#[cfg(target_feature = "simd128")]
+fn simd_exp_fast(x: v128) -> v128 {
+    let x = f32x4_min(f32x4_max(x, f32x4_splat(-87.3)), f32x4_splat(88.3));
+
+    let n = f32x4_nearest(f32x4_mul(x, f32x4_splat(std::f32::consts::LOG2_E)));
+    let r = f32x4_sub(x, f32x4_mul(n, f32x4_splat(std::f32::consts::LN_2)));
+
+    let mut p = f32x4_splat(1.656_683e-1);
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(5.049_633e-1));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.000_164_2));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(9.999_280_7e-1));
+
+    let exponent = i32x4_shl(i32x4_add(i32x4_trunc_sat_f32x4(n), i32x4_splat(127)), 23);
+    f32x4_mul(p, exponent)
+}
+
+#[cfg(target_feature = "simd128")]
+pub(crate) fn simd_sigmoid(x: v128, accuracy: ActivationAccuracy) -> v128 {
+    let one = f32x4_splat(1.0);
+    let e = match accuracy {
+        ActivationAccuracy::Accurate => simd_exp(f32x4_neg(x)),
+        ActivationAccuracy::Fast => simd_exp_fast(f32x4_neg(x)),
+    };
+    f32x4_div(one, f32x4_add(one, e))
+}
+
+#[cfg(target_feature = "simd128")]
+pub(crate) fn simd_tanh(x: v128, accuracy: ActivationAccuracy) -> v128 {
+    match accuracy {
+        ActivationAccuracy::Accurate => simd_tanh_accurate(x),
+        ActivationAccuracy::Fast => {
+            // tanh(x) = 2·sigmoid(2x) - 1, which loses relative precision near zero
+            let s = simd_sigmoid(f32x4_add(x, x), accuracy);
+            f32x4_sub(f32x4_add(s, s), f32x4_splat(1.0))
+        }
+    }
+}
+
+// Odd polynomial for |x| < 0.625 (Cephes tanhf), where 1 - 2/(e^2|x| + 1) cancels;
+// that form with the sign of x restored everywhere else
+#[cfg(target_feature = "simd128")]
+fn simd_tanh_accurate(x: v128) -> v128 {
+    let abs_x = f32x4_abs(x);
+    let z = f32x4_mul(x, x);
+
+    let mut p = f32x4_splat(-5.704_988_7e-3);
+    p = f32x4_add(f32x4_mul(p, z), f32x4_splat(2.063_909e-2));
+    p = f32x4_add(f32x4_mul(p, z), f32x4_splat(-5.373_971_6e-2));
+    p = f32x4_add(f32x4_mul(p, z), f32x4_splat(1.333_144_2e-1));
+    p = f32x4_add(f32x4_mul(p, z), f32x4_splat(-3.333_328e-1));
+    let small = f32x4_add(x, f32x4_mul(f32x4_mul(x, z), p));
+
+    let one = f32x4_splat(1.0);
+    let e = simd_exp(f32x4_add(abs_x, abs_x));
+    let large = f32x4_sub(one, f32x4_div(f32x4_splat(2.0), f32x4_add(e, one)));
+    let signed = v128_or(large, v128_and(x, f32x4_splat(-0.0)));
+
+    v128_bitselect(small, signed, f32x4_lt(abs_x, f32x4_splat(0.625)))
+}
+
+// Probabilities from logits, for classifiers whose last layer is linear
+#[wasm_bindgen]
+pub fn softmax(logits: &[f32]) -> Result<Vec<f32>, NeuralError> {
+    check_finite(logits)?;
+    let mut probabilities = logits.to_vec();
+    softmax_in_place(&mut probabilities, crate::check_simd_support());
+    Ok(probabilities)
+}
+
+// Index of the largest value (the first one on ties)
+#[wasm_bindgen]
+pub fn argmax(values: &[f32]) -> Result<usize, NeuralError> {
+    check_finite(values)?;
+    argmax_index(values, crate::check_simd_support())
+        .ok_or_else(|| NeuralError::InvalidConfiguration("argmax of an empty slice".to_string()))
+}
+
+pub(crate) fn check_finite(values: &[f32]) -> Result<(), NeuralError> {
+    match values.iter().position(|x| !x.is_finite()) {
+        Some(index) => Err(NeuralError::NonFiniteInput { index }),
+        None => Ok(()),
+    }
+}
+
+// Largest element; negative infinity for an empty slice
+pub fn max_value(values: &[f32], simd: bool) -> f32 {
+    simd_dispatch!(simd && values.len() >= 4, simd_max(values), scalar_max(values))
+}
+
+// Index of the first largest element; None for an empty slice or one holding NaN
+pub fn argmax_index(values: &[f32], simd: bool) -> Option<usize> {
+    let max = max_value(values, simd);
+    values.iter().position(|&value| value == max)
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_max(values: &[f32]) -> f32 {
+    let chunks = values.chunks_exact(4);
+    let tail = scalar_max(chunks.remainder());
+    let mut acc = f32x4_splat(f32::NEG_INFINITY);
+
+    for chunk in chunks {
+        acc = f32x4_max(acc, simd::load(chunk));
+    }
+
+    scalar_max(&simd::f32_lanes(acc)).max(tail)
+}
+
+fn scalar_max(values: &[f32]) -> f32 {
+    values.iter().copied().fold(f32::NEG_INFINITY, f32::max)
+}
+
+// Numerically stable softmax: subtract the maximum before exponentiating
+fn softmax_in_place(values: &mut [f32], simd: bool) {
+    if values.is_empty() {
+        return;
+    }
+
+    let max = max_value(values, simd);
+    let done = simd_dispatch!(simd, simd_exp_shifted(values, max), 0);
+    for value in values[done..].iter_mut() {
+        *value = (*value - max).exp();
+    }
+
+    let sum: f32 = values.iter().sum();
+    let inv_sum = 1.0 / sum;
+    for value in values.iter_mut() {
+        *value *= inv_sum;
+    }
+}
+
+// Replace each full group of four with e^(x - shift); returns how many elements were processed
+#[cfg(target_feature = "simd128")]
+fn simd_exp_shifted(values: &mut [f32], shift: f32) -> usize {
+    let len = values.len();
+    let mut chunks = values.chunks_exact_mut(4);
+    let shift_vec = f32x4_splat(shift);
+
+    for chunk in &mut chunks {
+        simd::store(chunk, simd_exp(f32x4_sub(simd::load(chunk), shift_vec)));
+    }
+    len - chunks.into_remainder().len()
+}
@@ -0,0 +1,303 @@
+// Many isolated agents behind one handle
+//
+// Each agent owns its network (with its own initializer RNG and recurrent state)
+// and optionally a spiking network, so agents never share mutable state. Agents are
+// addressed by IDs that are never reused within a pool, and batch calls visit them
+// in ascending ID order so JavaScript can iterate 25+ agents in one WASM call.
+//
+// A quota caps the bytes an agent's models may hold (0 = unlimited). It is checked
+// whenever an agent's models are installed or replaced, and a model that would not
+// fit is refused with MemoryLimitExceeded, leaving the agent as it was.
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::attestation::Attestation;
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+use crate::spiking::SpikingNetwork;
+
+#[derive(Debug, Clone)]
+struct Agent {
+    network: NeuralNetwork,
+    spiking: Option<SpikingNetwork>,
+    quota: usize,
+}
+
+impl Agent {
+    fn spiking_bytes(&self) -> usize {
+        self.spiking.as_ref().map_or(0, SpikingNetwork::memory_bytes)
+    }
+
+    fn memory_bytes(&self) -> usize {
+        self.network.parameter_bytes() + self.spiking_bytes()
+    }
+
+    // `installed` is the share of the agent's bytes that was just added
+    fn check_quota(&self, installed: usize) -> NeuralResult<()> {
+        let used = self.memory_bytes();
+        if self.quota > 0 && used > self.quota {
+            return Err(NeuralError::MemoryLimitExceeded { requested: installed, in_use: used - installed, limit: self.quota });
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct AgentPool {
+    agents: BTreeMap<u32, Agent>,
+    next_id: u32,
+    default_quota: usize,
+}
+
+#[wasm_bindgen]
+impl AgentPool {
+    // `default_quota` bytes apply to agents added later; 0 means unlimited
+    #[wasm_bindgen(constructor)]
+    pub fn new(default_quota: usize) -> AgentPool {
+        AgentPool { agents: BTreeMap::new(), next_id: 1, default_quota }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn default_quota(&self) -> usize {
+        self.default_quota
+    }
+
+    // Quota for agents added from now on; existing agents keep theirs
+    #[wasm_bindgen(setter)]
+    pub fn set_default_quota(&mut self, bytes: usize) {
+        self.default_quota = bytes;
+    }
+
+    // Add an agent running a copy of `network` and return its ID
+    #[wasm_bindgen]
+    pub fn add_agent(&mut self, network: &NeuralNetwork) -> Result<u32, NeuralError> {
+        let id = self.next_id;
+        let next_id = id.checked_add(1).ok_or_else(|| NeuralError::InvalidConfiguration("agent IDs exhausted".to_string()))?;
+        let agent = Agent { network: network.clone(), spiking: None, quota: self.default_quota };
+        agent.check_quota(agent.memory_bytes())?;
+        self.agents.insert(id, agent);
+        self.next_id = next_id;
+        Ok(id)
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_agent(&mut self, id: u32) -> Result<(), NeuralError> {
+        self.agents.remove(&id).map(|_| ()).ok_or_else(|| unknown_agent(id))
+    }
+
+    #[wasm_bindgen]
+    pub fn contains(&self, id: u32) -> bool {
+        self.agents.contains_key(&id)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn agent_count(&self) -> usize {
+        self.agents.len()
+    }
+
+    // IDs in the order batch operations visit them
+    #[wasm_bindgen]
+    pub fn agent_ids(&self) -> Vec<u32> {
+        self.agents.keys().copied().collect()
+    }
+
+    // Copy of the agent's network
+    #[wasm_bindgen]
+    pub fn network(&self, id: u32) -> Result<NeuralNetwork, NeuralError> {
+        Ok(self.agent(id)?.network.clone())
+    }
+
+    // Replace the agent's network; its input and output sizes may change
+    #[wasm_bindgen]
+    pub fn set_network(&mut self, id: u32, network: &NeuralNetwork) -> Result<(), NeuralError> {
+        let agent = self.agent_mut(id)?;
+        let previous = std::mem::replace(&mut agent.network, network.clone());
+        if let Err(err) = agent.check_quota(network.parameter_bytes()) {
+            agent.network = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    // Give the agent a copy of `spiking` for tick_all, replacing any previous one
+    #[wasm_bindgen]
+    pub fn set_spiking(&mut self, id: u32, spiking: &SpikingNetwork) -> Result<(), NeuralError> {
+        let agent = self.agent_mut(id)?;
+        let previous = agent.spiking.replace(spiking.clone());
+        if let Err(err) = agent.check_quota(agent.spiking_bytes()) {
+            agent.spiking = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn spiking(&self, id: u32) -> Result<SpikingNetwork, NeuralError> {
+        self.agent(id)?
+            .spiking
+            .clone()
+            .ok_or_else(|| NeuralError::InvalidConfiguration(format!("agent {} has no spiking network", id)))
+    }
+
+    #[wasm_bindgen]
+    pub fn set_input_currents(&mut self, id: u32, currents: &[f32]) -> Result<(), NeuralError> {
+        match self.agent_mut(id)?.spiking.as_mut() {
+            Some(spiking) => spiking.set_input_currents(currents),
+            None => Err(NeuralError::InvalidConfiguration(format!("agent {} has no spiking network", id))),
+        }
+    }
+
+    // Byte limit for this agent; 0 means unlimited. Fails if the agent already exceeds it.
+    #[wasm_bindgen]
+    pub fn set_quota(&mut self, id: u32, bytes: usize) -> Result<(), NeuralError> {
+        let agent = self.agent_mut(id)?;
+        let previous = std::mem::replace(&mut agent.quota, bytes);
+        if let Err(err) = agent.check_quota(0) {
+            agent.quota = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn quota(&self, id: u32) -> Result<usize, NeuralError> {
+        Ok(self.agent(id)?.quota)
+    }
+
+    // Bytes held by the agent's network parameters and spiking state
+    #[wasm_bindgen]
+    pub fn memory_usage(&self, id: u32) -> Result<usize, NeuralError> {
+        Ok(self.agent(id)?.memory_bytes())
+    }
+
+    // Bytes held by all agents
+    #[wasm_bindgen]
+    pub fn total_memory_usage(&self) -> usize {
+        self.agents.values().map(Agent::memory_bytes).sum()
+    }
+
+    // Per-agent breakdown of network and spiking bytes against each quota, as a parsed
+    // JSON object; see memory_report_json
+    #[wasm_bindgen]
+    pub fn memory_report(&self) -> Result<JsValue, NeuralError> {
+        js_sys::JSON::parse(&self.memory_report_json())
+            .map_err(|_| NeuralError::InvalidFormat("memory report is not valid JSON".to_string()))
+    }
+
+    // {"total_bytes", "default_quota", "agents": [{"id", "network_bytes", "spiking_bytes",
+    // "total_bytes", "quota", "quota_used"}]}, where quota_used is a fraction and null
+    // for unlimited agents
+    #[wasm_bindgen]
+    pub fn memory_report_json(&self) -> String {
+        let agents: Vec<String> = self
+            .agents
+            .iter()
+            .map(|(id, agent)| {
+                let total = agent.memory_bytes();
+                let quota_used = if agent.quota == 0 { "null".to_string() } else { (total as f64 / agent.quota as f64).to_string() };
+                format!(
+                    "{{\"id\":{},\"network_bytes\":{},\"spiking_bytes\":{},\"total_bytes\":{},\"quota\":{},\"quota_used\":{}}}",
+                    id,
+                    agent.network.parameter_bytes(),
+                    agent.spiking_bytes(),
+                    total,
+                    agent.quota,
+                    quota_used
+                )
+            })
+            .collect();
+        format!(
+            "{{\"total_bytes\":{},\"default_quota\":{},\"agents\":[{}]}}",
+            self.total_memory_usage(),
+            self.default_quota,
+            agents.join(",")
+        )
+    }
+
+    // Architecture, parameter counts, cost and memory footprint of one agent's
+    // network; see NeuralNetwork.describe_json
+    #[wasm_bindgen]
+    pub fn describe(&self, id: u32) -> Result<JsValue, NeuralError> {
+        self.agent(id)?.network.describe()
+    }
+
+    #[wasm_bindgen]
+    pub fn forward(&self, id: u32, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.agent(id)?.network.forward(inputs)
+    }
+
+    // One agent's forward with an attestation digest; see NeuralNetwork.compute_and_attest
+    #[wasm_bindgen]
+    pub fn compute_and_attest(&self, id: u32, inputs: &[f32]) -> Result<Attestation, NeuralError> {
+        self.agent(id)?.network.compute_and_attest(inputs)
+    }
+
+    // One sample per agent: `inputs` concatenates each agent's input vector in
+    // agent_ids() order and the result concatenates their outputs the same way
+    #[wasm_bindgen]
+    pub fn forward_all(&self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        let expected: usize = self.agents.values().map(|agent| agent.network.input_size()).sum();
+        if inputs.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: inputs.len() });
+        }
+        let mut outputs = Vec::with_capacity(self.agents.values().map(|agent| agent.network.output_size()).sum());
+        let mut rest = inputs;
+        for agent in self.agents.values() {
+            let (sample, tail) = rest.split_at(agent.network.input_size());
+            outputs.extend(agent.network.forward(sample)?);
+            rest = tail;
+        }
+        Ok(outputs)
+    }
+
+    // Advance every agent's spiking network by `dt` ms. Returns how many neurons
+    // fired per agent, in agent_ids() order; agents without one report 0.
+    #[wasm_bindgen]
+    pub fn tick_all(&mut self, dt: f32) -> Result<Vec<u32>, NeuralError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("time step must be positive".to_string()));
+        }
+        let mut fired = Vec::with_capacity(self.agents.len());
+        for agent in self.agents.values_mut() {
+            let count = match agent.spiking.as_mut() {
+                Some(spiking) => spiking.step(dt)?.len() as u32,
+                None => 0,
+            };
+            fired.push(count);
+        }
+        Ok(fired)
+    }
+
+    // Clear recurrent state and return spiking networks to rest for every agent
+    #[wasm_bindgen]
+    pub fn reset_all(&mut self) {
+        for agent in self.agents.values_mut() {
+            agent.network.reset_state();
+            if let Some(spiking) = agent.spiking.as_mut() {
+                spiking.reset();
+            }
+        }
+    }
+}
+
+impl AgentPool {
+    fn agent(&self, id: u32) -> NeuralResult<&Agent> {
+        self.agents.get(&id).ok_or_else(|| unknown_agent(id))
+    }
+
+    fn agent_mut(&mut self, id: u32) -> NeuralResult<&mut Agent> {
+        self.agents.get_mut(&id).ok_or_else(|| unknown_agent(id))
+    }
+
+    // Every agent's network with its ID, so an executor can run agents side by side
+    pub(crate) fn networks_mut(&mut self) -> impl Iterator<Item = (u32, &mut NeuralNetwork)> {
+        self.agents.iter_mut().map(|(id, agent)| (*id, &mut agent.network))
+    }
+}
+
+pub(crate) fn unknown_agent(id: u32) -> NeuralError {
+    NeuralError::InvalidConfiguration(format!("no agent with ID {}", id))
+}
@@ -0,0 +1,548 @@
+// Segmented pool allocator for f32 buffers shared with JavaScript
+//
+// Memory is reserved in segments that never move once created, so pointers handed
+// to JS stay valid until their allocation is freed. Each segment is tiled by block
+// headers (offset, size, owner) kept in address order; freed blocks go back on the
+// free list and merge with free neighbours. Blocks are whole 16-byte chunks, so
+// every allocation is aligned for v128 loads and stores.
+//
+// compact() is the exception to stable pointers: it slides live blocks together,
+// moves blocks out of later segments into earlier ones and drops the segments left
+// empty. Handles survive and map to the new locations; pointers must be fetched
+// again, which JS can detect through epoch(). A high-water mark shrink policy
+// releases empty segments whenever a free leaves the pool above the mark, and
+// optionally compacts first.
+
+use std::collections::HashMap;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::serialization::{ByteReader, ByteWriter};
+
+const CHUNK_FLOATS: usize = 4;
+const CHUNK_BYTES: usize = CHUNK_FLOATS * std::mem::size_of::<f32>();
+
+// 1MB per segment unless a single allocation needs more
+const DEFAULT_SEGMENT_CHUNKS: usize = (1024 * 1024) / CHUNK_BYTES;
+
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, Default)]
+struct Chunk([f32; CHUNK_FLOATS]);
+
+#[derive(Debug, Clone, Copy)]
+struct BlockHeader {
+    offset: usize,
+    chunks: usize,
+    owner: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    memory: Vec<Chunk>,
+    blocks: Vec<BlockHeader>,
+}
+
+impl Segment {
+    // None if the host cannot provide the memory
+    fn try_new(chunks: usize) -> Option<Segment> {
+        let mut memory = Vec::new();
+        memory.try_reserve_exact(chunks).ok()?;
+        memory.resize(chunks, Chunk::default());
+        Some(Segment { memory, blocks: vec![BlockHeader { offset: 0, chunks, owner: None }] })
+    }
+
+    // First-fit search over this segment's free blocks, splitting off any remainder
+    fn claim(&mut self, chunks: usize, owner: u32) -> Option<usize> {
+        let index = self.blocks.iter().position(|block| block.owner.is_none() && block.chunks >= chunks)?;
+        let block = self.blocks[index];
+
+        if block.chunks > chunks {
+            self.blocks.insert(index + 1, BlockHeader {
+                offset: block.offset + chunks,
+                chunks: block.chunks - chunks,
+                owner: None,
+            });
+        }
+        self.blocks[index] = BlockHeader { offset: block.offset, chunks, owner: Some(owner) };
+        self.memory[block.offset..block.offset + chunks].fill(Chunk::default());
+        Some(block.offset)
+    }
+
+    fn release(&mut self, offset: usize) {
+        let Ok(index) = self.blocks.binary_search_by_key(&offset, |block| block.offset) else {
+            return;
+        };
+        self.blocks[index].owner = None;
+
+        // Merge with the following free block, then with the preceding one
+        if index + 1 < self.blocks.len() && self.blocks[index + 1].owner.is_none() {
+            self.blocks[index].chunks += self.blocks[index + 1].chunks;
+            self.blocks.remove(index + 1);
+        }
+        if index > 0 && self.blocks[index - 1].owner.is_none() {
+            self.blocks[index - 1].chunks += self.blocks[index].chunks;
+            self.blocks.remove(index);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|block| block.owner.is_none())
+    }
+
+    fn live_blocks(&self) -> Vec<BlockHeader> {
+        self.blocks.iter().filter(|block| block.owner.is_some()).copied().collect()
+    }
+
+    // Slide live blocks to the front, leaving one free block at the end; returns
+    // (owner, new offset) for every block that moved
+    fn compact(&mut self) -> Vec<(u32, usize)> {
+        let mut moved = Vec::new();
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        let mut cursor = 0;
+        for block in self.live_blocks() {
+            if block.offset != cursor {
+                self.memory.copy_within(block.offset..block.offset + block.chunks, cursor);
+                moved.extend(block.owner.map(|owner| (owner, cursor)));
+            }
+            blocks.push(BlockHeader { offset: cursor, ..block });
+            cursor += block.chunks;
+        }
+        if cursor < self.memory.len() {
+            blocks.push(BlockHeader { offset: cursor, chunks: self.memory.len() - cursor, owner: None });
+        }
+        self.blocks = blocks;
+        moved
+    }
+
+    fn floats(&self, offset: usize, len: usize) -> &[f32] {
+        let chunks = &self.memory[offset..offset + len.div_ceil(CHUNK_FLOATS)];
+        // Chunk is repr(C) over [f32; 4], so a run of chunks is a run of f32s
+        unsafe { std::slice::from_raw_parts(chunks.as_ptr() as *const f32, len) }
+    }
+
+    fn floats_mut(&mut self, offset: usize, len: usize) -> &mut [f32] {
+        let chunks = &mut self.memory[offset..offset + len.div_ceil(CHUNK_FLOATS)];
+        unsafe { std::slice::from_raw_parts_mut(chunks.as_mut_ptr() as *mut f32, len) }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Allocation {
+    segment: usize,
+    offset: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolAllocator {
+    segments: Vec<Segment>,
+    allocations: HashMap<u32, Allocation>,
+    next_handle: u32,
+    // Cap on reserved bytes for new segments; 0 means unlimited
+    limit: usize,
+    // Shrink policy: reserved bytes above which frees release memory (0 = never)
+    high_water_mark: usize,
+    relocate_on_shrink: bool,
+    // Bumped whenever blocks move, invalidating pointers
+    epoch: u32,
+}
+
+impl Default for PoolAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoolAllocator {
+    pub fn new() -> PoolAllocator {
+        PoolAllocator::with_first_segment(DEFAULT_SEGMENT_CHUNKS)
+    }
+
+    // A pool whose first segment exactly fits allocations of the given lengths, for
+    // owners that know their buffers up front; later segments are 1MB
+    pub fn with_capacity(lengths: &[usize]) -> PoolAllocator {
+        let chunks: usize = lengths.iter().map(|len| len.div_ceil(CHUNK_FLOATS)).sum();
+        PoolAllocator::with_first_segment(chunks.max(1))
+    }
+
+    fn with_first_segment(chunks: usize) -> PoolAllocator {
+        PoolAllocator {
+            segments: Segment::try_new(chunks).into_iter().collect(),
+            allocations: HashMap::new(),
+            next_handle: 1,
+            limit: 0,
+            high_water_mark: 0,
+            relocate_on_shrink: false,
+            epoch: 0,
+        }
+    }
+
+    // Segments already reserved are kept even if they exceed a new, lower limit
+    pub fn set_limit(&mut self, bytes: usize) {
+        self.limit = bytes;
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    // Allocate `len` zeroed floats; handle 0 is never issued so JS can use it as "none"
+    pub fn allocate(&mut self, len: usize) -> NeuralResult<u32> {
+        if len == 0 {
+            return Err(NeuralError::InvalidConfiguration("allocation length must be non-zero".to_string()));
+        }
+        let chunks = len.div_ceil(CHUNK_FLOATS);
+        let handle = self.issue_handle();
+
+        let claimed = self
+            .segments
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, segment)| segment.claim(chunks, handle).map(|offset| (index, offset)));
+
+        let (segment, offset) = match claimed {
+            Some(found) => found,
+            None => {
+                let mut segment = self.reserve_segment(chunks)?;
+                let offset = segment.claim(chunks, handle).unwrap_or(0);
+                self.segments.push(segment);
+                (self.segments.len() - 1, offset)
+            }
+        };
+
+        self.allocations.insert(handle, Allocation { segment, offset, len });
+        Ok(handle)
+    }
+
+    pub fn free(&mut self, handle: u32) -> NeuralResult<()> {
+        let allocation = self.allocations.remove(&handle).ok_or(NeuralError::InvalidHandle(handle))?;
+        self.segments[allocation.segment].release(allocation.offset);
+        if self.high_water_mark > 0 && self.reserved_bytes() > self.high_water_mark {
+            if self.relocate_on_shrink {
+                self.compact();
+            }
+            self.release_empty_segments(self.high_water_mark);
+        }
+        Ok(())
+    }
+
+    // Release memory whenever a free leaves more than `high_water_mark` bytes reserved
+    // (0 disables the policy). Empty segments are always safe to drop; with `relocate`
+    // the pool also compacts, which moves live buffers.
+    pub fn set_shrink_policy(&mut self, high_water_mark: usize, relocate: bool) {
+        self.high_water_mark = high_water_mark;
+        self.relocate_on_shrink = relocate;
+    }
+
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    // Pack live blocks into as few segments as possible and drop the rest (the first
+    // segment is always kept). Returns the bytes released.
+    pub fn compact(&mut self) -> usize {
+        let before = self.reserved_bytes();
+        let mut relocated = false;
+        for (index, segment) in self.segments.iter_mut().enumerate() {
+            for (owner, offset) in segment.compact() {
+                self.allocations.insert(owner, Allocation { segment: index, offset, ..self.allocations[&owner] });
+                relocated = true;
+            }
+        }
+
+        // Move blocks from later segments into free space in earlier ones, smallest first
+        for source in (1..self.segments.len()).rev() {
+            let mut blocks = self.segments[source].live_blocks();
+            blocks.sort_by_key(|block| block.chunks);
+            for block in blocks {
+                let Some(owner) = block.owner else { continue };
+                let (earlier, later) = self.segments.split_at_mut(source);
+                let Some((target, offset)) =
+                    earlier.iter_mut().enumerate().find_map(|(index, segment)| segment.claim(block.chunks, owner).map(|offset| (index, offset)))
+                else {
+                    continue;
+                };
+                let from = &later[0].memory[block.offset..block.offset + block.chunks];
+                earlier[target].memory[offset..offset + block.chunks].copy_from_slice(from);
+                later[0].release(block.offset);
+                self.allocations.insert(owner, Allocation { segment: target, offset, ..self.allocations[&owner] });
+                relocated = true;
+            }
+        }
+
+        if relocated {
+            self.epoch = self.epoch.wrapping_add(1);
+        }
+        self.release_empty_segments(0);
+        before - self.reserved_bytes()
+    }
+
+    // Drop empty segments after the first, last first, until at most `target` bytes are reserved
+    fn release_empty_segments(&mut self, target: usize) {
+        let mut index = self.segments.len();
+        while index > 1 && self.reserved_bytes() > target {
+            index -= 1;
+            if !self.segments[index].is_empty() {
+                continue;
+            }
+            self.segments.remove(index);
+            for allocation in self.allocations.values_mut() {
+                if allocation.segment > index {
+                    allocation.segment -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, handle: u32) -> NeuralResult<&[f32]> {
+        let allocation = self.allocations.get(&handle).ok_or(NeuralError::InvalidHandle(handle))?;
+        Ok(self.segments[allocation.segment].floats(allocation.offset, allocation.len))
+    }
+
+    pub fn get_mut(&mut self, handle: u32) -> NeuralResult<&mut [f32]> {
+        let allocation = *self.allocations.get(&handle).ok_or(NeuralError::InvalidHandle(handle))?;
+        Ok(self.segments[allocation.segment].floats_mut(allocation.offset, allocation.len))
+    }
+
+    // Bytes covered by live blocks, including alignment padding
+    pub fn bytes_in_use(&self) -> usize {
+        self.allocations.values().map(|allocation| allocation.len.div_ceil(CHUNK_FLOATS) * CHUNK_BYTES).sum()
+    }
+
+    // Bytes reserved by all segments
+    pub fn reserved_bytes(&self) -> usize {
+        self.segments.iter().map(|segment| segment.memory.len() * CHUNK_BYTES).sum()
+    }
+
+    pub fn allocation_count(&self) -> usize {
+        self.allocations.len()
+    }
+
+    pub fn largest_free_block(&self) -> usize {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.blocks.iter())
+            .filter(|block| block.owner.is_none())
+            .map(|block| block.chunks * CHUNK_BYTES)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // A default-sized segment, or one sized to the request if the limit leaves less room
+    fn reserve_segment(&self, chunks: usize) -> NeuralResult<Segment> {
+        let requested = chunks.saturating_mul(CHUNK_BYTES);
+        let in_use = self.reserved_bytes();
+        let room = if self.limit == 0 { usize::MAX } else { self.limit.saturating_sub(in_use) / CHUNK_BYTES };
+        if chunks > room {
+            return Err(NeuralError::MemoryLimitExceeded { requested, in_use, limit: self.limit });
+        }
+        Segment::try_new(chunks.max(DEFAULT_SEGMENT_CHUNKS).min(room))
+            .or_else(|| Segment::try_new(chunks))
+            .ok_or(NeuralError::MemoryLimitExceeded { requested, in_use, limit: 0 })
+    }
+
+    // Layout: limit u64, high_water_mark u64, relocate u8, reserved [u8; 3], epoch u32,
+    // next_handle u32, allocation_count u32, then per allocation in handle order
+    //   handle u32, len u32, f32[len]
+    pub(crate) fn encode(&self, writer: &mut ByteWriter) {
+        writer.u64(self.limit as u64);
+        writer.u64(self.high_water_mark as u64);
+        writer.bytes(&[self.relocate_on_shrink as u8, 0, 0, 0]);
+        writer.u32(self.epoch);
+        writer.u32(self.next_handle);
+        let mut handles: Vec<u32> = self.allocations.keys().copied().collect();
+        handles.sort_unstable();
+        writer.u32(handles.len() as u32);
+        for handle in handles {
+            let allocation = self.allocations[&handle];
+            writer.u32(handle);
+            writer.u32(allocation.len as u32);
+            writer.f32_slice(self.segments[allocation.segment].floats(allocation.offset, allocation.len));
+        }
+    }
+
+    // A fresh pool holding the same handles and contents. The buffers live at new
+    // addresses, so the epoch moves on from the encoded one.
+    pub(crate) fn decode(reader: &mut ByteReader) -> NeuralResult<PoolAllocator> {
+        let limit = reader.u64()? as usize;
+        let high_water_mark = reader.u64()? as usize;
+        let relocate_on_shrink = reader.bytes(4)?[0] != 0;
+        let epoch = reader.u32()?;
+        let next_handle = reader.u32()?;
+        let mut pool = PoolAllocator::new();
+        for _ in 0..reader.u32()? {
+            let handle = reader.u32()?;
+            let len = reader.u32()? as usize;
+            if handle == 0 || pool.allocations.contains_key(&handle) {
+                return Err(NeuralError::InvalidFormat(format!("invalid or repeated buffer handle {}", handle)));
+            }
+            let values = reader.f32_vec(len)?;
+            pool.next_handle = handle;
+            pool.allocate(len)?;
+            pool.get_mut(handle)?.copy_from_slice(&values);
+        }
+        pool.next_handle = next_handle.max(1);
+        pool.limit = limit;
+        pool.high_water_mark = high_water_mark;
+        pool.relocate_on_shrink = relocate_on_shrink;
+        pool.epoch = epoch.wrapping_add(1);
+        Ok(pool)
+    }
+
+    fn issue_handle(&mut self) -> u32 {
+        loop {
+            let handle = self.next_handle;
+            self.next_handle = self.next_handle.wrapping_add(1).max(1);
+            if !self.allocations.contains_key(&handle) {
+                return handle;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(pool: &mut PoolAllocator, handle: u32, value: f32) {
+        pool.get_mut(handle).unwrap().fill(value);
+    }
+
+    fn address(pool: &PoolAllocator, handle: u32) -> *const f32 {
+        pool.get(handle).unwrap().as_ptr()
+    }
+
+    #[test]
+    fn freed_blocks_are_reused() {
+        let mut pool = PoolAllocator::new();
+        let reserved = pool.reserved_bytes();
+        let (a, b, c) = (pool.allocate(10).unwrap(), pool.allocate(10).unwrap(), pool.allocate(10).unwrap());
+        for (handle, value) in [(a, 1.0), (b, 2.0), (c, 3.0)] {
+            fill(&mut pool, handle, value);
+        }
+        let b_address = address(&pool, b);
+        assert_eq!(address(&pool, a) as usize % 16, 0);
+        assert_eq!(pool.bytes_in_use(), 3 * 3 * CHUNK_BYTES);
+
+        // A smaller buffer takes b's block, zeroed, under a new handle
+        pool.free(b).unwrap();
+        let d = pool.allocate(8).unwrap();
+        assert_ne!(d, b);
+        assert_eq!(address(&pool, d), b_address);
+        assert_eq!(pool.get(d).unwrap(), [0.0; 8]);
+        assert!(matches!(pool.get(b), Err(NeuralError::InvalidHandle(handle)) if handle == b));
+        assert!(matches!(pool.free(b), Err(NeuralError::InvalidHandle(_))));
+        assert_eq!(pool.get(a).unwrap(), [1.0; 10]);
+        assert_eq!(pool.get(c).unwrap(), [3.0; 10]);
+
+        // Freeing neighbours merges them, so a larger buffer fits where a and d were
+        let a_address = address(&pool, a);
+        pool.free(a).unwrap();
+        pool.free(d).unwrap();
+        let e = pool.allocate(24).unwrap();
+        assert_eq!(address(&pool, e), a_address);
+
+        // Repeated allocate and free of one size settles on one block
+        for _ in 0..100 {
+            let handle = pool.allocate(64).unwrap();
+            pool.free(handle).unwrap();
+        }
+        assert_eq!(pool.reserved_bytes(), reserved);
+        assert_eq!(pool.allocation_count(), 2);
+        assert!(matches!(pool.allocate(0), Err(NeuralError::InvalidConfiguration(_))));
+    }
+
+    #[test]
+    fn compaction_recovers_fragmented_space() {
+        // One 10-chunk segment, tiled and then freed in alternate blocks
+        let mut pool = PoolAllocator::with_capacity(&[40]);
+        let handles: Vec<u32> = (0..10).map(|_| pool.allocate(4).unwrap()).collect();
+        for (index, &handle) in handles.iter().enumerate() {
+            fill(&mut pool, handle, index as f32);
+        }
+        for &handle in handles.iter().step_by(2) {
+            pool.free(handle).unwrap();
+        }
+        assert_eq!(pool.largest_free_block(), CHUNK_BYTES);
+        // Five free chunks, but not two in a row: a second segment has to be reserved
+        let spill = pool.allocate(8).unwrap();
+        fill(&mut pool, spill, 9.5);
+        let reserved = pool.reserved_bytes();
+        assert!(reserved > 10 * CHUNK_BYTES);
+
+        let epoch = pool.epoch();
+        let old_address = address(&pool, handles[1]);
+        let released = pool.compact();
+        assert_eq!(pool.reserved_bytes(), 10 * CHUNK_BYTES);
+        assert_eq!(released, reserved - 10 * CHUNK_BYTES);
+        assert_eq!(pool.epoch(), epoch + 1);
+        assert_ne!(address(&pool, handles[1]), old_address);
+
+        // Live handles follow their data; freed ones stay invalid
+        for (index, &handle) in handles.iter().enumerate() {
+            match index % 2 {
+                0 => assert!(matches!(pool.get(handle), Err(NeuralError::InvalidHandle(_)))),
+                _ => assert_eq!(pool.get(handle).unwrap(), [index as f32; 4]),
+            }
+        }
+        assert_eq!(pool.get(spill).unwrap(), [9.5; 8]);
+        assert_eq!(pool.largest_free_block(), 3 * CHUNK_BYTES);
+
+        // Nothing left to move: the epoch stays
+        assert_eq!(pool.compact(), 0);
+        assert_eq!(pool.epoch(), epoch + 1);
+    }
+
+    #[test]
+    fn shrink_policy_releases_memory_on_free() {
+        let mut pool = PoolAllocator::with_capacity(&[16]);
+        pool.set_shrink_policy(4 * CHUNK_BYTES, true);
+        let kept = pool.allocate(16).unwrap();
+        let spill = pool.allocate(16).unwrap();
+        let moved = pool.allocate(4).unwrap();
+        fill(&mut pool, moved, 1.0);
+        let reserved = pool.reserved_bytes();
+
+        // The relocating policy packs `moved` into the emptied first segment...
+        pool.free(kept).unwrap();
+        assert_eq!(pool.epoch(), 1);
+        assert_eq!(pool.reserved_bytes(), reserved);
+        // ...so freeing spill leaves the second segment empty to be dropped
+        pool.free(spill).unwrap();
+        assert_eq!(pool.reserved_bytes(), 4 * CHUNK_BYTES);
+        assert_eq!(pool.get(moved).unwrap(), [1.0; 4]);
+        assert!(matches!(pool.get(spill), Err(NeuralError::InvalidHandle(_))));
+    }
+
+    #[test]
+    fn decoding_keeps_handles_and_bumps_the_epoch() {
+        let mut pool = PoolAllocator::new();
+        pool.set_limit(4 << 20);
+        let freed = pool.allocate(3).unwrap();
+        let live = pool.allocate(5).unwrap();
+        fill(&mut pool, live, 0.25);
+        pool.free(freed).unwrap();
+
+        let mut writer = ByteWriter::new();
+        pool.encode(&mut writer);
+        let bytes = writer.finish();
+        let mut decoded = PoolAllocator::decode(&mut ByteReader::new(&bytes)).unwrap();
+        assert_eq!(decoded.epoch(), pool.epoch() + 1);
+        assert_eq!(decoded.limit(), 4 << 20);
+        assert_eq!(decoded.get(live).unwrap(), [0.25; 5]);
+        assert!(matches!(decoded.get(freed), Err(NeuralError::InvalidHandle(_))));
+        // Handles are not reissued after a restore
+        let next = decoded.allocate(1).unwrap();
+        assert!(next != freed && next != live);
+    }
+
+    #[test]
+    fn limit_refuses_new_segments() {
+        let mut pool = PoolAllocator::with_capacity(&[4]);
+        pool.set_limit(8 * CHUNK_BYTES);
+        pool.allocate(4).unwrap();
+        assert!(matches!(pool.allocate(40), Err(NeuralError::MemoryLimitExceeded { .. })));
+        // Within the limit a segment sized to what is left is reserved
+        pool.allocate(8).unwrap();
+        assert!(pool.reserved_bytes() <= 8 * CHUNK_BYTES);
+    }
+}
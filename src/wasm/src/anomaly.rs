@@ -0,0 +1,452 @@
+// Anomaly detection over agent performance metrics
+//
+// An AnomalyMonitor keeps one detector per (agent, metric) series and scores each
+// observation against that series' history before adding it:
+//   ZScore           |x - mean| / std over the last `window` values; flagged above
+//                    `threshold` standard deviations (default 3)
+//   Ewma             EWMA control chart: z = λx + (1 - λ)z' against mean ± L·σ·
+//                    sqrt(λ / (2 - λ) · (1 - (1 - λ)^2t)), with mean and σ frozen
+//                    from the first `warmup` values, so slow drift is caught;
+//                    flagged above L = `threshold` (default 3)
+//   IsolationForest  univariate isolation forest (Liu et al., 2008) of `trees`
+//                    random trees grown on the last `window` values and regrown
+//                    after every window / 4 new ones; score 2^(-E[h(x)] / c(n)),
+//                    flagged above `threshold` (default 0.7)
+// No series is scored before `warmup` observations. A deviation from a constant
+// history scores infinity.
+//
+// Flagged observations become AnomalyEvents: they are logged under the "anomaly"
+// target, passed as JSON to the listener set with set_listener, and queued for
+// take_events(), which holds at most MAX_PENDING_EVENTS (the oldest are dropped).
+// An agent is flagged while the latest observation of any of its metrics is.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::logging::{log_event, push_json_string, LogLevel};
+use crate::online_stats::RunningStats;
+use crate::rng::Rng;
+use crate::training::json_number;
+
+const MAX_PENDING_EVENTS: usize = 1024;
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyMethod {
+    ZScore = 0,
+    Ewma = 1,
+    IsolationForest = 2,
+}
+
+impl AnomalyMethod {
+    fn name(self) -> &'static str {
+        match self {
+            AnomalyMethod::ZScore => "z_score",
+            AnomalyMethod::Ewma => "ewma",
+            AnomalyMethod::IsolationForest => "isolation_forest",
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyConfig {
+    pub method: AnomalyMethod,
+    // Score above which an observation is anomalous
+    pub threshold: f32,
+    // History length for ZScore and IsolationForest
+    pub window: usize,
+    // Observations per series before scoring starts
+    pub warmup: usize,
+    // EWMA smoothing factor λ in (0, 1]
+    pub ewma_lambda: f32,
+    // IsolationForest tree count
+    pub trees: usize,
+    pub seed: u64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        AnomalyConfig::new(AnomalyMethod::ZScore)
+    }
+}
+
+#[wasm_bindgen]
+impl AnomalyConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(method: AnomalyMethod) -> AnomalyConfig {
+        let threshold = match method {
+            AnomalyMethod::ZScore | AnomalyMethod::Ewma => 3.0,
+            AnomalyMethod::IsolationForest => 0.7,
+        };
+        AnomalyConfig { method, threshold, window: 64, warmup: 16, ewma_lambda: 0.2, trees: 32, seed: 0 }
+    }
+}
+
+impl AnomalyConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(self.threshold.is_finite() && self.threshold > 0.0) {
+            return Err(NeuralError::InvalidConfiguration("anomaly threshold must be positive and finite".to_string()));
+        }
+        if self.window < 2 || self.warmup < 2 {
+            return Err(NeuralError::InvalidConfiguration("anomaly window and warmup must be at least 2".to_string()));
+        }
+        if !(self.ewma_lambda > 0.0 && self.ewma_lambda <= 1.0) {
+            return Err(NeuralError::InvalidConfiguration("EWMA lambda must lie in (0, 1]".to_string()));
+        }
+        if self.trees == 0 {
+            return Err(NeuralError::InvalidConfiguration("isolation forest needs at least one tree".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// A flagged observation
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyEvent {
+    agent: String,
+    metric: String,
+    method: AnomalyMethod,
+    value: f32,
+    score: f32,
+    threshold: f32,
+    time_ms: f64,
+}
+
+#[wasm_bindgen]
+impl AnomalyEvent {
+    #[wasm_bindgen(getter)]
+    pub fn agent(&self) -> String {
+        self.agent.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn metric(&self) -> String {
+        self.metric.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn method(&self) -> AnomalyMethod {
+        self.method
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    // Detector score; may be Infinity
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    // Timestamp passed to observe()
+    #[wasm_bindgen(getter)]
+    pub fn time_ms(&self) -> f64 {
+        self.time_ms
+    }
+
+    // {"event", "agent", "metric", "method", "value", "score", "threshold", "time_ms"};
+    // an infinite score is null
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let mut json = "{\"event\":\"anomaly\",\"agent\":".to_string();
+        push_json_string(&mut json, &self.agent);
+        json.push_str(",\"metric\":");
+        push_json_string(&mut json, &self.metric);
+        json.push_str(&format!(
+            ",\"method\":\"{}\",\"value\":{},\"score\":{},\"threshold\":{},\"time_ms\":{}}}",
+            self.method.name(),
+            json_number(self.value),
+            json_number(self.score),
+            json_number(self.threshold),
+            self.time_ms
+        ));
+        json
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Detector {
+    ZScore { history: VecDeque<f32> },
+    Ewma { baseline: RunningStats, smoothed: f64, steps: i32 },
+    Forest { history: VecDeque<f32>, trees: Vec<IsolationTree>, since_fit: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Series {
+    detector: Detector,
+    observations: u64,
+    flagged: bool,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct AnomalyMonitor {
+    config: AnomalyConfig,
+    agents: BTreeMap<String, BTreeMap<String, Series>>,
+    pending: VecDeque<AnomalyEvent>,
+    dropped: u64,
+    listener: Option<js_sys::Function>,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl AnomalyMonitor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &AnomalyConfig) -> Result<AnomalyMonitor, NeuralError> {
+        config.validate()?;
+        Ok(AnomalyMonitor {
+            config: *config,
+            agents: BTreeMap::new(),
+            pending: VecDeque::new(),
+            dropped: 0,
+            listener: None,
+            rng: Rng::new(config.seed),
+        })
+    }
+
+    // Score one observation of `metric` for `agent`, then add it to the series'
+    // history; returns the event if it is anomalous
+    #[wasm_bindgen]
+    pub fn observe(&mut self, agent: &str, metric: &str, value: f32, time_ms: f64) -> Result<Option<AnomalyEvent>, NeuralError> {
+        if !value.is_finite() {
+            return Err(NeuralError::NonFiniteInput { index: 0 });
+        }
+        let config = self.config;
+        let series = self
+            .agents
+            .entry(agent.to_string())
+            .or_default()
+            .entry(metric.to_string())
+            .or_insert_with(|| Series { detector: Detector::new(config.method), observations: 0, flagged: false });
+        let score = series.detector.observe(value, series.observations, &config, &mut self.rng);
+        series.observations += 1;
+        series.flagged = score.is_some_and(|score| score > config.threshold);
+        if !series.flagged {
+            return Ok(None);
+        }
+
+        let event = AnomalyEvent {
+            agent: agent.to_string(),
+            metric: metric.to_string(),
+            method: config.method,
+            value,
+            score: score.unwrap_or(f32::INFINITY),
+            threshold: config.threshold,
+            time_ms,
+        };
+        let json = event.to_json();
+        log_event!(LogLevel::Warn, "anomaly", "{}", json);
+        if let Some(listener) = &self.listener {
+            // A failing listener must not stop monitoring
+            let _ = listener.call1(&JsValue::NULL, &JsValue::from_str(&json));
+        }
+        if self.pending.len() == MAX_PENDING_EVENTS {
+            self.pending.pop_front();
+            self.dropped += 1;
+        }
+        self.pending.push_back(event.clone());
+        Ok(Some(event))
+    }
+
+    // Call `listener(json)` for every anomaly; pass undefined to stop
+    #[wasm_bindgen]
+    pub fn set_listener(&mut self, listener: Option<js_sys::Function>) {
+        self.listener = listener;
+    }
+
+    // Queued events as a JSON array (see AnomalyEvent.to_json), oldest first; empties the queue
+    #[wasm_bindgen]
+    pub fn take_events(&mut self) -> String {
+        let events: Vec<String> = self.pending.drain(..).map(|event| event.to_json()).collect();
+        format!("[{}]", events.join(","))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pending_events(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Events dropped because the queue was full
+    #[wasm_bindgen(getter)]
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped
+    }
+
+    // Whether the latest observation of any of the agent's metrics was anomalous
+    #[wasm_bindgen]
+    pub fn is_flagged(&self, agent: &str) -> bool {
+        self.agents.get(agent).is_some_and(|metrics| metrics.values().any(|series| series.flagged))
+    }
+
+    // Every flagged agent, in sorted order
+    #[wasm_bindgen]
+    pub fn flagged_agents(&self) -> Vec<String> {
+        self.agents.keys().filter(|agent| self.is_flagged(agent)).cloned().collect()
+    }
+
+    // Forget an agent's history, e.g. after it is replaced
+    #[wasm_bindgen]
+    pub fn remove_agent(&mut self, agent: &str) -> bool {
+        self.agents.remove(agent).is_some()
+    }
+
+    // Forget every series and queued event
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.agents.clear();
+        self.pending.clear();
+        self.dropped = 0;
+    }
+}
+
+impl Detector {
+    fn new(method: AnomalyMethod) -> Detector {
+        match method {
+            AnomalyMethod::ZScore => Detector::ZScore { history: VecDeque::new() },
+            AnomalyMethod::Ewma => Detector::Ewma { baseline: RunningStats::default(), smoothed: 0.0, steps: 0 },
+            AnomalyMethod::IsolationForest => Detector::Forest { history: VecDeque::new(), trees: Vec::new(), since_fit: 0 },
+        }
+    }
+
+    // Score of `value` against the history so far, None while warming up
+    fn observe(&mut self, value: f32, seen: u64, config: &AnomalyConfig, rng: &mut Rng) -> Option<f32> {
+        let warm = seen >= config.warmup as u64;
+        match self {
+            Detector::ZScore { history } => {
+                let score = warm.then(|| {
+                    let (mean, variance) = mean_variance(history);
+                    deviation_score(value as f64 - mean, variance.sqrt())
+                });
+                push_bounded(history, value, config.window);
+                score
+            }
+            Detector::Ewma { baseline, smoothed, steps } => {
+                if !warm {
+                    baseline.add(value as f64);
+                    *smoothed = baseline.mean();
+                    return None;
+                }
+                let lambda = config.ewma_lambda as f64;
+                *smoothed = lambda * value as f64 + (1.0 - lambda) * *smoothed;
+                *steps = steps.saturating_add(1);
+                let spread = baseline.std_dev() * (lambda / (2.0 - lambda) * (1.0 - (1.0 - lambda).powi(2 * *steps))).sqrt();
+                Some(deviation_score(*smoothed - baseline.mean(), spread))
+            }
+            Detector::Forest { history, trees, since_fit } => {
+                let score = warm.then(|| {
+                    if trees.is_empty() || *since_fit >= (config.window / 4).max(1) {
+                        let sample: Vec<f32> = history.iter().copied().collect();
+                        *trees = (0..config.trees).map(|_| IsolationTree::grow(&sample, rng)).collect();
+                        *since_fit = 0;
+                    }
+                    let depth = trees.iter().map(|tree| tree.path_length(value)).sum::<f64>() / trees.len() as f64;
+                    2f64.powf(-depth / average_path(history.len())) as f32
+                });
+                *since_fit += 1;
+                push_bounded(history, value, config.window);
+                score
+            }
+        }
+    }
+}
+
+fn push_bounded(history: &mut VecDeque<f32>, value: f32, window: usize) {
+    if history.len() == window {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn mean_variance(values: &VecDeque<f32>) -> (f64, f64) {
+    let mut stats = RunningStats::default();
+    for &value in values {
+        stats.add(value as f64);
+    }
+    (stats.mean(), stats.variance())
+}
+
+// |deviation| in units of `spread`; any deviation from a constant history is infinite
+fn deviation_score(deviation: f64, spread: f64) -> f32 {
+    if spread > 0.0 {
+        (deviation.abs() / spread) as f32
+    } else if deviation == 0.0 {
+        0.0
+    } else {
+        f32::INFINITY
+    }
+}
+
+// c(n): average path length of an unsuccessful binary search tree lookup among n values
+fn average_path(n: usize) -> f64 {
+    match n {
+        0 | 1 => 0.0,
+        2 => 1.0,
+        _ => {
+            let n = n as f64;
+            2.0 * ((n - 1.0).ln() + EULER_GAMMA) - 2.0 * (n - 1.0) / n
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Split { at: f32, below: usize, above: usize },
+    Leaf { size: usize },
+}
+
+#[derive(Debug, Clone)]
+struct IsolationTree {
+    nodes: Vec<Node>,
+}
+
+impl IsolationTree {
+    // Random splits down to single values or depth ceil(log2 n)
+    fn grow(sample: &[f32], rng: &mut Rng) -> IsolationTree {
+        let limit = (sample.len().max(2) as f64).log2().ceil() as usize;
+        let mut tree = IsolationTree { nodes: Vec::new() };
+        tree.split(sample.to_vec(), 0, limit, rng);
+        tree
+    }
+
+    // Append the subtree for `values` and return its index
+    fn split(&mut self, values: Vec<f32>, depth: usize, limit: usize, rng: &mut Rng) -> usize {
+        let index = self.nodes.len();
+        let (min, max) = values.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        if values.len() <= 1 || depth >= limit || min >= max {
+            self.nodes.push(Node::Leaf { size: values.len() });
+            return index;
+        }
+        let at = rng.uniform(min, max);
+        self.nodes.push(Node::Leaf { size: 0 });
+        let (below, above): (Vec<f32>, Vec<f32>) = values.into_iter().partition(|&x| x < at);
+        let below = self.split(below, depth + 1, limit, rng);
+        let above = self.split(above, depth + 1, limit, rng);
+        self.nodes[index] = Node::Split { at, below, above };
+        index
+    }
+
+    fn path_length(&self, value: f32) -> f64 {
+        let (mut node, mut depth) = (0, 0.0);
+        loop {
+            match self.nodes[node] {
+                Node::Split { at, below, above } => {
+                    node = if value < at { below } else { above };
+                    depth += 1.0;
+                }
+                Node::Leaf { size } => return depth + average_path(size),
+            }
+        }
+    }
+}
@@ -0,0 +1,468 @@
+// Scaled dot-product attention and a transformer encoder block
+//
+// attention(Q, K, V) = softmax(Q·Kᵀ / √d) · V, where the rows of Q, K and V are
+// the query, key and value vectors of each position. With a causal mask, query i
+// only sees keys 0..=i (shifted by the number of earlier positions when the keys
+// include a cached prefix).
+//
+// TransformerBlock is a pre-norm encoder layer over a row-major [seq_len × d_model]
+// sequence, with GELU in the feed-forward network:
+//   h = x + MultiHead(LN₁(x))     MultiHead = concat(head₁ … headₕ) · Woᵀ + bo
+//   y = h + W₂ · GELU(W₁ · LN₂(h) + b₁) + b₂
+// Each head attends with its own d_model / heads rows of Wq, Wk and Wv, so every
+// projection is one SIMD matmul against a contiguous block of weights.
+//
+// For autoregressive generation, forward_cached() feeds new positions one step at
+// a time: their keys and values are kept in a KV cache in the block's own memory
+// pool, so each step attends to every cached position without recomputing its
+// projections. The cache holds at most max_len positions; older ones are evicted
+// first, which turns attention into a sliding window over the latest max_len.
+//
+// Parameter order (get_parameters/set_parameters), weights row-major [outputs][inputs]:
+//   γ₁ β₁  Wq bq  Wk bk  Wv bv  Wo bo  γ₂ β₂  W₁ b₁  W₂ b₂
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::ActivationKind;
+use crate::allocator::PoolAllocator;
+use crate::error::{NeuralError, NeuralResult};
+use crate::initializer::{InitDistribution, InitScheme, Initializer};
+use crate::linalg;
+use crate::rng::Rng;
+use crate::stats;
+
+const LAYER_NORM_EPSILON: f32 = 1e-5;
+
+// softmax(Q·Kᵀ / √dim) · V for `queries` query rows and `keys` key and value rows;
+// the result is [queries × value_dim] with value_dim = v.len() / keys
+#[wasm_bindgen]
+pub fn scaled_dot_product_attention(
+    q: &[f32],
+    k: &[f32],
+    v: &[f32],
+    queries: usize,
+    keys: usize,
+    dim: usize,
+    causal: bool,
+) -> Result<Vec<f32>, NeuralError> {
+    if keys == 0 || dim == 0 {
+        return Err(NeuralError::InvalidConfiguration("attention needs at least one key and a non-zero dimension".to_string()));
+    }
+    if !v.len().is_multiple_of(keys) {
+        return Err(NeuralError::InvalidConfiguration("values must hold one row per key".to_string()));
+    }
+    for values in [q, k, v] {
+        if let Some(index) = values.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+    }
+    // Causal queries are the last `queries` positions of the key sequence
+    let causal_offset = causal.then(|| keys.saturating_sub(queries));
+    attend(q, k, v, (queries, keys, dim), causal_offset, crate::check_simd_support())
+}
+
+// Attention for (queries, keys, dim) rows; `causal_offset` is the position of the
+// first query when a causal mask applies
+pub(crate) fn attend(
+    q: &[f32],
+    k: &[f32],
+    v: &[f32],
+    (queries, keys, dim): (usize, usize, usize),
+    causal_offset: Option<usize>,
+    simd: bool,
+) -> NeuralResult<Vec<f32>> {
+    let value_dim = v.len() / keys.max(1);
+    let mut scores = vec![0.0; queries * keys];
+    linalg::matmul_transposed_into(q, k, &mut scores, queries, keys, dim, simd)?;
+    linalg::scale(1.0 / (dim as f32).sqrt(), &mut scores, simd);
+    for (query, row) in scores.chunks_exact_mut(keys).enumerate() {
+        let visible = match causal_offset {
+            Some(offset) => (offset + query + 1).min(keys),
+            None => keys,
+        };
+        ActivationKind::Softmax.apply_slice(&mut row[..visible], simd);
+        row[visible..].fill(0.0);
+    }
+    let mut outputs = vec![0.0; queries * value_dim];
+    linalg::matmul_into(&scores, v, &mut outputs, queries, value_dim, keys, simd)?;
+    Ok(outputs)
+}
+
+// Fully connected map with row-major [outputs][inputs] weights
+#[derive(Debug, Clone)]
+struct Projection {
+    inputs: usize,
+    outputs: usize,
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Projection {
+    fn new(inputs: usize, outputs: usize, rng: &mut Rng) -> Projection {
+        let mut weights = vec![0.0; inputs * outputs];
+        let initializer = Initializer { scheme: InitScheme::Xavier, distribution: InitDistribution::Uniform };
+        initializer.fill(&mut weights, inputs, outputs, rng);
+        Projection { inputs, outputs, weights, biases: vec![0.0; outputs] }
+    }
+
+    // Outputs `first..first + count` for each of `rows` input rows: [rows × count]
+    fn apply_rows(&self, x: &[f32], rows: usize, first: usize, count: usize, simd: bool) -> NeuralResult<Vec<f32>> {
+        let weights = &self.weights[first * self.inputs..(first + count) * self.inputs];
+        let mut outputs = vec![0.0; rows * count];
+        linalg::matmul_transposed_into(x, weights, &mut outputs, rows, count, self.inputs, simd)?;
+        for row in outputs.chunks_exact_mut(count) {
+            linalg::axpy(1.0, &self.biases[first..first + count], row, simd);
+        }
+        Ok(outputs)
+    }
+
+    fn apply(&self, x: &[f32], rows: usize, simd: bool) -> NeuralResult<Vec<f32>> {
+        self.apply_rows(x, rows, 0, self.outputs, simd)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LayerNorm {
+    gamma: Vec<f32>,
+    beta: Vec<f32>,
+}
+
+impl LayerNorm {
+    fn new(size: usize) -> LayerNorm {
+        LayerNorm { gamma: vec![1.0; size], beta: vec![0.0; size] }
+    }
+
+    fn apply(&self, x: &[f32], simd: bool) -> Vec<f32> {
+        let mut outputs = x.to_vec();
+        for row in outputs.chunks_exact_mut(self.gamma.len()) {
+            let mean = stats::mean(row, simd).unwrap_or(0.0);
+            let inv_std = 1.0 / (stats::variance(row, mean, simd).unwrap_or(0.0) + LAYER_NORM_EPSILON).sqrt();
+            for ((value, gamma), beta) in row.iter_mut().zip(&self.gamma).zip(&self.beta) {
+                *value = (*value - mean) * inv_std * gamma + beta;
+            }
+        }
+        outputs
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct TransformerBlock {
+    d_model: usize,
+    heads: usize,
+    d_ff: usize,
+    norm1: LayerNorm,
+    query: Projection,
+    key: Projection,
+    value: Projection,
+    output: Projection,
+    norm2: LayerNorm,
+    expand: Projection,
+    contract: Projection,
+    cache: Option<KvCache>,
+    simd_enabled: bool,
+}
+
+#[wasm_bindgen]
+impl TransformerBlock {
+    // Xavier-uniform weights drawn from `seed`; d_model must split evenly over the heads
+    #[wasm_bindgen(constructor)]
+    pub fn new(d_model: usize, heads: usize, d_ff: usize, seed: u64) -> Result<TransformerBlock, NeuralError> {
+        if d_model == 0 || heads == 0 || d_ff == 0 {
+            return Err(NeuralError::InvalidConfiguration("transformer sizes must be non-zero".to_string()));
+        }
+        if !d_model.is_multiple_of(heads) {
+            return Err(NeuralError::InvalidConfiguration(format!("d_model {} does not split over {} heads", d_model, heads)));
+        }
+        let mut rng = Rng::new(seed);
+        Ok(TransformerBlock {
+            d_model,
+            heads,
+            d_ff,
+            norm1: LayerNorm::new(d_model),
+            query: Projection::new(d_model, d_model, &mut rng),
+            key: Projection::new(d_model, d_model, &mut rng),
+            value: Projection::new(d_model, d_model, &mut rng),
+            output: Projection::new(d_model, d_model, &mut rng),
+            norm2: LayerNorm::new(d_model),
+            expand: Projection::new(d_model, d_ff, &mut rng),
+            contract: Projection::new(d_ff, d_model, &mut rng),
+            cache: None,
+            simd_enabled: crate::check_simd_support(),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn d_model(&self) -> usize {
+        self.d_model
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn heads(&self) -> usize {
+        self.heads
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn d_ff(&self) -> usize {
+        self.d_ff
+    }
+
+    // Encode a row-major [seq_len × d_model] sequence; same shape out
+    #[wasm_bindgen]
+    pub fn forward(&self, inputs: &[f32], seq_len: usize, causal: bool) -> Result<Vec<f32>, NeuralError> {
+        self.check_sequence(inputs, seq_len)?;
+        let normed = self.norm1.apply(inputs, self.simd_enabled);
+        let head_dim = self.d_model / self.heads;
+        let mut heads = Vec::with_capacity(self.heads);
+        for head in 0..self.heads {
+            let first = head * head_dim;
+            let q = self.query.apply_rows(&normed, seq_len, first, head_dim, self.simd_enabled)?;
+            let k = self.key.apply_rows(&normed, seq_len, first, head_dim, self.simd_enabled)?;
+            let v = self.value.apply_rows(&normed, seq_len, first, head_dim, self.simd_enabled)?;
+            heads.push(attend(&q, &k, &v, (seq_len, seq_len, head_dim), causal.then_some(0), self.simd_enabled)?);
+        }
+        self.finish(inputs, &heads, seq_len)
+    }
+
+    // Keep keys and values of up to `max_len` positions for forward_cached; any
+    // cached positions are dropped
+    #[wasm_bindgen]
+    pub fn enable_cache(&mut self, max_len: usize) -> Result<(), NeuralError> {
+        if max_len == 0 {
+            return Err(NeuralError::InvalidConfiguration("cache length must be non-zero".to_string()));
+        }
+        self.cache = Some(KvCache::new(max_len, self.heads, self.d_model / self.heads)?);
+        Ok(())
+    }
+
+    // Release the cache and its memory
+    #[wasm_bindgen]
+    pub fn disable_cache(&mut self) {
+        self.cache = None;
+    }
+
+    // Forget cached positions, e.g. before generating a new sequence
+    #[wasm_bindgen]
+    pub fn reset_cache(&mut self) {
+        if let Some(cache) = &mut self.cache {
+            cache.len = 0;
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cached_len(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.len)
+    }
+
+    // 0 while the cache is disabled
+    #[wasm_bindgen(getter)]
+    pub fn max_cache_len(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.max_len)
+    }
+
+    // Bytes reserved by the cache's pool
+    #[wasm_bindgen(getter)]
+    pub fn cache_bytes(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.pool.reserved_bytes())
+    }
+
+    // Encode `steps` new positions ([steps × d_model]) that follow the cached ones,
+    // causally: each sees the cached positions and the new ones up to itself. Their
+    // keys and values join the cache, evicting the oldest beyond max_len.
+    #[wasm_bindgen]
+    pub fn forward_cached(&mut self, inputs: &[f32], steps: usize) -> Result<Vec<f32>, NeuralError> {
+        self.check_sequence(inputs, steps)?;
+        let Some(cache) = &mut self.cache else {
+            return Err(NeuralError::InvalidConfiguration("forward_cached needs enable_cache first".to_string()));
+        };
+        if steps > cache.max_len {
+            return Err(NeuralError::InvalidConfiguration(format!("{} steps exceed the cache length {}", steps, cache.max_len)));
+        }
+        cache.make_room(steps)?;
+        let start = cache.len;
+        let head_dim = self.d_model / self.heads;
+        let normed = self.norm1.apply(inputs, self.simd_enabled);
+        let mut heads = Vec::with_capacity(self.heads);
+        for head in 0..self.heads {
+            let first = head * head_dim;
+            let q = self.query.apply_rows(&normed, steps, first, head_dim, self.simd_enabled)?;
+            let k = self.key.apply_rows(&normed, steps, first, head_dim, self.simd_enabled)?;
+            let v = self.value.apply_rows(&normed, steps, first, head_dim, self.simd_enabled)?;
+            cache.write(head, start, &k, &v)?;
+            let (keys, values) = cache.head(head, start + steps)?;
+            heads.push(attend(&q, keys, values, (steps, start + steps, head_dim), Some(start), self.simd_enabled)?);
+        }
+        // Only now that every head is written do the new positions count as cached
+        cache.len = start + steps;
+        self.finish(inputs, &heads, steps)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn parameter_count(&self) -> usize {
+        self.parameter_blocks().iter().map(|block| block.len()).sum()
+    }
+
+    // Flat parameters in the order given at the top of attention.rs
+    #[wasm_bindgen]
+    pub fn get_parameters(&self) -> Vec<f32> {
+        self.parameter_blocks().concat()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_parameters(&mut self, parameters: &[f32]) -> Result<(), NeuralError> {
+        let expected = self.parameter_count();
+        if parameters.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: parameters.len() });
+        }
+        if let Some(index) = parameters.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        let mut rest = parameters;
+        for block in self.parameter_blocks_mut() {
+            let (head, tail) = rest.split_at(block.len());
+            block.copy_from_slice(head);
+            rest = tail;
+        }
+        // Cached keys and values came from the old weights
+        self.reset_cache();
+        Ok(())
+    }
+}
+
+impl TransformerBlock {
+    fn check_sequence(&self, inputs: &[f32], seq_len: usize) -> NeuralResult<()> {
+        if seq_len == 0 {
+            return Err(NeuralError::InvalidConfiguration("sequence must hold at least one position".to_string()));
+        }
+        let expected = seq_len * self.d_model;
+        if inputs.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: inputs.len() });
+        }
+        if let Some(index) = inputs.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(())
+    }
+
+    // Merge per-head [seq_len × head_dim] outputs, project, and apply both residual
+    // branches around the feed-forward network
+    fn finish(&self, inputs: &[f32], heads: &[Vec<f32>], seq_len: usize) -> NeuralResult<Vec<f32>> {
+        let head_dim = self.d_model / self.heads;
+        let mut merged = vec![0.0; seq_len * self.d_model];
+        for (head, values) in heads.iter().enumerate() {
+            for (row, head_row) in merged.chunks_exact_mut(self.d_model).zip(values.chunks_exact(head_dim)) {
+                row[head * head_dim..(head + 1) * head_dim].copy_from_slice(head_row);
+            }
+        }
+        let mut hidden = self.output.apply(&merged, seq_len, self.simd_enabled)?;
+        linalg::axpy(1.0, inputs, &mut hidden, self.simd_enabled);
+
+        let normed = self.norm2.apply(&hidden, self.simd_enabled);
+        let mut expanded = self.expand.apply(&normed, seq_len, self.simd_enabled)?;
+        ActivationKind::GELU.apply_slice(&mut expanded, self.simd_enabled);
+        let mut outputs = self.contract.apply(&expanded, seq_len, self.simd_enabled)?;
+        linalg::axpy(1.0, &hidden, &mut outputs, self.simd_enabled);
+        Ok(outputs)
+    }
+
+    fn parameter_blocks(&self) -> [&[f32]; 16] {
+        [
+            &self.norm1.gamma,
+            &self.norm1.beta,
+            &self.query.weights,
+            &self.query.biases,
+            &self.key.weights,
+            &self.key.biases,
+            &self.value.weights,
+            &self.value.biases,
+            &self.output.weights,
+            &self.output.biases,
+            &self.norm2.gamma,
+            &self.norm2.beta,
+            &self.expand.weights,
+            &self.expand.biases,
+            &self.contract.weights,
+            &self.contract.biases,
+        ]
+    }
+
+    fn parameter_blocks_mut(&mut self) -> [&mut [f32]; 16] {
+        [
+            &mut self.norm1.gamma,
+            &mut self.norm1.beta,
+            &mut self.query.weights,
+            &mut self.query.biases,
+            &mut self.key.weights,
+            &mut self.key.biases,
+            &mut self.value.weights,
+            &mut self.value.biases,
+            &mut self.output.weights,
+            &mut self.output.biases,
+            &mut self.norm2.gamma,
+            &mut self.norm2.beta,
+            &mut self.expand.weights,
+            &mut self.expand.biases,
+            &mut self.contract.weights,
+            &mut self.contract.biases,
+        ]
+    }
+}
+
+// Keys and values of the latest positions, per head: each buffer holds one
+// [max_len × head_dim] block per head, with positions 0..len in order
+#[derive(Debug, Clone)]
+struct KvCache {
+    max_len: usize,
+    len: usize,
+    head_dim: usize,
+    keys: u32,
+    values: u32,
+    pool: PoolAllocator,
+}
+
+impl KvCache {
+    fn new(max_len: usize, heads: usize, head_dim: usize) -> NeuralResult<KvCache> {
+        let size = max_len
+            .checked_mul(heads * head_dim)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("cache length overflows".to_string()))?;
+        let mut pool = PoolAllocator::with_capacity(&[size, size]);
+        let keys = pool.allocate(size)?;
+        let values = pool.allocate(size)?;
+        Ok(KvCache { max_len, len: 0, head_dim, keys, values, pool })
+    }
+
+    fn block(&self) -> usize {
+        self.max_len * self.head_dim
+    }
+
+    // Evict the oldest positions so that `steps` more fit
+    fn make_room(&mut self, steps: usize) -> NeuralResult<()> {
+        let evict = (self.len + steps).saturating_sub(self.max_len);
+        if evict == 0 {
+            return Ok(());
+        }
+        let (block, kept) = (self.block(), (self.len - evict) * self.head_dim);
+        for handle in [self.keys, self.values] {
+            for head in self.pool.get_mut(handle)?.chunks_exact_mut(block) {
+                head.copy_within(evict * self.head_dim..evict * self.head_dim + kept, 0);
+            }
+        }
+        self.len -= evict;
+        Ok(())
+    }
+
+    // Store [steps × head_dim] keys and values for `head` from position `start`
+    fn write(&mut self, head: usize, start: usize, keys: &[f32], values: &[f32]) -> NeuralResult<()> {
+        let from = head * self.block() + start * self.head_dim;
+        self.pool.get_mut(self.keys)?[from..from + keys.len()].copy_from_slice(keys);
+        self.pool.get_mut(self.values)?[from..from + values.len()].copy_from_slice(values);
+        Ok(())
+    }
+
+    // Keys and values of positions 0..len for `head`
+    fn head(&self, head: usize, len: usize) -> NeuralResult<(&[f32], &[f32])> {
+        let from = head * self.block();
+        let to = from + len * self.head_dim;
+        Ok((&self.pool.get(self.keys)?[from..to], &self.pool.get(self.values)?[from..to]))
+    }
+}
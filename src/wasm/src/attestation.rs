@@ -0,0 +1,91 @@
+// Attested inference for swarm consensus
+//
+// NeuralNetwork.compute_and_attest(inputs) runs forward and returns the outputs
+// with a SHA-256 digest binding them to the inputs and to the exact model that
+// produced them: the weights digest, SHA-256 of the network's export_weights() blob
+// (architecture, parameters, preprocessor), which is also what an IntegrityCheck
+// pins. LoRA adapters change the outputs too, so while any are attached the digest
+// covers the export_adapters() blob appended to the weights. A peer holding the same model checks a claimed result with
+// verify_attestation(inputs, digest), which re-runs the pass and compares 32 bytes
+// instead of whole output vectors; a peer holding only the claim recomputes the
+// digest with attestation_digest() to see that it matches the claimed outputs.
+//
+// Digests only agree when the outputs are bit-identical, so replicated agents that
+// may run on different browsers or CPUs should use Arithmetic::FixedQ16 (see
+// fixed_point.rs). The arithmetic is part of the digest.
+//
+// Digested message (all integers and floats little-endian):
+//   tag            b"SASA"
+//   version        u16
+//   arithmetic     u8 (Arithmetic), reserved u8
+//   weights_digest [u8; 32]
+//   input_len u32, f32[input_len]
+//   output_len u32, f32[output_len]
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::NeuralError;
+use crate::fixed_point::Arithmetic;
+use crate::integrity::{sha256_digest, SHA256_LEN};
+use crate::serialization::ByteWriter;
+
+pub const ATTESTATION_TAG: &[u8; 4] = b"SASA";
+pub const ATTESTATION_VERSION: u16 = 1;
+
+// Outputs of one attested forward pass
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attestation {
+    outputs: Vec<f32>,
+    digest: [u8; SHA256_LEN],
+    weights_digest: [u8; SHA256_LEN],
+}
+
+#[wasm_bindgen]
+impl Attestation {
+    #[wasm_bindgen(getter)]
+    pub fn outputs(&self) -> Vec<f32> {
+        self.outputs.clone()
+    }
+
+    // SHA-256 over inputs, weights digest, arithmetic and outputs
+    #[wasm_bindgen(getter)]
+    pub fn digest(&self) -> Vec<u8> {
+        self.digest.to_vec()
+    }
+
+    // SHA-256 of the network's model (see NeuralNetwork.weights_digest)
+    #[wasm_bindgen(getter)]
+    pub fn weights_digest(&self) -> Vec<u8> {
+        self.weights_digest.to_vec()
+    }
+}
+
+impl Attestation {
+    pub(crate) fn new(weights_digest: [u8; SHA256_LEN], arithmetic: Arithmetic, inputs: &[f32], outputs: Vec<f32>) -> Attestation {
+        let digest = digest(&weights_digest, arithmetic, inputs, &outputs);
+        Attestation { outputs, digest, weights_digest }
+    }
+}
+
+// Digest of a claimed result, for checking an Attestation without the model
+#[wasm_bindgen]
+pub fn attestation_digest(weights_digest: &[u8], arithmetic: Arithmetic, inputs: &[f32], outputs: &[f32]) -> Result<Vec<u8>, NeuralError> {
+    let weights_digest = <&[u8; SHA256_LEN]>::try_from(weights_digest)
+        .map_err(|_| NeuralError::DimensionMismatch { expected: SHA256_LEN, actual: weights_digest.len() })?;
+    Ok(digest(weights_digest, arithmetic, inputs, outputs).to_vec())
+}
+
+pub(crate) fn digest(weights_digest: &[u8; SHA256_LEN], arithmetic: Arithmetic, inputs: &[f32], outputs: &[f32]) -> [u8; SHA256_LEN] {
+    let mut writer = ByteWriter::new();
+    writer.bytes(ATTESTATION_TAG);
+    writer.u16(ATTESTATION_VERSION);
+    writer.u8(arithmetic as u8);
+    writer.u8(0);
+    writer.bytes(weights_digest);
+    writer.u32(inputs.len() as u32);
+    writer.f32_slice(inputs);
+    writer.u32(outputs.len() as u32);
+    writer.f32_slice(outputs);
+    sha256_digest(&writer.finish())
+}
@@ -0,0 +1,196 @@
+// Input attribution (saliency) for one sample's inference
+//
+// NeuralNetwork.explain scores how much each input feature drove one output, by
+// backpropagating that output to the inputs with inference semantics (dropout off,
+// batch norm on its running statistics, the network's output mode included):
+//   GradientTimesInput   a_i = x_i · ∂y/∂x_i, one backward pass
+//   IntegratedGradients  a_i = (x_i - b_i) · mean over k = 1..steps of
+//                        ∂y/∂x_i at b + (k / steps)(x - b), a right Riemann sum of
+//                        the path integral from the baseline b (zeros by default);
+//                        the attributions add up to about y(x) - y(b)
+// The explained output is the one the network scores highest for the sample unless
+// AttributionConfig names another; integrated gradients keep it fixed along the path.
+//
+// With a preprocessor attached, inputs are raw samples as usual, but attributions
+// and the baseline are per preprocessed feature (the first layer's inputs): one-hot
+// and clip steps have no useful gradient. Recurrent and convolutional layers are
+// not supported.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::{self, ActivationKind};
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+use crate::network::Layer;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributionMethod {
+    GradientTimesInput = 0,
+    IntegratedGradients = 1,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributionConfig {
+    pub method: AttributionMethod,
+    // Integration steps for IntegratedGradients
+    pub steps: usize,
+    output: Option<usize>,
+    baseline: Option<Vec<f32>>,
+}
+
+impl Default for AttributionConfig {
+    fn default() -> Self {
+        AttributionConfig::new(AttributionMethod::GradientTimesInput)
+    }
+}
+
+#[wasm_bindgen]
+impl AttributionConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(method: AttributionMethod) -> AttributionConfig {
+        AttributionConfig { method, steps: 50, output: None, baseline: None }
+    }
+
+    // Output to explain; undefined explains the highest-scoring one
+    #[wasm_bindgen]
+    pub fn set_output(&mut self, output: Option<usize>) {
+        self.output = output;
+    }
+
+    #[wasm_bindgen]
+    pub fn output(&self) -> Option<usize> {
+        self.output
+    }
+
+    // Starting point for IntegratedGradients; undefined means all zeros
+    #[wasm_bindgen]
+    pub fn set_baseline(&mut self, baseline: Option<Vec<f32>>) {
+        self.baseline = baseline;
+    }
+
+    #[wasm_bindgen]
+    pub fn baseline(&self) -> Option<Vec<f32>> {
+        self.baseline.clone()
+    }
+}
+
+impl AttributionConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if self.steps == 0 {
+            return Err(NeuralError::InvalidConfiguration("integration steps must be non-zero".to_string()));
+        }
+        if let Some(index) = self.baseline.iter().flatten().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(())
+    }
+}
+
+// Attributions of `inputs`, the first layer's inputs for one sample; `softmax` is
+// whether the network's output mode applies softmax
+pub(crate) fn attribute(layers: &[Layer], inputs: &[f32], softmax: bool, config: &AttributionConfig, simd: bool) -> NeuralResult<Vec<f32>> {
+    if layers.is_empty() {
+        return Err(NeuralError::InvalidConfiguration("network has no layers to explain".to_string()));
+    }
+    let (gradient, output) = output_gradient(layers, inputs, softmax, config.output, simd)?;
+    if config.method == AttributionMethod::GradientTimesInput {
+        return Ok(gradient.iter().zip(inputs).map(|(g, x)| g * x).collect());
+    }
+
+    let zeros;
+    let baseline = match &config.baseline {
+        Some(baseline) => baseline.as_slice(),
+        None => {
+            zeros = vec![0.0; inputs.len()];
+            &zeros
+        }
+    };
+    if baseline.len() != inputs.len() {
+        return Err(NeuralError::DimensionMismatch { expected: inputs.len(), actual: baseline.len() });
+    }
+    // The last step is the sample itself, whose gradient is already known
+    let mut total = gradient;
+    let mut point = vec![0.0; inputs.len()];
+    for step in 1..config.steps {
+        let alpha = step as f32 / config.steps as f32;
+        for ((value, x), b) in point.iter_mut().zip(inputs).zip(baseline) {
+            *value = b + alpha * (x - b);
+        }
+        let (gradient, _) = output_gradient(layers, &point, softmax, Some(output), simd)?;
+        for (sum, g) in total.iter_mut().zip(&gradient) {
+            *sum += g;
+        }
+    }
+    let scale = 1.0 / config.steps as f32;
+    Ok(total.iter().zip(inputs).zip(baseline).map(|((sum, x), b)| sum * scale * (x - b)).collect())
+}
+
+// ∂y/∂inputs for output `output` (the highest-scoring one if None), and that output
+fn output_gradient(layers: &[Layer], inputs: &[f32], softmax: bool, output: Option<usize>, simd: bool) -> NeuralResult<(Vec<f32>, usize)> {
+    // Inference pass keeping every layer's input and, for dense layers, pre-activations
+    let mut activations = vec![inputs.to_vec()];
+    let mut pre_activations = Vec::with_capacity(layers.len());
+    for layer in layers {
+        let input = &activations[activations.len() - 1];
+        let (pre, post) = match layer {
+            Layer::Dense(dense) => {
+                let mut pre = vec![0.0; dense.outputs];
+                linalg::matvec_into(&dense.effective_weights(simd), input, &mut pre, dense.outputs, dense.inputs, simd)?;
+                for (value, bias) in pre.iter_mut().zip(&dense.biases) {
+                    *value += bias;
+                }
+                let mut post = pre.clone();
+                dense.activation.apply_slice(&mut post, simd);
+                (pre, post)
+            }
+            Layer::Dropout(_) => (Vec::new(), input.clone()),
+            Layer::Norm(norm) => {
+                let mut post = vec![0.0; norm.size];
+                norm.forward_into(input, &mut post, simd)?;
+                (Vec::new(), post)
+            }
+            _ => return Err(unsupported()),
+        };
+        pre_activations.push(pre);
+        activations.push(post);
+    }
+
+    let logits = &activations[layers.len()];
+    let mut scores = logits.clone();
+    if softmax {
+        ActivationKind::Softmax.apply_slice(&mut scores, simd);
+    }
+    let output = match output {
+        Some(output) if output >= scores.len() => return Err(NeuralError::IndexOutOfRange { index: output, len: scores.len() }),
+        Some(output) => output,
+        None => activation::argmax_index(&scores, simd).unwrap_or(0),
+    };
+    let mut grad = vec![0.0; scores.len()];
+    grad[output] = 1.0;
+    if softmax {
+        ActivationKind::Softmax.backprop_slice(logits, &scores, &mut grad);
+    }
+
+    for (index, layer) in layers.iter().enumerate().rev() {
+        grad = match layer {
+            Layer::Dense(dense) => {
+                dense.activation.backprop_slice(&pre_activations[index], &activations[index + 1], &mut grad);
+                let mut upstream = vec![0.0; dense.inputs];
+                for (row, delta) in dense.effective_weights(simd).chunks_exact(dense.inputs).zip(&grad) {
+                    linalg::axpy(*delta, row, &mut upstream, simd);
+                }
+                upstream
+            }
+            Layer::Dropout(_) => grad,
+            Layer::Norm(norm) => norm.input_gradient(&activations[index], &grad, simd),
+            _ => return Err(unsupported()),
+        };
+    }
+    Ok((grad, output))
+}
+
+fn unsupported() -> NeuralError {
+    NeuralError::InvalidConfiguration("attribution supports dense, dropout and normalization layers only".to_string())
+}
@@ -0,0 +1,97 @@
+// Compute backends for the heavy kernels
+//
+// Kernels are written against the `Backend` trait so the runtime can pick an
+// implementation at run time: portable scalar code, wasm SIMD, or (with the
+// `webgpu` cargo feature) a WebGPU device.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::{ActivationAccuracy, ActivationKind};
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Scalar = 0,
+    Simd = 1,
+    WebGpu = 2,
+}
+
+pub trait Backend {
+    fn kind(&self) -> BackendKind;
+
+    // C[m×n] = A[m×k] · B[k×n]
+    fn matmul(&self, a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) -> NeuralResult<()>;
+
+    fn activate(&self, values: &mut [f32], kind: ActivationKind, accuracy: ActivationAccuracy);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarBackend;
+
+impl Backend for ScalarBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Scalar
+    }
+
+    fn matmul(&self, a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) -> NeuralResult<()> {
+        linalg::matmul_into(a, b, c, m, n, k, false)
+    }
+
+    fn activate(&self, values: &mut [f32], kind: ActivationKind, accuracy: ActivationAccuracy) {
+        kind.apply_slice_with(values, false, accuracy);
+    }
+}
+
+// Only constructible where SIMD kernels are compiled in and accepted by the engine
+#[derive(Debug, Clone, Copy)]
+pub struct SimdBackend {
+    _private: (),
+}
+
+impl SimdBackend {
+    pub fn new() -> NeuralResult<SimdBackend> {
+        if !crate::features::simd_available() {
+            return Err(NeuralError::Unavailable("SIMD kernels".to_string()));
+        }
+        Ok(SimdBackend { _private: () })
+    }
+}
+
+impl Backend for SimdBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Simd
+    }
+
+    fn matmul(&self, a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) -> NeuralResult<()> {
+        linalg::matmul_into(a, b, c, m, n, k, true)
+    }
+
+    fn activate(&self, values: &mut [f32], kind: ActivationKind, accuracy: ActivationAccuracy) {
+        kind.apply_slice_with(values, true, accuracy);
+    }
+}
+
+// Fastest CPU backend available in this build and engine
+pub fn best_cpu_backend() -> Box<dyn Backend> {
+    match SimdBackend::new() {
+        Ok(simd) => Box::new(simd),
+        Err(_) => Box::new(ScalarBackend),
+    }
+}
+
+// True when the host exposes `navigator.gpu`; a device may still be refused later
+#[wasm_bindgen]
+pub fn webgpu_available() -> bool {
+    #[cfg(js_host)]
+    {
+        js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("navigator"))
+            .and_then(|navigator| js_sys::Reflect::get(&navigator, &JsValue::from_str("gpu")))
+            .is_ok_and(|gpu| !gpu.is_undefined() && !gpu.is_null())
+    }
+    #[cfg(not(js_host))]
+    {
+        false
+    }
+}
@@ -0,0 +1,272 @@
+// Multi-armed bandits: exploration strategies for choosing among discrete actions
+//
+// Every strategy keeps per-arm pull counts and reward sums and picks an arm per
+// select() call; update() reports the reward the chosen arm earned.
+//   EpsilonGreedy  a uniformly random arm with probability ε, else the best mean;
+//                  ε = max(epsilon_end, epsilon_start · epsilon_decay^t) after t updates
+//   Ucb1           every arm once, then argmax mean + c·sqrt(ln t / nᵢ)
+//                  (Auer et al., 2002; c = √2 for rewards in [0, 1])
+//   Thompson       a draw from each arm's Beta(prior_alpha + Σr, prior_beta + Σ(1 - r))
+//                  posterior; rewards must lie in [0, 1]
+// Ties go to the lowest arm index.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::Rng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BanditStrategy {
+    EpsilonGreedy = 0,
+    Ucb1 = 1,
+    Thompson = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BanditConfig {
+    pub strategy: BanditStrategy,
+    pub epsilon_start: f32,
+    pub epsilon_end: f32,
+    // Factor applied to ε per update
+    pub epsilon_decay: f32,
+    // UCB1 exploration constant c
+    pub exploration: f32,
+    // Thompson sampling Beta prior
+    pub prior_alpha: f32,
+    pub prior_beta: f32,
+}
+
+impl Default for BanditConfig {
+    fn default() -> Self {
+        BanditConfig::new(BanditStrategy::EpsilonGreedy)
+    }
+}
+
+#[wasm_bindgen]
+impl BanditConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(strategy: BanditStrategy) -> BanditConfig {
+        BanditConfig {
+            strategy,
+            epsilon_start: 1.0,
+            epsilon_end: 0.01,
+            epsilon_decay: 0.99,
+            exploration: std::f32::consts::SQRT_2,
+            prior_alpha: 1.0,
+            prior_beta: 1.0,
+        }
+    }
+}
+
+impl BanditConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(0.0..=1.0).contains(&self.epsilon_start) || !(0.0..=1.0).contains(&self.epsilon_end) {
+            return Err(NeuralError::InvalidConfiguration("epsilon must lie in [0, 1]".to_string()));
+        }
+        if !(self.epsilon_decay > 0.0 && self.epsilon_decay <= 1.0) {
+            return Err(NeuralError::InvalidConfiguration("epsilon decay must lie in (0, 1]".to_string()));
+        }
+        if !self.exploration.is_finite() || self.exploration < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("UCB exploration constant must be finite and non-negative".to_string()));
+        }
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(self.prior_alpha) || !positive(self.prior_beta) {
+            return Err(NeuralError::InvalidConfiguration("Beta prior parameters must be positive and finite".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Arm {
+    pulls: u64,
+    // Sums in f64 so long runs keep their precision
+    reward_sum: f64,
+    reward_sq_sum: f64,
+}
+
+impl Arm {
+    fn mean(&self) -> f64 {
+        if self.pulls == 0 {
+            0.0
+        } else {
+            self.reward_sum / self.pulls as f64
+        }
+    }
+
+    // Population variance of the observed rewards
+    fn variance(&self) -> f64 {
+        if self.pulls == 0 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        (self.reward_sq_sum / self.pulls as f64 - mean * mean).max(0.0)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Bandit {
+    config: BanditConfig,
+    arms: Vec<Arm>,
+    updates: u64,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl Bandit {
+    #[wasm_bindgen(constructor)]
+    pub fn new(arms: usize, config: &BanditConfig, seed: u64) -> Result<Bandit, NeuralError> {
+        config.validate()?;
+        if arms == 0 {
+            return Err(NeuralError::InvalidConfiguration("a bandit needs at least one arm".to_string()));
+        }
+        Ok(Bandit { config: *config, arms: vec![Arm::default(); arms], updates: 0, rng: Rng::new(seed) })
+    }
+
+    // Arm to pull next under the configured strategy
+    #[wasm_bindgen]
+    pub fn select(&mut self) -> u32 {
+        let arm = match self.config.strategy {
+            BanditStrategy::EpsilonGreedy => {
+                if self.rng.next_f32() < self.epsilon() {
+                    self.rng.next_u64() as usize % self.arms.len()
+                } else {
+                    self.best()
+                }
+            }
+            BanditStrategy::Ucb1 => match self.arms.iter().position(|arm| arm.pulls == 0) {
+                Some(untried) => untried,
+                None => {
+                    let log_total = (self.updates as f64).ln();
+                    let exploration = self.config.exploration as f64;
+                    first_max(self.arms.iter().map(|arm| arm.mean() + exploration * (log_total / arm.pulls as f64).sqrt()))
+                }
+            },
+            BanditStrategy::Thompson => {
+                let (prior_alpha, prior_beta) = (self.config.prior_alpha as f64, self.config.prior_beta as f64);
+                let draws: Vec<f64> = (0..self.arms.len())
+                    .map(|index| {
+                        let arm = self.arms[index];
+                        let successes = self.rng.gamma(prior_alpha + arm.reward_sum);
+                        let failures = self.rng.gamma(prior_beta + arm.pulls as f64 - arm.reward_sum);
+                        successes / (successes + failures)
+                    })
+                    .collect();
+                first_max(draws.into_iter())
+            }
+        };
+        arm as u32
+    }
+
+    // Record the reward earned by pulling `arm`
+    #[wasm_bindgen]
+    pub fn update(&mut self, arm: u32, reward: f32) -> Result<(), NeuralError> {
+        let len = self.arms.len();
+        let slot = self.arms.get_mut(arm as usize).ok_or(NeuralError::IndexOutOfRange { index: arm as usize, len })?;
+        if !reward.is_finite() {
+            return Err(NeuralError::InvalidConfiguration("reward must be finite".to_string()));
+        }
+        if self.config.strategy == BanditStrategy::Thompson && !(0.0..=1.0).contains(&reward) {
+            return Err(NeuralError::InvalidConfiguration("Thompson sampling rewards must lie in [0, 1]".to_string()));
+        }
+        slot.pulls += 1;
+        slot.reward_sum += reward as f64;
+        slot.reward_sq_sum += reward as f64 * reward as f64;
+        self.updates += 1;
+        Ok(())
+    }
+
+    // Arm with the highest mean reward so far
+    #[wasm_bindgen]
+    pub fn best_arm(&self) -> u32 {
+        self.best() as u32
+    }
+
+    // Current ε of the epsilon-greedy strategy
+    #[wasm_bindgen(getter)]
+    pub fn epsilon(&self) -> f32 {
+        let decayed = self.config.epsilon_start as f64 * (self.config.epsilon_decay as f64).powf(self.updates as f64);
+        (decayed as f32).max(self.config.epsilon_end)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn arm_count(&self) -> usize {
+        self.arms.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn total_pulls(&self) -> f64 {
+        self.updates as f64
+    }
+
+    #[wasm_bindgen]
+    pub fn pulls(&self) -> Vec<f64> {
+        self.arms.iter().map(|arm| arm.pulls as f64).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn means(&self) -> Vec<f32> {
+        self.arms.iter().map(|arm| arm.mean() as f32).collect()
+    }
+
+    // Forget every observation; ε starts over
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.arms.fill(Arm::default());
+        self.updates = 0;
+    }
+
+    // {"strategy", "total_pulls", "epsilon", "best_arm", "arms": [{"arm", "pulls",
+    // "mean", "variance", "total_reward"}]}
+    #[wasm_bindgen]
+    pub fn stats_json(&self) -> String {
+        let strategy = match self.config.strategy {
+            BanditStrategy::EpsilonGreedy => "epsilon_greedy",
+            BanditStrategy::Ucb1 => "ucb1",
+            BanditStrategy::Thompson => "thompson",
+        };
+        let arms: Vec<String> = self
+            .arms
+            .iter()
+            .enumerate()
+            .map(|(index, arm)| {
+                format!(
+                    "{{\"arm\":{},\"pulls\":{},\"mean\":{},\"variance\":{},\"total_reward\":{}}}",
+                    index,
+                    arm.pulls,
+                    arm.mean(),
+                    arm.variance(),
+                    arm.reward_sum
+                )
+            })
+            .collect();
+        format!(
+            "{{\"strategy\":\"{}\",\"total_pulls\":{},\"epsilon\":{},\"best_arm\":{},\"arms\":[{}]}}",
+            strategy,
+            self.updates,
+            self.epsilon(),
+            self.best(),
+            arms.join(",")
+        )
+    }
+}
+
+impl Bandit {
+    fn best(&self) -> usize {
+        first_max(self.arms.iter().map(Arm::mean))
+    }
+}
+
+// Index of the first largest score
+fn first_max(scores: impl Iterator<Item = f64>) -> usize {
+    let mut best = (0, f64::NEG_INFINITY);
+    for (index, score) in scores.enumerate() {
+        if score > best.1 {
+            best = (index, score);
+        }
+    }
+    best.0
+}
@@ -0,0 +1,325 @@
+// Spike and activation traffic between the parts of a partitioned mesh
+//
+// Each worker owns one part of a MeshPartition (partition.rs) and one MeshBridge.
+// Neurons keep their global mesh ids on the wire; locally they are numbered by
+// rank within the part, the numbering of the worker's SpikingNetwork. Synapses
+// that cross parts are registered on every bridge with add_remote_synapse; each
+// bridge keeps the side it needs:
+//   pre local, post remote   spikes of pre are sent to post's part (once per
+//                            destination, however many synapses lead there)
+//   pre remote, post local   arriving spikes of pre are turned into deliveries
+//                            at spike time + delay with the synapse weight
+// Activations (any per-neuron value) follow the same routes and are kept as the
+// latest value seen per remote neuron.
+//
+// Frame layout (all integers and floats little-endian):
+//   magic             b"SASB"
+//   version           u16
+//   reserved          u16
+//   source part       u32
+//   destination part  u32
+//   sequence          u32  (per source → destination, from 1; gaps count as missed,
+//                           older frames are rejected)
+//   spike_count       u32
+//   activation_count  u32
+//   spike_count × { neuron u32, time_ms f32 }
+//   activation_count × { neuron u32, value f32 }
+
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::partition::MeshPartition;
+use crate::serialization::{ByteReader, ByteWriter};
+use crate::spiking::{SpikeEvents, SpikingNetwork};
+
+pub const BRIDGE_MAGIC: &[u8; 4] = b"SASB";
+pub const BRIDGE_VERSION: u16 = 1;
+
+const HEADER_BYTES: usize = 28;
+const RECORD_BYTES: usize = 8;
+
+// Traffic waiting for one destination part
+#[derive(Debug, Clone, Default)]
+struct Outbox {
+    spikes: Vec<(u32, f32)>,
+    activations: Vec<(u32, f32)>,
+    sequence: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InboundSynapse {
+    post: u32,
+    weight: f32,
+    delay_ms: f32,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct MeshBridge {
+    part: u32,
+    // (part, local index) of every global node
+    owners: Vec<(u32, u32)>,
+    local_nodes: Vec<u32>,
+    // Destination parts of each local neuron, ascending
+    routes: Vec<Vec<u32>>,
+    // Remote pre (global id) → synapses onto local neurons
+    inbound: HashMap<u32, Vec<InboundSynapse>>,
+    outboxes: Vec<Outbox>,
+    // (local post, arrival ms, weight) not yet handed to a network
+    deliveries: Vec<(u32, f32, f32)>,
+    remote_activations: HashMap<u32, f32>,
+    // Last sequence received from each source part
+    received: Vec<u32>,
+    missed_frames: u32,
+}
+
+#[wasm_bindgen]
+impl MeshBridge {
+    #[wasm_bindgen(constructor)]
+    pub fn new(partition: &MeshPartition, part: u32) -> Result<MeshBridge, NeuralError> {
+        let parts = partition.part_count();
+        if part as usize >= parts {
+            return Err(NeuralError::IndexOutOfRange { index: part as usize, len: parts });
+        }
+        let mut counts = vec![0u32; parts];
+        let owners: Vec<(u32, u32)> = partition
+            .assignments()
+            .iter()
+            .map(|&owner| {
+                let local = counts[owner as usize];
+                counts[owner as usize] += 1;
+                (owner, local)
+            })
+            .collect();
+        let local_nodes = partition.nodes_in(part)?;
+        Ok(MeshBridge {
+            part,
+            owners,
+            routes: vec![Vec::new(); local_nodes.len()],
+            local_nodes,
+            inbound: HashMap::new(),
+            outboxes: vec![Outbox::default(); parts],
+            deliveries: Vec::new(),
+            remote_activations: HashMap::new(),
+            received: vec![0; parts],
+            missed_frames: 0,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn part(&self) -> u32 {
+        self.part
+    }
+
+    // Global ids of this part's neurons; local index i is entry i
+    #[wasm_bindgen]
+    pub fn local_nodes(&self) -> Vec<u32> {
+        self.local_nodes.clone()
+    }
+
+    // Register a synapse between global neurons `pre` and `post`. Returns whether it
+    // crosses this part's boundary; synapses elsewhere in the mesh are ignored.
+    #[wasm_bindgen]
+    pub fn add_remote_synapse(&mut self, pre: u32, post: u32, weight: f32, delay_ms: f32) -> Result<bool, NeuralError> {
+        let (pre_part, pre_local) = self.owner(pre)?;
+        let (post_part, post_local) = self.owner(post)?;
+        if !weight.is_finite() || !delay_ms.is_finite() || delay_ms < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("synapse weight must be finite and delay non-negative".to_string()));
+        }
+        if pre_part == post_part {
+            return Ok(false);
+        }
+        if pre_part == self.part {
+            let routes = &mut self.routes[pre_local as usize];
+            if let Err(index) = routes.binary_search(&post_part) {
+                routes.insert(index, post_part);
+            }
+            Ok(true)
+        } else if post_part == self.part {
+            self.inbound.entry(pre).or_default().push(InboundSynapse { post: post_local, weight, delay_ms });
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // Queue spikes of local neurons at `time_ms`, e.g. the result of SpikingNetwork.step
+    #[wasm_bindgen]
+    pub fn queue_spikes(&mut self, local_neurons: &[u32], time_ms: f32) -> Result<(), NeuralError> {
+        for &local in local_neurons {
+            self.queue_spike(local, time_ms)?;
+        }
+        Ok(())
+    }
+
+    // Queue the spikes of an event-driven run, numbered locally
+    #[wasm_bindgen]
+    pub fn queue_spike_events(&mut self, spikes: &SpikeEvents) -> Result<(), NeuralError> {
+        for (&local, &time) in spikes.neurons.iter().zip(&spikes.times_ms) {
+            self.queue_spike(local, time)?;
+        }
+        Ok(())
+    }
+
+    // Queue one value per local neuron; only neurons with remote synapses are sent
+    #[wasm_bindgen]
+    pub fn queue_activations(&mut self, values: &[f32]) -> Result<(), NeuralError> {
+        if values.len() != self.local_nodes.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.local_nodes.len(), actual: values.len() });
+        }
+        if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        for (local, &value) in values.iter().enumerate() {
+            for &destination in &self.routes[local] {
+                self.outboxes[destination as usize].activations.push((self.local_nodes[local], value));
+            }
+        }
+        Ok(())
+    }
+
+    // Parts with queued traffic, ascending
+    #[wasm_bindgen]
+    pub fn destinations(&self) -> Vec<u32> {
+        (0..self.outboxes.len() as u32)
+            .filter(|&part| {
+                let outbox = &self.outboxes[part as usize];
+                !outbox.spikes.is_empty() || !outbox.activations.is_empty()
+            })
+            .collect()
+    }
+
+    // Frame of everything queued for `destination`, which is then cleared. An empty
+    // frame is still valid and keeps the sequence numbers contiguous.
+    #[wasm_bindgen]
+    pub fn export_outbound(&mut self, destination: u32) -> Result<Vec<u8>, NeuralError> {
+        if destination == self.part {
+            return Err(NeuralError::InvalidConfiguration("a part does not send frames to itself".to_string()));
+        }
+        let parts = self.outboxes.len();
+        let outbox = self.outboxes.get_mut(destination as usize).ok_or(NeuralError::IndexOutOfRange { index: destination as usize, len: parts })?;
+        outbox.sequence = outbox.sequence.wrapping_add(1);
+        let mut writer = ByteWriter::new();
+        writer.bytes(BRIDGE_MAGIC);
+        writer.u16(BRIDGE_VERSION);
+        writer.u16(0);
+        writer.u32(self.part);
+        writer.u32(destination);
+        writer.u32(outbox.sequence);
+        writer.u32(outbox.spikes.len() as u32);
+        writer.u32(outbox.activations.len() as u32);
+        for (neuron, value) in outbox.spikes.drain(..).chain(outbox.activations.drain(..)) {
+            writer.u32(neuron);
+            writer.f32(value);
+        }
+        Ok(writer.finish())
+    }
+
+    // Read a frame addressed to this part; returns the records it held
+    #[wasm_bindgen]
+    pub fn ingest_inbound(&mut self, frame: &[u8]) -> Result<usize, NeuralError> {
+        let mut reader = ByteReader::new(frame);
+        if reader.bytes(4)? != BRIDGE_MAGIC {
+            return Err(NeuralError::InvalidFormat("missing SASB header".to_string()));
+        }
+        let version = reader.u16()?;
+        if version == 0 || version > BRIDGE_VERSION {
+            return Err(NeuralError::InvalidFormat(format!("unsupported bridge frame version {}", version)));
+        }
+        reader.u16()?;
+        let source = reader.u32()?;
+        let destination = reader.u32()?;
+        let sequence = reader.u32()?;
+        let spike_count = reader.u32()? as usize;
+        let activation_count = reader.u32()? as usize;
+        if destination != self.part {
+            return Err(NeuralError::InvalidFormat(format!("frame is addressed to part {}, not {}", destination, self.part)));
+        }
+        if source as usize >= self.received.len() || source == self.part {
+            return Err(NeuralError::InvalidFormat(format!("frame comes from unknown part {}", source)));
+        }
+        let records = spike_count.saturating_add(activation_count);
+        if frame.len() != HEADER_BYTES + records.saturating_mul(RECORD_BYTES) {
+            return Err(NeuralError::InvalidFormat("frame length does not match its record counts".to_string()));
+        }
+        let mut spikes = Vec::with_capacity(spike_count);
+        for _ in 0..spike_count {
+            spikes.push((self.remote_neuron(reader.u32()?, source)?, reader.f32()?));
+        }
+        let mut activations = Vec::with_capacity(activation_count);
+        for _ in 0..activation_count {
+            activations.push((self.remote_neuron(reader.u32()?, source)?, reader.f32()?));
+        }
+
+        let gap = sequence.wrapping_sub(self.received[source as usize].wrapping_add(1));
+        if gap > u32::MAX / 2 {
+            return Err(NeuralError::InvalidFormat(format!("frame {} from part {} is stale or repeated", sequence, source)));
+        }
+        self.missed_frames = self.missed_frames.saturating_add(gap);
+        self.received[source as usize] = sequence;
+        for (pre, time) in spikes {
+            for synapse in self.inbound.get(&pre).into_iter().flatten() {
+                self.deliveries.push((synapse.post, time + synapse.delay_ms, synapse.weight));
+            }
+        }
+        self.remote_activations.extend(activations);
+        Ok(records)
+    }
+
+    // Hand every pending delivery to the part's network; returns how many
+    #[wasm_bindgen]
+    pub fn deliver(&mut self, network: &mut SpikingNetwork) -> Result<usize, NeuralError> {
+        if network.neuron_count() != self.local_nodes.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.local_nodes.len(), actual: network.neuron_count() });
+        }
+        let count = self.deliveries.len();
+        for (post, arrival_ms, weight) in self.deliveries.drain(..) {
+            network.schedule_external(post as usize, arrival_ms, weight)?;
+        }
+        Ok(count)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pending_deliveries(&self) -> usize {
+        self.deliveries.len()
+    }
+
+    // Latest activation received for global neuron `neuron`, undefined if none
+    #[wasm_bindgen]
+    pub fn remote_activation(&self, neuron: u32) -> Option<f32> {
+        self.remote_activations.get(&neuron).copied()
+    }
+
+    // Frames skipped in the sequence of some source
+    #[wasm_bindgen(getter)]
+    pub fn missed_frames(&self) -> u32 {
+        self.missed_frames
+    }
+}
+
+impl MeshBridge {
+    fn owner(&self, neuron: u32) -> NeuralResult<(u32, u32)> {
+        self.owners.get(neuron as usize).copied().ok_or(NeuralError::IndexOutOfRange { index: neuron as usize, len: self.owners.len() })
+    }
+
+    // A neuron named in a frame must belong to the part that sent it
+    fn remote_neuron(&self, neuron: u32, source: u32) -> NeuralResult<u32> {
+        match self.owners.get(neuron as usize) {
+            Some(&(owner, _)) if owner == source => Ok(neuron),
+            _ => Err(NeuralError::InvalidFormat(format!("neuron {} does not belong to part {}", neuron, source))),
+        }
+    }
+
+    fn queue_spike(&mut self, local: u32, time_ms: f32) -> NeuralResult<()> {
+        let routes = self.routes.get(local as usize).ok_or(NeuralError::IndexOutOfRange { index: local as usize, len: self.routes.len() })?;
+        if !time_ms.is_finite() {
+            return Err(NeuralError::InvalidConfiguration("spike time must be finite".to_string()));
+        }
+        let global = self.local_nodes[local as usize];
+        for &destination in routes {
+            self.outboxes[destination as usize].spikes.push((global, time_ms));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+// Per-tick time and memory budgets with automatic downshifting
+//
+// A caller that drives the runtime from a frame or scheduler loop declares a
+// budget with NeuralRuntime.set_budget and brackets each tick with begin_tick()
+// and end_tick(). Every runtime kernel run inside the tick is timed; the first one
+// that finishes past the time budget is logged under the "budget" target and named
+// in the tick's BudgetReport. end_tick() compares the tick's wall time with
+// `tick_ms` and the runtime's memory (pool reservation plus cached scratch
+// buffers) with `memory_bytes`, and returns a BudgetStatus code.
+//
+// With auto_downshift, each tick over budget moves one level down the ladder and
+// each run of `recovery_ticks` ticks within budget moves one level back up.
+// Levels are cumulative:
+//   SkipOptimization  optimize_connections passes leave connections unchanged
+//   ReducedPrecision  activation kernels use ActivationAccuracy::Fast
+//   SmallerBatch      forward_batch and forward_tensor run in chunks of at most
+//                     `reduced_batch` samples, and recommended_batch_size caps
+//                     batch sizes at it
+// A tick over its memory budget also releases the runtime's scratch buffers.
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::clock::Clock;
+use crate::error::{NeuralError, NeuralResult};
+use crate::logging::{log_event, LogLevel};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetConfig {
+    // Wall time per tick in ms (0 = unlimited)
+    pub tick_ms: f64,
+    // Bytes the runtime may hold (0 = unlimited)
+    pub memory_bytes: usize,
+    pub auto_downshift: bool,
+    // Consecutive ticks within budget before stepping back up a level
+    pub recovery_ticks: u32,
+    // Sample cap at the SmallerBatch level
+    pub reduced_batch: usize,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        BudgetConfig::new(0.0, 0)
+    }
+}
+
+#[wasm_bindgen]
+impl BudgetConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(tick_ms: f64, memory_bytes: usize) -> BudgetConfig {
+        BudgetConfig { tick_ms, memory_bytes, auto_downshift: true, recovery_ticks: 8, reduced_batch: 16 }
+    }
+}
+
+impl BudgetConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(self.tick_ms.is_finite() && self.tick_ms >= 0.0) {
+            return Err(NeuralError::InvalidConfiguration("tick budget must be finite and non-negative".to_string()));
+        }
+        if self.recovery_ticks == 0 || self.reduced_batch == 0 {
+            return Err(NeuralError::InvalidConfiguration("recovery ticks and reduced batch must be non-zero".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// Result of a tick, returned by end_tick
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetStatus {
+    WithinBudget = 0,
+    TimeExceeded = 1,
+    MemoryExceeded = 2,
+    TimeAndMemoryExceeded = 3,
+}
+
+impl BudgetStatus {
+    fn name(self) -> &'static str {
+        match self {
+            BudgetStatus::WithinBudget => "within_budget",
+            BudgetStatus::TimeExceeded => "time_exceeded",
+            BudgetStatus::MemoryExceeded => "memory_exceeded",
+            BudgetStatus::TimeAndMemoryExceeded => "time_and_memory_exceeded",
+        }
+    }
+}
+
+// Work the runtime sheds to get back within budget; each level includes those above it
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Downshift {
+    None = 0,
+    SkipOptimization = 1,
+    ReducedPrecision = 2,
+    SmallerBatch = 3,
+}
+
+impl Downshift {
+    fn name(self) -> &'static str {
+        match self {
+            Downshift::None => "none",
+            Downshift::SkipOptimization => "skip_optimization",
+            Downshift::ReducedPrecision => "reduced_precision",
+            Downshift::SmallerBatch => "smaller_batch",
+        }
+    }
+
+    fn lower(self) -> Downshift {
+        match self {
+            Downshift::None => Downshift::SkipOptimization,
+            Downshift::SkipOptimization => Downshift::ReducedPrecision,
+            Downshift::ReducedPrecision | Downshift::SmallerBatch => Downshift::SmallerBatch,
+        }
+    }
+
+    fn raise(self) -> Downshift {
+        match self {
+            Downshift::None | Downshift::SkipOptimization => Downshift::None,
+            Downshift::ReducedPrecision => Downshift::SkipOptimization,
+            Downshift::SmallerBatch => Downshift::ReducedPrecision,
+        }
+    }
+}
+
+// What happened in one tick
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetReport {
+    status: BudgetStatus,
+    elapsed_ms: f64,
+    memory_bytes: usize,
+    kernel_ms: BTreeMap<&'static str, f64>,
+    over_budget_kernel: Option<&'static str>,
+    // Level in force after the tick
+    downshift: Downshift,
+}
+
+#[wasm_bindgen]
+impl BudgetReport {
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> BudgetStatus {
+        self.status
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn elapsed_ms(&self) -> f64 {
+        self.elapsed_ms
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn memory_bytes(&self) -> usize {
+        self.memory_bytes
+    }
+
+    // First kernel that finished past the time budget, if any
+    #[wasm_bindgen(getter)]
+    pub fn over_budget_kernel(&self) -> Option<String> {
+        self.over_budget_kernel.map(str::to_string)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn downshift(&self) -> Downshift {
+        self.downshift
+    }
+
+    // {"status", "elapsed_ms", "memory_bytes", "over_budget_kernel", "downshift", "kernel_ms": {name: ms}}
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let kernels: Vec<String> = self.kernel_ms.iter().map(|(name, ms)| format!("\"{}\":{}", name, ms)).collect();
+        format!(
+            "{{\"status\":\"{}\",\"elapsed_ms\":{},\"memory_bytes\":{},\"over_budget_kernel\":{},\"downshift\":\"{}\",\"kernel_ms\":{{{}}}}}",
+            self.status.name(),
+            self.elapsed_ms,
+            self.memory_bytes,
+            self.over_budget_kernel.map_or("null".to_string(), |name| format!("\"{}\"", name)),
+            self.downshift.name(),
+            kernels.join(",")
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BudgetGuard {
+    config: BudgetConfig,
+    clock: Clock,
+    // Start of the tick in progress
+    tick_started: Option<f64>,
+    kernel_ms: BTreeMap<&'static str, f64>,
+    over_budget_kernel: Option<&'static str>,
+    level: Downshift,
+    good_ticks: u32,
+    last_report: Option<BudgetReport>,
+}
+
+impl BudgetGuard {
+    pub(crate) fn new(config: BudgetConfig) -> NeuralResult<BudgetGuard> {
+        config.validate()?;
+        Ok(BudgetGuard {
+            config,
+            clock: Clock::new(),
+            tick_started: None,
+            kernel_ms: BTreeMap::new(),
+            over_budget_kernel: None,
+            level: Downshift::None,
+            good_ticks: 0,
+            last_report: None,
+        })
+    }
+
+    pub(crate) fn config(&self) -> BudgetConfig {
+        self.config
+    }
+
+    pub(crate) fn level(&self) -> Downshift {
+        self.level
+    }
+
+    pub(crate) fn last_report(&self) -> Option<BudgetReport> {
+        self.last_report.clone()
+    }
+
+    pub(crate) fn begin_tick(&mut self) {
+        self.tick_started = Some(self.clock.now_ms());
+        self.kernel_ms.clear();
+        self.over_budget_kernel = None;
+    }
+
+    // Account a kernel that ran for `elapsed_ms`; kernels outside a tick are ignored
+    pub(crate) fn charge(&mut self, kernel: &'static str, elapsed_ms: f64) {
+        let Some(started) = self.tick_started else {
+            return;
+        };
+        *self.kernel_ms.entry(kernel).or_insert(0.0) += elapsed_ms;
+        let over = self.config.tick_ms > 0.0 && self.clock.now_ms() - started > self.config.tick_ms;
+        if over && self.over_budget_kernel.is_none() {
+            self.over_budget_kernel = Some(kernel);
+            log_event!(LogLevel::Warn, "budget", "{} finished past the {} ms tick budget", kernel, self.config.tick_ms);
+        }
+    }
+
+    // Close the tick, given the runtime's current memory, and adjust the level
+    pub(crate) fn end_tick(&mut self, memory_bytes: usize) -> NeuralResult<BudgetStatus> {
+        let started = self
+            .tick_started
+            .take()
+            .ok_or_else(|| NeuralError::InvalidConfiguration("end_tick called without begin_tick".to_string()))?;
+        let elapsed_ms = self.clock.now_ms() - started;
+        let time_over = self.config.tick_ms > 0.0 && elapsed_ms > self.config.tick_ms;
+        let memory_over = self.config.memory_bytes > 0 && memory_bytes > self.config.memory_bytes;
+        let status = match (time_over, memory_over) {
+            (false, false) => BudgetStatus::WithinBudget,
+            (true, false) => BudgetStatus::TimeExceeded,
+            (false, true) => BudgetStatus::MemoryExceeded,
+            (true, true) => BudgetStatus::TimeAndMemoryExceeded,
+        };
+
+        let previous = self.level;
+        if status == BudgetStatus::WithinBudget {
+            self.good_ticks += 1;
+            if self.good_ticks >= self.config.recovery_ticks {
+                self.level = self.level.raise();
+                self.good_ticks = 0;
+            }
+        } else {
+            self.good_ticks = 0;
+            if self.config.auto_downshift {
+                self.level = self.level.lower();
+            }
+        }
+        if self.level != previous {
+            log_event!(LogLevel::Info, "budget", "downshift {} -> {}", previous.name(), self.level.name());
+        }
+
+        self.last_report = Some(BudgetReport {
+            status,
+            elapsed_ms,
+            memory_bytes,
+            kernel_ms: std::mem::take(&mut self.kernel_ms),
+            over_budget_kernel: self.over_budget_kernel.take(),
+            downshift: self.level,
+        });
+        Ok(status)
+    }
+}
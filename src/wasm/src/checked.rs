@@ -0,0 +1,219 @@
+// Checked kernel mode, for chasing crashes reported from the field
+//
+// With set_checked_mode(true) every kernel runs in its most defensive form:
+//   - SIMD dispatch (simd_dispatch!) always takes the bounds-checked scalar path,
+//     and matmul_async stays off the GPU
+//   - linalg kernels check their outputs and fail with KernelCheckFailed on the
+//     first NaN or ±Infinity instead of passing it downstream
+//   - NeuralNetwork.forward, forward_batch and forward_step check every layer's
+//     parameter counts against its shape and the layer chain before running, and
+//     each layer's outputs after it
+// Shape arithmetic (rows × columns) is overflow-checked in every mode.
+//
+// The mode is process-wide, off by default, and costs roughly the scalar build's
+// speed plus one pass over each kernel's outputs. Reproduce a report with it on
+// and the error names the kernel and index where the numbers first went wrong.
+//
+// The tests below are a proptest harness over the kernels and the binary decoders;
+// run them natively with `cargo test checked`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::logging::{log_event, LogLevel};
+
+static CHECKED: AtomicBool = AtomicBool::new(false);
+
+#[wasm_bindgen]
+pub fn set_checked_mode(enabled: bool) {
+    CHECKED.store(enabled, Ordering::Relaxed);
+    log_event!(LogLevel::Info, "checked", "checked kernel mode {}", if enabled { "on" } else { "off" });
+}
+
+#[wasm_bindgen]
+pub fn checked_mode() -> bool {
+    enabled()
+}
+
+pub(crate) fn enabled() -> bool {
+    CHECKED.load(Ordering::Relaxed)
+}
+
+// Element count of a rows×cols matrix, failing instead of wrapping
+pub(crate) fn elements(rows: usize, cols: usize) -> NeuralResult<usize> {
+    rows.checked_mul(cols)
+        .ok_or_else(|| NeuralError::InvalidConfiguration(format!("{}×{} matrix overflows the address space", rows, cols)))
+}
+
+// In checked mode, fail if `kernel` produced a non-finite value
+pub(crate) fn check_outputs(kernel: &str, values: &[f32]) -> NeuralResult<()> {
+    if !enabled() {
+        return Ok(());
+    }
+    match values.iter().position(|value| !value.is_finite()) {
+        Some(index) => Err(NeuralError::KernelCheckFailed {
+            kernel: kernel.to_string(),
+            reason: format!("output {} is {}", index, values[index]),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::activation::{ActivationAccuracy, ActivationKind};
+    use crate::gradients::GradientSet;
+    use crate::linalg;
+    use crate::network::NeuralNetwork;
+    use crate::snapshot::RuntimeSnapshot;
+
+    // Tests share the process-wide flag, so none of them turns it off again
+    fn checked() {
+        set_checked_mode(true);
+    }
+
+    fn reference_matmul(a: &[f32], b: &[f32], m: usize, n: usize, k: usize) -> Vec<f32> {
+        let mut c = vec![0.0; m * n];
+        for i in 0..m {
+            for j in 0..n {
+                c[i * n + j] = (0..k).map(|p| a[i * k + p] * b[p * n + j]).sum();
+            }
+        }
+        c
+    }
+
+    fn close(actual: &[f32], expected: &[f32]) -> bool {
+        actual.len() == expected.len()
+            && actual.iter().zip(expected).all(|(x, y)| (x - y).abs() <= 1e-3 * (1.0 + y.abs()))
+    }
+
+    fn activation() -> impl Strategy<Value = ActivationKind> {
+        prop_oneof![
+            Just(ActivationKind::Linear),
+            Just(ActivationKind::ReLU),
+            Just(ActivationKind::Sigmoid),
+            Just(ActivationKind::Tanh),
+            Just(ActivationKind::GELU),
+        ]
+    }
+
+    // A small dense network: input width, then (size, activation) per layer
+    fn network() -> impl Strategy<Value = (usize, Vec<(usize, ActivationKind)>)> {
+        (1usize..8, prop::collection::vec((1usize..8, activation()), 1..4))
+    }
+
+    fn build((inputs, layers): &(usize, Vec<(usize, ActivationKind)>)) -> NeuralNetwork {
+        let mut network = NeuralNetwork::new(*inputs).unwrap();
+        for &(size, activation) in layers {
+            network.add_layer(size, activation).unwrap();
+        }
+        network
+    }
+
+    #[test]
+    fn toggle_is_visible() {
+        checked();
+        assert!(checked_mode());
+    }
+
+    #[test]
+    fn overflowing_shapes_are_rejected() {
+        checked();
+        let mut c = [0.0f32; 4];
+        assert!(linalg::matmul_into(&[1.0; 4], &[1.0; 4], &mut c, usize::MAX, 2, 2, true).is_err());
+        assert!(linalg::matmul_transposed_into(&[1.0; 4], &[1.0; 4], &mut c, 2, usize::MAX / 2 + 1, 2, true).is_err());
+        assert!(linalg::matvec_into(&[1.0; 4], &[1.0; 2], &mut c[..2], usize::MAX, 2, true).is_err());
+    }
+
+    #[test]
+    fn non_finite_outputs_name_the_kernel() {
+        checked();
+        let mut c = [0.0f32; 1];
+        let err = linalg::matmul_into(&[f32::MAX, f32::MAX], &[f32::MAX, f32::MAX], &mut c, 1, 1, 2, true).unwrap_err();
+        assert!(matches!(err, NeuralError::KernelCheckFailed { ref kernel, .. } if kernel == "matmul"));
+    }
+
+    proptest! {
+        #[test]
+        fn matmul_matches_reference(m in 0usize..9, n in 0usize..9, k in 0usize..9, seed in prop::collection::vec(-10.0f32..10.0, 64)) {
+            checked();
+            let a: Vec<f32> = (0..m * k).map(|i| seed[i % seed.len()]).collect();
+            let b: Vec<f32> = (0..k * n).map(|i| seed[(i * 7 + 3) % seed.len()]).collect();
+            let mut c = vec![0.0; m * n];
+            linalg::matmul_into(&a, &b, &mut c, m, n, k, true).unwrap();
+            prop_assert!(close(&c, &reference_matmul(&a, &b, m, n, k)));
+        }
+
+        #[test]
+        fn kernels_reject_mismatched_buffers(
+            m in 0usize..1 << 20,
+            n in 0usize..1 << 20,
+            k in 0usize..1 << 20,
+            a in prop::collection::vec(any::<f32>(), 0..32),
+            b in prop::collection::vec(any::<f32>(), 0..32),
+            c_len in 0usize..32,
+        ) {
+            checked();
+            let mut c = vec![0.0; c_len];
+            // Only a shape matching every buffer may run; any outcome but a panic is fine
+            let _ = linalg::matmul_into(&a, &b, &mut c, m, n, k, true);
+            let _ = linalg::matmul_transposed_into(&a, &b, &mut c, m, n, k, true);
+            let _ = linalg::matvec_into(&a, &b, &mut c, m, n, true);
+        }
+
+        #[test]
+        fn activations_keep_length(values in prop::collection::vec(any::<f32>(), 0..40), kind in activation()) {
+            checked();
+            let mut out = values.clone();
+            kind.apply_slice_with(&mut out, true, ActivationAccuracy::Accurate);
+            prop_assert_eq!(out.len(), values.len());
+        }
+
+        #[test]
+        fn forward_matches_batched(spec in network(), seed in prop::collection::vec(-5.0f32..5.0, 8), batch in 1usize..4) {
+            checked();
+            let network = build(&spec);
+            let inputs: Vec<f32> = (0..spec.0 * batch).map(|i| seed[i % seed.len()]).collect();
+            let single = network.forward(&inputs[..spec.0]).unwrap();
+            prop_assert!(single.iter().all(|value| value.is_finite()));
+            let batched = network.forward_batch(&inputs, batch).unwrap();
+            prop_assert!(close(&batched[..single.len()], &single));
+        }
+
+        #[test]
+        fn forward_rejects_bad_inputs(spec in network(), inputs in prop::collection::vec(any::<f32>(), 0..16), batch in 0usize..4) {
+            checked();
+            let network = build(&spec);
+            if let Ok(outputs) = network.forward_batch(&inputs, batch) {
+                prop_assert!(outputs.iter().all(|value| value.is_finite()));
+            }
+        }
+
+        #[test]
+        fn weight_decoder_survives_corruption(spec in network(), flips in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..4), cut in any::<prop::sample::Index>()) {
+            checked();
+            let mut bytes = build(&spec).export_weights();
+            for (index, value) in flips {
+                let at = index.index(bytes.len());
+                bytes[at] ^= value;
+            }
+            let len = cut.index(bytes.len() + 1);
+            if let Ok(network) = NeuralNetwork::from_weights(&bytes[..len]) {
+                let _ = network.forward(&vec![0.5; network.input_size()]);
+            }
+        }
+
+        #[test]
+        fn decoders_survive_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            checked();
+            let _ = NeuralNetwork::from_weights(&bytes);
+            let _ = RuntimeSnapshot::from_bytes(&bytes);
+            let _ = GradientSet::decode(&bytes);
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// Checkpoints with incremental deltas
+//
+// A checkpoint's state is the network's flat parameters (get_parameters), then the
+// parameters of its LoRA adapters in layer order, then the moments of its gradient
+// optimizer, so a restored network resumes training where the checkpointed one stood. Checkpointer writes a full snapshot, then deltas
+// holding only the entries that moved by at least `tolerance` since the state a
+// reader would have reconstructed, so skipped drift never accumulates past the
+// tolerance. CheckpointReader replays a snapshot and its deltas in order; every blob
+// carries a checksum of the state it produces, so a missing or reordered delta is
+// rejected rather than silently applied. Deltas need the network to keep its
+// architecture, adapters and optimizer settings; take a new snapshot after changing
+// them.
+//
+// Layout (all integers and floats little-endian):
+//   magic          b"SASC"
+//   version        u16
+//   kind           u8   (0 = full snapshot, 1 = delta)
+//   reserved       u8
+//   sequence       u32  (1 for the first snapshot, +1 per checkpoint)
+//   base_sequence  u32  (delta: the checkpoint it applies to; full: 0)
+//   checksum       u32  (FNV-1a over the resulting state's f32 bytes)
+//   full:  weights_len u32, SASW weight blob,
+//          adapters_len u32, SASL adapter blob                (version 3 and later)
+//          optimizer kind u8, reserved [u8; 3], beta1 f32, beta2 f32, epsilon f32,
+//          step u64                                          (version 2 and later)
+//          moments_len u32, f32[moments_len]
+//   delta: state_len u32, optimizer_step u64 (version 2 and later),
+//          run_count u32, run_count × { start u32, len u32, f32[len] }
+// Version 1 stored an optimizer vector supplied by the caller in place of the
+// moments; it is read past, and networks restored from it get a fresh optimizer.
+// A delta must have the version of the snapshot it builds on.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::gradient_optimizer::GradientOptimizerConfig;
+use crate::network::NeuralNetwork;
+use crate::replay;
+use crate::serialization::{ByteReader, ByteWriter};
+
+pub const CHECKPOINT_MAGIC: &[u8; 4] = b"SASC";
+pub const CHECKPOINT_VERSION: u16 = 3;
+
+const KIND_FULL: u8 = 0;
+const KIND_DELTA: u8 = 1;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Checkpointer {
+    tolerance: f32,
+    sequence: u32,
+    // State as a reader of the checkpoints written so far reconstructs it
+    persisted: Option<PersistedState>,
+}
+
+#[derive(Debug, Clone)]
+struct PersistedState {
+    state: Vec<f32>,
+    parameter_count: usize,
+    adapter_count: usize,
+    optimizer: GradientOptimizerConfig,
+}
+
+#[wasm_bindgen]
+impl Checkpointer {
+    // `tolerance` 0 records every change exactly
+    #[wasm_bindgen(constructor)]
+    pub fn new(tolerance: f32) -> Result<Checkpointer, NeuralError> {
+        if !tolerance.is_finite() || tolerance < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("checkpoint tolerance must be finite and non-negative".to_string()));
+        }
+        Ok(Checkpointer { tolerance, sequence: 0, persisted: None })
+    }
+
+    // Sequence number of the last checkpoint written, 0 before the first
+    #[wasm_bindgen(getter)]
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    // Full snapshot; later deltas are relative to it
+    #[wasm_bindgen]
+    pub fn snapshot(&mut self, network: &NeuralNetwork) -> Result<Vec<u8>, NeuralError> {
+        let (step, moments) = network.optimizer_checkpoint();
+        check_finite(&moments)?;
+        let (parameter_count, adapter_count) = (network.parameter_count(), network.adapter_parameter_count());
+        let mut state = network.get_parameters();
+        state.extend(network.adapter_parameters());
+        state.extend_from_slice(&moments);
+
+        let sequence = self.next_sequence()?;
+        let mut writer = header(KIND_FULL, sequence, 0, &state);
+        let weights = network.export_weights();
+        writer.u32(weights.len() as u32);
+        writer.bytes(&weights);
+        let adapters = network.export_adapters();
+        writer.u32(adapters.len() as u32);
+        writer.bytes(&adapters);
+        let optimizer = network.optimizer();
+        replay::write_optimizer(&mut writer, &optimizer);
+        writer.u64(step);
+        writer.u32(moments.len() as u32);
+        writer.f32_slice(&moments);
+
+        self.sequence = sequence;
+        self.persisted = Some(PersistedState { state, parameter_count, adapter_count, optimizer });
+        Ok(writer.finish())
+    }
+
+    // Entries that changed since the previous checkpoint; the network must keep the
+    // architecture and optimizer settings of the snapshot
+    #[wasm_bindgen]
+    pub fn delta(&mut self, network: &NeuralNetwork) -> Result<Vec<u8>, NeuralError> {
+        let (step, moments) = network.optimizer_checkpoint();
+        check_finite(&moments)?;
+        let tolerance = self.tolerance;
+        let sequence = self.next_sequence()?;
+        let Some(persisted) = self.persisted.as_mut() else {
+            return Err(NeuralError::InvalidConfiguration("take a full snapshot before the first delta".to_string()));
+        };
+        if network.parameter_count() != persisted.parameter_count {
+            return Err(NeuralError::DimensionMismatch { expected: persisted.parameter_count, actual: network.parameter_count() });
+        }
+        if network.adapter_parameter_count() != persisted.adapter_count {
+            return Err(NeuralError::DimensionMismatch { expected: persisted.adapter_count, actual: network.adapter_parameter_count() });
+        }
+        if network.optimizer() != persisted.optimizer {
+            return Err(NeuralError::InvalidConfiguration("the optimizer changed since the last snapshot".to_string()));
+        }
+        let mut current = network.get_parameters();
+        current.extend(network.adapter_parameters());
+        current.extend_from_slice(&moments);
+        if current.len() != persisted.state.len() {
+            return Err(NeuralError::DimensionMismatch { expected: persisted.state.len(), actual: current.len() });
+        }
+
+        // Runs of consecutive changed entries
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for (index, (old, new)) in persisted.state.iter_mut().zip(&current).enumerate() {
+            if old.to_bits() != new.to_bits() && (*new - *old).abs() >= tolerance {
+                *old = *new;
+                match runs.last_mut() {
+                    Some((start, len)) if *start + *len == index => *len += 1,
+                    _ => runs.push((index, 1)),
+                }
+            }
+        }
+
+        let mut writer = header(KIND_DELTA, sequence, self.sequence, &persisted.state);
+        writer.u32(persisted.state.len() as u32);
+        writer.u64(step);
+        writer.u32(runs.len() as u32);
+        for &(start, len) in &runs {
+            writer.u32(start as u32);
+            writer.u32(len as u32);
+            writer.f32_slice(&persisted.state[start..start + len]);
+        }
+        self.sequence = sequence;
+        Ok(writer.finish())
+    }
+}
+
+impl Checkpointer {
+    fn next_sequence(&self) -> NeuralResult<u32> {
+        self.sequence
+            .checked_add(1)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("checkpoint sequence exhausted".to_string()))
+    }
+}
+
+// Rebuilds a network, with its optimizer, from a snapshot and its deltas
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointReader {
+    network: Option<NeuralNetwork>,
+    state: Vec<f32>,
+    sequence: u32,
+    // Format version of the snapshot the deltas build on
+    version: u16,
+}
+
+#[wasm_bindgen]
+impl CheckpointReader {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CheckpointReader {
+        CheckpointReader::default()
+    }
+
+    // Sequence number of the last checkpoint applied, 0 before the first
+    #[wasm_bindgen(getter)]
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    // Apply a full snapshot (replacing everything) or the delta that follows the last
+    // checkpoint applied
+    #[wasm_bindgen]
+    pub fn apply(&mut self, blob: &[u8]) -> Result<(), NeuralError> {
+        let mut reader = ByteReader::new(blob);
+        if reader.bytes(4)? != CHECKPOINT_MAGIC {
+            return Err(NeuralError::InvalidFormat("missing SASC header".to_string()));
+        }
+        let version = reader.u16()?;
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(NeuralError::InvalidFormat(format!("unsupported checkpoint format version {}", version)));
+        }
+        let kind = reader.u8()?;
+        reader.u8()?;
+        let sequence = reader.u32()?;
+        let base_sequence = reader.u32()?;
+        let checksum = reader.u32()?;
+
+        let (network, state) = match kind {
+            KIND_FULL => {
+                let weights_len = reader.u32()? as usize;
+                let mut network = NeuralNetwork::from_weights(reader.bytes(weights_len)?)?;
+                if version >= 3 {
+                    let adapters_len = reader.u32()? as usize;
+                    network.import_adapters(reader.bytes(adapters_len)?)?;
+                }
+                let optimizer = if version >= 2 { Some((replay::read_optimizer(&mut reader)?, reader.u64()?)) } else { None };
+                let moments_len = reader.u32()? as usize;
+                let moments = reader.f32_vec(moments_len)?;
+                if let Some((config, step)) = optimizer {
+                    network
+                        .restore_optimizer(config, step, &moments)
+                        .map_err(|_| NeuralError::InvalidFormat("optimizer state does not match the network".to_string()))?;
+                }
+                let mut state = network.get_parameters();
+                state.extend(network.adapter_parameters());
+                state.extend(moments);
+                (network, state)
+            }
+            KIND_DELTA => {
+                let Some(network) = &self.network else {
+                    return Err(NeuralError::InvalidConfiguration("apply a full snapshot before its deltas".to_string()));
+                };
+                if base_sequence != self.sequence {
+                    return Err(NeuralError::InvalidFormat(format!(
+                        "delta applies to checkpoint {}, but the reader is at {}",
+                        base_sequence, self.sequence
+                    )));
+                }
+                if version != self.version {
+                    return Err(NeuralError::InvalidFormat(format!(
+                        "delta has format version {}, but its snapshot has version {}",
+                        version, self.version
+                    )));
+                }
+                let state_len = reader.u32()? as usize;
+                if state_len != self.state.len() {
+                    return Err(NeuralError::DimensionMismatch { expected: self.state.len(), actual: state_len });
+                }
+                let step = if version >= 2 { Some(reader.u64()?) } else { None };
+                let mut state = self.state.clone();
+                for _ in 0..reader.u32()? {
+                    let start = reader.u32()? as usize;
+                    let len = reader.u32()? as usize;
+                    let target = start
+                        .checked_add(len)
+                        .and_then(|end| state.get_mut(start..end))
+                        .ok_or_else(|| NeuralError::InvalidFormat("delta run lies outside the state".to_string()))?;
+                    target.copy_from_slice(&reader.f32_vec(len)?);
+                }
+                let mut network = network.clone();
+                let (parameters, rest) = state.split_at(network.parameter_count());
+                let (adapters, moments) = rest.split_at(network.adapter_parameter_count().min(rest.len()));
+                network.set_parameters(parameters)?;
+                network.set_adapter_parameters(adapters)?;
+                if let Some(step) = step {
+                    network.restore_optimizer(network.optimizer(), step, moments)?;
+                }
+                (network, state)
+            }
+            other => return Err(NeuralError::InvalidFormat(format!("unknown checkpoint kind {}", other))),
+        };
+        if !reader.is_empty() {
+            return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+        }
+        check_finite(&state)?;
+        if state_checksum(&state) != checksum {
+            return Err(NeuralError::InvalidFormat("checkpoint checksum mismatch".to_string()));
+        }
+
+        if kind == KIND_FULL {
+            self.version = version;
+        }
+        self.network = Some(network);
+        self.state = state;
+        self.sequence = sequence;
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn network(&self) -> Result<NeuralNetwork, NeuralError> {
+        self.network
+            .clone()
+            .ok_or_else(|| NeuralError::InvalidConfiguration("no checkpoint has been applied".to_string()))
+    }
+
+}
+
+fn header(kind: u8, sequence: u32, base_sequence: u32, state: &[f32]) -> ByteWriter {
+    let mut writer = ByteWriter::new();
+    writer.bytes(CHECKPOINT_MAGIC);
+    writer.u16(CHECKPOINT_VERSION);
+    writer.u8(kind);
+    writer.u8(0);
+    writer.u32(sequence);
+    writer.u32(base_sequence);
+    writer.u32(state_checksum(state));
+    writer
+}
+
+fn check_finite(values: &[f32]) -> NeuralResult<()> {
+    if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+        return Err(NeuralError::NonFiniteInput { index });
+    }
+    Ok(())
+}
+
+// 32-bit FNV-1a over the little-endian bytes of every value
+fn state_checksum(state: &[f32]) -> u32 {
+    let mut hash: u32 = 0x811C_9DC5;
+    for byte in state.iter().flat_map(|value| value.to_le_bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
@@ -0,0 +1,100 @@
+// Time sources that work in windows, Web Workers and Node
+//
+// Clock measures intervals with globalThis.performance.now(), which windows,
+// workers and Node all provide, falling back to the millisecond-resolution
+// Date.now() where it is missing. WASI and native builds use std::time::Instant.
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    // High-resolution performance.now()
+    Performance = 0,
+    // Date.now(); intervals shorter than a millisecond read as zero
+    Date = 1,
+    // std::time::Instant outside the browser
+    Native = 2,
+}
+
+// Source a new Clock would use in the current global scope
+#[wasm_bindgen]
+pub fn time_source() -> TimeSource {
+    Clock::new().source()
+}
+
+#[derive(Debug, Clone)]
+pub struct Clock {
+    // performance object and its now() method, looked up once
+    #[cfg(js_host)]
+    performance: Option<(JsValue, js_sys::Function)>,
+    #[cfg(not(js_host))]
+    origin: std::time::Instant,
+}
+
+impl Clock {
+    #[cfg(js_host)]
+    pub fn new() -> Clock {
+        // Duck-typed rather than checked against the Performance class, which Node lacks
+        let performance = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+            .ok()
+            .filter(|performance| performance.is_object())
+            .and_then(|performance| {
+                let now = js_sys::Reflect::get(&performance, &JsValue::from_str("now")).ok()?;
+                Some((performance, now.dyn_into::<js_sys::Function>().ok()?))
+            });
+        Clock { performance }
+    }
+
+    #[cfg(not(js_host))]
+    pub fn new() -> Clock {
+        Clock { origin: std::time::Instant::now() }
+    }
+
+    #[cfg(js_host)]
+    pub fn source(&self) -> TimeSource {
+        match self.performance {
+            Some(_) => TimeSource::Performance,
+            None => TimeSource::Date,
+        }
+    }
+
+    #[cfg(not(js_host))]
+    pub fn source(&self) -> TimeSource {
+        TimeSource::Native
+    }
+
+    // Milliseconds from an arbitrary origin; only differences are meaningful
+    #[cfg(js_host)]
+    pub fn now_ms(&self) -> f64 {
+        self.performance
+            .as_ref()
+            .and_then(|(performance, now)| now.call0(performance).ok())
+            .and_then(|value| value.as_f64())
+            .unwrap_or_else(js_sys::Date::now)
+    }
+
+    #[cfg(not(js_host))]
+    pub fn now_ms(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::new()
+    }
+}
+
+// Milliseconds since the Unix epoch, for timestamps rather than intervals
+#[cfg(js_host)]
+pub fn wall_time_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(js_host))]
+pub fn wall_time_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
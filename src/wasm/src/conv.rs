@@ -0,0 +1,132 @@
+// One-dimensional convolution over channel-major sequences
+//
+// Inputs are [in_channels][length] and outputs [out_channels][out_length], both
+// flattened; weights are [out_channels][in_channels][kernel]. Zero padding is
+// applied to both ends, so out_length = (length + 2·padding - kernel) / stride + 1.
+// The inner loop runs over output positions: with stride 1 every kernel tap is a
+// single vectorized axpy across the sequence.
+
+use std::ops::Range;
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Conv1dGeometry {
+    pub(crate) in_channels: usize,
+    pub(crate) out_channels: usize,
+    pub(crate) kernel: usize,
+    pub(crate) stride: usize,
+    pub(crate) padding: usize,
+}
+
+impl Conv1dGeometry {
+    // Positions per output channel for a sequence of `length`, or None if the kernel does not fit
+    pub(crate) fn out_length(&self, length: usize) -> Option<usize> {
+        let padded = length.checked_add(self.padding.checked_mul(2)?)?;
+        if self.kernel == 0 || self.stride == 0 || self.kernel > padded {
+            return None;
+        }
+        Some((padded - self.kernel) / self.stride + 1)
+    }
+
+    pub(crate) fn weight_count(&self) -> Option<usize> {
+        self.out_channels.checked_mul(self.in_channels)?.checked_mul(self.kernel)
+    }
+
+    // Sequence length implied by a layer's flattened sizes, if they fit this geometry
+    pub(crate) fn length_for(&self, inputs: usize, outputs: usize) -> Option<usize> {
+        if self.in_channels == 0 || self.out_channels == 0 || !inputs.is_multiple_of(self.in_channels) {
+            return None;
+        }
+        let length = inputs / self.in_channels;
+        (self.out_length(length)?.checked_mul(self.out_channels)? == outputs).then_some(length)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Conv1dLayer {
+    pub(crate) geometry: Conv1dGeometry,
+    pub(crate) length: usize,
+    pub(crate) out_length: usize,
+    pub(crate) weights: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+    pub(crate) activation: ActivationKind,
+}
+
+impl Conv1dLayer {
+    pub(crate) fn new(geometry: Conv1dGeometry, length: usize, activation: ActivationKind) -> NeuralResult<Conv1dLayer> {
+        if geometry.in_channels == 0 || geometry.out_channels == 0 || length == 0 {
+            return Err(NeuralError::InvalidConfiguration("convolution channels and length must be non-zero".to_string()));
+        }
+        let out_length = geometry.out_length(length).ok_or_else(|| {
+            NeuralError::InvalidConfiguration("kernel and stride must be non-zero and the kernel must fit the padded input".to_string())
+        })?;
+        let weight_count = geometry
+            .weight_count()
+            .ok_or_else(|| NeuralError::InvalidConfiguration("convolution shape overflows".to_string()))?;
+        Ok(Conv1dLayer {
+            geometry,
+            length,
+            out_length,
+            weights: vec![0.0; weight_count],
+            biases: vec![0.0; geometry.out_channels],
+            activation,
+        })
+    }
+
+    pub(crate) fn inputs(&self) -> usize {
+        self.geometry.in_channels * self.length
+    }
+
+    pub(crate) fn outputs(&self) -> usize {
+        self.geometry.out_channels * self.out_length
+    }
+
+    pub(crate) fn forward_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        if inputs.len() != self.inputs() {
+            return Err(NeuralError::DimensionMismatch { expected: self.inputs(), actual: inputs.len() });
+        }
+        if outputs.len() != self.outputs() {
+            return Err(NeuralError::DimensionMismatch { expected: self.outputs(), actual: outputs.len() });
+        }
+
+        let Conv1dGeometry { in_channels, kernel, stride, padding, .. } = self.geometry;
+        let filters = self.weights.chunks_exact(in_channels * kernel);
+        for ((output, filter), bias) in outputs.chunks_exact_mut(self.out_length).zip(filters).zip(&self.biases) {
+            output.fill(*bias);
+            for (channel, taps) in inputs.chunks_exact(self.length).zip(filter.chunks_exact(kernel)) {
+                for (tap, &weight) in taps.iter().enumerate() {
+                    let positions = self.valid_positions(tap);
+                    if positions.is_empty() {
+                        continue;
+                    }
+                    let first = positions.start * stride + tap - padding;
+                    let count = positions.len();
+                    if stride == 1 {
+                        linalg::axpy(weight, &channel[first..first + count], &mut output[positions], simd);
+                    } else {
+                        for (out, x) in output[positions].iter_mut().zip(channel[first..].iter().step_by(stride)) {
+                            *out += weight * x;
+                        }
+                    }
+                }
+            }
+        }
+        self.activation.apply_slice(outputs, simd);
+        Ok(())
+    }
+
+    // Output positions t whose input index t·stride + tap - padding falls inside the
+    // sequence; positions outside read zero padding and are skipped
+    fn valid_positions(&self, tap: usize) -> Range<usize> {
+        let Conv1dGeometry { stride, padding, .. } = self.geometry;
+        let start = padding.saturating_sub(tap).div_ceil(stride);
+        let end = match (self.length + padding).checked_sub(tap + 1) {
+            Some(last_offset) => (last_offset / stride + 1).min(self.out_length),
+            None => 0,
+        };
+        start..end.max(start)
+    }
+}
@@ -0,0 +1,182 @@
+// Training data held in WASM memory
+//
+// A Dataset copies its feature and label arrays in once. Training on it
+// (NeuralNetwork.train_dataset_epoch, fit_dataset, evaluate_dataset) then walks
+// the samples in mini-batches without crossing the JS boundary again: each batch
+// is gathered into buffers that are reused from batch to batch. shuffle() draws a
+// new sample order with a Fisher–Yates shuffle from the dataset's own seeded RNG,
+// so a run is reproducible from the seed; the data itself never moves.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::Rng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    inputs: Vec<f32>,
+    targets: Vec<f32>,
+    input_size: usize,
+    target_size: usize,
+    // Sample visited at each position of an epoch
+    order: Vec<usize>,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl Dataset {
+    // Row-major samples: inputs [len × input_size] and targets [len × target_size]
+    #[wasm_bindgen(constructor)]
+    pub fn new(inputs: Vec<f32>, targets: Vec<f32>, input_size: usize, target_size: usize, seed: u64) -> Result<Dataset, NeuralError> {
+        if input_size == 0 || target_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("dataset sample sizes must be non-zero".to_string()));
+        }
+        if inputs.is_empty() || !inputs.len().is_multiple_of(input_size) {
+            return Err(NeuralError::InvalidConfiguration("inputs must hold a whole number of samples".to_string()));
+        }
+        let len = inputs.len() / input_size;
+        if targets.len() != len * target_size {
+            return Err(NeuralError::DimensionMismatch { expected: len * target_size, actual: targets.len() });
+        }
+        for values in [&inputs, &targets] {
+            if let Some(index) = values.iter().position(|x| !x.is_finite()) {
+                return Err(NeuralError::NonFiniteInput { index });
+            }
+        }
+        Ok(Dataset { inputs, targets, input_size, target_size, order: (0..len).collect(), rng: Rng::new(seed) })
+    }
+
+    // Number of samples
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    // Always false: a dataset holds at least one sample
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn target_size(&self) -> usize {
+        self.target_size
+    }
+
+    // Draw a new sample order
+    #[wasm_bindgen]
+    pub fn shuffle(&mut self) {
+        for index in (1..self.order.len()).rev() {
+            self.order.swap(index, self.rng.next_u64() as usize % (index + 1));
+        }
+    }
+
+    // Restart the shuffle sequence from `seed`; the current order is kept
+    #[wasm_bindgen]
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    // Back to the samples' original order
+    #[wasm_bindgen]
+    pub fn reset_order(&mut self) {
+        for (position, sample) in self.order.iter_mut().enumerate() {
+            *sample = position;
+        }
+    }
+
+    // Sample indices in the current order
+    #[wasm_bindgen]
+    pub fn order(&self) -> Vec<u32> {
+        self.order.iter().map(|&sample| sample as u32).collect()
+    }
+
+    // Mini-batches per epoch; the last may be smaller
+    #[wasm_bindgen]
+    pub fn batch_count(&self, batch_size: usize) -> Result<usize, NeuralError> {
+        check_batch_size(batch_size)?;
+        Ok(self.len().div_ceil(batch_size))
+    }
+
+    // Mini-batch `index` in the current order, copied out for inspection
+    #[wasm_bindgen]
+    pub fn batch(&self, index: usize, batch_size: usize) -> Result<DatasetBatch, NeuralError> {
+        let count = self.batch_count(batch_size)?;
+        if index >= count {
+            return Err(NeuralError::IndexOutOfRange { index, len: count });
+        }
+        let (mut inputs, mut targets) = (Vec::new(), Vec::new());
+        let size = self.gather(index, batch_size, &mut inputs, &mut targets);
+        Ok(DatasetBatch { inputs, targets, size })
+    }
+}
+
+impl Dataset {
+    // Every sample's inputs in their original order
+    pub(crate) fn sample_inputs(&self) -> &[f32] {
+        &self.inputs
+    }
+
+    // Swap in targets of another size for the same samples; returns the old ones
+    // and their size
+    pub(crate) fn replace_targets(&mut self, targets: Vec<f32>, target_size: usize) -> (Vec<f32>, usize) {
+        debug_assert_eq!(targets.len(), self.len() * target_size);
+        let previous = std::mem::replace(&mut self.targets, targets);
+        (previous, std::mem::replace(&mut self.target_size, target_size))
+    }
+
+    // Gather batch `index` into the buffers, which are resized as needed; returns
+    // the number of samples in it
+    pub(crate) fn gather(&self, index: usize, batch_size: usize, inputs: &mut Vec<f32>, targets: &mut Vec<f32>) -> usize {
+        let start = (index * batch_size).min(self.len());
+        let samples = &self.order[start..(start + batch_size).min(self.len())];
+        inputs.clear();
+        targets.clear();
+        for &sample in samples {
+            inputs.extend_from_slice(&self.inputs[sample * self.input_size..(sample + 1) * self.input_size]);
+            targets.extend_from_slice(&self.targets[sample * self.target_size..(sample + 1) * self.target_size]);
+        }
+        samples.len()
+    }
+}
+
+// One mini-batch of a Dataset
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetBatch {
+    inputs: Vec<f32>,
+    targets: Vec<f32>,
+    size: usize,
+}
+
+#[wasm_bindgen]
+impl DatasetBatch {
+    #[wasm_bindgen(getter)]
+    pub fn inputs(&self) -> Vec<f32> {
+        self.inputs.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn targets(&self) -> Vec<f32> {
+        self.targets.clone()
+    }
+
+    // Samples in the batch
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+fn check_batch_size(batch_size: usize) -> NeuralResult<()> {
+    if batch_size == 0 {
+        return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// Per-layer health diagnostics for the monitoring UI
+//
+// NeuralNetwork.diagnostics() summarizes every layer as a compact binary buffer:
+// a histogram of its weights (γ for normalization layers, nothing for dropout) and
+// the L2 norm of its gradients over the most recent training steps (train_batch,
+// the fit helpers and apply_gradients record one row per step, before clipping).
+// diagnostics_with_probe(inputs, batch_size) also runs the probe batch through the
+// network in inference mode and adds a histogram of each layer's outputs and its
+// dead units: outputs that are exactly 0 for every probe sample, i.e. dead ReLUs.
+//
+// Histograms have HISTOGRAM_BINS equal-width bins spanning the finite values' min
+// to max (all in the first bin when those are equal); NaN and ±Infinity are only
+// counted. Changing the number of layers restarts the gradient history.
+//
+// Layout (all integers and floats little-endian):
+//   magic        b"SASD"
+//   version      u16
+//   flags        u16  (bit 0: activation sections present)
+//   bins         u32
+//   samples      u32  (probe samples; 0 without a probe)
+//   layer_count  u32
+//   layer_count × {
+//     kind u8 (LayerKind), activation u8 (ActivationKind), reserved u16
+//     weights      histogram
+//     if flag 0:   activations histogram, dead_units u32, units u32
+//   }
+//   steps        u32  (training steps in the history, oldest first)
+//   total_steps  u64  (steps recorded since the history started)
+//   steps × layer_count × gradient_norm f32
+// where histogram = { min f32, max f32, non_finite u32, counts u32[bins] }.
+
+use std::collections::VecDeque;
+
+use crate::activation::ActivationKind;
+use crate::network::LayerKind;
+use crate::serialization::ByteWriter;
+use crate::training::LayerGradients;
+
+pub const DIAGNOSTICS_MAGIC: &[u8; 4] = b"SASD";
+pub const DIAGNOSTICS_VERSION: u16 = 1;
+pub const HISTOGRAM_BINS: usize = 32;
+const FLAG_ACTIVATIONS: u16 = 1;
+// Training steps kept in the gradient history
+const TREND_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Histogram {
+    min: f32,
+    max: f32,
+    non_finite: u32,
+    counts: [u32; HISTOGRAM_BINS],
+}
+
+impl Histogram {
+    pub(crate) fn of(values: &[f32]) -> Histogram {
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        for &value in values.iter().filter(|value| value.is_finite()) {
+            min = min.min(value);
+            max = max.max(value);
+        }
+        if min > max {
+            (min, max) = (0.0, 0.0);
+        }
+        let mut histogram = Histogram { min, max, non_finite: 0, counts: [0; HISTOGRAM_BINS] };
+        let scale = if max > min { HISTOGRAM_BINS as f32 / (max - min) } else { 0.0 };
+        for &value in values {
+            if !value.is_finite() {
+                histogram.non_finite += 1;
+                continue;
+            }
+            let bin = (((value - min) * scale) as usize).min(HISTOGRAM_BINS - 1);
+            histogram.counts[bin] += 1;
+        }
+        histogram
+    }
+
+    fn write(&self, writer: &mut ByteWriter) {
+        writer.f32(self.min);
+        writer.f32(self.max);
+        writer.u32(self.non_finite);
+        for &count in &self.counts {
+            writer.u32(count);
+        }
+    }
+}
+
+// Outputs of one layer over a probe batch
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ActivationReport {
+    pub(crate) histogram: Histogram,
+    pub(crate) dead_units: u32,
+    pub(crate) units: u32,
+}
+
+impl ActivationReport {
+    // `outputs` is row-major [samples × units]
+    pub(crate) fn of(outputs: &[f32], units: usize) -> ActivationReport {
+        let mut active = vec![false; units];
+        for row in outputs.chunks_exact(units) {
+            for (alive, &value) in active.iter_mut().zip(row) {
+                *alive |= value != 0.0;
+            }
+        }
+        ActivationReport {
+            histogram: Histogram::of(outputs),
+            dead_units: active.iter().filter(|&&alive| !alive).count() as u32,
+            units: units as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LayerReport {
+    pub(crate) kind: LayerKind,
+    pub(crate) activation: ActivationKind,
+    pub(crate) weights: Histogram,
+    pub(crate) activations: Option<ActivationReport>,
+}
+
+// Per-layer gradient norms of the most recent training steps
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GradientTrend {
+    steps: VecDeque<Vec<f32>>,
+    total: u64,
+}
+
+impl GradientTrend {
+    pub(crate) fn record(&mut self, gradients: &[LayerGradients]) {
+        if self.steps.front().is_some_and(|step| step.len() != gradients.len()) {
+            self.clear();
+        }
+        if self.steps.len() == TREND_LEN {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(gradients.iter().map(gradient_norm).collect());
+        self.total += 1;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.steps.clear();
+        self.total = 0;
+    }
+}
+
+fn gradient_norm(gradient: &LayerGradients) -> f32 {
+    let squares: f64 = gradient.weights.iter().chain(&gradient.biases).map(|&grad| grad as f64 * grad as f64).sum();
+    squares.sqrt() as f32
+}
+
+pub(crate) fn encode(layers: &[LayerReport], samples: u32, trend: &GradientTrend) -> Vec<u8> {
+    let probed = layers.iter().any(|layer| layer.activations.is_some());
+    let mut writer = ByteWriter::new();
+    writer.bytes(DIAGNOSTICS_MAGIC);
+    writer.u16(DIAGNOSTICS_VERSION);
+    writer.u16(if probed { FLAG_ACTIVATIONS } else { 0 });
+    writer.u32(HISTOGRAM_BINS as u32);
+    writer.u32(samples);
+    writer.u32(layers.len() as u32);
+    for layer in layers {
+        writer.u8(layer.kind as u8);
+        writer.u8(layer.activation as u8);
+        writer.u16(0);
+        layer.weights.write(&mut writer);
+        if let Some(activations) = &layer.activations {
+            activations.histogram.write(&mut writer);
+            writer.u32(activations.dead_units);
+            writer.u32(activations.units);
+        }
+    }
+
+    // A history from before the layers changed is cleared on the next step; until
+    // then it is left out
+    let steps: Vec<&Vec<f32>> = trend.steps.iter().filter(|step| step.len() == layers.len()).collect();
+    writer.u32(steps.len() as u32);
+    writer.u64(if steps.is_empty() { 0 } else { trend.total });
+    for step in steps {
+        writer.f32_slice(step);
+    }
+    writer.finish()
+}
@@ -0,0 +1,131 @@
+// Knowledge distillation
+//
+// distill(teacher, student, dataset, temperature, options, on_epoch) compresses a
+// large agent model into a smaller one without leaving WASM. The teacher runs once
+// over the dataset's inputs in inference mode (its preprocessor, output mode and
+// arithmetic apply), and each of its outputs z becomes the soft target
+// p = softmax(z / T). The student then trains with fit_dataset under `options`
+// on the loss T² · KL(p ‖ softmax(s / T)) of its outputs s, whose gradient with
+// respect to s is T · (softmax(s / T) - p). A temperature above 1 flattens both
+// distributions so the student also learns how the teacher ranks the wrong
+// classes; T² keeps gradient magnitudes comparable across temperatures.
+//
+// Outputs are treated as logits, except when they are already probabilities: a
+// teacher with a softmax last layer or Softmax output mode, or a student with a
+// softmax last layer (training ignores the output mode). Their logarithms (clamped
+// away from 0) then stand in for the logits, which softmax maps back to the same
+// distribution. The teacher and student must agree on input and output sizes.
+//
+// The dataset's own targets are not used, and they are back in place when distill
+// returns; its sample order and shuffling work as in fit_dataset. The student's loss
+// is only swapped for the run, so a replay trace recording it diverges at the first
+// distillation step.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::ActivationKind;
+use crate::dataset::Dataset;
+use crate::error::{NeuralError, NeuralResult};
+use crate::loss::Loss;
+use crate::network::{NeuralNetwork, OutputMode};
+use crate::training::{FitOptions, FitReport};
+
+const PROBABILITY_EPSILON: f32 = 1e-7;
+
+// The loss a student trains with during distill; targets are soft probabilities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Distillation {
+    temperature: f32,
+    // The student's outputs are probabilities rather than logits
+    probabilities: bool,
+}
+
+impl Distillation {
+    // Loss of one sample; writes dL/dy into `grad`
+    pub(crate) fn sample(&self, predicted: &[f32], target: &[f32], grad: &mut [f32]) -> f32 {
+        let temperature = self.temperature;
+        let logits = logits(predicted, self.probabilities);
+        let softened = soften(&logits, temperature);
+        let mut loss = 0.0;
+        for ((g, q), p) in grad.iter_mut().zip(&softened).zip(target) {
+            if *p > 0.0 {
+                loss += p * (p.ln() - q.max(f32::MIN_POSITIVE).ln());
+            }
+            *g = temperature * (q - p);
+        }
+        if self.probabilities {
+            // Chain rule through z = ln(y)
+            for (g, y) in grad.iter_mut().zip(predicted) {
+                *g /= y.max(PROBABILITY_EPSILON);
+            }
+        }
+        temperature * temperature * loss
+    }
+}
+
+#[wasm_bindgen]
+pub fn distill(
+    teacher: &NeuralNetwork,
+    student: &mut NeuralNetwork,
+    dataset: &mut Dataset,
+    temperature: f32,
+    options: &FitOptions,
+    on_epoch: Option<js_sys::Function>,
+) -> Result<FitReport, NeuralError> {
+    if !temperature.is_finite() || temperature <= 0.0 {
+        return Err(NeuralError::InvalidConfiguration("distillation temperature must be positive and finite".to_string()));
+    }
+    options.validate()?;
+    if teacher.input_size() != student.input_size() {
+        return Err(NeuralError::DimensionMismatch { expected: teacher.input_size(), actual: student.input_size() });
+    }
+    if teacher.output_size() != student.output_size() {
+        return Err(NeuralError::DimensionMismatch { expected: teacher.output_size(), actual: student.output_size() });
+    }
+    if dataset.input_size() != teacher.input_size() {
+        return Err(NeuralError::DimensionMismatch { expected: teacher.input_size(), actual: dataset.input_size() });
+    }
+
+    let targets = soft_targets(teacher, dataset.sample_inputs(), temperature, options.batch_size)?;
+    let loss = Distillation { temperature, probabilities: student.final_activation() == Some(ActivationKind::Softmax) };
+    let hard = dataset.replace_targets(targets, student.output_size());
+    let report = student.fit_dataset_with_loss(dataset, options, on_epoch, Loss::Distillation(loss));
+    dataset.replace_targets(hard.0, hard.1);
+    report
+}
+
+// softmax(z / T) of the teacher's outputs for every sample, in dataset order
+fn soft_targets(teacher: &NeuralNetwork, inputs: &[f32], temperature: f32, batch_size: usize) -> NeuralResult<Vec<f32>> {
+    let probabilities = teacher.output_mode() == OutputMode::Softmax || teacher.final_activation() == Some(ActivationKind::Softmax);
+    let (input_size, output_size) = (teacher.input_size(), teacher.output_size());
+    let mut targets = Vec::with_capacity(inputs.len() / input_size * output_size);
+    for batch in inputs.chunks(batch_size * input_size) {
+        let outputs = teacher.forward_batch(batch, batch.len() / input_size)?;
+        for row in outputs.chunks_exact(output_size) {
+            targets.extend(soften(&logits(row, probabilities), temperature));
+        }
+    }
+    if let Some(index) = targets.iter().position(|value| !value.is_finite()) {
+        return Err(NeuralError::NonFiniteInput { index });
+    }
+    Ok(targets)
+}
+
+fn logits(outputs: &[f32], probabilities: bool) -> Vec<f32> {
+    if probabilities {
+        outputs.iter().map(|y| y.max(PROBABILITY_EPSILON).ln()).collect()
+    } else {
+        outputs.to_vec()
+    }
+}
+
+// softmax(z / T), shifted by the largest logit so the exponentials cannot overflow
+fn soften(logits: &[f32], temperature: f32) -> Vec<f32> {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let mut softened: Vec<f32> = logits.iter().map(|z| ((z - max) / temperature).exp()).collect();
+    let total: f32 = softened.iter().sum();
+    for value in softened.iter_mut() {
+        *value /= total;
+    }
+    softened
+}
@@ -0,0 +1,320 @@
+// Early-exit inference: best-effort predictions from auxiliary heads under a deadline
+//
+// An EarlyExitNetwork wraps a NeuralNetwork (the backbone) and attaches exits, each
+// a dense head from the output of one backbone layer to the backbone's output size.
+// Exits are numbered from the input side. forward_with_deadline() runs the backbone
+// a layer at a time and evaluates every exit it passes, keeping the latest exit's
+// prediction. Before each segment that follows an exit (its layers up to the next
+// exit, or to the end) it adds the segment's expected cost to the time already
+// used, and if that would overrun the budget it returns the prediction in hand
+// instead of starting the segment. Until the first exit there is nothing to fall
+// back on, so those layers always run.
+//
+// Expected costs are running means (α = 0.2) of the measured times of every layer
+// and head, zero until first measured, so the first calls tend to run to the end.
+// Exits learn with the backbone frozen: train_exits() takes one gradient step per
+// head on the mean squared error against the backbone's targets.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::ActivationKind;
+use crate::clock::Clock;
+use crate::error::{NeuralError, NeuralResult};
+use crate::initializer::{InitDistribution, InitScheme, Initializer};
+use crate::linalg;
+use crate::network::NeuralNetwork;
+use crate::rng::Rng;
+use crate::training;
+
+// Weight of the newest timing in each running mean
+const TIMING_SMOOTHING: f64 = 0.2;
+
+#[derive(Debug, Clone)]
+struct ExitHead {
+    // Backbone layer whose output the head reads
+    after: usize,
+    inputs: usize,
+    outputs: usize,
+    // Row-major [outputs][inputs]
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+    activation: ActivationKind,
+    expected_ms: f64,
+}
+
+impl ExitHead {
+    // (pre-activation, output)
+    fn forward(&self, inputs: &[f32], simd: bool) -> NeuralResult<(Vec<f32>, Vec<f32>)> {
+        let mut pre = vec![0.0; self.outputs];
+        linalg::matvec_into(&self.weights, inputs, &mut pre, self.outputs, self.inputs, simd)?;
+        for (value, bias) in pre.iter_mut().zip(&self.biases) {
+            *value += bias;
+        }
+        let mut outputs = pre.clone();
+        self.activation.apply_slice(&mut outputs, simd);
+        Ok((pre, outputs))
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarlyExitOutput {
+    outputs: Vec<f32>,
+    exit: Option<u32>,
+    layers_run: usize,
+    elapsed_ms: f64,
+}
+
+#[wasm_bindgen]
+impl EarlyExitOutput {
+    #[wasm_bindgen(getter)]
+    pub fn outputs(&self) -> Vec<f32> {
+        self.outputs.clone()
+    }
+
+    // Exit that produced the outputs; undefined when the whole backbone ran
+    #[wasm_bindgen(getter)]
+    pub fn exit(&self) -> Option<u32> {
+        self.exit
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn completed(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    // Backbone layers evaluated
+    #[wasm_bindgen(getter)]
+    pub fn layers_run(&self) -> usize {
+        self.layers_run
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn elapsed_ms(&self) -> f64 {
+        self.elapsed_ms
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct EarlyExitNetwork {
+    backbone: NeuralNetwork,
+    // Sorted by the layer they follow
+    exits: Vec<ExitHead>,
+    layer_ms: Vec<f64>,
+    simd_enabled: bool,
+    clock: Clock,
+}
+
+#[wasm_bindgen]
+impl EarlyExitNetwork {
+    // Wrap a copy of `backbone`, which needs at least one layer
+    #[wasm_bindgen(constructor)]
+    pub fn new(backbone: &NeuralNetwork) -> Result<EarlyExitNetwork, NeuralError> {
+        if backbone.layer_count() == 0 {
+            return Err(NeuralError::InvalidConfiguration("backbone has no layers".to_string()));
+        }
+        Ok(EarlyExitNetwork {
+            layer_ms: vec![0.0; backbone.layer_count()],
+            backbone: backbone.clone(),
+            exits: Vec::new(),
+            simd_enabled: crate::check_simd_support(),
+            clock: Clock::new(),
+        })
+    }
+
+    // Copy of the backbone
+    #[wasm_bindgen]
+    pub fn backbone(&self) -> NeuralNetwork {
+        self.backbone.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn exit_count(&self) -> usize {
+        self.exits.len()
+    }
+
+    // Attach an exit reading the output of backbone layer `after_layer`, with
+    // Xavier-uniform weights drawn from `seed`. Returns its number; exits further
+    // along the backbone are renumbered.
+    #[wasm_bindgen]
+    pub fn add_exit(&mut self, after_layer: usize, activation: ActivationKind, seed: u64) -> Result<usize, NeuralError> {
+        let count = self.backbone.layer_count();
+        if after_layer + 1 >= count {
+            return Err(NeuralError::LayerIndexOutOfRange { index: after_layer, count: count - 1 });
+        }
+        if self.exits.iter().any(|exit| exit.after == after_layer) {
+            return Err(NeuralError::InvalidConfiguration(format!("layer {} already has an exit", after_layer)));
+        }
+        let (inputs, outputs) = (self.backbone.layer_size(after_layer)?, self.backbone.output_size());
+        let mut weights = vec![0.0; inputs * outputs];
+        let initializer = Initializer { scheme: InitScheme::Xavier, distribution: InitDistribution::Uniform };
+        initializer.fill(&mut weights, inputs, outputs, &mut Rng::new(seed));
+        let head = ExitHead { after: after_layer, inputs, outputs, weights, biases: vec![0.0; outputs], activation, expected_ms: 0.0 };
+        let position = self.exits.partition_point(|exit| exit.after < after_layer);
+        self.exits.insert(position, head);
+        Ok(position)
+    }
+
+    // Backbone layer an exit reads
+    #[wasm_bindgen]
+    pub fn exit_layer(&self, exit: usize) -> Result<usize, NeuralError> {
+        Ok(self.exit(exit)?.after)
+    }
+
+    // Replace an exit's weights ([outputs][inputs], row-major) and biases
+    #[wasm_bindgen]
+    pub fn set_exit_parameters(&mut self, exit: usize, weights: &[f32], biases: &[f32]) -> Result<(), NeuralError> {
+        let head = self.exit_mut(exit)?;
+        if weights.len() != head.weights.len() {
+            return Err(NeuralError::DimensionMismatch { expected: head.weights.len(), actual: weights.len() });
+        }
+        if biases.len() != head.biases.len() {
+            return Err(NeuralError::DimensionMismatch { expected: head.biases.len(), actual: biases.len() });
+        }
+        head.weights.copy_from_slice(weights);
+        head.biases.copy_from_slice(biases);
+        Ok(())
+    }
+
+    // Outputs of one exit, running the backbone only as far as it
+    #[wasm_bindgen]
+    pub fn forward_exit(&self, inputs: &[f32], exit: usize) -> Result<Vec<f32>, NeuralError> {
+        let head = self.exit(exit)?;
+        let mut activations = self.check_inputs(inputs)?;
+        for layer in 0..=head.after {
+            activations = self.backbone.forward_layer(layer, &activations)?;
+        }
+        let (_, mut outputs) = head.forward(&activations, self.simd_enabled)?;
+        self.backbone.apply_output_mode(&mut outputs);
+        Ok(outputs)
+    }
+
+    // Full backbone prediction if it fits in `budget_ms`, else the deepest exit's
+    #[wasm_bindgen]
+    pub fn forward_with_deadline(&mut self, inputs: &[f32], budget_ms: f64) -> Result<EarlyExitOutput, NeuralError> {
+        if budget_ms.is_nan() || budget_ms <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("time budget must be positive".to_string()));
+        }
+        let started = self.clock.now_ms();
+        let mut activations = self.check_inputs(inputs)?;
+        let mut best: Option<(u32, Vec<f32>)> = None;
+        let mut next_exit = 0;
+        for layer in 0..self.layer_ms.len() {
+            let segment_start = next_exit > 0 && self.exits[next_exit - 1].after + 1 == layer;
+            if let Some((exit, outputs)) = best.as_mut().filter(|_| segment_start) {
+                let elapsed = self.clock.now_ms() - started;
+                if elapsed + self.segment_cost(layer, next_exit) > budget_ms {
+                    self.backbone.apply_output_mode(outputs);
+                    return Ok(EarlyExitOutput { outputs: std::mem::take(outputs), exit: Some(*exit), layers_run: layer, elapsed_ms: elapsed });
+                }
+            }
+
+            let before = self.clock.now_ms();
+            activations = self.backbone.forward_layer(layer, &activations)?;
+            let after = self.clock.now_ms();
+            smooth(&mut self.layer_ms[layer], after - before);
+
+            if let Some(head) = self.exits.get_mut(next_exit).filter(|head| head.after == layer) {
+                let (_, outputs) = head.forward(&activations, self.simd_enabled)?;
+                smooth(&mut head.expected_ms, self.clock.now_ms() - after);
+                best = Some((next_exit as u32, outputs));
+                next_exit += 1;
+            }
+        }
+        self.backbone.apply_output_mode(&mut activations);
+        Ok(EarlyExitOutput { outputs: activations, exit: None, layers_run: self.layer_ms.len(), elapsed_ms: self.clock.now_ms() - started })
+    }
+
+    // One gradient step for every exit on a row-major batch, with the backbone
+    // frozen; returns each exit's mean squared error before the step
+    #[wasm_bindgen]
+    pub fn train_exits(&mut self, inputs: &[f32], targets: &[f32], batch_size: usize, learning_rate: f32) -> Result<Vec<f32>, NeuralError> {
+        if self.exits.is_empty() {
+            return Err(NeuralError::InvalidConfiguration("network has no exits to train".to_string()));
+        }
+        if batch_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        training::check_learning_rate(learning_rate)?;
+        let (input_size, output_size) = (self.backbone.input_size(), self.backbone.output_size());
+        if inputs.len() != batch_size * input_size {
+            return Err(NeuralError::DimensionMismatch { expected: batch_size * input_size, actual: inputs.len() });
+        }
+        if targets.len() != batch_size * output_size {
+            return Err(NeuralError::DimensionMismatch { expected: batch_size * output_size, actual: targets.len() });
+        }
+
+        let mut weight_grads: Vec<Vec<f32>> = self.exits.iter().map(|head| vec![0.0; head.weights.len()]).collect();
+        let mut bias_grads: Vec<Vec<f32>> = self.exits.iter().map(|head| vec![0.0; head.outputs]).collect();
+        let mut losses = vec![0.0f32; self.exits.len()];
+        let last = self.exits.last().map_or(0, |head| head.after);
+        for (sample, target) in inputs.chunks_exact(input_size).zip(targets.chunks_exact(output_size)) {
+            let mut activations = self.check_inputs(sample)?;
+            let mut next_exit = 0;
+            for layer in 0..=last {
+                activations = self.backbone.forward_layer(layer, &activations)?;
+                let Some(head) = self.exits.get(next_exit).filter(|head| head.after == layer) else {
+                    continue;
+                };
+                let (pre, outputs) = head.forward(&activations, self.simd_enabled)?;
+                let mut grad: Vec<f32> = outputs.iter().zip(target).map(|(y, t)| 2.0 * (y - t) / output_size as f32).collect();
+                losses[next_exit] += outputs.iter().zip(target).map(|(y, t)| (y - t) * (y - t)).sum::<f32>() / output_size as f32;
+                head.activation.backprop_slice(&pre, &outputs, &mut grad);
+                for (row, &g) in weight_grads[next_exit].chunks_exact_mut(head.inputs).zip(&grad) {
+                    linalg::axpy(g, &activations, row, self.simd_enabled);
+                }
+                linalg::axpy(1.0, &grad, &mut bias_grads[next_exit], self.simd_enabled);
+                next_exit += 1;
+            }
+        }
+
+        let step = -learning_rate / batch_size as f32;
+        for ((head, weight_grad), bias_grad) in self.exits.iter_mut().zip(&weight_grads).zip(&bias_grads) {
+            linalg::axpy(step, weight_grad, &mut head.weights, self.simd_enabled);
+            linalg::axpy(step, bias_grad, &mut head.biases, self.simd_enabled);
+        }
+        for loss in losses.iter_mut() {
+            *loss /= batch_size as f32;
+        }
+        Ok(losses)
+    }
+
+    // Expected milliseconds for every backbone layer, as used for deadline decisions
+    #[wasm_bindgen]
+    pub fn layer_timings(&self) -> Vec<f64> {
+        self.layer_ms.clone()
+    }
+}
+
+impl EarlyExitNetwork {
+    fn exit(&self, exit: usize) -> NeuralResult<&ExitHead> {
+        self.exits.get(exit).ok_or(NeuralError::IndexOutOfRange { index: exit, len: self.exits.len() })
+    }
+
+    fn exit_mut(&mut self, exit: usize) -> NeuralResult<&mut ExitHead> {
+        let len = self.exits.len();
+        self.exits.get_mut(exit).ok_or(NeuralError::IndexOutOfRange { index: exit, len })
+    }
+
+    // Raw samples through the backbone's preprocessor, if any
+    fn check_inputs(&self, inputs: &[f32]) -> NeuralResult<Vec<f32>> {
+        Ok(self.backbone.prepare_inputs(inputs, 1)?.into_owned())
+    }
+
+    // Expected ms from `layer` through exit `next_exit`'s head, or to the end
+    fn segment_cost(&self, layer: usize, next_exit: usize) -> f64 {
+        match self.exits.get(next_exit) {
+            Some(head) => self.layer_ms[layer..=head.after].iter().sum::<f64>() + head.expected_ms,
+            None => self.layer_ms[layer..].iter().sum(),
+        }
+    }
+}
+
+fn smooth(expected: &mut f64, measured: f64) {
+    if *expected == 0.0 {
+        *expected = measured;
+    } else {
+        *expected += TIMING_SMOOTHING * (measured - *expected);
+    }
+}
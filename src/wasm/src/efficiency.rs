@@ -0,0 +1,158 @@
+// Mesh efficiency: how much information the mesh moves for the energy it spends
+//
+// Neuron activity aᵢ is read as the probability that neuron i spikes in one time
+// bin (clamped to [0, 1]); f is the mean fan-out in synapses per neuron.
+//   energy        spikes · spike_cost + spikes · f · synapse_cost per bin,
+//                 spikes = Σ aᵢ; scored 1 / (1 + energy / n)
+//   throughput    Σ H(aᵢ) bits per bin, H the binary entropy, so silent and
+//                 saturated neurons carry nothing; scored as bits / n
+//   connectivity  for a MeshGraph, the global efficiency
+//                 1/(n(n-1)) Σ 1/dᵢⱼ over ordered pairs (Latora & Marchiori, 2001);
+//                 for bare synapse weights, the fraction with |w| >= active_weight
+// The overall score is the weighted mean of the three component scores, each in
+// [0, 1].
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EfficiencyWeights {
+    pub energy: f32,
+    pub throughput: f32,
+    pub connectivity: f32,
+    // Energy units per spike and per synaptic event
+    pub spike_cost: f32,
+    pub synapse_cost: f32,
+    // Smallest |weight| of a synapse counted as functional
+    pub active_weight: f32,
+}
+
+impl Default for EfficiencyWeights {
+    fn default() -> Self {
+        EfficiencyWeights {
+            energy: 1.0,
+            throughput: 1.0,
+            connectivity: 1.0,
+            spike_cost: 1.0,
+            synapse_cost: 0.1,
+            active_weight: 1e-3,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl EfficiencyWeights {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> EfficiencyWeights {
+        EfficiencyWeights::default()
+    }
+}
+
+impl EfficiencyWeights {
+    pub fn validate(&self) -> NeuralResult<()> {
+        let values = [self.energy, self.throughput, self.connectivity, self.spike_cost, self.synapse_cost, self.active_weight];
+        if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err(NeuralError::InvalidConfiguration("efficiency weights and costs must be finite and non-negative".to_string()));
+        }
+        if self.energy + self.throughput + self.connectivity <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("at least one efficiency component needs a positive weight".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EfficiencyReport {
+    score: f64,
+    energy: f64,
+    energy_score: f64,
+    throughput_bits: f64,
+    throughput_score: f64,
+    connectivity_score: f64,
+}
+
+#[wasm_bindgen]
+impl EfficiencyReport {
+    // Weighted mean of the component scores
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    // Energy spent per time bin
+    #[wasm_bindgen(getter)]
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn energy_score(&self) -> f64 {
+        self.energy_score
+    }
+
+    // Bits carried per time bin
+    #[wasm_bindgen(getter)]
+    pub fn throughput_bits(&self) -> f64 {
+        self.throughput_bits
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn throughput_score(&self) -> f64 {
+        self.throughput_score
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn connectivity_score(&self) -> f64 {
+        self.connectivity_score
+    }
+
+    // {"score", "energy": {"value", "score"}, "throughput": {"bits", "score"}, "connectivity": {"score"}}
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"score\":{},\"energy\":{{\"value\":{},\"score\":{}}},\"throughput\":{{\"bits\":{},\"score\":{}}},\"connectivity\":{{\"score\":{}}}}}",
+            self.score, self.energy, self.energy_score, self.throughput_bits, self.throughput_score, self.connectivity_score
+        )
+    }
+}
+
+// Score `activity` given the mean fan-out and a connectivity score in [0, 1]
+pub(crate) fn evaluate(activity: &[f32], fan_out: f64, connectivity_score: f64, weights: &EfficiencyWeights) -> EfficiencyReport {
+    let neurons = activity.len().max(1) as f64;
+    let (mut spikes, mut bits) = (0.0f64, 0.0f64);
+    for &value in activity {
+        let p = if value.is_finite() { value.clamp(0.0, 1.0) as f64 } else { 0.0 };
+        spikes += p;
+        bits += binary_entropy(p);
+    }
+    let energy = spikes * weights.spike_cost as f64 + spikes * fan_out * weights.synapse_cost as f64;
+    let energy_score = 1.0 / (1.0 + energy / neurons);
+    let throughput_score = bits / neurons;
+    let components = [
+        (weights.energy as f64, energy_score),
+        (weights.throughput as f64, throughput_score),
+        (weights.connectivity as f64, connectivity_score),
+    ];
+    let total_weight: f64 = components.iter().map(|(weight, _)| weight).sum();
+    let score = components.iter().map(|(weight, value)| weight * value).sum::<f64>() / total_weight;
+    EfficiencyReport { score, energy, energy_score, throughput_bits: bits, throughput_score, connectivity_score }
+}
+
+// Fraction of synapses at least `active_weight` strong
+pub(crate) fn functional_fraction(synapse_weights: &[f32], active_weight: f32) -> f64 {
+    if synapse_weights.is_empty() {
+        return 0.0;
+    }
+    let active = synapse_weights.iter().filter(|weight| weight.abs() >= active_weight).count();
+    active as f64 / synapse_weights.len() as f64
+}
+
+fn binary_entropy(p: f64) -> f64 {
+    if p <= 0.0 || p >= 1.0 {
+        return 0.0;
+    }
+    -(p * p.log2() + (1.0 - p) * (1.0 - p).log2())
+}
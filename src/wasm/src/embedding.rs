@@ -0,0 +1,251 @@
+// Embedding tables for categorical inputs
+//
+// An Embedding maps a category to a learned row of `dim` floats. A plain table
+// has one row per id in 0..rows and rejects ids outside it. A hashed table
+// treats its rows as buckets: ids and feature strings (task types, peer ids) are
+// hashed with 32-bit FNV-1a and taken modulo the bucket count, so open-ended
+// vocabularies need no dictionary on the JS side, at the cost of unrelated
+// categories occasionally sharing a row.
+//
+// The table lives in a memory pool of its own, sized to fit it exactly; JS can
+// view it without copying through table_ptr() and wasm_memory(), as with
+// NeuralRuntime.alloc_buffer.
+
+use wasm_bindgen::prelude::*;
+
+use crate::allocator::PoolAllocator;
+use crate::error::{NeuralError, NeuralResult};
+use crate::initializer::{InitDistribution, InitScheme, Initializer};
+use crate::rng::Rng;
+use crate::training::check_learning_rate;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Embedding {
+    rows: usize,
+    dim: usize,
+    hashed: bool,
+    handle: u32,
+    pool: PoolAllocator,
+}
+
+#[wasm_bindgen]
+impl Embedding {
+    // A table with one row per id in 0..rows, drawn Xavier-uniform over `dim` from `seed`
+    #[wasm_bindgen(constructor)]
+    pub fn new(rows: usize, dim: usize, seed: u64) -> Result<Embedding, NeuralError> {
+        Embedding::with_mode(rows, dim, false, seed)
+    }
+
+    // A table of `buckets` rows addressed by hashing ids and feature strings
+    #[wasm_bindgen]
+    pub fn hashed(buckets: usize, dim: usize, seed: u64) -> Result<Embedding, NeuralError> {
+        Embedding::with_mode(buckets, dim, true, seed)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_hashed(&self) -> bool {
+        self.hashed
+    }
+
+    // Row an id maps to: the id itself, or its hash bucket in a hashed table
+    #[wasm_bindgen]
+    pub fn row_of(&self, id: u32) -> Result<usize, NeuralError> {
+        if self.hashed {
+            return Ok(self.bucket(&id.to_le_bytes()));
+        }
+        let row = id as usize;
+        if row >= self.rows {
+            return Err(NeuralError::IndexOutOfRange { index: row, len: self.rows });
+        }
+        Ok(row)
+    }
+
+    // Hash bucket of a feature string; only hashed tables accept features
+    #[wasm_bindgen]
+    pub fn row_of_feature(&self, feature: &str) -> Result<usize, NeuralError> {
+        if !self.hashed {
+            return Err(NeuralError::InvalidConfiguration("feature strings need a hashed embedding".to_string()));
+        }
+        Ok(self.bucket(feature.as_bytes()))
+    }
+
+    // Rows for `ids`, concatenated: [ids.len() × dim]
+    #[wasm_bindgen]
+    pub fn lookup(&self, ids: &[u32]) -> Result<Vec<f32>, NeuralError> {
+        let mut outputs = vec![0.0; ids.len() * self.dim];
+        self.lookup_into(ids, &mut outputs)?;
+        Ok(outputs)
+    }
+
+    // Rows for hashed feature strings, concatenated: [features.len() × dim]
+    #[wasm_bindgen]
+    pub fn lookup_features(&self, features: Vec<String>) -> Result<Vec<f32>, NeuralError> {
+        let rows = features.iter().map(|feature| self.row_of_feature(feature)).collect::<NeuralResult<Vec<_>>>()?;
+        Ok(self.gather(&rows))
+    }
+
+    // Mean of the rows for a bag of features, one `dim` vector for a set of tags
+    #[wasm_bindgen]
+    pub fn lookup_features_mean(&self, features: Vec<String>) -> Result<Vec<f32>, NeuralError> {
+        if features.is_empty() {
+            return Err(NeuralError::InvalidConfiguration("feature bag must hold at least one feature".to_string()));
+        }
+        let rows = self.lookup_features(features)?;
+        let count = rows.len() / self.dim;
+        let mut mean = vec![0.0; self.dim];
+        for row in rows.chunks_exact(self.dim) {
+            for (sum, value) in mean.iter_mut().zip(row) {
+                *sum += value;
+            }
+        }
+        mean.iter_mut().for_each(|value| *value /= count as f32);
+        Ok(mean)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_row(&self, row: usize) -> Result<Vec<f32>, NeuralError> {
+        self.check_row(row)?;
+        Ok(self.table()[row * self.dim..(row + 1) * self.dim].to_vec())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_row(&mut self, row: usize, values: &[f32]) -> Result<(), NeuralError> {
+        self.check_row(row)?;
+        if values.len() != self.dim {
+            return Err(NeuralError::DimensionMismatch { expected: self.dim, actual: values.len() });
+        }
+        check_finite(values)?;
+        let dim = self.dim;
+        self.table_mut()[row * dim..(row + 1) * dim].copy_from_slice(values);
+        Ok(())
+    }
+
+    // SGD step on the rows behind `ids`, with one `dim` gradient per id; an id that
+    // repeats accumulates its gradients
+    #[wasm_bindgen]
+    pub fn apply_gradients(&mut self, ids: &[u32], gradients: &[f32], learning_rate: f32) -> Result<(), NeuralError> {
+        check_learning_rate(learning_rate)?;
+        let expected = ids.len() * self.dim;
+        if gradients.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: gradients.len() });
+        }
+        check_finite(gradients)?;
+        let rows = ids.iter().map(|&id| self.row_of(id)).collect::<NeuralResult<Vec<_>>>()?;
+        let dim = self.dim;
+        let table = self.table_mut();
+        for (row, gradient) in rows.into_iter().zip(gradients.chunks_exact(dim)) {
+            for (weight, g) in table[row * dim..(row + 1) * dim].iter_mut().zip(gradient) {
+                *weight -= learning_rate * g;
+            }
+        }
+        Ok(())
+    }
+
+    // The whole table, row-major [rows × dim]
+    #[wasm_bindgen]
+    pub fn get_parameters(&self) -> Vec<f32> {
+        self.table().to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_parameters(&mut self, parameters: &[f32]) -> Result<(), NeuralError> {
+        let expected = self.rows * self.dim;
+        if parameters.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: parameters.len() });
+        }
+        check_finite(parameters)?;
+        self.table_mut().copy_from_slice(parameters);
+        Ok(())
+    }
+
+    // Byte address of the table inside WASM linear memory; rows * dim floats
+    #[wasm_bindgen]
+    pub fn table_ptr(&self) -> usize {
+        self.table().as_ptr() as usize
+    }
+
+    // Bytes reserved by the table's pool
+    #[wasm_bindgen(getter)]
+    pub fn memory_bytes(&self) -> usize {
+        self.pool.reserved_bytes()
+    }
+}
+
+impl Embedding {
+    fn with_mode(rows: usize, dim: usize, hashed: bool, seed: u64) -> NeuralResult<Embedding> {
+        if rows == 0 || dim == 0 {
+            return Err(NeuralError::InvalidConfiguration("embedding rows and dimension must be non-zero".to_string()));
+        }
+        let len = rows.checked_mul(dim).ok_or_else(|| NeuralError::InvalidConfiguration("embedding table size overflows".to_string()))?;
+        let mut pool = PoolAllocator::with_capacity(&[len]);
+        let handle = pool.allocate(len)?;
+        let initializer = Initializer { scheme: InitScheme::Xavier, distribution: InitDistribution::Uniform };
+        initializer.fill(pool.get_mut(handle)?, dim, dim, &mut Rng::new(seed));
+        Ok(Embedding { rows, dim, hashed, handle, pool })
+    }
+
+    // The handle is issued by our own pool and never freed
+    fn table(&self) -> &[f32] {
+        self.pool.get(self.handle).unwrap_or(&[])
+    }
+
+    fn table_mut(&mut self) -> &mut [f32] {
+        self.pool.get_mut(self.handle).unwrap_or(&mut [])
+    }
+
+    fn bucket(&self, bytes: &[u8]) -> usize {
+        fnv1a(bytes) as usize % self.rows
+    }
+
+    fn check_row(&self, row: usize) -> NeuralResult<()> {
+        if row >= self.rows {
+            return Err(NeuralError::IndexOutOfRange { index: row, len: self.rows });
+        }
+        Ok(())
+    }
+
+    // Rows for `ids` written into `outputs`, which must hold ids.len() × dim floats
+    pub(crate) fn lookup_into(&self, ids: &[u32], outputs: &mut [f32]) -> NeuralResult<()> {
+        let expected = ids.len() * self.dim;
+        if outputs.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: outputs.len() });
+        }
+        let table = self.table();
+        for (&id, output) in ids.iter().zip(outputs.chunks_exact_mut(self.dim)) {
+            let row = self.row_of(id)?;
+            output.copy_from_slice(&table[row * self.dim..(row + 1) * self.dim]);
+        }
+        Ok(())
+    }
+
+    fn gather(&self, rows: &[usize]) -> Vec<f32> {
+        let table = self.table();
+        let mut outputs = Vec::with_capacity(rows.len() * self.dim);
+        for &row in rows {
+            outputs.extend_from_slice(&table[row * self.dim..(row + 1) * self.dim]);
+        }
+        outputs
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5, |hash: u32, &byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+fn check_finite(values: &[f32]) -> NeuralResult<()> {
+    if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+        return Err(NeuralError::NonFiniteInput { index });
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// Encrypted weight blobs (AES-256-GCM)
+//
+// NeuralNetwork.export_weights_encrypted seals a SASW weight blob under a 256-bit
+// key so models kept in browser storage or synced to coordination servers are not
+// plaintext; import_weights_encrypted opens it again. Encryption uses the
+// RustCrypto aes-gcm crate with a fresh random 96-bit nonce per blob from the
+// platform CSPRNG. The header is authenticated along with the ciphertext, so a
+// wrong key, a modified byte anywhere or a swapped header all fail the same way,
+// with NeuralError::IntegrityViolation, and nothing is decoded.
+//
+// The key is raw bytes; the host generates or derives it (e.g. WebCrypto
+// generateKey or HKDF/PBKDF2 from a secret) and keeps it out of the storage the
+// blob goes to.
+//
+// Layout:
+//   magic "SASE", version u16 (little-endian), reserved u16, nonce [u8; 12],
+//   ciphertext (same length as the SASW blob), tag [u8; 16]
+// The first 8 bytes (magic, version, reserved) are the associated data.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::fill_secure;
+use crate::serialization::{ByteReader, ByteWriter};
+
+pub const ENCRYPTED_MAGIC: &[u8; 4] = b"SASE";
+pub const ENCRYPTED_VERSION: u16 = 1;
+pub const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = 8;
+
+fn cipher(key: &[u8]) -> NeuralResult<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(key)
+        .map_err(|_| NeuralError::InvalidConfiguration(format!("encryption key must be {} bytes, got {}", KEY_LEN, key.len())))
+}
+
+pub(crate) fn seal(plaintext: &[u8], key: &[u8]) -> NeuralResult<Vec<u8>> {
+    let cipher = cipher(key)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    fill_secure(&mut nonce)?;
+
+    let mut writer = ByteWriter::new();
+    writer.bytes(ENCRYPTED_MAGIC);
+    writer.u16(ENCRYPTED_VERSION);
+    writer.u16(0);
+    let header = writer.finish();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &header })
+        .map_err(|_| NeuralError::InvalidConfiguration("blob too large to encrypt".to_string()))?;
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&header);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+pub(crate) fn open(sealed: &[u8], key: &[u8]) -> NeuralResult<Vec<u8>> {
+    let cipher = cipher(key)?;
+    let mut reader = ByteReader::new(sealed);
+    if reader.bytes(4)? != ENCRYPTED_MAGIC {
+        return Err(NeuralError::InvalidFormat("missing SASE header".to_string()));
+    }
+    let version = reader.u16()?;
+    if version != ENCRYPTED_VERSION {
+        return Err(NeuralError::InvalidFormat(format!("unsupported encrypted blob version {}", version)));
+    }
+    reader.u16()?;
+    let nonce = reader.bytes(NONCE_LEN)?;
+    if sealed.len() < HEADER_LEN + NONCE_LEN + TAG_LEN {
+        return Err(NeuralError::InvalidFormat("encrypted blob is truncated".to_string()));
+    }
+    let ciphertext = &sealed[HEADER_LEN + NONCE_LEN..];
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &sealed[..HEADER_LEN] })
+        .map_err(|_| NeuralError::IntegrityViolation("encrypted blob failed authentication (wrong key or modified data)".to_string()))
+}
@@ -0,0 +1,111 @@
+// Error types for the neural runtime
+// Errors are returned as Result values and surface in JavaScript as thrown exceptions,
+// so invalid input no longer aborts the whole WASM instance.
+
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+use crate::logging::{log_event, LogLevel};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NeuralError {
+    // Input slice longer than the security limit
+    InputTooLarge { len: usize, max: usize },
+    // NaN or Infinity found in the input
+    NonFiniteInput { index: usize },
+    // Finite value outside the range accepted by the security policy
+    ValueOutOfBounds { index: usize, value: f32, min: f32, max: f32 },
+    // Buffer length does not match the shape it is used with
+    DimensionMismatch { expected: usize, actual: usize },
+    // Layer index past the end of the network
+    LayerIndexOutOfRange { index: usize, count: usize },
+    // Element index (neuron, synapse, ...) past the end of its collection
+    IndexOutOfRange { index: usize, len: usize },
+    // Structurally invalid configuration (zero-sized layer, etc.)
+    InvalidConfiguration(String),
+    // Buffer handle that was never allocated or has been freed
+    InvalidHandle(u32),
+    // Serialized data is malformed, truncated or from an unknown version
+    InvalidFormat(String),
+    // Platform facility (e.g. performance timer) not available
+    Unavailable(String),
+    // Training step rolled back because its loss, gradients or parameters (at
+    // `layer`, if any) went NaN or infinite
+    NonFiniteTraining { stage: String, layer: Option<usize> },
+    // Long-running task stopped through its cancellation token
+    Cancelled,
+    // Work that could not start or finish within its deadline
+    DeadlineExceeded { deadline_ms: f64, elapsed_ms: f64 },
+    // Allocation refused because it would take usage past a configured limit, or
+    // because the host could not provide the memory (limit 0)
+    MemoryLimitExceeded { requested: usize, in_use: usize, limit: usize },
+    // Serialized data failed its checksum, digest or signature check and was not loaded
+    IntegrityViolation(String),
+    // A differentially private release refused because it would take ε past the budget
+    PrivacyBudgetExhausted { epsilon: f64, limit: f64 },
+    // A kernel failed a checked-mode check (see set_checked_mode)
+    KernelCheckFailed { kernel: String, reason: String },
+}
+
+impl fmt::Display for NeuralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeuralError::InputTooLarge { len, max } => {
+                write!(f, "Input size {} exceeds security limit of {} elements", len, max)
+            }
+            NeuralError::NonFiniteInput { index } => {
+                write!(f, "Invalid input value detected at index {}: NaN or Infinity", index)
+            }
+            NeuralError::ValueOutOfBounds { index, value, min, max } => {
+                write!(f, "Input value {} at index {} is outside the security policy bounds [{}, {}]", value, index, min, max)
+            }
+            NeuralError::DimensionMismatch { expected, actual } => {
+                write!(f, "Dimension mismatch: expected {} elements, got {}", expected, actual)
+            }
+            NeuralError::LayerIndexOutOfRange { index, count } => {
+                write!(f, "Layer index {} out of range for network with {} layers", index, count)
+            }
+            NeuralError::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} out of range for collection of length {}", index, len)
+            }
+            NeuralError::InvalidConfiguration(reason) => write!(f, "Invalid configuration: {}", reason),
+            NeuralError::InvalidHandle(handle) => write!(f, "Invalid buffer handle {}", handle),
+            NeuralError::InvalidFormat(reason) => write!(f, "Invalid serialized data: {}", reason),
+            NeuralError::Unavailable(what) => write!(f, "{} is not available", what),
+            NeuralError::NonFiniteTraining { stage, layer: Some(layer) } => {
+                write!(f, "Non-finite {} at layer {} during training; the step was rolled back", stage, layer)
+            }
+            NeuralError::NonFiniteTraining { stage, layer: None } => {
+                write!(f, "Non-finite {} during training; the step was rolled back", stage)
+            }
+            NeuralError::Cancelled => write!(f, "Operation cancelled"),
+            NeuralError::DeadlineExceeded { deadline_ms, elapsed_ms } => {
+                write!(f, "Deadline of {} ms exceeded after {} ms", deadline_ms, elapsed_ms)
+            }
+            NeuralError::MemoryLimitExceeded { requested, in_use, limit } => write!(
+                f,
+                "Memory limit exceeded: {} bytes requested with {} in use, limit {}",
+                requested, in_use, limit
+            ),
+            NeuralError::IntegrityViolation(reason) => write!(f, "Integrity check failed: {}", reason),
+            NeuralError::PrivacyBudgetExhausted { epsilon, limit } => {
+                write!(f, "Privacy budget exhausted: the release would reach epsilon {} past the limit of {}", epsilon, limit)
+            }
+            NeuralError::KernelCheckFailed { kernel, reason } => write!(f, "Checked {} kernel failed: {}", kernel, reason),
+        }
+    }
+}
+
+impl std::error::Error for NeuralError {}
+
+// wasm-bindgen converts Err values into thrown JS exceptions through this impl
+impl From<NeuralError> for JsValue {
+    fn from(err: NeuralError) -> JsValue {
+        // Cancellation is requested by the caller, so it is not a failure worth flagging
+        let level = if matches!(err, NeuralError::Cancelled) { LogLevel::Info } else { LogLevel::Error };
+        log_event!(level, "error", "{}", err);
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+pub type NeuralResult<T> = Result<T, NeuralError>;
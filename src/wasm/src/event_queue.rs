@@ -0,0 +1,81 @@
+// Time-ordered event queue for spike propagation
+//
+// A binary min-heap keyed by event time (ms). Events due at the same time come
+// out in the order they were pushed, so simulations are deterministic. Push and
+// pop are O(log n), so delivering a spike no longer costs a scan of everything in
+// flight.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<T> {
+    time_ms: f32,
+    sequence: u64,
+    event: T,
+}
+
+// Reversed so BinaryHeap, a max-heap, yields the earliest entry first
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.time_ms.total_cmp(&self.time_ms).then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EventQueue<T> {
+    heap: BinaryHeap<Entry<T>>,
+    sequence: u64,
+}
+
+impl<T> EventQueue<T> {
+    pub(crate) fn new() -> EventQueue<T> {
+        EventQueue { heap: BinaryHeap::new(), sequence: 0 }
+    }
+
+    pub(crate) fn push(&mut self, time_ms: f32, event: T) {
+        self.heap.push(Entry { time_ms, sequence: self.sequence, event });
+        self.sequence += 1;
+    }
+
+    // Time of the earliest event
+    pub(crate) fn next_time(&self) -> Option<f32> {
+        self.heap.peek().map(|entry| entry.time_ms)
+    }
+
+    // Remove the earliest event if it is due at or before `now`
+    pub(crate) fn pop_due(&mut self, now: f32) -> Option<(f32, T)> {
+        if self.next_time()? > now {
+            return None;
+        }
+        self.heap.pop().map(|entry| (entry.time_ms, entry.event))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    // Bytes reserved by the heap
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.heap.capacity() * std::mem::size_of::<Entry<T>>()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.heap.clear();
+        self.sequence = 0;
+    }
+}
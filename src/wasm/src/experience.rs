@@ -0,0 +1,315 @@
+// Experience replay with prioritized sampling (Schaul et al., 2016)
+//
+// Transitions (s, a, r, s', done) live in WASM memory in a ring buffer whose
+// storage is allocated once, so a full buffer overwrites its oldest entry and
+// never grows. Transition i is sampled with probability
+//   P(i) = pᵢ^α / Σ pⱼ^α,  pᵢ = |δᵢ| + ε
+// where δᵢ is its latest TD error; α = 0 samples uniformly. New transitions get the
+// largest priority seen so far, so each is replayed at least once before its TD
+// error is known. A batch is drawn stratified: the priority mass is cut into
+// batch_size equal segments and one transition is taken from each. Importance
+// weights wᵢ = (N·P(i))^-β, divided by the batch's largest, undo the bias that
+// prioritization introduces.
+//
+// Priorities sit in a sum tree, so pushing, sampling and updating are O(log N).
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::NeuralError;
+use crate::rng::Rng;
+
+// Keeps transitions with zero TD error sampleable
+const PRIORITY_EPSILON: f32 = 1e-6;
+
+// Binary tree over the leaf priorities; every node holds the sum of its children
+#[derive(Debug, Clone)]
+struct SumTree {
+    // Leaf count, a power of two; node 1 is the root and leaves start at `leaves`
+    leaves: usize,
+    nodes: Vec<f64>,
+}
+
+impl SumTree {
+    fn new(capacity: usize) -> SumTree {
+        let leaves = capacity.next_power_of_two();
+        SumTree { leaves, nodes: vec![0.0; 2 * leaves] }
+    }
+
+    fn total(&self) -> f64 {
+        self.nodes[1]
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        self.nodes[self.leaves + index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let mut node = self.leaves + index;
+        self.nodes[node] = value;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.nodes[2 * node] + self.nodes[2 * node + 1];
+        }
+    }
+
+    // Leaf whose cumulative range holds `mass`; never an empty leaf while total > 0
+    fn find(&self, mut mass: f64) -> usize {
+        let mut node = 1;
+        while node < self.leaves {
+            let left = 2 * node;
+            if mass >= self.nodes[left] && self.nodes[left + 1] > 0.0 {
+                mass -= self.nodes[left];
+                node = left + 1;
+            } else {
+                node = left;
+            }
+        }
+        node - self.leaves
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ExperienceReplay {
+    capacity: usize,
+    state_size: usize,
+    states: Vec<f32>,
+    next_states: Vec<f32>,
+    actions: Vec<u32>,
+    rewards: Vec<f32>,
+    done: Vec<bool>,
+    len: usize,
+    // Slot the next transition is written to
+    next: usize,
+    alpha: f32,
+    // Largest pᵢ seen, before the α exponent
+    max_priority: f32,
+    priorities: SumTree,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl ExperienceReplay {
+    // Room for `capacity` transitions of `state_size` floats, allocated up front
+    #[wasm_bindgen(constructor)]
+    pub fn new(capacity: usize, state_size: usize, alpha: f32, seed: u64) -> Result<ExperienceReplay, NeuralError> {
+        if capacity == 0 || state_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("replay capacity and state size must be non-zero".to_string()));
+        }
+        if !alpha.is_finite() || alpha < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("priority alpha must be finite and non-negative".to_string()));
+        }
+        let floats = capacity
+            .checked_mul(state_size)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("replay buffer is too large".to_string()))?;
+        Ok(ExperienceReplay {
+            capacity,
+            state_size,
+            states: vec![0.0; floats],
+            next_states: vec![0.0; floats],
+            actions: vec![0; capacity],
+            rewards: vec![0.0; capacity],
+            done: vec![false; capacity],
+            len: 0,
+            next: 0,
+            alpha,
+            max_priority: 1.0,
+            priorities: SumTree::new(capacity),
+            rng: Rng::new(seed),
+        })
+    }
+
+    // Store a transition at the highest priority so far; returns its slot
+    #[wasm_bindgen]
+    pub fn push(&mut self, state: &[f32], action: u32, reward: f32, next_state: &[f32], done: bool) -> Result<u32, NeuralError> {
+        for observed in [state, next_state] {
+            if observed.len() != self.state_size {
+                return Err(NeuralError::DimensionMismatch { expected: self.state_size, actual: observed.len() });
+            }
+            if let Some(index) = observed.iter().position(|x| !x.is_finite()) {
+                return Err(NeuralError::NonFiniteInput { index });
+            }
+        }
+        if !reward.is_finite() {
+            return Err(NeuralError::InvalidConfiguration("reward must be finite".to_string()));
+        }
+        let slot = self.next;
+        let range = slot * self.state_size..(slot + 1) * self.state_size;
+        self.states[range.clone()].copy_from_slice(state);
+        self.next_states[range].copy_from_slice(next_state);
+        self.actions[slot] = action;
+        self.rewards[slot] = reward;
+        self.done[slot] = done;
+        self.priorities.set(slot, self.scaled(self.max_priority));
+        self.next = (slot + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+        Ok(slot as u32)
+    }
+
+    // Draw `batch_size` transitions (with replacement) by priority, weighting them
+    // with exponent `beta` (1 fully corrects the sampling bias)
+    #[wasm_bindgen]
+    pub fn sample(&mut self, batch_size: usize, beta: f32) -> Result<ReplayBatch, NeuralError> {
+        if batch_size == 0 || self.len == 0 {
+            return Err(NeuralError::InvalidConfiguration("sampling needs a non-empty buffer and batch".to_string()));
+        }
+        if !(0.0..=1.0).contains(&beta) {
+            return Err(NeuralError::InvalidConfiguration("importance beta must lie in [0, 1]".to_string()));
+        }
+        let total = self.priorities.total();
+        let segment = total / batch_size as f64;
+        let mut batch = ReplayBatch::with_capacity(batch_size, self.state_size);
+        for stratum in 0..batch_size {
+            let mass = segment * (stratum as f64 + self.rng.next_f64());
+            let slot = self.priorities.find(mass.min(total));
+            let probability = self.priorities.get(slot) / total;
+            batch.weights.push((self.len as f64 * probability).powf(-beta as f64) as f32);
+            batch.slots.push(slot as u32);
+            batch.states.extend_from_slice(self.state(slot));
+            batch.next_states.extend_from_slice(self.next_state(slot));
+            batch.actions.push(self.actions[slot]);
+            batch.rewards.push(self.rewards[slot]);
+            batch.done.push(self.done[slot] as u8);
+        }
+        let largest = batch.weights.iter().copied().fold(0.0, f32::max);
+        for weight in batch.weights.iter_mut() {
+            *weight /= largest;
+        }
+        Ok(batch)
+    }
+
+    // Set the priorities of sampled slots from their new TD errors
+    #[wasm_bindgen]
+    pub fn update_priorities(&mut self, slots: &[u32], td_errors: &[f32]) -> Result<(), NeuralError> {
+        if slots.len() != td_errors.len() {
+            return Err(NeuralError::DimensionMismatch { expected: slots.len(), actual: td_errors.len() });
+        }
+        if let Some(index) = td_errors.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        if let Some(&slot) = slots.iter().find(|&&slot| slot as usize >= self.len) {
+            return Err(NeuralError::IndexOutOfRange { index: slot as usize, len: self.len });
+        }
+        for (&slot, error) in slots.iter().zip(td_errors) {
+            let priority = error.abs() + PRIORITY_EPSILON;
+            self.max_priority = self.max_priority.max(priority);
+            self.priorities.set(slot as usize, self.scaled(priority));
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Bytes held by transitions and priorities, fixed at construction
+    #[wasm_bindgen]
+    pub fn memory_bytes(&self) -> usize {
+        (self.states.len() + self.next_states.len() + self.rewards.len()) * 4
+            + self.actions.len() * 4
+            + self.done.len()
+            + self.priorities.nodes.len() * 8
+    }
+
+    // Forget every transition; storage is kept
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+        self.max_priority = 1.0;
+        self.priorities = SumTree::new(self.capacity);
+    }
+}
+
+impl ExperienceReplay {
+    fn scaled(&self, priority: f32) -> f64 {
+        (priority as f64).powf(self.alpha as f64)
+    }
+
+    fn state(&self, slot: usize) -> &[f32] {
+        &self.states[slot * self.state_size..(slot + 1) * self.state_size]
+    }
+
+    fn next_state(&self, slot: usize) -> &[f32] {
+        &self.next_states[slot * self.state_size..(slot + 1) * self.state_size]
+    }
+}
+
+// Transitions drawn by ExperienceReplay.sample, row-major
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBatch {
+    pub(crate) slots: Vec<u32>,
+    pub(crate) states: Vec<f32>,
+    pub(crate) actions: Vec<u32>,
+    pub(crate) rewards: Vec<f32>,
+    pub(crate) next_states: Vec<f32>,
+    pub(crate) done: Vec<u8>,
+    pub(crate) weights: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl ReplayBatch {
+    // Buffer slots, for update_priorities
+    #[wasm_bindgen(getter)]
+    pub fn slots(&self) -> Vec<u32> {
+        self.slots.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn states(&self) -> Vec<f32> {
+        self.states.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn actions(&self) -> Vec<u32> {
+        self.actions.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rewards(&self) -> Vec<f32> {
+        self.rewards.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn next_states(&self) -> Vec<f32> {
+        self.next_states.clone()
+    }
+
+    // 1 where the episode ended
+    #[wasm_bindgen(getter)]
+    pub fn done(&self) -> Vec<u8> {
+        self.done.clone()
+    }
+
+    // Importance-sampling weights, the largest being 1
+    #[wasm_bindgen(getter)]
+    pub fn weights(&self) -> Vec<f32> {
+        self.weights.clone()
+    }
+}
+
+impl ReplayBatch {
+    fn with_capacity(batch_size: usize, state_size: usize) -> ReplayBatch {
+        ReplayBatch {
+            slots: Vec::with_capacity(batch_size),
+            states: Vec::with_capacity(batch_size * state_size),
+            actions: Vec::with_capacity(batch_size),
+            rewards: Vec::with_capacity(batch_size),
+            next_states: Vec::with_capacity(batch_size * state_size),
+            done: Vec::with_capacity(batch_size),
+            weights: Vec::with_capacity(batch_size),
+        }
+    }
+}
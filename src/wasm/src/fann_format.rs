@@ -0,0 +1,357 @@
+// FANN `.net` text format compatibility (FANN_FLO_2.1)
+//
+// FANN gives every layer a trailing bias neuron and stores one connection list per
+// neuron, with per-neuron activation steepness. On import the steepness is folded
+// into each neuron's weights, so the dense layer computes the same function; on
+// export the inverse steepness is written. Every neuron in a layer must share one
+// activation function, and shortcut networks or scaled inputs are rejected.
+
+use std::collections::HashMap;
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::{Layer, NeuralNetwork};
+
+const FANN_HEADER: &str = "FANN_FLO_2.1";
+
+// FANN activation function identifiers (fann_activationfunc_enum)
+const FANN_LINEAR: u32 = 0;
+const FANN_SIGMOID: u32 = 3;
+const FANN_SIGMOID_SYMMETRIC: u32 = 5;
+const FANN_LINEAR_PIECE_RECT: u32 = 17;
+const FANN_LINEAR_PIECE_RECT_LEAKY: u32 = 18;
+
+const NEURONS_KEY: &str = "neurons (num_inputs, activation_function, activation_steepness)";
+const CONNECTIONS_KEY: &str = "connections (connected_to_neuron, weight)";
+
+// Training parameters FANN expects in the header; written with FANN's defaults
+const DEFAULT_HEADER_FIELDS: &[(&str, &str)] = &[
+    ("learning_rate", "0.700000"),
+    ("connection_rate", "1.000000"),
+    ("network_type", "0"),
+    ("learning_momentum", "0.000000"),
+    ("training_algorithm", "2"),
+    ("train_error_function", "1"),
+    ("train_stop_function", "0"),
+    ("cascade_output_change_fraction", "0.010000"),
+    ("quickprop_decay", "-0.000100"),
+    ("quickprop_mu", "1.750000"),
+    ("rprop_increase_factor", "1.200000"),
+    ("rprop_decrease_factor", "0.500000"),
+    ("rprop_delta_min", "0.000000"),
+    ("rprop_delta_max", "50.000000"),
+    ("rprop_delta_zero", "0.100000"),
+    ("cascade_output_stagnation_epochs", "12"),
+    ("cascade_candidate_change_fraction", "0.010000"),
+    ("cascade_candidate_stagnation_epochs", "12"),
+    ("cascade_max_out_epochs", "150"),
+    ("cascade_min_out_epochs", "50"),
+    ("cascade_max_cand_epochs", "150"),
+    ("cascade_min_cand_epochs", "50"),
+    ("cascade_num_candidate_groups", "2"),
+    ("bit_fail_limit", "3.49999994039535522461e-01"),
+    ("cascade_candidate_limit", "1.00000000000000000000e+03"),
+    ("cascade_weight_multiplier", "4.00000000000000022204e-01"),
+    ("cascade_activation_functions_count", "10"),
+    ("cascade_activation_functions", "3 5 7 8 10 11 14 15 16 17 "),
+    ("cascade_activation_steepnesses_count", "4"),
+    ("cascade_activation_steepnesses", "2.50000000000000000000e-01 5.00000000000000000000e-01 7.50000000000000000000e-01 1.00000000000000000000e+00 "),
+];
+
+#[derive(Debug, Clone, Copy)]
+struct FannNeuron {
+    num_inputs: usize,
+    activation: u32,
+    steepness: f32,
+}
+
+// Map a FANN activation to the runtime's, plus the factor folded into the weights
+fn from_fann_activation(function: u32, steepness: f32) -> NeuralResult<(ActivationKind, f32)> {
+    match function {
+        FANN_LINEAR => Ok((ActivationKind::Linear, steepness)),
+        // FANN sigmoid is 1 / (1 + e^(-2·s·x))
+        FANN_SIGMOID => Ok((ActivationKind::Sigmoid, 2.0 * steepness)),
+        FANN_SIGMOID_SYMMETRIC => Ok((ActivationKind::Tanh, steepness)),
+        FANN_LINEAR_PIECE_RECT => Ok((ActivationKind::ReLU, steepness)),
+        FANN_LINEAR_PIECE_RECT_LEAKY => Ok((ActivationKind::LeakyReLU, steepness)),
+        other => Err(NeuralError::InvalidFormat(format!("unsupported FANN activation function {}", other))),
+    }
+}
+
+// Inverse of from_fann_activation for weights exported with factor 1
+fn to_fann_activation(kind: ActivationKind) -> NeuralResult<(u32, f32)> {
+    match kind {
+        ActivationKind::Linear => Ok((FANN_LINEAR, 1.0)),
+        ActivationKind::Sigmoid => Ok((FANN_SIGMOID, 0.5)),
+        ActivationKind::Tanh => Ok((FANN_SIGMOID_SYMMETRIC, 1.0)),
+        ActivationKind::ReLU => Ok((FANN_LINEAR_PIECE_RECT, 1.0)),
+        ActivationKind::LeakyReLU => Ok((FANN_LINEAR_PIECE_RECT_LEAKY, 1.0)),
+        other => Err(NeuralError::InvalidConfiguration(format!("{:?} has no FANN equivalent", other))),
+    }
+}
+
+pub fn parse_fann(text: &str) -> NeuralResult<NeuralNetwork> {
+    let mut lines = text.lines();
+    let header = lines.next().map(str::trim).unwrap_or_default();
+    if header != FANN_HEADER {
+        return Err(NeuralError::InvalidFormat(format!("expected {} header, found '{}'", FANN_HEADER, header)));
+    }
+
+    let fields: HashMap<&str, &str> = lines
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+    let field = |key: &str| {
+        fields
+            .get(key)
+            .copied()
+            .ok_or_else(|| NeuralError::InvalidFormat(format!("missing FANN field '{}'", key)))
+    };
+
+    if parse_number::<u32>(field("network_type")?)? != 0 {
+        return Err(NeuralError::InvalidFormat("shortcut FANN networks are not supported".to_string()));
+    }
+    if fields.get("scale_included").is_some_and(|value| value.trim() != "0") {
+        return Err(NeuralError::InvalidFormat("FANN networks with scaling parameters are not supported".to_string()));
+    }
+
+    let layer_sizes = field("layer_sizes")?
+        .split_whitespace()
+        .map(parse_number::<usize>)
+        .collect::<NeuralResult<Vec<_>>>()?;
+    let num_layers: usize = parse_number(field("num_layers")?)?;
+    if layer_sizes.len() != num_layers || num_layers < 2 || layer_sizes.iter().any(|&size| size < 2) {
+        return Err(NeuralError::InvalidFormat("layer_sizes does not describe a valid network".to_string()));
+    }
+
+    let neurons = parse_tuples(field(NEURONS_KEY)?)?
+        .iter()
+        .map(|tuple| match tuple.as_slice() {
+            [inputs, function, steepness] => Ok(FannNeuron {
+                num_inputs: parse_number(inputs)?,
+                activation: parse_number(function)?,
+                steepness: parse_number(steepness)?,
+            }),
+            _ => Err(NeuralError::InvalidFormat("neuron entries need three fields".to_string())),
+        })
+        .collect::<NeuralResult<Vec<_>>>()?;
+    let connections = parse_tuples(field(CONNECTIONS_KEY)?)?
+        .iter()
+        .map(|tuple| match tuple.as_slice() {
+            [target, weight] => Ok((parse_number::<usize>(target)?, parse_number::<f32>(weight)?)),
+            _ => Err(NeuralError::InvalidFormat("connection entries need two fields".to_string())),
+        })
+        .collect::<NeuralResult<Vec<_>>>()?;
+
+    let total_neurons: usize = layer_sizes.iter().sum();
+    if neurons.len() != total_neurons {
+        return Err(NeuralError::InvalidFormat(format!("expected {} neurons, found {}", total_neurons, neurons.len())));
+    }
+
+    let mut network = NeuralNetwork::new(layer_sizes[0] - 1)?;
+    let mut layer_start = layer_sizes[0];
+    let mut prev_start = 0;
+    let mut connection_index = 0;
+
+    for window in layer_sizes.windows(2) {
+        let (prev_size, size) = (window[0], window[1]);
+        let inputs = prev_size - 1;
+        let outputs = size - 1;
+        let bias_neuron = prev_start + inputs;
+
+        let mut layer_kind = None;
+        let mut weights = vec![0.0; inputs * outputs];
+        let mut biases = vec![0.0; outputs];
+
+        for (row, neuron) in neurons[layer_start..layer_start + outputs].iter().enumerate() {
+            let (kind, factor) = from_fann_activation(neuron.activation, neuron.steepness)?;
+            if *layer_kind.get_or_insert(kind) != kind {
+                return Err(NeuralError::InvalidFormat("mixed activation functions within a layer".to_string()));
+            }
+
+            let end = connection_index + neuron.num_inputs;
+            let incoming = connections
+                .get(connection_index..end)
+                .ok_or_else(|| NeuralError::InvalidFormat("connection list is truncated".to_string()))?;
+            for &(source, weight) in incoming {
+                if source == bias_neuron {
+                    biases[row] = weight * factor;
+                } else if (prev_start..bias_neuron).contains(&source) {
+                    weights[row * inputs + (source - prev_start)] = weight * factor;
+                } else {
+                    return Err(NeuralError::InvalidFormat(format!("connection from neuron {} skips a layer", source)));
+                }
+            }
+            connection_index = end;
+        }
+
+        let activation = layer_kind.unwrap_or(ActivationKind::Linear);
+        network.add_layer(outputs, activation)?;
+        let layer = network.layer_count() - 1;
+        network.set_weights(layer, &weights)?;
+        network.set_biases(layer, &biases)?;
+
+        prev_start = layer_start;
+        layer_start += size;
+    }
+
+    if connection_index != connections.len() {
+        return Err(NeuralError::InvalidFormat("unused trailing connections".to_string()));
+    }
+
+    Ok(network)
+}
+
+pub fn write_fann(input_size: usize, layers: &[Layer]) -> NeuralResult<String> {
+    // FANN networks have no recurrent connections
+    let layers = layers
+        .iter()
+        .map(|layer| {
+            layer
+                .as_dense()
+                .ok_or_else(|| NeuralError::InvalidConfiguration("FANN format only supports dense layers".to_string()))
+        })
+        .collect::<NeuralResult<Vec<_>>>()?;
+    let activations = layers
+        .iter()
+        .map(|layer| to_fann_activation(layer.activation))
+        .collect::<NeuralResult<Vec<_>>>()?;
+
+    let layer_sizes: Vec<usize> = std::iter::once(input_size)
+        .chain(layers.iter().map(|layer| layer.outputs))
+        .map(|size| size + 1)
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(FANN_HEADER);
+    out.push('\n');
+    out.push_str(&format!("num_layers={}\n", layer_sizes.len()));
+    for (key, value) in DEFAULT_HEADER_FIELDS {
+        out.push_str(&format!("{}={}\n", key, value));
+    }
+    out.push_str("layer_sizes=");
+    for size in &layer_sizes {
+        out.push_str(&format!("{} ", size));
+    }
+    out.push_str("\nscale_included=0\n");
+
+    // Input layer and every bias neuron have no inputs
+    out.push_str(NEURONS_KEY);
+    out.push('=');
+    for _ in 0..layer_sizes[0] {
+        out.push_str(&format!("(0, 0, {:.20e}) ", 0.0));
+    }
+    for (layer, (function, steepness)) in layers.iter().zip(&activations) {
+        for _ in 0..layer.outputs {
+            out.push_str(&format!("({}, {}, {:.20e}) ", layer.inputs + 1, function, steepness));
+        }
+        out.push_str(&format!("(0, {}, {:.20e}) ", function, 0.0));
+    }
+
+    out.push('\n');
+    out.push_str(CONNECTIONS_KEY);
+    out.push('=');
+    let mut prev_start = 0;
+    for layer in &layers {
+        let bias_neuron = prev_start + layer.inputs;
+        let weights = layer.effective_weights(false);
+        for (row, bias) in weights.chunks_exact(layer.inputs).zip(&layer.biases) {
+            for (column, weight) in row.iter().enumerate() {
+                out.push_str(&format!("({}, {:.20e}) ", prev_start + column, weight));
+            }
+            out.push_str(&format!("({}, {:.20e}) ", bias_neuron, bias));
+        }
+        prev_start += layer.inputs + 1;
+    }
+    out.push('\n');
+
+    Ok(out)
+}
+
+// "(a, b, c) (d, e, f)" -> [["a", "b", "c"], ["d", "e", "f"]]
+fn parse_tuples(value: &str) -> NeuralResult<Vec<Vec<&str>>> {
+    value
+        .split(')')
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| {
+            chunk
+                .strip_prefix('(')
+                .map(|inner| inner.split(',').map(str::trim).collect())
+                .ok_or_else(|| NeuralError::InvalidFormat(format!("malformed FANN tuple '{}'", chunk)))
+        })
+        .collect()
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> NeuralResult<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| NeuralError::InvalidFormat(format!("invalid number '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_network() -> NeuralNetwork {
+        let mut network = NeuralNetwork::new(2).unwrap();
+        network.add_layer(3, ActivationKind::Tanh).unwrap();
+        network.add_layer(1, ActivationKind::Sigmoid).unwrap();
+        network.set_weights(0, &[0.5, -0.25, 1.0, 0.75, -1.5, 0.125]).unwrap();
+        network.set_biases(0, &[0.1, -0.2, 0.3]).unwrap();
+        network.set_weights(1, &[1.25, -0.5, 0.25]).unwrap();
+        network.set_biases(1, &[-0.05]).unwrap();
+        network
+    }
+
+    #[test]
+    fn round_trip_preserves_parameters() {
+        let network = sample_network();
+        let text = network.to_fann().unwrap();
+        let restored = NeuralNetwork::from_fann(&text).unwrap();
+
+        assert_eq!(restored.layer_count(), 2);
+        for layer in 0..2 {
+            assert_eq!(restored.layer_activation(layer).unwrap(), network.layer_activation(layer).unwrap());
+            assert_eq!(restored.get_weights(layer).unwrap(), network.get_weights(layer).unwrap());
+            assert_eq!(restored.get_biases(layer).unwrap(), network.get_biases(layer).unwrap());
+        }
+    }
+
+    #[test]
+    fn writer_uses_fann_layout() {
+        let text = sample_network().to_fann().unwrap();
+        assert!(text.starts_with("FANN_FLO_2.1\nnum_layers=3\n"));
+        assert!(text.contains("layer_sizes=3 4 2 \n"));
+    }
+
+    #[test]
+    fn steepness_is_folded_into_weights() {
+        let text = "FANN_FLO_2.1\n\
+            num_layers=2\n\
+            network_type=0\n\
+            layer_sizes=2 2\n\
+            scale_included=0\n\
+            neurons (num_inputs, activation_function, activation_steepness)=(0, 0, 0.0) (0, 0, 0.0) (2, 5, 0.5) (0, 5, 0.0)\n\
+            connections (connected_to_neuron, weight)=(0, 2.0) (1, -1.0)\n";
+        let network = NeuralNetwork::from_fann(text).unwrap();
+
+        assert_eq!(network.layer_activation(0).unwrap(), ActivationKind::Tanh);
+        assert_eq!(network.get_weights(0).unwrap(), vec![1.0]);
+        assert_eq!(network.get_biases(0).unwrap(), vec![-0.5]);
+    }
+
+    #[test]
+    fn rejects_shortcut_networks() {
+        let text = sample_network().to_fann().unwrap().replace("network_type=0", "network_type=1");
+        assert!(matches!(NeuralNetwork::from_fann(&text), Err(NeuralError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn rejects_unsupported_activation_on_export() {
+        let mut network = NeuralNetwork::new(2).unwrap();
+        network.add_layer(2, ActivationKind::Softmax).unwrap();
+        assert!(network.to_fann().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+// Runtime feature detection and SIMD/scalar kernel dispatch
+//
+// A WASM module containing any SIMD opcode fails validation on engines without
+// SIMD support, so the crate is built twice: once with `-C target-feature=+simd128`
+// and once without. SIMD kernels only exist in the first build, and the loader
+// picks the build by calling the same probe (`engine_supports_simd`) up front.
+
+#[cfg(js_host)]
+use std::sync::OnceLock;
+
+use wasm_bindgen::prelude::*;
+
+// Smallest module using a SIMD instruction: (func (result v128) i32.const 0 i8x16.splat i8x16.popcnt)
+#[cfg(js_host)]
+const SIMD_PROBE_MODULE: [u8; 31] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b, 0x03,
+    0x02, 0x01, 0x00, 0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x00, 0xfd, 0x0f, 0xfd, 0x62, 0x0b,
+];
+
+#[cfg(js_host)]
+static ENGINE_SIMD: OnceLock<bool> = OnceLock::new();
+
+// True when this build contains the SIMD kernels (simd128, or AVX2/NEON natively)
+pub fn compiled_with_simd() -> bool {
+    cfg!(any(target_feature = "simd128", native_simd))
+}
+
+// Ask the host engine whether it can validate a module using SIMD opcodes
+#[cfg(js_host)]
+pub fn engine_supports_simd() -> bool {
+    *ENGINE_SIMD.get_or_init(|| {
+        let probe = js_sys::Uint8Array::from(&SIMD_PROBE_MODULE[..]);
+        js_sys::WebAssembly::validate(&probe).unwrap_or(false)
+    })
+}
+
+// Without a JS engine to ask, a SIMD build that got instantiated already proves
+// support; native builds check the CPU instead
+#[cfg(not(js_host))]
+pub fn engine_supports_simd() -> bool {
+    #[cfg(native_simd)]
+    {
+        crate::native_simd::cpu_supported()
+    }
+    #[cfg(not(native_simd))]
+    {
+        compiled_with_simd()
+    }
+}
+
+// SIMD kernels are usable only if they were compiled in and the engine accepts them
+pub fn simd_available() -> bool {
+    compiled_with_simd() && engine_supports_simd()
+}
+
+// Select the SIMD or scalar expression; the SIMD arm is compiled out of scalar builds.
+// Kernels with an AVX2/NEON version pass it as `native: expr`, used by native builds.
+// Checked kernel mode always takes the scalar arm.
+macro_rules! simd_dispatch {
+    ($enabled:expr, $simd:expr, $scalar:expr) => {{
+        #[cfg(target_feature = "simd128")]
+        {
+            if $enabled && !crate::checked::enabled() {
+                $simd
+            } else {
+                $scalar
+            }
+        }
+        #[cfg(not(target_feature = "simd128"))]
+        {
+            let _ = $enabled;
+            $scalar
+        }
+    }};
+    ($enabled:expr, $simd:expr, native: $native:expr, $scalar:expr) => {{
+        #[cfg(target_feature = "simd128")]
+        {
+            if $enabled && !crate::checked::enabled() {
+                $simd
+            } else {
+                $scalar
+            }
+        }
+        #[cfg(native_simd)]
+        {
+            if $enabled && !crate::checked::enabled() {
+                $native
+            } else {
+                $scalar
+            }
+        }
+        #[cfg(not(any(target_feature = "simd128", native_simd)))]
+        {
+            let _ = $enabled;
+            $scalar
+        }
+    }};
+}
+
+pub(crate) use simd_dispatch;
+
+// Export functions for the JavaScript loader
+#[wasm_bindgen]
+pub fn simd_build() -> bool {
+    compiled_with_simd()
+}
+
+#[wasm_bindgen]
+pub fn engine_simd_support() -> bool {
+    engine_supports_simd()
+}
@@ -0,0 +1,81 @@
+// Federated averaging of agent models (McMahan et al., 2017)
+//
+// Every participant must share one architecture. Each parameter of the result is
+// the weighted mean Σ wᵢ·pᵢ / Σ wᵢ, where wᵢ is usually the number of samples agent i
+// trained on; fed_avg uses equal weights. The averaged network takes everything
+// but its parameters (precision, output mode, initializer) from the first participant.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+
+// Equal-weight average of `networks`
+pub fn fed_avg(networks: &[&NeuralNetwork]) -> NeuralResult<NeuralNetwork> {
+    let mut average = FederatedAverage::new();
+    for network in networks {
+        average.add(network, 1.0)?;
+    }
+    average.result()
+}
+
+// Running weighted average, so JavaScript can pass participants one at a time
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct FederatedAverage {
+    // First participant, holding the architecture and settings of the result
+    base: Option<NeuralNetwork>,
+    // Σ wᵢ·pᵢ, accumulated in f64 so many participants do not lose precision
+    sums: Vec<f64>,
+    total_weight: f64,
+    participants: usize,
+}
+
+#[wasm_bindgen]
+impl FederatedAverage {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FederatedAverage {
+        FederatedAverage::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+
+    // Add one agent's model with weight `weight`, e.g. its training sample count
+    #[wasm_bindgen]
+    pub fn add(&mut self, network: &NeuralNetwork, weight: f32) -> Result<(), NeuralError> {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("participant weight must be finite and non-negative".to_string()));
+        }
+        match &self.base {
+            Some(base) => base.check_same_architecture(network)?,
+            None => {
+                self.base = Some(network.clone());
+                self.sums = vec![0.0; network.parameter_count()];
+            }
+        }
+        for (sum, parameter) in self.sums.iter_mut().zip(network.get_parameters()) {
+            *sum += weight as f64 * parameter as f64;
+        }
+        self.total_weight += weight as f64;
+        self.participants += 1;
+        Ok(())
+    }
+
+    // The averaged network; the accumulator stays usable for more participants
+    #[wasm_bindgen]
+    pub fn result(&self) -> Result<NeuralNetwork, NeuralError> {
+        let Some(base) = &self.base else {
+            return Err(NeuralError::InvalidConfiguration("federated average has no participants".to_string()));
+        };
+        if self.total_weight <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("participant weights sum to zero".to_string()));
+        }
+        let parameters: Vec<f32> = self.sums.iter().map(|sum| (sum / self.total_weight) as f32).collect();
+        let mut network = base.clone();
+        network.set_parameters(&parameters)?;
+        Ok(network)
+    }
+}
@@ -0,0 +1,310 @@
+// Deterministic Q16.16 fixed-point inference
+//
+// f32 results can differ in their last bits between browsers and CPUs: exp and tanh
+// come from each engine's math library, SIMD and scalar kernels sum in different
+// orders, and the fast activation kernels approximate. The swarm consensus layer
+// compares replicated agent computations bit for bit, so
+// NeuralNetwork.set_arithmetic(Arithmetic::FixedQ16) runs forward, forward_batch and
+// forward_step (and the runtime's forward paths over the network) in integer
+// arithmetic that gives the same bits everywhere.
+//
+// Q16.16 stores x as the i32 round(x · 2^16): a range of ±32768 at a resolution of
+// 2^-16 ≈ 1.5e-5. Inputs, parameters and outputs stay f32 at the boundary; each value
+// is rounded to Q16.16 as the pass begins (saturating, NaN becomes 0) and outputs
+// convert back exactly. Preprocessing runs before that in correctly rounded f32. Then:
+//   - dense layers sum exact Q32.32 products in i64 and round once per output
+//   - exp is 2^k times a degree-7 polynomial in Q2.30; sigmoid, tanh, GELU (tanh
+//     form) and softmax are built on it and stay within about 1e-4 of f32
+//   - batch norm folds its running statistics into a per-feature scale and shift;
+//     layer norm computes its moments in integers with an integer square root
+//   - dropout is the identity
+// Values beyond ±32768 saturate, so networks with large activations lose accuracy.
+// Recurrent and convolutional layers are not supported, and training is unaffected.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::{ActivationKind, LEAKY_RELU_SLOPE};
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::Layer;
+use crate::normalization::{NormKind, NormLayer};
+
+// Number format of a network's inference passes
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    F32 = 0,
+    // Q16.16 fixed point, bit-identical on every platform
+    FixedQ16 = 1,
+}
+
+impl Arithmetic {
+    pub(crate) fn from_u8(value: u8) -> Option<Arithmetic> {
+        match value {
+            0 => Some(Arithmetic::F32),
+            1 => Some(Arithmetic::FixedQ16),
+            _ => None,
+        }
+    }
+}
+
+const FRACTION_BITS: u32 = 16;
+const ONE: i64 = 1 << FRACTION_BITS;
+
+// log2(e) in Q2.30
+const LOG2_E: i64 = 1_549_082_005;
+// ln(2)^n / n! in Q2.30 for n = 7 down to 1: the Taylor series of 2^f on [0, 1)
+const EXP2_TERMS: [i64; 7] = [16_377, 165_394, 1_431_680, 10_327_387, 59_597_083, 257_941_248, 744_261_118];
+
+// sqrt(2 / pi) and the cubic coefficient of the tanh form of GELU, in Q16.16
+const GELU_SCALE: i64 = 52_290;
+const GELU_CUBIC: i64 = 2_930;
+
+pub(crate) fn from_f32(value: f32) -> i32 {
+    (value * ONE as f32).round() as i32
+}
+
+pub(crate) fn to_f32(value: i32) -> f32 {
+    value as f32 / ONE as f32
+}
+
+fn saturate(value: i64) -> i32 {
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+// value / 2^shift rounded to nearest, ties upward
+fn round_shift(value: i64, shift: u32) -> i64 {
+    match shift {
+        0 => value,
+        _ => value.saturating_add(1 << (shift - 1)) >> shift,
+    }
+}
+
+fn mul(a: i64, b: i64) -> i64 {
+    round_shift(a.saturating_mul(b), FRACTION_BITS)
+}
+
+// a / b in Q16.16 for b > 0, rounded to nearest
+fn div(a: i64, b: i64) -> i64 {
+    let numerator = a.saturating_mul(ONE).saturating_mul(2).saturating_add(b);
+    numerator.div_euclid(2 * b)
+}
+
+fn exp(x: i64) -> i64 {
+    // x · log2(e) = k + f with integer k and f in [0, 1)
+    let y = x.clamp(i32::MIN as i64, i32::MAX as i64) * LOG2_E;
+    let (k, f) = (y >> 46, (y & ((1 << 46) - 1)) >> 16);
+    let mut power = 0;
+    for term in EXP2_TERMS {
+        power = term + ((power * f) >> 30);
+    }
+    let power = (1 << 30) + ((power * f) >> 30);
+    // power · 2^k, from Q2.30 to Q16.16
+    match 14 - k {
+        shift if shift < 0 => i32::MAX as i64,
+        shift if shift >= 62 => 0,
+        shift => round_shift(power, shift as u32).min(i32::MAX as i64),
+    }
+}
+
+fn sigmoid(x: i64) -> i64 {
+    // 1 / (1 + e^-|x|), mirrored for negative x so both halves round alike
+    let upper = div(ONE, ONE + exp(-x.abs()));
+    if x < 0 {
+        ONE - upper
+    } else {
+        upper
+    }
+}
+
+fn tanh(x: i64) -> i64 {
+    let e = exp(-2 * x.abs().min(i32::MAX as i64));
+    let magnitude = div(ONE - e, ONE + e);
+    if x < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+fn gelu(x: i64) -> i64 {
+    let cubic = mul(GELU_CUBIC, mul(mul(x, x), x));
+    let inner = mul(GELU_SCALE, x.saturating_add(cubic));
+    round_shift(x.saturating_mul(ONE + tanh(inner)), FRACTION_BITS + 1)
+}
+
+fn softmax(values: &mut [i32]) {
+    let max = values.iter().copied().max().unwrap_or(0) as i64;
+    let exps: Vec<i64> = values.iter().map(|&value| exp(value as i64 - max)).collect();
+    let total: i64 = exps.iter().sum();
+    for (value, e) in values.iter_mut().zip(exps) {
+        *value = saturate(div(e, total));
+    }
+}
+
+pub(crate) fn apply_activation(kind: ActivationKind, values: &mut [i32]) {
+    let function: fn(i64) -> i64 = match kind {
+        ActivationKind::Linear => return,
+        ActivationKind::Softmax => return softmax(values),
+        ActivationKind::ReLU => |x| x.max(0),
+        ActivationKind::LeakyReLU => |x| if x < 0 { mul(x, from_f32(LEAKY_RELU_SLOPE) as i64) } else { x },
+        ActivationKind::Sigmoid => sigmoid,
+        ActivationKind::Tanh => tanh,
+        ActivationKind::GELU => gelu,
+    };
+    for value in values.iter_mut() {
+        *value = saturate(function(*value as i64));
+    }
+}
+
+// One layer over a row-major [batch_size × inputs] batch
+pub(crate) fn forward_layer(layer: &Layer, inputs: &[i32], batch_size: usize) -> NeuralResult<Vec<i32>> {
+    if inputs.len() != batch_size * layer.inputs() {
+        return Err(NeuralError::DimensionMismatch { expected: batch_size * layer.inputs(), actual: inputs.len() });
+    }
+    match layer {
+        Layer::Dense(dense) => {
+            let weights: Vec<i32> = dense.effective_weights(false).iter().map(|&weight| from_f32(weight)).collect();
+            let biases: Vec<i64> = dense.biases.iter().map(|&bias| (from_f32(bias) as i64) << FRACTION_BITS).collect();
+            let mut outputs = Vec::with_capacity(batch_size * dense.outputs);
+            for sample in inputs.chunks_exact(dense.inputs) {
+                let start = outputs.len();
+                for (row, &bias) in weights.chunks_exact(dense.inputs).zip(&biases) {
+                    let sum = row.iter().zip(sample).fold(bias, |sum, (&w, &x)| sum.saturating_add(w as i64 * x as i64));
+                    outputs.push(saturate(round_shift(sum, FRACTION_BITS)));
+                }
+                apply_activation(dense.activation, &mut outputs[start..]);
+            }
+            Ok(outputs)
+        }
+        Layer::Dropout(_) => Ok(inputs.to_vec()),
+        Layer::Norm(norm) => Ok(inputs.chunks_exact(norm.size).flat_map(|sample| normalize(norm, sample)).collect()),
+        _ => Err(NeuralError::InvalidConfiguration(
+            "fixed-point arithmetic supports dense, dropout and normalization layers only".to_string(),
+        )),
+    }
+}
+
+fn normalize(norm: &NormLayer, sample: &[i32]) -> Vec<i32> {
+    match norm.kind {
+        NormKind::Batch => (0..norm.size)
+            .map(|j| {
+                let scale = norm.gamma[j] / (norm.running_var[j] + norm.epsilon).sqrt();
+                let shift = norm.beta[j] - norm.running_mean[j] * scale;
+                saturate(mul(sample[j] as i64, from_f32(scale) as i64).saturating_add(from_f32(shift) as i64))
+            })
+            .collect(),
+        NormKind::Layer => {
+            let n = norm.size as i64;
+            let mean = sample.iter().map(|&x| x as i64).sum::<i64>().div_euclid(n);
+            // Variance in Q32.32, so its square root is Q16.16
+            let squares: i128 = sample.iter().map(|&x| (x as i64 - mean) as i128).map(|d| d * d).sum();
+            let epsilon = (norm.epsilon as f64 * (1u64 << 32) as f64).round() as i128;
+            let std = ((squares / n as i128 + epsilon) as u128).isqrt().max(1) as i64;
+            sample
+                .iter()
+                .zip(norm.gamma.iter().zip(&norm.beta))
+                .map(|(&x, (&gamma, &beta))| {
+                    let normalized = div(x as i64 - mean, std);
+                    saturate(mul(normalized, from_f32(gamma) as i64).saturating_add(from_f32(beta) as i64))
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::NeuralNetwork;
+
+    // 0, ±1, ±8 and the ends of the Q16.16 range
+    const POINTS: [i64; 7] = [0, ONE, -ONE, 8 * ONE, -8 * ONE, i32::MAX as i64, i32::MIN as i64];
+
+    fn gelu_f32(x: f32) -> f32 {
+        0.5 * x * (1.0 + ((2.0 / std::f32::consts::PI).sqrt() * (x + 0.044715 * x * x * x)).tanh())
+    }
+
+    // Exact values at POINTS, and the f32 function they approximate within 1e-4
+    fn check(function: fn(i64) -> i64, reference: fn(f32) -> f32, expected: [i64; 7]) {
+        let actual: Vec<i64> = POINTS.iter().map(|&x| function(x)).collect();
+        assert_eq!(actual, expected);
+        // The saturation bounds lie outside what f32 can be compared against
+        for (&x, &y) in POINTS.iter().zip(&actual).take(5) {
+            let exact = reference(to_f32(x as i32));
+            let approx = to_f32(y as i32);
+            assert!((approx - exact).abs() <= 1e-4 * exact.abs().max(1.0), "f({}) = {} rather than {}", to_f32(x as i32), approx, exact);
+        }
+    }
+
+    #[test]
+    fn exp_matches_f32() {
+        check(exp, f32::exp, [65_536, 178_145, 24_109, 195_360_062, 22, i32::MAX as i64, 0]);
+    }
+
+    #[test]
+    fn sigmoid_matches_f32() {
+        check(sigmoid, |x| 1.0 / (1.0 + (-x).exp()), [32_768, 47_911, 17_625, 65_514, 22, ONE, 0]);
+    }
+
+    #[test]
+    fn tanh_matches_f32() {
+        check(tanh, f32::tanh, [0, 49_912, -49_912, ONE, -ONE, ONE, -ONE]);
+    }
+
+    #[test]
+    fn gelu_matches_f32() {
+        check(gelu, gelu_f32, [0, 55_128, -10_408, 8 * ONE, 0, i32::MAX as i64, 0]);
+    }
+
+    #[test]
+    fn softmax_matches_f32() {
+        let mut values = [0, ONE as i32, -ONE as i32, 8 * ONE as i32];
+        softmax(&mut values);
+        assert_eq!(values, [22, 60, 8, 65_446]);
+        let exps = [0.0f32, 1.0, -1.0, 8.0].map(f32::exp);
+        let total: f32 = exps.iter().sum();
+        for (&value, e) in values.iter().zip(exps) {
+            assert!((to_f32(value) - e / total).abs() <= 1e-4);
+        }
+
+        let mut saturated = [i32::MAX, i32::MIN, 0];
+        softmax(&mut saturated);
+        assert_eq!(saturated, [ONE as i32, 0, 0]);
+    }
+
+    #[test]
+    fn dense_and_layer_norm_network_is_bit_exact() {
+        let mut network = NeuralNetwork::new(3).unwrap();
+        network.add_layer(4, ActivationKind::Tanh).unwrap();
+        network.add_layer_norm(1e-5).unwrap();
+        network.add_layer(2, ActivationKind::Softmax).unwrap();
+        let parameters: Vec<f32> = (0..network.get_parameters().len()).map(|i| ((i * 7 % 11) as f32 - 5.0) * 0.13).collect();
+        network.set_parameters(&parameters).unwrap();
+        let inputs = [0.5, -1.25, 2.0];
+
+        let reference = network.forward(&inputs).unwrap();
+        network.set_arithmetic(Arithmetic::FixedQ16);
+        let outputs = network.forward(&inputs).unwrap();
+        let bits: Vec<u32> = outputs.iter().map(|value| value.to_bits()).collect();
+        assert_eq!(bits, [1_048_651_776, 1_061_121_024]);
+        for (value, exact) in outputs.iter().zip(&reference) {
+            assert!((value - exact).abs() <= 1e-4, "{} rather than {}", value, exact);
+        }
+        assert_eq!(network.forward_batch(&[inputs, inputs].concat(), 2).unwrap(), [outputs.clone(), outputs].concat());
+    }
+
+    #[test]
+    fn rejects_recurrent_and_conv_layers() {
+        let mut lstm = NeuralNetwork::new(3).unwrap();
+        lstm.add_lstm(2).unwrap();
+        let mut gru = NeuralNetwork::new(3).unwrap();
+        gru.add_gru(2).unwrap();
+        let mut conv = NeuralNetwork::new(4).unwrap();
+        conv.add_conv1d(1, 1, 2, 1, 0, ActivationKind::ReLU).unwrap();
+        for mut network in [lstm, gru, conv] {
+            network.set_arithmetic(Arithmetic::FixedQ16);
+            let inputs = vec![0.5; network.input_size()];
+            assert!(matches!(network.forward(&inputs), Err(NeuralError::InvalidConfiguration(_))));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Fused elementwise pipelines
+//
+// An ElementwisePipeline is a chain of elementwise steps (activation, scale,
+// offset, clamp) applied in one pass over one buffer: each group of four values
+// is loaded once, taken through every step in registers and stored once, instead
+// of every step allocating and writing a Vec of its own. Results match running
+// the steps one after another, except that SIMD activations follow the selected
+// ActivationAccuracy as the runtime's own kernels do.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::activation::{ActivationAccuracy, ActivationKind};
+use crate::error::{NeuralError, NeuralResult};
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Activation(ActivationKind),
+    Scale(f32),
+    Offset(f32),
+    Clamp(f32, f32),
+}
+
+impl Step {
+    fn scalar(self, x: f32) -> f32 {
+        match self {
+            Step::Activation(kind) => kind.scalar_apply(x),
+            Step::Scale(factor) => x * factor,
+            Step::Offset(offset) => x + offset,
+            Step::Clamp(min, max) => x.clamp(min, max),
+        }
+    }
+
+    #[cfg(target_feature = "simd128")]
+    fn lanes(self, x: v128, accuracy: ActivationAccuracy) -> v128 {
+        match self {
+            Step::Activation(kind) => kind.simd_lanes(x, accuracy),
+            Step::Scale(factor) => f32x4_mul(x, f32x4_splat(factor)),
+            Step::Offset(offset) => f32x4_add(x, f32x4_splat(offset)),
+            // pmin/pmax match f32::clamp for the finite values the runtime accepts
+            Step::Clamp(min, max) => f32x4_pmin(f32x4_pmax(x, f32x4_splat(min)), f32x4_splat(max)),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementwisePipeline {
+    steps: Vec<Step>,
+    accuracy: ActivationAccuracy,
+}
+
+impl Default for ElementwisePipeline {
+    fn default() -> Self {
+        ElementwisePipeline { steps: Vec::new(), accuracy: ActivationAccuracy::Accurate }
+    }
+}
+
+#[wasm_bindgen]
+impl ElementwisePipeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ElementwisePipeline {
+        ElementwisePipeline::default()
+    }
+
+    // Append an activation; softmax is not elementwise and is refused
+    #[wasm_bindgen]
+    pub fn activation(&mut self, kind: ActivationKind) -> Result<(), NeuralError> {
+        if kind == ActivationKind::Softmax {
+            return Err(NeuralError::InvalidConfiguration("softmax cannot be fused into an elementwise pipeline".to_string()));
+        }
+        self.steps.push(Step::Activation(kind));
+        Ok(())
+    }
+
+    // Append x · factor
+    #[wasm_bindgen]
+    pub fn scale(&mut self, factor: f32) -> Result<(), NeuralError> {
+        check_finite(factor)?;
+        self.steps.push(Step::Scale(factor));
+        Ok(())
+    }
+
+    // Append x + offset
+    #[wasm_bindgen]
+    pub fn offset(&mut self, offset: f32) -> Result<(), NeuralError> {
+        check_finite(offset)?;
+        self.steps.push(Step::Offset(offset));
+        Ok(())
+    }
+
+    // Append a clamp to [min, max]
+    #[wasm_bindgen]
+    pub fn clamp(&mut self, min: f32, max: f32) -> Result<(), NeuralError> {
+        check_finite(min)?;
+        check_finite(max)?;
+        if min > max {
+            return Err(NeuralError::InvalidConfiguration("clamp minimum exceeds maximum".to_string()));
+        }
+        self.steps.push(Step::Clamp(min, max));
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    #[wasm_bindgen]
+    pub fn set_accuracy(&mut self, accuracy: ActivationAccuracy) {
+        self.accuracy = accuracy;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn accuracy(&self) -> ActivationAccuracy {
+        self.accuracy
+    }
+
+    // Run the pipeline over `values` in place
+    #[wasm_bindgen]
+    pub fn apply(&self, values: &mut [f32]) {
+        self.apply_with(values, crate::check_simd_support());
+    }
+
+    // Run the pipeline into a new buffer, the only allocation it makes
+    #[wasm_bindgen]
+    pub fn run(&self, values: &[f32]) -> Vec<f32> {
+        let mut outputs = values.to_vec();
+        self.apply(&mut outputs);
+        outputs
+    }
+}
+
+impl ElementwisePipeline {
+    pub(crate) fn apply_with(&self, values: &mut [f32], simd: bool) {
+        if self.steps.is_empty() {
+            return;
+        }
+        simd_dispatch!(simd && values.len() >= 4, self.simd_apply(values), self.scalar_apply(values))
+    }
+
+    fn scalar_apply(&self, values: &mut [f32]) {
+        for value in values.iter_mut() {
+            *value = self.steps.iter().fold(*value, |x, step| step.scalar(x));
+        }
+    }
+
+    #[cfg(target_feature = "simd128")]
+    fn simd_apply(&self, values: &mut [f32]) {
+        let mut chunks = values.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            let lanes = self.steps.iter().fold(simd::load(chunk), |x, step| step.lanes(x, self.accuracy));
+            simd::store(chunk, lanes);
+        }
+        self.scalar_apply(chunks.into_remainder());
+    }
+}
+
+fn check_finite(value: f32) -> NeuralResult<()> {
+    if !value.is_finite() {
+        return Err(NeuralError::InvalidConfiguration("pipeline parameters must be finite".to_string()));
+    }
+    Ok(())
+}
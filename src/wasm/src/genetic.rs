@@ -0,0 +1,256 @@
+// Genetic algorithm over the weights of a fixed architecture
+//
+// Where NEAT (neat.rs) evolves topology, WeightEvolution keeps the template
+// network's layers and evolves only its flat parameter vector (see
+// NeuralNetwork.get_parameters). Each evolve(fitness_scores) call, higher is better:
+//   elitism    the `elitism` fittest individuals are copied unchanged
+//   selection  each parent is the fittest of `tournament_size` random individuals
+//   crossover  with `crossover_rate`, BLX-α: every child gene is uniform in
+//              [lo - α·d, hi + α·d], lo/hi the parents' genes and d = hi - lo;
+//              otherwise the child copies the first parent
+//   mutation   each gene gains N(0, mutation_std) with probability `mutation_rate`
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+use crate::rng::Rng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneticConfig {
+    pub population_size: usize,
+    pub tournament_size: usize,
+    pub crossover_rate: f32,
+    // α of the blend crossover; 0 keeps children between their parents
+    pub blend_alpha: f32,
+    // Per-gene chance of a Gaussian mutation, and its standard deviation
+    pub mutation_rate: f32,
+    pub mutation_std: f32,
+    pub elitism: usize,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        GeneticConfig {
+            population_size: 50,
+            tournament_size: 3,
+            crossover_rate: 0.9,
+            blend_alpha: 0.5,
+            mutation_rate: 0.1,
+            mutation_std: 0.1,
+            elitism: 2,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl GeneticConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GeneticConfig {
+        GeneticConfig::default()
+    }
+}
+
+impl GeneticConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if self.population_size == 0 || self.tournament_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("population and tournament sizes must be non-zero".to_string()));
+        }
+        if self.elitism > self.population_size {
+            return Err(NeuralError::InvalidConfiguration("elitism cannot exceed the population size".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.crossover_rate) || !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err(NeuralError::InvalidConfiguration("GA rates must lie in [0, 1]".to_string()));
+        }
+        if !self.blend_alpha.is_finite() || self.blend_alpha < 0.0 || !self.mutation_std.is_finite() || self.mutation_std < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("blend_alpha and mutation_std must be finite and non-negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WeightEvolution {
+    config: GeneticConfig,
+    // Carries the architecture; its parameters are those of `loaded`
+    template: NeuralNetwork,
+    loaded: Option<usize>,
+    population: Vec<Vec<f32>>,
+    rng: Rng,
+    generation: u32,
+    // Fittest individual seen in any evaluated generation
+    champion: Option<(Vec<f32>, f32)>,
+}
+
+#[wasm_bindgen]
+impl WeightEvolution {
+    // Individual 0 is the template's own parameters; the rest are Gaussian-mutated copies
+    #[wasm_bindgen(constructor)]
+    pub fn new(template: &NeuralNetwork, config: &GeneticConfig, seed: u64) -> Result<WeightEvolution, NeuralError> {
+        config.validate()?;
+        if template.parameter_count() == 0 {
+            return Err(NeuralError::InvalidConfiguration("template network has no parameters to evolve".to_string()));
+        }
+        let mut rng = Rng::new(seed);
+        let origin = template.get_parameters();
+        let population = (0..config.population_size)
+            .map(|index| {
+                let mut individual = origin.clone();
+                if index > 0 {
+                    for gene in individual.iter_mut() {
+                        *gene += rng.normal() * config.mutation_std;
+                    }
+                }
+                individual
+            })
+            .collect();
+
+        Ok(WeightEvolution {
+            config: *config,
+            template: template.clone(),
+            loaded: None,
+            population,
+            rng,
+            generation: 0,
+            champion: None,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.population.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn parameter_count(&self) -> usize {
+        self.template.parameter_count()
+    }
+
+    #[wasm_bindgen]
+    pub fn parameters(&self, index: usize) -> Result<Vec<f32>, NeuralError> {
+        Ok(self.individual(index)?.to_vec())
+    }
+
+    // A standalone network carrying one individual's parameters
+    #[wasm_bindgen]
+    pub fn network(&self, index: usize) -> Result<NeuralNetwork, NeuralError> {
+        let mut network = self.template.clone();
+        network.set_parameters(self.individual(index)?)?;
+        Ok(network)
+    }
+
+    // Run one individual without copying its network out; consecutive calls for the
+    // same index reuse the loaded parameters
+    #[wasm_bindgen]
+    pub fn forward(&mut self, index: usize, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        if self.loaded != Some(index) {
+            let individual = self.population.get(index).ok_or(NeuralError::IndexOutOfRange { index, len: self.population.len() })?;
+            self.template.set_parameters(individual)?;
+            self.loaded = Some(index);
+        }
+        self.template.forward(inputs)
+    }
+
+    // Fittest network of any evaluated generation, undefined before the first evolve()
+    #[wasm_bindgen]
+    pub fn champion(&self) -> Result<Option<NeuralNetwork>, NeuralError> {
+        let Some((parameters, _)) = &self.champion else {
+            return Ok(None);
+        };
+        let mut network = self.template.clone();
+        network.set_parameters(parameters)?;
+        Ok(Some(network))
+    }
+
+    #[wasm_bindgen]
+    pub fn champion_fitness(&self) -> Option<f32> {
+        self.champion.as_ref().map(|&(_, fitness)| fitness)
+    }
+
+    // Score the current generation (one fitness per individual) and replace it with
+    // the next; returns the new generation number
+    #[wasm_bindgen]
+    pub fn evolve(&mut self, fitness_scores: &[f32]) -> Result<u32, NeuralError> {
+        if fitness_scores.len() != self.population.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.population.len(), actual: fitness_scores.len() });
+        }
+        if let Some(index) = fitness_scores.iter().position(|score| !score.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+
+        let mut ranked: Vec<usize> = (0..self.population.len()).collect();
+        ranked.sort_by(|&a, &b| fitness_scores[b].total_cmp(&fitness_scores[a]));
+        let best = ranked[0];
+        if self.champion.as_ref().is_none_or(|&(_, fitness)| fitness_scores[best] > fitness) {
+            self.champion = Some((self.population[best].clone(), fitness_scores[best]));
+        }
+
+        let mut next: Vec<Vec<f32>> = ranked[..self.config.elitism].iter().map(|&index| self.population[index].clone()).collect();
+        while next.len() < self.config.population_size {
+            let first = self.tournament(fitness_scores);
+            let mut child = if self.rng.next_f32() < self.config.crossover_rate {
+                let second = self.tournament(fitness_scores);
+                self.blend(first, second)
+            } else {
+                self.population[first].clone()
+            };
+            self.mutate(&mut child);
+            next.push(child);
+        }
+
+        self.population = next;
+        self.loaded = None;
+        self.generation += 1;
+        Ok(self.generation)
+    }
+}
+
+impl WeightEvolution {
+    fn individual(&self, index: usize) -> NeuralResult<&[f32]> {
+        self.population
+            .get(index)
+            .map(Vec::as_slice)
+            .ok_or(NeuralError::IndexOutOfRange { index, len: self.population.len() })
+    }
+
+    // Index of the fittest of `tournament_size` individuals drawn with replacement
+    fn tournament(&mut self, fitness_scores: &[f32]) -> usize {
+        let len = self.population.len();
+        let mut winner = self.rng.next_u64() as usize % len;
+        for _ in 1..self.config.tournament_size {
+            let challenger = self.rng.next_u64() as usize % len;
+            if fitness_scores[challenger] > fitness_scores[winner] {
+                winner = challenger;
+            }
+        }
+        winner
+    }
+
+    fn blend(&mut self, first: usize, second: usize) -> Vec<f32> {
+        let alpha = self.config.blend_alpha;
+        let (a, b) = (&self.population[first], &self.population[second]);
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| {
+                let (low, high) = (x.min(y), x.max(y));
+                let spread = alpha * (high - low);
+                self.rng.uniform(low - spread, high + spread)
+            })
+            .collect()
+    }
+
+    fn mutate(&mut self, individual: &mut [f32]) {
+        for gene in individual.iter_mut() {
+            if self.rng.next_f32() < self.config.mutation_rate {
+                *gene += self.rng.normal() * self.config.mutation_std;
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+// Update rules for gradient-based training
+//
+// Every rule moves each parameter p against its gradient g with step size lr (the
+// learning rate passed to train_batch or apply_gradients):
+//   Sgd      p -= lr · g
+//   Adam     m = β1·m + (1-β1)·g,  v = β2·v + (1-β2)·g²
+//            p -= lr · m̂ / (sqrt(v̂) + ε)   with m̂ = m/(1-β1^t), v̂ = v/(1-β2^t)
+//   RmsProp  v = β2·v + (1-β2)·g²,  p -= lr · g / (sqrt(v) + ε)
+//   AdaGrad  v += g²,               p -= lr · g / (sqrt(v) + ε)
+// The adaptive rules keep one or two moments per parameter. A network stores them
+// in its own memory pool, sized to fit them exactly and allocated on the first step,
+// so SGD networks carry no extra memory. The moments start over whenever the
+// optimizer is replaced or the network's parameter shapes change.
+//
+// Defaults follow PyTorch: Adam β1 0.9, β2 0.999, ε 1e-8; RmsProp β2 (its decay
+// α) 0.99, ε 1e-8; AdaGrad ε 1e-10.
+
+use wasm_bindgen::prelude::*;
+
+use crate::allocator::PoolAllocator;
+use crate::error::{NeuralError, NeuralResult};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientOptimizerKind {
+    Sgd = 0,
+    Adam = 1,
+    RmsProp = 2,
+    AdaGrad = 3,
+}
+
+impl GradientOptimizerKind {
+    pub fn from_u8(value: u8) -> Option<GradientOptimizerKind> {
+        match value {
+            0 => Some(GradientOptimizerKind::Sgd),
+            1 => Some(GradientOptimizerKind::Adam),
+            2 => Some(GradientOptimizerKind::RmsProp),
+            3 => Some(GradientOptimizerKind::AdaGrad),
+            _ => None,
+        }
+    }
+
+    // Moments kept per parameter
+    pub(crate) fn slots(self) -> usize {
+        match self {
+            GradientOptimizerKind::Sgd => 0,
+            GradientOptimizerKind::Adam => 2,
+            GradientOptimizerKind::RmsProp | GradientOptimizerKind::AdaGrad => 1,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientOptimizerConfig {
+    pub kind: GradientOptimizerKind,
+    // First-moment decay (Adam)
+    pub beta1: f32,
+    // Second-moment decay (Adam, RmsProp)
+    pub beta2: f32,
+    pub epsilon: f32,
+}
+
+impl Default for GradientOptimizerConfig {
+    fn default() -> Self {
+        GradientOptimizerConfig::new(GradientOptimizerKind::Sgd)
+    }
+}
+
+#[wasm_bindgen]
+impl GradientOptimizerConfig {
+    // The rule's default hyperparameters
+    #[wasm_bindgen(constructor)]
+    pub fn new(kind: GradientOptimizerKind) -> GradientOptimizerConfig {
+        let (beta2, epsilon) = match kind {
+            GradientOptimizerKind::RmsProp => (0.99, 1e-8),
+            GradientOptimizerKind::AdaGrad => (0.999, 1e-10),
+            _ => (0.999, 1e-8),
+        };
+        GradientOptimizerConfig { kind, beta1: 0.9, beta2, epsilon }
+    }
+}
+
+impl GradientOptimizerConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(0.0..1.0).contains(&self.beta1) || !(0.0..1.0).contains(&self.beta2) {
+            return Err(NeuralError::InvalidConfiguration("optimizer betas must lie in [0, 1)".to_string()));
+        }
+        if !self.epsilon.is_finite() || self.epsilon <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("optimizer epsilon must be positive and finite".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// Moments and step count behind a network's optimizer
+#[derive(Debug, Clone)]
+pub(crate) struct OptimizerState {
+    config: GradientOptimizerConfig,
+    // Completed steps, for Adam's bias correction
+    step: u64,
+    // Parameter tensor lengths the moments were allocated for
+    layout: Vec<usize>,
+    // One block of `slots × len` floats per non-empty tensor, in tensor order
+    handles: Vec<Option<u32>>,
+    pool: Option<PoolAllocator>,
+}
+
+impl Default for OptimizerState {
+    fn default() -> Self {
+        OptimizerState::new(GradientOptimizerConfig::default())
+    }
+}
+
+impl OptimizerState {
+    pub(crate) fn new(config: GradientOptimizerConfig) -> OptimizerState {
+        OptimizerState { config, step: 0, layout: Vec::new(), handles: Vec::new(), pool: None }
+    }
+
+    pub(crate) fn config(&self) -> GradientOptimizerConfig {
+        self.config
+    }
+
+    pub(crate) fn step_count(&self) -> u64 {
+        self.step
+    }
+
+    // Bytes reserved for moments
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.pool.as_ref().map_or(0, PoolAllocator::reserved_bytes)
+    }
+
+    // One update of every (parameters, gradients) tensor pair, in a fixed order
+    pub(crate) fn step(&mut self, tensors: Vec<(&mut [f32], &[f32])>, learning_rate: f32) -> NeuralResult<()> {
+        let layout: Vec<usize> = tensors.iter().map(|(parameters, _)| parameters.len()).collect();
+        if layout != self.layout {
+            self.allocate(layout)?;
+        }
+        self.step += 1;
+
+        let config = self.config;
+        let (beta1, beta2) = (config.beta1, config.beta2);
+        // Adam's bias corrections, in f64 so long runs do not lose the step count
+        let correction1 = (1.0 - (beta1 as f64).powf(self.step as f64)) as f32;
+        let correction2 = (1.0 - (beta2 as f64).powf(self.step as f64)) as f32;
+
+        for ((parameters, gradients), handle) in tensors.into_iter().zip(&self.handles) {
+            let moments = match (handle, self.pool.as_mut()) {
+                (Some(handle), Some(pool)) => pool.get_mut(*handle)?,
+                _ => &mut [],
+            };
+            match config.kind {
+                GradientOptimizerKind::Sgd => {
+                    for (p, g) in parameters.iter_mut().zip(gradients) {
+                        *p -= learning_rate * g;
+                    }
+                }
+                GradientOptimizerKind::Adam => {
+                    let (first, second) = moments.split_at_mut(parameters.len());
+                    for (((p, g), m), v) in parameters.iter_mut().zip(gradients).zip(first).zip(second) {
+                        *m = beta1 * *m + (1.0 - beta1) * g;
+                        *v = beta2 * *v + (1.0 - beta2) * g * g;
+                        *p -= learning_rate * (*m / correction1) / ((*v / correction2).sqrt() + config.epsilon);
+                    }
+                }
+                GradientOptimizerKind::RmsProp => {
+                    for ((p, g), v) in parameters.iter_mut().zip(gradients).zip(moments) {
+                        *v = beta2 * *v + (1.0 - beta2) * g * g;
+                        *p -= learning_rate * g / (v.sqrt() + config.epsilon);
+                    }
+                }
+                GradientOptimizerKind::AdaGrad => {
+                    for ((p, g), v) in parameters.iter_mut().zip(gradients).zip(moments) {
+                        *v += g * g;
+                        *p -= learning_rate * g / (v.sqrt() + config.epsilon);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Every moment in tensor order, empty before the first step
+    pub(crate) fn moments(&self) -> Vec<f32> {
+        let Some(pool) = &self.pool else {
+            return Vec::new();
+        };
+        self.handles.iter().flatten().filter_map(|&handle| pool.get(handle).ok()).flatten().copied().collect()
+    }
+
+    // Inverse of config/step_count/moments for parameter tensors of the given lengths
+    pub(crate) fn restore(config: GradientOptimizerConfig, step: u64, moments: &[f32], layout: Vec<usize>) -> NeuralResult<OptimizerState> {
+        config.validate()?;
+        let mut state = OptimizerState::new(config);
+        if moments.is_empty() {
+            state.step = step;
+            return Ok(state);
+        }
+        let expected: usize = layout.iter().sum::<usize>() * config.kind.slots();
+        if moments.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: moments.len() });
+        }
+        state.allocate(layout)?;
+        let mut rest = moments;
+        if let Some(pool) = state.pool.as_mut() {
+            for handle in state.handles.iter().flatten() {
+                let block = pool.get_mut(*handle)?;
+                let (head, tail) = rest.split_at(block.len());
+                block.copy_from_slice(head);
+                rest = tail;
+            }
+        }
+        state.step = step;
+        Ok(state)
+    }
+
+    // Zeroed moments for tensors of the given lengths; the step count restarts
+    fn allocate(&mut self, layout: Vec<usize>) -> NeuralResult<()> {
+        let slots = self.config.kind.slots();
+        let lengths: Vec<usize> = layout.iter().map(|len| len * slots).filter(|&len| len > 0).collect();
+        self.step = 0;
+        self.handles.clear();
+        self.pool = None;
+        if !lengths.is_empty() {
+            let mut pool = PoolAllocator::with_capacity(&lengths);
+            for len in layout.iter().map(|len| len * slots) {
+                self.handles.push(if len > 0 { Some(pool.allocate(len)?) } else { None });
+            }
+            self.pool = Some(pool);
+        } else {
+            self.handles.resize(layout.len(), None);
+        }
+        self.layout = layout;
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+// Serialized gradients for distributed training
+//
+// An agent calls NeuralNetwork.compute_gradients on its local batch and ships the
+// blob; the coordinator combines blobs with a GradientAggregator and sends the
+// result back to be applied with apply_gradients. Gradients cost as much as the
+// weights per round but are averaged exactly, where weight averaging (fed_avg)
+// only approximates a shared step.
+//
+// Layout (all integers and floats little-endian):
+//   magic        b"SASG"
+//   version      u16
+//   reserved     u16
+//   samples      u32  (samples the gradients are averaged over)
+//   loss         f32  (mean loss over those samples)
+//   layer_count  u32
+//   layer_count × { weight_count u32, bias_count u32 }
+//   layer_count × { weight gradients f32[weight_count], bias gradients f32[bias_count] }
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::serialization::{ByteReader, ByteWriter};
+use crate::training::LayerGradients;
+
+pub const GRADIENTS_MAGIC: &[u8; 4] = b"SASG";
+pub const GRADIENTS_VERSION: u16 = 1;
+
+// Mean gradients of one or more batches
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GradientSet {
+    pub(crate) samples: u32,
+    pub(crate) loss: f32,
+    pub(crate) layers: Vec<LayerGradients>,
+}
+
+impl GradientSet {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(GRADIENTS_MAGIC);
+        writer.u16(GRADIENTS_VERSION);
+        writer.u16(0);
+        writer.u32(self.samples);
+        writer.f32(self.loss);
+        writer.u32(self.layers.len() as u32);
+        for layer in &self.layers {
+            writer.u32(layer.weights.len() as u32);
+            writer.u32(layer.biases.len() as u32);
+        }
+        for layer in &self.layers {
+            writer.f32_slice(&layer.weights);
+            writer.f32_slice(&layer.biases);
+        }
+        writer.finish()
+    }
+
+    pub(crate) fn decode(bytes: &[u8]) -> NeuralResult<GradientSet> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.bytes(4)? != GRADIENTS_MAGIC {
+            return Err(NeuralError::InvalidFormat("missing SASG header".to_string()));
+        }
+        let version = reader.u16()?;
+        if version == 0 || version > GRADIENTS_VERSION {
+            return Err(NeuralError::InvalidFormat(format!("unsupported gradient format version {}", version)));
+        }
+        reader.u16()?;
+        let samples = reader.u32()?;
+        let loss = reader.f32()?;
+        let layer_count = reader.u32()? as usize;
+
+        let mut counts = Vec::with_capacity(layer_count.min(1024));
+        for _ in 0..layer_count {
+            counts.push((reader.u32()? as usize, reader.u32()? as usize));
+        }
+        let mut layers = Vec::with_capacity(counts.len());
+        for (weight_count, bias_count) in counts {
+            let weights = reader.f32_vec(weight_count)?;
+            let biases = reader.f32_vec(bias_count)?;
+            if weights.iter().chain(&biases).any(|grad| !grad.is_finite()) {
+                return Err(NeuralError::InvalidFormat("gradient blob holds non-finite values".to_string()));
+            }
+            layers.push(LayerGradients { weights, biases });
+        }
+        if !reader.is_empty() {
+            return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+        }
+        Ok(GradientSet { samples, loss, layers })
+    }
+}
+
+// Sample-weighted mean of gradient blobs from several agents
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct GradientAggregator {
+    // Σ samples · gradient, in the layout of the first blob
+    sums: Option<GradientSet>,
+    participants: usize,
+}
+
+#[wasm_bindgen]
+impl GradientAggregator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GradientAggregator {
+        GradientAggregator::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+
+    // Samples covered by the blobs added so far
+    #[wasm_bindgen(getter)]
+    pub fn samples(&self) -> u32 {
+        self.sums.as_ref().map_or(0, |sums| sums.samples)
+    }
+
+    #[wasm_bindgen]
+    pub fn add(&mut self, blob: &[u8]) -> Result<(), NeuralError> {
+        let gradients = GradientSet::decode(blob)?;
+        let weight = gradients.samples as f32;
+        let Some(sums) = self.sums.as_mut() else {
+            let mut first = gradients;
+            scale_layers(&mut first.layers, weight);
+            first.loss *= weight;
+            self.sums = Some(first);
+            self.participants = 1;
+            return Ok(());
+        };
+
+        let same_shape = sums.layers.len() == gradients.layers.len()
+            && sums.layers.iter().zip(&gradients.layers).all(|(a, b)| {
+                a.weights.len() == b.weights.len() && a.biases.len() == b.biases.len()
+            });
+        if !same_shape {
+            return Err(NeuralError::InvalidConfiguration("gradient blobs come from different architectures".to_string()));
+        }
+        let samples = sums.samples.checked_add(gradients.samples).ok_or_else(|| {
+            NeuralError::InvalidConfiguration("aggregated sample count exceeds u32".to_string())
+        })?;
+        for (sum, layer) in sums.layers.iter_mut().zip(&gradients.layers) {
+            for (total, grad) in sum.weights.iter_mut().zip(&layer.weights).chain(sum.biases.iter_mut().zip(&layer.biases)) {
+                *total += weight * grad;
+            }
+        }
+        sums.samples = samples;
+        sums.loss += weight * gradients.loss;
+        self.participants += 1;
+        Ok(())
+    }
+
+    // The averaged gradients as a blob for apply_gradients
+    #[wasm_bindgen]
+    pub fn result(&self) -> Result<Vec<u8>, NeuralError> {
+        let Some(sums) = &self.sums else {
+            return Err(NeuralError::InvalidConfiguration("no gradients have been added".to_string()));
+        };
+        if sums.samples == 0 {
+            return Err(NeuralError::InvalidConfiguration("gradient blobs cover no samples".to_string()));
+        }
+        let mut mean = sums.clone();
+        let scale = 1.0 / sums.samples as f32;
+        scale_layers(&mut mean.layers, scale);
+        mean.loss *= scale;
+        Ok(mean.encode())
+    }
+}
+
+fn scale_layers(layers: &mut [LayerGradients], factor: f32) {
+    for layer in layers.iter_mut() {
+        for grad in layer.weights.iter_mut().chain(layer.biases.iter_mut()) {
+            *grad *= factor;
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// C-ABI entry points for hosts without JS glue
+//
+// Built with `--features headless`, a wasm32-wasip1 module exports these as plain
+// functions, so CI and server-side coordinators can run the same kernels under a
+// WASI runtime, e.g. `wasmtime run --invoke neural_self_test neural_wasm_runtime.wasm`.
+// Native builds get the same symbols for linking from C or FFI test harnesses.
+
+use crate::activation::{softmax, ActivationKind};
+use crate::features;
+use crate::linalg;
+use crate::network::NeuralNetwork;
+
+// Tolerance for comparing kernels against the scalar reference
+const TOLERANCE: f32 = 1e-4;
+
+// Number of failed kernel checks; 0 means the build is usable on this host
+#[no_mangle]
+pub extern "C" fn neural_self_test() -> u32 {
+    [check_matmul(), check_softmax(), check_network()].iter().filter(|&&passed| !passed).count() as u32
+}
+
+// 1 when this module was built with SIMD kernels (a WASI host that loaded it supports them)
+#[no_mangle]
+pub extern "C" fn neural_simd_available() -> u32 {
+    features::simd_available() as u32
+}
+
+fn close(actual: &[f32], expected: &[f32]) -> bool {
+    actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| (a - e).abs() <= TOLERANCE)
+}
+
+// Both kernel paths against a hand-computed 2x3 · 3x2 product
+fn check_matmul() -> bool {
+    let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let b = [7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+    let expected = [58.0, 64.0, 139.0, 154.0];
+    [false, features::simd_available()].iter().all(|&simd| {
+        let mut c = [0.0; 4];
+        linalg::matmul_into(&a, &b, &mut c, 2, 2, 3, simd).is_ok() && close(&c, &expected)
+    })
+}
+
+fn check_softmax() -> bool {
+    match softmax(&[1.0, 2.0, 3.0, -4.0]) {
+        Ok(probabilities) => (probabilities.iter().sum::<f32>() - 1.0).abs() <= TOLERANCE && probabilities[2] > probabilities[1],
+        Err(_) => false,
+    }
+}
+
+// A 3-2 tanh layer with fixed parameters through the full network path
+fn check_network() -> bool {
+    let inputs = [0.5, -1.0, 2.0];
+    let weights = [0.1, 0.2, 0.3, -0.4, 0.5, -0.6];
+    let biases = [0.05, -0.05];
+    let expected: Vec<f32> = weights
+        .chunks_exact(inputs.len())
+        .zip(&biases)
+        .map(|(row, bias)| (row.iter().zip(&inputs).map(|(w, x)| w * x).sum::<f32>() + bias).tanh())
+        .collect();
+
+    let run = || -> Result<Vec<f32>, crate::error::NeuralError> {
+        let mut network = NeuralNetwork::new(inputs.len())?;
+        network.add_layer(biases.len(), ActivationKind::Tanh)?;
+        network.set_weights(0, &weights)?;
+        network.set_biases(0, &biases)?;
+        network.forward(&inputs)
+    };
+    run().is_ok_and(|outputs| close(&outputs, &expected))
+}
@@ -0,0 +1,151 @@
+// Homeostatic regulation of spiking neurons
+//
+// Each regulated neuron tracks its firing rate r as an exponential moving average
+// with time constant tau_ms (every spike adds 1000 / tau_ms Hz, and the estimate
+// starts in the middle of the target band). While r lies outside
+// [min_rate_hz, max_rate_hz] the relative deviation
+//   d = (min - r) / min   below the band,   d = (max - r) / max   above it
+// drives two slow corrections per second of simulated time:
+//   synaptic scaling     incoming excitatory weights × (1 + scaling_rate · d · dt),
+//                        inhibitory weights divided by the same factor
+//   intrinsic excitability  threshold offset -= threshold_rate · d · dt (mV),
+//                        kept within ±max_threshold_shift
+// Quiet neurons thus strengthen their inputs and lower their threshold, and
+// runaway neurons do the opposite. Populations are contiguous neuron ranges, each
+// with its own parameters.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HomeostasisParams {
+    pub min_rate_hz: f32,
+    pub max_rate_hz: f32,
+    pub tau_ms: f32,
+    // Fractional weight change per second per unit of deviation
+    pub scaling_rate: f32,
+    // Threshold shift in mV per second per unit of deviation
+    pub threshold_rate: f32,
+    pub max_threshold_shift: f32,
+}
+
+impl Default for HomeostasisParams {
+    fn default() -> Self {
+        HomeostasisParams {
+            min_rate_hz: 1.0,
+            max_rate_hz: 20.0,
+            tau_ms: 1000.0,
+            scaling_rate: 0.1,
+            threshold_rate: 1.0,
+            max_threshold_shift: 10.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl HomeostasisParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> HomeostasisParams {
+        HomeostasisParams::default()
+    }
+}
+
+impl HomeostasisParams {
+    pub fn validate(&self) -> NeuralResult<()> {
+        let values = [self.min_rate_hz, self.max_rate_hz, self.tau_ms, self.scaling_rate, self.threshold_rate, self.max_threshold_shift];
+        if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err(NeuralError::InvalidConfiguration("homeostasis parameters must be finite and non-negative".to_string()));
+        }
+        if self.min_rate_hz <= 0.0 || self.min_rate_hz > self.max_rate_hz {
+            return Err(NeuralError::InvalidConfiguration("target rates must satisfy 0 < min_rate_hz <= max_rate_hz".to_string()));
+        }
+        if self.tau_ms <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("rate time constant must be positive".to_string()));
+        }
+        Ok(())
+    }
+
+    // Relative distance of `rate` from the target band, 0 inside it
+    pub(crate) fn deviation(&self, rate: f32) -> f32 {
+        if rate < self.min_rate_hz {
+            (self.min_rate_hz - rate) / self.min_rate_hz
+        } else if rate > self.max_rate_hz {
+            (self.max_rate_hz - rate) / self.max_rate_hz
+        } else {
+            0.0
+        }
+    }
+}
+
+// Per-neuron regulation state of a SpikingNetwork
+#[derive(Debug, Clone)]
+pub(crate) struct Homeostasis {
+    // None for unregulated neurons
+    pub(crate) params: Vec<Option<HomeostasisParams>>,
+    pub(crate) rates: Vec<f32>,
+    pub(crate) threshold_offsets: Vec<f32>,
+    // Synaptic scaling factor per neuron from the last regulate(), 1 inside the band
+    pub(crate) scaling: Vec<f32>,
+}
+
+impl Homeostasis {
+    pub(crate) fn new(neuron_count: usize) -> Homeostasis {
+        Homeostasis {
+            params: vec![None; neuron_count],
+            rates: vec![0.0; neuron_count],
+            threshold_offsets: vec![0.0; neuron_count],
+            scaling: vec![1.0; neuron_count],
+        }
+    }
+
+    // Regulate neurons first..first + count with `params`; their rate estimates restart
+    pub(crate) fn assign(&mut self, first: usize, count: usize, params: Option<HomeostasisParams>) {
+        for neuron in first..first + count {
+            self.params[neuron] = params;
+            self.rates[neuron] = params.map_or(0.0, |params| (params.min_rate_hz + params.max_rate_hz) / 2.0);
+            if params.is_none() {
+                self.threshold_offsets[neuron] = 0.0;
+            }
+        }
+    }
+
+    // Advance every rate estimate by `dt` ms, counting the neurons that just fired
+    pub(crate) fn record(&mut self, dt: f32, fired: &[u32]) {
+        for (rate, params) in self.rates.iter_mut().zip(&self.params) {
+            if let Some(params) = params {
+                *rate *= (-dt / params.tau_ms).exp();
+            }
+        }
+        for &neuron in fired {
+            if let Some(params) = &self.params[neuron as usize] {
+                self.rates[neuron as usize] += 1000.0 / params.tau_ms;
+            }
+        }
+    }
+
+    // Move each out-of-band neuron's threshold offset and set its scaling factor
+    pub(crate) fn regulate(&mut self, dt: f32) {
+        let seconds = dt / 1000.0;
+        self.scaling.fill(1.0);
+        for (neuron, params) in self.params.iter().enumerate() {
+            let Some(params) = params else {
+                continue;
+            };
+            let deviation = params.deviation(self.rates[neuron]);
+            if deviation == 0.0 {
+                continue;
+            }
+            let offset = &mut self.threshold_offsets[neuron];
+            *offset = (*offset - params.threshold_rate * deviation * seconds).clamp(-params.max_threshold_shift, params.max_threshold_shift);
+            self.scaling[neuron] = (1.0 + params.scaling_rate * deviation * seconds).max(0.0);
+        }
+    }
+
+    pub(crate) fn reset_rates(&mut self) {
+        for (rate, params) in self.rates.iter_mut().zip(&self.params) {
+            *rate = params.map_or(0.0, |params| (params.min_rate_hz + params.max_rate_hz) / 2.0);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Layer output hooks for activation visualization
+//
+// NeuralRuntime.record_activations(layer, handle) makes the runtime copy a layer's
+// outputs into a pool buffer (see alloc_buffer) every time its forward,
+// forward_batch, forward_tensor or forward_in_place runs a network, so JS can draw
+// neuron activity from a Float32Array view of the buffer without a second pass.
+// The values are the layer's own outputs, before the network's output mode; a
+// batch is written sample after sample. Whatever does not fit in the buffer is
+// dropped, and recorded_activations(layer) tells how many values the last pass
+// wrote. While any hook is set, forward_batch stays on the calling thread.
+//
+// Hooks name layers by index and apply to whichever network the runtime runs; a
+// network without the layer fails with LayerIndexOutOfRange before any work is
+// done. Freeing a hooked buffer removes its hook.
+//
+// Outputs are staged during the pass and copied into the pool after it, so the
+// buffer of forward_in_place can itself be hooked.
+
+use crate::allocator::PoolAllocator;
+use crate::error::{NeuralError, NeuralResult};
+
+#[derive(Debug, Clone)]
+struct Hook {
+    layer: usize,
+    handle: u32,
+    // Length of the buffer, read when each pass begins
+    capacity: usize,
+    staged: Vec<f32>,
+    // Values written by the last completed pass
+    recorded: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActivationHooks {
+    hooks: Vec<Hook>,
+}
+
+impl ActivationHooks {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    // Hook `layer` to `handle`, replacing any hook already on the layer
+    pub(crate) fn insert(&mut self, layer: usize, handle: u32) {
+        self.remove(layer);
+        self.hooks.push(Hook { layer, handle, capacity: 0, staged: Vec::new(), recorded: 0 });
+    }
+
+    pub(crate) fn remove(&mut self, layer: usize) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|hook| hook.layer != layer);
+        self.hooks.len() != before
+    }
+
+    pub(crate) fn remove_handle(&mut self, handle: u32) {
+        self.hooks.retain(|hook| hook.handle != handle);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.hooks.clear();
+    }
+
+    pub(crate) fn recorded(&self, layer: usize) -> Option<usize> {
+        self.hooks.iter().find(|hook| hook.layer == layer).map(|hook| hook.recorded)
+    }
+
+    // Start a pass through a network of `layers` layers
+    pub(crate) fn begin(&mut self, layers: usize, pool: &PoolAllocator) -> NeuralResult<()> {
+        for hook in self.hooks.iter_mut() {
+            if hook.layer >= layers {
+                return Err(NeuralError::LayerIndexOutOfRange { index: hook.layer, count: layers });
+            }
+            hook.capacity = pool.get(hook.handle)?.len();
+            hook.staged.clear();
+        }
+        Ok(())
+    }
+
+    // Outputs of `layer` for the samples just run
+    pub(crate) fn observe(&mut self, layer: usize, outputs: &[f32]) {
+        if let Some(hook) = self.hooks.iter_mut().find(|hook| hook.layer == layer) {
+            let room = hook.capacity - hook.staged.len();
+            hook.staged.extend_from_slice(&outputs[..outputs.len().min(room)]);
+        }
+    }
+
+    // Copy the staged outputs into their buffers once the pass has succeeded
+    pub(crate) fn finish(&mut self, pool: &mut PoolAllocator) -> NeuralResult<()> {
+        for hook in self.hooks.iter_mut() {
+            pool.get_mut(hook.handle)?[..hook.staged.len()].copy_from_slice(&hook.staged);
+            hook.recorded = hook.staged.len();
+        }
+        Ok(())
+    }
+}
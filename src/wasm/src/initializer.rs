@@ -0,0 +1,74 @@
+// Weight initialization schemes parameterized by fan-in / fan-out
+//
+// Each scheme fixes the weight variance; the distribution then samples with it:
+//   Xavier (Glorot): 2 / (fan_in + fan_out)   suits tanh / sigmoid
+//   He (Kaiming):    2 / fan_in               suits ReLU family
+//   LeCun:           1 / fan_in               suits linear / SELU-style units
+// Uniform draws from [-sqrt(3·var), sqrt(3·var)], Normal from N(0, var).
+
+use wasm_bindgen::prelude::*;
+
+use crate::rng::Rng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitScheme {
+    Zeros = 0,
+    Xavier = 1,
+    He = 2,
+    LeCun = 3,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitDistribution {
+    Uniform = 0,
+    Normal = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Initializer {
+    pub scheme: InitScheme,
+    pub distribution: InitDistribution,
+}
+
+impl Default for Initializer {
+    fn default() -> Self {
+        Initializer { scheme: InitScheme::Zeros, distribution: InitDistribution::Uniform }
+    }
+}
+
+impl Initializer {
+    pub fn variance(&self, fan_in: usize, fan_out: usize) -> f32 {
+        let (fan_in, fan_out) = (fan_in.max(1) as f32, fan_out.max(1) as f32);
+        match self.scheme {
+            InitScheme::Zeros => 0.0,
+            InitScheme::Xavier => 2.0 / (fan_in + fan_out),
+            InitScheme::He => 2.0 / fan_in,
+            InitScheme::LeCun => 1.0 / fan_in,
+        }
+    }
+
+    pub fn fill(&self, weights: &mut [f32], fan_in: usize, fan_out: usize, rng: &mut Rng) {
+        let variance = self.variance(fan_in, fan_out);
+        if variance == 0.0 {
+            weights.fill(0.0);
+            return;
+        }
+
+        match self.distribution {
+            InitDistribution::Uniform => {
+                let limit = (3.0 * variance).sqrt();
+                for weight in weights.iter_mut() {
+                    *weight = rng.uniform(-limit, limit);
+                }
+            }
+            InitDistribution::Normal => {
+                let std_dev = variance.sqrt();
+                for weight in weights.iter_mut() {
+                    *weight = rng.normal() * std_dev;
+                }
+            }
+        }
+    }
+}
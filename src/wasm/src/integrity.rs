@@ -0,0 +1,801 @@
+// Integrity checks for serialized blobs: CRC32, SHA-256 and Ed25519 signatures
+//
+// Weight blobs and snapshots fetched from remote peers can be checked before they
+// are parsed. An IntegrityCheck holds what the blob is expected to match: any of a
+// CRC32 (catches corruption in transit), a SHA-256 digest (pins the exact blob, e.g.
+// from a manifest) and an Ed25519 signature with the signer's public key (proves
+// who produced it). verify() checks them in that order and fails with
+// NeuralError::IntegrityViolation on the first mismatch, before a single byte is
+// decoded. NeuralNetwork.from_weights_verified / import_weights_verified,
+// RuntimeSnapshot.from_bytes_verified and NeuralRuntime.restore_verified run the
+// check and refuse to load a blob that fails it.
+//
+// The primitives are implemented here rather than pulled in as dependencies:
+//   crc32           IEEE 802.3 (reflected, polynomial 0xEDB88320), as in zip and png
+//   sha256/sha512   FIPS 180-4
+//   verify_ed25519  RFC 8032 Ed25519 verification (not Ed25519ph or Ed25519ctx).
+//                   Public keys and signatures must be canonical encodings and S
+//                   must be below the group order, so signatures are not
+//                   malleable; the check is the cofactorless [S]B = R + [k]A.
+// Everything checked is public, so none of this needs to run in constant time.
+// Signing stays with the peer holding the private key (e.g. WebCrypto).
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::NeuralError;
+use crate::logging::{log_event, LogLevel};
+
+pub const ED25519_PUBLIC_KEY_LEN: usize = 32;
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+pub const SHA256_LEN: usize = 32;
+
+// Expected checksum, digest and/or signature of a blob
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IntegrityCheck {
+    crc32: Option<u32>,
+    sha256: Option<[u8; SHA256_LEN]>,
+    // Signer's public key and its signature over the whole blob
+    signature: Option<([u8; ED25519_PUBLIC_KEY_LEN], [u8; ED25519_SIGNATURE_LEN])>,
+}
+
+#[wasm_bindgen]
+impl IntegrityCheck {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> IntegrityCheck {
+        IntegrityCheck::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn expect_crc32(&mut self, crc: u32) {
+        self.crc32 = Some(crc);
+    }
+
+    #[wasm_bindgen]
+    pub fn expect_sha256(&mut self, digest: &[u8]) -> Result<(), NeuralError> {
+        let digest = digest
+            .try_into()
+            .map_err(|_| NeuralError::InvalidConfiguration(format!("SHA-256 digest must be {} bytes, got {}", SHA256_LEN, digest.len())))?;
+        self.sha256 = Some(digest);
+        Ok(())
+    }
+
+    // Require an Ed25519 signature over the blob by the holder of `public_key`
+    #[wasm_bindgen]
+    pub fn expect_signature(&mut self, public_key: &[u8], signature: &[u8]) -> Result<(), NeuralError> {
+        let key: [u8; ED25519_PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+            NeuralError::InvalidConfiguration(format!("Ed25519 public key must be {} bytes, got {}", ED25519_PUBLIC_KEY_LEN, public_key.len()))
+        })?;
+        let signature: [u8; ED25519_SIGNATURE_LEN] = signature.try_into().map_err(|_| {
+            NeuralError::InvalidConfiguration(format!("Ed25519 signature must be {} bytes, got {}", ED25519_SIGNATURE_LEN, signature.len()))
+        })?;
+        if Point::decompress(&key).is_none() {
+            return Err(NeuralError::InvalidConfiguration("Ed25519 public key is not a valid curve point".to_string()));
+        }
+        self.signature = Some((key, signature));
+        Ok(())
+    }
+
+    // Check `bytes` against every expectation; a check with none configured is an error
+    // rather than a silent pass
+    #[wasm_bindgen]
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), NeuralError> {
+        if self.crc32.is_none() && self.sha256.is_none() && self.signature.is_none() {
+            return Err(NeuralError::InvalidConfiguration("integrity check has no checksum, digest or signature to verify".to_string()));
+        }
+        if let Some(expected) = self.crc32 {
+            let actual = crc32(bytes);
+            if actual != expected {
+                return Err(violation(format!("CRC32 {:08x} does not match expected {:08x}", actual, expected)));
+            }
+        }
+        if let Some(expected) = self.sha256 {
+            if sha256_digest(bytes) != expected {
+                return Err(violation("SHA-256 digest does not match".to_string()));
+            }
+        }
+        if let Some((key, signature)) = &self.signature {
+            if !verify_ed25519(key, bytes, signature) {
+                return Err(violation("Ed25519 signature does not verify".to_string()));
+            }
+        }
+        log_event!(LogLevel::Debug, "integrity", "verified {} bytes", bytes.len());
+        Ok(())
+    }
+}
+
+fn violation(reason: String) -> NeuralError {
+    log_event!(LogLevel::Warn, "integrity", "refusing blob: {}", reason);
+    NeuralError::IntegrityViolation(reason)
+}
+
+#[wasm_bindgen]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[wasm_bindgen]
+pub fn sha256(bytes: &[u8]) -> Vec<u8> {
+    sha256_digest(bytes).to_vec()
+}
+
+// RFC 8032 Ed25519 verification of `signature` over `message`; false for keys and
+// signatures of the wrong length or with non-canonical encodings
+#[wasm_bindgen]
+pub fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let (Ok(key), Ok(signature)) = (<&[u8; 32]>::try_from(public_key), <&[u8; 64]>::try_from(signature)) else {
+        return false;
+    };
+    let Some(a) = Point::decompress(key) else {
+        return false;
+    };
+    let (r, s) = signature.split_at(32);
+    if !scalar_is_canonical(s) {
+        return false;
+    }
+    let mut hashed = Vec::with_capacity(64 + message.len());
+    hashed.extend_from_slice(r);
+    hashed.extend_from_slice(key);
+    hashed.extend_from_slice(message);
+    let k = reduce_scalar(&sha512_digest(&hashed));
+    // [S]B - [k]A must encode to R
+    let check = Point::base().mul(s).add(&a.neg().mul(&k));
+    check.compress()[..] == *r
+}
+
+// ---------------------------------------------------------------------------
+// SHA-2
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a, 0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19,
+];
+
+const SHA256_ROUNDS: [u32; 64] = [
+    0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5, 0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+    0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3, 0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+    0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc, 0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+    0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7, 0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+    0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13, 0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+    0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3, 0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+    0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5, 0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+    0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208, 0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+const SHA512_INIT: [u64; 8] = [
+    0x6a09_e667_f3bc_c908, 0xbb67_ae85_84ca_a73b, 0x3c6e_f372_fe94_f82b, 0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1, 0x9b05_688c_2b3e_6c1f, 0x1f83_d9ab_fb41_bd6b, 0x5be0_cd19_137e_2179,
+];
+
+const SHA512_ROUNDS: [u64; 80] = [
+    0x428a_2f98_d728_ae22, 0x7137_4491_23ef_65cd, 0xb5c0_fbcf_ec4d_3b2f, 0xe9b5_dba5_8189_dbbc,
+    0x3956_c25b_f348_b538, 0x59f1_11f1_b605_d019, 0x923f_82a4_af19_4f9b, 0xab1c_5ed5_da6d_8118,
+    0xd807_aa98_a303_0242, 0x1283_5b01_4570_6fbe, 0x2431_85be_4ee4_b28c, 0x550c_7dc3_d5ff_b4e2,
+    0x72be_5d74_f27b_896f, 0x80de_b1fe_3b16_96b1, 0x9bdc_06a7_25c7_1235, 0xc19b_f174_cf69_2694,
+    0xe49b_69c1_9ef1_4ad2, 0xefbe_4786_384f_25e3, 0x0fc1_9dc6_8b8c_d5b5, 0x240c_a1cc_77ac_9c65,
+    0x2de9_2c6f_592b_0275, 0x4a74_84aa_6ea6_e483, 0x5cb0_a9dc_bd41_fbd4, 0x76f9_88da_8311_53b5,
+    0x983e_5152_ee66_dfab, 0xa831_c66d_2db4_3210, 0xb003_27c8_98fb_213f, 0xbf59_7fc7_beef_0ee4,
+    0xc6e0_0bf3_3da8_8fc2, 0xd5a7_9147_930a_a725, 0x06ca_6351_e003_826f, 0x1429_2967_0a0e_6e70,
+    0x27b7_0a85_46d2_2ffc, 0x2e1b_2138_5c26_c926, 0x4d2c_6dfc_5ac4_2aed, 0x5338_0d13_9d95_b3df,
+    0x650a_7354_8baf_63de, 0x766a_0abb_3c77_b2a8, 0x81c2_c92e_47ed_aee6, 0x9272_2c85_1482_353b,
+    0xa2bf_e8a1_4cf1_0364, 0xa81a_664b_bc42_3001, 0xc24b_8b70_d0f8_9791, 0xc76c_51a3_0654_be30,
+    0xd192_e819_d6ef_5218, 0xd699_0624_5565_a910, 0xf40e_3585_5771_202a, 0x106a_a070_32bb_d1b8,
+    0x19a4_c116_b8d2_d0c8, 0x1e37_6c08_5141_ab53, 0x2748_774c_df8e_eb99, 0x34b0_bcb5_e19b_48a8,
+    0x391c_0cb3_c5c9_5a63, 0x4ed8_aa4a_e341_8acb, 0x5b9c_ca4f_7763_e373, 0x682e_6ff3_d6b2_b8a3,
+    0x748f_82ee_5def_b2fc, 0x78a5_636f_4317_2f60, 0x84c8_7814_a1f0_ab72, 0x8cc7_0208_1a64_39ec,
+    0x90be_fffa_2363_1e28, 0xa450_6ceb_de82_bde9, 0xbef9_a3f7_b2c6_7915, 0xc671_78f2_e372_532b,
+    0xca27_3ece_ea26_619c, 0xd186_b8c7_21c0_c207, 0xeada_7dd6_cde0_eb1e, 0xf57d_4f7f_ee6e_d178,
+    0x06f0_67aa_7217_6fba, 0x0a63_7dc5_a2c8_98a6, 0x113f_9804_bef9_0dae, 0x1b71_0b35_131c_471b,
+    0x28db_77f5_2304_7d84, 0x32ca_ab7b_40c7_2493, 0x3c9e_be0a_15c9_bebc, 0x431d_67c4_9c10_0d4c,
+    0x4cc5_d4be_cb3e_42b6, 0x597f_299c_fc65_7e2a, 0x5fcb_6fab_3ad6_faec, 0x6c44_198c_4a47_5817,
+];
+
+pub(crate) fn sha256_digest(bytes: &[u8]) -> [u8; 32] {
+    let mut state = SHA256_INIT;
+    for block in padded_blocks(bytes, 64, 8).chunks_exact(64) {
+        sha256_compress(&mut state, block);
+    }
+    let mut digest = [0u8; 32];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+pub(crate) fn sha512_digest(bytes: &[u8]) -> [u8; 64] {
+    let mut state = SHA512_INIT;
+    for block in padded_blocks(bytes, 128, 16).chunks_exact(128) {
+        sha512_compress(&mut state, block);
+    }
+    let mut digest = [0u8; 64];
+    for (out, word) in digest.chunks_exact_mut(8).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+// The message followed by 0x80, zeros and its bit length in `length_bytes` big-endian
+// bytes, filling whole blocks
+fn padded_blocks(bytes: &[u8], block: usize, length_bytes: usize) -> Vec<u8> {
+    let mut padded = Vec::with_capacity(bytes.len() + block + length_bytes);
+    padded.extend_from_slice(bytes);
+    padded.push(0x80);
+    while padded.len() % block != block - length_bytes {
+        padded.push(0);
+    }
+    let bits = (bytes.len() as u128) * 8;
+    padded.extend_from_slice(&bits.to_be_bytes()[16 - length_bytes..]);
+    padded
+}
+
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = w[t - 16].wrapping_add(s0).wrapping_add(w[t - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(SHA256_ROUNDS[t]).wrapping_add(w[t]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(majority);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+fn sha512_compress(state: &mut [u64; 8], block: &[u8]) {
+    let mut w = [0u64; 80];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().expect("8-byte chunk"));
+    }
+    for t in 16..80 {
+        let s0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+        let s1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+        w[t] = w[t - 16].wrapping_add(s0).wrapping_add(w[t - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let choice = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(SHA512_ROUNDS[t]).wrapping_add(w[t]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(majority);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Field arithmetic modulo p = 2^255 - 19, in five 51-bit limbs
+
+const LIMB_MASK: u64 = (1 << 51) - 1;
+
+// Exponents as little-endian bytes
+const P_MINUS_2: [u8; 32] = exponent(0xeb, 0x7f);
+// (p - 5) / 8 = 2^252 - 3
+const P_MINUS_5_OVER_8: [u8; 32] = exponent(0xfd, 0x0f);
+
+// `low`, thirty 0xff bytes, `high`
+const fn exponent(low: u8, high: u8) -> [u8; 32] {
+    let mut bytes = [0xff; 32];
+    bytes[0] = low;
+    bytes[31] = high;
+    bytes
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fe([u64; 5]);
+
+impl Fe {
+    const ZERO: Fe = Fe([0; 5]);
+    const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+    // d = -121665 / 121666, the edwards25519 curve constant, and 2d
+    const D: Fe = Fe([0x0003_4dca_1359_78a3, 0x0001_a828_3b15_6ebd, 0x0005_e7a2_6001_c029, 0x0007_39c6_63a0_3cbb, 0x0005_2036_cee2_b6ff]);
+    const D2: Fe = Fe([0x0006_9b94_26b2_f159, 0x0003_5050_762a_dd7a, 0x0003_cf44_c003_8052, 0x0006_738c_c740_7977, 0x0002_406d_9dc5_6dff]);
+    // A square root of -1
+    const SQRT_M1: Fe = Fe([0x0006_1b27_4a0e_a0b0, 0x0000_d5a5_fc8f_189d, 0x0007_ef5e_9cbd_0c60, 0x0007_8595_a680_4c9e, 0x0002_b832_4804_fc1d]);
+
+    // Little-endian, ignoring the top bit
+    fn from_bytes(bytes: &[u8; 32]) -> Fe {
+        let load = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().expect("8 bytes"));
+        Fe([
+            load(0) & LIMB_MASK,
+            (load(6) >> 3) & LIMB_MASK,
+            (load(12) >> 6) & LIMB_MASK,
+            (load(19) >> 1) & LIMB_MASK,
+            (load(24) >> 12) & LIMB_MASK,
+        ])
+    }
+
+    // Canonical little-endian encoding (fully reduced below p)
+    fn to_bytes(self) -> [u8; 32] {
+        let mut limbs = Fe::carry(Fe::carry(self.0).0).0;
+        // Add 19 and see whether it carries out of 2^255, i.e. whether the value is >= p
+        let mut q = (limbs[0] + 19) >> 51;
+        for limb in &limbs[1..] {
+            q = (limb + q) >> 51;
+        }
+        limbs[0] += 19 * q;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> 51;
+            limbs[i] &= LIMB_MASK;
+        }
+        limbs[4] &= LIMB_MASK;
+
+        let mut bytes = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut acc_bits = 0;
+        let mut out = 0;
+        for limb in limbs {
+            acc |= (limb as u128) << acc_bits;
+            acc_bits += 51;
+            while acc_bits >= 8 && out < 32 {
+                bytes[out] = acc as u8;
+                acc >>= 8;
+                acc_bits -= 8;
+                out += 1;
+            }
+        }
+        if out < 32 {
+            bytes[out] = acc as u8;
+        }
+        bytes
+    }
+
+    fn carry(mut limbs: [u64; 5]) -> Fe {
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> 51;
+            limbs[i] &= LIMB_MASK;
+        }
+        limbs[0] += 19 * (limbs[4] >> 51);
+        limbs[4] &= LIMB_MASK;
+        Fe(limbs)
+    }
+
+    fn add(self, rhs: Fe) -> Fe {
+        let mut limbs = self.0;
+        for (limb, other) in limbs.iter_mut().zip(rhs.0) {
+            *limb += other;
+        }
+        Fe::carry(limbs)
+    }
+
+    // Adds 4p first so the limbs never underflow
+    fn sub(self, rhs: Fe) -> Fe {
+        let bias = [4 * (LIMB_MASK - 18), 4 * LIMB_MASK, 4 * LIMB_MASK, 4 * LIMB_MASK, 4 * LIMB_MASK];
+        let mut limbs = self.0;
+        for ((limb, other), bias) in limbs.iter_mut().zip(rhs.0).zip(bias) {
+            *limb = *limb + bias - other;
+        }
+        Fe::carry(limbs)
+    }
+
+    fn neg(self) -> Fe {
+        Fe::ZERO.sub(self)
+    }
+
+    fn mul(self, rhs: Fe) -> Fe {
+        let [a0, a1, a2, a3, a4] = self.0;
+        let [b0, b1, b2, b3, b4] = rhs.0;
+        let m = |x: u64, y: u64| x as u128 * y as u128;
+        // 2^255 = 19 (mod p), so limb products past the top wrap around times 19
+        let (b1_19, b2_19, b3_19, b4_19) = (b1 * 19, b2 * 19, b3 * 19, b4 * 19);
+        let c0 = m(a0, b0) + m(a4, b1_19) + m(a3, b2_19) + m(a2, b3_19) + m(a1, b4_19);
+        let mut c1 = m(a1, b0) + m(a0, b1) + m(a4, b2_19) + m(a3, b3_19) + m(a2, b4_19);
+        let mut c2 = m(a2, b0) + m(a1, b1) + m(a0, b2) + m(a4, b3_19) + m(a3, b4_19);
+        let mut c3 = m(a3, b0) + m(a2, b1) + m(a1, b2) + m(a0, b3) + m(a4, b4_19);
+        let mut c4 = m(a4, b0) + m(a3, b1) + m(a2, b2) + m(a1, b3) + m(a0, b4);
+
+        let mask = LIMB_MASK as u128;
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+        let limbs = [
+            (c0 & mask) as u64 + 19 * (c4 >> 51) as u64,
+            (c1 & mask) as u64,
+            (c2 & mask) as u64,
+            (c3 & mask) as u64,
+            (c4 & mask) as u64,
+        ];
+        Fe::carry(limbs)
+    }
+
+    fn square(self) -> Fe {
+        self.mul(self)
+    }
+
+    // self^exponent for a little-endian exponent
+    fn pow(self, exponent: &[u8; 32]) -> Fe {
+        let mut result = Fe::ONE;
+        for bit in (0..256).rev() {
+            result = result.square();
+            if (exponent[bit / 8] >> (bit % 8)) & 1 == 1 {
+                result = result.mul(self);
+            }
+        }
+        result
+    }
+
+    fn invert(self) -> Fe {
+        self.pow(&P_MINUS_2)
+    }
+
+    fn is_zero(self) -> bool {
+        self.to_bytes() == [0; 32]
+    }
+
+    fn equals(self, other: Fe) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+
+    fn is_odd(self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Points on edwards25519 (-x^2 + y^2 = 1 + d x^2 y^2) in extended coordinates
+
+// x = X/Z, y = Y/Z, x*y = T/Z
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl Point {
+    fn identity() -> Point {
+        Point { x: Fe::ZERO, y: Fe::ONE, z: Fe::ONE, t: Fe::ZERO }
+    }
+
+    // The standard base point, y = 4/5 with x even
+    fn base() -> Point {
+        let mut encoded = [0x66; 32];
+        encoded[0] = 0x58;
+        Point::decompress(&encoded).expect("base point decodes")
+    }
+
+    // RFC 8032 section 5.1.3; None for non-canonical y or points off the curve
+    fn decompress(bytes: &[u8; 32]) -> Option<Point> {
+        let x_odd = bytes[31] >> 7 == 1;
+        let y = Fe::from_bytes(bytes);
+        let mut canonical = *bytes;
+        canonical[31] &= 0x7f;
+        if y.to_bytes() != canonical {
+            return None;
+        }
+
+        let y2 = y.square();
+        let u = y2.sub(Fe::ONE);
+        let v = Fe::D.mul(y2).add(Fe::ONE);
+        // Candidate square root of u/v: u v^3 (u v^7)^((p-5)/8)
+        let v3 = v.square().mul(v);
+        let v7 = v3.square().mul(v);
+        let mut x = u.mul(v3).mul(u.mul(v7).pow(&P_MINUS_5_OVER_8));
+        let vx2 = v.mul(x.square());
+        if vx2.equals(u.neg()) {
+            x = x.mul(Fe::SQRT_M1);
+        } else if !vx2.equals(u) {
+            return None;
+        }
+        if x.is_zero() && x_odd {
+            return None;
+        }
+        if x.is_odd() != x_odd {
+            x = x.neg();
+        }
+        Some(Point { x, y, z: Fe::ONE, t: x.mul(y) })
+    }
+
+    fn compress(&self) -> [u8; 32] {
+        let z_inv = self.z.invert();
+        let x = self.x.mul(z_inv);
+        let mut bytes = self.y.mul(z_inv).to_bytes();
+        bytes[31] |= (x.is_odd() as u8) << 7;
+        bytes
+    }
+
+    fn neg(&self) -> Point {
+        Point { x: self.x.neg(), y: self.y, z: self.z, t: self.t.neg() }
+    }
+
+    // Unified addition (RFC 8032 section 5.1.4), also used for doubling
+    fn add(&self, other: &Point) -> Point {
+        let a = self.y.sub(self.x).mul(other.y.sub(other.x));
+        let b = self.y.add(self.x).mul(other.y.add(other.x));
+        let c = self.t.mul(Fe::D2).mul(other.t);
+        let d = self.z.add(self.z).mul(other.z);
+        let (e, f, g, h) = (b.sub(a), d.sub(c), d.add(c), b.add(a));
+        Point { x: e.mul(f), y: g.mul(h), z: f.mul(g), t: e.mul(h) }
+    }
+
+    // [scalar]P for a 32-byte little-endian scalar
+    fn mul(&self, scalar: &[u8]) -> Point {
+        let mut result = Point::identity();
+        for bit in (0..scalar.len() * 8).rev() {
+            result = result.add(&result);
+            if (scalar[bit / 8] >> (bit % 8)) & 1 == 1 {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scalars modulo the group order L = 2^252 + 27742317777372353535851937790883648493
+
+const GROUP_ORDER: [u64; 4] = [0x5812_631a_5cf5_d3ed, 0x14de_f9de_a2f7_9cd6, 0, 0x1000_0000_0000_0000];
+
+fn below_group_order(limbs: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if limbs[i] != GROUP_ORDER[i] {
+            return limbs[i] < GROUP_ORDER[i];
+        }
+    }
+    false
+}
+
+fn scalar_is_canonical(bytes: &[u8]) -> bool {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"));
+    }
+    below_group_order(&limbs)
+}
+
+// A little-endian number of any length reduced modulo L, by shift and subtract
+fn reduce_scalar(bytes: &[u8]) -> [u8; 32] {
+    let mut limbs = [0u64; 4];
+    for bit in (0..bytes.len() * 8).rev() {
+        // limbs < L < 2^253, so doubling cannot overflow
+        for i in (1..4).rev() {
+            limbs[i] = (limbs[i] << 1) | (limbs[i - 1] >> 63);
+        }
+        limbs[0] = (limbs[0] << 1) | ((bytes[bit / 8] >> (bit % 8)) & 1) as u64;
+        if !below_group_order(&limbs) {
+            let mut borrow = 0u64;
+            for (limb, order) in limbs.iter_mut().zip(GROUP_ORDER) {
+                let (value, under) = limb.overflowing_sub(order);
+                let (value, under_borrow) = value.overflowing_sub(borrow);
+                *limb = value;
+                borrow = (under || under_borrow) as u64;
+            }
+        }
+    }
+    let mut out = [0u8; 32];
+    for (chunk, limb) in out.chunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+    }
+
+    // RFC 8032 section 7.1, tests 1 to 3: (public key, message, signature)
+    const RFC8032: [(&str, &str, &str); 3] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+    ];
+
+    // Little-endian encodings of L and p = 2^255 - 19
+    fn group_order_bytes() -> Vec<u8> {
+        GROUP_ORDER.iter().flat_map(|limb| limb.to_le_bytes()).collect()
+    }
+
+    fn field_prime_bytes() -> [u8; 32] {
+        let mut p = [0xff; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        p
+    }
+
+    #[test]
+    fn crc32_matches_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn sha256_matches_fips_vectors() {
+        assert_eq!(sha256(b""), hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+        assert_eq!(sha256(b"abc"), hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+        // Two-block message: the length no longer fits beside the padding
+        assert_eq!(
+            sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+        assert_eq!(sha256(&[b'a'; 1_000_000]), hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"));
+    }
+
+    #[test]
+    fn sha512_matches_fips_vectors() {
+        assert_eq!(
+            sha512_digest(b"").to_vec(),
+            hex("cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e")
+        );
+        assert_eq!(
+            sha512_digest(b"abc").to_vec(),
+            hex("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")
+        );
+        assert_eq!(
+            sha512_digest(&[b'a'; 1_000_000]).to_vec(),
+            hex("e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b")
+        );
+    }
+
+    #[test]
+    fn ed25519_accepts_rfc_vectors() {
+        for (key, message, signature) in RFC8032 {
+            assert!(verify_ed25519(&hex(key), &hex(message), &hex(signature)), "vector with message {:?}", message);
+        }
+    }
+
+    #[test]
+    fn ed25519_rejects_tampering() {
+        for (key, message, signature) in RFC8032 {
+            let (key, message, signature) = (hex(key), hex(message), hex(signature));
+            // One flipped bit anywhere in R or S
+            for bit in [0, 7, 255, 256, 300, 503] {
+                let mut flipped = signature.clone();
+                flipped[bit / 8] ^= 1 << (bit % 8);
+                assert!(!verify_ed25519(&key, &message, &flipped), "bit {}", bit);
+            }
+            let mut altered = message.clone();
+            altered.push(0);
+            assert!(!verify_ed25519(&key, &altered, &signature));
+        }
+        // A valid signature under another vector's key
+        let (key, _, _) = RFC8032[1];
+        let (_, message, signature) = RFC8032[0];
+        assert!(!verify_ed25519(&hex(key), &hex(message), &hex(signature)));
+    }
+
+    #[test]
+    fn ed25519_rejects_malleable_scalars() {
+        let (key, message, signature) = RFC8032[0];
+        let (key, message, signature) = (hex(key), hex(message), hex(signature));
+        // S + L is the same scalar mod L, so only the range check refuses it
+        let mut s_plus_l = signature.clone();
+        let mut carry = 0u16;
+        for (byte, order) in s_plus_l[32..].iter_mut().zip(group_order_bytes()) {
+            let sum = *byte as u16 + order as u16 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(carry, 0);
+        assert!(!verify_ed25519(&key, &message, &s_plus_l));
+
+        let mut s_is_l = signature.clone();
+        s_is_l[32..].copy_from_slice(&group_order_bytes());
+        assert!(!verify_ed25519(&key, &message, &s_is_l));
+        assert!(!scalar_is_canonical(&group_order_bytes()));
+    }
+
+    #[test]
+    fn ed25519_rejects_non_canonical_points() {
+        let (key, message, signature) = RFC8032[0];
+        let (key, message, signature) = (hex(key), hex(message), hex(signature));
+        // y = p + 1 encodes y = 1 (the identity) without being reduced
+        let mut y_above_p = field_prime_bytes();
+        y_above_p[0] += 1;
+        assert!(Point::decompress(&y_above_p).is_none());
+        // The identity with x = 0 but the sign bit set
+        let mut negative_zero = [0u8; 32];
+        negative_zero[0] = 1;
+        negative_zero[31] = 0x80;
+        assert!(Point::decompress(&negative_zero).is_none());
+
+        for bad in [y_above_p, negative_zero] {
+            assert!(!verify_ed25519(&bad, &message, &signature));
+            let mut bad_r = signature.clone();
+            bad_r[..32].copy_from_slice(&bad);
+            assert!(!verify_ed25519(&key, &message, &bad_r));
+        }
+    }
+
+    #[test]
+    fn ed25519_rejects_wrong_lengths() {
+        let (key, message, signature) = RFC8032[0];
+        let (key, message, signature) = (hex(key), hex(message), hex(signature));
+        assert!(!verify_ed25519(&key[..31], &message, &signature));
+        assert!(!verify_ed25519(&[key.clone(), vec![0]].concat(), &message, &signature));
+        assert!(!verify_ed25519(&key, &message, &signature[..63]));
+        assert!(!verify_ed25519(&key, &message, &[signature.clone(), vec![0]].concat()));
+
+        let mut check = IntegrityCheck::new();
+        assert!(matches!(check.expect_signature(&key[..31], &signature), Err(NeuralError::InvalidConfiguration(_))));
+        assert!(matches!(check.expect_signature(&key, &signature[..63]), Err(NeuralError::InvalidConfiguration(_))));
+        assert!(matches!(check.expect_signature(&field_prime_bytes(), &signature), Err(NeuralError::InvalidConfiguration(_))));
+        assert!(matches!(check.expect_sha256(&[0; 31]), Err(NeuralError::InvalidConfiguration(_))));
+    }
+
+    #[test]
+    fn reduces_scalars_modulo_group_order() {
+        assert_eq!(reduce_scalar(&group_order_bytes()), [0; 32]);
+        let mut l_plus_one = group_order_bytes();
+        l_plus_one[0] += 1;
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(reduce_scalar(&l_plus_one), one);
+        // (2^512 - 1) mod L
+        assert_eq!(reduce_scalar(&[0xff; 64]).to_vec(), hex("000f9c44e31106a447938568a71b0ed065bef517d273ecce3d9a307c1b419903"));
+    }
+
+    #[test]
+    fn integrity_check_verifies_in_order() {
+        let (key, message, signature) = RFC8032[2];
+        let (key, message, signature) = (hex(key), hex(message), hex(signature));
+        let mut check = IntegrityCheck::new();
+        assert!(matches!(check.verify(&message), Err(NeuralError::InvalidConfiguration(_))));
+
+        check.expect_crc32(crc32(&message));
+        check.expect_sha256(&sha256(&message)).unwrap();
+        check.expect_signature(&key, &signature).unwrap();
+        check.verify(&message).unwrap();
+
+        let tampered = [message.clone(), vec![0]].concat();
+        match check.verify(&tampered) {
+            Err(NeuralError::IntegrityViolation(reason)) => assert!(reason.starts_with("CRC32"), "{}", reason),
+            other => panic!("unexpected {:?}", other),
+        }
+        let mut signature_only = IntegrityCheck::new();
+        signature_only.expect_signature(&key, &signature).unwrap();
+        assert!(matches!(signature_only.verify(&tampered), Err(NeuralError::IntegrityViolation(_))));
+    }
+}
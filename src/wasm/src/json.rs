@@ -0,0 +1,243 @@
+// Minimal JSON (RFC 8259) reader for declarative inputs such as model specs
+//
+// Objects keep their members in document order, so callers can report the first
+// unknown key. Syntax errors give the line and column of the offending character.
+
+use crate::error::{NeuralError, NeuralResult};
+
+// Nesting bound, so hostile input cannot exhaust the stack
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub(crate) fn parse(text: &str) -> NeuralResult<JsonValue> {
+        let mut parser = Parser { text, offset: 0 };
+        let value = parser.value(0)?;
+        parser.whitespace();
+        if parser.offset < text.len() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    // Name of the value's type, for error messages
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "a boolean",
+            JsonValue::Number(_) => "a number",
+            JsonValue::String(_) => "a string",
+            JsonValue::Array(_) => "an array",
+            JsonValue::Object(_) => "an object",
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> NeuralError {
+        let before = &self.text[..self.offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |tail| tail.chars().count()) + 1;
+        NeuralError::InvalidFormat(format!("JSON line {} column {}: {}", line, column, reason))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> NeuralResult<()> {
+        self.whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> NeuralResult<JsonValue> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting is too deep"));
+        }
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => Ok(JsonValue::String(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => {
+                for (word, value) in [("true", JsonValue::Bool(true)), ("false", JsonValue::Bool(false)), ("null", JsonValue::Null)] {
+                    if self.text[self.offset..].starts_with(word) {
+                        self.offset += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> NeuralResult<JsonValue> {
+        self.offset += 1;
+        let mut members: Vec<(String, JsonValue)> = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b'}') {
+            self.offset += 1;
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let start = self.offset;
+            let name = self.string()?;
+            if members.iter().any(|(existing, _)| *existing == name) {
+                self.offset = start;
+                return Err(self.error(&format!("duplicate member \"{}\"", name)));
+            }
+            self.expect(b':')?;
+            members.push((name, self.value(depth + 1)?));
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b'}') => {
+                    self.offset += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> NeuralResult<JsonValue> {
+        self.offset += 1;
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.peek() == Some(b']') {
+            self.offset += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b']') => {
+                    self.offset += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> NeuralResult<String> {
+        self.offset += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.offset..];
+            let Some(special) = rest.find(|c: char| c == '"' || c == '\\' || c < ' ') else {
+                return Err(self.error("unterminated string"));
+            };
+            out.push_str(&rest[..special]);
+            self.offset += special;
+            match self.peek() {
+                Some(b'"') => {
+                    self.offset += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.offset += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let unit = self.hex4()?;
+                            // Surrogate pairs arrive as two escapes
+                            let code = if (0xD800..0xDC00).contains(&unit) && self.text[self.offset + 1..].starts_with("\\u") {
+                                self.offset += 2;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("unpaired surrogate in \\u escape"));
+                                }
+                                0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                            } else {
+                                unit
+                            };
+                            char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.offset += 1;
+                    out.push(escaped);
+                }
+                _ => return Err(self.error("control character in string")),
+            }
+        }
+    }
+
+    // Four hex digits after "\u"; leaves the offset on the last digit
+    fn hex4(&mut self) -> NeuralResult<u32> {
+        let digits = self.text.get(self.offset + 1..self.offset + 5).ok_or_else(|| self.error("truncated \\u escape"))?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid \\u escape"))?;
+        self.offset += 4;
+        Ok(value)
+    }
+
+    fn number(&mut self) -> NeuralResult<JsonValue> {
+        let start = self.offset;
+        while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.offset += 1;
+        }
+        let literal = &self.text[start..self.offset];
+        // Rust's float parser accepts a superset of JSON numbers; rule out the extras
+        let digits = literal.strip_prefix('-').unwrap_or(literal);
+        let leading_zero = digits.starts_with('0') && digits.as_bytes().get(1).is_some_and(u8::is_ascii_digit);
+        let valid = digits.starts_with(|c: char| c.is_ascii_digit())
+            && !leading_zero
+            && !digits.contains(".e")
+            && !digits.contains(".E")
+            && !digits.ends_with('.');
+        match literal.parse::<f64>() {
+            Ok(value) if valid && value.is_finite() => Ok(JsonValue::Number(value)),
+            _ => {
+                self.offset = start;
+                Err(self.error(&format!("invalid number {}", literal)))
+            }
+        }
+    }
+}
@@ -2,211 +2,690 @@
 // This module provides high-performance neural network operations
 
 use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
 use std::arch::wasm32::*;
 
+mod activation;
+mod agent_pool;
+mod allocator;
+mod anomaly;
+mod attention;
+mod attestation;
+mod attribution;
+mod backend;
+mod bandit;
+mod bridge;
+mod budget;
+mod checked;
+mod checkpoint;
+mod clock;
+mod conv;
+mod dataset;
+mod diagnostics;
+mod distillation;
+mod early_exit;
+mod efficiency;
+mod embedding;
+mod encryption;
+mod error;
+mod event_queue;
+mod experience;
+mod fann_format;
+mod features;
+mod federated;
+mod fixed_point;
+mod fusion;
+mod genetic;
+mod gradient_optimizer;
+mod gradients;
+#[cfg(feature = "headless")]
+mod headless;
+mod homeostasis;
+mod hooks;
+mod initializer;
+mod integrity;
+mod json;
+mod linalg;
+mod logging;
+mod lora;
+mod loss;
+mod mesh;
+mod metrics;
+mod mixed_precision;
+mod model_spec;
+#[cfg(native_simd)]
+mod native_simd;
+mod neat;
+mod network;
+mod neuron_model;
+mod normalization;
+mod npy_format;
+mod online_stats;
+mod onnx_format;
+mod optimizer;
+mod parallel;
+mod partition;
+mod plasticity;
+mod precision;
+mod preprocess;
+mod privacy;
+mod profiler;
+mod quantization;
+mod raster;
+mod recurrent;
+mod reinforcement;
+mod replay;
+mod rng;
+mod scheduler;
+mod scratch;
+mod security;
+mod serialization;
+mod shared_region;
+#[cfg(target_feature = "simd128")]
+mod simd;
+mod snapshot;
+mod sparse;
+mod spike_coding;
+mod spiking;
+mod stats;
+mod stream;
+mod swarm;
+mod tasks;
+mod tensor;
+mod trace;
+mod training;
+#[cfg(feature = "webgpu")]
+mod webgpu;
+mod weight_diff;
+
+pub use activation::{argmax, softmax, ActivationAccuracy, ActivationKind};
+pub use agent_pool::AgentPool;
+pub use anomaly::{AnomalyConfig, AnomalyEvent, AnomalyMethod, AnomalyMonitor};
+pub use attention::{scaled_dot_product_attention, TransformerBlock};
+pub use attestation::{attestation_digest, Attestation};
+pub use attribution::{AttributionConfig, AttributionMethod};
+pub use backend::{webgpu_available, BackendKind};
+pub use bandit::{Bandit, BanditConfig, BanditStrategy};
+pub use bridge::MeshBridge;
+pub use budget::{BudgetConfig, BudgetReport, BudgetStatus, Downshift};
+pub use checked::{checked_mode, set_checked_mode};
+pub use checkpoint::{CheckpointReader, Checkpointer};
+pub use clock::{time_source, TimeSource};
+pub use dataset::{Dataset, DatasetBatch};
+pub use distillation::distill;
+pub use early_exit::{EarlyExitNetwork, EarlyExitOutput};
+pub use efficiency::{EfficiencyReport, EfficiencyWeights};
+pub use embedding::Embedding;
+pub use error::{NeuralError, NeuralResult};
+pub use experience::{ExperienceReplay, ReplayBatch};
+pub use features::{engine_simd_support, simd_build};
+pub use federated::{fed_avg, FederatedAverage};
+pub use fixed_point::Arithmetic;
+pub use fusion::ElementwisePipeline;
+pub use genetic::{GeneticConfig, WeightEvolution};
+pub use gradient_optimizer::{GradientOptimizerConfig, GradientOptimizerKind};
+pub use gradients::GradientAggregator;
+pub use homeostasis::HomeostasisParams;
+pub use initializer::{InitDistribution, InitScheme};
+pub use integrity::{crc32, sha256, verify_ed25519, IntegrityCheck};
+pub use linalg::matmul;
+pub use logging::{install_panic_hook, log_level, set_console_logging, set_log_level, set_log_sink, LogLevel};
+pub use loss::{LossFunction, LossKind};
+pub use mesh::MeshGraph;
+pub use mixed_precision::TrainingPrecision;
+pub use neat::{Genome, NeatConfig, NeatPopulation};
+pub use network::{LayerKind, NeuralNetwork, OutputMode};
+pub use neuron_model::{AdExParams, IzhikevichParams, NeuronModel};
+pub use online_stats::{QuantileSketch, RunningStats};
+pub use optimizer::{ConnectionStats, OptimizationReport, OptimizerKind, OptimizerParams};
+pub use partition::MeshPartition;
+pub use plasticity::{anti_hebbian_update, hebbian_update, oja_update, HebbianRule, StdpParams};
+pub use precision::Precision;
+pub use preprocess::{Preprocessor, ScalingMethod};
+pub use privacy::{GradientPrivacy, PrivacyConfig};
+pub use reinforcement::{DqnAgent, DqnConfig};
+pub use replay::ReplayReport;
+pub use rng::RandomSource;
+pub use scheduler::{EarlyStopping, LearningRateSchedule, ScheduleKind};
+pub use scratch::ScratchStats;
+pub use security::{SecurityPolicy, ViolationAction};
+pub use shared_region::SharedTensorRegion;
+pub use snapshot::{CompatibilityReport, RuntimeSnapshot};
+pub use sparse::SparseMatrix;
+pub use spike_coding::{SpikeCoder, SpikeCoding};
+pub use spiking::{LifParams, SpikeEvents, SpikingNetwork};
+pub use stats::{kahan_sum, l2_norm, summarize, Summary};
+pub use stream::StreamProcessor;
+pub use swarm::{JobStatus, SwarmExecutor};
+pub use tasks::CancellationToken;
+pub use tensor::{DType, Tensor};
+pub use training::{FaultStage, FitOptions, FitReport, TrainingFault, TrainingOutcome, TrainingSafety};
+#[cfg(feature = "webgpu")]
+pub use webgpu::GpuContext;
+pub use weight_diff::{diff_weights, WeightDiff};
+#[cfg(all(feature = "threads", js_host))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+use allocator::PoolAllocator;
+use backend::{Backend, ScalarBackend, SimdBackend};
+use budget::BudgetGuard;
+use clock::Clock;
+use features::simd_dispatch;
+use hooks::ActivationHooks;
+use logging::log_event;
+use metrics::{MetricKind, PrometheusWriter};
+use optimizer::ConnectionOptimizer;
+use profiler::Profiler;
+use rng::{Rng, SecureRng};
+use scratch::ScratchAllocator;
+use trace::{OpenSpan, Tracer};
+
 #[wasm_bindgen]
 pub struct NeuralRuntime {
-    memory_pool: Vec<f32>,
+    memory_pool: PoolAllocator,
+    rng: Rng,
+    secure_rng: Option<SecureRng>,
+    backend: Box<dyn Backend>,
+    #[cfg(feature = "webgpu")]
+    gpu: Option<webgpu::GpuContext>,
     simd_enabled: bool,
+    activation_accuracy: ActivationAccuracy,
+    thread_count: usize,
     operations_count: u32,
-    memory_usage: usize,
+    // Spikes counted by process_spike_train, the window they covered and the last rate
+    spikes_total: u64,
+    spike_window_ms: f64,
+    last_spike_rate: f32,
+    profiler: Profiler,
+    optimizer: Box<dyn ConnectionOptimizer>,
+    efficiency_weights: EfficiencyWeights,
+    scratch: ScratchAllocator,
+    budget: Option<BudgetGuard>,
+    tracer: Option<Tracer>,
+    security: SecurityPolicy,
+    activation_hooks: ActivationHooks,
+}
+
+impl Default for NeuralRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
 impl NeuralRuntime {
     #[wasm_bindgen(constructor)]
     pub fn new() -> NeuralRuntime {
-        NeuralRuntime {
-            memory_pool: Vec::with_capacity(1024 * 1024), // 1MB initial pool
+        logging::install_panic_hook();
+        let runtime = NeuralRuntime {
+            memory_pool: PoolAllocator::new(), // 1MB initial segment
+            rng: Rng::default(),
+            secure_rng: None,
+            backend: backend::best_cpu_backend(),
+            #[cfg(feature = "webgpu")]
+            gpu: None,
             simd_enabled: Self::detect_simd_support(),
+            activation_accuracy: ActivationAccuracy::Accurate,
+            thread_count: 1,
             operations_count: 0,
-            memory_usage: 0,
+            spikes_total: 0,
+            spike_window_ms: 0.0,
+            last_spike_rate: 0.0,
+            profiler: Profiler::new(),
+            optimizer: optimizer::default_optimizer(),
+            efficiency_weights: EfficiencyWeights::default(),
+            scratch: ScratchAllocator::new(),
+            budget: None,
+            tracer: None,
+            security: SecurityPolicy::default(),
+            activation_hooks: ActivationHooks::default(),
+        };
+        log_event!(LogLevel::Info, "runtime", "created with {:?} backend", runtime.backend.kind());
+        runtime
+    }
+
+    // Reseed the deterministic generator, making runs reproducible
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    // Choose between the fast deterministic generator and the platform CSPRNG
+    #[wasm_bindgen]
+    pub fn set_random_source(&mut self, source: RandomSource) {
+        self.secure_rng = match source {
+            RandomSource::Deterministic => None,
+            RandomSource::Secure => Some(SecureRng::new()),
+        };
+    }
+
+    #[wasm_bindgen]
+    pub fn random_source(&self) -> RandomSource {
+        if self.secure_rng.is_some() {
+            RandomSource::Secure
+        } else {
+            RandomSource::Deterministic
         }
     }
 
+    // False if secure mode had to fall back to a clock-seeded generator
+    #[wasm_bindgen]
+    pub fn secure_entropy_available(&self) -> bool {
+        self.secure_rng.as_ref().map_or_else(|| rng::fill_secure(&mut [0; 1]).is_ok(), |secure| !secure.is_degraded())
+    }
+
+    // Nonce bytes straight from the CSPRNG; never falls back to weak randomness
+    #[wasm_bindgen]
+    pub fn generate_nonce(&self, len: usize) -> Result<Vec<u8>, NeuralError> {
+        let mut nonce = vec![0; len];
+        rng::fill_secure(&mut nonce)?;
+        Ok(nonce)
+    }
+
     // SIMD Detection
     #[wasm_bindgen]
     pub fn simd_supported(&self) -> bool {
         self.simd_enabled
     }
 
-    fn detect_simd_support() -> bool {
-        // Check for WASM SIMD support at runtime
-        // This is simplified - in real implementation would use feature detection
-        true // Assume SIMD is available for now
+    // Force scalar kernels (e.g. when comparing results); SIMD is only re-enabled where available
+    #[wasm_bindgen]
+    pub fn set_simd_enabled(&mut self, enabled: bool) {
+        let kind = if enabled && Self::detect_simd_support() { BackendKind::Simd } else { BackendKind::Scalar };
+        // Both CPU backends are always constructible here
+        let _ = self.set_backend(kind);
     }
 
-    // High-performance neural activation with SIMD and security validation
+    // Select the kernels used by matmul and activations. WebGpu needs a device
+    // attached with use_gpu() first.
     #[wasm_bindgen]
-    pub fn calculate_neural_activation(&mut self, inputs: &[f32]) -> Vec<f32> {
-        // Security validation: Check input bounds
-        if inputs.len() > 10000 {
-            panic!("Input size exceeds security limit of 10000 elements");
-        }
+    pub fn set_backend(&mut self, kind: BackendKind) -> Result<(), NeuralError> {
+        self.backend = match kind {
+            BackendKind::Scalar => Box::new(ScalarBackend),
+            BackendKind::Simd => Box::new(SimdBackend::new()?),
+            #[cfg(feature = "webgpu")]
+            BackendKind::WebGpu => match &self.gpu {
+                Some(gpu) => Box::new(gpu.clone()),
+                None => return Err(NeuralError::Unavailable("WebGPU device (call use_gpu first)".to_string())),
+            },
+            #[cfg(not(feature = "webgpu"))]
+            BackendKind::WebGpu => return Err(NeuralError::Unavailable("WebGPU backend (built without the webgpu feature)".to_string())),
+        };
+        self.simd_enabled = kind != BackendKind::Scalar && Self::detect_simd_support();
+        log_event!(LogLevel::Info, "runtime", "switched to {:?} backend", kind);
+        Ok(())
+    }
 
-        // Validate input values for security
-        for &input in inputs.iter() {
-            if !input.is_finite() {
-                panic!("Invalid input value detected: NaN or Infinity");
-            }
-            if input.abs() > 1000.0 {
-                panic!("Input value exceeds security bounds: {}", input);
-            }
-        }
+    #[wasm_bindgen]
+    pub fn backend(&self) -> BackendKind {
+        self.backend.kind()
+    }
+
+    // Attach a device from GpuContext.request() and switch to the WebGpu backend
+    #[cfg(feature = "webgpu")]
+    #[wasm_bindgen]
+    pub fn use_gpu(&mut self, context: &webgpu::GpuContext) {
+        self.gpu = Some(context.clone());
+        self.backend = Box::new(context.clone());
+    }
 
+    // Promise resolving to the m×n product as a Float32Array. Runs on the GPU when the
+    // WebGpu backend is selected and the problem is large enough to pay for the transfer.
+    #[cfg(feature = "webgpu")]
+    #[wasm_bindgen]
+    pub fn matmul_async(&mut self, mut a: Vec<f32>, mut b: Vec<f32>, m: usize, n: usize, k: usize) -> js_sys::Promise {
+        if let Err(err) = self.security.apply_rows(&mut a, k).and_then(|()| self.security.apply_rows(&mut b, n)) {
+            return js_sys::Promise::reject(&err.into());
+        }
         self.operations_count += 1;
-        
-        if self.simd_enabled && inputs.len() >= 4 {
-            self.simd_neural_activation(inputs)
-        } else {
-            self.scalar_neural_activation(inputs)
+
+        let len = match checked::elements(m, n) {
+            Ok(len) => len,
+            Err(err) => return js_sys::Promise::reject(&err.into()),
+        };
+        // Checked mode keeps the product on the CPU, where the kernel can check it
+        let gpu = self.gpu.clone().filter(|_| {
+            self.backend.kind() == BackendKind::WebGpu && !checked::enabled() && len.saturating_mul(k) >= webgpu::GPU_MIN_WORK
+        });
+        if let Some(gpu) = gpu {
+            return gpu.matmul(a, b, m, n, k);
+        }
+        let mut c = vec![0.0; len];
+        match self.backend.matmul(&a, &b, &mut c, m, n, k) {
+            Ok(()) => js_sys::Promise::resolve(&JsValue::from(js_sys::Float32Array::from(&c[..]))),
+            Err(err) => js_sys::Promise::reject(&err.into()),
         }
     }
 
-    // SIMD-optimized activation function (tanh) with bounds checking
-    fn simd_neural_activation(&self, inputs: &[f32]) -> Vec<f32> {
-        let mut outputs = vec![0.0; inputs.len()];
-        let chunks = inputs.len() / 4;
-        
-        // Process 4 elements at a time with SIMD
-        for i in 0..chunks {
-            let base_idx = i * 4;
-            
-            // Bounds checking for memory safety
-            if base_idx + 3 >= inputs.len() {
-                break; // Prevent buffer overflow
-            }
-            
-            // Additional safety check for aligned access
-            if (base_idx * 4) % 16 != 0 {
-                // Fall back to scalar for unaligned access
-                for j in 0..4 {
-                    if base_idx + j < inputs.len() {
-                        outputs[base_idx + j] = (inputs[base_idx + j] * 0.5).tanh();
-                    }
-                }
-                continue;
-            }
-            
-            // Load 4 f32 values into SIMD register (with bounds check)
-            let input_vec = v128_load(&inputs[base_idx] as *const f32 as *const v128);
-            
-            // Scale by 0.5
-            let scale = f32x4_splat(0.5);
-            let scaled = f32x4_mul(input_vec, scale);
-            
-            // Apply tanh approximation for SIMD (simplified)
-            let result = self.simd_tanh_approx(scaled);
-            
-            // Store results with bounds check
-            if base_idx + 3 < outputs.len() {
-                v128_store(&mut outputs[base_idx] as *mut f32 as *mut v128, result);
-            }
+    fn detect_simd_support() -> bool {
+        // SIMD kernels must be compiled into this build and accepted by the engine
+        features::simd_available()
+    }
+
+    // Threads used to shard batch work, clamped to the pool size; returns the count in effect.
+    // 1 (the default) keeps everything on the calling thread.
+    #[wasm_bindgen]
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        self.thread_count = threads.clamp(1, parallel::available_threads());
+        self.thread_count
+    }
+
+    #[wasm_bindgen]
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    // Batch inference sharded across the configured threads (on one thread, reusing the
+    // scratch buffers); same layout as NeuralNetwork.forward_batch
+    #[wasm_bindgen]
+    pub fn forward_batch(&mut self, network: &NeuralNetwork, inputs: &[f32], batch_size: usize) -> Result<Vec<f32>, NeuralError> {
+        self.operations_count += 1;
+
+        if batch_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
         }
-        
-        // Handle remaining elements with scalar operations
-        for i in (chunks * 4)..inputs.len() {
-            outputs[i] = (inputs[i] * 0.5).tanh();
+        let expected = batch_size
+            .checked_mul(network.input_size())
+            .ok_or_else(|| NeuralError::InvalidConfiguration("batch size overflows".to_string()))?;
+        if inputs.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: inputs.len() });
         }
-        
-        outputs
+        let inputs = &*self.security.sanitize(inputs, network.input_size())?;
+
+        let started = self.start_kernel("forward_batch");
+        let (input_size, output_size) = (network.input_size(), network.output_size());
+        self.activation_hooks.begin(network.layer_count(), &self.memory_pool)?;
+        let outputs = if self.thread_count > 1 && self.activation_hooks.is_empty() {
+            let mut outputs = vec![0.0; batch_size * output_size];
+            parallel::for_each_shard(inputs, input_size, &mut outputs, output_size, self.thread_count, |input, output| {
+                let result = network.forward_batch(input, input.len() / input_size)?;
+                output.copy_from_slice(&result);
+                Ok(())
+            })?;
+            outputs
+        } else {
+            let chunk = self.batch_chunk();
+            let hooks = &mut self.activation_hooks;
+            forward_chunked(network, inputs, batch_size, chunk, &mut self.scratch, &mut |layer, outputs| hooks.observe(layer, outputs))?
+        };
+        self.activation_hooks.finish(&mut self.memory_pool)?;
+        self.finish_kernel(started, float_bytes(inputs.len() + outputs.len()), &[batch_size, input_size, output_size]);
+        Ok(outputs)
     }
 
-    // SIMD tanh approximation
-    fn simd_tanh_approx(&self, x: v128) -> v128 {
-        // Simplified tanh approximation using SIMD
-        // tanh(x) ≈ x / (1 + |x|) for fast approximation
-        let abs_x = f32x4_abs(x);
-        let one = f32x4_splat(1.0);
-        let denominator = f32x4_add(one, abs_x);
-        f32x4_div(x, denominator)
+    // Single-sample inference reusing the runtime's scratch buffers between calls;
+    // same result as NeuralNetwork.forward
+    #[wasm_bindgen]
+    pub fn forward(&mut self, network: &NeuralNetwork, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        let inputs = &*self.security.sanitize(inputs, inputs.len())?;
+        self.operations_count += 1;
+        let started = self.start_kernel("forward");
+        self.activation_hooks.begin(network.layer_count(), &self.memory_pool)?;
+        let hooks = &mut self.activation_hooks;
+        let outputs = network.forward_with_scratch(inputs, 1, &mut self.scratch, &mut |layer, outputs| hooks.observe(layer, outputs))?;
+        self.activation_hooks.finish(&mut self.memory_pool)?;
+        self.finish_kernel(started, float_bytes(inputs.len() + outputs.len()), &[inputs.len(), outputs.len()]);
+        Ok(outputs)
     }
 
-    // Scalar fallback activation
-    fn scalar_neural_activation(&self, inputs: &[f32]) -> Vec<f32> {
-        inputs.iter().map(|&x| (x * 0.5).tanh()).collect()
+    // Release the intermediate buffers kept for forward, forward_batch and forward_in_place
+    #[wasm_bindgen]
+    pub fn clear_scratch(&mut self) {
+        self.scratch.clear();
     }
 
-    // High-performance connection optimization
+    // Reuse counts of the scratch buffers since the last reset_metrics, and what is cached now
+    #[wasm_bindgen]
+    pub fn scratch_stats(&self) -> ScratchStats {
+        self.scratch.stats()
+    }
+
+    // High-performance neural activation with SIMD and security validation
     #[wasm_bindgen]
-    pub fn optimize_connections(&mut self, connections: &[f32]) -> Vec<f32> {
+    pub fn calculate_neural_activation(&mut self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        let mut outputs = inputs.to_vec();
+        self.security.apply(&mut outputs)?;
+
         self.operations_count += 1;
-        
-        if self.simd_enabled && connections.len() >= 4 {
-            self.simd_optimize_connections(connections)
-        } else {
-            self.scalar_optimize_connections(connections)
-        }
+        let started = self.start_kernel("neural_activation");
+        let accuracy = self.kernel_accuracy();
+        neural_activation(&mut outputs, self.simd_enabled, accuracy);
+        self.finish_kernel(started, float_bytes(2 * inputs.len()), &[inputs.len()]);
+        Ok(outputs)
     }
 
-    fn simd_optimize_connections(&self, connections: &[f32]) -> Vec<f32> {
-        let mut optimized = vec![0.0; connections.len()];
-        let chunks = connections.len() / 4;
-        
-        for i in 0..chunks {
-            let base_idx = i * 4;
-            
-            // Load connections
-            let conn_vec = v128_load(&connections[base_idx] as *const f32 as *const v128);
-            
-            // Apply optimization (small random adjustments with bounds)
-            let adjustment_range = f32x4_splat(0.1);
-            let random_adj = self.simd_random_vec(); // Simplified random
-            let scaled_adj = f32x4_mul(random_adj, adjustment_range);
-            
-            let adjusted = f32x4_add(conn_vec, scaled_adj);
-            
-            // Clamp to [0, 1] range
-            let zero = f32x4_splat(0.0);
-            let one = f32x4_splat(1.0);
-            let clamped = f32x4_max(zero, f32x4_min(one, adjusted));
-            
-            v128_store(&mut optimized[base_idx] as *mut f32 as *mut v128, clamped);
+    // In-place variants of the elementwise kernels: same validation and results as
+    // the copying calls, written over `values` without allocating. JS arrays are
+    // still copied across the boundary; the *_buffer variants avoid even that.
+    #[wasm_bindgen]
+    pub fn calculate_neural_activation_in_place(&mut self, values: &mut [f32]) -> Result<(), NeuralError> {
+        self.security.apply(values)?;
+
+        self.operations_count += 1;
+        let started = self.start_kernel("neural_activation");
+        let accuracy = self.kernel_accuracy();
+        neural_activation(values, self.simd_enabled, accuracy);
+        self.finish_kernel(started, float_bytes(2 * values.len()), &[values.len()]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn calculate_activation_in_place(&mut self, values: &mut [f32], kind: ActivationKind) -> Result<(), NeuralError> {
+        self.security.apply(values)?;
+
+        self.operations_count += 1;
+        let started = self.start_kernel("activation");
+        let accuracy = self.kernel_accuracy();
+        self.backend.activate(values, kind, accuracy);
+        self.finish_kernel(started, float_bytes(2 * values.len()), &[values.len()]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn calculate_pipeline_in_place(&mut self, values: &mut [f32], pipeline: &ElementwisePipeline) -> Result<(), NeuralError> {
+        self.security.apply(values)?;
+
+        self.operations_count += 1;
+        let started = self.start_kernel("pipeline");
+        pipeline.apply_with(values, self.simd_enabled);
+        self.finish_kernel(started, float_bytes(2 * values.len()), &[values.len()]);
+        Ok(())
+    }
+
+    // The elementwise kernels on a pool buffer (see alloc_buffer), whose whole
+    // length is processed; JS reads the results through its view of the buffer
+    #[wasm_bindgen]
+    pub fn neural_activation_buffer(&mut self, handle: u32) -> Result<(), NeuralError> {
+        let started = self.start_kernel("neural_activation");
+        let accuracy = self.kernel_accuracy();
+        let buffer = self.memory_pool.get_mut(handle)?;
+        self.security.apply(buffer)?;
+        neural_activation(buffer, self.simd_enabled, accuracy);
+        let len = buffer.len();
+        self.operations_count += 1;
+        self.finish_kernel(started, float_bytes(2 * len), &[len]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn activation_buffer(&mut self, handle: u32, kind: ActivationKind) -> Result<(), NeuralError> {
+        let started = self.start_kernel("activation");
+        let accuracy = self.kernel_accuracy();
+        let buffer = self.memory_pool.get_mut(handle)?;
+        self.security.apply(buffer)?;
+        self.backend.activate(buffer, kind, accuracy);
+        let len = buffer.len();
+        self.operations_count += 1;
+        self.finish_kernel(started, float_bytes(2 * len), &[len]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn pipeline_buffer(&mut self, handle: u32, pipeline: &ElementwisePipeline) -> Result<(), NeuralError> {
+        let started = self.start_kernel("pipeline");
+        let buffer = self.memory_pool.get_mut(handle)?;
+        self.security.apply(buffer)?;
+        pipeline.apply_with(buffer, self.simd_enabled);
+        let len = buffer.len();
+        self.operations_count += 1;
+        self.finish_kernel(started, float_bytes(2 * len), &[len]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn optimize_connections_buffer(&mut self, handle: u32) -> Result<(), NeuralError> {
+        let started = self.start_kernel("optimize_connections");
+        let skip = self.optimization_skipped();
+        let buffer = self.memory_pool.get_mut(handle)?;
+        self.security.apply(buffer)?;
+        if !skip {
+            optimize_with(self.optimizer.as_mut(), &mut self.rng, &mut self.secure_rng, buffer, &[]);
         }
-        
-        // Handle remaining elements
-        for i in (chunks * 4)..connections.len() {
-            let adjustment = (self.pseudo_random() - 0.5) * 0.1;
-            optimized[i] = (connections[i] + adjustment).clamp(0.0, 1.0);
+        let len = buffer.len();
+        self.operations_count += 1;
+        self.finish_kernel(started, float_bytes(2 * len), &[len]);
+        Ok(())
+    }
+
+    // Embedding rows for `ids` written into a pool buffer of exactly ids.len() × dim
+    // floats, so categorical inputs reach a network without leaving WASM memory
+    #[wasm_bindgen]
+    pub fn embedding_buffer(&mut self, handle: u32, embedding: &Embedding, ids: &[u32]) -> Result<(), NeuralError> {
+        let started = self.start_kernel("embedding");
+        let buffer = self.memory_pool.get_mut(handle)?;
+        embedding.lookup_into(ids, buffer)?;
+        let len = buffer.len();
+        self.operations_count += 1;
+        self.finish_kernel(started, float_bytes(2 * len), &[len]);
+        Ok(())
+    }
+
+    // Activation with a caller-selected function, validated like calculate_neural_activation
+    #[wasm_bindgen]
+    pub fn calculate_activation(&mut self, inputs: &[f32], kind: ActivationKind) -> Result<Vec<f32>, NeuralError> {
+        let mut outputs = inputs.to_vec();
+        self.security.apply(&mut outputs)?;
+
+        self.operations_count += 1;
+        let started = self.start_kernel("activation");
+        let accuracy = self.kernel_accuracy();
+        self.backend.activate(&mut outputs, kind, accuracy);
+        self.finish_kernel(started, float_bytes(2 * inputs.len()), &[inputs.len()]);
+        Ok(outputs)
+    }
+
+    // Run a fused elementwise pipeline over validated inputs in one pass
+    #[wasm_bindgen]
+    pub fn calculate_pipeline(&mut self, inputs: &[f32], pipeline: &ElementwisePipeline) -> Result<Vec<f32>, NeuralError> {
+        let mut outputs = inputs.to_vec();
+        self.security.apply(&mut outputs)?;
+
+        self.operations_count += 1;
+        let started = self.start_kernel("pipeline");
+        pipeline.apply_with(&mut outputs, self.simd_enabled);
+        self.finish_kernel(started, float_bytes(2 * inputs.len()), &[inputs.len()]);
+        Ok(outputs)
+    }
+
+    // Kernels used by calculate_activation and calculate_neural_activation; networks
+    // always evaluate with Accurate, and a budget downshifted to ReducedPrecision
+    // overrides this with Fast
+    #[wasm_bindgen]
+    pub fn set_activation_accuracy(&mut self, accuracy: ActivationAccuracy) {
+        self.activation_accuracy = accuracy;
+    }
+
+    #[wasm_bindgen]
+    pub fn activation_accuracy(&self) -> ActivationAccuracy {
+        self.activation_accuracy
+    }
+
+    // Length limit, value bounds and NaN handling applied to the input of every
+    // entry point (see security.rs)
+    #[wasm_bindgen]
+    pub fn set_security_policy(&mut self, policy: &SecurityPolicy) -> Result<(), NeuralError> {
+        policy.validate()?;
+        self.security = *policy;
+        log_event!(LogLevel::Info, "security", "policy set: {:?}", policy);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn security_policy(&self) -> SecurityPolicy {
+        self.security
+    }
+
+    // Apply the selected connection optimizer (Jitter unless changed) to a copy of `connections`
+    #[wasm_bindgen]
+    pub fn optimize_connections(&mut self, connections: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        let connections = self.security.sanitize(connections, connections.len())?;
+        Ok(self.run_optimizer(&connections, &[]))
+    }
+
+    // Like optimize_connections, with a per-connection signal for the strategies that
+    // read one (gradients for GradientPruning, co-activity for Hebbian, costs for
+    // Annealing), and statistics of the connections before and after
+    #[wasm_bindgen]
+    pub fn optimize_connections_with(&mut self, connections: &[f32], signals: &[f32]) -> Result<OptimizationReport, NeuralError> {
+        if !signals.is_empty() && signals.len() != connections.len() {
+            return Err(NeuralError::DimensionMismatch { expected: connections.len(), actual: signals.len() });
         }
-        
-        optimized
+        let connections = self.security.sanitize(connections, connections.len())?;
+        let signals = self.security.sanitize(signals, signals.len())?;
+        let optimized = self.run_optimizer(&connections, &signals);
+        Ok(OptimizationReport::new(self.optimizer.kind(), &connections, optimized))
     }
 
-    fn scalar_optimize_connections(&self, connections: &[f32]) -> Vec<f32> {
-        connections.iter().map(|&w| {
-            let adjustment = (self.pseudo_random() - 0.5) * 0.1;
-            (w + adjustment).clamp(0.0, 1.0)
-        }).collect()
+    // Select the strategy used by optimize_connections; resets any strategy state
+    // such as the annealing temperature
+    #[wasm_bindgen]
+    pub fn set_connection_optimizer(&mut self, kind: OptimizerKind, params: &OptimizerParams) -> Result<(), NeuralError> {
+        self.optimizer = optimizer::create(kind, params)?;
+        log_event!(LogLevel::Debug, "runtime", "connection optimizer set to {:?}", kind);
+        Ok(())
     }
 
-    // Simplified random vector for SIMD
-    fn simd_random_vec(&self) -> v128 {
-        // In production, would use proper SIMD random number generation
-        let r1 = self.pseudo_random() - 0.5;
-        let r2 = self.pseudo_random() - 0.5;
-        let r3 = self.pseudo_random() - 0.5;
-        let r4 = self.pseudo_random() - 0.5;
-        f32x4(r1, r2, r3, r4)
+    #[wasm_bindgen]
+    pub fn connection_optimizer(&self) -> OptimizerKind {
+        self.optimizer.kind()
     }
 
-    // Cryptographically secure random number generation
-    fn pseudo_random(&self) -> f32 {
-        // Security improvement: Use cryptographically secure random
-        // In WASM environment, would use crypto.getRandomValues()
-        // For now, using a better seed with operation count and memory address
-        let seed = (self.operations_count as u64)
-            .wrapping_mul(6364136223846793005)
-            .wrapping_add(1442695040888963407);
-        
-        // XORShift for better randomness
-        let mut x = seed;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        
-        // Convert to [0,1) range
-        (x as f32) / (u64::MAX as f32)
+    // In-place optimize_connections
+    #[wasm_bindgen]
+    pub fn optimize_connections_in_place(&mut self, connections: &mut [f32]) -> Result<(), NeuralError> {
+        self.security.apply(connections)?;
+        self.operations_count += 1;
+        let started = self.start_kernel("optimize_connections");
+        if !self.optimization_skipped() {
+            optimize_with(self.optimizer.as_mut(), &mut self.rng, &mut self.secure_rng, connections, &[]);
+        }
+        self.finish_kernel(started, float_bytes(2 * connections.len()), &[connections.len()]);
+        Ok(())
+    }
+
+    fn run_optimizer(&mut self, connections: &[f32], signals: &[f32]) -> Vec<f32> {
+        self.operations_count += 1;
+        let started = self.start_kernel("optimize_connections");
+
+        let mut optimized = connections.to_vec();
+        if !self.optimization_skipped() {
+            optimize_with(self.optimizer.as_mut(), &mut self.rng, &mut self.secure_rng, &mut optimized, signals);
+        }
+        self.finish_kernel(started, float_bytes(2 * connections.len()), &[connections.len()]);
+        optimized
+    }
+
+    // Dense matrix multiplication: returns C[m×n] = A[m×k] · B[k×n]
+    #[wasm_bindgen]
+    pub fn matmul(&mut self, a: &[f32], b: &[f32], m: usize, n: usize, k: usize) -> Result<Vec<f32>, NeuralError> {
+        let (a, b) = (self.security.sanitize(a, k)?, self.security.sanitize(b, n)?);
+        self.operations_count += 1;
+        let started = self.start_kernel("matmul");
+
+        let mut c = vec![0.0; checked::elements(m, n)?];
+        self.backend.matmul(&a, &b, &mut c, m, n, k)?;
+        self.finish_kernel(started, float_bytes(a.len() + b.len() + c.len()), &[m, n, k]);
+        Ok(c)
     }
 
     // Spike train processing
@@ -217,127 +696,276 @@ impl NeuralRuntime {
         if spikes.is_empty() || window_size <= 0.0 {
             return 0.0;
         }
+        let Some(spikes) = self.security.sanitize_or_log(spikes, "process_spike_train") else {
+            return 0.0;
+        };
+        let spikes = &*spikes;
 
-        let spike_count = if self.simd_enabled && spikes.len() >= 4 {
-            self.simd_count_spikes(spikes)
-        } else {
+        let started = self.start_kernel("spike_train");
+        let spike_count = simd_dispatch!(
+            self.simd_enabled && spikes.len() >= 4,
+            self.simd_count_spikes(spikes),
             spikes.iter().filter(|&&x| x > 0.1).count() as f32
-        };
+        );
+        self.finish_kernel(started, float_bytes(spikes.len()), &[spikes.len()]);
         
         // Return spike rate in Hz
-        spike_count / (window_size / 1000.0)
+        let rate = spike_count / (window_size / 1000.0);
+        self.spikes_total += spike_count as u64;
+        self.spike_window_ms += window_size as f64;
+        self.last_spike_rate = rate;
+        rate
     }
 
+    #[cfg(target_feature = "simd128")]
     fn simd_count_spikes(&self, spikes: &[f32]) -> f32 {
         let threshold = f32x4_splat(0.1);
-        let chunks = spikes.len() / 4;
-        let mut count = 0.0;
-        
-        for i in 0..chunks {
-            let base_idx = i * 4;
-            let spike_vec = v128_load(&spikes[base_idx] as *const f32 as *const v128);
-            
-            // Compare with threshold
-            let mask = f32x4_gt(spike_vec, threshold);
-            
-            // Count spikes (simplified)
-            let spike_data = [
-                spikes[base_idx], spikes[base_idx + 1], 
-                spikes[base_idx + 2], spikes[base_idx + 3]
-            ];
-            
-            for (j, &spike) in spike_data.iter().enumerate() {
-                if spike > 0.1 {
-                    count += 1.0;
-                }
-            }
-        }
-        
-        // Handle remaining elements
-        for i in (chunks * 4)..spikes.len() {
-            if spikes[i] > 0.1 {
-                count += 1.0;
-            }
+        let chunks = spikes.chunks_exact(4);
+        let mut count = chunks.remainder().iter().filter(|&&x| x > 0.1).count() as u32;
+
+        // One mask bit per lane above the threshold
+        for chunk in chunks {
+            count += i32x4_bitmask(f32x4_gt(simd::load(chunk), threshold)).count_ones();
         }
-        
-        count
+
+        count as f32
     }
 
-    // Mesh efficiency calculation
+    // Overall mesh efficiency score in [0, 1] from per-neuron activity (spike
+    // probability per time bin) and synapse weights; 0 when either is empty
     #[wasm_bindgen]
     pub fn calculate_mesh_efficiency(&mut self, neurons: &[f32], synapses: &[f32]) -> f32 {
+        self.mesh_efficiency_report(neurons, synapses).map_or(0.0, |report| report.score() as f32)
+    }
+
+    // Energy, throughput and connectivity components behind calculate_mesh_efficiency
+    #[wasm_bindgen]
+    pub fn mesh_efficiency_report(&mut self, neurons: &[f32], synapses: &[f32]) -> Option<EfficiencyReport> {
         self.operations_count += 1;
-        
         if neurons.is_empty() || synapses.is_empty() {
-            return 0.0;
+            return None;
         }
+        let neurons = self.security.sanitize_or_log(neurons, "mesh_efficiency_report")?;
+        let synapses = self.security.sanitize_or_log(synapses, "mesh_efficiency_report")?;
+        let (neurons, synapses) = (&*neurons, &*synapses);
 
-        let neuron_activity = if self.simd_enabled && neurons.len() >= 4 {
-            self.simd_sum(neurons) / neurons.len() as f32
-        } else {
-            neurons.iter().sum::<f32>() / neurons.len() as f32
-        };
-
-        let synapse_weight = if self.simd_enabled && synapses.len() >= 4 {
-            self.simd_sum(synapses) / synapses.len() as f32
-        } else {
-            synapses.iter().sum::<f32>() / synapses.len() as f32
-        };
+        let started = self.start_kernel("mesh_efficiency");
+        let fan_out = synapses.len() as f64 / neurons.len() as f64;
+        let connectivity = efficiency::functional_fraction(synapses, self.efficiency_weights.active_weight);
+        let report = efficiency::evaluate(neurons, fan_out, connectivity, &self.efficiency_weights);
+        self.finish_kernel(started, float_bytes(neurons.len() + synapses.len()), &[neurons.len(), synapses.len()]);
+        Some(report)
+    }
 
-        neuron_activity * synapse_weight
+    // Component weights and costs used by the mesh efficiency score
+    #[wasm_bindgen]
+    pub fn set_efficiency_weights(&mut self, weights: &EfficiencyWeights) -> Result<(), NeuralError> {
+        weights.validate()?;
+        self.efficiency_weights = *weights;
+        Ok(())
     }
 
-    fn simd_sum(&self, values: &[f32]) -> f32 {
-        let chunks = values.len() / 4;
-        let mut sum_vec = f32x4_splat(0.0);
-        
-        for i in 0..chunks {
-            let base_idx = i * 4;
-            let val_vec = v128_load(&values[base_idx] as *const f32 as *const v128);
-            sum_vec = f32x4_add(sum_vec, val_vec);
-        }
-        
-        // Extract sum from SIMD register
-        let sum_array = [0.0f32; 4];
-        v128_store(sum_array.as_ptr() as *mut v128, sum_vec);
-        let simd_sum = sum_array.iter().sum::<f32>();
-        
-        // Add remaining elements
-        let scalar_sum: f32 = values[(chunks * 4)..].iter().sum();
-        
-        simd_sum + scalar_sum
+    #[wasm_bindgen]
+    pub fn efficiency_weights(&self) -> EfficiencyWeights {
+        self.efficiency_weights
     }
 
     // Memory management
     #[wasm_bindgen]
     pub fn get_memory_usage(&self) -> usize {
-        self.memory_usage + (self.memory_pool.capacity() * std::mem::size_of::<f32>())
+        self.memory_pool.reserved_bytes()
     }
 
+    // Bytes held by live allocations
     #[wasm_bindgen]
-    pub fn allocate_memory(&mut self, size: usize) -> usize {
-        let required_floats = size / std::mem::size_of::<f32>();
-        
-        if self.memory_pool.len() + required_floats > self.memory_pool.capacity() {
-            self.memory_pool.reserve(required_floats);
-        }
-        
-        let ptr = self.memory_pool.len();
-        self.memory_pool.resize(self.memory_pool.len() + required_floats, 0.0);
-        self.memory_usage += size;
-        
-        ptr * std::mem::size_of::<f32>()
+    pub fn get_allocated_bytes(&self) -> usize {
+        self.memory_pool.bytes_in_use()
     }
 
     #[wasm_bindgen]
-    pub fn deallocate_memory(&mut self, size: usize) {
-        self.memory_usage = self.memory_usage.saturating_sub(size);
-        
-        // In production, would implement proper memory pool management
-        if self.memory_pool.len() > 1024 * 1024 { // 1M floats
-            self.memory_pool.clear();
-            self.memory_pool.shrink_to_fit();
+    pub fn get_allocation_count(&self) -> usize {
+        self.memory_pool.allocation_count()
+    }
+
+    // Largest contiguous free block in bytes, a quick fragmentation indicator
+    #[wasm_bindgen]
+    pub fn get_largest_free_block(&self) -> usize {
+        self.memory_pool.largest_free_block()
+    }
+
+    // Cap on bytes the memory pool may reserve (0 = unlimited, the default). Allocations
+    // that would need more fail with a MemoryLimitExceeded error instead of growing memory.
+    #[wasm_bindgen]
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.memory_pool.set_limit(bytes);
+    }
+
+    #[wasm_bindgen]
+    pub fn memory_limit(&self) -> usize {
+        self.memory_pool.limit()
+    }
+
+    // Defragment the pool: live buffers are packed together and memory left unused is
+    // released. Handles stay valid but buffers move, so JS must call buffer_ptr again
+    // (memory_epoch() changes when anything moved). Returns the bytes released.
+    #[wasm_bindgen]
+    pub fn compact_memory(&mut self) -> usize {
+        let released = self.memory_pool.compact();
+        log_event!(LogLevel::Debug, "memory", "compaction released {} bytes", released);
+        released
+    }
+
+    // Once a free leaves more than `high_water_mark` bytes reserved (0 disables this),
+    // release empty segments, compacting first if `relocate` allows buffers to move
+    #[wasm_bindgen]
+    pub fn set_shrink_policy(&mut self, high_water_mark: usize, relocate: bool) {
+        self.memory_pool.set_shrink_policy(high_water_mark, relocate);
+    }
+
+    // Changes whenever buffers move; pointers from buffer_ptr are valid within one epoch
+    #[wasm_bindgen]
+    pub fn memory_epoch(&self) -> u32 {
+        self.memory_pool.epoch()
+    }
+
+    // Reserve `size` bytes (rounded up to 16-byte blocks) and return the allocation handle
+    #[wasm_bindgen]
+    pub fn allocate_memory(&mut self, size: usize) -> Result<u32, NeuralError> {
+        self.memory_pool.allocate(size.div_ceil(std::mem::size_of::<f32>()))
+    }
+
+    #[wasm_bindgen]
+    pub fn deallocate_memory(&mut self, handle: u32) -> Result<(), NeuralError> {
+        self.free_buffer(handle)
+    }
+
+    // Zero-copy buffers: JS views them with `new Float32Array(wasm_memory().buffer, ptr, len)`
+    // and must recreate views after any allocation, since growing memory detaches the old buffer
+    #[wasm_bindgen]
+    pub fn alloc_buffer(&mut self, len: usize) -> Result<u32, NeuralError> {
+        self.memory_pool.allocate(len)
+    }
+
+    #[wasm_bindgen]
+    pub fn free_buffer(&mut self, handle: u32) -> Result<(), NeuralError> {
+        self.memory_pool.free(handle)?;
+        self.activation_hooks.remove_handle(handle);
+        Ok(())
+    }
+
+    // Byte address of the buffer inside WASM linear memory
+    #[wasm_bindgen]
+    pub fn buffer_ptr(&self, handle: u32) -> Result<usize, NeuralError> {
+        Ok(self.memory_pool.get(handle)?.as_ptr() as usize)
+    }
+
+    #[wasm_bindgen]
+    pub fn buffer_len(&self, handle: u32) -> Result<usize, NeuralError> {
+        Ok(self.memory_pool.get(handle)?.len())
+    }
+
+    // Copy `layer`'s outputs into pool buffer `handle` on every forward pass the
+    // runtime runs, for activation visualization (see hooks.rs). Replaces any hook
+    // already on the layer.
+    #[wasm_bindgen]
+    pub fn record_activations(&mut self, layer: usize, handle: u32) -> Result<(), NeuralError> {
+        self.memory_pool.get(handle)?;
+        self.activation_hooks.insert(layer, handle);
+        Ok(())
+    }
+
+    // Remove the hook on `layer`; false if there was none
+    #[wasm_bindgen]
+    pub fn stop_recording(&mut self, layer: usize) -> bool {
+        self.activation_hooks.remove(layer)
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_activation_hooks(&mut self) {
+        self.activation_hooks.clear();
+    }
+
+    // Values the last forward pass wrote into `layer`'s hooked buffer, or undefined
+    // if the layer has no hook
+    #[wasm_bindgen]
+    pub fn recorded_activations(&self, layer: usize) -> Option<usize> {
+        self.activation_hooks.recorded(layer)
+    }
+
+    // Contiguous tensor of `shape` over a pool buffer; views of it see later writes to the buffer
+    #[wasm_bindgen]
+    pub fn tensor_from_buffer(&self, handle: u32, shape: &[u32]) -> Result<Tensor, NeuralError> {
+        Tensor::from_pool(&self.memory_pool, handle, shape)
+    }
+
+    // Elements of any tensor, pool-backed or not, in row-major order
+    #[wasm_bindgen]
+    pub fn tensor_data(&self, tensor: &Tensor) -> Result<Vec<f32>, NeuralError> {
+        Ok(tensor.data(&self.memory_pool)?.into_owned())
+    }
+
+    // [m×k] · [k×n] for rank-2 tensors; strided views such as transposes are gathered first
+    #[wasm_bindgen]
+    pub fn matmul_tensors(&mut self, a: &Tensor, b: &Tensor) -> Result<Tensor, NeuralError> {
+        let (&[m, k], &[k2, n]) = (a.dims(), b.dims()) else {
+            return Err(NeuralError::InvalidConfiguration("matmul_tensors needs rank-2 tensors".to_string()));
+        };
+        if k != k2 {
+            return Err(NeuralError::DimensionMismatch { expected: k, actual: k2 });
         }
+        self.operations_count += 1;
+        let started = self.start_kernel("matmul");
+
+        let (a, b) = (a.data(&self.memory_pool)?, b.data(&self.memory_pool)?);
+        let (a, b) = (self.security.sanitize(&a, k)?, self.security.sanitize(&b, n)?);
+        let mut c = vec![0.0; checked::elements(m, n)?];
+        self.backend.matmul(&a, &b, &mut c, m, n, k)?;
+        self.finish_kernel(started, float_bytes(m * k + k * n + c.len()), &[m, n, k]);
+        Ok(Tensor::from_vec(c, vec![m, n]))
+    }
+
+    // Inference on a [input_size] sample or a [batch, input_size] batch, returning
+    // [output_size] or [batch, output_size]
+    #[wasm_bindgen]
+    pub fn forward_tensor(&mut self, network: &NeuralNetwork, inputs: &Tensor) -> Result<Tensor, NeuralError> {
+        let batch_size = match *inputs.dims() {
+            [_] => 1,
+            [batch, _] => batch,
+            _ => return Err(NeuralError::InvalidConfiguration("forward_tensor needs a rank-1 or rank-2 tensor".to_string())),
+        };
+        self.operations_count += 1;
+        let started = self.start_kernel("forward_tensor");
+        let chunk = self.batch_chunk();
+
+        let data = inputs.data(&self.memory_pool)?;
+        let data = self.security.sanitize(&data, network.input_size())?;
+        self.activation_hooks.begin(network.layer_count(), &self.memory_pool)?;
+        let hooks = &mut self.activation_hooks;
+        let outputs = forward_chunked(network, &data, batch_size, chunk, &mut self.scratch, &mut |layer, outputs| hooks.observe(layer, outputs))?;
+        let bytes = float_bytes(data.len() + outputs.len());
+        self.activation_hooks.finish(&mut self.memory_pool)?;
+        self.finish_kernel(started, bytes, &[batch_size, network.input_size(), network.output_size()]);
+        let shape = match inputs.rank() {
+            1 => vec![outputs.len()],
+            _ => vec![batch_size, network.output_size()],
+        };
+        Ok(Tensor::from_vec(outputs, shape))
+    }
+
+    // Run the network on the inputs at the front of the buffer, writing outputs back into it
+    #[wasm_bindgen]
+    pub fn forward_in_place(&mut self, network: &NeuralNetwork, handle: u32) -> Result<(), NeuralError> {
+        self.operations_count += 1;
+        let started = self.start_kernel("forward_in_place");
+        self.activation_hooks.begin(network.layer_count(), &self.memory_pool)?;
+        let buffer = self.memory_pool.get_mut(handle)?;
+        let input_len = network.input_size().min(buffer.len());
+        self.security.apply(&mut buffer[..input_len])?;
+        let hooks = &mut self.activation_hooks;
+        network.forward_in_place(buffer, &mut self.scratch, &mut |layer, outputs| hooks.observe(layer, outputs))?;
+        self.activation_hooks.finish(&mut self.memory_pool)?;
+        self.finish_kernel(started, float_bytes(network.input_size() + network.output_size()), &[network.input_size(), network.output_size()]);
+        Ok(())
     }
 
     // Performance metrics
@@ -349,45 +977,450 @@ impl NeuralRuntime {
     #[wasm_bindgen]
     pub fn reset_metrics(&mut self) {
         self.operations_count = 0;
-        self.memory_usage = 0;
+        self.spikes_total = 0;
+        self.spike_window_ms = 0.0;
+        self.last_spike_rate = 0.0;
+        self.profiler.reset();
+        self.scratch.reset_stats();
+    }
+
+    // Per-kernel counts, bytes processed and latency percentiles (p50/p95/p99) with
+    // the underlying histogram, as a parsed JSON object
+    #[wasm_bindgen]
+    pub fn get_profile(&self) -> Result<JsValue, NeuralError> {
+        js_sys::JSON::parse(&self.profiler.to_json())
+            .map_err(|_| NeuralError::InvalidFormat("profile is not valid JSON".to_string()))
+    }
+
+    // The runtime's state as a SASN snapshot without networks or meshes (see
+    // snapshot.rs); RuntimeSnapshot bundles those alongside
+    #[wasm_bindgen]
+    pub fn snapshot(&self) -> Result<Vec<u8>, NeuralError> {
+        RuntimeSnapshot::capture(self).to_bytes()
+    }
+
+    // Load the runtime state of a SASN snapshot, migrating older formats, and report
+    // what the migration changed; nothing changes if the snapshot is invalid
+    #[wasm_bindgen]
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<CompatibilityReport, NeuralError> {
+        let snapshot = RuntimeSnapshot::from_bytes(bytes)?;
+        self.restore_snapshot(&snapshot, bytes.len())
+    }
+
+    // restore, refusing a snapshot that fails `check` (see integrity.rs)
+    #[wasm_bindgen]
+    pub fn restore_verified(&mut self, bytes: &[u8], check: &IntegrityCheck) -> Result<CompatibilityReport, NeuralError> {
+        let snapshot = RuntimeSnapshot::from_bytes_verified(bytes, check)?;
+        self.restore_snapshot(&snapshot, bytes.len())
+    }
+
+    // Emit a trace span for every kernel (see trace.rs), buffering up to `capacity`
+    // finished spans for take_trace_log; restarts any tracing already enabled
+    #[wasm_bindgen]
+    pub fn enable_tracing(&mut self, capacity: usize) -> Result<(), NeuralError> {
+        self.tracer = Some(Tracer::new(capacity)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn disable_tracing(&mut self) {
+        self.tracer = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn tracing_enabled(&self) -> bool {
+        self.tracer.is_some()
+    }
+
+    // Parent later spans under a W3C traceparent header; undefined starts a fresh root trace
+    #[wasm_bindgen]
+    pub fn set_trace_parent(&mut self, traceparent: Option<String>) -> Result<(), NeuralError> {
+        self.tracer_mut()?.set_parent(traceparent.as_deref())
+    }
+
+    // Hex id of the trace spans are recorded in, undefined while tracing is off
+    #[wasm_bindgen]
+    pub fn trace_id(&self) -> Option<String> {
+        self.tracer.as_ref().map(Tracer::trace_id)
+    }
+
+    // Call `listener(json)` as each kernel starts and ends; pass undefined to stop
+    #[wasm_bindgen]
+    pub fn set_trace_listener(&mut self, listener: Option<js_sys::Function>) -> Result<(), NeuralError> {
+        self.tracer_mut()?.set_listener(listener);
+        Ok(())
+    }
+
+    // Drain the buffered spans in the binary log format described in trace.rs
+    #[wasm_bindgen]
+    pub fn take_trace_log(&mut self) -> Result<Vec<u8>, NeuralError> {
+        Ok(self.tracer_mut()?.take_log())
+    }
+
+    #[wasm_bindgen]
+    pub fn pending_spans(&self) -> usize {
+        self.tracer.as_ref().map_or(0, Tracer::pending)
+    }
+
+    // Spans lost to a full buffer since tracing was enabled
+    #[wasm_bindgen]
+    pub fn dropped_spans(&self) -> u64 {
+        self.tracer.as_ref().map_or(0, Tracer::dropped)
+    }
+
+    // Operation, kernel latency, memory, scratch, spike and budget metrics in the
+    // Prometheus text format (see metrics.rs), for a server-side scrape endpoint;
+    // counters restart from zero after reset_metrics()
+    #[wasm_bindgen]
+    pub fn export_metrics_prometheus(&self) -> String {
+        let mut writer = PrometheusWriter::new();
+        writer.family("operations_total", MetricKind::Counter, "Runtime operations started.");
+        writer.sample("operations_total", &[], self.operations_count as f64);
+        self.profiler.write_prometheus(&mut writer);
+
+        let scratch = self.scratch.stats();
+        let gauges = [
+            ("memory_reserved_bytes", "Bytes reserved by the memory pool.", self.memory_pool.reserved_bytes()),
+            ("memory_allocated_bytes", "Bytes held by live pool allocations.", self.memory_pool.bytes_in_use()),
+            ("memory_allocations", "Live pool allocations.", self.memory_pool.allocation_count()),
+            ("memory_largest_free_block_bytes", "Largest free block in the memory pool.", self.memory_pool.largest_free_block()),
+            ("memory_limit_bytes", "Memory pool limit, 0 when unlimited.", self.memory_pool.limit()),
+            ("scratch_cached_bytes", "Bytes held by cached scratch buffers.", scratch.cached_bytes()),
+        ];
+        for (name, help, value) in gauges {
+            writer.family(name, MetricKind::Gauge, help);
+            writer.sample(name, &[], value as f64);
+        }
+        writer.family("scratch_requests_total", MetricKind::Counter, "Scratch buffer requests by outcome.");
+        writer.sample("scratch_requests_total", &[("result", "hit")], scratch.hits() as f64);
+        writer.sample("scratch_requests_total", &[("result", "miss")], scratch.misses() as f64);
+
+        writer.family("spikes_total", MetricKind::Counter, "Spikes counted by process_spike_train.");
+        writer.sample("spikes_total", &[], self.spikes_total as f64);
+        writer.family("spike_window_seconds_total", MetricKind::Counter, "Spike train time processed.");
+        writer.sample("spike_window_seconds_total", &[], self.spike_window_ms / 1000.0);
+        writer.family("spike_rate_hz", MetricKind::Gauge, "Rate of the last spike train processed.");
+        writer.sample("spike_rate_hz", &[], self.last_spike_rate as f64);
+
+        writer.family("budget_downshift_level", MetricKind::Gauge, "Budget downshift level, 0 when none or no budget is set.");
+        writer.sample("budget_downshift_level", &[], self.downshift() as u8 as f64);
+        writer.finish()
+    }
+
+    // Timing costs two clock reads per call; switch it off for the tightest loops
+    #[wasm_bindgen]
+    pub fn set_profiling_enabled(&mut self, enabled: bool) {
+        self.profiler.set_enabled(enabled);
+    }
+
+    #[wasm_bindgen]
+    pub fn profiling_enabled(&self) -> bool {
+        self.profiler.is_enabled()
+    }
+
+    // Declare per-tick time and memory budgets (see budget.rs); starts with no downshift
+    #[wasm_bindgen]
+    pub fn set_budget(&mut self, config: &BudgetConfig) -> Result<(), NeuralError> {
+        self.budget = Some(BudgetGuard::new(*config)?);
+        Ok(())
+    }
+
+    // Drop the budget and any downshift in force
+    #[wasm_bindgen]
+    pub fn clear_budget(&mut self) {
+        self.budget = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn budget(&self) -> Option<BudgetConfig> {
+        self.budget.as_ref().map(BudgetGuard::config)
+    }
+
+    #[wasm_bindgen]
+    pub fn begin_tick(&mut self) -> Result<(), NeuralError> {
+        let budget = self.budget.as_mut().ok_or_else(|| NeuralError::InvalidConfiguration("no budget is set".to_string()))?;
+        budget.begin_tick();
+        Ok(())
+    }
+
+    // Close the tick and return its status code; the details are in last_budget_report()
+    #[wasm_bindgen]
+    pub fn end_tick(&mut self) -> Result<BudgetStatus, NeuralError> {
+        let memory_bytes = self.memory_pool.reserved_bytes() + self.scratch.stats().cached_bytes();
+        let budget = self.budget.as_mut().ok_or_else(|| NeuralError::InvalidConfiguration("no budget is set".to_string()))?;
+        let status = budget.end_tick(memory_bytes)?;
+        if matches!(status, BudgetStatus::MemoryExceeded | BudgetStatus::TimeAndMemoryExceeded) {
+            self.scratch.clear();
+        }
+        Ok(status)
+    }
+
+    #[wasm_bindgen]
+    pub fn last_budget_report(&self) -> Option<BudgetReport> {
+        self.budget.as_ref().and_then(BudgetGuard::last_report)
+    }
+
+    #[wasm_bindgen]
+    pub fn downshift(&self) -> Downshift {
+        self.budget.as_ref().map_or(Downshift::None, BudgetGuard::level)
+    }
+
+    // `requested` capped at the budget's reduced batch while downshifted to SmallerBatch
+    #[wasm_bindgen]
+    pub fn recommended_batch_size(&self, requested: usize) -> usize {
+        requested.min(self.batch_chunk())
     }
 
     // Benchmark function
     #[wasm_bindgen]
-    pub fn benchmark(&mut self) -> BenchmarkResult {
-        let test_size = 10000;
-        let test_data: Vec<f32> = (0..test_size).map(|i| (i as f32) / test_size as f32).collect();
-        
-        let start_time = web_sys::window()
-            .unwrap()
-            .performance()
-            .unwrap()
-            .now();
-        
-        // Run multiple operations
-        for _ in 0..100 {
-            let _result = self.calculate_neural_activation(&test_data);
+    pub fn benchmark(&mut self) -> Result<BenchmarkResult, NeuralError> {
+        let test_data = benchmark_data(BENCHMARK_LEN);
+        self.time_kernel(&Clock::new(), 100, |runtime| runtime.calculate_neural_activation(&test_data).map(drop))
+    }
+
+    // Time matmul, activation, spike counting and buffer allocation separately,
+    // `iterations` calls each. Works in windows, workers and Node; see time_source()
+    // for the resolution of the clock in use.
+    #[wasm_bindgen]
+    pub fn benchmark_suite(&mut self, iterations: u32) -> Result<BenchmarkSuite, NeuralError> {
+        let clock = Clock::new();
+        let iterations = iterations.max(1);
+        let test_data = benchmark_data(BENCHMARK_LEN);
+        let matrix = benchmark_data(BENCHMARK_MATRIX * BENCHMARK_MATRIX);
+        let size = BENCHMARK_MATRIX;
+
+        Ok(BenchmarkSuite {
+            matmul: self.time_kernel(&clock, iterations, |runtime| runtime.matmul(&matrix, &matrix, size, size, size).map(drop))?,
+            activation: self.time_kernel(&clock, iterations, |runtime| runtime.calculate_neural_activation(&test_data).map(drop))?,
+            spike_counting: self.time_kernel(&clock, iterations, |runtime| {
+                runtime.process_spike_train(&test_data, 1000.0);
+                Ok(())
+            })?,
+            memory: self.time_kernel(&clock, iterations, |runtime| {
+                let handle = runtime.alloc_buffer(BENCHMARK_LEN)?;
+                runtime.free_buffer(handle)
+            })?,
+        })
+    }
+}
+
+impl NeuralRuntime {
+    // Start timing `kernel`; the clock is read when profiling, a budget or tracing needs it
+    fn start_kernel(&mut self, kernel: &'static str) -> KernelTimer {
+        let started = match self.budget.is_some() || self.tracer.is_some() {
+            true => self.profiler.now_ms(),
+            false => self.profiler.start(),
+        };
+        let span = self.tracer.as_mut().map(|tracer| tracer.start(kernel));
+        KernelTimer { kernel, started, span }
+    }
+
+    // Record a finished kernel with the profiler, charge it to the budget's tick and
+    // end its trace span
+    fn finish_kernel(&mut self, timer: KernelTimer, bytes: usize, dims: &[usize]) {
+        if self.budget.is_none() && timer.span.is_none() {
+            self.profiler.record(timer.kernel, timer.started, bytes);
+            return;
         }
-        
-        let end_time = web_sys::window()
-            .unwrap()
-            .performance()
-            .unwrap()
-            .now();
-        
-        let duration_ms = end_time - start_time;
-        let operations_per_second = (100.0 * 1000.0) / duration_ms;
-        
+        let elapsed_ms = (self.profiler.now_ms() - timer.started).max(0.0);
+        self.profiler.record_elapsed(timer.kernel, elapsed_ms, bytes);
+        if let Some(budget) = self.budget.as_mut() {
+            budget.charge(timer.kernel, elapsed_ms);
+        }
+        if let (Some(tracer), Some(span)) = (self.tracer.as_mut(), timer.span) {
+            tracer.finish(timer.kernel, span, elapsed_ms, bytes, dims);
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: &RuntimeSnapshot, len: usize) -> NeuralResult<CompatibilityReport> {
+        snapshot.restore_runtime(self)?;
+        let report = snapshot.compatibility_report();
+        log_event!(LogLevel::Info, "runtime", "restored from a version {} snapshot of {} bytes", report.source_version(), len);
+        Ok(report)
+    }
+
+    fn tracer_mut(&mut self) -> NeuralResult<&mut Tracer> {
+        self.tracer.as_mut().ok_or_else(|| NeuralError::InvalidConfiguration("tracing is not enabled".to_string()))
+    }
+
+    fn kernel_accuracy(&self) -> ActivationAccuracy {
+        match self.downshift() >= Downshift::ReducedPrecision {
+            true => ActivationAccuracy::Fast,
+            false => self.activation_accuracy,
+        }
+    }
+
+    fn optimization_skipped(&self) -> bool {
+        self.downshift() >= Downshift::SkipOptimization
+    }
+
+    // Most samples a batch kernel runs at once
+    fn batch_chunk(&self) -> usize {
+        match &self.budget {
+            Some(budget) if budget.level() >= Downshift::SmallerBatch => budget.config().reduced_batch,
+            _ => usize::MAX,
+        }
+    }
+
+    // Run `kernel` `iterations` times and summarize the elapsed time
+    fn time_kernel<F>(&mut self, clock: &Clock, iterations: u32, mut kernel: F) -> NeuralResult<BenchmarkResult>
+    where
+        F: FnMut(&mut NeuralRuntime) -> NeuralResult<()>,
+    {
+        let start_time = clock.now_ms();
+        for _ in 0..iterations {
+            kernel(self)?;
+        }
+        let duration_ms = clock.now_ms() - start_time;
+        Ok(self.benchmark_result(iterations, duration_ms))
+    }
+
+    fn benchmark_result(&self, iterations: u32, duration_ms: f64) -> BenchmarkResult {
         BenchmarkResult {
-            operations_per_second: operations_per_second as u32,
+            operations_per_second: (iterations as f64 * 1000.0 / duration_ms) as u32,
             memory_usage: self.get_memory_usage(),
             simd_acceleration: self.simd_enabled,
-            average_operation_time: duration_ms / 100.0,
+            average_operation_time: duration_ms / iterations as f64,
+        }
+    }
+
+    // Same backend and thread configuration, with fresh state
+    fn fork(&self) -> NeuralRuntime {
+        let mut runtime = NeuralRuntime::new();
+        #[cfg(feature = "webgpu")]
+        {
+            runtime.gpu = self.gpu.clone();
         }
+        let _ = runtime.set_backend(self.backend());
+        runtime.thread_count = self.thread_count;
+        runtime.profiler.set_enabled(self.profiler.is_enabled());
+        runtime
+    }
+}
+
+#[wasm_bindgen]
+impl NeuralRuntime {
+    // Benchmark without blocking the page: runs `iterations` activation passes,
+    // yielding to the event loop between them, and rejects with a Cancelled error
+    // once `token` is cancelled. Time spent yielded is excluded from the result.
+    #[wasm_bindgen]
+    pub fn benchmark_async(&self, iterations: u32, token: &CancellationToken) -> js_sys::Promise {
+        let mut runtime = self.fork();
+        let token = token.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let clock = Clock::new();
+            let test_data = benchmark_data(BENCHMARK_LEN);
+            let iterations = iterations.max(1);
+
+            let mut yielder = tasks::Yielder::new(tasks::DEFAULT_SLICE_MS);
+            let mut duration_ms = 0.0;
+            for _ in 0..iterations {
+                yielder.checkpoint(&token).await?;
+                let start_time = clock.now_ms();
+                runtime.calculate_neural_activation(&test_data)?;
+                duration_ms += clock.now_ms() - start_time;
+            }
+
+            Ok(runtime.benchmark_result(iterations, duration_ms).into())
+        })
+    }
+}
+
+// Bytes held by `count` f32 values, for the profiler's throughput figures
+fn float_bytes(count: usize) -> usize {
+    count * std::mem::size_of::<f32>()
+}
+
+// tanh(x / 2) over `values` in place, the kernel behind calculate_neural_activation
+fn neural_activation(values: &mut [f32], simd: bool, accuracy: ActivationAccuracy) {
+    simd_dispatch!(simd && values.len() >= 4, simd_neural_activation(values, accuracy), {
+        let _ = accuracy;
+        scalar_neural_activation(values)
+    })
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_neural_activation(values: &mut [f32], accuracy: ActivationAccuracy) {
+    let mut chunks = values.chunks_exact_mut(4);
+    let scale = f32x4_splat(0.5);
+
+    // Process 4 elements at a time with SIMD
+    for chunk in &mut chunks {
+        let scaled = f32x4_mul(simd::load(chunk), scale);
+        simd::store(chunk, activation::simd_tanh(scaled, accuracy));
+    }
+
+    // Handle remaining elements with scalar operations
+    scalar_neural_activation(chunks.into_remainder());
+}
+
+// Scalar fallback activation
+fn scalar_neural_activation(values: &mut [f32]) {
+    for value in values.iter_mut() {
+        *value = (*value * 0.5).tanh();
+    }
+}
+
+// A kernel between start_kernel and finish_kernel
+struct KernelTimer {
+    kernel: &'static str,
+    started: f64,
+    span: Option<OpenSpan>,
+}
+
+// forward_with_scratch in chunks of at most `chunk` samples
+fn forward_chunked(
+    network: &NeuralNetwork,
+    inputs: &[f32],
+    batch_size: usize,
+    chunk: usize,
+    scratch: &mut ScratchAllocator,
+    observe: &mut dyn FnMut(usize, &[f32]),
+) -> NeuralResult<Vec<f32>> {
+    if batch_size <= chunk {
+        return network.forward_with_scratch(inputs, batch_size, scratch, observe);
     }
+    let (input_size, output_size) = (network.input_size(), network.output_size());
+    if inputs.len() != batch_size * input_size {
+        return Err(NeuralError::DimensionMismatch { expected: batch_size * input_size, actual: inputs.len() });
+    }
+    let mut outputs = Vec::with_capacity(batch_size * output_size);
+    for rows in inputs.chunks(chunk * input_size) {
+        let samples = rows.len() / input_size;
+        let part = network.forward_with_scratch(rows, samples, scratch, observe)?;
+        outputs.extend_from_slice(&part);
+        scratch.give((samples, output_size), part);
+    }
+    Ok(outputs)
+}
+
+// Run `optimizer` over `values`, drawing uniform [0, 1) samples from the secure
+// source when one is selected
+fn optimize_with(
+    optimizer: &mut dyn ConnectionOptimizer,
+    rng: &mut Rng,
+    secure_rng: &mut Option<SecureRng>,
+    values: &mut [f32],
+    signals: &[f32],
+) {
+    let mut random = || match secure_rng.as_mut() {
+        Some(secure) => secure.next_f32(),
+        None => rng.next_f32(),
+    };
+    optimizer.optimize(values, signals, &mut random);
+}
+
+// Elements per activation and spike benchmark call, and the benchmark matrix side
+const BENCHMARK_LEN: usize = 10000;
+const BENCHMARK_MATRIX: usize = 64;
+
+// Ramp over [0, 1): inside the activation bounds, and mostly above the spike threshold
+fn benchmark_data(len: usize) -> Vec<f32> {
+    (0..len).map(|i| i as f32 / len as f32).collect()
 }
 
 #[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
 pub struct BenchmarkResult {
     pub operations_per_second: u32,
     pub memory_usage: usize,
@@ -395,6 +1428,16 @@ pub struct BenchmarkResult {
     pub average_operation_time: f64,
 }
 
+// Per-kernel results of NeuralRuntime.benchmark_suite
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkSuite {
+    pub matmul: BenchmarkResult,
+    pub activation: BenchmarkResult,
+    pub spike_counting: BenchmarkResult,
+    pub memory: BenchmarkResult,
+}
+
 // Export functions for JavaScript integration
 #[wasm_bindgen]
 pub fn create_neural_runtime() -> NeuralRuntime {
@@ -404,4 +1447,10 @@ pub fn create_neural_runtime() -> NeuralRuntime {
 #[wasm_bindgen]
 pub fn check_simd_support() -> bool {
     NeuralRuntime::detect_simd_support()
+}
+
+// Linear memory backing zero-copy buffer views
+#[wasm_bindgen]
+pub fn wasm_memory() -> JsValue {
+    wasm_bindgen::memory()
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+// Dense linear algebra kernels with SIMD acceleration (simd128, or AVX2/NEON natively)
+// All matrices are row-major f32 buffers.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::checked;
+use crate::error::{NeuralError, NeuralResult};
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+#[cfg(native_simd)]
+use crate::native_simd;
+
+// C[m×n] = A[m×k] · B[k×n]
+pub fn matmul_into(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize, simd: bool) -> NeuralResult<()> {
+    check_len(a.len(), checked::elements(m, k)?)?;
+    check_len(b.len(), checked::elements(k, n)?)?;
+    check_len(c.len(), checked::elements(m, n)?)?;
+
+    simd_dispatch!(
+        simd && n >= 4,
+        simd_matmul(a, b, c, m, n, k),
+        native: native_simd::matmul(a, b, c, m, n, k),
+        scalar_matmul(a, b, c, m, n, k)
+    );
+    checked::check_outputs("matmul", c)
+}
+
+// y[rows] = W[rows×cols] · x[cols]
+pub fn matvec_into(w: &[f32], x: &[f32], y: &mut [f32], rows: usize, cols: usize, simd: bool) -> NeuralResult<()> {
+    check_len(w.len(), checked::elements(rows, cols)?)?;
+    check_len(x.len(), cols)?;
+    check_len(y.len(), rows)?;
+
+    for (out, row) in y.iter_mut().zip(w.chunks_exact(cols)) {
+        *out = simd_dispatch!(simd && cols >= 4, simd_dot(row, x), native: native_simd::dot(row, x), scalar_dot(row, x));
+    }
+    checked::check_outputs("matvec", y)
+}
+
+// C[m×n] = A[m×k] · B[n×k]ᵀ, i.e. every output is a dot product of two contiguous rows
+pub fn matmul_transposed_into(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize, simd: bool) -> NeuralResult<()> {
+    check_len(a.len(), checked::elements(m, k)?)?;
+    check_len(b.len(), checked::elements(n, k)?)?;
+    check_len(c.len(), checked::elements(m, n)?)?;
+
+    if k == 0 {
+        c.fill(0.0);
+        return Ok(());
+    }
+    for (a_row, c_row) in a.chunks_exact(k).zip(c.chunks_exact_mut(n.max(1))) {
+        for (out, b_row) in c_row.iter_mut().zip(b.chunks_exact(k)) {
+            *out = simd_dispatch!(
+                simd && k >= 4,
+                simd_dot(a_row, b_row),
+                native: native_simd::dot(a_row, b_row),
+                scalar_dot(a_row, b_row)
+            );
+        }
+    }
+    checked::check_outputs("matmul_transposed", c)
+}
+
+// Dot product over the common length of `a` and `b`
+pub fn dot(a: &[f32], b: &[f32], simd: bool) -> f32 {
+    simd_dispatch!(simd && a.len().min(b.len()) >= 4, simd_dot(a, b), native: native_simd::dot(a, b), scalar_dot(a, b))
+}
+
+// y += alpha · x over the common length of `x` and `y`
+pub fn axpy(alpha: f32, x: &[f32], y: &mut [f32], simd: bool) {
+    simd_dispatch!(
+        simd && x.len().min(y.len()) >= 4,
+        simd_axpy(alpha, x, y),
+        native: native_simd::axpy(alpha, x, y),
+        scalar_axpy(alpha, x, y)
+    )
+}
+
+// y *= alpha
+pub fn scale(alpha: f32, y: &mut [f32], simd: bool) {
+    simd_dispatch!(simd && y.len() >= 4, simd_scale(alpha, y), scalar_scale(alpha, y))
+}
+
+fn check_len(actual: usize, expected: usize) -> NeuralResult<()> {
+    if actual != expected {
+        return Err(NeuralError::DimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+// Broadcast each A element across a row of B so the inner loop runs over contiguous memory
+#[cfg(target_feature = "simd128")]
+fn simd_matmul(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) {
+    for i in 0..m {
+        let c_row = &mut c[i * n..(i + 1) * n];
+        c_row.fill(0.0);
+
+        for p in 0..k {
+            let a_ip = a[i * k + p];
+            let a_vec = f32x4_splat(a_ip);
+            let mut c_chunks = c_row.chunks_exact_mut(4);
+            let mut b_chunks = b[p * n..(p + 1) * n].chunks_exact(4);
+
+            for (c_chunk, b_chunk) in (&mut c_chunks).zip(&mut b_chunks) {
+                let sum = f32x4_add(simd::load(c_chunk), f32x4_mul(a_vec, simd::load(b_chunk)));
+                simd::store(c_chunk, sum);
+            }
+
+            // Handle remaining columns with scalar operations
+            scalar_axpy(a_ip, b_chunks.remainder(), c_chunks.into_remainder());
+        }
+    }
+}
+
+fn scalar_matmul(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) {
+    for i in 0..m {
+        for j in 0..n {
+            let mut sum = 0.0;
+            for p in 0..k {
+                sum += a[i * k + p] * b[p * n + j];
+            }
+            c[i * n + j] = sum;
+        }
+    }
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_dot(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let a_chunks = a[..len].chunks_exact(4);
+    let b_chunks = b[..len].chunks_exact(4);
+    let tail = scalar_dot(a_chunks.remainder(), b_chunks.remainder());
+    let mut acc = f32x4_splat(0.0);
+
+    for (a_chunk, b_chunk) in a_chunks.zip(b_chunks) {
+        acc = f32x4_add(acc, f32x4_mul(simd::load(a_chunk), simd::load(b_chunk)));
+    }
+
+    simd::f32_sum(acc) + tail
+}
+
+fn scalar_dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+    let len = x.len().min(y.len());
+    let mut x_chunks = x[..len].chunks_exact(4);
+    let mut y_chunks = y[..len].chunks_exact_mut(4);
+    let alpha_vec = f32x4_splat(alpha);
+
+    for (x_chunk, y_chunk) in (&mut x_chunks).zip(&mut y_chunks) {
+        let sum = f32x4_add(simd::load(y_chunk), f32x4_mul(alpha_vec, simd::load(x_chunk)));
+        simd::store(y_chunk, sum);
+    }
+
+    scalar_axpy(alpha, x_chunks.remainder(), y_chunks.into_remainder());
+}
+
+fn scalar_axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += alpha * x;
+    }
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_scale(alpha: f32, y: &mut [f32]) {
+    let mut chunks = y.chunks_exact_mut(4);
+    let alpha_vec = f32x4_splat(alpha);
+    for chunk in &mut chunks {
+        simd::store(chunk, f32x4_mul(alpha_vec, simd::load(chunk)));
+    }
+    scalar_scale(alpha, chunks.into_remainder());
+}
+
+fn scalar_scale(alpha: f32, y: &mut [f32]) {
+    for y in y.iter_mut() {
+        *y *= alpha;
+    }
+}
+
+// Export for JavaScript integration: returns the m×n product
+#[wasm_bindgen]
+pub fn matmul(a: &[f32], b: &[f32], m: usize, n: usize, k: usize) -> Result<Vec<f32>, NeuralError> {
+    let mut c = vec![0.0; checked::elements(m, n)?];
+    matmul_into(a, b, &mut c, m, n, k, crate::check_simd_support())?;
+    Ok(c)
+}
@@ -0,0 +1,141 @@
+// Leveled logging to the JS console with optional structured forwarding
+//
+// Events below the current level are dropped before their message is formatted.
+// Enabled events go to the matching console method and, when a sink is set, to a
+// JS callback as a JSON string {"time_ms", "level", "target", "message"} so a
+// dashboard can collect them. Errors thrown into JS are logged under the "error"
+// target, and panics print their message and stack through console_error_panic_hook.
+// Settings are per thread: workers of the `threads` pool start from the defaults.
+
+use std::cell::{Cell, RefCell};
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace = 0,
+    Debug = 1,
+    Info = 2,
+    Warn = 3,
+    Error = 4,
+    // Disables logging entirely
+    Off = 5,
+}
+
+impl LogLevel {
+    fn name(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Off => "off",
+        }
+    }
+}
+
+thread_local! {
+    static LEVEL: Cell<LogLevel> = const { Cell::new(LogLevel::Warn) };
+    static CONSOLE: Cell<bool> = const { Cell::new(true) };
+    static SINK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+// Route Rust panics to console.error with a stack trace; NeuralRuntime::new calls this too
+#[wasm_bindgen]
+pub fn install_panic_hook() {
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.with(|current| current.set(level));
+}
+
+#[wasm_bindgen]
+pub fn log_level() -> LogLevel {
+    LEVEL.with(Cell::get)
+}
+
+// Write enabled events to the browser console (on by default)
+#[wasm_bindgen]
+pub fn set_console_logging(enabled: bool) {
+    CONSOLE.with(|console| console.set(enabled));
+}
+
+// Call `sink(json)` for every enabled event; pass undefined to stop forwarding
+#[wasm_bindgen]
+pub fn set_log_sink(sink: Option<js_sys::Function>) {
+    SINK.with(|current| *current.borrow_mut() = sink);
+}
+
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level >= log_level()
+}
+
+pub(crate) fn emit(level: LogLevel, target: &str, message: &str) {
+    if CONSOLE.with(Cell::get) {
+        write_console(level, &format!("[{}] {}", target, message));
+    }
+    // Clone the callback out so a sink that reconfigures logging doesn't hit a live borrow
+    if let Some(sink) = SINK.with(|sink| sink.borrow().clone()) {
+        let event = event_json(crate::clock::wall_time_ms(), level, target, message);
+        let _ = sink.call1(&JsValue::NULL, &JsValue::from_str(&event));
+    }
+}
+
+// Log a formatted message; the arguments are only formatted when `level` is enabled
+macro_rules! log_event {
+    ($level:expr, $target:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::logging::enabled(level) {
+            $crate::logging::emit(level, $target, &format!($($arg)+));
+        }
+    }};
+}
+
+pub(crate) use log_event;
+
+fn event_json(time_ms: f64, level: LogLevel, target: &str, message: &str) -> String {
+    let mut json = format!("{{\"time_ms\":{},\"level\":\"{}\",\"target\":", time_ms, level.name());
+    push_json_string(&mut json, target);
+    json.push_str(",\"message\":");
+    push_json_string(&mut json, message);
+    json.push('}');
+    json
+}
+
+pub(crate) fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(js_host)]
+fn write_console(level: LogLevel, line: &str) {
+    use web_sys::console;
+
+    let line = JsValue::from_str(line);
+    match level {
+        LogLevel::Trace | LogLevel::Debug => console::debug_1(&line),
+        LogLevel::Info => console::info_1(&line),
+        LogLevel::Warn => console::warn_1(&line),
+        LogLevel::Error | LogLevel::Off => console::error_1(&line),
+    }
+}
+
+#[cfg(not(js_host))]
+fn write_console(level: LogLevel, line: &str) {
+    eprintln!("{} {}", level.name(), line);
+}
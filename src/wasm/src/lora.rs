@@ -0,0 +1,199 @@
+// Low-rank adaptation (LoRA) of dense layers
+//
+// NeuralNetwork.add_adapter(layer, rank, alpha) attaches an adapter to a dense
+// layer, which then computes W·x + (alpha / rank)·B·(A·x) + b. A (rank × inputs)
+// starts uniform in ±1/√inputs from the network's RNG and B (outputs × rank) at
+// zero, so attaching changes no output. Once any adapter is attached, training
+// (train_batch and everything built on it, compute_gradients and apply_gradients)
+// updates the adapters only: base weights, biases and normalization parameters stay
+// frozen, so many agents can share one base model and personalize it with
+// rank · (inputs + outputs) parameters per adapted layer. Batch norm still updates
+// its running statistics in training passes, and mixed-precision training does not
+// support adapters.
+//
+// The base model and the adapters are stored apart: export_weights, get_parameters
+// and diff_weights cover the base only, while export_adapters and import_adapters
+// move the adapters on their own and merge_adapters folds them into the base weights
+// for good. import_weights (and its encrypted and verified forms) replaces the base
+// weights and leaves the adapters attached. Snapshots, checkpoints and replay traces
+// hold both, since the optimizer's moments follow the adapters, and weights_digest
+// (so attestations) covers both. Inference (including fixed-point), attribution and
+// the FANN export see the adapted layers.
+//
+// Layout (all integers and floats little-endian):
+//   magic          b"SASL"
+//   version        u16
+//   reserved       u16
+//   adapter_count  u32
+//   adapter_count × { layer u32, inputs u32, outputs u32, rank u32, alpha f32,
+//                     A f32[rank × inputs], B f32[outputs × rank] }
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+use crate::rng::Rng;
+use crate::serialization::{ByteReader, ByteWriter};
+
+pub const ADAPTERS_MAGIC: &[u8; 4] = b"SASL";
+pub const ADAPTERS_VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LoraAdapter {
+    inputs: usize,
+    outputs: usize,
+    pub(crate) rank: usize,
+    pub(crate) alpha: f32,
+    // A then B in one buffer, so the optimizer steps the adapter as one tensor
+    pub(crate) parameters: Vec<f32>,
+}
+
+impl LoraAdapter {
+    pub(crate) fn new(inputs: usize, outputs: usize, rank: usize, alpha: f32, rng: &mut Rng) -> NeuralResult<LoraAdapter> {
+        check_config(inputs, outputs, rank, alpha)?;
+        let bound = 1.0 / (inputs as f32).sqrt();
+        let mut parameters: Vec<f32> = (0..rank * inputs).map(|_| rng.uniform(-bound, bound)).collect();
+        parameters.resize(rank * (inputs + outputs), 0.0);
+        Ok(LoraAdapter { inputs, outputs, rank, alpha, parameters })
+    }
+
+    fn from_parts(inputs: usize, outputs: usize, rank: usize, alpha: f32, parameters: Vec<f32>) -> NeuralResult<LoraAdapter> {
+        check_config(inputs, outputs, rank, alpha)?;
+        if let Some(index) = parameters.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(LoraAdapter { inputs, outputs, rank, alpha, parameters })
+    }
+
+    pub(crate) fn matches(&self, inputs: usize, outputs: usize) -> bool {
+        self.inputs == inputs && self.outputs == outputs && self.parameters.len() == self.rank * (inputs + outputs)
+    }
+
+    fn scale(&self) -> f32 {
+        self.alpha / self.rank as f32
+    }
+
+    // (A, B)
+    fn factors(&self) -> (&[f32], &[f32]) {
+        self.parameters.split_at(self.rank * self.inputs)
+    }
+
+    // Add scale · B·(A·x) to every row of `outputs` [batch × outputs] for the
+    // matching row of `inputs` [batch × inputs]
+    pub(crate) fn add_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        let (down, up) = self.factors();
+        let (mut hidden, mut update) = (vec![0.0; self.rank], vec![0.0; self.outputs]);
+        for (x, y) in inputs.chunks_exact(self.inputs).zip(outputs.chunks_exact_mut(self.outputs)) {
+            linalg::matvec_into(down, x, &mut hidden, self.rank, self.inputs, simd)?;
+            linalg::matvec_into(up, &hidden, &mut update, self.outputs, self.rank, simd)?;
+            linalg::axpy(self.scale(), &update, y, simd);
+        }
+        Ok(())
+    }
+
+    // base + scale · B·A
+    pub(crate) fn merged(&self, base: &[f32], simd: bool) -> Vec<f32> {
+        let (down, up) = self.factors();
+        let mut weights = base.to_vec();
+        for (row, coefficients) in weights.chunks_exact_mut(self.inputs).zip(up.chunks_exact(self.rank)) {
+            for (b, a_row) in coefficients.iter().zip(down.chunks_exact(self.inputs)) {
+                linalg::axpy(self.scale() * b, a_row, row, simd);
+            }
+        }
+        weights
+    }
+
+    // One sample's backward pass given dL/d(pre-activation) `delta`: accumulate
+    // dL/dA and dL/dB into `gradients` (laid out like the parameters) and add the
+    // adapter's share of dL/dx to `upstream`
+    pub(crate) fn backward(&self, x: &[f32], delta: &[f32], gradients: &mut [f32], upstream: Option<&mut [f32]>, simd: bool) -> NeuralResult<()> {
+        let (down, up) = self.factors();
+        let scale = self.scale();
+        let mut hidden = vec![0.0; self.rank];
+        linalg::matvec_into(down, x, &mut hidden, self.rank, self.inputs, simd)?;
+        // Bᵀ · delta
+        let mut back = vec![0.0; self.rank];
+        for (b_row, &d) in up.chunks_exact(self.rank).zip(delta) {
+            linalg::axpy(d, b_row, &mut back, simd);
+        }
+
+        let (down_grad, up_grad) = gradients.split_at_mut(self.rank * self.inputs);
+        for (row, &d) in up_grad.chunks_exact_mut(self.rank).zip(delta) {
+            linalg::axpy(scale * d, &hidden, row, simd);
+        }
+        for (row, &u) in down_grad.chunks_exact_mut(self.inputs).zip(&back) {
+            linalg::axpy(scale * u, x, row, simd);
+        }
+        if let Some(upstream) = upstream {
+            for (a_row, &u) in down.chunks_exact(self.inputs).zip(&back) {
+                linalg::axpy(scale * u, a_row, upstream, simd);
+            }
+        }
+        Ok(())
+    }
+
+    // Extra floating-point operations per sample: both products and the scaled add
+    pub(crate) fn forward_flops(&self) -> usize {
+        2 * self.parameters.len() + 2 * self.outputs
+    }
+}
+
+fn check_config(inputs: usize, outputs: usize, rank: usize, alpha: f32) -> NeuralResult<()> {
+    if rank == 0 || rank > inputs.min(outputs) {
+        return Err(NeuralError::InvalidConfiguration(format!(
+            "adapter rank must be between 1 and {} for a {}×{} layer",
+            inputs.min(outputs),
+            outputs,
+            inputs
+        )));
+    }
+    if !alpha.is_finite() || alpha <= 0.0 {
+        return Err(NeuralError::InvalidConfiguration("adapter alpha must be positive and finite".to_string()));
+    }
+    Ok(())
+}
+
+pub(crate) fn encode(adapters: &[(usize, &LoraAdapter)]) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.bytes(ADAPTERS_MAGIC);
+    writer.u16(ADAPTERS_VERSION);
+    writer.u16(0);
+    writer.u32(adapters.len() as u32);
+    for &(layer, adapter) in adapters {
+        writer.u32(layer as u32);
+        writer.u32(adapter.inputs as u32);
+        writer.u32(adapter.outputs as u32);
+        writer.u32(adapter.rank as u32);
+        writer.f32(adapter.alpha);
+        writer.f32_slice(&adapter.parameters);
+    }
+    writer.finish()
+}
+
+// (layer, adapter) pairs; the caller checks them against its layers
+pub(crate) fn decode(bytes: &[u8]) -> NeuralResult<Vec<(usize, LoraAdapter)>> {
+    let mut reader = ByteReader::new(bytes);
+    if reader.bytes(4)? != ADAPTERS_MAGIC {
+        return Err(NeuralError::InvalidFormat("missing SASL header".to_string()));
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > ADAPTERS_VERSION {
+        return Err(NeuralError::InvalidFormat(format!("unsupported adapter format version {}", version)));
+    }
+    reader.u16()?;
+    let count = reader.u32()? as usize;
+    let mut adapters = Vec::with_capacity(count.min(256));
+    for _ in 0..count {
+        let layer = reader.u32()? as usize;
+        let (inputs, outputs, rank) = (reader.u32()? as usize, reader.u32()? as usize, reader.u32()? as usize);
+        let alpha = reader.f32()?;
+        let len = inputs
+            .checked_add(outputs)
+            .and_then(|width| width.checked_mul(rank))
+            .ok_or_else(|| NeuralError::InvalidFormat("adapter size overflows".to_string()))?;
+        let parameters = reader.f32_vec(len)?;
+        adapters.push((layer, LoraAdapter::from_parts(inputs, outputs, rank, alpha, parameters)?));
+    }
+    if !reader.is_empty() {
+        return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+    }
+    Ok(adapters)
+}
@@ -0,0 +1,206 @@
+// Training losses
+//
+// Each loss scores one sample's n outputs y against its targets t and gives the
+// gradient dL/dy that backpropagation starts from; batches average the samples.
+//   MeanSquaredError    Σ(y - t)² / n
+//   MeanAbsoluteError   Σ|y - t| / n
+//   Huber               Σ h(y - t) / n, h(d) = d²/2 for |d| ≤ δ, else δ(|d| - δ/2)
+//   CrossEntropy        -Σ t·ln(y)                      (y: probabilities, e.g. softmax)
+//   BinaryCrossEntropy  -Σ (t·ln(y) + (1-t)·ln(1-y)) / n (y: sigmoid outputs)
+//   Hinge               Σ max(0, 1 - t·y) / n           (t: -1 or 1)
+// Probabilities are clamped to [1e-7, 1 - 1e-7] so the logarithms stay finite.
+//
+// A JavaScript function can replace the built-in losses, e.g. for reward shaping:
+// it is called as loss(predicted, target) with one sample's Float32Arrays and
+// returns an array of n + 1 numbers, the loss followed by dL/dy.
+
+use wasm_bindgen::prelude::*;
+
+use crate::distillation::Distillation;
+use crate::error::{NeuralError, NeuralResult};
+
+const PROBABILITY_EPSILON: f32 = 1e-7;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossKind {
+    MeanSquaredError = 0,
+    MeanAbsoluteError = 1,
+    Huber = 2,
+    CrossEntropy = 3,
+    BinaryCrossEntropy = 4,
+    Hinge = 5,
+}
+
+impl LossKind {
+    pub fn from_u8(value: u8) -> Option<LossKind> {
+        match value {
+            0 => Some(LossKind::MeanSquaredError),
+            1 => Some(LossKind::MeanAbsoluteError),
+            2 => Some(LossKind::Huber),
+            3 => Some(LossKind::CrossEntropy),
+            4 => Some(LossKind::BinaryCrossEntropy),
+            5 => Some(LossKind::Hinge),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LossFunction {
+    pub kind: LossKind,
+    // Huber threshold δ between the quadratic and linear regions
+    pub delta: f32,
+}
+
+impl Default for LossFunction {
+    fn default() -> Self {
+        LossFunction::new(LossKind::MeanSquaredError)
+    }
+}
+
+#[wasm_bindgen]
+impl LossFunction {
+    #[wasm_bindgen(constructor)]
+    pub fn new(kind: LossKind) -> LossFunction {
+        LossFunction { kind, delta: 1.0 }
+    }
+
+    // Mean loss over a row-major batch of predictions and targets
+    #[wasm_bindgen]
+    pub fn evaluate(&self, predicted: &[f32], targets: &[f32], batch_size: usize) -> Result<f32, NeuralError> {
+        self.validate()?;
+        if batch_size == 0 || !predicted.len().is_multiple_of(batch_size) {
+            return Err(NeuralError::InvalidConfiguration("predictions must hold batch_size whole samples".to_string()));
+        }
+        if targets.len() != predicted.len() {
+            return Err(NeuralError::DimensionMismatch { expected: predicted.len(), actual: targets.len() });
+        }
+        let outputs = predicted.len() / batch_size;
+        let mut grad = vec![0.0; outputs];
+        let total: f32 = predicted
+            .chunks_exact(outputs.max(1))
+            .zip(targets.chunks_exact(outputs.max(1)))
+            .map(|(y, t)| self.sample(y, t, &mut grad))
+            .sum();
+        Ok(total / batch_size as f32)
+    }
+}
+
+impl LossFunction {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if self.kind == LossKind::Huber && !(self.delta.is_finite() && self.delta > 0.0) {
+            return Err(NeuralError::InvalidConfiguration("Huber delta must be positive and finite".to_string()));
+        }
+        Ok(())
+    }
+
+    // Loss of one sample; writes dL/dy into `grad`
+    pub(crate) fn sample(&self, predicted: &[f32], target: &[f32], grad: &mut [f32]) -> f32 {
+        let n = target.len() as f32;
+        let mut loss = 0.0;
+        match self.kind {
+            LossKind::MeanSquaredError => {
+                let scale = 2.0 / n;
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let diff = y - t;
+                    loss += diff * diff;
+                    *g = scale * diff;
+                }
+            }
+            LossKind::MeanAbsoluteError => {
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let diff = y - t;
+                    loss += diff.abs();
+                    *g = if diff == 0.0 { 0.0 } else { diff.signum() / n };
+                }
+            }
+            LossKind::Huber => {
+                let delta = self.delta;
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let diff = y - t;
+                    if diff.abs() <= delta {
+                        loss += 0.5 * diff * diff;
+                        *g = diff / n;
+                    } else {
+                        loss += delta * (diff.abs() - 0.5 * delta);
+                        *g = delta * diff.signum() / n;
+                    }
+                }
+            }
+            LossKind::CrossEntropy => {
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let p = y.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    loss -= t * p.ln();
+                    *g = -t / p;
+                }
+                // Not averaged over outputs
+                return loss;
+            }
+            LossKind::BinaryCrossEntropy => {
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let p = y.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    loss -= t * p.ln() + (1.0 - t) * (1.0 - p).ln();
+                    *g = (p - t) / (p * (1.0 - p)) / n;
+                }
+            }
+            LossKind::Hinge => {
+                for ((g, y), t) in grad.iter_mut().zip(predicted).zip(target) {
+                    let margin = 1.0 - t * y;
+                    if margin > 0.0 {
+                        loss += margin;
+                        *g = -t / n;
+                    } else {
+                        *g = 0.0;
+                    }
+                }
+            }
+        }
+        loss / n
+    }
+}
+
+// The loss a network trains with
+#[derive(Debug, Clone)]
+pub(crate) enum Loss {
+    Builtin(LossFunction),
+    Custom(js_sys::Function),
+    // Soft teacher targets, only while distill trains a student
+    Distillation(Distillation),
+}
+
+impl Default for Loss {
+    fn default() -> Self {
+        Loss::Builtin(LossFunction::default())
+    }
+}
+
+impl Loss {
+    pub(crate) fn sample(&self, predicted: &[f32], target: &[f32], grad: &mut [f32]) -> NeuralResult<f32> {
+        let callback = match self {
+            Loss::Builtin(function) => return Ok(function.sample(predicted, target, grad)),
+            Loss::Distillation(distillation) => return Ok(distillation.sample(predicted, target, grad)),
+            Loss::Custom(callback) => callback,
+        };
+        let returned = callback
+            .call2(&JsValue::NULL, &js_sys::Float32Array::from(predicted), &js_sys::Float32Array::from(target))
+            .map_err(|err| {
+                let reason = err.as_string().unwrap_or_else(|| "exception".to_string());
+                NeuralError::InvalidConfiguration(format!("custom loss threw: {}", reason))
+            })?;
+        let values = js_sys::Float32Array::new(&returned).to_vec();
+        if values.len() != grad.len() + 1 {
+            return Err(NeuralError::InvalidConfiguration(format!(
+                "custom loss returned {} values, expected the loss and {} gradients",
+                values.len(),
+                grad.len()
+            )));
+        }
+        if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        grad.copy_from_slice(&values[1..]);
+        Ok(values[0])
+    }
+}
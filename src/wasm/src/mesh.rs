@@ -0,0 +1,537 @@
+// Topology of the synaptic mesh
+//
+// MeshGraph is an undirected graph of neurons (nodes 0..n) joined by weighted
+// synapses (edges). Each node keeps its neighbours sorted by id, so adjacency
+// queries are a binary search and triangle counting is a merge of two lists.
+//
+// The metrics are topological; weights only enter the strength totals:
+//   clustering coefficient  mean over nodes of links among neighbours / possible links
+//                           (nodes with fewer than two neighbours count as 0)
+//   average path length     mean shortest hop count over connected ordered pairs
+//   small-worldness         sigma = (C / C_rand) / (L / L_rand) against an
+//                           Erdős–Rényi graph of the same size and mean degree k,
+//                           C_rand = k / n and L_rand = ln n / ln k; 0 when undefined
+//   global efficiency       1/(n(n-1)) Σ 1/d over ordered pairs, unreachable pairs
+//                           adding 0, so fragmented meshes score low (Latora & Marchiori)
+// Path lengths run a BFS from every node, O(n · (n + m)).
+//
+// Seeded generators build the standard random topologies with unit weights:
+//   erdos_renyi      each pair linked with probability p, drawn by geometric skips
+//                    so sparse graphs cost O(n + m) (Batagelj & Brandes, 2005)
+//   watts_strogatz   a ring where each node links to its k nearest neighbours, each
+//                    edge rewired to a random node with probability beta
+//   barabasi_albert  a clique of m + 1 nodes, then each new node links to m distinct
+//                    nodes chosen with probability proportional to their degree
+
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+use crate::efficiency::{self, EfficiencyReport, EfficiencyWeights};
+use crate::error::{NeuralError, NeuralResult};
+use crate::serialization::{ByteReader, ByteWriter};
+use crate::rng::Rng;
+use crate::sparse::SparseMatrix;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshGraph {
+    // adjacency[node] = (neighbour, weight), sorted by neighbour
+    adjacency: Vec<Vec<(u32, f32)>>,
+    edge_count: usize,
+}
+
+#[wasm_bindgen]
+impl MeshGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new(nodes: usize) -> Result<MeshGraph, NeuralError> {
+        check_node_count(nodes)?;
+        Ok(MeshGraph { adjacency: vec![Vec::new(); nodes], edge_count: 0 })
+    }
+
+    // A square connectivity matrix, e.g. synapse weights from pre (row) to post (col).
+    // A synapse in either direction links the pair; reciprocal weights are summed and
+    // the diagonal is ignored.
+    #[wasm_bindgen]
+    pub fn from_connectivity(matrix: &SparseMatrix) -> Result<MeshGraph, NeuralError> {
+        let csr = matrix.csr();
+        if csr.rows != csr.cols {
+            return Err(NeuralError::DimensionMismatch { expected: csr.rows, actual: csr.cols });
+        }
+        let mut graph = MeshGraph::new(csr.rows)?;
+        for pre in 0..csr.rows {
+            let (cols, values) = csr.row(pre);
+            for (&post, &weight) in cols.iter().zip(values) {
+                let post = post as usize;
+                if post != pre {
+                    let total = graph.edge_weight(pre, post).unwrap_or(0.0) + weight;
+                    graph.add_edge(pre, post, total)?;
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    #[wasm_bindgen]
+    pub fn erdos_renyi(nodes: usize, probability: f64, seed: u64) -> Result<MeshGraph, NeuralError> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(NeuralError::InvalidConfiguration("edge probability must lie in [0, 1]".to_string()));
+        }
+        let mut graph = MeshGraph::new(nodes)?;
+        if probability == 0.0 {
+            return Ok(graph);
+        }
+        let mut rng = Rng::new(seed);
+        let log_miss = (1.0 - probability).ln();
+        // Walk the lower triangle (v, w < v) in row order, skipping the pairs left out
+        let (mut v, mut w) = (1usize, -1i64);
+        while v < nodes {
+            let skip = ((1.0 - rng.next_f64()).ln() / log_miss).floor();
+            w += 1 + if skip.is_finite() { skip.min(u32::MAX as f64) as i64 } else { 0 };
+            while v < nodes && w >= v as i64 {
+                w -= v as i64;
+                v += 1;
+            }
+            if v < nodes {
+                graph.add_edge(v, w as usize, 1.0)?;
+            }
+        }
+        Ok(graph)
+    }
+
+    // `neighbours` (even) is the ring lattice degree k
+    #[wasm_bindgen]
+    pub fn watts_strogatz(nodes: usize, neighbours: usize, rewire_probability: f64, seed: u64) -> Result<MeshGraph, NeuralError> {
+        if neighbours < 2 || !neighbours.is_multiple_of(2) || neighbours >= nodes {
+            return Err(NeuralError::InvalidConfiguration("ring degree must be even, at least 2 and below the node count".to_string()));
+        }
+        if !(0.0..=1.0).contains(&rewire_probability) {
+            return Err(NeuralError::InvalidConfiguration("rewire probability must lie in [0, 1]".to_string()));
+        }
+        let mut graph = MeshGraph::new(nodes)?;
+        for node in 0..nodes {
+            for offset in 1..=neighbours / 2 {
+                graph.add_edge(node, (node + offset) % nodes, 1.0)?;
+            }
+        }
+        let mut rng = Rng::new(seed);
+        for offset in 1..=neighbours / 2 {
+            for node in 0..nodes {
+                let old = (node + offset) % nodes;
+                // A node already linked to every other one has nowhere to rewire to
+                if rng.next_f64() >= rewire_probability || graph.adjacency[node].len() >= nodes - 1 || !graph.has_edge(node, old) {
+                    continue;
+                }
+                let mut new = rng.next_u64() as usize % nodes;
+                while new == node || graph.has_edge(node, new) {
+                    new = rng.next_u64() as usize % nodes;
+                }
+                graph.remove_edge(node, old)?;
+                graph.add_edge(node, new, 1.0)?;
+            }
+        }
+        Ok(graph)
+    }
+
+    // `attachments` is the number of edges m each new node brings
+    #[wasm_bindgen]
+    pub fn barabasi_albert(nodes: usize, attachments: usize, seed: u64) -> Result<MeshGraph, NeuralError> {
+        if attachments == 0 || attachments >= nodes {
+            return Err(NeuralError::InvalidConfiguration("attachments must be at least 1 and below the node count".to_string()));
+        }
+        let mut graph = MeshGraph::new(nodes)?;
+        // Every edge endpoint, so a uniform pick is degree-proportional
+        let mut endpoints = Vec::with_capacity(2 * attachments * nodes);
+        for a in 0..=attachments {
+            for b in a + 1..=attachments {
+                graph.add_edge(a, b, 1.0)?;
+                endpoints.extend([a, b]);
+            }
+        }
+        let mut rng = Rng::new(seed);
+        let mut targets = Vec::with_capacity(attachments);
+        for node in attachments + 1..nodes {
+            targets.clear();
+            while targets.len() < attachments {
+                let target = endpoints[rng.next_u64() as usize % endpoints.len()];
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            for &target in &targets {
+                graph.add_edge(node, target, 1.0)?;
+                endpoints.extend([node, target]);
+            }
+        }
+        Ok(graph)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    // Append an unconnected node and return its id
+    #[wasm_bindgen]
+    pub fn add_node(&mut self) -> Result<usize, NeuralError> {
+        check_node_count(self.adjacency.len() + 1)?;
+        self.adjacency.push(Vec::new());
+        Ok(self.adjacency.len() - 1)
+    }
+
+    // Connect `a` and `b`, replacing the weight if they are already connected
+    #[wasm_bindgen]
+    pub fn add_edge(&mut self, a: usize, b: usize, weight: f32) -> Result<(), NeuralError> {
+        self.check_node(a)?;
+        self.check_node(b)?;
+        if a == b {
+            return Err(NeuralError::InvalidConfiguration("mesh edges cannot connect a node to itself".to_string()));
+        }
+        if !weight.is_finite() {
+            return Err(NeuralError::NonFiniteInput { index: 0 });
+        }
+        let inserted = insert_neighbour(&mut self.adjacency[a], b as u32, weight);
+        insert_neighbour(&mut self.adjacency[b], a as u32, weight);
+        if inserted {
+            self.edge_count += 1;
+        }
+        Ok(())
+    }
+
+    // Returns whether the edge existed
+    #[wasm_bindgen]
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> Result<bool, NeuralError> {
+        self.check_node(a)?;
+        self.check_node(b)?;
+        let removed = remove_neighbour(&mut self.adjacency[a], b as u32);
+        remove_neighbour(&mut self.adjacency[b], a as u32);
+        if removed {
+            self.edge_count -= 1;
+        }
+        Ok(removed)
+    }
+
+    #[wasm_bindgen]
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.edge_weight(a, b).is_some()
+    }
+
+    // Weight of the a–b edge, undefined when they are not connected
+    #[wasm_bindgen]
+    pub fn edge_weight(&self, a: usize, b: usize) -> Option<f32> {
+        let neighbours = self.adjacency.get(a)?;
+        let b = u32::try_from(b).ok()?;
+        neighbours.binary_search_by_key(&b, |&(node, _)| node).ok().map(|index| neighbours[index].1)
+    }
+
+    // Neighbour ids in ascending order
+    #[wasm_bindgen]
+    pub fn neighbors(&self, node: usize) -> Result<Vec<u32>, NeuralError> {
+        self.check_node(node)?;
+        Ok(self.adjacency[node].iter().map(|&(neighbour, _)| neighbour).collect())
+    }
+
+    #[wasm_bindgen]
+    pub fn degree(&self, node: usize) -> Result<usize, NeuralError> {
+        self.check_node(node)?;
+        Ok(self.adjacency[node].len())
+    }
+
+    // Sum of the weights of the node's edges
+    #[wasm_bindgen]
+    pub fn strength(&self, node: usize) -> Result<f32, NeuralError> {
+        self.check_node(node)?;
+        Ok(self.adjacency[node].iter().map(|&(_, weight)| weight).sum())
+    }
+
+    // Sum of all edge weights, each edge counted once
+    #[wasm_bindgen]
+    pub fn total_weight(&self) -> f32 {
+        let edges = self.adjacency.iter().enumerate().flat_map(|(node, neighbours)| {
+            neighbours.iter().filter(move |&&(neighbour, _)| neighbour as usize > node)
+        });
+        edges.fold(0.0, |total, &(_, weight)| total + weight)
+    }
+
+    // Edges as a fraction of the n(n-1)/2 possible ones
+    #[wasm_bindgen]
+    pub fn density(&self) -> f64 {
+        let nodes = self.adjacency.len() as f64;
+        if nodes < 2.0 {
+            return 0.0;
+        }
+        self.edge_count as f64 / (nodes * (nodes - 1.0) / 2.0)
+    }
+
+    #[wasm_bindgen]
+    pub fn mean_degree(&self) -> f64 {
+        if self.adjacency.is_empty() {
+            return 0.0;
+        }
+        2.0 * self.edge_count as f64 / self.adjacency.len() as f64
+    }
+
+    // distribution[d] = number of nodes with degree d, up to the maximum degree
+    #[wasm_bindgen]
+    pub fn degree_distribution(&self) -> Vec<u32> {
+        let max_degree = self.adjacency.iter().map(Vec::len).max().unwrap_or(0);
+        let mut distribution = vec![0u32; if self.adjacency.is_empty() { 0 } else { max_degree + 1 }];
+        for neighbours in &self.adjacency {
+            distribution[neighbours.len()] += 1;
+        }
+        distribution
+    }
+
+    // Links among the node's neighbours over the k(k-1)/2 possible
+    #[wasm_bindgen]
+    pub fn local_clustering(&self, node: usize) -> Result<f64, NeuralError> {
+        self.check_node(node)?;
+        Ok(self.clustering_of(node))
+    }
+
+    // Watts–Strogatz average of the local coefficients
+    #[wasm_bindgen]
+    pub fn clustering_coefficient(&self) -> f64 {
+        if self.adjacency.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = (0..self.adjacency.len()).map(|node| self.clustering_of(node)).sum();
+        total / self.adjacency.len() as f64
+    }
+
+    // Mean hop count over ordered pairs that can reach each other; 0 without any
+    #[wasm_bindgen]
+    pub fn average_path_length(&self) -> f64 {
+        self.path_summary().average_length
+    }
+
+    #[wasm_bindgen]
+    pub fn connected_components(&self) -> usize {
+        self.path_summary().components
+    }
+
+    #[wasm_bindgen]
+    pub fn small_worldness(&self) -> f64 {
+        self.small_worldness_from(self.clustering_coefficient(), self.average_path_length())
+    }
+
+    #[wasm_bindgen]
+    pub fn global_efficiency(&self) -> f64 {
+        self.path_summary().global_efficiency
+    }
+
+    // Energy, throughput and connectivity efficiency of the mesh under per-node
+    // `activity` (spike probability per time bin; see efficiency.rs)
+    #[wasm_bindgen]
+    pub fn efficiency_report(&self, activity: &[f32], weights: &EfficiencyWeights) -> Result<EfficiencyReport, NeuralError> {
+        weights.validate()?;
+        if activity.len() != self.adjacency.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.adjacency.len(), actual: activity.len() });
+        }
+        Ok(efficiency::evaluate(activity, self.mean_degree(), self.global_efficiency(), weights))
+    }
+
+    // Every metric at once, as a parsed JSON object (see to_json)
+    #[wasm_bindgen]
+    pub fn report(&self) -> Result<JsValue, NeuralError> {
+        js_sys::JSON::parse(&self.to_json())
+            .map_err(|_| NeuralError::InvalidFormat("mesh report is not valid JSON".to_string()))
+    }
+
+    // {"nodes", "edges", "density", "mean_degree", "total_weight", "clustering_coefficient",
+    //  "average_path_length", "diameter", "components", "small_worldness", "global_efficiency",
+    //  "degree_distribution"}
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let clustering = self.clustering_coefficient();
+        let paths = self.path_summary();
+        let mut out = format!(
+            "{{\"nodes\":{},\"edges\":{},\"density\":{},\"mean_degree\":{},\"total_weight\":{},\"clustering_coefficient\":{},\"average_path_length\":{},\"diameter\":{},\"components\":{},\"small_worldness\":{},\"global_efficiency\":{},\"degree_distribution\":[",
+            self.adjacency.len(),
+            self.edge_count,
+            self.density(),
+            self.mean_degree(),
+            self.total_weight(),
+            clustering,
+            paths.average_length,
+            paths.diameter,
+            paths.components,
+            self.small_worldness_from(clustering, paths.average_length),
+            paths.global_efficiency,
+        );
+        for (degree, count) in self.degree_distribution().iter().enumerate() {
+            if degree > 0 {
+                out.push(',');
+            }
+            out.push_str(&count.to_string());
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+// Shortest-path statistics from one BFS per node
+struct PathSummary {
+    average_length: f64,
+    // Longest shortest path between connected nodes
+    diameter: usize,
+    components: usize,
+    global_efficiency: f64,
+}
+
+impl MeshGraph {
+    // Layout: node_count u32, edge_count u32, edge_count × { a u32, b u32, weight f32 }
+    // with a < b, in node order
+    pub(crate) fn encode(&self, writer: &mut ByteWriter) {
+        writer.u32(self.adjacency.len() as u32);
+        writer.u32(self.edge_count as u32);
+        for (a, neighbours) in self.adjacency.iter().enumerate() {
+            for &(b, weight) in neighbours.iter().filter(|&&(b, _)| b as usize > a) {
+                writer.u32(a as u32);
+                writer.u32(b);
+                writer.f32(weight);
+            }
+        }
+    }
+
+    pub(crate) fn decode(reader: &mut ByteReader) -> NeuralResult<MeshGraph> {
+        let invalid = |err: NeuralError| NeuralError::InvalidFormat(format!("invalid mesh graph: {}", err));
+        let mut graph = MeshGraph::new(reader.u32()? as usize).map_err(invalid)?;
+        for _ in 0..reader.u32()? {
+            let (a, b) = (reader.u32()? as usize, reader.u32()? as usize);
+            graph.add_edge(a, b, reader.f32()?).map_err(invalid)?;
+        }
+        Ok(graph)
+    }
+
+    // (neighbour, weight) pairs of a node, sorted by neighbour
+    pub(crate) fn neighbours_of(&self, node: usize) -> &[(u32, f32)] {
+        &self.adjacency[node]
+    }
+
+    fn check_node(&self, node: usize) -> NeuralResult<()> {
+        if node >= self.adjacency.len() {
+            return Err(NeuralError::IndexOutOfRange { index: node, len: self.adjacency.len() });
+        }
+        Ok(())
+    }
+
+    fn clustering_of(&self, node: usize) -> f64 {
+        let neighbours = &self.adjacency[node];
+        let degree = neighbours.len();
+        if degree < 2 {
+            return 0.0;
+        }
+        // Each neighbour-neighbour link is seen from both ends
+        let links: usize = neighbours
+            .iter()
+            .map(|&(neighbour, _)| count_common(neighbours, &self.adjacency[neighbour as usize]))
+            .sum();
+        links as f64 / (degree * (degree - 1)) as f64
+    }
+
+    fn path_summary(&self) -> PathSummary {
+        let nodes = self.adjacency.len();
+        let mut distance = vec![usize::MAX; nodes];
+        let mut queue = VecDeque::new();
+        let mut total_hops = 0u64;
+        let mut inverse_hops = 0.0f64;
+        let mut pairs = 0u64;
+        let mut diameter = 0;
+        let mut component = vec![false; nodes];
+        let mut components = 0;
+
+        for source in 0..nodes {
+            distance.fill(usize::MAX);
+            distance[source] = 0;
+            queue.push_back(source);
+            while let Some(node) = queue.pop_front() {
+                for &(neighbour, _) in &self.adjacency[node] {
+                    let neighbour = neighbour as usize;
+                    if distance[neighbour] == usize::MAX {
+                        distance[neighbour] = distance[node] + 1;
+                        total_hops += distance[neighbour] as u64;
+                        inverse_hops += 1.0 / distance[neighbour] as f64;
+                        pairs += 1;
+                        diameter = diameter.max(distance[neighbour]);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+            // The first BFS to reach a node starts a new component
+            if !component[source] {
+                components += 1;
+                for (seen, &hops) in component.iter_mut().zip(&distance) {
+                    *seen |= hops != usize::MAX;
+                }
+            }
+        }
+
+        let average_length = if pairs == 0 { 0.0 } else { total_hops as f64 / pairs as f64 };
+        let global_efficiency = if nodes < 2 { 0.0 } else { inverse_hops / (nodes * (nodes - 1)) as f64 };
+        PathSummary { average_length, diameter, components, global_efficiency }
+    }
+
+    fn small_worldness_from(&self, clustering: f64, path_length: f64) -> f64 {
+        let nodes = self.adjacency.len() as f64;
+        let degree = self.mean_degree();
+        if nodes < 3.0 || degree <= 1.0 || clustering <= 0.0 || path_length <= 0.0 {
+            return 0.0;
+        }
+        let random_clustering = degree / nodes;
+        let random_path_length = nodes.ln() / degree.ln();
+        (clustering / random_clustering) / (path_length / random_path_length)
+    }
+}
+
+fn check_node_count(nodes: usize) -> NeuralResult<()> {
+    if u32::try_from(nodes).is_err() {
+        return Err(NeuralError::InvalidConfiguration("mesh node count exceeds u32".to_string()));
+    }
+    Ok(())
+}
+
+// Returns true when the neighbour is new
+fn insert_neighbour(neighbours: &mut Vec<(u32, f32)>, node: u32, weight: f32) -> bool {
+    match neighbours.binary_search_by_key(&node, |&(neighbour, _)| neighbour) {
+        Ok(index) => {
+            neighbours[index].1 = weight;
+            false
+        }
+        Err(index) => {
+            neighbours.insert(index, (node, weight));
+            true
+        }
+    }
+}
+
+fn remove_neighbour(neighbours: &mut Vec<(u32, f32)>, node: u32) -> bool {
+    match neighbours.binary_search_by_key(&node, |&(neighbour, _)| neighbour) {
+        Ok(index) => {
+            neighbours.remove(index);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+// Size of the intersection of two sorted neighbour lists
+fn count_common(a: &[(u32, f32)], b: &[(u32, f32)]) -> usize {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    common
+}
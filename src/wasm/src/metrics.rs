@@ -0,0 +1,93 @@
+// Prometheus text exposition (format 0.0.4) for the runtime's metrics
+//
+// Every metric family is written as
+//   # HELP <name> <help>
+//   # TYPE <name> counter|gauge|histogram
+//   <name>{label="value",...} <value>
+// Histograms follow the Prometheus layout: cumulative `_bucket` samples with an
+// `le` upper bound (the last one "+Inf"), then `_sum` and `_count`. Latencies are
+// exported in seconds and sizes in bytes, per the Prometheus naming conventions.
+
+pub(crate) const PREFIX: &str = "neural_runtime_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn name(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PrometheusWriter {
+    out: String,
+}
+
+impl PrometheusWriter {
+    pub(crate) fn new() -> PrometheusWriter {
+        PrometheusWriter::default()
+    }
+
+    // HELP and TYPE lines opening the family `name` (without PREFIX)
+    pub(crate) fn family(&mut self, name: &str, kind: MetricKind, help: &str) {
+        self.out.push_str(&format!("# HELP {}{} {}\n", PREFIX, name, help));
+        self.out.push_str(&format!("# TYPE {}{} {}\n", PREFIX, name, kind.name()));
+    }
+
+    // One sample of `name` (without PREFIX; histograms pass the _bucket/_sum/_count suffix)
+    pub(crate) fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(PREFIX);
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (position, (label, value)) in labels.iter().enumerate() {
+                if position > 0 {
+                    self.out.push(',');
+                }
+                self.out.push_str(label);
+                self.out.push_str("=\"");
+                push_label_value(&mut self.out, value);
+                self.out.push('"');
+            }
+            self.out.push('}');
+        }
+        self.out.push(' ');
+        self.out.push_str(&format_value(value));
+        self.out.push('\n');
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.out
+    }
+}
+
+// Label values escape backslash, double quote and newline
+fn push_label_value(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+}
+
+pub(crate) fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
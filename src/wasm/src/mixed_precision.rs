@@ -0,0 +1,249 @@
+// Mixed-precision training: f32 master weights, f16 compute
+//
+// With TrainingPrecision::Mixed, train_batch rounds each dense layer's weights to
+// f16 for the step and keeps every activation the backward pass needs as f16,
+// roughly halving the memory a step holds on to. Matrix products widen f16 rows
+// and accumulate in f32 (precision::matvec_f16_into), and weight gradients are
+// accumulated in f32 and applied to the f32 master weights, so small updates are
+// not lost to f16 rounding. Batch normalization runs in f32 on widened inputs.
+//
+// Backward values are rounded to f16 as they flow between layers, so they
+// underflow below 2^-24 and overflow above 65504. Dynamic loss scaling keeps them
+// in range: the loss gradient is multiplied by the loss scale before the backward
+// pass and the weight gradients are divided by it afterwards. A step whose scaled
+// gradients overflow is skipped and the scale halved; after GROWTH_INTERVAL clean
+// steps in a row the scale doubles. Once the scale is down to 1 an overflow is a
+// genuine non-finite gradient and goes through the TrainingSafety rollback.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+use crate::loss::Loss;
+use crate::network::{DenseLayer, Layer};
+use crate::normalization::NormCache;
+use crate::precision::{f16_to_f32, f32_slice_to_f16, f32_to_f16, matvec_f16_into, widen_into};
+use crate::rng::Rng;
+use crate::training::{self, LayerGradients};
+
+// Arithmetic used by train_batch
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainingPrecision {
+    F32 = 0,
+    // f16 weights and activations, f32 accumulation and master weights
+    Mixed = 1,
+}
+
+impl TrainingPrecision {
+    pub(crate) fn from_u8(value: u8) -> Option<TrainingPrecision> {
+        match value {
+            0 => Some(TrainingPrecision::F32),
+            1 => Some(TrainingPrecision::Mixed),
+            _ => None,
+        }
+    }
+}
+
+// Defaults follow PyTorch's GradScaler
+const INITIAL_LOSS_SCALE: f32 = 65536.0;
+const GROWTH_INTERVAL: u32 = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LossScaler {
+    pub(crate) scale: f32,
+    // Clean steps since the scale last changed
+    pub(crate) good_steps: u32,
+}
+
+impl Default for LossScaler {
+    fn default() -> Self {
+        LossScaler { scale: INITIAL_LOSS_SCALE, good_steps: 0 }
+    }
+}
+
+impl LossScaler {
+    // Whether an overflow at the current scale can be retried at a lower one
+    pub(crate) fn can_back_off(&self) -> bool {
+        self.scale > 1.0
+    }
+
+    pub(crate) fn back_off(&mut self) {
+        self.scale = (self.scale * 0.5).max(1.0);
+        self.good_steps = 0;
+    }
+
+    pub(crate) fn record_good_step(&mut self) {
+        self.good_steps += 1;
+        if self.good_steps >= GROWTH_INTERVAL {
+            self.scale = (self.scale * 2.0).min(f32::MAX / 2.0);
+            self.good_steps = 0;
+        }
+    }
+}
+
+enum MixedCache {
+    // f16 weights of the step and f16 pre-activations
+    Dense(Vec<u16>, Vec<u16>),
+    Dropout(Vec<f32>),
+    Norm(NormCache),
+}
+
+// training::compute_gradients in mixed precision with the loss gradient scaled by
+// `loss_scale`; the returned gradients are unscaled and averaged over the batch,
+// and the loss is the unscaled mean
+pub(crate) fn compute_gradients(
+    layers: &mut [Layer],
+    (inputs, targets, batch_size): (&[f32], &[f32], usize),
+    simd: bool,
+    rng: &mut Rng,
+    loss: &Loss,
+    loss_scale: f32,
+) -> NeuralResult<(Vec<LayerGradients>, f32)> {
+    let output_size = training::check_batch(layers, inputs, targets, batch_size)?;
+    if training::has_adapters(layers) {
+        return Err(NeuralError::InvalidConfiguration("mixed-precision training does not support adapters".to_string()));
+    }
+
+    // Forward pass, keeping every layer's input as f16
+    let mut outputs: Vec<Vec<u16>> = Vec::with_capacity(layers.len() + 1);
+    outputs.push(f32_slice_to_f16(inputs));
+    let mut caches = Vec::with_capacity(layers.len());
+    for layer in layers.iter_mut() {
+        let input = &outputs[outputs.len() - 1];
+        let (output, cache) = match layer {
+            Layer::Dense(dense) => {
+                let weights = f32_slice_to_f16(training::f32_weights(dense)?);
+                let (pre, post) = dense_forward(dense, &weights, input, simd);
+                (post, MixedCache::Dense(weights, pre))
+            }
+            Layer::Dropout(dropout) => {
+                let (output, mask) = dropout.forward_train(&widen(input, simd), rng);
+                (f32_slice_to_f16(&output), MixedCache::Dropout(mask))
+            }
+            Layer::Norm(norm) => {
+                let (output, cache) = norm.forward_train(&widen(input, simd), batch_size, simd)?;
+                (f32_slice_to_f16(&output), MixedCache::Norm(cache))
+            }
+            _ => unreachable!("check_batch accepts trainable layers only"),
+        };
+        caches.push(cache);
+        outputs.push(output);
+    }
+
+    let prediction = widen(&outputs[layers.len()], simd);
+    let mut total_loss = 0.0;
+    let mut grad = vec![0.0; prediction.len()];
+    for ((predicted, target), sample_grad) in
+        prediction.chunks_exact(output_size).zip(targets.chunks_exact(output_size)).zip(grad.chunks_exact_mut(output_size))
+    {
+        total_loss += loss.sample(predicted, target, sample_grad)?;
+    }
+    linalg::scale(loss_scale, &mut grad, simd);
+    round_to_f16(&mut grad);
+
+    let mut gradients: Vec<LayerGradients> = layers
+        .iter()
+        .map(|layer| LayerGradients { weights: vec![0.0; layer.weight_count()], biases: vec![0.0; layer.biases().len()] })
+        .collect();
+    for (index, (layer, cache)) in layers.iter().zip(&caches).enumerate().rev() {
+        let gradient = &mut gradients[index];
+        grad = match (layer, cache) {
+            (Layer::Dense(dense), MixedCache::Dense(weights, pre)) => {
+                let layer_io = (&outputs[index][..], &outputs[index + 1][..]);
+                dense_backward(dense, weights, pre, layer_io, grad, gradient, (index > 0, simd))
+            }
+            (Layer::Dropout(_), MixedCache::Dropout(mask)) => {
+                for (delta, factor) in grad.iter_mut().zip(mask) {
+                    *delta *= factor;
+                }
+                grad
+            }
+            (Layer::Norm(norm), MixedCache::Norm(cache)) => norm.backward(cache, &grad, batch_size, gradient),
+            _ => unreachable!("caches are built from the same layers"),
+        };
+        round_to_f16(&mut grad);
+    }
+
+    let unscale = 1.0 / (batch_size as f32 * loss_scale);
+    for gradient in gradients.iter_mut() {
+        linalg::scale(unscale, &mut gradient.weights, simd);
+        linalg::scale(unscale, &mut gradient.biases, simd);
+    }
+    Ok((gradients, total_loss / batch_size as f32))
+}
+
+// f16 pre-activations and outputs of a dense layer for every sample
+fn dense_forward(layer: &DenseLayer, weights: &[u16], inputs: &[u16], simd: bool) -> (Vec<u16>, Vec<u16>) {
+    let samples = inputs.len() / layer.inputs;
+    let (mut pre, mut post) = (vec![0; samples * layer.outputs], vec![0; samples * layer.outputs]);
+    let mut input = vec![0.0; layer.inputs];
+    let mut row = vec![0.0; layer.outputs];
+    for (sample, input_half) in inputs.chunks_exact(layer.inputs).enumerate() {
+        widen_into(input_half, &mut input, simd);
+        matvec_f16_into(weights, &input, &mut row, layer.outputs, layer.inputs, simd);
+        linalg::axpy(1.0, &layer.biases, &mut row, simd);
+        let span = sample * layer.outputs..(sample + 1) * layer.outputs;
+        narrow_into(&row, &mut pre[span.clone()]);
+        layer.activation.apply_slice(&mut row, simd);
+        narrow_into(&row, &mut post[span]);
+    }
+    (pre, post)
+}
+
+// Accumulate the layer's f32 weight and bias gradients and return dL/d(input),
+// left empty for the first layer
+fn dense_backward(
+    layer: &DenseLayer,
+    weights: &[u16],
+    pre_activations: &[u16],
+    (inputs, outputs): (&[u16], &[u16]),
+    mut grad: Vec<f32>,
+    gradient: &mut LayerGradients,
+    (upstream, simd): (bool, bool),
+) -> Vec<f32> {
+    let mut previous_grad = if upstream { vec![0.0; inputs.len()] } else { Vec::new() };
+    let (mut pre, mut post) = (vec![0.0; layer.outputs], vec![0.0; layer.outputs]);
+    let mut previous = vec![0.0; layer.inputs];
+    let mut weight_row = vec![0.0; layer.inputs];
+    for (sample, delta) in grad.chunks_exact_mut(layer.outputs).enumerate() {
+        let span = sample * layer.outputs..(sample + 1) * layer.outputs;
+        widen_into(&pre_activations[span.clone()], &mut pre, simd);
+        widen_into(&outputs[span], &mut post, simd);
+        layer.activation.backprop_slice(&pre, &post, delta);
+
+        widen_into(&inputs[sample * layer.inputs..(sample + 1) * layer.inputs], &mut previous, simd);
+        for ((row, bias_grad), &d) in gradient.weights.chunks_exact_mut(layer.inputs).zip(gradient.biases.iter_mut()).zip(delta.iter()) {
+            *bias_grad += d;
+            linalg::axpy(d, &previous, row, simd);
+        }
+
+        if upstream {
+            let up = &mut previous_grad[sample * layer.inputs..(sample + 1) * layer.inputs];
+            for (row, &d) in weights.chunks_exact(layer.inputs).zip(delta.iter()) {
+                widen_into(row, &mut weight_row, simd);
+                linalg::axpy(d, &weight_row, up, simd);
+            }
+        }
+    }
+    previous_grad
+}
+
+fn widen(halves: &[u16], simd: bool) -> Vec<f32> {
+    let mut values = vec![0.0; halves.len()];
+    widen_into(halves, &mut values, simd);
+    values
+}
+
+fn narrow_into(values: &[f32], halves: &mut [u16]) {
+    for (half, &value) in halves.iter_mut().zip(values) {
+        *half = f32_to_f16(value);
+    }
+}
+
+// Round in place to the nearest f16 value, overflowing to ±inf like f16 storage
+fn round_to_f16(values: &mut [f32]) {
+    for value in values.iter_mut() {
+        *value = f16_to_f32(f32_to_f16(*value));
+    }
+}
@@ -0,0 +1,351 @@
+// Declarative JSON model descriptions
+//
+//   {
+//     "input_size": 4,
+//     "initializer": { "scheme": "he", "distribution": "normal", "seed": 7 },
+//     "layers": [
+//       { "type": "conv1d", "in_channels": 1, "out_channels": 2, "kernel_size": 3, "activation": "relu" },
+//       { "type": "lstm", "units": 8 },
+//       { "type": "layer_norm" },
+//       { "type": "dropout", "rate": 0.2 },
+//       { "units": 3, "activation": "softmax" }
+//     ],
+//     "output_mode": "raw",
+//     "precision": "f32"
+//   }
+//
+// Only input_size and layers are required. A layer's type defaults to "dense" and
+// its activation to "linear"; conv1d stride defaults to 1 and padding to 0. Without
+// an initializer the runtime default (zeros) applies, as for `new NeuralNetwork(n)`.
+// Dropout, batch_norm and layer_norm keep the previous layer's size; normalization
+// epsilon defaults to 1e-5 and batch_norm momentum to 0.1.
+// Names are case-insensitive. Unknown keys are rejected, and every error names the
+// offending field as a path such as `layers[2].activation`.
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::initializer::{InitDistribution, InitScheme};
+use crate::json::JsonValue;
+use crate::logging::push_json_string;
+use crate::network::{NeuralNetwork, OutputMode};
+use crate::normalization::{DEFAULT_EPSILON, DEFAULT_MOMENTUM};
+use crate::precision::Precision;
+
+// Largest integer a JSON number (f64) holds exactly
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+pub fn parse_model_spec(text: &str) -> NeuralResult<NeuralNetwork> {
+    let spec = JsonValue::parse(text)?;
+    let root = Field { path: String::new(), value: &spec };
+    root.allow_keys(&["input_size", "initializer", "layers", "output_mode", "precision"])?;
+
+    let mut network = NeuralNetwork::new(root.required("input_size")?.positive_integer()?)?;
+    if let Some(initializer) = root.optional("initializer") {
+        initializer.allow_keys(&["scheme", "distribution", "seed"])?;
+        let scheme = initializer.required("scheme")?.choice(&[
+            ("zeros", InitScheme::Zeros),
+            ("xavier", InitScheme::Xavier),
+            ("he", InitScheme::He),
+            ("lecun", InitScheme::LeCun),
+        ])?;
+        let distribution = match initializer.optional("distribution") {
+            Some(field) => field.choice(&[("uniform", InitDistribution::Uniform), ("normal", InitDistribution::Normal)])?,
+            None => InitDistribution::Uniform,
+        };
+        let seed = match initializer.optional("seed") {
+            Some(field) => field.u64()?,
+            None => 0,
+        };
+        network.set_initializer(scheme, distribution, seed);
+    }
+
+    let layers = root.required("layers")?;
+    let JsonValue::Array(items) = layers.value else {
+        return Err(layers.error(&format!("expected an array, found {}", layers.value.type_name())));
+    };
+    if items.is_empty() {
+        return Err(layers.error("a network needs at least one layer"));
+    }
+    for (index, value) in items.iter().enumerate() {
+        let layer = Field { path: format!("layers[{}]", index), value };
+        add_layer(&mut network, &layer)?;
+    }
+
+    if let Some(field) = root.optional("output_mode") {
+        network.set_output_mode(field.choice(&[("raw", OutputMode::Raw), ("softmax", OutputMode::Softmax)])?);
+    }
+    if let Some(field) = root.optional("precision") {
+        network.set_precision(field.choice(&[("f32", Precision::F32), ("f16", Precision::F16), ("int8", Precision::Int8)])?);
+    }
+    Ok(network)
+}
+
+fn add_layer(network: &mut NeuralNetwork, layer: &Field) -> NeuralResult<()> {
+    let kind = match layer.optional("type") {
+        Some(field) => field.choice(&[
+            ("dense", "dense"),
+            ("lstm", "lstm"),
+            ("gru", "gru"),
+            ("conv1d", "conv1d"),
+            ("dropout", "dropout"),
+            ("batch_norm", "batch_norm"),
+            ("layer_norm", "layer_norm"),
+        ])?,
+        None => "dense",
+    };
+    let activation = || match layer.optional("activation") {
+        Some(field) => field.choice(&[
+            ("linear", ActivationKind::Linear),
+            ("relu", ActivationKind::ReLU),
+            ("sigmoid", ActivationKind::Sigmoid),
+            ("tanh", ActivationKind::Tanh),
+            ("leaky_relu", ActivationKind::LeakyReLU),
+            ("gelu", ActivationKind::GELU),
+            ("softmax", ActivationKind::Softmax),
+        ]),
+        None => Ok(ActivationKind::Linear),
+    };
+    match kind {
+        "dense" => {
+            layer.allow_keys(&["type", "units", "activation"])?;
+            let units = layer.required("units")?.positive_integer()?;
+            network.add_layer(units, activation()?).map_err(|err| layer.context(err))
+        }
+        "lstm" | "gru" => {
+            // Recurrent cells fix their own gate activations
+            layer.allow_keys(&["type", "units"])?;
+            let units = layer.required("units")?.positive_integer()?;
+            let added = if kind == "lstm" { network.add_lstm(units) } else { network.add_gru(units) };
+            added.map_err(|err| layer.context(err))
+        }
+        "dropout" => {
+            layer.allow_keys(&["type", "rate"])?;
+            let rate = layer.required("rate")?.number()?;
+            network.add_dropout(rate).map_err(|err| layer.context(err))
+        }
+        "batch_norm" | "layer_norm" => {
+            let epsilon = match layer.optional("epsilon") {
+                Some(field) => field.number()?,
+                None => DEFAULT_EPSILON,
+            };
+            let added = if kind == "batch_norm" {
+                layer.allow_keys(&["type", "epsilon", "momentum"])?;
+                let momentum = match layer.optional("momentum") {
+                    Some(field) => field.number()?,
+                    None => DEFAULT_MOMENTUM,
+                };
+                network.add_batch_norm(epsilon, momentum)
+            } else {
+                layer.allow_keys(&["type", "epsilon"])?;
+                network.add_layer_norm(epsilon)
+            };
+            added.map_err(|err| layer.context(err))
+        }
+        _ => {
+            layer.allow_keys(&["type", "in_channels", "out_channels", "kernel_size", "stride", "padding", "activation"])?;
+            let stride = match layer.optional("stride") {
+                Some(field) => field.positive_integer()?,
+                None => 1,
+            };
+            let padding = match layer.optional("padding") {
+                Some(field) => field.integer()?,
+                None => 0,
+            };
+            let in_channels = layer.required("in_channels")?.positive_integer()?;
+            let out_channels = layer.required("out_channels")?.positive_integer()?;
+            let kernel_size = layer.required("kernel_size")?.positive_integer()?;
+            network
+                .add_conv1d(in_channels, out_channels, kernel_size, stride, padding, activation()?)
+                .map_err(|err| layer.context(err))
+        }
+    }
+}
+
+// A value together with its path from the root, for error messages
+struct Field<'a> {
+    path: String,
+    value: &'a JsonValue,
+}
+
+impl<'a> Field<'a> {
+    fn error(&self, reason: &str) -> NeuralError {
+        let path = if self.path.is_empty() { "model" } else { &self.path };
+        NeuralError::InvalidConfiguration(format!("{}: {}", path, reason))
+    }
+
+    // Builder errors concern the field as a whole
+    fn context(&self, err: NeuralError) -> NeuralError {
+        match err {
+            NeuralError::InvalidConfiguration(reason) => self.error(&reason),
+            other => other,
+        }
+    }
+
+    fn child(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    fn allow_keys(&self, keys: &[&str]) -> NeuralResult<()> {
+        let JsonValue::Object(members) = self.value else {
+            return Err(self.error(&format!("expected an object, found {}", self.value.type_name())));
+        };
+        match members.iter().find(|(name, _)| !keys.contains(&name.as_str())) {
+            Some((name, _)) => Err(NeuralError::InvalidConfiguration(format!(
+                "{}: unknown field; expected one of {}",
+                self.child(name),
+                keys.join(", ")
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn optional(&self, key: &str) -> Option<Field<'a>> {
+        self.value.get(key).map(|value| Field { path: self.child(key), value })
+    }
+
+    fn required(&self, key: &str) -> NeuralResult<Field<'a>> {
+        self.optional(key).ok_or_else(|| NeuralError::InvalidConfiguration(format!("{}: required field is missing", self.child(key))))
+    }
+
+    // Seeds may exceed usize on wasm32
+    fn u64(&self) -> NeuralResult<u64> {
+        match self.value {
+            JsonValue::Number(number) if number.fract() == 0.0 && (0.0..=MAX_EXACT_INTEGER).contains(number) => Ok(*number as u64),
+            other => Err(self.error(&format!("expected a non-negative integer, found {}", describe(other)))),
+        }
+    }
+
+    fn number(&self) -> NeuralResult<f32> {
+        match self.value {
+            JsonValue::Number(number) => Ok(*number as f32),
+            other => Err(self.error(&format!("expected a number, found {}", describe(other)))),
+        }
+    }
+
+    fn integer(&self) -> NeuralResult<usize> {
+        usize::try_from(self.u64()?).map_err(|_| self.error("integer is too large"))
+    }
+
+    fn positive_integer(&self) -> NeuralResult<usize> {
+        match self.integer()? {
+            0 => Err(self.error("must be at least 1")),
+            value => Ok(value),
+        }
+    }
+
+    fn choice<T: Copy>(&self, options: &[(&str, T)]) -> NeuralResult<T> {
+        let names: Vec<&str> = options.iter().map(|(name, _)| *name).collect();
+        let JsonValue::String(text) = self.value else {
+            return Err(self.error(&format!("expected one of \"{}\", found {}", names.join("\", \""), describe(self.value))));
+        };
+        options
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(text))
+            .map(|&(_, value)| value)
+            .ok_or_else(|| self.error(&format!("unknown value {}; expected one of \"{}\"", quoted(text), names.join("\", \""))))
+    }
+}
+
+// Type and, for scalars, the value itself
+fn describe(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Number(number) => format!("{}", number),
+        JsonValue::String(text) => quoted(text),
+        other => other.type_name().to_string(),
+    }
+}
+
+// `text` as a JSON string literal, so quotes and control characters in the spec
+// cannot garble the message
+fn quoted(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    push_json_string(&mut out, text);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::LayerKind;
+
+    fn reason(text: &str) -> String {
+        match parse_model_spec(text).unwrap_err() {
+            NeuralError::InvalidConfiguration(reason) | NeuralError::InvalidFormat(reason) => reason,
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn builds_every_layer_kind() {
+        let network = parse_model_spec(
+            r#"{
+                "input_size": 8,
+                "initializer": { "scheme": "He", "distribution": "normal", "seed": 7 },
+                "layers": [
+                    { "type": "conv1d", "in_channels": 2, "out_channels": 3, "kernel_size": 3, "padding": 1, "activation": "relu" },
+                    { "type": "gru", "units": 5 },
+                    { "type": "lstm", "units": 4 },
+                    { "type": "batch_norm", "momentum": 0.2 },
+                    { "type": "layer_norm" },
+                    { "type": "dropout", "rate": 0.5 },
+                    { "units": 2, "activation": "softmax" }
+                ],
+                "output_mode": "softmax"
+            }"#,
+        )
+        .unwrap();
+        let kinds: Vec<LayerKind> = (0..7).map(|layer| network.layer_kind(layer).unwrap()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LayerKind::Conv1d,
+                LayerKind::Gru,
+                LayerKind::Lstm,
+                LayerKind::BatchNorm,
+                LayerKind::LayerNorm,
+                LayerKind::Dropout,
+                LayerKind::Dense
+            ]
+        );
+        assert_eq!(network.layer_size(5).unwrap(), 4);
+        assert_eq!(network.layer_size(0).unwrap(), 12);
+        assert_eq!(network.output_size(), 2);
+        assert_eq!(network.output_mode(), OutputMode::Softmax);
+        assert!(network.get_parameters().iter().any(|&weight| weight != 0.0));
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        assert_eq!(reason(r#"{"layers": [{"units": 2}]}"#), "input_size: required field is missing");
+        assert_eq!(
+            reason(r#"{"input_size": 2, "layers": [{"units": 2}, {"units": 1, "activation": "swish"}]}"#),
+            "layers[1].activation: unknown value \"swish\"; expected one of \"linear\", \"relu\", \"sigmoid\", \"tanh\", \"leaky_relu\", \"gelu\", \"softmax\""
+        );
+        assert_eq!(
+            reason(r#"{"input_size": 2, "layers": [{"units": 2.5}]}"#),
+            "layers[0].units: expected a non-negative integer, found 2.5"
+        );
+        assert!(reason(r#"{"input_size": 2, "layers": [{"unit": 2}]}"#).starts_with("layers[0].unit: unknown field"));
+        assert!(reason(r#"{"input_size": 5, "layers": [{"type": "conv1d", "in_channels": 2, "out_channels": 1, "kernel_size": 1}]}"#)
+            .starts_with("layers[0]: "));
+        assert_eq!(
+            reason(r#"{"input_size": 2, "layers": [{"type": "dropout", "rate": 1}]}"#),
+            "layers[0]: dropout rate must lie in [0, 1)"
+        );
+        assert_eq!(reason("{\"input_size\": 2,\n \"layers\": [}"), "JSON line 2 column 13: expected a value");
+    }
+
+    #[test]
+    fn errors_escape_quoted_values() {
+        assert!(reason(r#"{"input_size": 2, "layers": [{"units": 2, "activation": "re\"lu\n"}]}"#)
+            .starts_with(r#"layers[0].activation: unknown value "re\"lu\n"; expected"#));
+        assert_eq!(
+            reason(r#"{"input_size": 2, "layers": [{"units": "2\"}"}]}"#),
+            r#"layers[0].units: expected a non-negative integer, found "2\"}""#
+        );
+    }
+}
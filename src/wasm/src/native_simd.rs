@@ -0,0 +1,153 @@
+// Vectorized linear algebra kernels for native (non-WASM) builds
+//
+// Native targets have no simd128, so the dense hot loops get their own versions:
+// AVX2 with FMA on x86_64, detected at runtime because older CPUs lack it, and NEON
+// on aarch64, where it is part of the baseline. Only the linalg kernels (dot, axpy
+// and the broadcast matmul) are covered; everything else runs its scalar path.
+// Fused multiply-add rounds once per step, so results can differ from the scalar
+// kernels in the last bits.
+
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+// std caches the CPUID result, so this is a couple of loads per call
+#[cfg(target_arch = "x86_64")]
+pub fn cpu_supported() -> bool {
+    is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma")
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn cpu_supported() -> bool {
+    true
+}
+
+// Dot product over the common length of `a` and `b`
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    assert!(cpu_supported(), "native SIMD kernels called on an unsupported CPU");
+    let len = a.len().min(b.len());
+    // Safety: the CPU features were checked above
+    unsafe { dot_lanes(&a[..len], &b[..len]) }
+}
+
+// y += alpha · x over the common length of `x` and `y`
+pub fn axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+    assert!(cpu_supported(), "native SIMD kernels called on an unsupported CPU");
+    let len = x.len().min(y.len());
+    // Safety: the CPU features were checked above
+    unsafe { axpy_lanes(alpha, &x[..len], &mut y[..len]) }
+}
+
+// C[m×n] = A[m×k] · B[k×n]; broadcasts each A element across a row of B like the wasm kernel
+pub fn matmul(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) {
+    assert!(cpu_supported(), "native SIMD kernels called on an unsupported CPU");
+    // Safety: the CPU features were checked above
+    unsafe { matmul_lanes(a, b, c, m, n, k) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn matmul_lanes(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) {
+    for i in 0..m {
+        let c_row = &mut c[i * n..(i + 1) * n];
+        c_row.fill(0.0);
+        for p in 0..k {
+            axpy_lanes(a[i * k + p], &b[p * n..(p + 1) * n], c_row);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+fn matmul_lanes(a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) {
+    for i in 0..m {
+        let c_row = &mut c[i * n..(i + 1) * n];
+        c_row.fill(0.0);
+        for p in 0..k {
+            axpy_lanes(a[i * k + p], &b[p * n..(p + 1) * n], c_row);
+        }
+    }
+}
+
+// Slices must have equal lengths
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn dot_lanes(a: &[f32], b: &[f32]) -> f32 {
+    let chunks = a.len() / 8;
+    let mut acc = _mm256_setzero_ps();
+    for chunk in 0..chunks {
+        let base_idx = chunk * 8;
+        // Loads stay inside both slices: base_idx + 7 < chunks * 8 <= len
+        unsafe {
+            let a_vec = _mm256_loadu_ps(a[base_idx..].as_ptr());
+            let b_vec = _mm256_loadu_ps(b[base_idx..].as_ptr());
+            acc = _mm256_fmadd_ps(a_vec, b_vec, acc);
+        }
+    }
+
+    // Fold 8 lanes to 4, then 4 to 1
+    let quad = _mm_add_ps(_mm256_castps256_ps128(acc), _mm256_extractf128_ps::<1>(acc));
+    let pair = _mm_add_ps(quad, _mm_movehl_ps(quad, quad));
+    let single = _mm_add_ss(pair, _mm_shuffle_ps::<0b01>(pair, pair));
+    let tail: f32 = a[chunks * 8..].iter().zip(&b[chunks * 8..]).map(|(x, y)| x * y).sum();
+    _mm_cvtss_f32(single) + tail
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+fn dot_lanes(a: &[f32], b: &[f32]) -> f32 {
+    let chunks = a.len() / 4;
+    let mut acc = vdupq_n_f32(0.0);
+    for chunk in 0..chunks {
+        let base_idx = chunk * 4;
+        // Loads stay inside both slices: base_idx + 3 < chunks * 4 <= len
+        unsafe {
+            let a_vec = vld1q_f32(a[base_idx..].as_ptr());
+            let b_vec = vld1q_f32(b[base_idx..].as_ptr());
+            acc = vfmaq_f32(acc, a_vec, b_vec);
+        }
+    }
+
+    let tail: f32 = a[chunks * 4..].iter().zip(&b[chunks * 4..]).map(|(x, y)| x * y).sum();
+    vaddvq_f32(acc) + tail
+}
+
+// Slices must have equal lengths
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn axpy_lanes(alpha: f32, x: &[f32], y: &mut [f32]) {
+    let chunks = x.len() / 8;
+    let alpha_vec = _mm256_set1_ps(alpha);
+    for chunk in 0..chunks {
+        let base_idx = chunk * 8;
+        unsafe {
+            let x_vec = _mm256_loadu_ps(x[base_idx..].as_ptr());
+            let y_vec = _mm256_loadu_ps(y[base_idx..].as_ptr());
+            _mm256_storeu_ps(y[base_idx..].as_mut_ptr(), _mm256_fmadd_ps(alpha_vec, x_vec, y_vec));
+        }
+    }
+
+    for (y, x) in y[chunks * 8..].iter_mut().zip(&x[chunks * 8..]) {
+        *y += alpha * x;
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+fn axpy_lanes(alpha: f32, x: &[f32], y: &mut [f32]) {
+    let chunks = x.len() / 4;
+    let alpha_vec = vdupq_n_f32(alpha);
+    for chunk in 0..chunks {
+        let base_idx = chunk * 4;
+        unsafe {
+            let x_vec = vld1q_f32(x[base_idx..].as_ptr());
+            let y_vec = vld1q_f32(y[base_idx..].as_ptr());
+            vst1q_f32(y[base_idx..].as_mut_ptr(), vfmaq_f32(y_vec, alpha_vec, x_vec));
+        }
+    }
+
+    for (y, x) in y[chunks * 4..].iter_mut().zip(&x[chunks * 4..]) {
+        *y += alpha * x;
+    }
+}
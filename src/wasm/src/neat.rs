@@ -0,0 +1,642 @@
+// NEAT-style neuroevolution (Stanley & Miikkulainen, 2002)
+//
+// A Genome is a list of node genes and connection genes; connection genes carry a
+// historical innovation number so genomes with different topologies can be aligned
+// for crossover and compared for speciation. The population keeps one innovation
+// table for its whole run, so the same structural mutation always gets the same
+// number.
+//
+// Genomes stay feed-forward: every node has a depth (sensors 0, outputs 1, hidden
+// nodes halfway between the ends of the connection they split) and connections only
+// run from lower to higher depth. Depth is fixed per node id, so crossover can never
+// produce a cycle.
+//
+// NeatPopulation.evolve(fitness_scores) takes one score per genome (higher is
+// better, any finite value) and replaces the population with the next generation:
+//   1. speciate by compatibility distance δ = c1·E/N + c2·D/N + c3·W̄ against each
+//      species' representative, N = larger genome's connection count
+//   2. drop species that have not improved for `stagnation_limit` generations,
+//      except the one holding the best genome
+//   3. share offspring between species in proportion to their mean fitness
+//      (scores shifted so the worst is 0)
+//   4. copy each species' elites, then breed the rest from its top
+//      `survival_fraction` by crossover and mutation
+
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::Rng;
+
+// Chance that a gene disabled in either parent stays disabled in the child
+const INHERIT_DISABLED: f32 = 0.75;
+// Random (from, to) pairs tried before an add-connection mutation gives up
+const CONNECTION_ATTEMPTS: usize = 20;
+const OUTPUT_DEPTH: f32 = 1.0;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeatConfig {
+    pub population_size: usize,
+    // Activation of hidden and output nodes
+    pub activation: ActivationKind,
+    // Compatibility distance: excess (c1), disjoint (c2) and mean weight difference (c3)
+    pub excess_coefficient: f32,
+    pub disjoint_coefficient: f32,
+    pub weight_coefficient: f32,
+    pub compatibility_threshold: f32,
+    // Per-genome chance of mutating weights, then per-connection chance of replacing
+    // a weight instead of perturbing it by N(0, weight_perturbation)
+    pub weight_mutation_rate: f32,
+    pub weight_replace_rate: f32,
+    pub weight_perturbation: f32,
+    // Weights are clamped to ±weight_limit
+    pub weight_limit: f32,
+    pub add_connection_rate: f32,
+    pub add_node_rate: f32,
+    // Chance a child comes from two parents rather than one
+    pub crossover_rate: f32,
+    // Fraction of each species allowed to reproduce
+    pub survival_fraction: f32,
+    // Best genomes per species copied unchanged
+    pub elitism: usize,
+    pub stagnation_limit: u32,
+}
+
+impl Default for NeatConfig {
+    fn default() -> Self {
+        NeatConfig {
+            population_size: 150,
+            activation: ActivationKind::Sigmoid,
+            excess_coefficient: 1.0,
+            disjoint_coefficient: 1.0,
+            weight_coefficient: 0.4,
+            compatibility_threshold: 3.0,
+            weight_mutation_rate: 0.8,
+            weight_replace_rate: 0.1,
+            weight_perturbation: 0.5,
+            weight_limit: 8.0,
+            add_connection_rate: 0.05,
+            add_node_rate: 0.03,
+            crossover_rate: 0.75,
+            survival_fraction: 0.2,
+            elitism: 1,
+            stagnation_limit: 15,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl NeatConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> NeatConfig {
+        NeatConfig::default()
+    }
+}
+
+impl NeatConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if self.population_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("population size must be non-zero".to_string()));
+        }
+        if self.activation == ActivationKind::Softmax {
+            return Err(NeuralError::InvalidConfiguration("softmax is not a per-node activation".to_string()));
+        }
+        let values = [
+            self.excess_coefficient,
+            self.disjoint_coefficient,
+            self.weight_coefficient,
+            self.compatibility_threshold,
+            self.weight_perturbation,
+            self.weight_limit,
+        ];
+        if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err(NeuralError::InvalidConfiguration("NEAT coefficients must be finite and non-negative".to_string()));
+        }
+        let rates = [
+            self.weight_mutation_rate,
+            self.weight_replace_rate,
+            self.add_connection_rate,
+            self.add_node_rate,
+            self.crossover_rate,
+            self.survival_fraction,
+        ];
+        if rates.iter().any(|rate| !(0.0..=1.0).contains(rate)) {
+            return Err(NeuralError::InvalidConfiguration("NEAT rates must lie in [0, 1]".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NodeGene {
+    id: u32,
+    depth: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ConnectionGene {
+    innovation: u32,
+    from: u32,
+    to: u32,
+    weight: f32,
+    enabled: bool,
+}
+
+// Node ids: inputs 0..n, then the bias node, then the outputs, then hidden nodes
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome {
+    inputs: usize,
+    outputs: usize,
+    activation: ActivationKind,
+    // Sorted by id
+    nodes: Vec<NodeGene>,
+    // Sorted by innovation
+    connections: Vec<ConnectionGene>,
+}
+
+#[wasm_bindgen]
+impl Genome {
+    #[wasm_bindgen(getter)]
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hidden_count(&self) -> usize {
+        self.nodes.len() - self.inputs - 1 - self.outputs
+    }
+
+    // Connection genes, including disabled ones
+    #[wasm_bindgen(getter)]
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn enabled_connection_count(&self) -> usize {
+        self.connections.iter().filter(|gene| gene.enabled).count()
+    }
+
+    // Evaluate the network in depth order; outputs are returned in node id order
+    #[wasm_bindgen]
+    pub fn activate(&self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        if inputs.len() != self.inputs {
+            return Err(NeuralError::DimensionMismatch { expected: self.inputs, actual: inputs.len() });
+        }
+        if let Some(index) = inputs.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+
+        let mut outgoing: Vec<Vec<(usize, f32)>> = vec![Vec::new(); self.nodes.len()];
+        for gene in self.connections.iter().filter(|gene| gene.enabled) {
+            outgoing[self.node_index(gene.from)].push((self.node_index(gene.to), gene.weight));
+        }
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by(|&a, &b| self.nodes[a].depth.total_cmp(&self.nodes[b].depth));
+
+        let mut values = vec![0.0; self.nodes.len()];
+        for index in order {
+            values[index] = match self.nodes[index].id as usize {
+                id if id < self.inputs => inputs[id],
+                id if id == self.inputs => 1.0,
+                _ => self.activation.scalar_apply(values[index]),
+            };
+            for &(target, weight) in &outgoing[index] {
+                values[target] += weight * values[index];
+            }
+        }
+
+        let first_output = self.inputs + 1;
+        Ok(values[first_output..first_output + self.outputs].to_vec())
+    }
+}
+
+impl Genome {
+    fn node_index(&self, id: u32) -> usize {
+        self.nodes.binary_search_by_key(&id, |node| node.id).expect("connection gene refers to a missing node")
+    }
+
+    fn depth(&self, id: u32) -> f32 {
+        self.nodes[self.node_index(id)].depth
+    }
+
+    fn has_connection(&self, from: u32, to: u32) -> bool {
+        self.connections.iter().any(|gene| gene.from == from && gene.to == to)
+    }
+
+    fn insert_connection(&mut self, gene: ConnectionGene) {
+        let index = self.connections.partition_point(|existing| existing.innovation < gene.innovation);
+        self.connections.insert(index, gene);
+    }
+
+    fn insert_node(&mut self, node: NodeGene) {
+        let index = self.nodes.partition_point(|existing| existing.id < node.id);
+        self.nodes.insert(index, node);
+    }
+
+    fn compatibility(&self, other: &Genome, config: &NeatConfig) -> f32 {
+        let (a, b) = (&self.connections, &other.connections);
+        let (mut i, mut j) = (0, 0);
+        let (mut disjoint, mut matching, mut weight_difference) = (0usize, 0usize, 0.0f32);
+        while i < a.len() && j < b.len() {
+            match a[i].innovation.cmp(&b[j].innovation) {
+                std::cmp::Ordering::Less => {
+                    disjoint += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    disjoint += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a[i].weight - b[j].weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        // Whatever is left over lies past the end of the other genome
+        let excess = (a.len() - i) + (b.len() - j);
+        let genes = a.len().max(b.len()).max(1) as f32;
+        let mean_weight_difference = if matching == 0 { 0.0 } else { weight_difference / matching as f32 };
+        config.excess_coefficient * excess as f32 / genes
+            + config.disjoint_coefficient * disjoint as f32 / genes
+            + config.weight_coefficient * mean_weight_difference
+    }
+
+    // Matching genes come from either parent at random, the rest from `self`, the fitter one
+    fn crossover(&self, other: &Genome, rng: &mut Rng) -> Genome {
+        let mut child = self.clone();
+        let mut j = 0;
+        for gene in child.connections.iter_mut() {
+            while j < other.connections.len() && other.connections[j].innovation < gene.innovation {
+                j += 1;
+            }
+            if let Some(partner) = other.connections.get(j).filter(|partner| partner.innovation == gene.innovation) {
+                if rng.next_f32() < 0.5 {
+                    gene.weight = partner.weight;
+                }
+                gene.enabled = (gene.enabled && partner.enabled) || rng.next_f32() >= INHERIT_DISABLED;
+            }
+        }
+        child
+    }
+
+    fn mutate(&mut self, config: &NeatConfig, innovations: &mut Innovations, rng: &mut Rng) {
+        if rng.next_f32() < config.weight_mutation_rate {
+            for gene in self.connections.iter_mut() {
+                gene.weight = if rng.next_f32() < config.weight_replace_rate {
+                    rng.uniform(-1.0, 1.0)
+                } else {
+                    gene.weight + rng.normal() * config.weight_perturbation
+                }
+                .clamp(-config.weight_limit, config.weight_limit);
+            }
+        }
+        if rng.next_f32() < config.add_connection_rate {
+            self.mutate_add_connection(innovations, rng);
+        }
+        if rng.next_f32() < config.add_node_rate {
+            self.mutate_add_node(innovations, rng);
+        }
+    }
+
+    fn mutate_add_connection(&mut self, innovations: &mut Innovations, rng: &mut Rng) {
+        let sensors = self.inputs + 1;
+        for _ in 0..CONNECTION_ATTEMPTS {
+            let from = self.nodes[rng.next_u64() as usize % self.nodes.len()];
+            let to = self.nodes[sensors + rng.next_u64() as usize % (self.nodes.len() - sensors)];
+            if from.depth < to.depth && !self.has_connection(from.id, to.id) {
+                let innovation = innovations.connection(from.id, to.id);
+                self.insert_connection(ConnectionGene {
+                    innovation,
+                    from: from.id,
+                    to: to.id,
+                    weight: rng.uniform(-1.0, 1.0),
+                    enabled: true,
+                });
+                return;
+            }
+        }
+    }
+
+    // Split an enabled connection a→b into a→new (weight 1) and new→b (old weight)
+    fn mutate_add_node(&mut self, innovations: &mut Innovations, rng: &mut Rng) {
+        let enabled: Vec<usize> = (0..self.connections.len()).filter(|&index| self.connections[index].enabled).collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let split = self.connections[enabled[rng.next_u64() as usize % enabled.len()]];
+        let (from_depth, to_depth) = (self.depth(split.from), self.depth(split.to));
+        let depth = (from_depth + to_depth) / 2.0;
+        if depth <= from_depth || depth >= to_depth {
+            return; // depths have run out of f32 precision
+        }
+        let mut node = innovations.split(split.innovation);
+        if self.nodes.binary_search_by_key(&node, |existing| existing.id).is_ok() {
+            node = innovations.fresh_node();
+        }
+
+        if let Some(gene) = self.connections.iter_mut().find(|gene| gene.innovation == split.innovation) {
+            gene.enabled = false;
+        }
+        self.insert_node(NodeGene { id: node, depth });
+        let incoming = innovations.connection(split.from, node);
+        self.insert_connection(ConnectionGene { innovation: incoming, from: split.from, to: node, weight: 1.0, enabled: true });
+        let outgoing = innovations.connection(node, split.to);
+        self.insert_connection(ConnectionGene { innovation: outgoing, from: node, to: split.to, weight: split.weight, enabled: true });
+    }
+}
+
+// Historical markings shared by the whole population
+#[derive(Debug, Clone, Default)]
+struct Innovations {
+    connections: HashMap<(u32, u32), u32>,
+    // Hidden node created by splitting each connection innovation
+    splits: HashMap<u32, u32>,
+    next_innovation: u32,
+    next_node: u32,
+}
+
+impl Innovations {
+    fn connection(&mut self, from: u32, to: u32) -> u32 {
+        let next = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn split(&mut self, innovation: u32) -> u32 {
+        match self.splits.get(&innovation) {
+            Some(&node) => node,
+            None => {
+                let node = self.fresh_node();
+                self.splits.insert(innovation, node);
+                node
+            }
+        }
+    }
+
+    fn fresh_node(&mut self) -> u32 {
+        self.next_node += 1;
+        self.next_node - 1
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Species {
+    representative: Genome,
+    // Indices into the generation being evaluated
+    members: Vec<usize>,
+    best_fitness: f32,
+    stale_generations: u32,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct NeatPopulation {
+    config: NeatConfig,
+    genomes: Vec<Genome>,
+    species: Vec<Species>,
+    innovations: Innovations,
+    rng: Rng,
+    generation: u32,
+    // Fittest genome seen in any evaluated generation
+    champion: Option<(Genome, f32)>,
+}
+
+#[wasm_bindgen]
+impl NeatPopulation {
+    // Minimal genomes: every input and the bias wired straight to every output with
+    // uniform(-1, 1) weights
+    #[wasm_bindgen(constructor)]
+    pub fn new(inputs: usize, outputs: usize, config: &NeatConfig, seed: u64) -> Result<NeatPopulation, NeuralError> {
+        config.validate()?;
+        if inputs == 0 || outputs == 0 {
+            return Err(NeuralError::InvalidConfiguration("genomes need at least one input and one output".to_string()));
+        }
+        let sensors = inputs + 1;
+        if u32::try_from(sensors + outputs).is_err() {
+            return Err(NeuralError::InvalidConfiguration("genome node count exceeds u32".to_string()));
+        }
+
+        let mut innovations = Innovations { next_node: (sensors + outputs) as u32, ..Innovations::default() };
+        let mut nodes: Vec<NodeGene> = (0..sensors as u32).map(|id| NodeGene { id, depth: 0.0 }).collect();
+        nodes.extend((sensors..sensors + outputs).map(|id| NodeGene { id: id as u32, depth: OUTPUT_DEPTH }));
+        let wiring: Vec<(u32, u32, u32)> = (0..sensors as u32)
+            .flat_map(|from| (sensors..sensors + outputs).map(move |to| (from, to as u32)))
+            .map(|(from, to)| (innovations.connection(from, to), from, to))
+            .collect();
+
+        let mut rng = Rng::new(seed);
+        let genomes = (0..config.population_size)
+            .map(|_| Genome {
+                inputs,
+                outputs,
+                activation: config.activation,
+                nodes: nodes.clone(),
+                connections: wiring
+                    .iter()
+                    .map(|&(innovation, from, to)| ConnectionGene {
+                        innovation,
+                        from,
+                        to,
+                        weight: rng.uniform(-1.0, 1.0),
+                        enabled: true,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(NeatPopulation {
+            config: *config,
+            genomes,
+            species: Vec::new(),
+            innovations,
+            rng,
+            generation: 0,
+            champion: None,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.genomes.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    // Species found when the last generation was evaluated
+    #[wasm_bindgen(getter)]
+    pub fn species_count(&self) -> usize {
+        self.species.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn genome(&self, index: usize) -> Result<Genome, NeuralError> {
+        self.genomes
+            .get(index)
+            .cloned()
+            .ok_or(NeuralError::IndexOutOfRange { index, len: self.genomes.len() })
+    }
+
+    // Run one genome without copying it out to JavaScript
+    #[wasm_bindgen]
+    pub fn activate(&self, index: usize, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.genomes
+            .get(index)
+            .ok_or(NeuralError::IndexOutOfRange { index, len: self.genomes.len() })?
+            .activate(inputs)
+    }
+
+    // Fittest genome of any evaluated generation, undefined before the first evolve()
+    #[wasm_bindgen]
+    pub fn champion(&self) -> Option<Genome> {
+        self.champion.as_ref().map(|(genome, _)| genome.clone())
+    }
+
+    #[wasm_bindgen]
+    pub fn champion_fitness(&self) -> Option<f32> {
+        self.champion.as_ref().map(|&(_, fitness)| fitness)
+    }
+
+    // Score the current generation (one fitness per genome, higher is better) and
+    // replace it with the next; returns the new generation number
+    #[wasm_bindgen]
+    pub fn evolve(&mut self, fitness_scores: &[f32]) -> Result<u32, NeuralError> {
+        if fitness_scores.len() != self.genomes.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.genomes.len(), actual: fitness_scores.len() });
+        }
+        if let Some(index) = fitness_scores.iter().position(|score| !score.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+
+        let best = (0..fitness_scores.len()).max_by(|&a, &b| fitness_scores[a].total_cmp(&fitness_scores[b])).unwrap_or(0);
+        if self.champion.as_ref().is_none_or(|&(_, fitness)| fitness_scores[best] > fitness) {
+            self.champion = Some((self.genomes[best].clone(), fitness_scores[best]));
+        }
+
+        self.speciate();
+        self.cull_stagnant(fitness_scores, best);
+        let offspring = self.allocate_offspring(fitness_scores);
+
+        let mut next = Vec::with_capacity(self.config.population_size);
+        for (species, &count) in self.species.iter().zip(&offspring) {
+            let mut ranked = species.members.clone();
+            ranked.sort_by(|&a, &b| fitness_scores[b].total_cmp(&fitness_scores[a]));
+            let elites = self.config.elitism.min(count).min(ranked.len());
+            next.extend(ranked[..elites].iter().map(|&index| self.genomes[index].clone()));
+
+            let parents = &ranked[..((ranked.len() as f32 * self.config.survival_fraction).ceil() as usize).clamp(1, ranked.len())];
+            for _ in elites..count {
+                let first = parents[self.rng.next_u64() as usize % parents.len()];
+                let mut child = if parents.len() > 1 && self.rng.next_f32() < self.config.crossover_rate {
+                    let second = parents[self.rng.next_u64() as usize % parents.len()];
+                    let (fitter, other) = if fitness_scores[second] > fitness_scores[first] { (second, first) } else { (first, second) };
+                    self.genomes[fitter].crossover(&self.genomes[other], &mut self.rng)
+                } else {
+                    self.genomes[first].clone()
+                };
+                child.mutate(&self.config, &mut self.innovations, &mut self.rng);
+                next.push(child);
+            }
+        }
+
+        // Each species is represented in the next round by a random current member
+        for species in self.species.iter_mut() {
+            let member = species.members[self.rng.next_u64() as usize % species.members.len()];
+            species.representative = self.genomes[member].clone();
+        }
+        self.genomes = next;
+        self.generation += 1;
+        Ok(self.generation)
+    }
+}
+
+impl NeatPopulation {
+    // Place every genome in the first species whose representative is close enough
+    fn speciate(&mut self) {
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+        for (index, genome) in self.genomes.iter().enumerate() {
+            let threshold = self.config.compatibility_threshold;
+            match self
+                .species
+                .iter_mut()
+                .find(|species| genome.compatibility(&species.representative, &self.config) < threshold)
+            {
+                Some(species) => species.members.push(index),
+                None => self.species.push(Species {
+                    representative: genome.clone(),
+                    members: vec![index],
+                    best_fitness: f32::NEG_INFINITY,
+                    stale_generations: 0,
+                }),
+            }
+        }
+        self.species.retain(|species| !species.members.is_empty());
+    }
+
+    fn cull_stagnant(&mut self, fitness_scores: &[f32], best: usize) {
+        for species in self.species.iter_mut() {
+            let top = species.members.iter().map(|&index| fitness_scores[index]).fold(f32::NEG_INFINITY, f32::max);
+            if top > species.best_fitness {
+                species.best_fitness = top;
+                species.stale_generations = 0;
+            } else {
+                species.stale_generations += 1;
+            }
+        }
+        let limit = self.config.stagnation_limit;
+        self.species.retain(|species| species.stale_generations < limit || species.members.contains(&best));
+    }
+
+    // Offspring per species, summing to the population size (largest remainder)
+    fn allocate_offspring(&self, fitness_scores: &[f32]) -> Vec<usize> {
+        let worst = fitness_scores.iter().copied().fold(f32::INFINITY, f32::min);
+        let mut shares: Vec<f64> = self
+            .species
+            .iter()
+            .map(|species| {
+                let total: f64 = species.members.iter().map(|&index| (fitness_scores[index] - worst) as f64).sum();
+                total / species.members.len() as f64
+            })
+            .collect();
+        let total: f64 = shares.iter().sum();
+        if total <= 0.0 {
+            // No species is ahead of another: split by current size
+            shares = self.species.iter().map(|species| species.members.len() as f64).collect();
+        }
+        let total: f64 = shares.iter().sum();
+
+        let size = self.config.population_size;
+        let exact: Vec<f64> = shares.iter().map(|share| share / total * size as f64).collect();
+        let mut counts: Vec<usize> = exact.iter().map(|value| value.floor() as usize).collect();
+        let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+        by_remainder.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+        let assigned: usize = counts.iter().sum();
+        for &index in by_remainder.iter().take(size - assigned) {
+            counts[index] += 1;
+        }
+        counts
+    }
+}
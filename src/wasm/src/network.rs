@@ -0,0 +1,2495 @@
+// Feed-forward neural network with per-layer activations
+// Dense layers are fully connected; weights are stored row-major as [outputs][inputs],
+// or as CSR once pruning has zeroed at least half of an f32 layer.
+// LSTM and GRU layers carry hidden state between forward_step() calls; Conv1d
+// layers read their input as [channels][length]. Dropout and BatchNorm behave
+// differently while training; see normalization.rs.
+
+use std::borrow::Cow;
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::{self, ActivationKind};
+use crate::attestation::Attestation;
+use crate::attribution::{self, AttributionConfig};
+use crate::checked;
+use crate::conv::{Conv1dGeometry, Conv1dLayer};
+use crate::dataset::Dataset;
+use crate::diagnostics::{self, ActivationReport, GradientTrend, Histogram, LayerReport};
+use crate::encryption;
+use crate::error::{NeuralError, NeuralResult};
+use crate::fann_format;
+use crate::fixed_point::{self, Arithmetic};
+use crate::gradient_optimizer::{GradientOptimizerConfig, OptimizerState};
+use crate::gradients::GradientSet;
+use crate::initializer::{InitDistribution, InitScheme, Initializer};
+use crate::integrity::{self, IntegrityCheck, SHA256_LEN};
+use crate::linalg;
+use crate::logging::{log_event, LogLevel};
+use crate::lora::{self, LoraAdapter};
+use crate::loss::{Loss, LossFunction};
+use crate::mixed_precision::{self, LossScaler, TrainingPrecision};
+use crate::model_spec;
+use crate::normalization::{DropoutLayer, NormKind, NormLayer, RegularizerConfig};
+use crate::npy_format;
+use crate::onnx_format;
+use crate::precision::{self, Precision};
+use crate::preprocess::Preprocessor;
+use crate::quantization::{QuantParams, QuantizedMatrix};
+use crate::recurrent::{CellKind, RecurrentLayer};
+use crate::replay::{self, ExecutionState, Operation, Recorder, ReplayReport};
+use crate::rng::Rng;
+use crate::scratch::ScratchAllocator;
+use crate::serialization::{self, WeightEncoding};
+use crate::sparse::CsrMatrix;
+use crate::tasks::{CancellationToken, Yielder, DEFAULT_SLICE_MS};
+use crate::training::{self, FitOptions, FitProgress, FitReport, TrainingFault, TrainingOutcome, TrainingSafety};
+use crate::weight_diff;
+
+// Weight matrix in one of the supported storage precisions
+#[derive(Debug, Clone)]
+pub(crate) enum WeightStorage {
+    F32(Vec<f32>),
+    F16(Vec<u16>),
+    Int8(QuantizedMatrix),
+    // f32 non-zeros of a pruned layer
+    Sparse(CsrMatrix),
+}
+
+// Zero fraction at which pruned f32 layers switch to CSR, where it starts saving memory
+const SPARSE_MIN_SPARSITY: f32 = 0.5;
+
+#[derive(Debug, Clone)]
+pub(crate) struct DenseLayer {
+    pub(crate) inputs: usize,
+    pub(crate) outputs: usize,
+    pub(crate) weights: WeightStorage,
+    pub(crate) biases: Vec<f32>,
+    pub(crate) activation: ActivationKind,
+    // Low-rank update trained in place of the frozen base weights
+    pub(crate) adapter: Option<LoraAdapter>,
+}
+
+impl DenseLayer {
+    fn new(inputs: usize, outputs: usize, activation: ActivationKind) -> DenseLayer {
+        DenseLayer {
+            inputs,
+            outputs,
+            weights: WeightStorage::F32(vec![0.0; inputs * outputs]),
+            biases: vec![0.0; outputs],
+            activation,
+            adapter: None,
+        }
+    }
+
+    // Weights as f32, widening or dequantizing reduced-precision storage
+    pub(crate) fn dense_weights(&self) -> Cow<'_, [f32]> {
+        match &self.weights {
+            WeightStorage::F32(weights) => Cow::Borrowed(weights),
+            WeightStorage::F16(halves) => Cow::Owned(precision::f16_slice_to_f32(halves)),
+            WeightStorage::Int8(matrix) => Cow::Owned(matrix.dequantize()),
+            WeightStorage::Sparse(matrix) => Cow::Owned(matrix.to_dense()),
+        }
+    }
+
+    // Weights including the adapter's update, as inference computes them
+    pub(crate) fn effective_weights(&self, simd: bool) -> Cow<'_, [f32]> {
+        match &self.adapter {
+            Some(adapter) => Cow::Owned(adapter.merged(&self.dense_weights(), simd)),
+            None => self.dense_weights(),
+        }
+    }
+
+    // Replace the weights, keeping the current precision (and int8 parameters)
+    fn store_weights(&mut self, weights: &[f32]) {
+        self.weights = match &self.weights {
+            WeightStorage::F32(_) => WeightStorage::F32(weights.to_vec()),
+            WeightStorage::F16(_) => WeightStorage::F16(precision::f32_slice_to_f16(weights)),
+            WeightStorage::Int8(matrix) => {
+                WeightStorage::Int8(QuantizedMatrix::from_f32(weights, self.outputs, self.inputs, matrix.params))
+            }
+            WeightStorage::Sparse(_) => WeightStorage::Sparse(CsrMatrix::from_dense(weights, self.outputs, self.inputs)),
+        };
+    }
+
+    // Re-encode the weights; int8 parameters are calibrated to the layer's range.
+    // F32 always means dense storage, so it also expands sparse layers.
+    fn set_precision(&mut self, target: Precision) {
+        if self.precision() == target && !matches!(self.weights, WeightStorage::Sparse(_)) {
+            return;
+        }
+        match target {
+            Precision::Int8 => self.quantize(QuantParams::calibrate(&self.dense_weights())),
+            Precision::F16 => self.weights = WeightStorage::F16(precision::f32_slice_to_f16(&self.dense_weights())),
+            Precision::F32 => self.weights = WeightStorage::F32(self.dense_weights().into_owned()),
+        }
+    }
+
+    fn quantize(&mut self, params: QuantParams) {
+        let matrix = QuantizedMatrix::from_f32(&self.dense_weights(), self.outputs, self.inputs, params);
+        self.weights = WeightStorage::Int8(matrix);
+    }
+
+    fn precision(&self) -> Precision {
+        match self.weights {
+            WeightStorage::F32(_) => Precision::F32,
+            WeightStorage::F16(_) => Precision::F16,
+            WeightStorage::Int8(_) => Precision::Int8,
+            WeightStorage::Sparse(_) => Precision::F32,
+        }
+    }
+
+    // Zero every non-zero weight for which `prune(index, weight)` holds, then switch
+    // to CSR if the layer is now sparse enough; returns how many were zeroed
+    fn prune_where(&mut self, mut prune: impl FnMut(usize, f32) -> bool) -> usize {
+        let mut weights = self.dense_weights().into_owned();
+        let mut pruned = 0;
+        for (index, weight) in weights.iter_mut().enumerate() {
+            if *weight != 0.0 && prune(index, *weight) {
+                *weight = 0.0;
+                pruned += 1;
+            }
+        }
+        if pruned > 0 {
+            self.store_weights(&weights);
+        }
+        let zeros = weights.iter().filter(|&&weight| weight == 0.0).count();
+        if matches!(self.weights, WeightStorage::F32(_)) && zeros as f32 >= SPARSE_MIN_SPARSITY * weights.len() as f32 {
+            self.weights = WeightStorage::Sparse(CsrMatrix::from_dense(&weights, self.outputs, self.inputs));
+        }
+        pruned
+    }
+
+    fn zero_weights(&self) -> usize {
+        match &self.weights {
+            WeightStorage::Sparse(matrix) => self.inputs * self.outputs - matrix.nnz(),
+            _ => self.dense_weights().iter().filter(|&&weight| weight == 0.0).count(),
+        }
+    }
+
+    // Whether the stored weights are exactly an outputs×inputs matrix (and the
+    // adapter fits it)
+    fn storage_matches_shape(&self) -> bool {
+        let count = self.inputs * self.outputs;
+        if self.adapter.as_ref().is_some_and(|adapter| !adapter.matches(self.inputs, self.outputs)) {
+            return false;
+        }
+        match &self.weights {
+            WeightStorage::F32(weights) => weights.len() == count,
+            WeightStorage::F16(halves) => halves.len() == count,
+            WeightStorage::Int8(matrix) => matrix.rows == self.outputs && matrix.cols == self.inputs && matrix.data.len() == count,
+            WeightStorage::Sparse(matrix) => {
+                matrix.rows == self.outputs
+                    && matrix.cols == self.inputs
+                    && matrix.row_offsets.len() == matrix.rows + 1
+                    && matrix.row_offsets.windows(2).all(|pair| pair[0] <= pair[1])
+                    && matrix.row_offsets.last().map(|&end| end as usize) == Some(matrix.values.len())
+                    && matrix.col_indices.len() == matrix.values.len()
+                    && matrix.col_indices.iter().all(|&col| (col as usize) < matrix.cols)
+            }
+        }
+    }
+
+    fn weight_bytes(&self) -> usize {
+        match &self.weights {
+            WeightStorage::F32(weights) => weights.len() * std::mem::size_of::<f32>(),
+            WeightStorage::F16(halves) => halves.len() * std::mem::size_of::<u16>(),
+            WeightStorage::Int8(matrix) => matrix.data.len(),
+            WeightStorage::Sparse(matrix) => matrix.byte_len(),
+        }
+    }
+
+    // outputs = activation(W · inputs + b)
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        match &self.weights {
+            WeightStorage::F32(weights) => linalg::matvec_into(weights, inputs, outputs, self.outputs, self.inputs, simd)?,
+            WeightStorage::F16(halves) => {
+                check_len(inputs.len(), self.inputs)?;
+                check_len(outputs.len(), self.outputs)?;
+                precision::matvec_f16_into(halves, inputs, outputs, self.outputs, self.inputs, simd);
+            }
+            WeightStorage::Int8(matrix) => matrix.matvec_into(inputs, outputs, simd)?,
+            WeightStorage::Sparse(matrix) => matrix.matvec_into(inputs, outputs, simd)?,
+        }
+        if let Some(adapter) = &self.adapter {
+            adapter.add_into(inputs, outputs, simd)?;
+        }
+        for (output, bias) in outputs.iter_mut().zip(&self.biases) {
+            *output += bias;
+        }
+        self.activation.apply_slice(outputs, simd);
+        Ok(())
+    }
+
+    // Row-major batch: outputs[batch×out] = activation(inputs[batch×in] · Wᵀ + b)
+    fn forward_batch(&self, inputs: &[f32], batch_size: usize, simd: bool) -> NeuralResult<Vec<f32>> {
+        let mut outputs = vec![0.0; batch_size * self.outputs];
+        self.forward_batch_into(inputs, batch_size, &mut outputs, simd)?;
+        Ok(outputs)
+    }
+
+    fn forward_batch_into(&self, inputs: &[f32], batch_size: usize, outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        check_len(outputs.len(), batch_size * self.outputs)?;
+        match &self.weights {
+            WeightStorage::F32(weights) => {
+                linalg::matmul_transposed_into(inputs, weights, outputs, batch_size, self.outputs, self.inputs, simd)?
+            }
+            // Widen each weight row once and reuse it for every sample
+            WeightStorage::F16(halves) => {
+                check_len(inputs.len(), batch_size * self.inputs)?;
+                let mut row_buffer = vec![0.0; self.inputs];
+                for (neuron, row) in halves.chunks_exact(self.inputs.max(1)).enumerate() {
+                    precision::widen_into(row, &mut row_buffer, simd);
+                    for (sample, input) in inputs.chunks_exact(self.inputs.max(1)).enumerate() {
+                        outputs[sample * self.outputs + neuron] = linalg::dot(&row_buffer, input, simd);
+                    }
+                }
+            }
+            // Each sample gets its own activation scale, so int8 batches run row by row
+            WeightStorage::Int8(matrix) => {
+                for (input, output) in inputs.chunks_exact(self.inputs).zip(outputs.chunks_exact_mut(self.outputs)) {
+                    matrix.matvec_into(input, output, simd)?;
+                }
+            }
+            WeightStorage::Sparse(matrix) => {
+                check_len(inputs.len(), batch_size * self.inputs)?;
+                for (input, output) in inputs.chunks_exact(self.inputs.max(1)).zip(outputs.chunks_exact_mut(self.outputs)) {
+                    matrix.matvec_into(input, output, simd)?;
+                }
+            }
+        }
+        if let Some(adapter) = &self.adapter {
+            adapter.add_into(inputs, outputs, simd)?;
+        }
+        for row in outputs.chunks_exact_mut(self.outputs) {
+            for (output, bias) in row.iter_mut().zip(&self.biases) {
+                *output += bias;
+            }
+            self.activation.apply_slice(row, simd);
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+    Dense = 0,
+    Lstm = 1,
+    Gru = 2,
+    Conv1d = 3,
+    Dropout = 4,
+    BatchNorm = 5,
+    LayerNorm = 6,
+}
+
+impl LayerKind {
+    // Inverse of `kind as u8`, used when decoding serialized models
+    pub fn from_u8(value: u8) -> Option<LayerKind> {
+        match value {
+            0 => Some(LayerKind::Dense),
+            1 => Some(LayerKind::Lstm),
+            2 => Some(LayerKind::Gru),
+            3 => Some(LayerKind::Conv1d),
+            4 => Some(LayerKind::Dropout),
+            5 => Some(LayerKind::BatchNorm),
+            6 => Some(LayerKind::LayerNorm),
+            _ => None,
+        }
+    }
+
+    // Layer type as written in model specs and describe()
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            LayerKind::Dense => "dense",
+            LayerKind::Lstm => "lstm",
+            LayerKind::Gru => "gru",
+            LayerKind::Conv1d => "conv1d",
+            LayerKind::Dropout => "dropout",
+            LayerKind::BatchNorm => "batch_norm",
+            LayerKind::LayerNorm => "layer_norm",
+        }
+    }
+}
+
+// Post-processing applied to each sample's final-layer outputs
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    // Outputs exactly as the last layer produces them
+    Raw = 0,
+    // Numerically stable softmax, turning logits into class probabilities
+    Softmax = 1,
+}
+
+impl OutputMode {
+    fn name(self) -> &'static str {
+        match self {
+            OutputMode::Raw => "raw",
+            OutputMode::Softmax => "softmax",
+        }
+    }
+}
+
+// Architecture of one layer, as recorded in serialized models
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LayerShape {
+    pub(crate) kind: LayerKind,
+    pub(crate) inputs: usize,
+    pub(crate) outputs: usize,
+    pub(crate) activation: ActivationKind,
+    // Present exactly for Conv1d layers
+    pub(crate) conv: Option<Conv1dGeometry>,
+    // Present exactly for Dropout, BatchNorm and LayerNorm layers
+    pub(crate) regularizer: Option<RegularizerConfig>,
+}
+
+impl LayerShape {
+    // (weight count, bias count) for a layer of this shape, or None if it is
+    // inconsistent or overflows
+    pub(crate) fn parameter_counts(&self) -> Option<(usize, usize)> {
+        let (inputs, outputs) = (self.inputs, self.outputs);
+        match (self.kind, self.conv, self.regularizer) {
+            (LayerKind::Dense, None, None) => Some((inputs.checked_mul(outputs)?, outputs)),
+            (LayerKind::Lstm, None, None) => Some((RecurrentLayer::weight_count(CellKind::Lstm, inputs, outputs)?, 4 * outputs)),
+            (LayerKind::Gru, None, None) => Some((RecurrentLayer::weight_count(CellKind::Gru, inputs, outputs)?, 3 * outputs)),
+            (LayerKind::Conv1d, Some(geometry), None) => {
+                geometry.length_for(inputs, outputs)?;
+                Some((geometry.weight_count()?, geometry.out_channels))
+            }
+            (LayerKind::Dropout, None, Some(config @ RegularizerConfig::Dropout { .. })) if inputs == outputs => {
+                config.validate().ok()?;
+                Some((0, 0))
+            }
+            (LayerKind::BatchNorm, None, Some(config @ RegularizerConfig::BatchNorm { .. }))
+            | (LayerKind::LayerNorm, None, Some(config @ RegularizerConfig::LayerNorm { .. }))
+                if inputs == outputs =>
+            {
+                config.validate().ok()?;
+                Some((outputs, outputs))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Any layer a network can hold. Reduced precision applies to dense layers; the
+// others always keep f32 parameters. Training supports dense, dropout and
+// normalization layers.
+#[derive(Debug, Clone)]
+pub(crate) enum Layer {
+    Dense(DenseLayer),
+    Recurrent(RecurrentLayer),
+    Conv1d(Conv1dLayer),
+    Dropout(DropoutLayer),
+    Norm(NormLayer),
+}
+
+impl Layer {
+    // Build an f32 layer from parameters sized by LayerShape::parameter_counts()
+    pub(crate) fn with_parameters(shape: &LayerShape, weights: &[f32], biases: &[f32]) -> NeuralResult<Layer> {
+        let (inputs, outputs) = (shape.inputs, shape.outputs);
+        let mut layer = match (shape.kind, shape.conv) {
+            (LayerKind::Conv1d, Some(geometry)) => {
+                let length = geometry
+                    .length_for(inputs, outputs)
+                    .ok_or_else(|| NeuralError::InvalidConfiguration("convolution geometry does not match layer size".to_string()))?;
+                Layer::Conv1d(Conv1dLayer::new(geometry, length, shape.activation)?)
+            }
+            (LayerKind::Lstm, _) => Layer::Recurrent(RecurrentLayer::new(CellKind::Lstm, inputs, outputs)),
+            (LayerKind::Gru, _) => Layer::Recurrent(RecurrentLayer::new(CellKind::Gru, inputs, outputs)),
+            (LayerKind::Dropout | LayerKind::BatchNorm | LayerKind::LayerNorm, _) => {
+                let config = shape
+                    .regularizer
+                    .ok_or_else(|| NeuralError::InvalidConfiguration("layer settings are missing".to_string()))?;
+                Layer::regularizer(config, outputs)?
+            }
+            _ => Layer::Dense(DenseLayer::new(inputs, outputs, shape.activation)),
+        };
+        check_len(weights.len(), layer.weight_count())?;
+        check_len(biases.len(), layer.biases().len())?;
+        layer.store_weights(weights);
+        layer.biases_mut().copy_from_slice(biases);
+        Ok(layer)
+    }
+
+    // Dropout, BatchNorm or LayerNorm over `size` features
+    pub(crate) fn regularizer(config: RegularizerConfig, size: usize) -> NeuralResult<Layer> {
+        config.validate()?;
+        Ok(match config {
+            RegularizerConfig::Dropout { rate } => Layer::Dropout(DropoutLayer { size, rate }),
+            RegularizerConfig::BatchNorm { epsilon, momentum } => Layer::Norm(NormLayer::new(NormKind::Batch, size, epsilon, momentum)),
+            RegularizerConfig::LayerNorm { epsilon } => Layer::Norm(NormLayer::new(NormKind::Layer, size, epsilon, 0.0)),
+        })
+    }
+
+    pub(crate) fn shape(&self) -> LayerShape {
+        LayerShape {
+            kind: self.kind(),
+            inputs: self.inputs(),
+            outputs: self.outputs(),
+            activation: self.activation(),
+            conv: match self {
+                Layer::Conv1d(layer) => Some(layer.geometry),
+                _ => None,
+            },
+            regularizer: match self {
+                Layer::Dropout(layer) => Some(RegularizerConfig::Dropout { rate: layer.rate }),
+                Layer::Norm(layer) => Some(layer.config()),
+                _ => None,
+            },
+        }
+    }
+
+    pub(crate) fn kind(&self) -> LayerKind {
+        match self {
+            Layer::Dense(_) => LayerKind::Dense,
+            Layer::Recurrent(layer) => match layer.cell {
+                CellKind::Lstm => LayerKind::Lstm,
+                CellKind::Gru => LayerKind::Gru,
+            },
+            Layer::Conv1d(_) => LayerKind::Conv1d,
+            Layer::Dropout(_) => LayerKind::Dropout,
+            Layer::Norm(layer) => match layer.kind {
+                NormKind::Batch => LayerKind::BatchNorm,
+                NormKind::Layer => LayerKind::LayerNorm,
+            },
+        }
+    }
+
+    pub(crate) fn as_dense(&self) -> Option<&DenseLayer> {
+        match self {
+            Layer::Dense(layer) => Some(layer),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn inputs(&self) -> usize {
+        match self {
+            Layer::Dense(layer) => layer.inputs,
+            Layer::Recurrent(layer) => layer.inputs,
+            Layer::Conv1d(layer) => layer.inputs(),
+            Layer::Dropout(layer) => layer.size,
+            Layer::Norm(layer) => layer.size,
+        }
+    }
+
+    pub(crate) fn outputs(&self) -> usize {
+        match self {
+            Layer::Dense(layer) => layer.outputs,
+            Layer::Recurrent(layer) => layer.hidden,
+            Layer::Conv1d(layer) => layer.outputs(),
+            Layer::Dropout(layer) => layer.size,
+            Layer::Norm(layer) => layer.size,
+        }
+    }
+
+    // Recurrent outputs are the hidden state, which is tanh-bounded
+    pub(crate) fn activation(&self) -> ActivationKind {
+        match self {
+            Layer::Dense(layer) => layer.activation,
+            Layer::Recurrent(_) => ActivationKind::Tanh,
+            Layer::Conv1d(layer) => layer.activation,
+            Layer::Dropout(_) | Layer::Norm(_) => ActivationKind::Linear,
+        }
+    }
+
+    pub(crate) fn biases(&self) -> &[f32] {
+        match self {
+            Layer::Dense(layer) => &layer.biases,
+            Layer::Recurrent(layer) => &layer.biases,
+            Layer::Conv1d(layer) => &layer.biases,
+            Layer::Dropout(_) => &[],
+            Layer::Norm(layer) => &layer.beta,
+        }
+    }
+
+    pub(crate) fn biases_mut(&mut self) -> &mut [f32] {
+        match self {
+            Layer::Dense(layer) => &mut layer.biases,
+            Layer::Recurrent(layer) => &mut layer.biases,
+            Layer::Conv1d(layer) => &mut layer.biases,
+            Layer::Dropout(_) => &mut [],
+            Layer::Norm(layer) => &mut layer.beta,
+        }
+    }
+
+    pub(crate) fn dense_weights(&self) -> Cow<'_, [f32]> {
+        match self {
+            Layer::Dense(layer) => layer.dense_weights(),
+            Layer::Recurrent(layer) => Cow::Borrowed(&layer.weights),
+            Layer::Conv1d(layer) => Cow::Borrowed(&layer.weights),
+            Layer::Dropout(_) => Cow::Borrowed(&[]),
+            Layer::Norm(layer) => Cow::Borrowed(&layer.gamma),
+        }
+    }
+
+    pub(crate) fn weight_count(&self) -> usize {
+        match self {
+            Layer::Dense(layer) => layer.inputs * layer.outputs,
+            Layer::Recurrent(layer) => layer.weights.len(),
+            Layer::Conv1d(layer) => layer.weights.len(),
+            Layer::Dropout(_) => 0,
+            Layer::Norm(layer) => layer.gamma.len(),
+        }
+    }
+
+    // Whether the parameter buffers agree with the layer's shape
+    pub(crate) fn parameters_match_shape(&self) -> bool {
+        let storage = match self {
+            Layer::Dense(layer) => layer.storage_matches_shape(),
+            _ => true,
+        };
+        storage && self.shape().parameter_counts() == Some((self.weight_count(), self.biases().len()))
+    }
+
+    // (fan_in, fan_out) seen by the initializer; each recurrent gate reads [x; h]
+    // and each convolution output reads one kernel window of every input channel
+    fn fan(&self) -> (usize, usize) {
+        match self {
+            Layer::Dense(layer) => (layer.inputs, layer.outputs),
+            Layer::Recurrent(layer) => (layer.inputs + layer.hidden, layer.hidden),
+            Layer::Conv1d(layer) => {
+                let geometry = layer.geometry;
+                (geometry.in_channels * geometry.kernel, geometry.out_channels * geometry.kernel)
+            }
+            Layer::Dropout(layer) => (layer.size, layer.size),
+            Layer::Norm(layer) => (layer.size, layer.size),
+        }
+    }
+
+    // Draw fresh weights and zero the biases; normalization layers return to
+    // γ = 1, β = 0 without consuming randomness
+    fn initialize(&mut self, initializer: &Initializer, rng: &mut Rng) {
+        if let Layer::Norm(layer) = self {
+            layer.reset();
+            return;
+        }
+        let (fan_in, fan_out) = self.fan();
+        let mut weights = vec![0.0; self.weight_count()];
+        initializer.fill(&mut weights, fan_in, fan_out, rng);
+        self.store_weights(&weights);
+        self.biases_mut().fill(0.0);
+    }
+
+    fn store_weights(&mut self, weights: &[f32]) {
+        match self {
+            Layer::Dense(layer) => layer.store_weights(weights),
+            Layer::Recurrent(layer) => layer.weights.copy_from_slice(weights),
+            Layer::Conv1d(layer) => layer.weights.copy_from_slice(weights),
+            Layer::Dropout(_) => {}
+            Layer::Norm(layer) => layer.gamma.copy_from_slice(weights),
+        }
+    }
+
+    fn set_precision(&mut self, target: Precision) {
+        if let Layer::Dense(layer) = self {
+            layer.set_precision(target);
+        }
+    }
+
+    fn quantize(&mut self, params: QuantParams) {
+        if let Layer::Dense(layer) = self {
+            layer.quantize(params);
+        }
+    }
+
+    fn weight_bytes(&self) -> usize {
+        match self {
+            Layer::Dense(layer) => layer.weight_bytes(),
+            Layer::Recurrent(layer) => layer.weights.len() * std::mem::size_of::<f32>(),
+            Layer::Conv1d(layer) => layer.weights.len() * std::mem::size_of::<f32>(),
+            Layer::Dropout(_) => 0,
+            // Running statistics are stored alongside γ
+            Layer::Norm(layer) => {
+                (layer.gamma.len() + layer.running_mean.len() + layer.running_var.len()) * std::mem::size_of::<f32>()
+            }
+        }
+    }
+
+    // Estimated floating-point operations in one sample's inference pass. A
+    // multiply-add counts as two; bias, activation and elementwise steps as one per
+    // element. Sparse layers count only their stored weights.
+    fn forward_flops(&self) -> usize {
+        let outputs = self.outputs();
+        match self {
+            Layer::Dense(layer) => {
+                let products = match &layer.weights {
+                    WeightStorage::Sparse(matrix) => matrix.nnz(),
+                    _ => layer.inputs * layer.outputs,
+                };
+                2 * products + 2 * outputs + layer.adapter.as_ref().map_or(0, LoraAdapter::forward_flops)
+            }
+            // Gate pre-activations, their activations, then about five steps per unit
+            // to update the cell and hidden state
+            Layer::Recurrent(layer) => 2 * layer.weights.len() + 2 * layer.biases.len() + 5 * layer.hidden,
+            // Every kernel weight is applied once per output position
+            Layer::Conv1d(layer) => 2 * layer.weights.len() * layer.out_length + 2 * outputs,
+            Layer::Dropout(_) => 0,
+            // Normalize, scale and shift; layer norm also computes its statistics
+            Layer::Norm(layer) => match layer.kind {
+                NormKind::Batch => 4 * outputs,
+                NormKind::Layer => 7 * outputs,
+            },
+        }
+    }
+
+    // {"type", "inputs", "outputs", "activation", "weights", "biases", "parameters",
+    // "bytes", "flops"}
+    fn describe_json(&self) -> String {
+        let (weights, biases) = (self.weight_count(), self.biases().len());
+        format!(
+            "{{\"type\":\"{}\",\"inputs\":{},\"outputs\":{},\"activation\":\"{}\",\"weights\":{},\"biases\":{},\"parameters\":{},\"bytes\":{},\"flops\":{}}}",
+            self.kind().name(),
+            self.inputs(),
+            self.outputs(),
+            self.activation().name(),
+            weights,
+            biases,
+            weights + biases,
+            self.weight_bytes() + std::mem::size_of_val(self.biases()),
+            self.forward_flops()
+        )
+    }
+
+    fn report(&self, activations: Option<ActivationReport>) -> LayerReport {
+        LayerReport {
+            kind: self.kind(),
+            activation: self.activation(),
+            weights: Histogram::of(&self.dense_weights()),
+            activations,
+        }
+    }
+
+    fn reset_state(&mut self) {
+        if let Layer::Recurrent(layer) = self {
+            layer.reset_state();
+        }
+    }
+
+    // Stateless pass: recurrent layers start from zero state
+    fn forward(&self, inputs: &[f32], simd: bool) -> NeuralResult<Vec<f32>> {
+        let mut outputs = vec![0.0; self.outputs()];
+        self.forward_into(inputs, &mut outputs, simd)?;
+        Ok(outputs)
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        match self {
+            Layer::Dense(layer) => layer.forward_into(inputs, outputs, simd),
+            Layer::Recurrent(layer) => layer.forward_into(inputs, outputs, simd),
+            Layer::Conv1d(layer) => layer.forward_into(inputs, outputs, simd),
+            // Inverted dropout leaves inference untouched
+            Layer::Dropout(_) => {
+                check_len(inputs.len(), outputs.len())?;
+                outputs.copy_from_slice(inputs);
+                Ok(())
+            }
+            Layer::Norm(layer) => layer.forward_into(inputs, outputs, simd),
+        }
+    }
+
+    fn forward_batch(&self, inputs: &[f32], batch_size: usize, simd: bool) -> NeuralResult<Vec<f32>> {
+        match self {
+            Layer::Dense(layer) => layer.forward_batch(inputs, batch_size, simd),
+            _ => {
+                let mut outputs = vec![0.0; batch_size * self.outputs()];
+                self.forward_batch_into(inputs, batch_size, &mut outputs, simd)?;
+                Ok(outputs)
+            }
+        }
+    }
+
+    fn forward_batch_into(&self, inputs: &[f32], batch_size: usize, outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        match self {
+            Layer::Dense(layer) => layer.forward_batch_into(inputs, batch_size, outputs, simd),
+            _ => {
+                let (input_size, output_size) = (self.inputs(), self.outputs());
+                check_len(inputs.len(), batch_size * input_size)?;
+                check_len(outputs.len(), batch_size * output_size)?;
+                for (input, output) in inputs.chunks_exact(input_size).zip(outputs.chunks_exact_mut(output_size)) {
+                    self.forward_into(input, output, simd)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Stateful pass: recurrent layers advance their carried state
+    fn step(&mut self, inputs: &[f32], simd: bool) -> NeuralResult<Vec<f32>> {
+        match self {
+            Layer::Recurrent(layer) => layer.step(inputs, simd),
+            _ => self.forward(inputs, simd),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct NeuralNetwork {
+    input_size: usize,
+    layers: Vec<Layer>,
+    initializer: Initializer,
+    rng: Rng,
+    precision: Precision,
+    output_mode: OutputMode,
+    simd_enabled: bool,
+    optimizer: OptimizerState,
+    loss: Loss,
+    safety: TrainingSafety,
+    last_fault: Option<TrainingFault>,
+    last_gradient_norm: f32,
+    gradient_trend: GradientTrend,
+    training_precision: TrainingPrecision,
+    arithmetic: Arithmetic,
+    loss_scaler: LossScaler,
+    preprocessor: Option<Preprocessor>,
+    recorder: Recorder,
+}
+
+#[wasm_bindgen]
+impl NeuralNetwork {
+    #[wasm_bindgen(constructor)]
+    pub fn new(input_size: usize) -> Result<NeuralNetwork, NeuralError> {
+        if input_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("input size must be non-zero".to_string()));
+        }
+        Ok(NeuralNetwork {
+            input_size,
+            layers: Vec::new(),
+            initializer: Initializer::default(),
+            rng: Rng::default(),
+            precision: Precision::F32,
+            output_mode: OutputMode::Raw,
+            simd_enabled: crate::check_simd_support(),
+            optimizer: OptimizerState::default(),
+            loss: Loss::default(),
+            safety: TrainingSafety::default(),
+            last_fault: None,
+            last_gradient_norm: 0.0,
+            gradient_trend: GradientTrend::default(),
+            training_precision: TrainingPrecision::F32,
+            arithmetic: Arithmetic::F32,
+            loss_scaler: LossScaler::default(),
+            preprocessor: None,
+            recorder: Recorder::default(),
+        })
+    }
+
+    // Network whose layers are initialized with the given scheme as they are added
+    #[wasm_bindgen]
+    pub fn with_initializer(
+        input_size: usize,
+        scheme: InitScheme,
+        distribution: InitDistribution,
+        seed: u64,
+    ) -> Result<NeuralNetwork, NeuralError> {
+        let mut network = NeuralNetwork::new(input_size)?;
+        network.set_initializer(scheme, distribution, seed);
+        Ok(network)
+    }
+
+    // Scheme used for layers added from now on; call reinitialize() to apply it to existing ones
+    #[wasm_bindgen]
+    pub fn set_initializer(&mut self, scheme: InitScheme, distribution: InitDistribution, seed: u64) {
+        self.initializer = Initializer { scheme, distribution };
+        self.rng = Rng::new(seed);
+        self.recorder.record(|| Operation::SetInitializer { scheme, distribution, seed }, &[]);
+    }
+
+    // Redraw every layer's weights and zero the biases; normalization layers
+    // return to their initial state
+    #[wasm_bindgen]
+    pub fn reinitialize(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.initialize(&self.initializer, &mut self.rng);
+        }
+        self.recorder.record(|| Operation::Reinitialize, &[]);
+    }
+
+    // Append a fully connected layer fed by the previous layer's outputs
+    #[wasm_bindgen]
+    pub fn add_layer(&mut self, size: usize, activation: ActivationKind) -> Result<(), NeuralError> {
+        if size == 0 {
+            return Err(NeuralError::InvalidConfiguration("layer size must be non-zero".to_string()));
+        }
+        let inputs = self.output_size();
+        let mut layer = DenseLayer::new(inputs, size, activation);
+        let mut weights = vec![0.0; inputs * size];
+        self.initializer.fill(&mut weights, inputs, size, &mut self.rng);
+        layer.store_weights(&weights);
+        layer.set_precision(self.precision);
+        self.layers.push(Layer::Dense(layer));
+        Ok(())
+    }
+
+    // Append an LSTM layer with `hidden` units; its outputs are the hidden state
+    #[wasm_bindgen]
+    pub fn add_lstm(&mut self, hidden: usize) -> Result<(), NeuralError> {
+        self.add_recurrent(CellKind::Lstm, hidden)
+    }
+
+    // Append a GRU layer with `hidden` units; its outputs are the hidden state
+    #[wasm_bindgen]
+    pub fn add_gru(&mut self, hidden: usize) -> Result<(), NeuralError> {
+        self.add_recurrent(CellKind::Gru, hidden)
+    }
+
+    // Append a 1D convolution reading the previous outputs as [in_channels][length];
+    // its outputs are [out_channels][out_length]
+    #[wasm_bindgen]
+    pub fn add_conv1d(
+        &mut self,
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        stride: usize,
+        padding: usize,
+        activation: ActivationKind,
+    ) -> Result<(), NeuralError> {
+        let inputs = self.output_size();
+        if in_channels == 0 || !inputs.is_multiple_of(in_channels) {
+            return Err(NeuralError::InvalidConfiguration("previous layer size must be a multiple of the input channel count".to_string()));
+        }
+        let geometry = Conv1dGeometry { in_channels, out_channels, kernel: kernel_size, stride, padding };
+        let layer = Layer::Conv1d(Conv1dLayer::new(geometry, inputs / in_channels, activation)?);
+        self.push_initialized(layer);
+        Ok(())
+    }
+
+    // Append inverted dropout zeroing each value with probability `rate` during training
+    #[wasm_bindgen]
+    pub fn add_dropout(&mut self, rate: f32) -> Result<(), NeuralError> {
+        self.add_regularizer(RegularizerConfig::Dropout { rate })
+    }
+
+    // Append batch normalization; PyTorch defaults are epsilon 1e-5 and momentum 0.1.
+    // Training batches then need at least two samples.
+    #[wasm_bindgen]
+    pub fn add_batch_norm(&mut self, epsilon: f32, momentum: f32) -> Result<(), NeuralError> {
+        self.add_regularizer(RegularizerConfig::BatchNorm { epsilon, momentum })
+    }
+
+    // Append layer normalization over each sample's features (PyTorch default epsilon 1e-5)
+    #[wasm_bindgen]
+    pub fn add_layer_norm(&mut self, epsilon: f32) -> Result<(), NeuralError> {
+        self.add_regularizer(RegularizerConfig::LayerNorm { epsilon })
+    }
+
+    #[wasm_bindgen]
+    pub fn set_weights(&mut self, layer: usize, weights: &[f32]) -> Result<(), NeuralError> {
+        let target = self.layer_mut(layer)?;
+        let expected = target.weight_count();
+        if weights.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: weights.len() });
+        }
+        target.store_weights(weights);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_biases(&mut self, layer: usize, biases: &[f32]) -> Result<(), NeuralError> {
+        let target = self.layer_mut(layer)?;
+        let expected = target.biases().len();
+        if biases.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: biases.len() });
+        }
+        target.biases_mut().copy_from_slice(biases);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_weights(&self, layer: usize) -> Result<Vec<f32>, NeuralError> {
+        Ok(self.layer(layer)?.dense_weights().into_owned())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_biases(&self, layer: usize) -> Result<Vec<f32>, NeuralError> {
+        Ok(self.layer(layer)?.biases().to_vec())
+    }
+
+    // Weights and biases of every layer
+    #[wasm_bindgen]
+    pub fn parameter_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.weight_count() + layer.biases().len()).sum()
+    }
+
+    // All parameters as one flat vector: each layer's weights then its biases, in layer order
+    #[wasm_bindgen]
+    pub fn get_parameters(&self) -> Vec<f32> {
+        let mut parameters = Vec::with_capacity(self.parameter_count());
+        for layer in &self.layers {
+            parameters.extend_from_slice(&layer.dense_weights());
+            parameters.extend_from_slice(layer.biases());
+        }
+        parameters
+    }
+
+    // Inverse of get_parameters; weights keep each layer's current precision
+    #[wasm_bindgen]
+    pub fn set_parameters(&mut self, parameters: &[f32]) -> Result<(), NeuralError> {
+        check_len(parameters.len(), self.parameter_count())?;
+        if let Some(index) = parameters.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        let mut rest = parameters;
+        for layer in self.layers.iter_mut() {
+            let (weights, tail) = rest.split_at(layer.weight_count());
+            let (biases, tail) = tail.split_at(layer.biases().len());
+            layer.store_weights(weights);
+            layer.biases_mut().copy_from_slice(biases);
+            rest = tail;
+        }
+        Ok(())
+    }
+
+    // Move to the parameters of a weight set this network's parameters were diffed
+    // against; `delta` comes from diff_weights (see weight_diff.rs)
+    #[wasm_bindgen]
+    pub fn apply_weight_delta(&mut self, delta: &[u8]) -> Result<(), NeuralError> {
+        let parameters = weight_diff::apply_delta(&self.get_parameters(), delta)?;
+        self.set_parameters(&parameters)
+    }
+
+    // Blend another agent's parameters into this network: p = (1 - alpha) · p + alpha · other.
+    // Both networks must share the exact architecture.
+    #[wasm_bindgen]
+    pub fn merge_weights(&mut self, other: &NeuralNetwork, alpha: f32) -> Result<(), NeuralError> {
+        if !alpha.is_finite() || !(0.0..=1.0).contains(&alpha) {
+            return Err(NeuralError::InvalidConfiguration("merge alpha must lie in [0, 1]".to_string()));
+        }
+        self.check_same_architecture(other)?;
+        let mut parameters = self.get_parameters();
+        for (own, theirs) in parameters.iter_mut().zip(other.get_parameters()) {
+            *own += alpha * (theirs - *own);
+        }
+        self.set_parameters(&parameters)
+    }
+
+    // Attach a LoRA adapter of `rank` to dense layer `layer`, replacing any it had.
+    // From then on training updates only the adapters (see lora.rs).
+    #[wasm_bindgen]
+    pub fn add_adapter(&mut self, layer: usize, rank: usize, alpha: f32) -> Result<(), NeuralError> {
+        let Layer::Dense(dense) = self.layer(layer)? else {
+            return Err(NeuralError::InvalidConfiguration(format!("layer {} is not a dense layer", layer)));
+        };
+        let adapter = LoraAdapter::new(dense.inputs, dense.outputs, rank, alpha, &mut self.rng)?;
+        if let Layer::Dense(dense) = self.layer_mut(layer)? {
+            dense.adapter = Some(adapter);
+        }
+        self.reset_optimizer();
+        Ok(())
+    }
+
+    // Detach a layer's adapter, discarding its update; returns whether it had one
+    #[wasm_bindgen]
+    pub fn remove_adapter(&mut self, layer: usize) -> Result<bool, NeuralError> {
+        let removed = match self.layer_mut(layer)? {
+            Layer::Dense(dense) => dense.adapter.take().is_some(),
+            _ => false,
+        };
+        if removed {
+            self.reset_optimizer();
+        }
+        Ok(removed)
+    }
+
+    // Trainable parameters of every attached adapter
+    #[wasm_bindgen]
+    pub fn adapter_parameter_count(&self) -> usize {
+        self.adapters().map(|(_, adapter)| adapter.parameters.len()).sum()
+    }
+
+    // The attached adapters alone, without the base model (see lora.rs)
+    #[wasm_bindgen]
+    pub fn export_adapters(&self) -> Vec<u8> {
+        lora::encode(&self.adapters().collect::<Vec<_>>())
+    }
+
+    // Replace every adapter with those of an export_adapters blob, made for a
+    // network of this architecture
+    #[wasm_bindgen]
+    pub fn import_adapters(&mut self, bytes: &[u8]) -> Result<(), NeuralError> {
+        self.install_adapters(lora::decode(bytes)?)?;
+        self.reset_optimizer();
+        Ok(())
+    }
+
+    // Fold every adapter into its layer's weights and detach it; reduced-precision
+    // layers round the merged weights to their precision
+    #[wasm_bindgen]
+    pub fn merge_adapters(&mut self) {
+        let simd = self.simd_enabled;
+        let mut merged = false;
+        for dense in self.dense_layers_mut() {
+            if let Some(adapter) = dense.adapter.take() {
+                let weights = adapter.merged(&dense.dense_weights(), simd);
+                dense.store_weights(&weights);
+                merged = true;
+            }
+        }
+        if merged {
+            self.reset_optimizer();
+        }
+    }
+
+    // Run inference through every layer; recurrent layers start from zero state
+    // and their carried state is left untouched
+    #[wasm_bindgen]
+    pub fn forward(&self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.check_layers()?;
+        let activations = match self.arithmetic {
+            Arithmetic::F32 => {
+                let mut activations = self.prepare_inputs(inputs, 1)?.into_owned();
+                for (index, layer) in self.layers.iter().enumerate() {
+                    activations = layer.forward(&activations, self.simd_enabled)?;
+                    check_layer_outputs(index, layer.kind(), &activations)?;
+                }
+                self.apply_output_mode(&mut activations);
+                activations
+            }
+            Arithmetic::FixedQ16 => self.forward_fixed(inputs, 1, &mut |_, _| {})?,
+        };
+        self.recorder.record(|| Operation::Forward { inputs: inputs.to_vec() }, &activations);
+        Ok(activations)
+    }
+
+    // Run inference for many samples in one call; `inputs` is a row-major [batch_size × input_size]
+    // matrix and the result is [batch_size × output_size] in the same layout
+    #[wasm_bindgen]
+    pub fn forward_batch(&self, inputs: &[f32], batch_size: usize) -> Result<Vec<f32>, NeuralError> {
+        self.check_layers()?;
+        let activations = match self.arithmetic {
+            Arithmetic::F32 => {
+                let mut activations = self.prepare_inputs(inputs, batch_size)?.into_owned();
+                for (index, layer) in self.layers.iter().enumerate() {
+                    activations = layer.forward_batch(&activations, batch_size, self.simd_enabled)?;
+                    check_layer_outputs(index, layer.kind(), &activations)?;
+                }
+                self.apply_output_mode(&mut activations);
+                activations
+            }
+            Arithmetic::FixedQ16 => self.forward_fixed(inputs, batch_size, &mut |_, _| {})?,
+        };
+        self.recorder.record(|| Operation::ForwardBatch { inputs: inputs.to_vec(), batch_size }, &activations);
+        Ok(activations)
+    }
+
+    // Gradient × input attribution of the highest-scoring output for one sample:
+    // how far each input feature pushed that output up or down (see attribution.rs)
+    #[wasm_bindgen]
+    pub fn explain(&self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.explain_with(inputs, &AttributionConfig::default())
+    }
+
+    // Attribution by `config`'s method, output and baseline
+    #[wasm_bindgen]
+    pub fn explain_with(&self, inputs: &[f32], config: &AttributionConfig) -> Result<Vec<f32>, NeuralError> {
+        config.validate()?;
+        let inputs = self.prepare_inputs(inputs, 1)?;
+        attribution::attribute(&self.layers, &inputs, self.output_mode == OutputMode::Softmax, config, self.simd_enabled)
+    }
+
+    // Weight histograms and per-layer gradient norms of recent training steps as a
+    // binary buffer; see diagnostics.rs for the layout
+    #[wasm_bindgen]
+    pub fn diagnostics(&self) -> Vec<u8> {
+        let layers: Vec<LayerReport> = self.layers.iter().map(|layer| layer.report(None)).collect();
+        diagnostics::encode(&layers, 0, &self.gradient_trend)
+    }
+
+    // diagnostics() plus each layer's output histogram and dead units over a probe
+    // batch, a row-major [batch_size × input_size] matrix run in inference mode
+    #[wasm_bindgen]
+    pub fn diagnostics_with_probe(&self, inputs: &[f32], batch_size: usize) -> Result<Vec<u8>, NeuralError> {
+        let mut activations = self.prepare_inputs(inputs, batch_size)?.into_owned();
+        let mut layers = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            activations = layer.forward_batch(&activations, batch_size, self.simd_enabled)?;
+            layers.push(layer.report(Some(ActivationReport::of(&activations, layer.outputs()))));
+        }
+        let samples = u32::try_from(batch_size)
+            .map_err(|_| NeuralError::InvalidConfiguration("probe batch is too large".to_string()))?;
+        Ok(diagnostics::encode(&layers, samples, &self.gradient_trend))
+    }
+
+    // forward with a digest binding the outputs to the inputs and this exact model,
+    // for peers to check a claimed result (see attestation.rs)
+    #[wasm_bindgen]
+    pub fn compute_and_attest(&self, inputs: &[f32]) -> Result<Attestation, NeuralError> {
+        let outputs = self.forward(inputs)?;
+        Ok(Attestation::new(self.weights_digest_bytes(), self.arithmetic, inputs, outputs))
+    }
+
+    // Whether running `inputs` here reproduces a claimed attestation digest
+    #[wasm_bindgen]
+    pub fn verify_attestation(&self, inputs: &[f32], digest: &[u8]) -> Result<bool, NeuralError> {
+        Ok(self.compute_and_attest(inputs)?.digest() == digest)
+    }
+
+    // SHA-256 of export_weights() (followed by export_adapters() while adapters are
+    // attached), identifying the model in attestations
+    #[wasm_bindgen]
+    pub fn weights_digest(&self) -> Vec<u8> {
+        self.weights_digest_bytes().to_vec()
+    }
+
+    // Run one time step of a sequence: recurrent layers read and update the hidden
+    // state kept from the previous call
+    #[wasm_bindgen]
+    pub fn forward_step(&mut self, inputs: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.check_layers()?;
+        let activations = match self.arithmetic {
+            Arithmetic::F32 => {
+                let mut activations = self.prepare_inputs(inputs, 1)?.into_owned();
+                for (index, layer) in self.layers.iter_mut().enumerate() {
+                    activations = layer.step(&activations, self.simd_enabled)?;
+                    check_layer_outputs(index, layer.kind(), &activations)?;
+                }
+                self.apply_output_mode(&mut activations);
+                activations
+            }
+            // Fixed point has no recurrent layers, so a step is a stateless pass
+            Arithmetic::FixedQ16 => self.forward_fixed(inputs, 1, &mut |_, _| {})?,
+        };
+        self.recorder.record(|| Operation::ForwardStep { inputs: inputs.to_vec() }, &activations);
+        Ok(activations)
+    }
+
+    // Zero every recurrent layer's hidden state before starting a new sequence
+    #[wasm_bindgen]
+    pub fn reset_state(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.reset_state();
+        }
+        self.recorder.record(|| Operation::ResetState, &[]);
+    }
+
+    // Attach a fitted Preprocessor whose output width is the first layer's input
+    // size; from then on every entry point taking samples expects raw samples of
+    // its input width. Stored with the model by export_weights.
+    #[wasm_bindgen]
+    pub fn set_preprocessor(&mut self, preprocessor: &Preprocessor) -> Result<(), NeuralError> {
+        if !preprocessor.is_fitted() {
+            return Err(NeuralError::InvalidConfiguration("preprocessor must be fitted before use".to_string()));
+        }
+        if preprocessor.output_width() != self.input_size {
+            return Err(NeuralError::DimensionMismatch { expected: self.input_size, actual: preprocessor.output_width() });
+        }
+        self.preprocessor = Some(preprocessor.clone());
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_preprocessor(&mut self) {
+        self.preprocessor = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn preprocessor(&self) -> Option<Preprocessor> {
+        self.preprocessor.clone()
+    }
+
+    // Post-processing for inference outputs; training always sees the raw outputs
+    #[wasm_bindgen]
+    pub fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+    }
+
+    #[wasm_bindgen]
+    pub fn output_mode(&self) -> OutputMode {
+        self.output_mode
+    }
+
+    // Index of the highest-scoring output for one sample
+    #[wasm_bindgen]
+    pub fn classify(&self, inputs: &[f32]) -> Result<usize, NeuralError> {
+        self.predicted_class(&self.forward(inputs)?)
+    }
+
+    // Predicted class for every sample of a row-major [batch_size × input_size] batch
+    #[wasm_bindgen]
+    pub fn classify_batch(&self, inputs: &[f32], batch_size: usize) -> Result<Vec<u32>, NeuralError> {
+        let outputs = self.forward_batch(inputs, batch_size)?;
+        outputs
+            .chunks_exact(self.output_size())
+            .map(|row| self.predicted_class(row).map(|class| class as u32))
+            .collect()
+    }
+
+    // Storage precision for every layer, including layers added later. F16 halves
+    // weight memory; Int8 quarters it using parameters calibrated per layer.
+    #[wasm_bindgen]
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+        for layer in self.layers.iter_mut() {
+            layer.set_precision(precision);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    // Store every layer's weights as int8 with the given affine parameters
+    // (real = scale · (q - zero_point)); inference then runs on integer kernels
+    #[wasm_bindgen]
+    pub fn quantize_weights(&mut self, scale: f32, zero_point: i8) -> Result<(), NeuralError> {
+        let params = QuantParams::new(scale, zero_point)?;
+        self.precision = Precision::Int8;
+        for layer in self.layers.iter_mut() {
+            layer.quantize(params);
+        }
+        Ok(())
+    }
+
+    // Quantize with parameters fitted to each layer's own weight range
+    #[wasm_bindgen]
+    pub fn quantize_weights_calibrated(&mut self) {
+        self.set_precision(Precision::Int8);
+    }
+
+    // Return every layer to dense f32 storage; values keep their quantization error
+    #[wasm_bindgen]
+    pub fn dequantize_weights(&mut self) {
+        self.set_precision(Precision::F32);
+    }
+
+    #[wasm_bindgen]
+    pub fn is_quantized(&self) -> bool {
+        self.precision == Precision::Int8
+    }
+
+    // Bytes held by weight and bias storage
+    #[wasm_bindgen]
+    pub fn parameter_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weight_bytes() + std::mem::size_of_val(layer.biases()))
+            .sum()
+    }
+
+    // Estimated floating-point operations in one sample's inference pass; see
+    // describe_json for the per-layer breakdown
+    #[wasm_bindgen]
+    pub fn forward_flops(&self) -> usize {
+        let output_mode = match self.output_mode {
+            OutputMode::Raw => 0,
+            // Max, exponent, sum and divide
+            OutputMode::Softmax => 4 * self.output_size(),
+        };
+        self.layers.iter().map(Layer::forward_flops).sum::<usize>() + output_mode
+    }
+
+    // Architecture, parameter counts, cost and memory footprint as a parsed JSON
+    // object; see describe_json
+    #[wasm_bindgen]
+    pub fn describe(&self) -> Result<JsValue, NeuralError> {
+        js_sys::JSON::parse(&self.describe_json())
+            .map_err(|_| NeuralError::InvalidFormat("network description is not valid JSON".to_string()))
+    }
+
+    // {"input_size", "output_size", "output_mode", "precision", "parameters",
+    // "flops", "parameter_bytes", "optimizer_state_bytes", "activation_bytes",
+    // "memory_bytes", "layers": [{"type", "inputs", "outputs", "activation",
+    // "weights", "biases", "parameters", "bytes", "flops"}]}. Type, activation,
+    // output_mode and precision use the model spec names. flops is an estimate for
+    // one sample's inference pass. activation_bytes is the largest pair of adjacent
+    // activation buffers one sample needs; memory_bytes is parameter_bytes plus
+    // optimizer_state_bytes.
+    #[wasm_bindgen]
+    pub fn describe_json(&self) -> String {
+        let layers: Vec<String> = self.layers.iter().map(Layer::describe_json).collect();
+        let activation_bytes = self
+            .layers
+            .iter()
+            .map(|layer| (layer.inputs() + layer.outputs()) * std::mem::size_of::<f32>())
+            .max()
+            .unwrap_or(0);
+        let (parameter_bytes, optimizer_state_bytes) = (self.parameter_bytes(), self.optimizer_state_bytes());
+        format!(
+            "{{\"input_size\":{},\"output_size\":{},\"output_mode\":\"{}\",\"precision\":\"{}\",\"parameters\":{},\"flops\":{},\"parameter_bytes\":{},\"optimizer_state_bytes\":{},\"activation_bytes\":{},\"memory_bytes\":{},\"layers\":[{}]}}",
+            self.input_size,
+            self.output_size(),
+            self.output_mode.name(),
+            self.precision.name(),
+            self.parameter_count(),
+            self.forward_flops(),
+            parameter_bytes,
+            optimizer_state_bytes,
+            activation_bytes,
+            parameter_bytes + optimizer_state_bytes,
+            layers.join(",")
+        )
+    }
+
+    // Magnitude pruning: zero every dense-layer weight with |w| < threshold. Layers
+    // left at least half zero switch to sparse (CSR) storage and sparse kernels.
+    // Returns how many weights were zeroed.
+    #[wasm_bindgen]
+    pub fn prune(&mut self, threshold: f32) -> Result<usize, NeuralError> {
+        if !threshold.is_finite() || threshold < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("pruning threshold must be finite and non-negative".to_string()));
+        }
+        Ok(self.dense_layers_mut().map(|layer| layer.prune_where(|_, weight| weight.abs() < threshold)).sum())
+    }
+
+    // Zero the smallest-magnitude weights of each dense layer until `sparsity`
+    // (e.g. 0.8) of its weights are zero; returns how many weights were zeroed
+    #[wasm_bindgen]
+    pub fn prune_to_sparsity(&mut self, sparsity: f32) -> Result<usize, NeuralError> {
+        if !(0.0..=1.0).contains(&sparsity) {
+            return Err(NeuralError::InvalidConfiguration("sparsity must be between 0 and 1".to_string()));
+        }
+        let mut pruned = 0;
+        for layer in self.dense_layers_mut() {
+            let selected = {
+                let weights = layer.dense_weights();
+                let target = (sparsity * weights.len() as f32) as usize;
+                let mut selected = vec![false; weights.len()];
+                if target > 0 {
+                    let mut order: Vec<usize> = (0..weights.len()).collect();
+                    order.select_nth_unstable_by(target - 1, |&a, &b| weights[a].abs().total_cmp(&weights[b].abs()));
+                    for &index in &order[..target] {
+                        selected[index] = true;
+                    }
+                }
+                selected
+            };
+            pruned += layer.prune_where(|index, _| selected[index]);
+        }
+        Ok(pruned)
+    }
+
+    // Activity-based pruning over a calibration batch of row-major inputs
+    // [batch_size × input_size]: zero each dense-layer weight whose mean contribution
+    // |w| · mean|input| stays below `threshold`. Returns how many weights were zeroed.
+    #[wasm_bindgen]
+    pub fn prune_by_activity(&mut self, inputs: &[f32], batch_size: usize, threshold: f32) -> Result<usize, NeuralError> {
+        if batch_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        if !threshold.is_finite() || threshold < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("pruning threshold must be finite and non-negative".to_string()));
+        }
+        let inputs = self.prepare_inputs(inputs, batch_size)?;
+
+        // Mean absolute value of every layer input over the batch
+        let mut activity: Vec<Vec<f32>> = self.layers.iter().map(|layer| vec![0.0; layer.inputs()]).collect();
+        for sample in inputs.chunks_exact(self.input_size.max(1)) {
+            let mut activations = sample.to_vec();
+            for (layer, mean) in self.layers.iter().zip(activity.iter_mut()) {
+                for (mean, activation) in mean.iter_mut().zip(&activations) {
+                    *mean += activation.abs() / batch_size as f32;
+                }
+                activations = layer.forward(&activations, self.simd_enabled)?;
+            }
+        }
+
+        let mut pruned = 0;
+        for (layer, mean) in self.layers.iter_mut().zip(&activity) {
+            if let Layer::Dense(layer) = layer {
+                let inputs = layer.inputs.max(1);
+                pruned += layer.prune_where(|index, weight| (weight * mean[index % inputs]).abs() < threshold);
+            }
+        }
+        Ok(pruned)
+    }
+
+    // Fraction of dense-layer weights that are zero
+    #[wasm_bindgen]
+    pub fn sparsity(&self) -> f32 {
+        let (zeros, total) = self
+            .layers
+            .iter()
+            .filter_map(Layer::as_dense)
+            .fold((0, 0), |(zeros, total), layer| (zeros + layer.zero_weights(), total + layer.inputs * layer.outputs));
+        if total == 0 {
+            0.0
+        } else {
+            zeros as f32 / total as f32
+        }
+    }
+
+    // Update rule for train_batch and apply_gradients (SGD by default). Any moments
+    // kept by the previous optimizer are discarded, even if the config is unchanged.
+    #[wasm_bindgen]
+    pub fn set_optimizer(&mut self, config: &GradientOptimizerConfig) -> Result<(), NeuralError> {
+        config.validate()?;
+        self.optimizer = OptimizerState::new(*config);
+        let config = *config;
+        self.recorder.record(|| Operation::SetOptimizer { config }, &[]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn optimizer(&self) -> GradientOptimizerConfig {
+        self.optimizer.config()
+    }
+
+    // Bytes the optimizer's per-parameter moments occupy; 0 for SGD
+    #[wasm_bindgen]
+    pub fn optimizer_state_bytes(&self) -> usize {
+        self.optimizer.memory_bytes()
+    }
+
+    // Gradient clipping and non-finite rollback for train_batch and apply_gradients;
+    // see training.rs. Rollback is on and clipping off by default.
+    #[wasm_bindgen]
+    pub fn set_training_safety(&mut self, safety: &TrainingSafety) -> Result<(), NeuralError> {
+        safety.validate()?;
+        self.safety = *safety;
+        let safety = *safety;
+        self.recorder.record(|| Operation::SetTrainingSafety { safety }, &[]);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn training_safety(&self) -> TrainingSafety {
+        self.safety
+    }
+
+    // The most recent rolled-back step, if any
+    #[wasm_bindgen]
+    pub fn last_training_fault(&self) -> Option<TrainingFault> {
+        self.last_fault
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_training_fault(&mut self) {
+        self.last_fault = None;
+    }
+
+    // Global gradient norm of the latest step, before clipping
+    #[wasm_bindgen(getter)]
+    pub fn last_gradient_norm(&self) -> f32 {
+        self.last_gradient_norm
+    }
+
+    // Arithmetic for train_batch (and so train_epoch and fit): F32 by default, or
+    // Mixed for f16 compute with f32 master weights; see mixed_precision.rs.
+    // Switching restarts the loss scale.
+    #[wasm_bindgen]
+    pub fn set_training_precision(&mut self, precision: TrainingPrecision) {
+        self.training_precision = precision;
+        self.loss_scaler = LossScaler::default();
+        self.recorder.record(|| Operation::SetTrainingPrecision { precision }, &[]);
+    }
+
+    #[wasm_bindgen]
+    pub fn training_precision(&self) -> TrainingPrecision {
+        self.training_precision
+    }
+
+    // Number format of forward, forward_batch and forward_step: F32 by default, or
+    // FixedQ16 for results that are bit-identical across platforms (see fixed_point.rs)
+    #[wasm_bindgen]
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.arithmetic = arithmetic;
+        self.recorder.record(|| Operation::SetArithmetic { arithmetic }, &[]);
+    }
+
+    #[wasm_bindgen]
+    pub fn arithmetic(&self) -> Arithmetic {
+        self.arithmetic
+    }
+
+    // Current dynamic loss scale of mixed-precision training
+    #[wasm_bindgen(getter)]
+    pub fn loss_scale(&self) -> f32 {
+        self.loss_scaler.scale
+    }
+
+    // Loss minimized by train_batch, fit and compute_gradients (mean squared error by default)
+    #[wasm_bindgen]
+    pub fn set_loss(&mut self, loss: &LossFunction) -> Result<(), NeuralError> {
+        loss.validate()?;
+        self.loss = Loss::Builtin(*loss);
+        let loss = Some(*loss);
+        self.recorder.record(|| Operation::SetLoss { loss }, &[]);
+        Ok(())
+    }
+
+    // Train with `callback(predicted, target)` returning [loss, ...dL/dy] for each sample;
+    // see loss.rs. Traces that use a JavaScript loss cannot be replayed.
+    #[wasm_bindgen]
+    pub fn set_custom_loss(&mut self, callback: js_sys::Function) {
+        self.loss = Loss::Custom(callback);
+        self.recorder.record(|| Operation::SetLoss { loss: None }, &[]);
+    }
+
+    // The built-in loss in use, or undefined for a JavaScript one
+    #[wasm_bindgen]
+    pub fn loss_function(&self) -> Option<LossFunction> {
+        match &self.loss {
+            Loss::Builtin(function) => Some(*function),
+            Loss::Custom(_) | Loss::Distillation(_) => None,
+        }
+    }
+
+    // Mean loss over a row-major batch in inference mode; parameters and state are untouched
+    #[wasm_bindgen]
+    pub fn evaluate(&self, inputs: &[f32], targets: &[f32], batch_size: usize) -> Result<f32, NeuralError> {
+        let predicted = self.forward_batch(inputs, batch_size)?;
+        if targets.len() != predicted.len() {
+            return Err(NeuralError::DimensionMismatch { expected: predicted.len(), actual: targets.len() });
+        }
+        let output_size = predicted.len() / batch_size;
+        let mut grad = vec![0.0; output_size];
+        let mut total = 0.0;
+        for (y, t) in predicted.chunks_exact(output_size).zip(targets.chunks_exact(output_size)) {
+            total += self.loss.sample(y, t, &mut grad)?;
+        }
+        Ok(total / batch_size as f32)
+    }
+
+    // One optimizer step on the network's loss over a row-major batch of
+    // inputs [batch_size × input_size] and targets [batch_size × output_size];
+    // returns the batch's mean loss. Requires dense layers with dense f32 weights,
+    // optionally interleaved with dropout and normalization layers.
+    #[wasm_bindgen]
+    pub fn train_batch(&mut self, inputs: &[f32], targets: &[f32], batch_size: usize, learning_rate: f32) -> Result<f32, NeuralError> {
+        let prepared = self.prepare_inputs(inputs, batch_size)?;
+        training::check_learning_rate(learning_rate)?;
+        let backup = self.safety_backup();
+        let (mut gradients, loss) = match self.training_precision {
+            TrainingPrecision::F32 => {
+                training::compute_gradients(&mut self.layers, &prepared, targets, batch_size, self.simd_enabled, &mut self.rng, &self.loss)?
+            }
+            TrainingPrecision::Mixed => {
+                let batch = (&prepared[..], targets, batch_size);
+                let scale = self.loss_scaler.scale;
+                mixed_precision::compute_gradients(&mut self.layers, batch, self.simd_enabled, &mut self.rng, &self.loss, scale)?
+            }
+        };
+        let mixed = self.training_precision == TrainingPrecision::Mixed;
+        if mixed && loss.is_finite() && !training::gradient_norm(&gradients).is_finite() && self.loss_scaler.can_back_off() {
+            // Scaled f16 gradients overflowed: skip the step and retry later at half the scale
+            self.loss_scaler.back_off();
+            log_event!(LogLevel::Debug, "training", "f16 gradient overflow, step skipped; loss scale now {}", self.loss_scaler.scale);
+        } else {
+            self.safe_step(&mut gradients, loss, learning_rate, backup)?;
+            if mixed {
+                self.loss_scaler.record_good_step();
+            }
+        }
+        self.recorder.record(
+            || Operation::TrainBatch { inputs: inputs.to_vec(), targets: targets.to_vec(), batch_size, learning_rate },
+            &[loss],
+        );
+        Ok(loss)
+    }
+
+    // Gradients of the mean loss over one batch, serialized for another agent or a
+    // coordinator. Parameters are left unchanged, but the pass runs in training mode:
+    // dropout draws from the network's RNG and batch norm updates its running statistics.
+    #[wasm_bindgen]
+    pub fn compute_gradients(&mut self, inputs: &[f32], targets: &[f32], batch_size: usize) -> Result<Vec<u8>, NeuralError> {
+        let inputs = self.prepare_inputs(inputs, batch_size)?;
+        let samples = u32::try_from(batch_size)
+            .map_err(|_| NeuralError::InvalidConfiguration("batch size exceeds u32".to_string()))?;
+        let (layers, loss) = training::compute_gradients(&mut self.layers, &inputs, targets, batch_size, self.simd_enabled, &mut self.rng, &self.loss)?;
+        Ok(GradientSet { samples, loss, layers }.encode())
+    }
+
+    // Take one optimizer step with a blob from compute_gradients or a GradientAggregator
+    #[wasm_bindgen]
+    pub fn apply_gradients(&mut self, blob: &[u8], learning_rate: f32) -> Result<(), NeuralError> {
+        let mut gradients = GradientSet::decode(blob)?;
+        let backup = self.safety_backup();
+        self.safe_step(&mut gradients.layers, gradients.loss, learning_rate, backup)
+    }
+
+    // One pass over a dataset in mini-batches of `batch_size` (the last may be smaller);
+    // returns the mean loss over all samples
+    #[wasm_bindgen]
+    pub fn train_epoch(&mut self, inputs: &[f32], targets: &[f32], batch_size: usize, learning_rate: f32) -> Result<f32, NeuralError> {
+        let mut batches = self.batches(inputs, targets, batch_size)?;
+        let mut total = 0.0;
+        for (batch_inputs, batch_targets, samples) in batches.by_ref() {
+            total += self.train_batch(batch_inputs, batch_targets, samples, learning_rate)? * samples as f32;
+        }
+        Ok(total / batches.sample_count as f32)
+    }
+
+    // One pass over a Dataset in mini-batches gathered inside WASM, reshuffled first
+    // if `shuffle` is set; returns the mean loss over all samples
+    #[wasm_bindgen]
+    pub fn train_dataset_epoch(
+        &mut self,
+        dataset: &mut Dataset,
+        batch_size: usize,
+        learning_rate: f32,
+        shuffle: bool,
+    ) -> Result<f32, NeuralError> {
+        let batches = self.check_dataset(dataset, batch_size)?;
+        if shuffle {
+            dataset.shuffle();
+        }
+        let (mut inputs, mut targets) = (Vec::new(), Vec::new());
+        let mut total = 0.0;
+        for index in 0..batches {
+            let samples = dataset.gather(index, batch_size, &mut inputs, &mut targets);
+            total += self.train_batch(&inputs, &targets, samples, learning_rate)? * samples as f32;
+        }
+        Ok(total / dataset.len() as f32)
+    }
+
+    // fit() over a Dataset; with options.shuffle it is reshuffled before every epoch
+    #[wasm_bindgen]
+    pub fn fit_dataset(
+        &mut self,
+        dataset: &mut Dataset,
+        options: &FitOptions,
+        on_epoch: Option<js_sys::Function>,
+    ) -> Result<FitReport, NeuralError> {
+        let mut progress = FitProgress::new(options)?;
+        self.check_dataset(dataset, options.batch_size)?;
+        let mut best = None;
+        while let Some(rate) = progress.next_rate() {
+            let loss = self.train_dataset_epoch(dataset, options.batch_size, rate, options.shuffle)?;
+            if progress.record(loss, rate, on_epoch.as_ref())? && progress.restore_best() {
+                best = Some(self.layers.clone());
+            }
+        }
+        Ok(self.finish_fit(progress, best))
+    }
+
+    // fit_dataset() with `loss` in place of the network's own loss for the run
+    pub(crate) fn fit_dataset_with_loss(
+        &mut self,
+        dataset: &mut Dataset,
+        options: &FitOptions,
+        on_epoch: Option<js_sys::Function>,
+        loss: Loss,
+    ) -> NeuralResult<FitReport> {
+        let previous = std::mem::replace(&mut self.loss, loss);
+        let report = self.fit_dataset(dataset, options, on_epoch);
+        self.loss = previous;
+        report
+    }
+
+    // Mean loss over a Dataset in inference mode, in its current order
+    #[wasm_bindgen]
+    pub fn evaluate_dataset(&self, dataset: &Dataset, batch_size: usize) -> Result<f32, NeuralError> {
+        let batches = self.check_dataset(dataset, batch_size)?;
+        let (mut inputs, mut targets) = (Vec::new(), Vec::new());
+        let mut total = 0.0;
+        for index in 0..batches {
+            let samples = dataset.gather(index, batch_size, &mut inputs, &mut targets);
+            total += self.evaluate(&inputs, &targets, samples)? * samples as f32;
+        }
+        Ok(total / dataset.len() as f32)
+    }
+
+    // Train for `epochs` epochs without blocking the page: the work yields to the event
+    // loop every few milliseconds and stops with a Cancelled error once `token` is
+    // cancelled. Trains a copy, so this network stays usable meanwhile; the Promise
+    // resolves to a TrainingOutcome holding the trained copy and per-epoch losses.
+    #[wasm_bindgen]
+    pub fn train_async(
+        &self,
+        inputs: Vec<f32>,
+        targets: Vec<f32>,
+        epochs: usize,
+        batch_size: usize,
+        learning_rate: f32,
+        token: &CancellationToken,
+    ) -> js_sys::Promise {
+        let mut network = self.clone();
+        let token = token.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let mut yielder = Yielder::new(DEFAULT_SLICE_MS);
+            let mut losses = Vec::with_capacity(epochs);
+            for _ in 0..epochs {
+                let mut batches = network.batches(&inputs, &targets, batch_size)?;
+                let mut total = 0.0;
+                for (batch_inputs, batch_targets, samples) in batches.by_ref() {
+                    yielder.checkpoint(&token).await?;
+                    total += network.train_batch(batch_inputs, batch_targets, samples, learning_rate)? * samples as f32;
+                }
+                let loss = total / batches.sample_count as f32;
+                log_event!(LogLevel::Debug, "training", "epoch {} loss {}", losses.len() + 1, loss);
+                losses.push(loss);
+            }
+            Ok(TrainingOutcome::new(network, losses).into())
+        })
+    }
+
+    // Train epoch by epoch under `options` (schedule, early stopping, best-weight
+    // restore). `on_epoch(epoch, loss, learning_rate)` runs after every epoch and
+    // stops training by returning false.
+    #[wasm_bindgen]
+    pub fn fit(
+        &mut self,
+        inputs: &[f32],
+        targets: &[f32],
+        options: &FitOptions,
+        on_epoch: Option<js_sys::Function>,
+    ) -> Result<FitReport, NeuralError> {
+        let mut progress = FitProgress::new(options)?;
+        self.batches(inputs, targets, options.batch_size)?;
+        let mut best = None;
+        while let Some(rate) = progress.next_rate() {
+            let loss = self.train_epoch(inputs, targets, options.batch_size, rate)?;
+            if progress.record(loss, rate, on_epoch.as_ref())? && progress.restore_best() {
+                best = Some(self.layers.clone());
+            }
+        }
+        Ok(self.finish_fit(progress, best))
+    }
+
+    // fit() without blocking the page, yielding and cancelling like train_async. Trains
+    // a copy; the Promise resolves to a TrainingOutcome whose report is set.
+    #[wasm_bindgen]
+    pub fn fit_async(
+        &self,
+        inputs: Vec<f32>,
+        targets: Vec<f32>,
+        options: &FitOptions,
+        on_epoch: Option<js_sys::Function>,
+        token: &CancellationToken,
+    ) -> js_sys::Promise {
+        let mut network = self.clone();
+        let options = *options;
+        let token = token.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let mut progress = FitProgress::new(&options)?;
+            let mut yielder = Yielder::new(DEFAULT_SLICE_MS);
+            let mut best = None;
+            while let Some(rate) = progress.next_rate() {
+                let mut batches = network.batches(&inputs, &targets, options.batch_size)?;
+                let mut total = 0.0;
+                for (batch_inputs, batch_targets, samples) in batches.by_ref() {
+                    yielder.checkpoint(&token).await?;
+                    total += network.train_batch(batch_inputs, batch_targets, samples, rate)? * samples as f32;
+                }
+                let loss = total / batches.sample_count as f32;
+                log_event!(LogLevel::Debug, "training", "epoch {} loss {} rate {}", progress.epochs_run() + 1, loss, rate);
+                if progress.record(loss, rate, on_epoch.as_ref())? && progress.restore_best() {
+                    best = Some(network.layers.clone());
+                }
+            }
+            let report = network.finish_fit(progress, best);
+            Ok(TrainingOutcome::with_report(network, report).into())
+        })
+    }
+
+    // Serialize architecture and parameters to the versioned SASW binary format
+    #[wasm_bindgen]
+    pub fn export_weights(&self) -> Vec<u8> {
+        serialization::encode_weights(self.input_size, &self.layers, self.preprocessor.as_ref(), WeightEncoding::F32)
+    }
+
+    // Same layout with 8-bit quantized tensors, roughly 4x smaller
+    #[wasm_bindgen]
+    pub fn export_weights_quantized(&self) -> Vec<u8> {
+        serialization::encode_weights(self.input_size, &self.layers, self.preprocessor.as_ref(), WeightEncoding::Quantized8)
+    }
+
+    // export_weights sealed with AES-256-GCM under a 32-byte key (see encryption.rs)
+    #[wasm_bindgen]
+    pub fn export_weights_encrypted(&self, key: &[u8]) -> Result<Vec<u8>, NeuralError> {
+        encryption::seal(&self.export_weights(), key)
+    }
+
+    // Load parameters into this network; the blob's architecture must match exactly.
+    // A preprocessor stored in the blob replaces this network's; a blob without one
+    // leaves it in place. LoRA adapters stay attached on top of the new base weights.
+    #[wasm_bindgen]
+    pub fn import_weights(&mut self, bytes: &[u8]) -> Result<(), NeuralError> {
+        let mut decoded = serialization::decode_weights(bytes)?;
+
+        let same_shape = decoded.input_size == self.input_size
+            && decoded.layers.len() == self.layers.len()
+            && decoded.layers.iter().zip(&self.layers).all(|(new, old)| new.shape() == old.shape());
+        if !same_shape {
+            return Err(NeuralError::InvalidConfiguration("serialized architecture does not match this network".to_string()));
+        }
+
+        for (new, old) in decoded.layers.iter_mut().zip(self.layers.iter_mut()) {
+            if let (Layer::Dense(new), Layer::Dense(old)) = (new, old) {
+                new.adapter = old.adapter.take();
+            }
+        }
+        self.layers = decoded.layers;
+        for layer in self.layers.iter_mut() {
+            layer.set_precision(self.precision);
+        }
+        if decoded.preprocessor.is_some() {
+            self.preprocessor = decoded.preprocessor;
+        }
+        Ok(())
+    }
+
+    // Rebuild a complete network from a serialized blob
+    #[wasm_bindgen]
+    pub fn from_weights(bytes: &[u8]) -> Result<NeuralNetwork, NeuralError> {
+        let decoded = serialization::decode_weights(bytes)?;
+        let mut network = NeuralNetwork::new(decoded.input_size)?;
+        network.layers = decoded.layers;
+        network.preprocessor = decoded.preprocessor;
+        Ok(network)
+    }
+
+    // import_weights for a blob from export_weights_encrypted; a wrong key or a
+    // modified blob fails authentication and leaves the network unchanged
+    #[wasm_bindgen]
+    pub fn import_weights_encrypted(&mut self, blob: &[u8], key: &[u8]) -> Result<(), NeuralError> {
+        let bytes = encryption::open(blob, key)?;
+        self.import_weights(&bytes)
+    }
+
+    // from_weights for blobs from untrusted sources: the blob must pass `check`
+    // (see integrity.rs) before it is decoded
+    #[wasm_bindgen]
+    pub fn from_weights_verified(bytes: &[u8], check: &IntegrityCheck) -> Result<NeuralNetwork, NeuralError> {
+        check.verify(bytes)?;
+        NeuralNetwork::from_weights(bytes)
+    }
+
+    // import_weights, refusing a blob that fails `check`
+    #[wasm_bindgen]
+    pub fn import_weights_verified(&mut self, bytes: &[u8], check: &IntegrityCheck) -> Result<(), NeuralError> {
+        check.verify(bytes)?;
+        self.import_weights(bytes)
+    }
+
+    // Load a network saved by FANN (`fann_save`) in the FANN_FLO_2.1 text format
+    #[wasm_bindgen]
+    pub fn from_fann(text: &str) -> Result<NeuralNetwork, NeuralError> {
+        fann_format::parse_fann(text)
+    }
+
+    // Load a feed-forward model exported to ONNX (e.g. torch.onnx.export of an MLP);
+    // see onnx_format.rs for the supported operators
+    #[wasm_bindgen]
+    pub fn from_onnx(bytes: &[u8]) -> Result<NeuralNetwork, NeuralError> {
+        onnx_format::parse_onnx(bytes)
+    }
+
+    // Build a network from a JSON architecture spec; see model_spec.rs for the schema
+    #[wasm_bindgen]
+    pub fn from_json(spec: &str) -> Result<NeuralNetwork, NeuralError> {
+        model_spec::parse_model_spec(spec)
+    }
+
+    // Set a layer's weights from an .npy buffer (np.save). Dense weights must be
+    // [out][in] as in PyTorch; save a Keras kernel as kernel.T
+    #[wasm_bindgen]
+    pub fn set_weights_npy(&mut self, layer: usize, bytes: &[u8]) -> Result<(), NeuralError> {
+        let weights = npy_format::layer_weights(self, layer, &npy_format::parse_npy(bytes)?, false)?;
+        self.set_weights(layer, &weights)
+    }
+
+    #[wasm_bindgen]
+    pub fn set_biases_npy(&mut self, layer: usize, bytes: &[u8]) -> Result<(), NeuralError> {
+        let biases = npy_format::layer_biases(layer, &npy_format::parse_npy(bytes)?)?;
+        self.set_biases(layer, &biases)
+    }
+
+    // Load all layers from an .npz archive (np.savez of a PyTorch state_dict or Keras
+    // weights), naming layers in order with comma-separated `layer_names`; see
+    // npy_format.rs for the accepted array names
+    #[wasm_bindgen]
+    pub fn load_npz(&mut self, bytes: &[u8], layer_names: &str) -> Result<(), NeuralError> {
+        npy_format::load_npz(self, bytes, layer_names)
+    }
+
+    // Write the network as a FANN_FLO_2.1 `.net` file loadable by `fann_create_from_file`
+    #[wasm_bindgen]
+    pub fn to_fann(&self) -> Result<String, NeuralError> {
+        fann_format::write_fann(self.input_size, &self.layers)
+    }
+
+    // Begin a deterministic replay trace from the current state, discarding any
+    // trace in progress; see replay.rs for what is recorded
+    #[wasm_bindgen]
+    pub fn start_recording(&mut self) {
+        self.recorder.start(self.export_weights(), self.execution_state());
+    }
+
+    // Stop logging operations; the trace so far can still be exported
+    #[wasm_bindgen]
+    pub fn stop_recording(&mut self) {
+        self.recorder.stop();
+    }
+
+    #[wasm_bindgen]
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
+    // The trace as a binary blob, whether or not recording has been stopped
+    #[wasm_bindgen]
+    pub fn export_trace(&self) -> Result<Vec<u8>, NeuralError> {
+        self.recorder.export()
+    }
+
+    // Re-run a trace from its recorded starting state and compare every output bit for bit
+    #[wasm_bindgen]
+    pub fn replay(trace: &[u8]) -> Result<ReplayReport, NeuralError> {
+        replay::replay(trace)
+    }
+
+    // Values per raw sample: the preprocessor's input width when one is attached,
+    // otherwise model_input_size()
+    #[wasm_bindgen]
+    pub fn input_size(&self) -> usize {
+        self.preprocessor.as_ref().map_or(self.input_size, Preprocessor::input_width)
+    }
+
+    // Values the first layer reads, after preprocessing
+    #[wasm_bindgen]
+    pub fn model_input_size(&self) -> usize {
+        self.input_size
+    }
+
+    #[wasm_bindgen]
+    pub fn output_size(&self) -> usize {
+        self.layers.last().map_or(self.input_size, |layer| layer.outputs())
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_size(&self, layer: usize) -> Result<usize, NeuralError> {
+        Ok(self.layer(layer)?.outputs())
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_activation(&self, layer: usize) -> Result<ActivationKind, NeuralError> {
+        Ok(self.layer(layer)?.activation())
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_kind(&self, layer: usize) -> Result<LayerKind, NeuralError> {
+        Ok(self.layer(layer)?.kind())
+    }
+}
+
+impl NeuralNetwork {
+    // Read inputs from the front of `buffer` and overwrite it with the outputs,
+    // taking intermediate buffers from `scratch`
+    pub(crate) fn forward_in_place(
+        &self,
+        buffer: &mut [f32],
+        scratch: &mut ScratchAllocator,
+        observe: &mut dyn FnMut(usize, &[f32]),
+    ) -> NeuralResult<()> {
+        let required = self.input_size().max(self.output_size());
+        if buffer.len() < required {
+            return Err(NeuralError::DimensionMismatch { expected: required, actual: buffer.len() });
+        }
+        let outputs = self.forward_with_scratch(&buffer[..self.input_size()], 1, scratch, observe)?;
+        buffer[..outputs.len()].copy_from_slice(&outputs);
+        scratch.give((1, outputs.len()), outputs);
+        Ok(())
+    }
+
+    // forward (batch_size 1) or forward_batch with every buffer taken from `scratch`;
+    // intermediates go back to it, the returned outputs belong to the caller.
+    // `observe` sees each layer's outputs (layer index, batch_size rows) as they are made.
+    pub(crate) fn forward_with_scratch(
+        &self,
+        inputs: &[f32],
+        batch_size: usize,
+        scratch: &mut ScratchAllocator,
+        observe: &mut dyn FnMut(usize, &[f32]),
+    ) -> NeuralResult<Vec<f32>> {
+        let activations = match self.arithmetic {
+            Arithmetic::F32 => self.scratch_pass(inputs, batch_size, scratch, observe)?,
+            Arithmetic::FixedQ16 => self.forward_fixed(inputs, batch_size, observe)?,
+        };
+        match batch_size {
+            1 => self.recorder.record(|| Operation::Forward { inputs: inputs.to_vec() }, &activations),
+            _ => self.recorder.record(|| Operation::ForwardBatch { inputs: inputs.to_vec(), batch_size }, &activations),
+        }
+        Ok(activations)
+    }
+
+    fn scratch_pass(
+        &self,
+        inputs: &[f32],
+        batch_size: usize,
+        scratch: &mut ScratchAllocator,
+        observe: &mut dyn FnMut(usize, &[f32]),
+    ) -> NeuralResult<Vec<f32>> {
+        let prepared = self.prepare_inputs(inputs, batch_size)?;
+        let mut shape = (batch_size, self.input_size);
+        let mut activations = scratch.take(shape);
+        activations.copy_from_slice(&prepared);
+        for (index, layer) in self.layers.iter().enumerate() {
+            let next_shape = (batch_size, layer.outputs());
+            let mut next = scratch.take(next_shape);
+            let result = match batch_size {
+                1 => layer.forward_into(&activations, &mut next, self.simd_enabled),
+                _ => layer.forward_batch_into(&activations, batch_size, &mut next, self.simd_enabled),
+            };
+            scratch.give(shape, std::mem::replace(&mut activations, next));
+            shape = next_shape;
+            if let Err(err) = result {
+                scratch.give(shape, activations);
+                return Err(err);
+            }
+            observe(index, &activations);
+        }
+        self.apply_output_mode(&mut activations);
+        Ok(activations)
+    }
+
+    fn weights_digest_bytes(&self) -> [u8; SHA256_LEN] {
+        let mut model = self.export_weights();
+        if self.adapters().next().is_some() {
+            model.extend_from_slice(&self.export_adapters());
+        }
+        integrity::sha256_digest(&model)
+    }
+
+    // Q16.16 inference pass with the output mode applied; see fixed_point.rs
+    fn forward_fixed(&self, inputs: &[f32], batch_size: usize, observe: &mut dyn FnMut(usize, &[f32])) -> NeuralResult<Vec<f32>> {
+        let prepared = self.prepare_inputs(inputs, batch_size)?;
+        let mut activations: Vec<i32> = prepared.iter().map(|&value| fixed_point::from_f32(value)).collect();
+        for (index, layer) in self.layers.iter().enumerate() {
+            activations = fixed_point::forward_layer(layer, &activations, batch_size)?;
+            observe(index, &activations.iter().map(|&value| fixed_point::to_f32(value)).collect::<Vec<f32>>());
+        }
+        if self.output_mode == OutputMode::Softmax {
+            for row in activations.chunks_exact_mut(self.output_size()) {
+                fixed_point::apply_activation(ActivationKind::Softmax, row);
+            }
+        }
+        Ok(activations.iter().map(|&value| fixed_point::to_f32(value)).collect())
+    }
+
+    // Sum over the batch of every sample's gradients clipped to L2 norm `clip_norm`,
+    // for differentially private release (privacy.rs)
+    pub(crate) fn clipped_gradient_sum(&mut self, inputs: &[f32], targets: &[f32], batch_size: usize, clip_norm: f32) -> NeuralResult<Vec<training::LayerGradients>> {
+        if self.layers.iter().any(|layer| layer.kind() == LayerKind::BatchNorm) {
+            return Err(NeuralError::InvalidConfiguration("batch norm couples the samples of a batch, so per-sample gradients cannot be clipped".to_string()));
+        }
+        let inputs = self.prepare_inputs(inputs, batch_size)?;
+        let output_size = self.output_size();
+        let expected = batch_size
+            .checked_mul(output_size)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("batch size overflows".to_string()))?;
+        if targets.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: targets.len() });
+        }
+
+        let mut sum: Option<Vec<training::LayerGradients>> = None;
+        for (input, target) in inputs.chunks_exact(self.input_size).zip(targets.chunks_exact(output_size)) {
+            let (mut gradients, _) = training::compute_gradients(&mut self.layers, input, target, 1, self.simd_enabled, &mut self.rng, &self.loss)?;
+            let norm = training::gradient_norm(&gradients);
+            if !norm.is_finite() {
+                return Err(NeuralError::NonFiniteTraining { stage: "gradients".to_string(), layer: None });
+            }
+            training::clip_gradients(&mut gradients, norm, clip_norm);
+            match sum.as_mut() {
+                None => sum = Some(gradients),
+                Some(sum) => {
+                    for (total, layer) in sum.iter_mut().zip(&gradients) {
+                        for (acc, grad) in total.weights.iter_mut().zip(&layer.weights).chain(total.biases.iter_mut().zip(&layer.biases)) {
+                            *acc += grad;
+                        }
+                    }
+                }
+            }
+        }
+        sum.ok_or_else(|| NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()))
+    }
+
+    // Validate row-major raw samples [rows × input_size()] and run them through the
+    // preprocessor, if any, giving the first layer's inputs
+    pub(crate) fn prepare_inputs<'a>(&self, inputs: &'a [f32], rows: usize) -> NeuralResult<Cow<'a, [f32]>> {
+        if let Some(preprocessor) = &self.preprocessor {
+            return Ok(Cow::Owned(preprocessor.transform(inputs, rows)?));
+        }
+        if rows == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        let expected = rows
+            .checked_mul(self.input_size)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("batch size overflows".to_string()))?;
+        if inputs.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: inputs.len() });
+        }
+        if let Some(index) = inputs.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(Cow::Borrowed(inputs))
+    }
+
+    // Checked mode: every layer's parameters agree with its shape and each layer
+    // reads the previous layer's outputs
+    fn check_layers(&self) -> NeuralResult<()> {
+        if !checked::enabled() {
+            return Ok(());
+        }
+        let mut width = self.input_size;
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.inputs() != width || !layer.parameters_match_shape() {
+                return Err(NeuralError::KernelCheckFailed {
+                    kernel: "forward".to_string(),
+                    reason: format!("layer {} does not match its shape", index),
+                });
+            }
+            width = layer.outputs();
+        }
+        Ok(())
+    }
+
+    // Stateless pass through one layer, for callers that walk the layers themselves
+    pub(crate) fn forward_layer(&self, layer: usize, inputs: &[f32]) -> NeuralResult<Vec<f32>> {
+        self.layer(layer)?.forward(inputs, self.simd_enabled)
+    }
+
+    // Activation of the last layer, before the output mode
+    pub(crate) fn final_activation(&self) -> Option<ActivationKind> {
+        self.layers.last().map(Layer::activation)
+    }
+
+    fn predicted_class(&self, outputs: &[f32]) -> NeuralResult<usize> {
+        activation::argmax_index(outputs, self.simd_enabled)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("network produced non-finite outputs".to_string()))
+    }
+
+    // Apply the output mode to each sample of a row-major output batch
+    pub(crate) fn apply_output_mode(&self, outputs: &mut [f32]) {
+        if self.output_mode == OutputMode::Softmax {
+            for row in outputs.chunks_exact_mut(self.output_size()) {
+                ActivationKind::Softmax.apply_slice(row, self.simd_enabled);
+            }
+        }
+    }
+
+    // Split a dataset into consecutive mini-batches after validating its shape
+    fn batches<'a>(&self, inputs: &'a [f32], targets: &'a [f32], batch_size: usize) -> NeuralResult<Batches<'a>> {
+        if self.layers.is_empty() {
+            return Err(NeuralError::InvalidConfiguration("network has no layers to train".to_string()));
+        }
+        if batch_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        let input_size = self.input_size();
+        if inputs.is_empty() || !inputs.len().is_multiple_of(input_size) {
+            return Err(NeuralError::InvalidConfiguration("inputs must hold a whole number of samples".to_string()));
+        }
+        let sample_count = inputs.len() / input_size;
+        let output_size = self.output_size();
+        if targets.len() != sample_count * output_size {
+            return Err(NeuralError::DimensionMismatch { expected: sample_count * output_size, actual: targets.len() });
+        }
+        Ok(Batches {
+            inputs: inputs.chunks(batch_size * input_size),
+            targets: targets.chunks(batch_size * output_size),
+            input_size,
+            sample_count,
+        })
+    }
+
+    // Check that a Dataset's samples fit the network; returns its batch count
+    fn check_dataset(&self, dataset: &Dataset, batch_size: usize) -> NeuralResult<usize> {
+        if self.layers.is_empty() {
+            return Err(NeuralError::InvalidConfiguration("network has no layers to train".to_string()));
+        }
+        if dataset.input_size() != self.input_size() {
+            return Err(NeuralError::DimensionMismatch { expected: self.input_size(), actual: dataset.input_size() });
+        }
+        if dataset.target_size() != self.output_size() {
+            return Err(NeuralError::DimensionMismatch { expected: self.output_size(), actual: dataset.target_size() });
+        }
+        dataset.batch_count(batch_size)
+    }
+
+    // Roll back to the best epoch's layers if asked, then summarize the run
+    fn finish_fit(&mut self, progress: FitProgress, best: Option<Vec<Layer>>) -> FitReport {
+        let restored = match best {
+            Some(layers) if progress.best_epoch() + 1 < progress.epochs_run() => {
+                self.layers = layers;
+                true
+            }
+            _ => false,
+        };
+        progress.finish(restored)
+    }
+
+    fn add_regularizer(&mut self, config: RegularizerConfig) -> NeuralResult<()> {
+        let layer = Layer::regularizer(config, self.output_size())?;
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    // Layers and optimizer to restore if the coming step goes non-finite
+    fn safety_backup(&self) -> Option<(Vec<Layer>, OptimizerState)> {
+        self.safety.rollback_non_finite.then(|| (self.layers.clone(), self.optimizer.clone()))
+    }
+
+    fn safe_step(
+        &mut self,
+        gradients: &mut [training::LayerGradients],
+        loss: f32,
+        learning_rate: f32,
+        backup: Option<(Vec<Layer>, OptimizerState)>,
+    ) -> NeuralResult<()> {
+        self.gradient_trend.record(gradients);
+        let (norm, fault) =
+            training::guarded_step(&mut self.layers, &mut self.optimizer, (gradients, loss), learning_rate, &self.safety, backup)?;
+        self.last_gradient_norm = norm;
+        match fault {
+            Some(fault) => {
+                self.last_fault = Some(fault);
+                Err(fault.error())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn add_recurrent(&mut self, cell: CellKind, hidden: usize) -> NeuralResult<()> {
+        if hidden == 0 {
+            return Err(NeuralError::InvalidConfiguration("layer size must be non-zero".to_string()));
+        }
+        let layer = Layer::Recurrent(RecurrentLayer::new(cell, self.output_size(), hidden));
+        self.push_initialized(layer);
+        Ok(())
+    }
+
+    // Everything besides the parameters that replay needs to reproduce results
+    pub(crate) fn execution_state(&self) -> ExecutionState {
+        let recurrent_state = self
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Recurrent(recurrent) => Some(recurrent.state()),
+                _ => None,
+            })
+            .flatten()
+            .copied()
+            .collect();
+        ExecutionState {
+            precision: self.precision,
+            output_mode: self.output_mode,
+            simd_enabled: self.simd_enabled,
+            initializer: self.initializer,
+            rng: self.rng.clone(),
+            recurrent_state,
+            optimizer: self.optimizer.config(),
+            optimizer_step: self.optimizer.step_count(),
+            optimizer_moments: self.optimizer.moments(),
+            loss: self.loss_function(),
+            training_safety: self.safety,
+            training_precision: self.training_precision,
+            loss_scaler: self.loss_scaler,
+            arithmetic: self.arithmetic,
+            adapters: self.adapters().map(|(layer, adapter)| (layer, adapter.clone())).collect(),
+        }
+    }
+
+    // Optimizer step count and moments for checkpoints. The moments always span the
+    // current trainable parameters: before the first step, or once they no longer fit
+    // (the next step would start over), zeros at step 0 stand in for them, which an
+    // optimizer treats exactly like freshly allocated moments.
+    pub(crate) fn optimizer_checkpoint(&self) -> (u64, Vec<f32>) {
+        let expected = self.optimizer_layout().iter().sum::<usize>() * self.optimizer.config().kind.slots();
+        let moments = self.optimizer.moments();
+        if moments.len() == expected {
+            (self.optimizer.step_count(), moments)
+        } else {
+            (0, vec![0.0; expected])
+        }
+    }
+
+    // Replace the optimizer with one at `step` holding `moments` for the current
+    // trainable parameters (empty for none yet)
+    pub(crate) fn restore_optimizer(&mut self, config: GradientOptimizerConfig, step: u64, moments: &[f32]) -> NeuralResult<()> {
+        self.optimizer = OptimizerState::restore(config, step, moments, self.optimizer_layout())?;
+        Ok(())
+    }
+
+    // Lengths of the (weights, biases) tensors the optimizer steps, layer by layer
+    fn optimizer_layout(&self) -> Vec<usize> {
+        let adapted = training::has_adapters(&self.layers);
+        self.layers
+            .iter()
+            .flat_map(|layer| {
+                let (weights, biases) = training::trainable_shape(layer, adapted);
+                [weights, biases]
+            })
+            .collect()
+    }
+
+    // Inverse of execution_state for a network of the same architecture. SIMD stays
+    // off where the engine lacks it, which may change results.
+    pub(crate) fn restore_execution_state(&mut self, state: ExecutionState) -> NeuralResult<()> {
+        let mut rest = state.recurrent_state.as_slice();
+        for layer in self.layers.iter_mut() {
+            if let Layer::Recurrent(recurrent) = layer {
+                let len = recurrent.state().len();
+                if rest.len() < len {
+                    return Err(NeuralError::InvalidFormat("recurrent state is shorter than the network needs".to_string()));
+                }
+                let (head, tail) = rest.split_at(len);
+                recurrent.state_mut().copy_from_slice(head);
+                rest = tail;
+            }
+        }
+        if !rest.is_empty() {
+            return Err(NeuralError::InvalidFormat("recurrent state is longer than the network needs".to_string()));
+        }
+        // Adapters first: they decide which parameters the optimizer's moments cover
+        self.install_adapters(state.adapters)?;
+        self.restore_optimizer(state.optimizer, state.optimizer_step, &state.optimizer_moments)
+            .map_err(|_| NeuralError::InvalidFormat("optimizer state does not match the network".to_string()))?;
+        self.loss = Loss::Builtin(
+            state.loss.ok_or_else(|| NeuralError::InvalidFormat("traces recorded with a JavaScript loss cannot be replayed".to_string()))?,
+        );
+        self.set_precision(state.precision);
+        self.output_mode = state.output_mode;
+        self.simd_enabled = state.simd_enabled && crate::check_simd_support();
+        self.initializer = state.initializer;
+        self.rng = state.rng;
+        self.safety = state.training_safety;
+        self.training_precision = state.training_precision;
+        self.loss_scaler = state.loss_scaler;
+        self.arithmetic = state.arithmetic;
+        Ok(())
+    }
+
+    // Draw the layer's weights from the initializer and append it
+    fn push_initialized(&mut self, mut layer: Layer) {
+        layer.initialize(&self.initializer, &mut self.rng);
+        self.layers.push(layer);
+    }
+
+    pub(crate) fn check_same_architecture(&self, other: &NeuralNetwork) -> NeuralResult<()> {
+        let same = self.input_size == other.input_size
+            && self.layers.len() == other.layers.len()
+            && self.layers.iter().zip(&other.layers).all(|(a, b)| a.shape() == b.shape());
+        if !same {
+            return Err(NeuralError::InvalidConfiguration("networks have different architectures".to_string()));
+        }
+        Ok(())
+    }
+
+    fn layer(&self, index: usize) -> NeuralResult<&Layer> {
+        let count = self.layers.len();
+        self.layers.get(index).ok_or(NeuralError::LayerIndexOutOfRange { index, count })
+    }
+
+    fn layer_mut(&mut self, index: usize) -> NeuralResult<&mut Layer> {
+        let count = self.layers.len();
+        self.layers.get_mut(index).ok_or(NeuralError::LayerIndexOutOfRange { index, count })
+    }
+
+    // Replace a batch norm layer's running mean and variance
+    pub(crate) fn set_running_statistics(&mut self, layer: usize, mean: &[f32], variance: &[f32]) -> NeuralResult<()> {
+        let Layer::Norm(norm @ NormLayer { kind: NormKind::Batch, .. }) = self.layer_mut(layer)? else {
+            return Err(NeuralError::InvalidConfiguration(format!("layer {} is not a batch norm layer", layer)));
+        };
+        check_len(mean.len(), norm.size)?;
+        check_len(variance.len(), norm.size)?;
+        if variance.iter().any(|&var| !(var >= 0.0 && var.is_finite())) {
+            return Err(NeuralError::InvalidConfiguration("running variance must be non-negative".to_string()));
+        }
+        norm.running_mean.copy_from_slice(mean);
+        norm.running_var.copy_from_slice(variance);
+        Ok(())
+    }
+
+    // (layer index, adapter) of every adapted dense layer
+    fn adapters(&self) -> impl Iterator<Item = (usize, &LoraAdapter)> {
+        self.layers.iter().enumerate().filter_map(|(index, layer)| match layer {
+            Layer::Dense(DenseLayer { adapter: Some(adapter), .. }) => Some((index, adapter)),
+            _ => None,
+        })
+    }
+
+    // Parameters of every adapter, in layer order
+    pub(crate) fn adapter_parameters(&self) -> Vec<f32> {
+        self.adapters().flat_map(|(_, adapter)| adapter.parameters.iter().copied()).collect()
+    }
+
+    // Inverse of adapter_parameters for the adapters attached now
+    pub(crate) fn set_adapter_parameters(&mut self, parameters: &[f32]) -> NeuralResult<()> {
+        check_len(parameters.len(), self.adapter_parameter_count())?;
+        let mut rest = parameters;
+        for dense in self.dense_layers_mut() {
+            if let Some(adapter) = &mut dense.adapter {
+                let (head, tail) = rest.split_at(adapter.parameters.len());
+                adapter.parameters.copy_from_slice(head);
+                rest = tail;
+            }
+        }
+        Ok(())
+    }
+
+    // Replace every adapter with `adapters`, after checking that each fits its layer
+    fn install_adapters(&mut self, adapters: Vec<(usize, LoraAdapter)>) -> NeuralResult<()> {
+        for (index, adapter) in &adapters {
+            let Layer::Dense(dense) = self.layer(*index)? else {
+                return Err(NeuralError::InvalidConfiguration(format!("layer {} is not a dense layer", index)));
+            };
+            if !adapter.matches(dense.inputs, dense.outputs) {
+                return Err(NeuralError::InvalidConfiguration(format!("adapter does not fit layer {}", index)));
+            }
+        }
+        for dense in self.dense_layers_mut() {
+            dense.adapter = None;
+        }
+        for (index, adapter) in adapters {
+            if let Layer::Dense(dense) = self.layer_mut(index)? {
+                dense.adapter = Some(adapter);
+            }
+        }
+        Ok(())
+    }
+
+    // Adapters change which parameters train, so moments from before no longer apply
+    fn reset_optimizer(&mut self) {
+        self.optimizer = OptimizerState::new(self.optimizer.config());
+    }
+
+    fn dense_layers_mut(&mut self) -> impl Iterator<Item = &mut DenseLayer> {
+        self.layers.iter_mut().filter_map(|layer| match layer {
+            Layer::Dense(layer) => Some(layer),
+            _ => None,
+        })
+    }
+}
+
+// Checked mode: fail on the first non-finite value a layer produced
+fn check_layer_outputs(index: usize, kind: LayerKind, outputs: &[f32]) -> NeuralResult<()> {
+    if !checked::enabled() {
+        return Ok(());
+    }
+    checked::check_outputs(&format!("layer {} ({:?})", index, kind), outputs)
+}
+
+fn check_len(actual: usize, expected: usize) -> NeuralResult<()> {
+    if actual != expected {
+        return Err(NeuralError::DimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+// Consecutive (inputs, targets, sample count) mini-batches of a dataset
+struct Batches<'a> {
+    inputs: std::slice::Chunks<'a, f32>,
+    targets: std::slice::Chunks<'a, f32>,
+    input_size: usize,
+    sample_count: usize,
+}
+
+impl<'a> Iterator for Batches<'a> {
+    type Item = (&'a [f32], &'a [f32], usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inputs = self.inputs.next()?;
+        let targets = self.targets.next()?;
+        Some((inputs, targets, inputs.len() / self.input_size))
+    }
+}
@@ -0,0 +1,268 @@
+// Neuron models for the spiking network, from cheapest to most detailed
+//
+//   Lif         one variable, see spiking.rs; input current I scaled by `resistance`
+//   Izhikevich  dv/dt = 0.04v² + 5v + 140 - u + I,  du/dt = a(bv - u)
+//               v ≥ v_peak: v = c, u += d                         (Izhikevich, 2003)
+//   AdEx        C dV/dt = -g_L(V - E_L) + g_L Δ_T exp((V - V_T)/Δ_T) - w + I
+//               τ_w dw/dt = a(V - E_L) - w
+//               V ≥ V_peak: V = V_reset, w += b                   (Brette & Gerstner, 2005)
+// Izhikevich takes dimensionless currents (about 10 for tonic spiking); AdEx takes
+// pA with C in pF and conductances in nS. Izhikevich and AdEx integrate with forward
+// Euler in substeps of at most 0.5 ms and 0.1 ms, and a neuron fires at most once
+// per network step. Incoming spikes jump v (mV) by the synapse weight in every model.
+//
+// A homeostatic threshold offset shifts the LIF threshold and the AdEx V_T. The
+// Izhikevich model has no explicit threshold, so the offset enters as a bias
+// current of -offset instead.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::spiking::LifParams;
+
+const IZHIKEVICH_SUBSTEP_MS: f32 = 0.5;
+const ADEX_SUBSTEP_MS: f32 = 0.1;
+// Caps the AdEx exponential so one substep cannot overflow before V_peak is seen
+const ADEX_MAX_EXPONENT: f32 = 20.0;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeuronModel {
+    Lif = 0,
+    Izhikevich = 1,
+    AdEx = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IzhikevichParams {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub v_peak: f32,
+}
+
+impl Default for IzhikevichParams {
+    fn default() -> Self {
+        IzhikevichParams::regular_spiking()
+    }
+}
+
+#[wasm_bindgen]
+impl IzhikevichParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> IzhikevichParams {
+        IzhikevichParams::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn regular_spiking() -> IzhikevichParams {
+        IzhikevichParams { a: 0.02, b: 0.2, c: -65.0, d: 8.0, v_peak: 30.0 }
+    }
+
+    #[wasm_bindgen]
+    pub fn fast_spiking() -> IzhikevichParams {
+        IzhikevichParams { a: 0.1, b: 0.2, c: -65.0, d: 2.0, v_peak: 30.0 }
+    }
+
+    #[wasm_bindgen]
+    pub fn chattering() -> IzhikevichParams {
+        IzhikevichParams { a: 0.02, b: 0.2, c: -50.0, d: 2.0, v_peak: 30.0 }
+    }
+}
+
+impl IzhikevichParams {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if [self.a, self.b, self.c, self.d, self.v_peak].iter().any(|value| !value.is_finite()) {
+            return Err(NeuralError::InvalidConfiguration("Izhikevich parameters must be finite".to_string()));
+        }
+        if self.c >= self.v_peak {
+            return Err(NeuralError::InvalidConfiguration("Izhikevich reset c must be below v_peak".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdExParams {
+    pub capacitance: f32,
+    pub g_leak: f32,
+    pub e_leak: f32,
+    pub v_threshold: f32,
+    pub delta_t: f32,
+    pub v_reset: f32,
+    pub v_peak: f32,
+    pub tau_w: f32,
+    pub a: f32,
+    pub b: f32,
+    pub refractory_ms: f32,
+}
+
+impl Default for AdExParams {
+    fn default() -> Self {
+        AdExParams {
+            capacitance: 281.0,
+            g_leak: 30.0,
+            e_leak: -70.6,
+            v_threshold: -50.4,
+            delta_t: 2.0,
+            v_reset: -70.6,
+            v_peak: 0.0,
+            tau_w: 144.0,
+            a: 4.0,
+            b: 80.5,
+            refractory_ms: 0.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl AdExParams {
+    // Brette & Gerstner's cortical pyramidal cell (pF, nS, mV, ms, pA)
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AdExParams {
+        AdExParams::default()
+    }
+}
+
+impl AdExParams {
+    pub fn validate(&self) -> NeuralResult<()> {
+        let values = [
+            self.capacitance,
+            self.g_leak,
+            self.e_leak,
+            self.v_threshold,
+            self.delta_t,
+            self.v_reset,
+            self.v_peak,
+            self.tau_w,
+            self.a,
+            self.b,
+            self.refractory_ms,
+        ];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(NeuralError::InvalidConfiguration("AdEx parameters must be finite".to_string()));
+        }
+        if self.capacitance <= 0.0 || self.g_leak <= 0.0 || self.delta_t <= 0.0 || self.tau_w <= 0.0 || self.refractory_ms < 0.0 {
+            return Err(NeuralError::InvalidConfiguration(
+                "AdEx capacitance, g_leak, delta_t and tau_w must be positive and refractory_ms non-negative".to_string(),
+            ));
+        }
+        if self.v_reset >= self.v_peak || self.v_threshold >= self.v_peak {
+            return Err(NeuralError::InvalidConfiguration("AdEx v_reset and v_threshold must be below v_peak".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// The model and parameters shared by every neuron of a network
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Model {
+    Lif(LifParams),
+    Izhikevich(IzhikevichParams),
+    AdEx(AdExParams),
+}
+
+// Outcome of advancing one neuron
+pub(crate) struct Update {
+    pub(crate) v: f32,
+    pub(crate) recovery: f32,
+    pub(crate) fired: bool,
+}
+
+impl Model {
+    pub(crate) fn kind(&self) -> NeuronModel {
+        match self {
+            Model::Lif(_) => NeuronModel::Lif,
+            Model::Izhikevich(_) => NeuronModel::Izhikevich,
+            Model::AdEx(_) => NeuronModel::AdEx,
+        }
+    }
+
+    // Resting potential and recovery variable (u or w; unused by LIF)
+    pub(crate) fn rest(&self) -> (f32, f32) {
+        match self {
+            Model::Lif(params) => (params.v_rest, 0.0),
+            Model::Izhikevich(params) => (params.c, params.b * params.c),
+            Model::AdEx(params) => (params.e_leak, 0.0),
+        }
+    }
+
+    pub(crate) fn refractory_ms(&self) -> f32 {
+        match self {
+            Model::Lif(params) => params.refractory_ms,
+            Model::Izhikevich(_) => 0.0,
+            Model::AdEx(params) => params.refractory_ms,
+        }
+    }
+
+    // Potential a neuron is held at while refractory
+    pub(crate) fn reset_potential(&self) -> f32 {
+        match self {
+            Model::Lif(params) => params.v_reset,
+            Model::Izhikevich(params) => params.c,
+            Model::AdEx(params) => params.v_reset,
+        }
+    }
+
+    // Largest threshold shift that still lets a neuron fire
+    pub(crate) fn threshold_headroom(&self) -> f32 {
+        match self {
+            Model::Lif(params) => params.v_threshold - params.v_reset,
+            Model::Izhikevich(_) => f32::INFINITY,
+            Model::AdEx(params) => params.v_peak - params.v_threshold,
+        }
+    }
+
+    // Advance one neuron by `dt` ms under a constant input current
+    pub(crate) fn integrate(&self, v: f32, recovery: f32, current: f32, threshold_offset: f32, dt: f32) -> Update {
+        match self {
+            Model::Lif(params) => {
+                let v = v + dt * (-(v - params.v_rest) + params.resistance * current) / params.tau_m;
+                if v >= params.v_threshold + threshold_offset {
+                    Update { v: params.v_reset, recovery, fired: true }
+                } else {
+                    Update { v, recovery, fired: false }
+                }
+            }
+            Model::Izhikevich(params) => {
+                let current = current - threshold_offset;
+                let (mut v, mut u) = (v, recovery);
+                for h in substeps(dt, IZHIKEVICH_SUBSTEP_MS) {
+                    let dv = 0.04 * v * v + 5.0 * v + 140.0 - u + current;
+                    let du = params.a * (params.b * v - u);
+                    v += h * dv;
+                    u += h * du;
+                    if v >= params.v_peak {
+                        return Update { v: params.c, recovery: u + params.d, fired: true };
+                    }
+                }
+                Update { v, recovery: u, fired: false }
+            }
+            Model::AdEx(params) => {
+                let v_threshold = params.v_threshold + threshold_offset;
+                let (mut v, mut w) = (v, recovery);
+                for h in substeps(dt, ADEX_SUBSTEP_MS) {
+                    let exponent = ((v - v_threshold) / params.delta_t).min(ADEX_MAX_EXPONENT);
+                    let spike_current = params.g_leak * params.delta_t * exponent.exp();
+                    let dv = (-params.g_leak * (v - params.e_leak) + spike_current - w + current) / params.capacitance;
+                    let dw = (params.a * (v - params.e_leak) - w) / params.tau_w;
+                    v += h * dv;
+                    w += h * dw;
+                    if v >= params.v_peak {
+                        return Update { v: params.v_reset, recovery: w + params.b, fired: true };
+                    }
+                }
+                Update { v, recovery: w, fired: false }
+            }
+        }
+    }
+}
+
+// Equal substeps of at most `max` covering `dt`
+fn substeps(dt: f32, max: f32) -> impl Iterator<Item = f32> {
+    let count = (dt / max).ceil().max(1.0) as usize;
+    std::iter::repeat_n(dt / count as f32, count)
+}
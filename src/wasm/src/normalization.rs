@@ -0,0 +1,274 @@
+// Dropout and normalization layers
+//
+// Dropout zeroes each value with probability `rate` while training and scales the
+// survivors by 1/(1 - rate) (inverted dropout), so the expected activation is the
+// same in both modes and inference passes values through untouched. Masks are drawn
+// from the network's RNG, so seeded networks train reproducibly.
+//
+// BatchNorm normalizes each feature with the mean and variance of the training
+// batch, and at inference with running averages updated on every training batch:
+//   running = (1 - momentum) · running + momentum · batch
+// where the batch variance is the unbiased one, as in PyTorch. LayerNorm normalizes
+// each sample over its own features, the same way in training and inference. Both
+// then apply a learned per-feature scale γ and shift β, exposed as the layer's
+// weights and biases and initialized to 1 and 0.
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::Rng;
+use crate::stats;
+use crate::training::LayerGradients;
+
+pub(crate) const DEFAULT_EPSILON: f32 = 1e-5;
+pub(crate) const DEFAULT_MOMENTUM: f32 = 0.1;
+
+// Hyperparameters recorded in a layer's shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RegularizerConfig {
+    Dropout { rate: f32 },
+    BatchNorm { epsilon: f32, momentum: f32 },
+    LayerNorm { epsilon: f32 },
+}
+
+impl RegularizerConfig {
+    pub(crate) fn validate(&self) -> NeuralResult<()> {
+        match *self {
+            RegularizerConfig::Dropout { rate } if !(0.0..1.0).contains(&rate) => {
+                Err(NeuralError::InvalidConfiguration("dropout rate must lie in [0, 1)".to_string()))
+            }
+            RegularizerConfig::BatchNorm { momentum, .. } if !(0.0..=1.0).contains(&momentum) => {
+                Err(NeuralError::InvalidConfiguration("batch norm momentum must lie in [0, 1]".to_string()))
+            }
+            RegularizerConfig::BatchNorm { epsilon, .. } | RegularizerConfig::LayerNorm { epsilon }
+                if !epsilon.is_finite() || epsilon <= 0.0 =>
+            {
+                Err(NeuralError::InvalidConfiguration("normalization epsilon must be positive and finite".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DropoutLayer {
+    pub(crate) size: usize,
+    pub(crate) rate: f32,
+}
+
+impl DropoutLayer {
+    // Scaled inputs and the per-value factor applied (0 or 1/(1 - rate))
+    pub(crate) fn forward_train(&self, inputs: &[f32], rng: &mut Rng) -> (Vec<f32>, Vec<f32>) {
+        let keep = 1.0 / (1.0 - self.rate);
+        let mask: Vec<f32> = inputs.iter().map(|_| if rng.next_f32() < self.rate { 0.0 } else { keep }).collect();
+        let outputs = inputs.iter().zip(&mask).map(|(x, m)| x * m).collect();
+        (outputs, mask)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NormKind {
+    Batch,
+    Layer,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NormLayer {
+    pub(crate) kind: NormKind,
+    pub(crate) size: usize,
+    pub(crate) epsilon: f32,
+    // BatchNorm only
+    pub(crate) momentum: f32,
+    pub(crate) gamma: Vec<f32>,
+    pub(crate) beta: Vec<f32>,
+    // BatchNorm only; empty for LayerNorm
+    pub(crate) running_mean: Vec<f32>,
+    pub(crate) running_var: Vec<f32>,
+}
+
+// What the backward pass needs from a training forward pass
+pub(crate) struct NormCache {
+    normalized: Vec<f32>,
+    // Per feature for BatchNorm, per sample for LayerNorm
+    inv_std: Vec<f32>,
+}
+
+impl NormLayer {
+    pub(crate) fn new(kind: NormKind, size: usize, epsilon: f32, momentum: f32) -> NormLayer {
+        let running = if kind == NormKind::Batch { size } else { 0 };
+        NormLayer {
+            kind,
+            size,
+            epsilon,
+            momentum,
+            gamma: vec![1.0; size],
+            beta: vec![0.0; size],
+            running_mean: vec![0.0; running],
+            running_var: vec![1.0; running],
+        }
+    }
+
+    pub(crate) fn config(&self) -> RegularizerConfig {
+        match self.kind {
+            NormKind::Batch => RegularizerConfig::BatchNorm { epsilon: self.epsilon, momentum: self.momentum },
+            NormKind::Layer => RegularizerConfig::LayerNorm { epsilon: self.epsilon },
+        }
+    }
+
+    // γ = 1, β = 0 and fresh running statistics
+    pub(crate) fn reset(&mut self) {
+        *self = NormLayer::new(self.kind, self.size, self.epsilon, self.momentum);
+    }
+
+    // Inference for one sample
+    pub(crate) fn forward_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        check_len(inputs.len(), self.size)?;
+        check_len(outputs.len(), self.size)?;
+        match self.kind {
+            NormKind::Batch => {
+                for (j, (output, &x)) in outputs.iter_mut().zip(inputs).enumerate() {
+                    let inv_std = 1.0 / (self.running_var[j] + self.epsilon).sqrt();
+                    *output = (x - self.running_mean[j]) * inv_std * self.gamma[j] + self.beta[j];
+                }
+            }
+            NormKind::Layer => {
+                let (mean, inv_std) = self.sample_moments(inputs, simd);
+                for (j, (output, &x)) in outputs.iter_mut().zip(inputs).enumerate() {
+                    *output = (x - mean) * inv_std * self.gamma[j] + self.beta[j];
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // dL/d(inputs) of one sample's inference pass, given dL/d(outputs)
+    pub(crate) fn input_gradient(&self, inputs: &[f32], grad: &[f32], simd: bool) -> Vec<f32> {
+        match self.kind {
+            NormKind::Batch => (0..self.size)
+                .map(|j| grad[j] * self.gamma[j] / (self.running_var[j] + self.epsilon).sqrt())
+                .collect(),
+            NormKind::Layer => {
+                // dx = inv_std · (g - mean(g) - x̂ · mean(g · x̂)) with g = γ · dL/dy
+                let (mean, inv_std) = self.sample_moments(inputs, simd);
+                let scaled: Vec<f32> = grad.iter().zip(&self.gamma).map(|(g, gamma)| g * gamma).collect();
+                let normalized: Vec<f32> = inputs.iter().map(|x| (x - mean) * inv_std).collect();
+                let n = self.size as f32;
+                let mean_grad = scaled.iter().sum::<f32>() / n;
+                let mean_product = scaled.iter().zip(&normalized).map(|(g, x)| g * x).sum::<f32>() / n;
+                scaled.iter().zip(&normalized).map(|(g, x)| inv_std * (g - mean_grad - x * mean_product)).collect()
+            }
+        }
+    }
+
+    // Training pass over a row-major [batch_size × size] batch; BatchNorm also
+    // folds the batch statistics into its running averages
+    pub(crate) fn forward_train(&mut self, inputs: &[f32], batch_size: usize, simd: bool) -> NeuralResult<(Vec<f32>, NormCache)> {
+        check_len(inputs.len(), batch_size * self.size)?;
+        let mut normalized = vec![0.0; inputs.len()];
+        let inv_std = match self.kind {
+            NormKind::Batch => {
+                if batch_size < 2 {
+                    return Err(NeuralError::InvalidConfiguration(
+                        "batch normalization needs at least two samples per training batch".to_string(),
+                    ));
+                }
+                let mut inv_std = vec![0.0; self.size];
+                let mut column = vec![0.0; batch_size];
+                for j in 0..self.size {
+                    for (value, row) in column.iter_mut().zip(inputs.chunks_exact(self.size)) {
+                        *value = row[j];
+                    }
+                    let mean = stats::mean(&column, simd).unwrap_or(0.0);
+                    let variance = stats::variance(&column, mean, simd).unwrap_or(0.0);
+                    inv_std[j] = 1.0 / (variance + self.epsilon).sqrt();
+                    for (sample, &x) in column.iter().enumerate() {
+                        normalized[sample * self.size + j] = (x - mean) * inv_std[j];
+                    }
+                    let unbiased = variance * batch_size as f32 / (batch_size - 1) as f32;
+                    self.running_mean[j] += self.momentum * (mean - self.running_mean[j]);
+                    self.running_var[j] += self.momentum * (unbiased - self.running_var[j]);
+                }
+                inv_std
+            }
+            NormKind::Layer => {
+                let mut inv_std = Vec::with_capacity(batch_size);
+                for (row, out) in inputs.chunks_exact(self.size).zip(normalized.chunks_exact_mut(self.size)) {
+                    let (mean, sample_inv_std) = self.sample_moments(row, simd);
+                    for (value, &x) in out.iter_mut().zip(row) {
+                        *value = (x - mean) * sample_inv_std;
+                    }
+                    inv_std.push(sample_inv_std);
+                }
+                inv_std
+            }
+        };
+        let mut outputs = normalized.clone();
+        for row in outputs.chunks_exact_mut(self.size) {
+            for ((value, gamma), beta) in row.iter_mut().zip(&self.gamma).zip(&self.beta) {
+                *value = *value * gamma + beta;
+            }
+        }
+        Ok((outputs, NormCache { normalized, inv_std }))
+    }
+
+    // Accumulate dL/dγ and dL/dβ and turn dL/d(output) into dL/d(input) for the batch
+    pub(crate) fn backward(&self, cache: &NormCache, grad: &[f32], batch_size: usize, gradients: &mut LayerGradients) -> Vec<f32> {
+        let size = self.size;
+        let mut d_normalized = vec![0.0; grad.len()];
+        for ((d_row, g_row), x_row) in d_normalized.chunks_exact_mut(size).zip(grad.chunks_exact(size)).zip(cache.normalized.chunks_exact(size)) {
+            for j in 0..size {
+                gradients.weights[j] += g_row[j] * x_row[j];
+                gradients.biases[j] += g_row[j];
+                d_row[j] = g_row[j] * self.gamma[j];
+            }
+        }
+
+        // dx = inv_std / n · (n·dx̂ - Σdx̂ - x̂·Σ(dx̂·x̂)), summed over the normalized axis
+        let mut upstream = vec![0.0; grad.len()];
+        match self.kind {
+            NormKind::Batch => {
+                let n = batch_size as f32;
+                for j in 0..size {
+                    let (mut sum, mut dot) = (0.0, 0.0);
+                    for sample in 0..batch_size {
+                        let index = sample * size + j;
+                        sum += d_normalized[index];
+                        dot += d_normalized[index] * cache.normalized[index];
+                    }
+                    for sample in 0..batch_size {
+                        let index = sample * size + j;
+                        upstream[index] = cache.inv_std[j] / n * (n * d_normalized[index] - sum - cache.normalized[index] * dot);
+                    }
+                }
+            }
+            NormKind::Layer => {
+                let n = size as f32;
+                for (((up, d_row), x_row), &inv_std) in upstream
+                    .chunks_exact_mut(size)
+                    .zip(d_normalized.chunks_exact(size))
+                    .zip(cache.normalized.chunks_exact(size))
+                    .zip(&cache.inv_std)
+                {
+                    let sum: f32 = d_row.iter().sum();
+                    let dot: f32 = d_row.iter().zip(x_row).map(|(d, x)| d * x).sum();
+                    for ((up, &d), &x) in up.iter_mut().zip(d_row).zip(x_row) {
+                        *up = inv_std / n * (n * d - sum - x * dot);
+                    }
+                }
+            }
+        }
+        upstream
+    }
+
+    // Mean and 1/sqrt(variance + epsilon) of one sample's features
+    fn sample_moments(&self, values: &[f32], simd: bool) -> (f32, f32) {
+        let mean = stats::mean(values, simd).unwrap_or(0.0);
+        let variance = stats::variance(values, mean, simd).unwrap_or(0.0);
+        (mean, 1.0 / (variance + self.epsilon).sqrt())
+    }
+}
+
+fn check_len(actual: usize, expected: usize) -> NeuralResult<()> {
+    if actual != expected {
+        return Err(NeuralError::DimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
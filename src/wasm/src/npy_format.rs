@@ -0,0 +1,629 @@
+// NumPy .npy / .npz weight import
+//
+// .npy is a magic string, a Python dict literal header ({'descr': '<f4',
+// 'fortran_order': False, 'shape': (3, 4), }) and the raw array. Little-endian
+// float32 and float64 arrays are accepted; Fortran-ordered arrays are converted
+// to C order. .npz is a zip archive of .npy members, written either stored
+// (np.savez) or deflated (np.savez_compressed); members are CRC-checked.
+//
+// NeuralNetwork.load_npz maps arrays onto layers by name, accepting the two common
+// conventions for a layer called `name`:
+//   PyTorch  name.weight [out][in], name.bias [out]
+//   Keras    name/kernel [in][out] (transposed on load), name/bias [out]
+// Non-dense layers take `name.weight` in the runtime's own layout, which matches
+// PyTorch for Conv1d ([out_channels][in_channels][kernel]) and for BatchNorm1d and
+// LayerNorm (γ as weight, β as bias). Batch norm layers also pick up
+// `name.running_mean` and `name.running_var` when present. Dropout layers have no
+// parameters and need no arrays.
+
+use std::collections::HashMap;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::integrity::crc32;
+use crate::network::{LayerKind, NeuralNetwork};
+
+const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+const ZIP_LOCAL_HEADER: u32 = 0x0403_4B50;
+const ZIP_CENTRAL_HEADER: u32 = 0x0201_4B50;
+const ZIP_END_OF_DIRECTORY: u32 = 0x0605_4B50;
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+// Extra field holding 64-bit sizes and offsets
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NpyArray {
+    pub shape: Vec<usize>,
+    // C (row-major) order
+    pub values: Vec<f32>,
+}
+
+fn invalid(reason: &str) -> NeuralError {
+    NeuralError::InvalidFormat(format!("npy: {}", reason))
+}
+
+pub fn parse_npy(bytes: &[u8]) -> NeuralResult<NpyArray> {
+    if bytes.len() < 10 || &bytes[..6] != NPY_MAGIC {
+        return Err(invalid("missing \\x93NUMPY magic"));
+    }
+    let (header_len, header_start): (usize, usize) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+        version => return Err(invalid(&format!("unsupported format version {}", version))),
+    };
+    let data_start = header_start.checked_add(header_len).filter(|&end| end <= bytes.len()).ok_or_else(|| invalid("truncated header"))?;
+    let header = std::str::from_utf8(&bytes[header_start..data_start]).map_err(|_| invalid("header is not text"))?;
+
+    let descr = dict_value(header, "descr").ok_or_else(|| invalid("header has no 'descr'"))?;
+    let element_size = match descr.trim_matches(|c| c == '\'' || c == '"') {
+        "<f4" | "=f4" => 4,
+        "<f8" | "=f8" => 8,
+        other => return Err(invalid(&format!("unsupported dtype {}; expected little-endian float32 or float64", other))),
+    };
+    let fortran_order = match dict_value(header, "fortran_order") {
+        Some("True") => true,
+        Some("False") | None => false,
+        Some(other) => return Err(invalid(&format!("invalid fortran_order {}", other))),
+    };
+    let shape_text = dict_value(header, "shape").ok_or_else(|| invalid("header has no 'shape'"))?;
+    let shape = shape_text
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>().map_err(|_| invalid(&format!("invalid shape {}", shape_text))))
+        .collect::<NeuralResult<Vec<usize>>>()?;
+
+    let count = shape.iter().try_fold(1usize, |count, &dim| count.checked_mul(dim)).ok_or_else(|| invalid("shape overflows"))?;
+    let data = &bytes[data_start..];
+    if count.checked_mul(element_size) != Some(data.len()) {
+        return Err(invalid(&format!("{} data bytes for shape {:?}", data.len(), shape)));
+    }
+    let mut values: Vec<f32> = match element_size {
+        4 => data.chunks_exact(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect(),
+        _ => data
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("8-byte chunk")) as f32)
+            .collect(),
+    };
+    if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+        return Err(NeuralError::NonFiniteInput { index });
+    }
+    if fortran_order && shape.len() > 1 {
+        values = fortran_to_c(&values, &shape);
+    }
+    Ok(NpyArray { shape, values })
+}
+
+// Text of `key`'s value in the header dict, up to the next top-level comma
+fn dict_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{}'", key))? + key.len() + 2;
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let end = if rest.starts_with('(') { rest.find(')')? + 1 } else { rest.find([',', '}'])? };
+    Some(rest[..end].trim())
+}
+
+// Column-major element order to row-major
+fn fortran_to_c(values: &[f32], shape: &[usize]) -> Vec<f32> {
+    let mut converted = vec![0.0; values.len()];
+    let mut index = vec![0usize; shape.len()];
+    for &value in values {
+        // Row-major offset of the current multi-index
+        let offset = index.iter().zip(shape).fold(0, |offset, (&i, &dim)| offset * dim + i);
+        converted[offset] = value;
+        // Fortran order advances the first axis fastest
+        for (i, &dim) in index.iter_mut().zip(shape) {
+            *i += 1;
+            if *i < dim {
+                break;
+            }
+            *i = 0;
+        }
+    }
+    converted
+}
+
+// Every .npy member of an .npz archive, keyed by name without the extension
+pub fn parse_npz(bytes: &[u8]) -> NeuralResult<HashMap<String, NpyArray>> {
+    let mut arrays = HashMap::new();
+    for entry in zip_entries(bytes)? {
+        let name = entry.name.strip_suffix(".npy").unwrap_or(&entry.name).to_string();
+        let data = match entry.method {
+            ZIP_STORED => entry.data.to_vec(),
+            ZIP_DEFLATED => inflate(entry.data, entry.size)?,
+            method => return Err(invalid(&format!("member '{}' uses unsupported compression {}", entry.name, method))),
+        };
+        if data.len() != entry.size || crc32(&data) != entry.crc {
+            return Err(invalid(&format!("member '{}' is corrupt", entry.name)));
+        }
+        arrays.insert(name, parse_npy(&data)?);
+    }
+    Ok(arrays)
+}
+
+struct ZipEntry<'a> {
+    name: String,
+    method: u16,
+    crc: u32,
+    size: usize,
+    // Member bytes as stored, possibly compressed
+    data: &'a [u8],
+}
+
+fn zip_entries(bytes: &[u8]) -> NeuralResult<Vec<ZipEntry<'_>>> {
+    let u16_at = |offset: usize| -> NeuralResult<u16> {
+        bytes.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]])).ok_or_else(|| invalid("truncated zip archive"))
+    };
+    let u32_at = |offset: usize| -> NeuralResult<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| invalid("truncated zip archive"))
+    };
+
+    // The end-of-directory record is the last 22 bytes plus an optional comment
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&offset| u32_at(offset).ok() == Some(ZIP_END_OF_DIRECTORY))
+        .ok_or_else(|| invalid("not a zip archive"))?;
+    let count = u16_at(end + 10)? as usize;
+    let mut offset = u32_at(end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        if u32_at(offset)? != ZIP_CENTRAL_HEADER {
+            return Err(invalid("corrupt zip central directory"));
+        }
+        let method = u16_at(offset + 10)?;
+        let crc = u32_at(offset + 16)?;
+        let mut compressed = u32_at(offset + 20)? as u64;
+        let mut size = u32_at(offset + 24)? as u64;
+        let name_len = u16_at(offset + 28)? as usize;
+        let extra_len = u16_at(offset + 30)? as usize;
+        let comment_len = u16_at(offset + 32)? as usize;
+        let mut local = u32_at(offset + 42)? as u64;
+        let name_bytes = bytes.get(offset + 46..offset + 46 + name_len).ok_or_else(|| invalid("truncated zip archive"))?;
+        let name = String::from_utf8(name_bytes.to_vec()).map_err(|_| invalid("zip member name is not UTF-8"))?;
+
+        // Zip64: saturated fields continue in the 0x0001 extra record, in this order
+        let mut extra = offset + 46 + name_len;
+        let extra_end = extra + extra_len;
+        while extra + 4 <= extra_end {
+            let (id, len) = (u16_at(extra)?, u16_at(extra + 2)? as usize);
+            if id == ZIP64_EXTRA_ID {
+                let mut field = extra + 4;
+                for value in [&mut size, &mut compressed, &mut local] {
+                    if *value == u32::MAX as u64 {
+                        *value = u64::from(u32_at(field)?) | u64::from(u32_at(field + 4)?) << 32;
+                        field += 8;
+                    }
+                }
+            }
+            extra += 4 + len;
+        }
+
+        let local = usize::try_from(local).map_err(|_| invalid("zip offset overflows"))?;
+        if u32_at(local)? != ZIP_LOCAL_HEADER {
+            return Err(invalid("corrupt zip local header"));
+        }
+        let data_start = local + 30 + u16_at(local + 26)? as usize + u16_at(local + 28)? as usize;
+        let data = usize::try_from(compressed)
+            .ok()
+            .and_then(|len| bytes.get(data_start..data_start.checked_add(len)?))
+            .ok_or_else(|| invalid(&format!("member '{}' is truncated", name)))?;
+        let size = usize::try_from(size).map_err(|_| invalid("zip member too large"))?;
+        entries.push(ZipEntry { name, method, crc, size, data });
+        offset = extra_end + comment_len;
+    }
+    Ok(entries)
+}
+
+// Raw DEFLATE (RFC 1951) decoder; `expected` bounds the output size
+fn inflate(data: &[u8], expected: usize) -> NeuralResult<Vec<u8>> {
+    let mut bits = BitReader { data, offset: 0, bit: 0 };
+    // `expected` comes from the archive, so only a bounded guess is reserved up front
+    let mut out = Vec::with_capacity(expected.min(data.len().saturating_mul(4)).min(1 << 20));
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => {
+                bits.align();
+                let len = bits.take(16)? as usize;
+                if bits.take(16)? as usize != !len & 0xFFFF {
+                    return Err(invalid("corrupt stored deflate block"));
+                }
+                out.extend_from_slice(bits.bytes(len)?);
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(&mut bits, &literals, &distances, &mut out, expected)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &literals, &distances, &mut out, expected)?;
+            }
+            _ => return Err(invalid("invalid deflate block type")),
+        }
+        if out.len() > expected {
+            return Err(invalid("deflate stream is longer than declared"));
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289,
+    16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// Order in which code length code lengths are sent
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn inflate_block(bits: &mut BitReader, literals: &Huffman, distances: &Huffman, out: &mut Vec<u8>, expected: usize) -> NeuralResult<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let len = LENGTH_BASE[index] as usize + bits.take(LENGTH_EXTRA[index] as u32)? as usize;
+                let code = distances.decode(bits)? as usize;
+                if code >= 30 {
+                    return Err(invalid("invalid deflate distance code"));
+                }
+                let distance = DISTANCE_BASE[code] as usize + bits.take(DISTANCE_EXTRA[code] as u32)? as usize;
+                if distance > out.len() || out.len() + len > expected {
+                    return Err(invalid("invalid deflate back-reference"));
+                }
+                // Byte by byte, since the copy may overlap its own output
+                let start = out.len() - distance;
+                for index in 0..len {
+                    out.push(out[start + index]);
+                }
+            }
+            _ => return Err(invalid("invalid deflate literal/length code")),
+        }
+        if out.len() > expected {
+            return Err(invalid("deflate stream is longer than declared"));
+        }
+    }
+}
+
+fn dynamic_tables(bits: &mut BitReader) -> NeuralResult<(Huffman, Huffman)> {
+    let literal_count = bits.take(5)? as usize + 257;
+    let distance_count = bits.take(5)? as usize + 1;
+    let code_length_count = bits.take(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = bits.take(3)? as u8;
+    }
+    let code_length_codes = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let (value, repeat) = match code_length_codes.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..index].last().ok_or_else(|| invalid("deflate length repeat with no previous length"))?;
+                (previous, 3 + bits.take(2)? as usize)
+            }
+            17 => (0, 3 + bits.take(3)? as usize),
+            _ => (0, 11 + bits.take(7)? as usize),
+        };
+        let target = lengths.get_mut(index..index + repeat).ok_or_else(|| invalid("deflate code lengths overrun"))?;
+        target.fill(value);
+        index += repeat;
+    }
+    Ok((Huffman::new(&lengths[..literal_count])?, Huffman::new(&lengths[literal_count..])?))
+}
+
+// Canonical Huffman code decoded one bit at a time
+struct Huffman {
+    // Number of codes of each bit length
+    counts: [u16; 16],
+    // Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> NeuralResult<Huffman> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len > 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> NeuralResult<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.take(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid deflate Huffman code"))
+    }
+}
+
+// Least-significant-bit-first reader over a deflate stream
+struct BitReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn take(&mut self, count: u32) -> NeuralResult<u32> {
+        let mut value = 0;
+        for position in 0..count {
+            let byte = *self.data.get(self.offset).ok_or_else(|| invalid("truncated deflate stream"))?;
+            value |= ((byte >> self.bit) as u32 & 1) << position;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.offset += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.offset += 1;
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> NeuralResult<&'a [u8]> {
+        let slice = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or_else(|| invalid("truncated deflate stream"))?;
+        self.offset += len;
+        Ok(slice)
+    }
+}
+
+// Weights for `layer` from an array: dense layers need [out][in], or [in][out] when
+// `kernel` is set (Keras); other layers take their own layout, matched by element count
+pub(crate) fn layer_weights(network: &NeuralNetwork, layer: usize, array: &NpyArray, kernel: bool) -> NeuralResult<Vec<f32>> {
+    let outputs = network.layer_size(layer)?;
+    if network.layer_kind(layer)? != LayerKind::Dense {
+        return Ok(array.values.clone());
+    }
+    let inputs = if layer == 0 { network.model_input_size() } else { network.layer_size(layer - 1)? };
+    let expected = if kernel { [inputs, outputs] } else { [outputs, inputs] };
+    if array.shape != expected {
+        return Err(NeuralError::InvalidConfiguration(format!(
+            "layer {} weights have shape {:?}, expected {:?}",
+            layer, array.shape, expected
+        )));
+    }
+    if !kernel {
+        return Ok(array.values.clone());
+    }
+    let mut weights = vec![0.0; array.values.len()];
+    for (input, row) in array.values.chunks_exact(outputs.max(1)).enumerate() {
+        for (output, &value) in row.iter().enumerate() {
+            weights[output * inputs + input] = value;
+        }
+    }
+    Ok(weights)
+}
+
+pub(crate) fn layer_biases(layer: usize, array: &NpyArray) -> NeuralResult<Vec<f32>> {
+    if array.shape.len() != 1 {
+        return Err(NeuralError::InvalidConfiguration(format!("layer {} biases have shape {:?}, expected one axis", layer, array.shape)));
+    }
+    Ok(array.values.clone())
+}
+
+// Load every layer of `network` from an .npz archive. `layer_names` lists one name
+// per layer, comma-separated; empty means "0", "1", … as nn.Sequential numbers them.
+pub fn load_npz(network: &mut NeuralNetwork, bytes: &[u8], layer_names: &str) -> NeuralResult<()> {
+    let arrays = parse_npz(bytes)?;
+    let names: Vec<String> = if layer_names.trim().is_empty() {
+        (0..network.layer_count()).map(|layer| layer.to_string()).collect()
+    } else {
+        layer_names.split(',').map(|name| name.trim().to_string()).collect()
+    };
+    if names.len() != network.layer_count() {
+        return Err(NeuralError::DimensionMismatch { expected: network.layer_count(), actual: names.len() });
+    }
+
+    // Resolve everything before writing, so a bad archive leaves the network untouched
+    let mut parameters = Vec::with_capacity(names.len());
+    let mut statistics = Vec::new();
+    for (layer, name) in names.iter().enumerate() {
+        let kind = network.layer_kind(layer)?;
+        if kind == LayerKind::Dropout {
+            parameters.push((Vec::new(), Vec::new()));
+            continue;
+        }
+        if kind == LayerKind::BatchNorm {
+            if let (Some(mean), Some(variance)) = (arrays.get(&format!("{}.running_mean", name)), arrays.get(&format!("{}.running_var", name))) {
+                statistics.push((layer, mean.values.clone(), variance.values.clone()));
+            }
+        }
+        let (weights, kernel, biases) = match (arrays.get(&format!("{}.weight", name)), arrays.get(&format!("{}/kernel", name))) {
+            (Some(weights), _) => (weights, false, format!("{}.bias", name)),
+            (None, Some(kernel)) => (kernel, true, format!("{}/bias", name)),
+            (None, None) => {
+                return Err(NeuralError::InvalidConfiguration(format!(
+                    "archive has no '{0}.weight' or '{0}/kernel' array for layer {1}",
+                    name, layer
+                )))
+            }
+        };
+        let biases = arrays
+            .get(&biases)
+            .ok_or_else(|| NeuralError::InvalidConfiguration(format!("archive has no '{}' array for layer {}", biases, layer)))?;
+        parameters.push((layer_weights(network, layer, weights, kernel)?, layer_biases(layer, biases)?));
+    }
+    let mut updated = network.clone();
+    for (layer, (weights, biases)) in parameters.iter().enumerate() {
+        updated.set_weights(layer, weights)?;
+        updated.set_biases(layer, biases)?;
+    }
+    for (layer, mean, variance) in &statistics {
+        updated.set_running_statistics(*layer, mean, variance)?;
+    }
+    *network = updated;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::ActivationKind;
+
+    fn npy(descr: &str, shape: &[usize], fortran: bool, data: &[u8]) -> Vec<u8> {
+        let dims: Vec<String> = shape.iter().map(usize::to_string).collect();
+        let shape = if dims.len() == 1 { format!("({},)", dims[0]) } else { format!("({})", dims.join(", ")) };
+        let header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n",
+            descr,
+            if fortran { "True" } else { "False" },
+            shape
+        );
+        let mut out = NPY_MAGIC.to_vec();
+        out.extend_from_slice(&[1, 0]);
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn f32_npy(shape: &[usize], values: &[f32]) -> Vec<u8> {
+        npy("<f4", shape, false, &values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>())
+    }
+
+    // Uncompressed archive, as np.savez writes it
+    fn stored_zip(members: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let (mut out, mut directory) = (Vec::new(), Vec::new());
+        for (name, data) in members {
+            let offset = out.len() as u32;
+            let mut fields = Vec::new();
+            fields.extend_from_slice(&crc32(data).to_le_bytes());
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&[0, 0]);
+
+            out.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+            out.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            out.extend_from_slice(&fields);
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(data);
+
+            directory.extend_from_slice(&ZIP_CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            directory.extend_from_slice(&fields);
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let directory_offset = out.len() as u32;
+        out.extend_from_slice(&directory);
+        out.extend_from_slice(&ZIP_END_OF_DIRECTORY.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        out.extend_from_slice(&directory_offset.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    #[test]
+    fn parses_c_and_fortran_order() {
+        let c_order = parse_npy(&f32_npy(&[2, 3], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])).unwrap();
+        assert_eq!(c_order.shape, vec![2, 3]);
+
+        // Same matrix stored column by column as float64
+        let columns: Vec<u8> = [1.0f64, 4.0, 2.0, 5.0, 3.0, 6.0].iter().flat_map(|value| value.to_le_bytes()).collect();
+        let fortran = parse_npy(&npy("<f8", &[2, 3], true, &columns)).unwrap();
+        assert_eq!(fortran, c_order);
+
+        assert!(parse_npy(&npy("<i4", &[1], false, &[0; 4])).is_err());
+        assert!(parse_npy(&npy("<f4", &[2], false, &[0; 4])).is_err());
+    }
+
+    #[test]
+    fn inflates_fixed_and_dynamic_blocks() {
+        let fixed = [0x4B, 0x4C, 0x4A, 0x4E, 0xA4, 0x06, 0x02, 0x00];
+        assert_eq!(inflate(&fixed, 72).unwrap(), b"abc".repeat(24));
+        // A huge declared size reserves nothing beyond what the data can produce
+        assert_eq!(inflate(&fixed, 0xFFFF_FFF0).unwrap(), b"abc".repeat(24));
+
+        let dynamic = [
+            0x3D, 0xCE, 0xB1, 0x12, 0x82, 0x40, 0x10, 0x04, 0xD1, 0x6F, 0xED, 0xD9, 0x48, 0x4E, 0x23, 0x04, 0x8D, 0x04, 0x89,
+            0x14, 0x34, 0xC2, 0xD3, 0xDF, 0xF5, 0x92, 0x9D, 0x68, 0xAA, 0x93, 0x57, 0x03, 0xA0, 0x1A, 0x3C, 0xDA, 0x28, 0x5A,
+            0x30, 0x2B, 0x7A, 0xB2, 0x8A, 0xB4, 0x93, 0xB5, 0xA1, 0x5B, 0x64, 0x8D, 0x70, 0x50, 0xD6, 0x17, 0x9E, 0xCA, 0xBA,
+            0xC3, 0x39, 0x31, 0x1D, 0x83, 0x8F, 0xE9, 0x97, 0x62, 0x32, 0x7D, 0x91, 0x3A, 0xD3, 0x3F, 0xB4, 0x9A, 0x5E, 0x60,
+            0x30, 0x7D, 0x82, 0x6A, 0xFA, 0xDD, 0xEE, 0x99, 0xBE, 0x06, 0xC5, 0xF4, 0x1F,
+        ];
+        let expected: Vec<u8> = (0..200usize)
+            .map(|i| if i * i % 13 < 11 { b"aaaabbc"[(i * i * 7 + i) % 7] } else { (100 + i * 31 % 53) as u8 })
+            .collect();
+        assert_eq!(inflate(&dynamic, 200).unwrap(), expected);
+        assert!(inflate(&dynamic, 199).is_err());
+    }
+
+    #[test]
+    fn loads_pytorch_and_keras_names() {
+        let mut network = NeuralNetwork::new(3).unwrap();
+        network.add_layer(2, ActivationKind::ReLU).unwrap();
+        network.add_layer(1, ActivationKind::Linear).unwrap();
+        let archive = stored_zip(&[
+            ("hidden.weight.npy", f32_npy(&[2, 3], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])),
+            ("hidden.bias.npy", f32_npy(&[2], &[0.5, -0.5])),
+            ("out/kernel.npy", f32_npy(&[2, 1], &[1.0, -1.0])),
+            ("out/bias.npy", f32_npy(&[1], &[0.25])),
+        ]);
+        network.load_npz(&archive, "hidden, out").unwrap();
+        assert_eq!(network.get_weights(0).unwrap(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(network.get_biases(0).unwrap(), vec![0.5, -0.5]);
+        assert_eq!(network.get_weights(1).unwrap(), vec![1.0, -1.0]);
+        assert_eq!(network.get_biases(1).unwrap(), vec![0.25]);
+
+        // A missing array leaves the network unchanged
+        let before = network.get_parameters();
+        assert!(network.load_npz(&archive, "").is_err());
+        assert_eq!(network.get_parameters(), before);
+    }
+}
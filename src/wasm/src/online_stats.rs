@@ -0,0 +1,341 @@
+// Incremental statistics for streaming telemetry
+//
+// RunningStats keeps count, mean, variance, min and max of a stream in constant
+// memory with Welford's update:
+//   mean' = mean + (x - mean) / n
+//   M2'   = M2 + (x - mean) · (x - mean')
+// accumulated in f64, so long streams do not drift the way naive sums of squares
+// do. Two accumulators merge with Chan et al.'s pairwise formula, which lets
+// workers summarize their own shards and combine the results.
+//
+// QuantileSketch estimates quantiles with the P² algorithm (Jain & Chlamtac,
+// 1985): five markers per quantile track the minimum, the maximum, the quantile
+// and two points halfway to it, and are nudged towards their ideal positions with
+// piecewise-parabolic interpolation as values arrive. Memory and update cost are
+// constant per quantile. Until five values have arrived the estimate is exact,
+// interpolated between the sorted values.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::check_finite;
+use crate::error::NeuralError;
+use crate::stats::Summary;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    // Sum of squared deviations from the mean
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for RunningStats {
+    fn default() -> Self {
+        RunningStats { count: 0, mean: 0.0, m2: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+}
+
+#[wasm_bindgen]
+impl RunningStats {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RunningStats {
+        RunningStats::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn push(&mut self, value: f32) -> Result<(), NeuralError> {
+        check_finite(&[value])?;
+        self.add(value as f64);
+        Ok(())
+    }
+
+    // Push a batch of samples; nothing is added if any of them is non-finite
+    #[wasm_bindgen]
+    pub fn push_many(&mut self, values: &[f32]) -> Result<(), NeuralError> {
+        check_finite(values)?;
+        for &value in values {
+            self.add(value as f64);
+        }
+        Ok(())
+    }
+
+    // Fold another accumulator's samples into this one
+    #[wasm_bindgen]
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count as f64 * other.count as f64 / count as f64);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        *self = RunningStats::default();
+    }
+
+    // Number of samples; a u64 count is a BigInt in JavaScript
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // Mean of the samples, 0 before the first one
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    // Population variance, 0 before the first sample
+    #[wasm_bindgen(getter)]
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    // Unbiased (n - 1) variance, 0 before the second sample
+    #[wasm_bindgen(getter)]
+    pub fn sample_variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    // Smallest sample, +Infinity before the first one
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    // Largest sample, -Infinity before the first one
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    // The same figures summarize() reports for a buffer holding every sample
+    #[wasm_bindgen]
+    pub fn summary(&self) -> Result<Summary, NeuralError> {
+        if self.count == 0 {
+            return Err(NeuralError::InvalidConfiguration("statistics of an empty stream".to_string()));
+        }
+        let n = self.count as f64;
+        let variance = self.variance();
+        Ok(Summary {
+            count: self.count as usize,
+            sum: (self.mean * n) as f32,
+            mean: self.mean as f32,
+            variance: variance as f32,
+            std_dev: variance.sqrt() as f32,
+            min: self.min as f32,
+            max: self.max as f32,
+            // Σx² = M2 + n · mean²
+            l2_norm: (self.m2 + n * self.mean * self.mean).sqrt() as f32,
+        })
+    }
+}
+
+impl RunningStats {
+    pub(crate) fn add(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+}
+
+// One P² estimator; markers are 0-based, positions 1-based as in the paper
+#[derive(Debug, Clone, PartialEq)]
+struct P2Estimator {
+    quantile: f64,
+    count: u64,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Estimator {
+    fn new(quantile: f64) -> P2Estimator {
+        let p = quantile;
+        P2Estimator {
+            quantile,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        if self.count < 5 {
+            // Keep the first values sorted in the marker heights
+            let filled = self.count as usize;
+            let at = self.heights[..filled].partition_point(|&height| height <= value);
+            self.heights.copy_within(at..filled, at + 1);
+            self.heights[at] = value;
+            self.count += 1;
+            return;
+        }
+        self.count += 1;
+
+        // Cell the value falls in, widening the extremes if needed
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|&i| value < self.heights[i]).map_or(3, |i| i - 1)
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        // Move the middle markers towards their desired positions
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            let room_up = self.positions[i + 1] - self.positions[i] > 1.0;
+            let room_down = self.positions[i - 1] - self.positions[i] < -1.0;
+            if (offset >= 1.0 && room_up) || (offset <= -1.0 && room_down) {
+                let step = offset.signum();
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] = if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                    parabolic
+                } else {
+                    self.linear(i, step)
+                };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i] + step * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+
+    fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            1..=4 => {
+                // Exact, interpolated between the sorted values
+                let sorted = &self.heights[..self.count as usize];
+                let rank = self.quantile * (sorted.len() - 1) as f64;
+                let below = rank.floor() as usize;
+                let above = (below + 1).min(sorted.len() - 1);
+                Some(sorted[below] + (rank - below as f64) * (sorted[above] - sorted[below]))
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+}
+
+// Streaming estimates of a fixed set of quantiles
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileSketch {
+    estimators: Vec<P2Estimator>,
+}
+
+#[wasm_bindgen]
+impl QuantileSketch {
+    // Track each probability in `quantiles`, e.g. [0.5, 0.9, 0.99]; each must lie in (0, 1)
+    #[wasm_bindgen(constructor)]
+    pub fn new(quantiles: &[f32]) -> Result<QuantileSketch, NeuralError> {
+        if quantiles.is_empty() {
+            return Err(NeuralError::InvalidConfiguration("quantile sketch needs at least one quantile".to_string()));
+        }
+        if quantiles.iter().any(|&q| !(q > 0.0 && q < 1.0)) {
+            return Err(NeuralError::InvalidConfiguration("quantiles must lie in (0, 1)".to_string()));
+        }
+        Ok(QuantileSketch { estimators: quantiles.iter().map(|&q| P2Estimator::new(q as f64)).collect() })
+    }
+
+    #[wasm_bindgen]
+    pub fn push(&mut self, value: f32) -> Result<(), NeuralError> {
+        self.push_many(&[value])
+    }
+
+    // Push a batch of samples; nothing is added if any of them is non-finite
+    #[wasm_bindgen]
+    pub fn push_many(&mut self, values: &[f32]) -> Result<(), NeuralError> {
+        check_finite(values)?;
+        for estimator in self.estimators.iter_mut() {
+            for &value in values {
+                estimator.add(value as f64);
+            }
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        for estimator in self.estimators.iter_mut() {
+            *estimator = P2Estimator::new(estimator.quantile);
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u64 {
+        self.estimators[0].count
+    }
+
+    // The tracked probabilities, in construction order
+    #[wasm_bindgen]
+    pub fn quantiles(&self) -> Vec<f32> {
+        self.estimators.iter().map(|estimator| estimator.quantile as f32).collect()
+    }
+
+    // Current estimate of every tracked quantile, in construction order
+    #[wasm_bindgen]
+    pub fn estimates(&self) -> Result<Vec<f32>, NeuralError> {
+        (0..self.estimators.len()).map(|index| self.estimate(index)).collect()
+    }
+
+    // Current estimate of the quantile at `index` in quantiles()
+    #[wasm_bindgen]
+    pub fn estimate(&self, index: usize) -> Result<f32, NeuralError> {
+        let estimator = self.estimators.get(index).ok_or(NeuralError::IndexOutOfRange { index, len: self.estimators.len() })?;
+        estimator
+            .estimate()
+            .map(|value| value as f32)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("quantile of an empty stream".to_string()))
+    }
+}
@@ -0,0 +1,625 @@
+// ONNX model import (inference only)
+//
+// Decodes the ModelProto protobuf directly, reading only the fields needed, and
+// maps a single chain of operators onto dense layers:
+//   Gemm(x, B, C)        one layer; alpha, beta and transB are folded in, transA must be 0
+//   MatMul(x, W)         starts a layer with W as [in][out]
+//   Add(h, b)            bias of the layer MatMul started
+//   Relu, Sigmoid, Tanh  activation of the preceding layer
+//   Softmax              same, over the last axis only
+// Each operator must consume the previous one's output, and tensors must be float32
+// initializers stored in the model (no external data). Anything else, including
+// branches and other operators, is rejected with the offending node named.
+
+use std::collections::HashMap;
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+
+// TensorProto.DataType.FLOAT
+const ONNX_FLOAT: u64 = 1;
+// TensorProto.DataLocation.EXTERNAL
+const ONNX_EXTERNAL: u64 = 1;
+
+pub fn parse_onnx(bytes: &[u8]) -> NeuralResult<NeuralNetwork> {
+    let mut graph = None;
+    let mut fields = ProtoReader::new(bytes);
+    while let Some((field, value)) = fields.next_field()? {
+        // ModelProto.graph
+        if field == 7 {
+            graph = Some(value.bytes()?);
+        }
+    }
+    let graph = Graph::decode(graph.ok_or_else(|| invalid("model has no graph"))?)?;
+    graph.to_network()
+}
+
+fn invalid(reason: &str) -> NeuralError {
+    NeuralError::InvalidFormat(format!("ONNX: {}", reason))
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    op_type: String,
+    domain: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    attributes: HashMap<String, Attribute>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Attribute {
+    Float(f32),
+    Int(i64),
+    // Strings, tensors and lists; no supported operator reads these
+    Other,
+}
+
+#[derive(Debug)]
+struct Tensor {
+    dims: Vec<usize>,
+    values: Vec<f32>,
+}
+
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    initializers: HashMap<String, Tensor>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+// A dense layer as it is assembled from the operator chain
+struct PendingLayer {
+    inputs: usize,
+    outputs: usize,
+    // Row-major [outputs][inputs]
+    weights: Vec<f32>,
+    biases: Option<Vec<f32>>,
+    activation: Option<ActivationKind>,
+}
+
+impl Graph {
+    fn decode(bytes: &[u8]) -> NeuralResult<Graph> {
+        let mut graph = Graph::default();
+        let mut fields = ProtoReader::new(bytes);
+        while let Some((field, value)) = fields.next_field()? {
+            match field {
+                1 => graph.nodes.push(Node::decode(value.bytes()?)?),
+                5 => {
+                    let (name, tensor) = decode_tensor(value.bytes()?)?;
+                    graph.initializers.insert(name, tensor);
+                }
+                11 => graph.inputs.push(value_info_name(value.bytes()?)?),
+                12 => graph.outputs.push(value_info_name(value.bytes()?)?),
+                _ => {}
+            }
+        }
+        Ok(graph)
+    }
+
+    fn to_network(&self) -> NeuralResult<NeuralNetwork> {
+        // Older exporters also list every initializer as a graph input
+        let mut data_inputs = self.inputs.iter().filter(|name| !self.initializers.contains_key(*name));
+        let (Some(input), None) = (data_inputs.next(), data_inputs.next()) else {
+            return Err(invalid("graph must have exactly one non-initializer input"));
+        };
+        let [output] = self.outputs.as_slice() else {
+            return Err(invalid("graph must have exactly one output"));
+        };
+
+        let mut current = input.as_str();
+        let mut layers: Vec<PendingLayer> = Vec::new();
+        for node in &self.nodes {
+            if !node.domain.is_empty() && node.domain != "ai.onnx" {
+                return Err(node.error(&format!("operator domain '{}' is not supported", node.domain)));
+            }
+            let [node_output] = node.outputs.as_slice() else {
+                return Err(node.error("expected exactly one output"));
+            };
+            if !node.inputs.iter().any(|name| name == current) {
+                return Err(node.error("does not consume the previous operator's output; only single chains are supported"));
+            }
+            match node.op_type.as_str() {
+                "Gemm" => layers.push(self.gemm(node, current)?),
+                "MatMul" => layers.push(self.matmul(node, current)?),
+                "Add" => {
+                    let bias = self.constant_operand(node, current)?;
+                    let layer = match layers.last_mut() {
+                        Some(layer) if layer.biases.is_none() && layer.activation.is_none() => layer,
+                        _ => return Err(node.error("Add must directly follow a MatMul")),
+                    };
+                    layer.biases = Some(broadcast_bias(node, bias, layer.outputs)?);
+                }
+                "Relu" | "Sigmoid" | "Tanh" | "Softmax" => {
+                    let activation = match node.op_type.as_str() {
+                        "Relu" => ActivationKind::ReLU,
+                        "Sigmoid" => ActivationKind::Sigmoid,
+                        "Tanh" => ActivationKind::Tanh,
+                        _ => {
+                            // Opset 13+ defaults to the last axis, earlier opsets to axis 1,
+                            // which is also the last axis of a [batch, features] tensor
+                            match node.attributes.get("axis") {
+                                None | Some(Attribute::Int(-1)) | Some(Attribute::Int(1)) => {}
+                                _ => return Err(node.error("Softmax is only supported over the last axis")),
+                            }
+                            ActivationKind::Softmax
+                        }
+                    };
+                    match layers.last_mut() {
+                        Some(layer) if layer.activation.is_none() => layer.activation = Some(activation),
+                        _ => return Err(node.error("activation must follow a Gemm, MatMul or Add")),
+                    }
+                }
+                other => return Err(node.error(&format!("unsupported operator '{}'", other))),
+            }
+            current = node_output;
+        }
+        if current != output {
+            return Err(invalid("graph output is not produced by the operator chain"));
+        }
+
+        let Some(first) = layers.first() else {
+            return Err(invalid("graph has no layers"));
+        };
+        let mut network = NeuralNetwork::new(first.inputs)?;
+        for (index, layer) in layers.into_iter().enumerate() {
+            if layer.inputs != network.output_size() {
+                return Err(NeuralError::DimensionMismatch { expected: network.output_size(), actual: layer.inputs });
+            }
+            network.add_layer(layer.outputs, layer.activation.unwrap_or(ActivationKind::Linear))?;
+            network.set_weights(index, &layer.weights)?;
+            network.set_biases(index, &layer.biases.unwrap_or_else(|| vec![0.0; layer.outputs]))?;
+        }
+        Ok(network)
+    }
+
+    // Y = alpha · A · op(B) + beta · C
+    fn gemm(&self, node: &Node, current: &str) -> NeuralResult<PendingLayer> {
+        if node.inputs.first().map(String::as_str) != Some(current) {
+            return Err(node.error("Gemm must take the previous output as A"));
+        }
+        if node.int_attribute("transA", 0) != 0 {
+            return Err(node.error("transA is not supported"));
+        }
+        let alpha = node.float_attribute("alpha", 1.0);
+        let beta = node.float_attribute("beta", 1.0);
+        let b = self.initializer(node, 1)?;
+        let [rows, cols] = b.dims[..] else {
+            return Err(node.error("B must be a matrix"));
+        };
+        if rows == 0 || cols == 0 {
+            return Err(node.error("weight operand has an empty dimension"));
+        }
+        // Weights are [out][in], which is B itself when transB is set
+        let (inputs, outputs, mut weights) = if node.int_attribute("transB", 0) != 0 {
+            (cols, rows, b.values.clone())
+        } else {
+            (rows, cols, transpose(&b.values, rows, cols))
+        };
+        for weight in weights.iter_mut() {
+            *weight *= alpha;
+        }
+        let biases = match node.inputs.get(2).filter(|name| !name.is_empty()) {
+            Some(_) => {
+                let mut biases = broadcast_bias(node, self.initializer(node, 2)?, outputs)?;
+                for bias in biases.iter_mut() {
+                    *bias *= beta;
+                }
+                Some(biases)
+            }
+            None => None,
+        };
+        Ok(PendingLayer { inputs, outputs, weights, biases, activation: None })
+    }
+
+    fn matmul(&self, node: &Node, current: &str) -> NeuralResult<PendingLayer> {
+        if node.inputs.first().map(String::as_str) != Some(current) {
+            return Err(node.error("MatMul must take the previous output as its first operand"));
+        }
+        let w = self.initializer(node, 1)?;
+        let [inputs, outputs] = w.dims[..] else {
+            return Err(node.error("weight operand must be a matrix"));
+        };
+        if inputs == 0 || outputs == 0 {
+            return Err(node.error("weight operand has an empty dimension"));
+        }
+        Ok(PendingLayer { inputs, outputs, weights: transpose(&w.values, inputs, outputs), biases: None, activation: None })
+    }
+
+    fn initializer(&self, node: &Node, operand: usize) -> NeuralResult<&Tensor> {
+        let name = node.inputs.get(operand).ok_or_else(|| node.error(&format!("missing operand {}", operand)))?;
+        self.initializers
+            .get(name)
+            .ok_or_else(|| node.error(&format!("operand '{}' must be a stored initializer", name)))
+    }
+
+    // The operand of a binary node that is not the data flowing through the chain
+    fn constant_operand(&self, node: &Node, current: &str) -> NeuralResult<&Tensor> {
+        match node.inputs.as_slice() {
+            [a, _] if a == current => self.initializer(node, 1),
+            [_, b] if b == current => self.initializer(node, 0),
+            _ => Err(node.error("expected two operands")),
+        }
+    }
+}
+
+impl Node {
+    fn decode(bytes: &[u8]) -> NeuralResult<Node> {
+        let mut node = Node::default();
+        let mut fields = ProtoReader::new(bytes);
+        while let Some((field, value)) = fields.next_field()? {
+            match field {
+                1 => node.inputs.push(value.string()?),
+                2 => node.outputs.push(value.string()?),
+                3 => node.name = value.string()?,
+                4 => node.op_type = value.string()?,
+                5 => {
+                    let (name, attribute) = decode_attribute(value.bytes()?)?;
+                    node.attributes.insert(name, attribute);
+                }
+                7 => node.domain = value.string()?,
+                _ => {}
+            }
+        }
+        Ok(node)
+    }
+
+    fn error(&self, reason: &str) -> NeuralError {
+        let name = if self.name.is_empty() { self.outputs.first().map_or("?", String::as_str) } else { &self.name };
+        invalid(&format!("{} node '{}': {}", self.op_type, name, reason))
+    }
+
+    fn float_attribute(&self, name: &str, default: f32) -> f32 {
+        match self.attributes.get(name) {
+            Some(Attribute::Float(value)) => *value,
+            _ => default,
+        }
+    }
+
+    fn int_attribute(&self, name: &str, default: i64) -> i64 {
+        match self.attributes.get(name) {
+            Some(Attribute::Int(value)) => *value,
+            _ => default,
+        }
+    }
+}
+
+// [rows][cols] -> [cols][rows]
+fn transpose(values: &[f32], rows: usize, cols: usize) -> Vec<f32> {
+    let mut transposed = vec![0.0; values.len()];
+    for (row, chunk) in values.chunks_exact(cols).enumerate() {
+        for (col, &value) in chunk.iter().enumerate() {
+            transposed[col * rows + row] = value;
+        }
+    }
+    transposed
+}
+
+// Bias of shape [], [1], [n] or [1, n] expanded to n values
+fn broadcast_bias(node: &Node, tensor: &Tensor, outputs: usize) -> NeuralResult<Vec<f32>> {
+    match tensor.values.len() {
+        1 => Ok(vec![tensor.values[0]; outputs]),
+        len if len == outputs && tensor.dims.iter().rev().skip(1).all(|&dim| dim == 1) => Ok(tensor.values.clone()),
+        _ => Err(node.error(&format!("bias of shape {:?} does not broadcast to {} outputs", tensor.dims, outputs))),
+    }
+}
+
+fn decode_attribute(bytes: &[u8]) -> NeuralResult<(String, Attribute)> {
+    let (mut name, mut attribute) = (String::new(), Attribute::Other);
+    let mut fields = ProtoReader::new(bytes);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => name = value.string()?,
+            2 => attribute = Attribute::Float(f32::from_bits(value.fixed32()?)),
+            3 => attribute = Attribute::Int(value.varint()? as i64),
+            _ => {}
+        }
+    }
+    Ok((name, attribute))
+}
+
+fn decode_tensor(bytes: &[u8]) -> NeuralResult<(String, Tensor)> {
+    let (mut name, mut dims, mut data_type, mut location) = (String::new(), Vec::new(), ONNX_FLOAT, 0);
+    let (mut float_data, mut raw_data) = (Vec::new(), None);
+    let mut fields = ProtoReader::new(bytes);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => value.for_each_varint(|dim| dims.push(dim))?,
+            2 => data_type = value.varint()?,
+            4 => value.for_each_fixed32(|bits| float_data.push(f32::from_bits(bits)))?,
+            8 => name = value.string()?,
+            9 => raw_data = Some(value.bytes()?),
+            14 => location = value.varint()?,
+            _ => {}
+        }
+    }
+    if location == ONNX_EXTERNAL {
+        return Err(invalid(&format!("tensor '{}' uses external data", name)));
+    }
+    if data_type != ONNX_FLOAT {
+        return Err(invalid(&format!("tensor '{}' has data type {}; only float32 is supported", name, data_type)));
+    }
+    let values = match raw_data {
+        Some(raw) if raw.len() % 4 == 0 => {
+            raw.chunks_exact(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect()
+        }
+        Some(_) => return Err(invalid(&format!("tensor '{}' raw data is not a whole number of floats", name))),
+        None => float_data,
+    };
+
+    let dims = dims
+        .into_iter()
+        .map(|dim| usize::try_from(dim).map_err(|_| invalid(&format!("tensor '{}' has an invalid dimension", name))))
+        .collect::<NeuralResult<Vec<usize>>>()?;
+    let count = dims.iter().try_fold(1usize, |count, &dim| count.checked_mul(dim));
+    if count != Some(values.len()) {
+        return Err(invalid(&format!("tensor '{}' holds {} values for shape {:?}", name, values.len(), dims)));
+    }
+    if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+        return Err(invalid(&format!("tensor '{}' has a non-finite value at index {}", name, index)));
+    }
+    Ok((name, Tensor { dims, values }))
+}
+
+fn value_info_name(bytes: &[u8]) -> NeuralResult<String> {
+    let mut fields = ProtoReader::new(bytes);
+    while let Some((field, value)) = fields.next_field()? {
+        if field == 1 {
+            return value.string();
+        }
+    }
+    Ok(String::new())
+}
+
+// One field's payload in the protobuf wire format
+#[derive(Debug, Clone, Copy)]
+enum WireValue<'a> {
+    Varint(u64),
+    // No field the importer reads is 64-bit fixed, so the payload is skipped
+    Fixed64,
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> WireValue<'a> {
+    fn varint(self) -> NeuralResult<u64> {
+        match self {
+            WireValue::Varint(value) => Ok(value),
+            _ => Err(invalid("expected a varint field")),
+        }
+    }
+
+    fn fixed32(self) -> NeuralResult<u32> {
+        match self {
+            WireValue::Fixed32(value) => Ok(value),
+            _ => Err(invalid("expected a 32-bit field")),
+        }
+    }
+
+    fn bytes(self) -> NeuralResult<&'a [u8]> {
+        match self {
+            WireValue::Bytes(bytes) => Ok(bytes),
+            _ => Err(invalid("expected a length-delimited field")),
+        }
+    }
+
+    fn string(self) -> NeuralResult<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid("string field is not UTF-8"))
+    }
+
+    // Repeated varints arrive one per field or packed into a single byte string
+    fn for_each_varint(self, mut push: impl FnMut(u64)) -> NeuralResult<()> {
+        match self {
+            WireValue::Varint(value) => push(value),
+            WireValue::Bytes(bytes) => {
+                let mut reader = ProtoReader::new(bytes);
+                while !reader.is_empty() {
+                    push(reader.varint()?);
+                }
+            }
+            _ => return Err(invalid("expected varint values")),
+        }
+        Ok(())
+    }
+
+    fn for_each_fixed32(self, mut push: impl FnMut(u32)) -> NeuralResult<()> {
+        match self {
+            WireValue::Fixed32(value) => push(value),
+            WireValue::Bytes(bytes) if bytes.len() % 4 == 0 => {
+                for chunk in bytes.chunks_exact(4) {
+                    push(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+                }
+            }
+            _ => return Err(invalid("expected 32-bit values")),
+        }
+        Ok(())
+    }
+}
+
+// Protobuf wire-format field reader; fails cleanly on truncated input
+struct ProtoReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(bytes: &'a [u8]) -> ProtoReader<'a> {
+        ProtoReader { bytes, offset: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn next_field(&mut self) -> NeuralResult<Option<(u64, WireValue<'a>)>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => WireValue::Varint(self.varint()?),
+            1 => {
+                self.take(8)?;
+                WireValue::Fixed64
+            }
+            2 => {
+                let len = usize::try_from(self.varint()?).map_err(|_| invalid("field length overflows"))?;
+                WireValue::Bytes(self.take(len)?)
+            }
+            5 => WireValue::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().expect("4-byte slice"))),
+            wire_type => return Err(invalid(&format!("unsupported protobuf wire type {}", wire_type))),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+
+    fn varint(&mut self) -> NeuralResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.offset).ok_or_else(|| invalid("truncated varint"))?;
+            self.offset += 1;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint is longer than 10 bytes"))
+    }
+
+    fn take(&mut self, len: usize) -> NeuralResult<&'a [u8]> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(|| invalid("truncated field"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal protobuf writer for building test models
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        varint(out, field << 3 | 2);
+        varint(out, bytes.len() as u64);
+        out.extend_from_slice(bytes);
+    }
+
+    fn int_field(out: &mut Vec<u8>, field: u64, value: u64) {
+        varint(out, field << 3);
+        varint(out, value);
+    }
+
+    fn tensor(name: &str, dims: &[u64], values: &[f32]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for &dim in dims {
+            int_field(&mut out, 1, dim);
+        }
+        int_field(&mut out, 2, ONNX_FLOAT);
+        bytes_field(&mut out, 8, name.as_bytes());
+        let raw: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+        bytes_field(&mut out, 9, &raw);
+        out
+    }
+
+    fn node(op: &str, inputs: &[&str], output: &str, attributes: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for input in inputs {
+            bytes_field(&mut out, 1, input.as_bytes());
+        }
+        bytes_field(&mut out, 2, output.as_bytes());
+        bytes_field(&mut out, 4, op.as_bytes());
+        for attribute in attributes {
+            bytes_field(&mut out, 5, attribute);
+        }
+        out
+    }
+
+    fn int_attribute(name: &str, value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        bytes_field(&mut out, 1, name.as_bytes());
+        int_field(&mut out, 3, value);
+        out
+    }
+
+    fn model(nodes: &[Vec<u8>], initializers: &[Vec<u8>], output: &str) -> Vec<u8> {
+        let mut graph = Vec::new();
+        for node in nodes {
+            bytes_field(&mut graph, 1, node);
+        }
+        for initializer in initializers {
+            bytes_field(&mut graph, 5, initializer);
+        }
+        let mut value_info = Vec::new();
+        bytes_field(&mut value_info, 1, b"x");
+        bytes_field(&mut graph, 11, &value_info);
+        let mut value_info = Vec::new();
+        bytes_field(&mut value_info, 1, output.as_bytes());
+        bytes_field(&mut graph, 12, &value_info);
+
+        let mut model = Vec::new();
+        int_field(&mut model, 1, 8);
+        bytes_field(&mut model, 7, &graph);
+        model
+    }
+
+    #[test]
+    fn imports_gemm_and_matmul_chain() {
+        // Gemm with transB: weights already [out][in]
+        let gemm = node("Gemm", &["x", "w1", "b1"], "h", &[int_attribute("transB", 1)]);
+        let relu = node("Relu", &["h"], "a", &[]);
+        let matmul = node("MatMul", &["a", "w2"], "m", &[]);
+        let add = node("Add", &["b2", "m"], "y", &[]);
+        let initializers = [
+            tensor("w1", &[2, 3], &[1.0, 0.0, -1.0, 0.5, 0.5, 0.5]),
+            tensor("b1", &[2], &[0.0, -1.0]),
+            tensor("w2", &[2, 1], &[2.0, -3.0]),
+            tensor("b2", &[1], &[0.25]),
+        ];
+        let bytes = model(&[gemm, relu, matmul, add], &initializers, "y");
+        let network = NeuralNetwork::from_onnx(&bytes).unwrap();
+
+        assert_eq!(network.input_size(), 3);
+        assert_eq!(network.layer_activation(0).unwrap(), ActivationKind::ReLU);
+        assert_eq!(network.get_weights(1).unwrap(), vec![2.0, -3.0]);
+        // h = [1 - 3, 0.5·6 - 1] = [-2, 2] -> relu [0, 2] -> 2·0 - 3·2 + 0.25
+        assert_eq!(network.forward(&[1.0, 2.0, 3.0]).unwrap(), vec![-5.75]);
+    }
+
+    #[test]
+    fn rejects_unsupported_operators_by_name() {
+        let conv = node("Conv", &["x", "w"], "y", &[]);
+        let bytes = model(&[conv], &[tensor("w", &[1, 1], &[1.0])], "y");
+        let error = NeuralNetwork::from_onnx(&bytes).unwrap_err();
+        assert!(error.to_string().contains("unsupported operator 'Conv'"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_truncated_models() {
+        let bytes = model(&[node("MatMul", &["x", "w"], "y", &[])], &[tensor("w", &[1, 1], &[1.0])], "y");
+        assert!(matches!(NeuralNetwork::from_onnx(&bytes[..bytes.len() - 3]), Err(NeuralError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn rejects_empty_weight_dimensions() {
+        let models = [
+            model(&[node("MatMul", &["x", "w"], "y", &[])], &[tensor("w", &[3, 0], &[])], "y"),
+            model(&[node("Gemm", &["x", "w"], "y", &[])], &[tensor("w", &[3, 0], &[])], "y"),
+            model(&[node("Gemm", &["x", "w"], "y", &[int_attribute("transB", 1)])], &[tensor("w", &[0, 3], &[])], "y"),
+        ];
+        for bytes in models {
+            let error = NeuralNetwork::from_onnx(&bytes).unwrap_err();
+            assert!(error.to_string().contains("weight operand has an empty dimension"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+// Connection optimization strategies
+//
+// NeuralRuntime.optimize_connections hands a vector of connection strengths in
+// [0, 1] to the runtime's ConnectionOptimizer. Each agent owns its runtime, so
+// agents can run different strategies side by side. Strategies may read a
+// per-connection signal vector supplied alongside the weights:
+//   Jitter           w += U(-jitter/2, jitter/2)                  (no signal)
+//   GradientPruning  zero the prune_fraction of weights with the lowest saliency
+//                    |w·g|, g = signal (loss gradient); |w| without a signal
+//   Hebbian          w += learning_rate · c, c = signal (mean pre·post co-activity)
+//   WeightDecay      w -= decay · w                                (no signal)
+//   Annealing        per-connection Metropolis step on the energy Σ s·w with
+//                    proposals of width `jitter`; the temperature cools by
+//                    `cooling` after every call
+// Every strategy clamps the result back to [0, 1].
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::stats::Summary;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizerKind {
+    Jitter = 0,
+    GradientPruning = 1,
+    Hebbian = 2,
+    WeightDecay = 3,
+    Annealing = 4,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptimizerParams {
+    // Width of random perturbations (Jitter, Annealing proposals)
+    pub jitter: f32,
+    pub learning_rate: f32,
+    pub decay: f32,
+    // Fraction of connections zeroed per GradientPruning call
+    pub prune_fraction: f32,
+    // Starting temperature and per-call cooling factor for Annealing
+    pub temperature: f32,
+    pub cooling: f32,
+}
+
+impl Default for OptimizerParams {
+    fn default() -> Self {
+        OptimizerParams {
+            jitter: 0.1,
+            learning_rate: 0.01,
+            decay: 0.001,
+            prune_fraction: 0.1,
+            temperature: 1.0,
+            cooling: 0.95,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl OptimizerParams {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> OptimizerParams {
+        OptimizerParams::default()
+    }
+}
+
+impl OptimizerParams {
+    pub fn validate(&self) -> NeuralResult<()> {
+        let values = [self.jitter, self.learning_rate, self.decay, self.prune_fraction, self.temperature, self.cooling];
+        if values.iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err(NeuralError::InvalidConfiguration("optimizer parameters must be finite and non-negative".to_string()));
+        }
+        if self.decay > 1.0 || self.prune_fraction > 1.0 || self.cooling > 1.0 {
+            return Err(NeuralError::InvalidConfiguration("decay, prune_fraction and cooling must not exceed 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+pub trait ConnectionOptimizer {
+    fn kind(&self) -> OptimizerKind;
+
+    // Update `weights` in place. `signals` is empty or one value per weight;
+    // `random` yields uniform samples in [0, 1).
+    fn optimize(&mut self, weights: &mut [f32], signals: &[f32], random: &mut dyn FnMut() -> f32);
+
+    fn params(&self) -> OptimizerParams;
+
+    // Strategy state that evolves across calls, for runtime snapshots
+    fn state(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    fn restore_state(&mut self, _state: &[f32]) {}
+}
+
+pub fn create(kind: OptimizerKind, params: &OptimizerParams) -> NeuralResult<Box<dyn ConnectionOptimizer>> {
+    params.validate()?;
+    let params = *params;
+    Ok(match kind {
+        OptimizerKind::Jitter => Box::new(Jitter { params }),
+        OptimizerKind::GradientPruning => Box::new(GradientPruning { params }),
+        OptimizerKind::Hebbian => Box::new(Hebbian { params }),
+        OptimizerKind::WeightDecay => Box::new(WeightDecay { params }),
+        OptimizerKind::Annealing => Box::new(Annealing { params, temperature: params.temperature }),
+    })
+}
+
+// Jitter with default parameters, the strategy a new runtime starts with
+pub fn default_optimizer() -> Box<dyn ConnectionOptimizer> {
+    Box::new(Jitter { params: OptimizerParams::default() })
+}
+
+// Summary of a connection vector, for comparing before and after an optimization
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionStats {
+    pub count: usize,
+    pub mean: f32,
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+    pub l2_norm: f32,
+    // Connections at exactly zero, e.g. pruned ones
+    pub zeros: usize,
+}
+
+impl ConnectionStats {
+    pub fn of(weights: &[f32]) -> ConnectionStats {
+        let Some(summary) = Summary::of(weights, crate::check_simd_support()) else {
+            return ConnectionStats { count: 0, mean: 0.0, std_dev: 0.0, min: 0.0, max: 0.0, l2_norm: 0.0, zeros: 0 };
+        };
+        ConnectionStats {
+            count: summary.count,
+            mean: summary.mean,
+            std_dev: summary.std_dev,
+            min: summary.min,
+            max: summary.max,
+            l2_norm: summary.l2_norm,
+            zeros: weights.iter().filter(|&&w| w == 0.0).count(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct OptimizationReport {
+    kind: OptimizerKind,
+    connections: Vec<f32>,
+    before: ConnectionStats,
+    after: ConnectionStats,
+    changed: usize,
+}
+
+#[wasm_bindgen]
+impl OptimizationReport {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> OptimizerKind {
+        self.kind
+    }
+
+    // The optimized connection strengths
+    #[wasm_bindgen(getter)]
+    pub fn connections(&self) -> Vec<f32> {
+        self.connections.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn before(&self) -> ConnectionStats {
+        self.before
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn after(&self) -> ConnectionStats {
+        self.after
+    }
+
+    // Connections whose value differs from the input
+    #[wasm_bindgen(getter)]
+    pub fn changed(&self) -> usize {
+        self.changed
+    }
+}
+
+impl OptimizationReport {
+    pub fn new(kind: OptimizerKind, before: &[f32], after: Vec<f32>) -> OptimizationReport {
+        OptimizationReport {
+            kind,
+            before: ConnectionStats::of(before),
+            after: ConnectionStats::of(&after),
+            changed: before.iter().zip(&after).filter(|(old, new)| old != new).count(),
+            connections: after,
+        }
+    }
+}
+
+fn clamp_unit(weights: &mut [f32]) {
+    for weight in weights.iter_mut() {
+        *weight = weight.clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Debug)]
+struct Jitter {
+    params: OptimizerParams,
+}
+
+impl ConnectionOptimizer for Jitter {
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Jitter
+    }
+
+    fn params(&self) -> OptimizerParams {
+        self.params
+    }
+
+    fn optimize(&mut self, weights: &mut [f32], _signals: &[f32], random: &mut dyn FnMut() -> f32) {
+        for weight in weights.iter_mut() {
+            *weight += (random() - 0.5) * self.params.jitter;
+        }
+        clamp_unit(weights);
+    }
+}
+
+#[derive(Debug)]
+struct GradientPruning {
+    params: OptimizerParams,
+}
+
+impl ConnectionOptimizer for GradientPruning {
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::GradientPruning
+    }
+
+    fn params(&self) -> OptimizerParams {
+        self.params
+    }
+
+    // First-order estimate of the loss change from removing each connection
+    fn optimize(&mut self, weights: &mut [f32], signals: &[f32], _random: &mut dyn FnMut() -> f32) {
+        let prune = (self.params.prune_fraction * weights.len() as f32) as usize;
+        if prune > 0 {
+            let saliency = |index: usize| match signals.get(index) {
+                Some(gradient) => (weights[index] * gradient).abs(),
+                None => weights[index].abs(),
+            };
+            let mut order: Vec<(f32, usize)> = (0..weights.len()).map(|index| (saliency(index), index)).collect();
+            order.select_nth_unstable_by(prune - 1, |a, b| a.0.total_cmp(&b.0));
+            for &(_, index) in &order[..prune] {
+                weights[index] = 0.0;
+            }
+        }
+        clamp_unit(weights);
+    }
+}
+
+#[derive(Debug)]
+struct Hebbian {
+    params: OptimizerParams,
+}
+
+impl ConnectionOptimizer for Hebbian {
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Hebbian
+    }
+
+    fn params(&self) -> OptimizerParams {
+        self.params
+    }
+
+    fn optimize(&mut self, weights: &mut [f32], signals: &[f32], _random: &mut dyn FnMut() -> f32) {
+        for (weight, coactivity) in weights.iter_mut().zip(signals) {
+            *weight += self.params.learning_rate * coactivity;
+        }
+        clamp_unit(weights);
+    }
+}
+
+#[derive(Debug)]
+struct WeightDecay {
+    params: OptimizerParams,
+}
+
+impl ConnectionOptimizer for WeightDecay {
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::WeightDecay
+    }
+
+    fn params(&self) -> OptimizerParams {
+        self.params
+    }
+
+    fn optimize(&mut self, weights: &mut [f32], _signals: &[f32], _random: &mut dyn FnMut() -> f32) {
+        for weight in weights.iter_mut() {
+            *weight -= self.params.decay * *weight;
+        }
+        clamp_unit(weights);
+    }
+}
+
+#[derive(Debug)]
+struct Annealing {
+    params: OptimizerParams,
+    temperature: f32,
+}
+
+impl ConnectionOptimizer for Annealing {
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Annealing
+    }
+
+    fn params(&self) -> OptimizerParams {
+        self.params
+    }
+
+    // The current temperature
+    fn state(&self) -> Vec<f32> {
+        vec![self.temperature]
+    }
+
+    fn restore_state(&mut self, state: &[f32]) {
+        if let Some(&temperature) = state.first() {
+            self.temperature = temperature;
+        }
+    }
+
+    // Without a signal every proposal is free, so this degrades to a random walk
+    fn optimize(&mut self, weights: &mut [f32], signals: &[f32], random: &mut dyn FnMut() -> f32) {
+        for (index, weight) in weights.iter_mut().enumerate() {
+            let proposal = (*weight + (random() - 0.5) * self.params.jitter).clamp(0.0, 1.0);
+            let delta_energy = signals.get(index).map_or(0.0, |cost| cost * (proposal - *weight));
+            let accept = delta_energy <= 0.0
+                || (self.temperature > 0.0 && random() < (-delta_energy / self.temperature).exp());
+            if accept {
+                *weight = proposal;
+            }
+        }
+        clamp_unit(weights);
+        self.temperature *= self.params.cooling;
+    }
+}
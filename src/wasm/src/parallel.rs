@@ -0,0 +1,87 @@
+// Sharding of batch work across a thread pool (cargo feature `threads`)
+//
+// Row-major batches are split into contiguous groups of samples, one per
+// thread, so every shard reads and writes disjoint slices. Without the feature,
+// or with a thread count of 1, the whole batch runs on the calling thread.
+
+use crate::error::NeuralResult;
+
+// Threads the pool can actually run; 1 when built without `threads`
+pub fn available_threads() -> usize {
+    #[cfg(feature = "threads")]
+    {
+        rayon::current_num_threads().max(1)
+    }
+    #[cfg(not(feature = "threads"))]
+    {
+        1
+    }
+}
+
+// Run `kernel(input_shard, output_shard)` over `threads` shards of a batch whose
+// samples are `input_stride` and `output_stride` floats wide
+pub fn for_each_shard<F>(
+    inputs: &[f32],
+    input_stride: usize,
+    outputs: &mut [f32],
+    output_stride: usize,
+    threads: usize,
+    kernel: F,
+) -> NeuralResult<()>
+where
+    F: Fn(&[f32], &mut [f32]) -> NeuralResult<()> + Sync,
+{
+    let samples = inputs.len().checked_div(input_stride).unwrap_or(0);
+    if threads <= 1 || samples <= 1 || output_stride == 0 {
+        return kernel(inputs, outputs);
+    }
+
+    let per_shard = samples.div_ceil(threads.min(samples));
+    run_shards(inputs, per_shard * input_stride, outputs, per_shard * output_stride, kernel)
+}
+
+#[cfg(feature = "threads")]
+fn run_shards<F>(inputs: &[f32], input_chunk: usize, outputs: &mut [f32], output_chunk: usize, kernel: F) -> NeuralResult<()>
+where
+    F: Fn(&[f32], &mut [f32]) -> NeuralResult<()> + Sync,
+{
+    use rayon::prelude::*;
+
+    inputs
+        .par_chunks(input_chunk)
+        .zip(outputs.par_chunks_mut(output_chunk))
+        .try_for_each(|(input, output)| kernel(input, output))
+}
+
+#[cfg(not(feature = "threads"))]
+fn run_shards<F>(inputs: &[f32], input_chunk: usize, outputs: &mut [f32], output_chunk: usize, kernel: F) -> NeuralResult<()>
+where
+    F: Fn(&[f32], &mut [f32]) -> NeuralResult<()> + Sync,
+{
+    inputs
+        .chunks(input_chunk)
+        .zip(outputs.chunks_mut(output_chunk))
+        .try_for_each(|(input, output)| kernel(input, output))
+}
+
+// Apply `task` to every item, each item a separate unit the pool's workers can
+// steal, and return the results in item order
+#[cfg(feature = "threads")]
+pub fn map_tasks<T, R, F>(items: Vec<T>, task: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(usize, T) -> R + Sync + Send,
+{
+    use rayon::prelude::*;
+
+    items.into_par_iter().with_max_len(1).enumerate().map(|(index, item)| task(index, item)).collect()
+}
+
+#[cfg(not(feature = "threads"))]
+pub fn map_tasks<T, R, F>(items: Vec<T>, task: F) -> Vec<R>
+where
+    F: Fn(usize, T) -> R,
+{
+    items.into_iter().enumerate().map(|(index, item)| task(index, item)).collect()
+}
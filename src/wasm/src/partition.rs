@@ -0,0 +1,204 @@
+// k-way partitioning of a MeshGraph so one mesh can be split across workers
+//
+// Two phases in the style of METIS's initial partitioning and refinement:
+//   growing     parts are filled one at a time to an equal share of the nodes
+//               still unassigned, starting from the lowest-degree unassigned node
+//               and always taking the unassigned node with the most edges into
+//               the growing part; a part that runs out of neighbours continues
+//               from a new seed
+//   refinement  boundary passes in seeded random order move a node to the
+//               neighbouring part holding most of its edges when that cuts fewer
+//               edges (or as many while evening out sizes), provided no part
+//               grows past ceil(n / k · (1 + imbalance)); at most
+//               REFINEMENT_PASSES passes, stopping early once nothing moves
+// The cut counts edges between parts, whatever their weights.
+
+use std::collections::BinaryHeap;
+use wasm_bindgen::prelude::*;
+
+use crate::error::NeuralError;
+use crate::mesh::MeshGraph;
+use crate::rng::Rng;
+
+const REFINEMENT_PASSES: usize = 8;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshPartition {
+    assignments: Vec<u32>,
+    sizes: Vec<usize>,
+    cut_edges: usize,
+}
+
+#[wasm_bindgen]
+impl MeshPartition {
+    // Part of every node
+    #[wasm_bindgen(getter)]
+    pub fn assignments(&self) -> Vec<u32> {
+        self.assignments.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn part_count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    // Node count of every part
+    #[wasm_bindgen]
+    pub fn part_sizes(&self) -> Vec<u32> {
+        self.sizes.iter().map(|&size| size as u32).collect()
+    }
+
+    // Edges whose ends lie in different parts
+    #[wasm_bindgen(getter)]
+    pub fn cut_edges(&self) -> usize {
+        self.cut_edges
+    }
+
+    // Largest part over the mean part size; 1 is perfect balance
+    #[wasm_bindgen(getter)]
+    pub fn imbalance(&self) -> f64 {
+        let mean = self.assignments.len() as f64 / self.sizes.len() as f64;
+        self.sizes.iter().copied().max().unwrap_or(0) as f64 / mean
+    }
+
+    // Ids of the nodes in `part`, ascending
+    #[wasm_bindgen]
+    pub fn nodes_in(&self, part: u32) -> Result<Vec<u32>, NeuralError> {
+        if part as usize >= self.sizes.len() {
+            return Err(NeuralError::IndexOutOfRange { index: part as usize, len: self.sizes.len() });
+        }
+        Ok((0..self.assignments.len() as u32).filter(|&node| self.assignments[node as usize] == part).collect())
+    }
+}
+
+#[wasm_bindgen]
+impl MeshGraph {
+    // Split the nodes into `parts` parts of near-equal size with few edges between
+    // them; `imbalance` is the fraction by which a part may exceed the mean size
+    #[wasm_bindgen]
+    pub fn partition(&self, parts: usize, imbalance: f64, seed: u64) -> Result<MeshPartition, NeuralError> {
+        let nodes = self.node_count();
+        if parts == 0 || parts > nodes {
+            return Err(NeuralError::InvalidConfiguration("part count must be between 1 and the node count".to_string()));
+        }
+        if !imbalance.is_finite() || imbalance < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("imbalance must be finite and non-negative".to_string()));
+        }
+        let max_size = ((nodes as f64 / parts as f64) * (1.0 + imbalance)).ceil().max(nodes.div_ceil(parts) as f64) as usize;
+
+        let mut assignments = self.grow_parts(parts);
+        let mut sizes = vec![0usize; parts];
+        for &part in &assignments {
+            sizes[part as usize] += 1;
+        }
+        self.refine(&mut assignments, &mut sizes, max_size, &mut Rng::new(seed));
+
+        let cut_edges = (0..nodes)
+            .map(|node| {
+                let own = assignments[node];
+                self.neighbours_of(node)
+                    .iter()
+                    .filter(|&&(neighbour, _)| neighbour as usize > node && assignments[neighbour as usize] != own)
+                    .count()
+            })
+            .sum();
+        Ok(MeshPartition { assignments, sizes, cut_edges })
+    }
+}
+
+impl MeshGraph {
+    fn grow_parts(&self, parts: usize) -> Vec<u32> {
+        let nodes = self.node_count();
+        let mut assignments = vec![u32::MAX; nodes];
+        let mut links = vec![0u32; nodes];
+        let mut by_degree: Vec<usize> = (0..nodes).collect();
+        by_degree.sort_by_key(|&node| self.neighbours_of(node).len());
+        let mut next_seed = 0;
+
+        let mut unassigned = nodes;
+        for part in 0..parts {
+            let target = unassigned.div_ceil(parts - part);
+            unassigned -= target;
+            let part = part as u32;
+            links.fill(0);
+            // (edges into the part, lowest id first); stale entries are skipped
+            let mut frontier: BinaryHeap<(u32, std::cmp::Reverse<usize>)> = BinaryHeap::new();
+            let mut size = 0;
+            while size < target {
+                let node = match frontier.pop() {
+                    Some((count, std::cmp::Reverse(node))) => {
+                        if assignments[node] != u32::MAX || count != links[node] {
+                            continue;
+                        }
+                        node
+                    }
+                    None => {
+                        while next_seed < nodes && assignments[by_degree[next_seed]] != u32::MAX {
+                            next_seed += 1;
+                        }
+                        if next_seed == nodes {
+                            return assignments;
+                        }
+                        by_degree[next_seed]
+                    }
+                };
+                assignments[node] = part;
+                size += 1;
+                for &(neighbour, _) in self.neighbours_of(node) {
+                    let neighbour = neighbour as usize;
+                    if assignments[neighbour] == u32::MAX {
+                        links[neighbour] += 1;
+                        frontier.push((links[neighbour], std::cmp::Reverse(neighbour)));
+                    }
+                }
+            }
+        }
+        assignments
+    }
+
+    fn refine(&self, assignments: &mut [u32], sizes: &mut [usize], max_size: usize, rng: &mut Rng) {
+        let nodes = self.node_count();
+        let mut order: Vec<usize> = (0..nodes).collect();
+        let mut counts: Vec<(u32, u32)> = Vec::new();
+        for _ in 0..REFINEMENT_PASSES {
+            // Fisher–Yates shuffle
+            for index in (1..nodes).rev() {
+                order.swap(index, rng.next_u64() as usize % (index + 1));
+            }
+            let mut moved = false;
+            for &node in &order {
+                let own = assignments[node];
+                if sizes[own as usize] <= 1 {
+                    continue;
+                }
+                counts.clear();
+                for &(neighbour, _) in self.neighbours_of(node) {
+                    let part = assignments[neighbour as usize];
+                    match counts.iter_mut().find(|(candidate, _)| *candidate == part) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((part, 1)),
+                    }
+                }
+                let internal = counts.iter().find(|(part, _)| *part == own).map_or(0, |&(_, count)| count);
+                let best = counts
+                    .iter()
+                    .filter(|&&(part, _)| part != own && sizes[part as usize] < max_size)
+                    .max_by_key(|&&(part, count)| (count, std::cmp::Reverse(sizes[part as usize])));
+                let Some(&(target, external)) = best else {
+                    continue;
+                };
+                let evens_out = external == internal && sizes[target as usize] + 1 < sizes[own as usize];
+                if external > internal || evens_out {
+                    assignments[node] = target;
+                    sizes[own as usize] -= 1;
+                    sizes[target as usize] += 1;
+                    moved = true;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// Synaptic plasticity rules
+//
+// Spike-timing-dependent plasticity (pair-based):
+//   Δt = t_post - t_pre
+//   Δt > 0  ->  w += A+ · exp(-Δt / tau+)   (pre before post: potentiation)
+//   Δt < 0  ->  w -= A- · exp( Δt / tau-)   (post before pre: depression)
+// Weights are clipped to [w_min, w_max] after every update.
+//
+// Rate-based Hebbian rules, for presynaptic activity x, postsynaptic activity y
+// and learning rate η:
+//   Hebbian       Δw =  η · y · x
+//   AntiHebbian   Δw = -η · y · x            (decorrelates the two sides)
+//   Oja           Δw =  η · y · (x - y · w)  (keeps each neuron's weight vector near unit length)
+// Dense weights are row-major [post × pre], one row per postsynaptic neuron; the
+// update of a row is a scaled axpy, so it runs on the SIMD kernels in linalg.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::features::simd_dispatch;
+use crate::linalg;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StdpParams {
+    pub a_plus: f32,
+    pub a_minus: f32,
+    pub tau_plus: f32,
+    pub tau_minus: f32,
+    pub w_min: f32,
+    pub w_max: f32,
+}
+
+impl Default for StdpParams {
+    fn default() -> Self {
+        StdpParams {
+            a_plus: 0.01,
+            a_minus: 0.012,
+            tau_plus: 20.0,
+            tau_minus: 20.0,
+            w_min: 0.0,
+            w_max: 1.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StdpParams {
+    // Slightly depression-dominated defaults keep total weight bounded
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> StdpParams {
+        StdpParams::default()
+    }
+}
+
+impl StdpParams {
+    pub fn validate(&self) -> NeuralResult<()> {
+        let values = [self.a_plus, self.a_minus, self.tau_plus, self.tau_minus, self.w_min, self.w_max];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(NeuralError::InvalidConfiguration("STDP parameters must be finite".to_string()));
+        }
+        if self.tau_plus <= 0.0 || self.tau_minus <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("STDP time constants must be positive".to_string()));
+        }
+        if self.w_min > self.w_max {
+            return Err(NeuralError::InvalidConfiguration("w_min must not exceed w_max".to_string()));
+        }
+        Ok(())
+    }
+
+    // Weight change for one pre/post spike pair
+    pub fn pair_update(&self, t_pre: f32, t_post: f32) -> f32 {
+        let dt = t_post - t_pre;
+        if dt > 0.0 {
+            self.a_plus * (-dt / self.tau_plus).exp()
+        } else if dt < 0.0 {
+            -self.a_minus * (dt / self.tau_minus).exp()
+        } else {
+            0.0
+        }
+    }
+
+    pub fn clip(&self, weight: f32) -> f32 {
+        weight.clamp(self.w_min, self.w_max)
+    }
+}
+
+// Exponentially decaying spike traces used for online STDP
+#[derive(Debug, Clone)]
+pub struct StdpTraces {
+    pub params: StdpParams,
+    pub pre: Vec<f32>,
+    pub post: Vec<f32>,
+}
+
+impl StdpTraces {
+    pub fn new(params: StdpParams, neuron_count: usize) -> StdpTraces {
+        StdpTraces {
+            params,
+            pre: vec![0.0; neuron_count],
+            post: vec![0.0; neuron_count],
+        }
+    }
+
+    pub fn decay(&mut self, dt: f32) {
+        let pre_decay = (-dt / self.params.tau_plus).exp();
+        let post_decay = (-dt / self.params.tau_minus).exp();
+        for trace in self.pre.iter_mut() {
+            *trace *= pre_decay;
+        }
+        for trace in self.post.iter_mut() {
+            *trace *= post_decay;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pre.fill(0.0);
+        self.post.fill(0.0);
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HebbianRule {
+    Hebbian = 0,
+    AntiHebbian = 1,
+    Oja = 2,
+}
+
+impl HebbianRule {
+    // (η', c) with Δw = η' · y · (x - c · y · w)
+    fn coefficients(self, learning_rate: f32) -> (f32, f32) {
+        match self {
+            HebbianRule::Hebbian => (learning_rate, 0.0),
+            HebbianRule::AntiHebbian => (-learning_rate, 0.0),
+            HebbianRule::Oja => (learning_rate, 1.0),
+        }
+    }
+}
+
+// Apply `rule` to dense weights [post × pre] in place
+pub(crate) fn hebbian_dense(
+    rule: HebbianRule,
+    weights: &mut [f32],
+    pre: &[f32],
+    post: &[f32],
+    learning_rate: f32,
+    simd: bool,
+) -> NeuralResult<()> {
+    check_hebbian_inputs(&[pre, post], learning_rate)?;
+    if weights.len() != pre.len() * post.len() {
+        return Err(NeuralError::DimensionMismatch { expected: pre.len() * post.len(), actual: weights.len() });
+    }
+    let (rate, decay) = rule.coefficients(learning_rate);
+    for (row, &y) in weights.chunks_exact_mut(pre.len().max(1)).zip(post) {
+        if decay != 0.0 {
+            linalg::scale(1.0 - rate * decay * y * y, row, simd);
+        }
+        linalg::axpy(rate * y, pre, row, simd);
+    }
+    Ok(())
+}
+
+// Apply `rule` to independent synapses: weights[k] connects activity pre[k] to post[k]
+pub(crate) fn hebbian_pairs(rule: HebbianRule, weights: &mut [f32], pre: &[f32], post: &[f32], learning_rate: f32, simd: bool) {
+    let (rate, decay) = rule.coefficients(learning_rate);
+    simd_dispatch!(
+        simd && weights.len() >= 4,
+        simd_hebbian_pairs(weights, pre, post, rate, decay),
+        scalar_hebbian_pairs(weights, pre, post, rate, decay)
+    )
+}
+
+pub(crate) fn check_hebbian_inputs(activities: &[&[f32]], learning_rate: f32) -> NeuralResult<()> {
+    if !learning_rate.is_finite() || learning_rate < 0.0 {
+        return Err(NeuralError::InvalidConfiguration("learning rate must be finite and non-negative".to_string()));
+    }
+    for activity in activities {
+        if let Some(index) = activity.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_hebbian_pairs(weights: &mut [f32], pre: &[f32], post: &[f32], rate: f32, decay: f32) {
+    let mut w_chunks = weights.chunks_exact_mut(4);
+    let mut x_chunks = pre.chunks_exact(4);
+    let mut y_chunks = post.chunks_exact(4);
+    let (rate_vec, decay_vec) = (f32x4_splat(rate), f32x4_splat(decay));
+    for ((w_chunk, x_chunk), y_chunk) in (&mut w_chunks).zip(&mut x_chunks).zip(&mut y_chunks) {
+        let (w, y) = (simd::load(w_chunk), simd::load(y_chunk));
+        let delta = f32x4_sub(simd::load(x_chunk), f32x4_mul(decay_vec, f32x4_mul(y, w)));
+        simd::store(w_chunk, f32x4_add(w, f32x4_mul(rate_vec, f32x4_mul(y, delta))));
+    }
+    scalar_hebbian_pairs(w_chunks.into_remainder(), x_chunks.remainder(), y_chunks.remainder(), rate, decay);
+}
+
+fn scalar_hebbian_pairs(weights: &mut [f32], pre: &[f32], post: &[f32], rate: f32, decay: f32) {
+    for ((w, x), y) in weights.iter_mut().zip(pre).zip(post) {
+        *w += rate * y * (x - decay * y * *w);
+    }
+}
+
+// Hebbian update of dense weights [post × pre]: w += η · y · xᵀ
+#[wasm_bindgen]
+pub fn hebbian_update(weights: &mut [f32], pre: &[f32], post: &[f32], learning_rate: f32) -> Result<(), NeuralError> {
+    hebbian_dense(HebbianRule::Hebbian, weights, pre, post, learning_rate, crate::check_simd_support())
+}
+
+// Anti-Hebbian update of dense weights [post × pre]: w -= η · y · xᵀ
+#[wasm_bindgen]
+pub fn anti_hebbian_update(weights: &mut [f32], pre: &[f32], post: &[f32], learning_rate: f32) -> Result<(), NeuralError> {
+    hebbian_dense(HebbianRule::AntiHebbian, weights, pre, post, learning_rate, crate::check_simd_support())
+}
+
+// Oja's rule on dense weights [post × pre]: each row w += η · y · (x - y · w)
+#[wasm_bindgen]
+pub fn oja_update(weights: &mut [f32], pre: &[f32], post: &[f32], learning_rate: f32) -> Result<(), NeuralError> {
+    hebbian_dense(HebbianRule::Oja, weights, pre, post, learning_rate, crate::check_simd_support())
+}
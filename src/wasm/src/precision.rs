@@ -0,0 +1,141 @@
+// Weight storage precision and IEEE 754 half-precision conversion
+//
+// F16 weights are packed as u16 bit patterns and widened to f32 one row at a time
+// during inference, halving weight memory while keeping f32 arithmetic.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    F32 = 0,
+    F16 = 1,
+    Int8 = 2,
+}
+
+impl Precision {
+    // Name used by model specs and describe()
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Precision::F32 => "f32",
+            Precision::F16 => "f16",
+            Precision::Int8 => "int8",
+        }
+    }
+}
+
+// Round-to-nearest-even conversion; out-of-range values become ±inf, NaN stays NaN
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if half_exponent <= 0 {
+        // Subnormal half (or zero): shift the implicit-one mantissa into place
+        if half_exponent < -10 {
+            return sign;
+        }
+        let full = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let half_mantissa = full >> shift;
+        let remainder = full & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = remainder > halfway || (remainder == halfway && half_mantissa & 1 == 1);
+        return sign | (half_mantissa + round_up as u32) as u16;
+    }
+
+    let half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round_up = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+    // A mantissa carry rolls into the exponent, which correctly rounds up to inf at the top
+    sign | (half + round_up as u32) as u16
+}
+
+// Exact widening: shift the magnitude into f32 position and rescale the exponent
+// bias with one multiply, which also normalizes half subnormals
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let magnitude = (half & 0x7fff) as u32;
+    if magnitude >= 0x7c00 {
+        return f32::from_bits(sign | 0x7f80_0000 | ((magnitude & 0x03ff) << 13));
+    }
+    let scaled = f32::from_bits(magnitude << 13) * F16_EXPONENT_RESCALE;
+    f32::from_bits(sign | scaled.to_bits())
+}
+
+// 2^(127 - 15)
+const F16_EXPONENT_RESCALE: f32 = 5.192_297e33;
+
+pub fn f32_slice_to_f16(values: &[f32]) -> Vec<u16> {
+    values.iter().map(|&value| f32_to_f16(value)).collect()
+}
+
+pub fn f16_slice_to_f32(halves: &[u16]) -> Vec<f32> {
+    let mut values = vec![0.0; halves.len()];
+    widen_into(halves, &mut values, false);
+    values
+}
+
+// Convert `src` into the equally long `dst`
+pub fn widen_into(src: &[u16], dst: &mut [f32], simd: bool) {
+    simd_dispatch!(simd && src.len() >= 8, simd_widen(src, dst), scalar_widen(src, dst));
+}
+
+fn scalar_widen(src: &[u16], dst: &mut [f32]) {
+    for (out, &half) in dst.iter_mut().zip(src) {
+        *out = f16_to_f32(half);
+    }
+}
+
+// Eight halves per iteration, split into two u32x4 halves of the same bit trick
+#[cfg(target_feature = "simd128")]
+fn simd_widen(src: &[u16], dst: &mut [f32]) {
+    let len = src.len().min(dst.len());
+    let mut src_chunks = src[..len].chunks_exact(8);
+    let mut dst_chunks = dst[..len].chunks_exact_mut(8);
+
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        let halves = simd::load(src_chunk);
+        let (low, high) = dst_chunk.split_at_mut(4);
+        simd::store(low, simd_widen_lanes(u32x4_extend_low_u16x8(halves)));
+        simd::store(high, simd_widen_lanes(u32x4_extend_high_u16x8(halves)));
+    }
+
+    scalar_widen(src_chunks.remainder(), dst_chunks.into_remainder());
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_widen_lanes(halves: v128) -> v128 {
+    let sign = i32x4_shl(v128_and(halves, u32x4_splat(0x8000)), 16);
+    let magnitude = v128_and(halves, u32x4_splat(0x7fff));
+    let finite = f32x4_mul(i32x4_shl(magnitude, 13), f32x4_splat(F16_EXPONENT_RESCALE));
+    let special = v128_or(u32x4_splat(0x7f80_0000), i32x4_shl(v128_and(magnitude, u32x4_splat(0x03ff)), 13));
+    let is_special = u32x4_ge(magnitude, u32x4_splat(0x7c00));
+    v128_or(sign, v128_bitselect(special, finite, is_special))
+}
+
+// y[rows] = W[rows×cols] · x[cols] with W stored as f16
+pub fn matvec_f16_into(w: &[u16], x: &[f32], y: &mut [f32], rows: usize, cols: usize, simd: bool) {
+    let mut row_buffer = vec![0.0; cols];
+    for (out, row) in y.iter_mut().zip(w.chunks_exact(cols.max(1))).take(rows) {
+        widen_into(row, &mut row_buffer, simd);
+        *out = crate::linalg::dot(&row_buffer, x, simd);
+    }
+}
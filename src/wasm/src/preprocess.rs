@@ -0,0 +1,323 @@
+// Input preprocessing fitted once and stored with the model
+//
+// A Preprocessor is an ordered list of steps applied to every sample before the
+// first layer. Each step sees the columns produced by the steps before it:
+//   scale   standardize columns first..first+count, by z-score (x - mean) / std or
+//           min-max (x - min) / (max - min); the statistics come from fit()
+//   one_hot replace one column holding a category index with `categories` columns,
+//           1 at the index and 0 elsewhere; values that are not an index in
+//           0..categories (unseen categories) encode as all zeros
+//   clip    clamp columns first..first+count to [min, max]
+// Scaling statistics are population statistics computed with the scalar path, so
+// a fit is reproducible across engines. A constant column scales to 0.
+//
+// Attached to a NeuralNetwork with set_preprocessor, it runs inside every entry
+// point that takes samples (forward, forward_batch, forward_step, train_batch,
+// compute_gradients, the epoch and dataset helpers and the runtime's batch
+// paths), which then take raw samples of input_size() values. It is written into
+// the SASW weight blob, so an exported model normalizes its inputs the same way
+// wherever it is loaded.
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::serialization::{ByteReader, ByteWriter};
+use crate::stats;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingMethod {
+    ZScore = 0,
+    MinMax = 1,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    // y = (x - offset) · factor per column; empty until fitted
+    Scale { method: ScalingMethod, first: usize, count: usize, params: Vec<(f32, f32)> },
+    OneHot { column: usize, categories: usize },
+    Clip { first: usize, count: usize, min: f32, max: f32 },
+}
+
+impl Step {
+    fn output_width(&self, width: usize) -> usize {
+        match *self {
+            Step::OneHot { categories, .. } => width - 1 + categories,
+            _ => width,
+        }
+    }
+
+    fn is_fitted(&self) -> bool {
+        !matches!(self, Step::Scale { params, .. } if params.is_empty())
+    }
+
+    // Statistics of a scale step from row-major data of `width` columns
+    fn fit(&mut self, data: &[f32], width: usize) {
+        let Step::Scale { method, first, count, params } = self else {
+            return;
+        };
+        params.clear();
+        let mut column = Vec::with_capacity(data.len() / width);
+        for index in *first..*first + *count {
+            column.clear();
+            column.extend(data.chunks_exact(width).map(|row| row[index]));
+            let (offset, range) = match method {
+                ScalingMethod::ZScore => {
+                    let mean = stats::mean(&column, false).unwrap_or(0.0);
+                    (mean, stats::variance(&column, mean, false).unwrap_or(0.0).sqrt())
+                }
+                ScalingMethod::MinMax => {
+                    let (min, max) = stats::min_max(&column, false).unwrap_or((0.0, 0.0));
+                    (min, max - min)
+                }
+            };
+            let factor = if range > 0.0 && range.is_finite() { 1.0 / range } else { 0.0 };
+            params.push((offset, factor));
+        }
+    }
+
+    fn apply(&self, data: Vec<f32>, width: usize) -> Vec<f32> {
+        match self {
+            Step::Scale { first, count, params, .. } => map_columns(data, width, *first..*first + *count, |index, value| {
+                let (offset, factor) = params[index];
+                (value - offset) * factor
+            }),
+            Step::Clip { first, count, min, max } => map_columns(data, width, *first..*first + *count, |_, value| value.clamp(*min, *max)),
+            Step::OneHot { column, categories } => {
+                let mut encoded = Vec::with_capacity(data.len() / width * self.output_width(width));
+                for row in data.chunks_exact(width) {
+                    encoded.extend_from_slice(&row[..*column]);
+                    let value = row[*column];
+                    let hot = (value >= 0.0 && value.fract() == 0.0).then_some(value as usize).filter(|&index| index < *categories);
+                    encoded.extend((0..*categories).map(|index| if Some(index) == hot { 1.0 } else { 0.0 }));
+                    encoded.extend_from_slice(&row[*column + 1..]);
+                }
+                encoded
+            }
+        }
+    }
+}
+
+// Rewrite `columns` of every row with f(position within columns, value)
+fn map_columns(mut data: Vec<f32>, width: usize, columns: Range<usize>, f: impl Fn(usize, f32) -> f32) -> Vec<f32> {
+    for row in data.chunks_exact_mut(width) {
+        for (index, value) in row[columns.clone()].iter_mut().enumerate() {
+            *value = f(index, *value);
+        }
+    }
+    data
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preprocessor {
+    input_width: usize,
+    steps: Vec<Step>,
+}
+
+#[wasm_bindgen]
+impl Preprocessor {
+    // An empty pipeline over raw samples of `input_width` values
+    #[wasm_bindgen(constructor)]
+    pub fn new(input_width: usize) -> Result<Preprocessor, NeuralError> {
+        if input_width == 0 {
+            return Err(NeuralError::InvalidConfiguration("preprocessor input width must be non-zero".to_string()));
+        }
+        Ok(Preprocessor { input_width, steps: Vec::new() })
+    }
+
+    // Append a scaling step over columns first..first+count; needs fit()
+    #[wasm_bindgen]
+    pub fn scale(&mut self, method: ScalingMethod, first: usize, count: usize) -> Result<(), NeuralError> {
+        self.check_columns(first, count)?;
+        self.steps.push(Step::Scale { method, first, count, params: Vec::new() });
+        Ok(())
+    }
+
+    // Append a one-hot expansion of `column` into `categories` columns
+    #[wasm_bindgen]
+    pub fn one_hot(&mut self, column: usize, categories: usize) -> Result<(), NeuralError> {
+        self.check_columns(column, 1)?;
+        if categories == 0 {
+            return Err(NeuralError::InvalidConfiguration("one-hot encoding needs at least one category".to_string()));
+        }
+        self.output_width()
+            .checked_add(categories)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("one-hot width overflows".to_string()))?;
+        self.steps.push(Step::OneHot { column, categories });
+        Ok(())
+    }
+
+    // Append a clamp of columns first..first+count to [min, max]
+    #[wasm_bindgen]
+    pub fn clip(&mut self, first: usize, count: usize, min: f32, max: f32) -> Result<(), NeuralError> {
+        self.check_columns(first, count)?;
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Err(NeuralError::InvalidConfiguration("clip bounds must be finite with min <= max".to_string()));
+        }
+        self.steps.push(Step::Clip { first, count, min, max });
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn input_width(&self) -> usize {
+        self.input_width
+    }
+
+    // Values per sample after every step, i.e. the network input size it feeds
+    #[wasm_bindgen(getter)]
+    pub fn output_width(&self) -> usize {
+        self.steps.iter().fold(self.input_width, |width, step| step.output_width(width))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    // Whether every scaling step has statistics
+    #[wasm_bindgen(getter)]
+    pub fn is_fitted(&self) -> bool {
+        self.steps.iter().all(Step::is_fitted)
+    }
+
+    // Compute scaling statistics from row-major raw samples [rows × input_width],
+    // each step fitted on the output of the steps before it
+    #[wasm_bindgen]
+    pub fn fit(&mut self, data: &[f32], rows: usize) -> Result<(), NeuralError> {
+        self.check_data(data, rows)?;
+        let mut values = data.to_vec();
+        let mut width = self.input_width;
+        for step in self.steps.iter_mut() {
+            step.fit(&values, width);
+            values = step.apply(values, width);
+            width = step.output_width(width);
+        }
+        Ok(())
+    }
+
+    // Preprocessed samples [rows × output_width]
+    #[wasm_bindgen]
+    pub fn transform(&self, data: &[f32], rows: usize) -> Result<Vec<f32>, NeuralError> {
+        if !self.is_fitted() {
+            return Err(NeuralError::InvalidConfiguration("preprocessor must be fitted before use".to_string()));
+        }
+        self.check_data(data, rows)?;
+        let mut values = data.to_vec();
+        let mut width = self.input_width;
+        for step in &self.steps {
+            values = step.apply(values, width);
+            width = step.output_width(width);
+        }
+        Ok(values)
+    }
+
+    #[wasm_bindgen]
+    pub fn fit_transform(&mut self, data: &[f32], rows: usize) -> Result<Vec<f32>, NeuralError> {
+        self.fit(data, rows)?;
+        self.transform(data, rows)
+    }
+}
+
+impl Preprocessor {
+    // Columns must exist in the output of the steps added so far
+    fn check_columns(&self, first: usize, count: usize) -> NeuralResult<()> {
+        let width = self.output_width();
+        if count == 0 || first.checked_add(count).is_none_or(|end| end > width) {
+            return Err(NeuralError::IndexOutOfRange { index: first.saturating_add(count.max(1) - 1), len: width });
+        }
+        Ok(())
+    }
+
+    fn check_data(&self, data: &[f32], rows: usize) -> NeuralResult<()> {
+        if rows == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        let expected = rows
+            .checked_mul(self.input_width)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("batch size overflows".to_string()))?;
+        if data.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: data.len() });
+        }
+        if let Some(index) = data.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(())
+    }
+
+    // Layout: input_width u32, step_count u32, then per step
+    //   kind u8 (0 = scale, 1 = one-hot, 2 = clip), method u8, reserved [u8; 2], and
+    //   scale:   first u32, count u32, fitted_count u32, fitted_count × { offset f32, factor f32 }
+    //   one-hot: column u32, categories u32
+    //   clip:    first u32, count u32, min f32, max f32
+    pub(crate) fn encode(&self, writer: &mut ByteWriter) {
+        writer.u32(self.input_width as u32);
+        writer.u32(self.steps.len() as u32);
+        for step in &self.steps {
+            match step {
+                Step::Scale { method, first, count, params } => {
+                    writer.bytes(&[0, *method as u8, 0, 0]);
+                    for value in [*first, *count, params.len()] {
+                        writer.u32(value as u32);
+                    }
+                    for &(offset, factor) in params {
+                        writer.f32(offset);
+                        writer.f32(factor);
+                    }
+                }
+                Step::OneHot { column, categories } => {
+                    writer.bytes(&[1, 0, 0, 0]);
+                    writer.u32(*column as u32);
+                    writer.u32(*categories as u32);
+                }
+                Step::Clip { first, count, min, max } => {
+                    writer.bytes(&[2, 0, 0, 0]);
+                    writer.u32(*first as u32);
+                    writer.u32(*count as u32);
+                    writer.f32(*min);
+                    writer.f32(*max);
+                }
+            }
+        }
+    }
+
+    // Rebuilds through the same checks as the builder methods
+    pub(crate) fn decode(reader: &mut ByteReader) -> NeuralResult<Preprocessor> {
+        let invalid = |err: NeuralError| NeuralError::InvalidFormat(format!("invalid preprocessor: {}", err));
+        let mut preprocessor = Preprocessor::new(reader.u32()? as usize).map_err(invalid)?;
+        let step_count = reader.u32()?;
+        for index in 0..step_count {
+            let kind = reader.u8()?;
+            let method = reader.u8()?;
+            reader.bytes(2)?;
+            let (a, b) = (reader.u32()? as usize, reader.u32()? as usize);
+            match kind {
+                0 => {
+                    let method = match method {
+                        0 => ScalingMethod::ZScore,
+                        1 => ScalingMethod::MinMax,
+                        other => return Err(NeuralError::InvalidFormat(format!("step {} has unknown scaling method {}", index, other))),
+                    };
+                    preprocessor.scale(method, a, b).map_err(invalid)?;
+                    let fitted = reader.u32()? as usize;
+                    if fitted != 0 && fitted != b {
+                        return Err(NeuralError::InvalidFormat(format!("step {} has {} statistics for {} columns", index, fitted, b)));
+                    }
+                    let values = reader.f32_vec(fitted * 2)?;
+                    if values.iter().any(|x| !x.is_finite()) {
+                        return Err(NeuralError::InvalidFormat(format!("step {} has non-finite statistics", index)));
+                    }
+                    if let Some(Step::Scale { params, .. }) = preprocessor.steps.last_mut() {
+                        params.extend(values.chunks_exact(2).map(|pair| (pair[0], pair[1])));
+                    }
+                }
+                1 => preprocessor.one_hot(a, b).map_err(invalid)?,
+                2 => preprocessor.clip(a, b, reader.f32()?, reader.f32()?).map_err(invalid)?,
+                other => return Err(NeuralError::InvalidFormat(format!("step {} has unknown kind {}", index, other))),
+            }
+        }
+        Ok(preprocessor)
+    }
+}
@@ -0,0 +1,282 @@
+// Differential privacy for shared gradients (DP-SGD)
+//
+// GradientPrivacy.compute_gradients is the private counterpart of
+// NeuralNetwork.compute_gradients. Each sample's gradient is clipped to L2 norm
+// `clip_norm`, the clipped gradients are summed, Gaussian noise with standard
+// deviation noise_multiplier · clip_norm is added to every coordinate and the sum
+// is divided by the batch size. Adding or removing one sample moves the clipped sum
+// by at most clip_norm, so every released blob is a Gaussian mechanism with that
+// sensitivity. The blob's loss is not released (it is written as 0). The sample
+// count is, as usual in DP-SGD, where the batch size is public.
+//
+// Networks with batch norm are refused: it mixes the samples of a batch, so one
+// sample's influence is not confined to its own clipped gradient.
+//
+// The accountant composes releases with Rényi DP. T releases at noise multiplier σ
+// are (α, α·ρ)-RDP for every order α > 1 with ρ = T / (2σ²); minimizing
+// α·ρ + ln(1/δ) / (α - 1) over α gives ε = ρ + 2·sqrt(ρ·ln(1/δ)). It takes no
+// credit for subsampling, so ε is overstated when batches are drawn at random from
+// a larger dataset. With max_epsilon set, a release that would take ε past it is
+// refused with NeuralError::PrivacyBudgetExhausted before any gradient is computed.
+//
+// Noise is drawn from the platform CSPRNG; if that fails, the blob is withheld.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::gradients::GradientSet;
+use crate::logging::{log_event, LogLevel};
+use crate::network::NeuralNetwork;
+use crate::rng::SecureRng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrivacyConfig {
+    // L2 bound on each sample's gradient
+    pub clip_norm: f32,
+    // Noise standard deviation as a multiple of clip_norm
+    pub noise_multiplier: f64,
+    pub delta: f64,
+    // Largest ε the releases may reach (0 = no limit)
+    pub max_epsilon: f64,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        PrivacyConfig::new(1.0, 1.1)
+    }
+}
+
+#[wasm_bindgen]
+impl PrivacyConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(clip_norm: f32, noise_multiplier: f64) -> PrivacyConfig {
+        PrivacyConfig { clip_norm, noise_multiplier, delta: 1e-5, max_epsilon: 0.0 }
+    }
+}
+
+impl PrivacyConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(self.clip_norm.is_finite() && self.clip_norm > 0.0) {
+            return Err(NeuralError::InvalidConfiguration("clip norm must be finite and positive".to_string()));
+        }
+        if !(self.noise_multiplier.is_finite() && self.noise_multiplier > 0.0) {
+            return Err(NeuralError::InvalidConfiguration("noise multiplier must be finite and positive".to_string()));
+        }
+        if !(self.delta > 0.0 && self.delta < 1.0) {
+            return Err(NeuralError::InvalidConfiguration("delta must be in (0, 1)".to_string()));
+        }
+        if !(self.max_epsilon.is_finite() && self.max_epsilon >= 0.0) {
+            return Err(NeuralError::InvalidConfiguration("max epsilon must be finite and non-negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// Differentially private gradient releases of one agent's data, with their ε
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct GradientPrivacy {
+    config: PrivacyConfig,
+    releases: u64,
+    noise: SecureRng,
+}
+
+#[wasm_bindgen]
+impl GradientPrivacy {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &PrivacyConfig) -> Result<GradientPrivacy, NeuralError> {
+        config.validate()?;
+        Ok(GradientPrivacy { config: *config, releases: 0, noise: SecureRng::new() })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn config(&self) -> PrivacyConfig {
+        self.config
+    }
+
+    // Blobs released so far
+    #[wasm_bindgen(getter)]
+    pub fn releases(&self) -> u64 {
+        self.releases
+    }
+
+    // ε spent so far, at the configured δ
+    #[wasm_bindgen(getter)]
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon_after(self.releases)
+    }
+
+    // ε after `releases` blobs in total, for planning how many rounds a budget allows
+    #[wasm_bindgen]
+    pub fn epsilon_after(&self, releases: u64) -> f64 {
+        if releases == 0 {
+            return 0.0;
+        }
+        let rho = releases as f64 / (2.0 * self.config.noise_multiplier * self.config.noise_multiplier);
+        rho + 2.0 * (rho * (1.0 / self.config.delta).ln()).sqrt()
+    }
+
+    // Clipped, noised mean gradients of one batch as a SASG blob for
+    // apply_gradients or a GradientAggregator. Like NeuralNetwork.compute_gradients
+    // the parameters are left unchanged, but dropout draws from the network's RNG.
+    #[wasm_bindgen]
+    pub fn compute_gradients(
+        &mut self,
+        network: &mut NeuralNetwork,
+        inputs: &[f32],
+        targets: &[f32],
+        batch_size: usize,
+    ) -> Result<Vec<u8>, NeuralError> {
+        let epsilon = self.epsilon_after(self.releases + 1);
+        if self.config.max_epsilon > 0.0 && epsilon > self.config.max_epsilon {
+            return Err(NeuralError::PrivacyBudgetExhausted { epsilon, limit: self.config.max_epsilon });
+        }
+        let samples = u32::try_from(batch_size)
+            .map_err(|_| NeuralError::InvalidConfiguration("batch size exceeds u32".to_string()))?;
+        let mut layers = network.clipped_gradient_sum(inputs, targets, batch_size, self.config.clip_norm)?;
+
+        let std_dev = self.config.noise_multiplier * self.config.clip_norm as f64;
+        let scale = 1.0 / batch_size as f64;
+        for layer in layers.iter_mut() {
+            for grad in layer.weights.iter_mut().chain(layer.biases.iter_mut()) {
+                *grad = ((*grad as f64 + std_dev * self.gaussian()) * scale) as f32;
+            }
+        }
+        if self.noise.is_degraded() {
+            return Err(NeuralError::Unavailable("secure random source for privacy noise".to_string()));
+        }
+
+        self.releases += 1;
+        log_event!(LogLevel::Info, "privacy", "released gradients of {} samples; epsilon now {} at delta {}", batch_size, epsilon, self.config.delta);
+        Ok(GradientSet { samples, loss: 0.0, layers }.encode())
+    }
+}
+
+impl GradientPrivacy {
+    // Standard normal draw (Box-Muller)
+    fn gaussian(&mut self) -> f64 {
+        // In (0, 1], so the logarithm is finite
+        let u1 = ((self.noise.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        let u2 = (self.noise.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::ActivationKind;
+    use crate::training;
+
+    // 3 → 4 tanh → 2 with weights large enough that most samples' gradients exceed 1
+    fn network() -> NeuralNetwork {
+        let mut network = NeuralNetwork::new(3).unwrap();
+        network.add_layer(4, ActivationKind::Tanh).unwrap();
+        network.add_layer(2, ActivationKind::Linear).unwrap();
+        let parameters: Vec<f32> = (0..network.get_parameters().len()).map(|i| ((i * 5 % 9) as f32 - 4.0) * 0.6).collect();
+        network.set_parameters(&parameters).unwrap();
+        network
+    }
+
+    const INPUTS: [f32; 12] = [1.0, -2.0, 0.5, 0.3, 0.1, -0.2, -1.5, 2.5, 1.0, 0.0, 0.0, 0.01];
+    const TARGETS: [f32; 8] = [4.0, -4.0, 0.1, 0.2, -6.0, 3.0, 0.0, 0.0];
+
+    // TARGETS with the last sample's target moved close to its output, so its
+    // gradient stays below the clip norm
+    fn targets(network: &NeuralNetwork) -> Vec<f32> {
+        let mut targets = TARGETS.to_vec();
+        let output = network.forward(&INPUTS[9..]).unwrap();
+        targets[6] = output[0] + 0.05;
+        targets[7] = output[1] - 0.05;
+        targets
+    }
+
+    #[test]
+    fn clips_each_sample_gradient() {
+        let clip_norm = 1.0;
+        let mut network = network();
+        let targets = targets(&network);
+        let (mut clipped, mut unclipped) = (0, 0);
+        for (input, target) in INPUTS.chunks_exact(3).zip(targets.chunks_exact(2)) {
+            let raw = GradientSet::decode(&network.compute_gradients(input, target, 1).unwrap()).unwrap().layers;
+            let raw_norm = training::gradient_norm(&raw);
+            let sample = network.clipped_gradient_sum(input, target, 1, clip_norm).unwrap();
+            let norm = training::gradient_norm(&sample);
+            assert!(norm <= clip_norm * (1.0 + 1e-6), "clipped norm {} above {}", norm, clip_norm);
+            if raw_norm > clip_norm {
+                clipped += 1;
+                assert!((norm - clip_norm).abs() < 1e-5);
+            } else {
+                unclipped += 1;
+                assert_eq!(sample, raw);
+            }
+        }
+        assert!(clipped > 0 && unclipped > 0, "{} clipped, {} unclipped", clipped, unclipped);
+
+        // The batch sum is the sum of the clipped samples, so its norm is at most n·C
+        let sum = network.clipped_gradient_sum(&INPUTS, &targets, 4, clip_norm).unwrap();
+        assert!(training::gradient_norm(&sum) <= 4.0 * clip_norm * (1.0 + 1e-6));
+    }
+
+    #[test]
+    fn released_mean_stays_within_clip_norm() {
+        // Noise this small leaves the released mean at the clipped mean
+        let config = PrivacyConfig::new(0.5, 1e-9);
+        let mut privacy = GradientPrivacy::new(&config).unwrap();
+        let mut network = network();
+        let before = network.get_parameters();
+        let blob = privacy.compute_gradients(&mut network, &INPUTS, &TARGETS, 4).unwrap();
+        let released = GradientSet::decode(&blob).unwrap();
+        assert_eq!(released.samples, 4);
+        assert_eq!(released.loss, 0.0);
+        assert!(training::gradient_norm(&released.layers) <= 0.5 * (1.0 + 1e-5));
+        assert_eq!(network.get_parameters(), before);
+        assert_eq!(privacy.releases(), 1);
+    }
+
+    #[test]
+    fn accountant_matches_known_epsilons() {
+        // q = 1 (no subsampling credit): ρ = T / (2σ²), ε = ρ + 2·sqrt(ρ·ln(1/δ))
+        let mut config = PrivacyConfig::new(1.0, 1.0);
+        // σ = 1, T = 2, δ = e^-4: ρ = 1, ε = 1 + 2·2
+        config.delta = (-4.0f64).exp();
+        let privacy = GradientPrivacy::new(&config).unwrap();
+        assert_eq!(privacy.epsilon(), 0.0);
+        assert!((privacy.epsilon_after(2) - 5.0).abs() < 1e-12);
+
+        // σ = 1.1, T = 100, δ = 1e-5
+        let privacy = GradientPrivacy::new(&PrivacyConfig::new(1.0, 1.1)).unwrap();
+        assert!((privacy.epsilon_after(100) - 84.945_276_887_660_24).abs() < 1e-9);
+    }
+
+    #[test]
+    fn refuses_releases_past_the_budget() {
+        let mut config = PrivacyConfig::new(1.0, 1.0);
+        config.delta = (-4.0f64).exp();
+        config.max_epsilon = 5.0;
+        let mut privacy = GradientPrivacy::new(&config).unwrap();
+        let mut network = network();
+        privacy.compute_gradients(&mut network, &INPUTS, &TARGETS, 4).unwrap();
+        privacy.compute_gradients(&mut network, &INPUTS, &TARGETS, 4).unwrap();
+        assert!(matches!(
+            privacy.compute_gradients(&mut network, &INPUTS, &TARGETS, 4),
+            Err(NeuralError::PrivacyBudgetExhausted { limit, .. }) if limit == 5.0
+        ));
+        assert_eq!(privacy.releases(), 2);
+    }
+
+    #[test]
+    fn refuses_batch_norm_networks() {
+        let mut network = NeuralNetwork::new(3).unwrap();
+        network.add_layer(4, ActivationKind::ReLU).unwrap();
+        network.add_batch_norm(1e-5, 0.1).unwrap();
+        network.add_layer(2, ActivationKind::Linear).unwrap();
+        let mut privacy = GradientPrivacy::new(&PrivacyConfig::default()).unwrap();
+        assert!(matches!(
+            privacy.compute_gradients(&mut network, &INPUTS, &TARGETS, 4),
+            Err(NeuralError::InvalidConfiguration(_))
+        ));
+        assert_eq!(privacy.releases(), 0);
+    }
+}
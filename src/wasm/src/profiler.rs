@@ -0,0 +1,249 @@
+// Per-kernel latency profiling for the runtime's operations
+//
+// Each kernel keeps a call count, bytes processed and a latency histogram with
+// quarter-octave buckets (each bucket spans a factor of 2^(1/4) ≈ 1.19), so
+// percentiles are accurate to within that factor however skewed the timings are.
+// Only calls that succeed are recorded.
+
+use std::collections::BTreeMap;
+
+use crate::clock::Clock;
+use crate::error::{NeuralError, NeuralResult};
+use crate::metrics::{format_value, MetricKind, PrometheusWriter};
+use crate::serialization::{ByteReader, ByteWriter};
+
+// 4 buckets per doubling from 1µs; the last bucket also holds anything slower (~33s)
+const BUCKETS_PER_OCTAVE: f64 = 4.0;
+const BUCKET_COUNT: usize = 100;
+
+#[derive(Debug, Clone)]
+struct KernelStats {
+    count: u64,
+    bytes: u64,
+    total_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+    buckets: [u64; BUCKET_COUNT],
+}
+
+impl KernelStats {
+    fn new() -> KernelStats {
+        KernelStats { count: 0, bytes: 0, total_ms: 0.0, min_ms: f64::INFINITY, max_ms: 0.0, buckets: [0; BUCKET_COUNT] }
+    }
+
+    fn record(&mut self, elapsed_ms: f64, bytes: usize) {
+        self.count += 1;
+        self.bytes += bytes as u64;
+        self.total_ms += elapsed_ms;
+        self.min_ms = self.min_ms.min(elapsed_ms);
+        self.max_ms = self.max_ms.max(elapsed_ms);
+        self.buckets[bucket_index(elapsed_ms)] += 1;
+    }
+
+    // Upper bound of the bucket holding the q-quantile, clamped to the observed range
+    fn percentile(&self, q: f64) -> f64 {
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper_ms(index).clamp(self.min_ms, self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str(&format!(
+            "{{\"count\":{},\"bytes\":{},\"total_ms\":{},\"mean_ms\":{},\"min_ms\":{},\"max_ms\":{},\"p50_ms\":{},\"p95_ms\":{},\"p99_ms\":{},\"histogram\":[",
+            self.count,
+            self.bytes,
+            self.total_ms,
+            self.total_ms / self.count as f64,
+            self.min_ms,
+            self.max_ms,
+            self.percentile(0.50),
+            self.percentile(0.95),
+            self.percentile(0.99),
+        ));
+        // Only occupied buckets, as {le_ms, count} pairs in ascending order
+        let occupied = self.buckets.iter().enumerate().filter(|(_, &count)| count > 0);
+        for (position, (index, count)) in occupied.enumerate() {
+            if position > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!("{{\"le_ms\":{},\"count\":{}}}", bucket_upper_ms(index), count));
+        }
+        out.push_str("]}");
+    }
+}
+
+// Prometheus buckets are every 4th boundary, i.e. one per doubling from 2µs to
+// ~16.8s, so the bucket set stays fixed across scrapes; the final boundary is
+// left out because the last fine bucket also holds overflow
+const EXPORTED_BUCKETS: usize = BUCKET_COUNT / 4 - 1;
+
+fn bucket_index(elapsed_ms: f64) -> usize {
+    let micros = elapsed_ms * 1000.0;
+    if micros <= 1.0 {
+        return 0;
+    }
+    ((micros.log2() * BUCKETS_PER_OCTAVE) as usize).min(BUCKET_COUNT - 1)
+}
+
+fn bucket_upper_ms(index: usize) -> f64 {
+    2f64.powf((index + 1) as f64 / BUCKETS_PER_OCTAVE) / 1000.0
+}
+
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    enabled: bool,
+    clock: Clock,
+    kernels: BTreeMap<String, KernelStats>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler { enabled: true, clock: Clock::new(), kernels: BTreeMap::new() }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Timestamp to pass to record() once the kernel has finished
+    pub fn start(&self) -> f64 {
+        if self.enabled {
+            self.clock.now_ms()
+        } else {
+            0.0
+        }
+    }
+
+    pub fn record(&mut self, kernel: &'static str, started_ms: f64, bytes: usize) {
+        if !self.enabled {
+            return;
+        }
+        let elapsed_ms = (self.clock.now_ms() - started_ms).max(0.0);
+        self.record_elapsed(kernel, elapsed_ms, bytes);
+    }
+
+    // For callers that timed the kernel themselves with now_ms()
+    pub fn record_elapsed(&mut self, kernel: &'static str, elapsed_ms: f64, bytes: usize) {
+        if !self.enabled {
+            return;
+        }
+        match self.kernels.get_mut(kernel) {
+            Some(stats) => stats.record(elapsed_ms, bytes),
+            None => {
+                let mut stats = KernelStats::new();
+                stats.record(elapsed_ms, bytes);
+                self.kernels.insert(kernel.to_string(), stats);
+            }
+        }
+    }
+
+    // Read the clock whether or not profiling is enabled
+    pub fn now_ms(&self) -> f64 {
+        self.clock.now_ms()
+    }
+
+    pub fn reset(&mut self) {
+        self.kernels.clear();
+    }
+
+    // Layout: enabled u8, reserved [u8; 3], kernel_count u32, then per kernel
+    //   name_len u16, name, count u64, bytes u64, total_ms f64, min_ms f64, max_ms f64,
+    //   occupied u32, occupied × { bucket u32, count u64 }
+    pub(crate) fn encode(&self, writer: &mut ByteWriter) {
+        writer.bytes(&[self.enabled as u8, 0, 0, 0]);
+        writer.u32(self.kernels.len() as u32);
+        for (name, stats) in &self.kernels {
+            writer.u16(name.len() as u16);
+            writer.bytes(name.as_bytes());
+            writer.u64(stats.count);
+            writer.u64(stats.bytes);
+            for value in [stats.total_ms, stats.min_ms, stats.max_ms] {
+                writer.f64(value);
+            }
+            let occupied: Vec<(usize, u64)> = stats.buckets.iter().copied().enumerate().filter(|&(_, count)| count > 0).collect();
+            writer.u32(occupied.len() as u32);
+            for (index, count) in occupied {
+                writer.u32(index as u32);
+                writer.u64(count);
+            }
+        }
+    }
+
+    // The clock restarts; recorded statistics carry over
+    pub(crate) fn decode(reader: &mut ByteReader) -> NeuralResult<Profiler> {
+        let mut profiler = Profiler::new();
+        profiler.enabled = reader.bytes(4)?[0] != 0;
+        for _ in 0..reader.u32()? {
+            let len = reader.u16()? as usize;
+            let name = std::str::from_utf8(reader.bytes(len)?)
+                .map_err(|_| NeuralError::InvalidFormat("kernel name is not UTF-8".to_string()))?
+                .to_string();
+            let mut stats = KernelStats::new();
+            stats.count = reader.u64()?;
+            stats.bytes = reader.u64()?;
+            stats.total_ms = reader.f64()?;
+            stats.min_ms = reader.f64()?;
+            stats.max_ms = reader.f64()?;
+            for _ in 0..reader.u32()? {
+                let index = reader.u32()? as usize;
+                let bucket = stats.buckets.get_mut(index).ok_or_else(|| NeuralError::InvalidFormat(format!("latency bucket {} out of range", index)))?;
+                *bucket = reader.u64()?;
+            }
+            profiler.kernels.insert(name, stats);
+        }
+        Ok(profiler)
+    }
+
+    // Call and byte counters and a latency histogram per kernel
+    pub(crate) fn write_prometheus(&self, writer: &mut PrometheusWriter) {
+        writer.family("kernel_calls_total", MetricKind::Counter, "Successful kernel calls recorded by the profiler.");
+        for (name, stats) in &self.kernels {
+            writer.sample("kernel_calls_total", &[("kernel", name)], stats.count as f64);
+        }
+        writer.family("kernel_bytes_total", MetricKind::Counter, "Bytes processed by kernel calls.");
+        for (name, stats) in &self.kernels {
+            writer.sample("kernel_bytes_total", &[("kernel", name)], stats.bytes as f64);
+        }
+        writer.family("kernel_latency_seconds", MetricKind::Histogram, "Kernel call latency.");
+        for (name, stats) in &self.kernels {
+            let mut cumulative = 0;
+            for octave in 0..EXPORTED_BUCKETS {
+                cumulative += stats.buckets[octave * 4..octave * 4 + 4].iter().sum::<u64>();
+                let le = metrics_le(bucket_upper_ms(octave * 4 + 3) / 1000.0);
+                writer.sample("kernel_latency_seconds_bucket", &[("kernel", name), ("le", &le)], cumulative as f64);
+            }
+            writer.sample("kernel_latency_seconds_bucket", &[("kernel", name), ("le", "+Inf")], stats.count as f64);
+            writer.sample("kernel_latency_seconds_sum", &[("kernel", name)], stats.total_ms / 1000.0);
+            writer.sample("kernel_latency_seconds_count", &[("kernel", name)], stats.count as f64);
+        }
+    }
+
+    // {"kernels": {name: {count, bytes, total_ms, mean_ms, min_ms, max_ms, p50_ms, p95_ms, p99_ms, histogram}}}
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"kernels\":{");
+        for (position, (name, stats)) in self.kernels.iter().enumerate() {
+            if position > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!("\"{}\":", name));
+            stats.write_json(&mut out);
+        }
+        out.push_str("}}");
+        out
+    }
+}
+
+// Bucket bounds are powers of two in µs; round off the float noise of powf
+fn metrics_le(seconds: f64) -> String {
+    format_value((seconds * 1e6).round() / 1e6)
+}
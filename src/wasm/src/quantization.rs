@@ -0,0 +1,137 @@
+// Int8 quantized inference
+//
+// Weights use affine quantization: real = scale · (q - zero_point), q in [-128, 127].
+// Activations are quantized per call with a symmetric scale (zero_point 0), so a
+// dense layer reduces to an integer dot product plus one dequantizing multiply:
+//   y_i = s_w · s_x · (Σ_j qw_ij · qx_j - zp_w · Σ_j qx_j) + b_i
+// Storing i8 instead of f32 cuts weight memory by 4x.
+
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantParams {
+    pub scale: f32,
+    pub zero_point: i8,
+}
+
+impl QuantParams {
+    pub fn new(scale: f32, zero_point: i8) -> NeuralResult<QuantParams> {
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("quantization scale must be positive and finite".to_string()));
+        }
+        Ok(QuantParams { scale, zero_point })
+    }
+
+    // Asymmetric parameters covering [min, max] of `values`; the range always includes 0
+    // so zero weights stay exact
+    pub fn calibrate(values: &[f32]) -> QuantParams {
+        let min = values.iter().copied().fold(0.0f32, f32::min);
+        let max = values.iter().copied().fold(0.0f32, f32::max);
+        if max <= min {
+            return QuantParams { scale: 1.0, zero_point: 0 };
+        }
+        let scale = (max - min) / 255.0;
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i8;
+        QuantParams { scale, zero_point }
+    }
+
+    // Symmetric parameters for activations: max |x| maps to 127
+    pub fn symmetric(values: &[f32]) -> QuantParams {
+        let max_abs = values.iter().fold(0.0f32, |acc, x| acc.max(x.abs()));
+        let scale = if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 };
+        QuantParams { scale, zero_point: 0 }
+    }
+
+    pub fn quantize(&self, value: f32) -> i8 {
+        (value / self.scale + self.zero_point as f32).round().clamp(-128.0, 127.0) as i8
+    }
+
+    pub fn dequantize(&self, value: i8) -> f32 {
+        self.scale * (value as i32 - self.zero_point as i32) as f32
+    }
+
+    pub fn quantize_slice(&self, values: &[f32]) -> Vec<i8> {
+        values.iter().map(|&value| self.quantize(value)).collect()
+    }
+}
+
+// Row-major [rows][cols] int8 matrix with one set of affine parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub data: Vec<i8>,
+    pub params: QuantParams,
+}
+
+impl QuantizedMatrix {
+    pub fn from_f32(values: &[f32], rows: usize, cols: usize, params: QuantParams) -> QuantizedMatrix {
+        QuantizedMatrix { rows, cols, data: params.quantize_slice(values), params }
+    }
+
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.data.iter().map(|&value| self.params.dequantize(value)).collect()
+    }
+
+    // y[rows] = dequantize(W · x) for float inputs, quantizing `x` on the fly
+    pub fn matvec_into(&self, x: &[f32], y: &mut [f32], simd: bool) -> NeuralResult<()> {
+        if x.len() != self.cols {
+            return Err(NeuralError::DimensionMismatch { expected: self.cols, actual: x.len() });
+        }
+        if y.len() != self.rows {
+            return Err(NeuralError::DimensionMismatch { expected: self.rows, actual: y.len() });
+        }
+
+        let x_params = QuantParams::symmetric(x);
+        let x_q = x_params.quantize_slice(x);
+        let x_sum: i32 = x_q.iter().map(|&value| value as i32).sum();
+        let output_scale = self.params.scale * x_params.scale;
+        let zero_point = self.params.zero_point as i32;
+
+        if self.cols == 0 {
+            y.fill(0.0);
+            return Ok(());
+        }
+        for (out, row) in y.iter_mut().zip(self.data.chunks_exact(self.cols)) {
+            let dot = simd_dispatch!(simd && self.cols >= 16, simd_dot_i8(row, &x_q), scalar_dot_i8(row, &x_q));
+            *out = output_scale * (dot - zero_point * x_sum) as f32;
+        }
+        Ok(())
+    }
+}
+
+// Widen both operands to i16 lanes, then i32x4_dot_i16x8 multiplies lane pairs and
+// adds neighbours into i32 accumulators, so no intermediate product can overflow
+#[cfg(target_feature = "simd128")]
+fn simd_dot_i8(a: &[i8], b: &[i8]) -> i32 {
+    let len = a.len().min(b.len());
+    let a_chunks = a[..len].chunks_exact(16);
+    let b_chunks = b[..len].chunks_exact(16);
+    let tail = scalar_dot_i8(a_chunks.remainder(), b_chunks.remainder());
+    let mut acc = i32x4_splat(0);
+
+    for (a_chunk, b_chunk) in a_chunks.zip(b_chunks) {
+        let a_vec = simd::load(a_chunk);
+        let b_vec = simd::load(b_chunk);
+        let low = i32x4_dot_i16x8(i16x8_extend_low_i8x16(a_vec), i16x8_extend_low_i8x16(b_vec));
+        let high = i32x4_dot_i16x8(i16x8_extend_high_i8x16(a_vec), i16x8_extend_high_i8x16(b_vec));
+        acc = i32x4_add(acc, i32x4_add(low, high));
+    }
+
+    let simd_sum = i32x4_extract_lane::<0>(acc)
+        + i32x4_extract_lane::<1>(acc)
+        + i32x4_extract_lane::<2>(acc)
+        + i32x4_extract_lane::<3>(acc);
+
+    simd_sum + tail
+}
+
+fn scalar_dot_i8(a: &[i8], b: &[i8]) -> i32 {
+    a.iter().zip(b).map(|(&x, &y)| x as i32 * y as i32).sum()
+}
@@ -0,0 +1,96 @@
+// Recorded spike history and its binned views for visualization
+//
+// A SpikingNetwork with recording enabled keeps its most recent spikes in a ring
+// of fixed capacity. The trailing window [now - window_ms, now] is cut into
+// ceil(window_ms / bin_ms) bins, the first being the oldest, and read out as
+//   raster     spike count per (bin, neuron), bins × neurons row-major, saturating
+//              at 255, ready to upload as a heatmap texture
+//   histogram  mean firing rate per bin (Hz) over a range of neurons
+// Spikes exactly at `now` fall in the last bin.
+
+use std::collections::VecDeque;
+
+use crate::error::{NeuralError, NeuralResult};
+
+#[derive(Debug, Clone)]
+pub(crate) struct SpikeLog {
+    capacity: usize,
+    // (neuron, time ms) in time order
+    spikes: VecDeque<(u32, f32)>,
+}
+
+impl SpikeLog {
+    pub(crate) fn new(capacity: usize) -> SpikeLog {
+        SpikeLog { capacity, spikes: VecDeque::with_capacity(capacity) }
+    }
+
+    // Append a spike, dropping the oldest when full
+    pub(crate) fn record(&mut self, neuron: usize, time_ms: f32) {
+        if self.spikes.len() == self.capacity {
+            self.spikes.pop_front();
+        }
+        self.spikes.push_back((neuron as u32, time_ms));
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.spikes.len()
+    }
+
+    pub(crate) fn memory_bytes(&self) -> usize {
+        self.spikes.capacity() * std::mem::size_of::<(u32, f32)>()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.spikes.clear();
+    }
+
+    pub(crate) fn spikes(&self) -> impl Iterator<Item = &(u32, f32)> {
+        self.spikes.iter()
+    }
+
+    // Spikes in the window ending at `now`, newest first, with their bin index
+    fn binned(&self, now: f32, window_ms: f32, bin_ms: f32) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let bins = bin_count(window_ms, bin_ms);
+        let start = now - window_ms;
+        self.spikes.iter().rev().take_while(move |(_, time)| *time >= start).filter(move |(_, time)| *time <= now).map(
+            move |&(neuron, time)| {
+                let bin = (((time - start) / bin_ms) as usize).min(bins - 1);
+                (bin, neuron as usize)
+            },
+        )
+    }
+
+    pub(crate) fn raster(&self, neuron_count: usize, now: f32, window_ms: f32, bin_ms: f32) -> Vec<u8> {
+        let mut raster = vec![0u8; bin_count(window_ms, bin_ms) * neuron_count];
+        for (bin, neuron) in self.binned(now, window_ms, bin_ms) {
+            let cell = &mut raster[bin * neuron_count + neuron];
+            *cell = cell.saturating_add(1);
+        }
+        raster
+    }
+
+    pub(crate) fn histogram(&self, first: usize, count: usize, now: f32, window_ms: f32, bin_ms: f32) -> Vec<f32> {
+        let mut histogram = vec![0.0f32; bin_count(window_ms, bin_ms)];
+        for (bin, neuron) in self.binned(now, window_ms, bin_ms) {
+            if neuron >= first && neuron - first < count {
+                histogram[bin] += 1.0;
+            }
+        }
+        let scale = 1000.0 / (bin_ms * count as f32);
+        for rate in histogram.iter_mut() {
+            *rate *= scale;
+        }
+        histogram
+    }
+}
+
+pub(crate) fn check_window(window_ms: f32, bin_ms: f32) -> NeuralResult<()> {
+    if !window_ms.is_finite() || !bin_ms.is_finite() || window_ms <= 0.0 || bin_ms <= 0.0 || bin_ms > window_ms {
+        return Err(NeuralError::InvalidConfiguration("window and bin width must be positive, with bin_ms <= window_ms".to_string()));
+    }
+    Ok(())
+}
+
+fn bin_count(window_ms: f32, bin_ms: f32) -> usize {
+    (window_ms / bin_ms).ceil().max(1.0) as usize
+}
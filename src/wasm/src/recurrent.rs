@@ -0,0 +1,169 @@
+// Recurrent cells (LSTM and GRU) with hidden state carried across calls
+//
+// A cell holds one weight matrix, row-major [gates·hidden][inputs + hidden], whose
+// rows act on the concatenation [x; h]. Rows are grouped gate by gate: LSTM uses
+// (input, forget, cell, output) and GRU uses (update, reset, candidate). Gate
+// pre-activations and nonlinearities run on the SIMD matvec and activation kernels.
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CellKind {
+    Lstm,
+    Gru,
+}
+
+impl CellKind {
+    pub(crate) fn gates(self) -> usize {
+        match self {
+            CellKind::Lstm => 4,
+            CellKind::Gru => 3,
+        }
+    }
+
+    // Floats of carried state: h, followed by the cell state c for LSTM
+    fn state_len(self, hidden: usize) -> usize {
+        match self {
+            CellKind::Lstm => 2 * hidden,
+            CellKind::Gru => hidden,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RecurrentLayer {
+    pub(crate) cell: CellKind,
+    pub(crate) inputs: usize,
+    pub(crate) hidden: usize,
+    pub(crate) weights: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+    state: Vec<f32>,
+}
+
+impl RecurrentLayer {
+    pub(crate) fn new(cell: CellKind, inputs: usize, hidden: usize) -> RecurrentLayer {
+        let rows = cell.gates() * hidden;
+        RecurrentLayer {
+            cell,
+            inputs,
+            hidden,
+            weights: vec![0.0; rows * (inputs + hidden)],
+            biases: vec![0.0; rows],
+            state: vec![0.0; cell.state_len(hidden)],
+        }
+    }
+
+    // Weight count for a cell of this shape, or None if it overflows
+    pub(crate) fn weight_count(cell: CellKind, inputs: usize, hidden: usize) -> Option<usize> {
+        cell.gates().checked_mul(hidden)?.checked_mul(inputs.checked_add(hidden)?)
+    }
+
+    pub(crate) fn state(&self) -> &[f32] {
+        &self.state
+    }
+
+    pub(crate) fn state_mut(&mut self) -> &mut [f32] {
+        &mut self.state
+    }
+
+    pub(crate) fn reset_state(&mut self) {
+        self.state.fill(0.0);
+    }
+
+    // Advance the carried state by one time step and return the new hidden state;
+    // on error the state is left as it was
+    pub(crate) fn step(&mut self, inputs: &[f32], simd: bool) -> NeuralResult<Vec<f32>> {
+        let mut state = self.state.clone();
+        self.advance(inputs, &mut state, simd)?;
+        self.state = state;
+        Ok(self.state[..self.hidden].to_vec())
+    }
+
+    // One step from zero state, leaving the carried state untouched
+    pub(crate) fn forward_into(&self, inputs: &[f32], outputs: &mut [f32], simd: bool) -> NeuralResult<()> {
+        if outputs.len() != self.hidden {
+            return Err(NeuralError::DimensionMismatch { expected: self.hidden, actual: outputs.len() });
+        }
+        let mut state = vec![0.0; self.cell.state_len(self.hidden)];
+        self.advance(inputs, &mut state, simd)?;
+        outputs.copy_from_slice(&state[..self.hidden]);
+        Ok(())
+    }
+
+    fn advance(&self, inputs: &[f32], state: &mut [f32], simd: bool) -> NeuralResult<()> {
+        if inputs.len() != self.inputs {
+            return Err(NeuralError::DimensionMismatch { expected: self.inputs, actual: inputs.len() });
+        }
+        let mut joined = Vec::with_capacity(self.inputs + self.hidden);
+        joined.extend_from_slice(inputs);
+        joined.extend_from_slice(&state[..self.hidden]);
+        match self.cell {
+            CellKind::Lstm => self.lstm_step(&joined, state, simd),
+            CellKind::Gru => self.gru_step(&joined, state, simd),
+        }
+    }
+
+    // i, f, o = σ(·), g = tanh(·); c' = f⊙c + i⊙g; h' = o⊙tanh(c')
+    fn lstm_step(&self, joined: &[f32], state: &mut [f32], simd: bool) -> NeuralResult<()> {
+        let hidden = self.hidden;
+        let mut gates = vec![0.0; 4 * hidden];
+        linalg::matvec_into(&self.weights, joined, &mut gates, 4 * hidden, joined.len(), simd)?;
+        for (gate, bias) in gates.iter_mut().zip(&self.biases) {
+            *gate += bias;
+        }
+
+        let (input_forget, rest) = gates.split_at_mut(2 * hidden);
+        let (cell_gate, output_gate) = rest.split_at_mut(hidden);
+        ActivationKind::Sigmoid.apply_slice(input_forget, simd);
+        ActivationKind::Tanh.apply_slice(cell_gate, simd);
+        ActivationKind::Sigmoid.apply_slice(output_gate, simd);
+        let (input_gate, forget_gate) = input_forget.split_at(hidden);
+
+        let (h, c) = state.split_at_mut(hidden);
+        for (((c, f), i), g) in c.iter_mut().zip(forget_gate).zip(input_gate).zip(&*cell_gate) {
+            *c = f * *c + i * g;
+        }
+        h.copy_from_slice(c);
+        ActivationKind::Tanh.apply_slice(h, simd);
+        for (h, o) in h.iter_mut().zip(&*output_gate) {
+            *h *= o;
+        }
+        Ok(())
+    }
+
+    // z, r = σ(W·[x; h] + b); n = tanh(W_n·x + b_n + r⊙(U_n·h)); h' = (1 - z)⊙n + z⊙h
+    fn gru_step(&self, joined: &[f32], state: &mut [f32], simd: bool) -> NeuralResult<()> {
+        let hidden = self.hidden;
+        let cols = joined.len();
+        let (gate_rows, candidate_rows) = self.weights.split_at(2 * hidden * cols);
+        let (gate_biases, candidate_biases) = self.biases.split_at(2 * hidden);
+
+        let mut gates = vec![0.0; 2 * hidden];
+        linalg::matvec_into(gate_rows, joined, &mut gates, 2 * hidden, cols, simd)?;
+        for (gate, bias) in gates.iter_mut().zip(gate_biases) {
+            *gate += bias;
+        }
+        ActivationKind::Sigmoid.apply_slice(&mut gates, simd);
+        let (update, reset) = gates.split_at(hidden);
+
+        // The reset gate scales only the recurrent half of each candidate row
+        let (x, previous) = joined.split_at(self.inputs);
+        let mut candidate: Vec<f32> = candidate_rows
+            .chunks_exact(cols)
+            .zip(candidate_biases)
+            .zip(reset)
+            .map(|((row, bias), r)| {
+                let (input_weights, recurrent_weights) = row.split_at(self.inputs);
+                linalg::dot(input_weights, x, simd) + bias + r * linalg::dot(recurrent_weights, previous, simd)
+            })
+            .collect();
+        ActivationKind::Tanh.apply_slice(&mut candidate, simd);
+
+        for ((h, z), n) in state.iter_mut().zip(update).zip(&candidate) {
+            *h = (1.0 - z) * n + z * *h;
+        }
+        Ok(())
+    }
+}
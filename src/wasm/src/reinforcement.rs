@@ -0,0 +1,274 @@
+// Deep Q-learning (Mnih et al., 2015) for agents that learn from rewards
+//
+// The online network maps a state to one Q-value per action and its output mode
+// must be Raw. Every transition (s, a, r, s', done) goes into an ExperienceReplay
+// buffer (experience.rs); once `learn_start` transitions are stored, each new one
+// triggers a train_batch step on a sampled minibatch.
+// The target for a sampled transition is
+//   y = r                                 if done
+//   y = r + discount · max_a' Q̂(s', a')    otherwise
+// where Q̂ is the target network. Only the output of the taken action is moved
+// towards y; the other outputs are trained on their own predictions, so they get
+// no gradient under the network's loss (MSE by default, Huber is the usual choice).
+// The target network follows the online one every `target_sync_interval`
+// transitions: copied outright when `target_update_rate` is 1, otherwise blended
+// by that rate (Polyak averaging).
+//
+// With priority_alpha > 0 minibatches favour transitions with large TD errors
+// δ = y - Q(s, a). The importance weight w of each is applied by training towards
+// Q(s, a) + w·δ instead of y, which scales its gradient by exactly w under MSE.
+//
+// Actions are chosen ε-greedily, with ε falling linearly from epsilon_start to
+// epsilon_end over the first epsilon_decay_steps transitions.
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::argmax;
+use crate::error::{NeuralError, NeuralResult};
+use crate::experience::ExperienceReplay;
+use crate::network::{NeuralNetwork, OutputMode};
+use crate::rng::Rng;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DqnConfig {
+    pub discount: f32,
+    pub learning_rate: f32,
+    pub batch_size: usize,
+    pub replay_capacity: usize,
+    // Transitions stored before learning begins
+    pub learn_start: usize,
+    pub epsilon_start: f32,
+    pub epsilon_end: f32,
+    pub epsilon_decay_steps: usize,
+    pub target_sync_interval: usize,
+    // 1 copies the online parameters; smaller values blend them in (soft updates)
+    pub target_update_rate: f32,
+    // Prioritized replay exponents; alpha 0 samples uniformly
+    pub priority_alpha: f32,
+    pub priority_beta: f32,
+}
+
+impl Default for DqnConfig {
+    fn default() -> Self {
+        DqnConfig {
+            discount: 0.99,
+            learning_rate: 1e-3,
+            batch_size: 32,
+            replay_capacity: 10_000,
+            learn_start: 100,
+            epsilon_start: 1.0,
+            epsilon_end: 0.05,
+            epsilon_decay_steps: 10_000,
+            target_sync_interval: 500,
+            target_update_rate: 1.0,
+            priority_alpha: 0.0,
+            priority_beta: 0.4,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl DqnConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> DqnConfig {
+        DqnConfig::default()
+    }
+}
+
+impl DqnConfig {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(0.0..=1.0).contains(&self.discount) {
+            return Err(NeuralError::InvalidConfiguration("discount must lie in [0, 1]".to_string()));
+        }
+        if !self.learning_rate.is_finite() || self.learning_rate <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("learning rate must be positive and finite".to_string()));
+        }
+        if self.batch_size == 0 || self.replay_capacity < self.batch_size {
+            return Err(NeuralError::InvalidConfiguration("replay capacity must hold at least one non-empty batch".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.epsilon_start) || !(0.0..=1.0).contains(&self.epsilon_end) {
+            return Err(NeuralError::InvalidConfiguration("epsilon must lie in [0, 1]".to_string()));
+        }
+        if self.target_sync_interval == 0 {
+            return Err(NeuralError::InvalidConfiguration("target sync interval must be non-zero".to_string()));
+        }
+        if !(self.target_update_rate > 0.0 && self.target_update_rate <= 1.0) {
+            return Err(NeuralError::InvalidConfiguration("target update rate must lie in (0, 1]".to_string()));
+        }
+        if !self.priority_alpha.is_finite() || self.priority_alpha < 0.0 || !(0.0..=1.0).contains(&self.priority_beta) {
+            return Err(NeuralError::InvalidConfiguration("priority alpha must be non-negative and beta lie in [0, 1]".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct DqnAgent {
+    config: DqnConfig,
+    online: NeuralNetwork,
+    target: NeuralNetwork,
+    buffer: ExperienceReplay,
+    rng: Rng,
+    // Transitions observed so far
+    steps: usize,
+    updates: usize,
+}
+
+#[wasm_bindgen]
+impl DqnAgent {
+    // Train a copy of `network`; its optimizer and loss settings are kept
+    #[wasm_bindgen(constructor)]
+    pub fn new(network: &NeuralNetwork, config: &DqnConfig, seed: u64) -> Result<DqnAgent, NeuralError> {
+        config.validate()?;
+        if network.layer_count() == 0 {
+            return Err(NeuralError::InvalidConfiguration("Q-network has no layers".to_string()));
+        }
+        if network.output_mode() != OutputMode::Raw {
+            return Err(NeuralError::InvalidConfiguration("Q-network outputs must be raw".to_string()));
+        }
+        let mut rng = Rng::new(seed);
+        let buffer = ExperienceReplay::new(config.replay_capacity, network.input_size(), config.priority_alpha, rng.next_u64())?;
+        Ok(DqnAgent {
+            config: *config,
+            online: network.clone(),
+            target: network.clone(),
+            buffer,
+            rng,
+            steps: 0,
+            updates: 0,
+        })
+    }
+
+    // Q-value of every action in `state`
+    #[wasm_bindgen]
+    pub fn q_values(&self, state: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        self.online.forward(state)
+    }
+
+    #[wasm_bindgen]
+    pub fn greedy_action(&self, state: &[f32]) -> Result<u32, NeuralError> {
+        Ok(argmax(&self.q_values(state)?)? as u32)
+    }
+
+    // ε-greedy choice at the current exploration rate
+    #[wasm_bindgen]
+    pub fn select_action(&mut self, state: &[f32]) -> Result<u32, NeuralError> {
+        let greedy = self.greedy_action(state)?;
+        if self.rng.next_f32() < self.epsilon() {
+            return Ok((self.rng.next_u64() as usize % self.action_count()) as u32);
+        }
+        Ok(greedy)
+    }
+
+    // Store one transition and, once enough are stored, learn from a sampled
+    // minibatch. Returns that step's loss, or undefined while the buffer fills.
+    #[wasm_bindgen]
+    pub fn learn_from_transition(
+        &mut self,
+        state: &[f32],
+        action: u32,
+        reward: f32,
+        next_state: &[f32],
+        done: bool,
+    ) -> Result<Option<f32>, NeuralError> {
+        if action as usize >= self.action_count() {
+            return Err(NeuralError::IndexOutOfRange { index: action as usize, len: self.action_count() });
+        }
+        self.buffer.push(state, action, reward, next_state, done)?;
+        self.steps += 1;
+        let loss = if self.buffer.len() >= self.config.learn_start.max(self.config.batch_size) {
+            self.updates += 1;
+            Some(self.learn()?)
+        } else {
+            None
+        };
+        if self.steps.is_multiple_of(self.config.target_sync_interval) {
+            self.sync_target()?;
+        }
+        Ok(loss)
+    }
+
+    // Move the target network to the online one by target_update_rate
+    #[wasm_bindgen]
+    pub fn sync_target(&mut self) -> Result<(), NeuralError> {
+        if self.config.target_update_rate >= 1.0 {
+            self.target.set_parameters(&self.online.get_parameters())
+        } else {
+            self.target.merge_weights(&self.online, self.config.target_update_rate)
+        }
+    }
+
+    // Current exploration rate
+    #[wasm_bindgen(getter)]
+    pub fn epsilon(&self) -> f32 {
+        let (start, end) = (self.config.epsilon_start, self.config.epsilon_end);
+        if self.steps >= self.config.epsilon_decay_steps {
+            return end;
+        }
+        start + (end - start) * self.steps as f32 / self.config.epsilon_decay_steps as f32
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // Minibatch updates made so far
+    #[wasm_bindgen(getter)]
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn buffer_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_buffer(&mut self) {
+        self.buffer.clear();
+    }
+
+    // Copy of the online network
+    #[wasm_bindgen]
+    pub fn network(&self) -> NeuralNetwork {
+        self.online.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn target_network(&self) -> NeuralNetwork {
+        self.target.clone()
+    }
+}
+
+impl DqnAgent {
+    fn action_count(&self) -> usize {
+        self.online.output_size()
+    }
+
+    fn learn(&mut self) -> NeuralResult<f32> {
+        let batch_size = self.config.batch_size;
+        let batch = self.buffer.sample(batch_size, self.config.priority_beta)?;
+        let mut targets = self.online.forward_batch(&batch.states, batch_size)?;
+        let next_values = self.target.forward_batch(&batch.next_states, batch_size)?;
+
+        let actions = self.action_count();
+        let mut td_errors = Vec::with_capacity(batch_size);
+        for (sample, (row, next_row)) in targets.chunks_exact_mut(actions).zip(next_values.chunks_exact(actions)).enumerate() {
+            let mut value = batch.rewards[sample];
+            if batch.done[sample] == 0 {
+                value += self.config.discount * next_row.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            }
+            let predicted = &mut row[batch.actions[sample] as usize];
+            let td_error = value - *predicted;
+            *predicted = if batch.weights[sample] == 1.0 { value } else { *predicted + batch.weights[sample] * td_error };
+            td_errors.push(td_error);
+        }
+        if self.config.priority_alpha > 0.0 {
+            self.buffer.update_priorities(&batch.slots, &td_errors)?;
+        }
+        self.online.train_batch(&batch.states, &targets, batch_size, self.config.learning_rate)
+    }
+}
@@ -0,0 +1,577 @@
+// Deterministic record and replay of a network's operations
+//
+// start_recording() captures everything that decides the network's results: its
+// parameters, precision, output mode, SIMD setting, initializer and RNG state, the
+// hidden state of recurrent layers, the optimizer with its moments, the loss and
+// the training safety and precision settings and the inference arithmetic. Every
+// successful forward, forward_batch, forward_step, reset_state, set_initializer,
+// reinitialize, set_optimizer, set_loss, set_training_safety,
+// set_training_precision, set_arithmetic and train_batch call is then logged in order with its inputs and outputs. NeuralNetwork.replay(trace)
+// rebuilds the starting state, re-runs the log and reports the first operation
+// whose outputs differ in any bit. Other mutations (set_weights, import_weights,
+// ...) are not logged, so a trace that spans them diverges at the next operation.
+// Copies of a network do not inherit its recording. A JavaScript loss cannot be
+// stored, so traces that start with one fail to replay and set_custom_loss
+// operations always diverge.
+//
+// Layout (all integers and floats little-endian):
+//   magic          b"SAST"
+//   version        u16
+//   reserved       u16
+//   weights_len u32, SASW weight blob (f32)
+//   precision u8, output_mode u8, simd u8, init_scheme u8, init_distribution u8, reserved [u8; 3]
+//   rng_state      u64 × 4
+//   state_len u32, f32[state_len]    (recurrent hidden state, layer order)
+//   optimizer      kind u8, reserved [u8; 3], beta1 f32, beta2 f32, epsilon f32, step u64,
+//                  moments_len u32, f32[moments_len]            (version 2 and later)
+//   loss           kind u8 (255: JavaScript), reserved [u8; 3], delta f32 (version 3 and later)
+//   safety         max_gradient_norm f32, rollback u8, reserved [u8; 3]   (version 4 and later)
+//   precision      training_precision u8, reserved [u8; 3], loss_scale f32,
+//                  good_steps u32                                    (version 5 and later)
+//   arithmetic     arithmetic u8, reserved [u8; 3]                   (version 6 and later)
+//   adapters       adapters_len u32, SASL adapter blob (lora.rs)    (version 7 and later)
+//   op_count u32
+//   op_count × { tag u8, reserved [u8; 3], payload, output_len u32, f32[output_len] }
+// Payloads by tag:
+//   0 forward          input_len u32, f32[input_len]
+//   1 forward_batch    batch_size u32, input_len u32, f32[input_len]
+//   2 forward_step     input_len u32, f32[input_len]
+//   3 reset_state      (none)
+//   4 set_initializer  scheme u8, distribution u8, reserved [u8; 2], seed u64
+//   5 reinitialize     (none)
+//   6 train_batch      batch_size u32, learning_rate f32, input_len u32, f32[input_len],
+//                      target_len u32, f32[target_len]     (output: the loss)
+//   7 set_optimizer    kind u8, reserved [u8; 3], beta1 f32, beta2 f32, epsilon f32
+//   8 set_loss         kind u8 (255: JavaScript), reserved [u8; 3], delta f32
+//   9 set_training_safety  max_gradient_norm f32, rollback u8, reserved [u8; 3]
+//  10 set_training_precision  training_precision u8, reserved [u8; 3]
+//  11 set_arithmetic   arithmetic u8, reserved [u8; 3]
+// Version 1 traces start from plain SGD; versions 1 and 2 train on mean squared error;
+// versions 1 to 3 train with the default TrainingSafety, versions 1 to 4 in f32,
+// versions 1 to 5 run inference in f32 and versions 1 to 6 start without adapters.
+
+use std::sync::Mutex;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::fixed_point::Arithmetic;
+use crate::gradient_optimizer::{GradientOptimizerConfig, GradientOptimizerKind};
+use crate::initializer::{InitDistribution, InitScheme, Initializer};
+use crate::lora::{self, LoraAdapter};
+use crate::loss::{LossFunction, LossKind};
+use crate::mixed_precision::{LossScaler, TrainingPrecision};
+use crate::network::{NeuralNetwork, OutputMode};
+use crate::precision::Precision;
+use crate::rng::Rng;
+use crate::serialization::{ByteReader, ByteWriter};
+use crate::training::TrainingSafety;
+
+pub const TRACE_MAGIC: &[u8; 4] = b"SAST";
+pub const TRACE_VERSION: u16 = 7;
+
+// Loss kind byte standing for a JavaScript loss
+const JAVASCRIPT_LOSS: u8 = 255;
+
+// Settings and state beyond the parameters that affect a network's results
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExecutionState {
+    pub(crate) precision: Precision,
+    pub(crate) output_mode: OutputMode,
+    pub(crate) simd_enabled: bool,
+    pub(crate) initializer: Initializer,
+    pub(crate) rng: Rng,
+    pub(crate) recurrent_state: Vec<f32>,
+    pub(crate) optimizer: GradientOptimizerConfig,
+    pub(crate) optimizer_step: u64,
+    pub(crate) optimizer_moments: Vec<f32>,
+    // None for a JavaScript loss
+    pub(crate) loss: Option<LossFunction>,
+    pub(crate) training_safety: TrainingSafety,
+    pub(crate) training_precision: TrainingPrecision,
+    pub(crate) loss_scaler: LossScaler,
+    pub(crate) arithmetic: Arithmetic,
+    // (layer, adapter) of every adapted dense layer
+    pub(crate) adapters: Vec<(usize, LoraAdapter)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operation {
+    Forward { inputs: Vec<f32> },
+    ForwardBatch { inputs: Vec<f32>, batch_size: usize },
+    ForwardStep { inputs: Vec<f32> },
+    ResetState,
+    SetInitializer { scheme: InitScheme, distribution: InitDistribution, seed: u64 },
+    Reinitialize,
+    TrainBatch { inputs: Vec<f32>, targets: Vec<f32>, batch_size: usize, learning_rate: f32 },
+    SetOptimizer { config: GradientOptimizerConfig },
+    SetLoss { loss: Option<LossFunction> },
+    SetTrainingSafety { safety: TrainingSafety },
+    SetTrainingPrecision { precision: TrainingPrecision },
+    SetArithmetic { arithmetic: Arithmetic },
+}
+
+#[derive(Debug, Clone)]
+struct Trace {
+    weights: Vec<u8>,
+    state: ExecutionState,
+    log: Vec<(Operation, Vec<f32>)>,
+    // Cleared by stop(); the trace stays exportable
+    active: bool,
+}
+
+// Recording slot inside NeuralNetwork; a Mutex because forward takes &self
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    trace: Mutex<Option<Trace>>,
+}
+
+impl Clone for Recorder {
+    fn clone(&self) -> Recorder {
+        Recorder::default()
+    }
+}
+
+impl Recorder {
+    pub(crate) fn start(&self, weights: Vec<u8>, state: ExecutionState) {
+        *self.lock() = Some(Trace { weights, state, log: Vec::new(), active: true });
+    }
+
+    pub(crate) fn stop(&self) {
+        if let Some(trace) = self.lock().as_mut() {
+            trace.active = false;
+        }
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.lock().as_ref().is_some_and(|trace| trace.active)
+    }
+
+    // `operation` is only built while recording, so idle networks copy nothing
+    pub(crate) fn record(&self, operation: impl FnOnce() -> Operation, outputs: &[f32]) {
+        if let Some(trace) = self.lock().as_mut().filter(|trace| trace.active) {
+            trace.log.push((operation(), outputs.to_vec()));
+        }
+    }
+
+    pub(crate) fn export(&self) -> NeuralResult<Vec<u8>> {
+        let guard = self.lock();
+        let trace = guard.as_ref().ok_or_else(|| NeuralError::InvalidConfiguration("call start_recording() first".to_string()))?;
+        Ok(encode_trace(trace))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Trace>> {
+        // A panic while recording leaves a usable trace; keep using it
+        self.trace.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Outcome of NeuralNetwork.replay
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    operations: u32,
+    first_divergence: Option<u32>,
+    max_abs_difference: f32,
+    network: NeuralNetwork,
+}
+
+#[wasm_bindgen]
+impl ReplayReport {
+    // Operations in the trace
+    #[wasm_bindgen(getter)]
+    pub fn operations(&self) -> u32 {
+        self.operations
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diverged(&self) -> bool {
+        self.first_divergence.is_some()
+    }
+
+    // Index of the first operation whose outputs (or success) differ, -1 if none did
+    #[wasm_bindgen(getter)]
+    pub fn first_divergence(&self) -> i64 {
+        self.first_divergence.map_or(-1, i64::from)
+    }
+
+    // Largest |recorded - replayed| over all outputs; infinite when an operation
+    // failed or changed its output length
+    #[wasm_bindgen(getter)]
+    pub fn max_abs_difference(&self) -> f32 {
+        self.max_abs_difference
+    }
+
+    // The network as it stands after the replay
+    #[wasm_bindgen]
+    pub fn network(&self) -> NeuralNetwork {
+        self.network.clone()
+    }
+}
+
+pub(crate) fn replay(bytes: &[u8]) -> NeuralResult<ReplayReport> {
+    let trace = decode_trace(bytes)?;
+    let mut network = NeuralNetwork::from_weights(&trace.weights)?;
+    network.restore_execution_state(trace.state)?;
+
+    let mut first_divergence = None;
+    let mut max_abs_difference = 0.0f32;
+    for (index, (operation, recorded)) in trace.log.iter().enumerate() {
+        let outcome = match operation {
+            Operation::Forward { inputs } => network.forward(inputs),
+            Operation::ForwardBatch { inputs, batch_size } => network.forward_batch(inputs, *batch_size),
+            Operation::ForwardStep { inputs } => network.forward_step(inputs),
+            Operation::ResetState => {
+                network.reset_state();
+                Ok(Vec::new())
+            }
+            Operation::SetInitializer { scheme, distribution, seed } => {
+                network.set_initializer(*scheme, *distribution, *seed);
+                Ok(Vec::new())
+            }
+            Operation::Reinitialize => {
+                network.reinitialize();
+                Ok(Vec::new())
+            }
+            Operation::TrainBatch { inputs, targets, batch_size, learning_rate } => {
+                network.train_batch(inputs, targets, *batch_size, *learning_rate).map(|loss| vec![loss])
+            }
+            Operation::SetOptimizer { config } => network.set_optimizer(config).map(|_| Vec::new()),
+            Operation::SetLoss { loss: Some(loss) } => network.set_loss(loss).map(|_| Vec::new()),
+            Operation::SetLoss { loss: None } => {
+                Err(NeuralError::InvalidFormat("a JavaScript loss cannot be replayed".to_string()))
+            }
+            Operation::SetTrainingSafety { safety } => network.set_training_safety(safety).map(|_| Vec::new()),
+            Operation::SetTrainingPrecision { precision } => {
+                network.set_training_precision(*precision);
+                Ok(Vec::new())
+            }
+            Operation::SetArithmetic { arithmetic } => {
+                network.set_arithmetic(*arithmetic);
+                Ok(Vec::new())
+            }
+        };
+        let same = match &outcome {
+            Ok(outputs) if outputs.len() == recorded.len() => {
+                for (a, b) in outputs.iter().zip(recorded) {
+                    max_abs_difference = max_abs_difference.max((a - b).abs());
+                }
+                outputs.iter().zip(recorded).all(|(a, b)| a.to_bits() == b.to_bits())
+            }
+            _ => {
+                max_abs_difference = f32::INFINITY;
+                false
+            }
+        };
+        if !same && first_divergence.is_none() {
+            first_divergence = Some(index as u32);
+        }
+    }
+    Ok(ReplayReport { operations: trace.log.len() as u32, first_divergence, max_abs_difference, network })
+}
+
+fn encode_trace(trace: &Trace) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.bytes(TRACE_MAGIC);
+    writer.u16(TRACE_VERSION);
+    writer.u16(0);
+    writer.u32(trace.weights.len() as u32);
+    writer.bytes(&trace.weights);
+
+    write_execution_state(&mut writer, &trace.state);
+
+    writer.u32(trace.log.len() as u32);
+    for (operation, outputs) in &trace.log {
+        match operation {
+            Operation::Forward { inputs } => {
+                writer.bytes(&[0, 0, 0, 0]);
+                write_floats(&mut writer, inputs);
+            }
+            Operation::ForwardBatch { inputs, batch_size } => {
+                writer.bytes(&[1, 0, 0, 0]);
+                writer.u32(*batch_size as u32);
+                write_floats(&mut writer, inputs);
+            }
+            Operation::ForwardStep { inputs } => {
+                writer.bytes(&[2, 0, 0, 0]);
+                write_floats(&mut writer, inputs);
+            }
+            Operation::ResetState => writer.bytes(&[3, 0, 0, 0]),
+            Operation::SetInitializer { scheme, distribution, seed } => {
+                writer.bytes(&[4, 0, 0, 0]);
+                writer.bytes(&[*scheme as u8, *distribution as u8, 0, 0]);
+                writer.u64(*seed);
+            }
+            Operation::Reinitialize => writer.bytes(&[5, 0, 0, 0]),
+            Operation::TrainBatch { inputs, targets, batch_size, learning_rate } => {
+                writer.bytes(&[6, 0, 0, 0]);
+                writer.u32(*batch_size as u32);
+                writer.f32(*learning_rate);
+                write_floats(&mut writer, inputs);
+                write_floats(&mut writer, targets);
+            }
+            Operation::SetOptimizer { config } => {
+                writer.bytes(&[7, 0, 0, 0]);
+                write_optimizer(&mut writer, config);
+            }
+            Operation::SetLoss { loss } => {
+                writer.bytes(&[8, 0, 0, 0]);
+                write_loss(&mut writer, *loss);
+            }
+            Operation::SetTrainingSafety { safety } => {
+                writer.bytes(&[9, 0, 0, 0]);
+                write_safety(&mut writer, safety);
+            }
+            Operation::SetTrainingPrecision { precision } => {
+                writer.bytes(&[10, 0, 0, 0]);
+                writer.bytes(&[*precision as u8, 0, 0, 0]);
+            }
+            Operation::SetArithmetic { arithmetic } => {
+                writer.bytes(&[11, 0, 0, 0]);
+                writer.bytes(&[*arithmetic as u8, 0, 0, 0]);
+            }
+        }
+        write_floats(&mut writer, outputs);
+    }
+    writer.finish()
+}
+
+fn decode_trace(bytes: &[u8]) -> NeuralResult<Trace> {
+    let mut reader = ByteReader::new(bytes);
+    if reader.bytes(4)? != TRACE_MAGIC {
+        return Err(NeuralError::InvalidFormat("missing SAST header".to_string()));
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > TRACE_VERSION {
+        return Err(NeuralError::InvalidFormat(format!("unsupported trace format version {}", version)));
+    }
+    reader.u16()?;
+    let weights_len = reader.u32()? as usize;
+    let weights = reader.bytes(weights_len)?.to_vec();
+
+    let state = read_execution_state(&mut reader, version)?;
+
+    let count = reader.u32()? as usize;
+    let mut log = Vec::with_capacity(count.min(4096));
+    for _ in 0..count {
+        let tag = reader.bytes(4)?[0];
+        let operation = match tag {
+            0 => Operation::Forward { inputs: read_floats(&mut reader)? },
+            1 => {
+                let batch_size = reader.u32()? as usize;
+                Operation::ForwardBatch { inputs: read_floats(&mut reader)?, batch_size }
+            }
+            2 => Operation::ForwardStep { inputs: read_floats(&mut reader)? },
+            3 => Operation::ResetState,
+            4 => {
+                let fields = reader.bytes(4)?;
+                let (scheme, distribution) = (decode_scheme(fields[0])?, decode_distribution(fields[1])?);
+                Operation::SetInitializer { scheme, distribution, seed: reader.u64()? }
+            }
+            5 => Operation::Reinitialize,
+            6 => {
+                let batch_size = reader.u32()? as usize;
+                let learning_rate = reader.f32()?;
+                let inputs = read_floats(&mut reader)?;
+                Operation::TrainBatch { inputs, targets: read_floats(&mut reader)?, batch_size, learning_rate }
+            }
+            7 => Operation::SetOptimizer { config: read_optimizer(&mut reader)? },
+            8 => Operation::SetLoss { loss: read_loss(&mut reader)? },
+            9 => Operation::SetTrainingSafety { safety: read_safety(&mut reader)? },
+            10 => Operation::SetTrainingPrecision { precision: read_training_precision(reader.bytes(4)?[0])? },
+            11 => Operation::SetArithmetic { arithmetic: read_arithmetic(reader.bytes(4)?[0])? },
+            other => return Err(NeuralError::InvalidFormat(format!("unknown trace operation {}", other))),
+        };
+        log.push((operation, read_floats(&mut reader)?));
+    }
+    if !reader.is_empty() {
+        return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+    }
+    Ok(Trace { weights, state, log, active: false })
+}
+
+// The state section from precision to loss_scaler, as written by the current version
+pub(crate) fn write_execution_state(writer: &mut ByteWriter, state: &ExecutionState) {
+    writer.u8(state.precision as u8);
+    writer.u8(state.output_mode as u8);
+    writer.u8(state.simd_enabled as u8);
+    writer.u8(state.initializer.scheme as u8);
+    writer.u8(state.initializer.distribution as u8);
+    writer.bytes(&[0; 3]);
+    for word in state.rng.state() {
+        writer.u64(word);
+    }
+    write_floats(writer, &state.recurrent_state);
+    write_optimizer(writer, &state.optimizer);
+    writer.u64(state.optimizer_step);
+    write_floats(writer, &state.optimizer_moments);
+    write_loss(writer, state.loss);
+    write_safety(writer, &state.training_safety);
+    writer.bytes(&[state.training_precision as u8, 0, 0, 0]);
+    writer.f32(state.loss_scaler.scale);
+    writer.u32(state.loss_scaler.good_steps);
+    writer.bytes(&[state.arithmetic as u8, 0, 0, 0]);
+    let adapters: Vec<(usize, &LoraAdapter)> = state.adapters.iter().map(|(layer, adapter)| (*layer, adapter)).collect();
+    let adapters = lora::encode(&adapters);
+    writer.u32(adapters.len() as u32);
+    writer.bytes(&adapters);
+}
+
+// The state section of a trace written by `version`, defaulting the fields it predates
+pub(crate) fn read_execution_state(reader: &mut ByteReader, version: u16) -> NeuralResult<ExecutionState> {
+    let precision = match reader.u8()? {
+        0 => Precision::F32,
+        1 => Precision::F16,
+        2 => Precision::Int8,
+        other => return Err(NeuralError::InvalidFormat(format!("unknown precision {}", other))),
+    };
+    let output_mode = match reader.u8()? {
+        0 => OutputMode::Raw,
+        1 => OutputMode::Softmax,
+        other => return Err(NeuralError::InvalidFormat(format!("unknown output mode {}", other))),
+    };
+    let simd_enabled = reader.u8()? != 0;
+    let scheme = decode_scheme(reader.u8()?)?;
+    let distribution = decode_distribution(reader.u8()?)?;
+    reader.bytes(3)?;
+    let rng = Rng::from_state([reader.u64()?, reader.u64()?, reader.u64()?, reader.u64()?]);
+    let recurrent_state = read_floats(reader)?;
+    let (optimizer, optimizer_step, optimizer_moments) = if version >= 2 {
+        (read_optimizer(reader)?, reader.u64()?, read_floats(reader)?)
+    } else {
+        (GradientOptimizerConfig::default(), 0, Vec::new())
+    };
+    let loss = if version >= 3 { read_loss(reader)? } else { Some(LossFunction::default()) };
+    let training_safety = if version >= 4 { read_safety(reader)? } else { TrainingSafety::default() };
+    let (training_precision, loss_scaler) = if version >= 5 {
+        let precision = read_training_precision(reader.bytes(4)?[0])?;
+        (precision, LossScaler { scale: reader.f32()?, good_steps: reader.u32()? })
+    } else {
+        (TrainingPrecision::F32, LossScaler::default())
+    };
+    let arithmetic = if version >= 6 { read_arithmetic(reader.bytes(4)?[0])? } else { Arithmetic::F32 };
+    let adapters = if version >= 7 {
+        let len = reader.u32()? as usize;
+        lora::decode(reader.bytes(len)?)?
+    } else {
+        Vec::new()
+    };
+    Ok(ExecutionState {
+        precision,
+        output_mode,
+        simd_enabled,
+        initializer: Initializer { scheme, distribution },
+        rng,
+        recurrent_state,
+        optimizer,
+        optimizer_step,
+        optimizer_moments,
+        loss,
+        training_safety,
+        training_precision,
+        loss_scaler,
+        arithmetic,
+        adapters,
+    })
+}
+
+// What read_execution_state fills in with defaults for a trace of `version`
+pub(crate) fn defaulted_state(version: u16) -> Vec<&'static str> {
+    let mut defaulted = Vec::new();
+    if version < 2 {
+        defaulted.push("gradient optimizer reset to SGD without moments");
+    }
+    if version < 3 {
+        defaulted.push("loss set to mean squared error");
+    }
+    if version < 4 {
+        defaulted.push("training safety set to its defaults");
+    }
+    if version < 5 {
+        defaulted.push("training precision set to f32 with a fresh loss scale");
+    }
+    if version < 6 {
+        defaulted.push("inference arithmetic set to f32");
+    }
+    if version < 7 {
+        defaulted.push("no LoRA adapters attached");
+    }
+    defaulted
+}
+
+fn write_floats(writer: &mut ByteWriter, values: &[f32]) {
+    writer.u32(values.len() as u32);
+    writer.f32_slice(values);
+}
+
+fn read_floats(reader: &mut ByteReader) -> NeuralResult<Vec<f32>> {
+    let len = reader.u32()? as usize;
+    reader.f32_vec(len)
+}
+
+pub(crate) fn write_optimizer(writer: &mut ByteWriter, config: &GradientOptimizerConfig) {
+    writer.bytes(&[config.kind as u8, 0, 0, 0]);
+    writer.f32(config.beta1);
+    writer.f32(config.beta2);
+    writer.f32(config.epsilon);
+}
+
+pub(crate) fn read_optimizer(reader: &mut ByteReader) -> NeuralResult<GradientOptimizerConfig> {
+    let kind = reader.bytes(4)?[0];
+    let kind = GradientOptimizerKind::from_u8(kind)
+        .ok_or_else(|| NeuralError::InvalidFormat(format!("unknown optimizer kind {}", kind)))?;
+    Ok(GradientOptimizerConfig { kind, beta1: reader.f32()?, beta2: reader.f32()?, epsilon: reader.f32()? })
+}
+
+fn write_loss(writer: &mut ByteWriter, loss: Option<LossFunction>) {
+    let loss_kind = loss.map_or(JAVASCRIPT_LOSS, |loss| loss.kind as u8);
+    writer.bytes(&[loss_kind, 0, 0, 0]);
+    writer.f32(loss.map_or(0.0, |loss| loss.delta));
+}
+
+fn read_loss(reader: &mut ByteReader) -> NeuralResult<Option<LossFunction>> {
+    let kind = reader.bytes(4)?[0];
+    let delta = reader.f32()?;
+    if kind == JAVASCRIPT_LOSS {
+        return Ok(None);
+    }
+    let kind = LossKind::from_u8(kind).ok_or_else(|| NeuralError::InvalidFormat(format!("unknown loss kind {}", kind)))?;
+    Ok(Some(LossFunction { kind, delta }))
+}
+
+fn write_safety(writer: &mut ByteWriter, safety: &TrainingSafety) {
+    writer.f32(safety.max_gradient_norm);
+    writer.bytes(&[safety.rollback_non_finite as u8, 0, 0, 0]);
+}
+
+fn read_safety(reader: &mut ByteReader) -> NeuralResult<TrainingSafety> {
+    let max_gradient_norm = reader.f32()?;
+    let rollback_non_finite = reader.bytes(4)?[0] != 0;
+    Ok(TrainingSafety { max_gradient_norm, rollback_non_finite })
+}
+
+fn read_training_precision(value: u8) -> NeuralResult<TrainingPrecision> {
+    TrainingPrecision::from_u8(value).ok_or_else(|| NeuralError::InvalidFormat(format!("unknown training precision {}", value)))
+}
+
+fn read_arithmetic(value: u8) -> NeuralResult<Arithmetic> {
+    Arithmetic::from_u8(value).ok_or_else(|| NeuralError::InvalidFormat(format!("unknown arithmetic {}", value)))
+}
+
+fn decode_scheme(value: u8) -> NeuralResult<InitScheme> {
+    match value {
+        0 => Ok(InitScheme::Zeros),
+        1 => Ok(InitScheme::Xavier),
+        2 => Ok(InitScheme::He),
+        3 => Ok(InitScheme::LeCun),
+        other => Err(NeuralError::InvalidFormat(format!("unknown initializer scheme {}", other))),
+    }
+}
+
+fn decode_distribution(value: u8) -> NeuralResult<InitDistribution> {
+    match value {
+        0 => Ok(InitDistribution::Uniform),
+        1 => Ok(InitDistribution::Normal),
+        other => Err(NeuralError::InvalidFormat(format!("unknown initializer distribution {}", other))),
+    }
+}
@@ -0,0 +1,189 @@
+// Random number generation
+// `Rng` is a deterministic, seedable xoshiro256++ generator so runs can be reproduced
+// from a seed. `SecureRng` draws from the platform CSPRNG (crypto.getRandomValues()
+// in the browser) for security-sensitive randomness.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+
+// Which generator stochastic kernels draw from
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomSource {
+    // Fast and reproducible via set_seed
+    Deterministic = 0,
+    // Platform CSPRNG; not reproducible
+    Secure = 1,
+}
+
+// Seed used until the caller chooses one
+pub const DEFAULT_SEED: u64 = 0x5A51_5EED;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::new(DEFAULT_SEED)
+    }
+}
+
+impl Rng {
+    // Expand the seed with SplitMix64 so nearby seeds give unrelated streams
+    pub fn new(seed: u64) -> Rng {
+        let mut splitmix = seed;
+        let mut next = || {
+            splitmix = splitmix.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        Rng { state: [next(), next(), next(), next()] }
+    }
+
+    // Raw generator state, so a stream can be resumed exactly (deterministic replay)
+    pub fn state(&self) -> [u64; 4] {
+        self.state
+    }
+
+    pub fn from_state(state: [u64; 4]) -> Rng {
+        Rng { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    // Uniform in [0, 1) from the top 24 bits, so every value is exactly representable
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    // Uniform in [low, high)
+    pub fn uniform(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next_f32()
+    }
+
+    // Standard normal sample (Box-Muller)
+    pub fn normal(&mut self) -> f32 {
+        let u1 = 1.0 - self.next_f64(); // (0, 1], keeps ln finite
+        let u2 = self.next_f64();
+        ((-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()) as f32
+    }
+
+    // Gamma(shape, 1) sample (Marsaglia & Tsang); shape must be positive
+    pub fn gamma(&mut self, shape: f64) -> f64 {
+        if shape < 1.0 {
+            // Gamma(a) = Gamma(a + 1) · U^(1/a)
+            let u = 1.0 - self.next_f64();
+            return self.gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.normal() as f64;
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = 1.0 - self.next_f64();
+            if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+}
+
+// Fill `bytes` from the platform CSPRNG, failing rather than degrading
+pub fn fill_secure(bytes: &mut [u8]) -> NeuralResult<()> {
+    getrandom::getrandom(bytes).map_err(|err| NeuralError::Unavailable(format!("secure random source ({})", err)))
+}
+
+// Buffered CSPRNG stream. If the platform source fails it falls back to a
+// time-seeded xoshiro generator and reports itself as degraded.
+#[derive(Debug, Clone)]
+pub struct SecureRng {
+    buffer: [u8; 256],
+    position: usize,
+    fallback: Option<Rng>,
+}
+
+impl Default for SecureRng {
+    fn default() -> Self {
+        SecureRng::new()
+    }
+}
+
+impl SecureRng {
+    pub fn new() -> SecureRng {
+        let mut rng = SecureRng { buffer: [0; 256], position: 256, fallback: None };
+        rng.refill();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if let Some(fallback) = self.fallback.as_mut() {
+            return fallback.next_u64();
+        }
+        if self.position + 8 > self.buffer.len() {
+            self.refill();
+            if let Some(fallback) = self.fallback.as_mut() {
+                return fallback.next_u64();
+            }
+        }
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.buffer[self.position..self.position + 8]);
+        self.position += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    // True once the platform source has failed and the weak fallback is in use
+    pub fn is_degraded(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    fn refill(&mut self) {
+        match fill_secure(&mut self.buffer) {
+            Ok(()) => self.position = 0,
+            Err(_) => self.fallback = Some(Rng::new(weak_seed())),
+        }
+    }
+}
+
+// Last-resort seed from the clock; only used when no CSPRNG is reachable
+#[cfg(js_host)]
+fn weak_seed() -> u64 {
+    let now = js_sys::Date::now().to_bits();
+    let jitter = (js_sys::Math::random() * (1u64 << 53) as f64) as u64;
+    now ^ jitter.rotate_left(17)
+}
+
+#[cfg(not(js_host))]
+fn weak_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(DEFAULT_SEED, |elapsed| elapsed.as_nanos() as u64)
+}
@@ -0,0 +1,186 @@
+// Learning-rate schedules and early stopping for multi-epoch training
+//
+// A schedule maps an epoch (counted from 0) to a learning rate. After
+// `warmup_epochs` epochs ramping linearly up to `base_rate`, with epoch e at
+// base_rate·(e+1)/(warmup+1), the decay runs on t = epoch - warmup_epochs:
+//   Constant     base_rate
+//   StepDecay    base_rate · gamma^floor(t / step_size)
+//   Exponential  base_rate · gamma^t
+//   Cosine       min_rate + (base_rate - min_rate)·(1 + cos(π·t/period))/2, held at
+//                min_rate once t reaches period
+//
+// EarlyStopping watches the epoch losses: an epoch improves when its loss is more
+// than min_delta below the best so far, and training stops after `patience`
+// epochs in a row without improvement.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleKind {
+    Constant = 0,
+    StepDecay = 1,
+    Exponential = 2,
+    Cosine = 3,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LearningRateSchedule {
+    pub kind: ScheduleKind,
+    pub base_rate: f32,
+    pub warmup_epochs: usize,
+    // Epochs between StepDecay drops
+    pub step_size: usize,
+    // Decay factor per drop (StepDecay) or per epoch (Exponential)
+    pub gamma: f32,
+    // Cosine floor and the epochs it takes to reach it
+    pub min_rate: f32,
+    pub period: usize,
+}
+
+#[wasm_bindgen]
+impl LearningRateSchedule {
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_rate: f32) -> LearningRateSchedule {
+        LearningRateSchedule {
+            kind: ScheduleKind::Constant,
+            base_rate,
+            warmup_epochs: 0,
+            step_size: 10,
+            gamma: 0.1,
+            min_rate: 0.0,
+            period: 100,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn step_decay(base_rate: f32, step_size: usize, gamma: f32) -> LearningRateSchedule {
+        LearningRateSchedule { kind: ScheduleKind::StepDecay, step_size, gamma, ..LearningRateSchedule::new(base_rate) }
+    }
+
+    #[wasm_bindgen]
+    pub fn exponential(base_rate: f32, gamma: f32) -> LearningRateSchedule {
+        LearningRateSchedule { kind: ScheduleKind::Exponential, gamma, ..LearningRateSchedule::new(base_rate) }
+    }
+
+    #[wasm_bindgen]
+    pub fn cosine(base_rate: f32, min_rate: f32, period: usize) -> LearningRateSchedule {
+        LearningRateSchedule { kind: ScheduleKind::Cosine, min_rate, period, ..LearningRateSchedule::new(base_rate) }
+    }
+
+    // Learning rate for `epoch`, counted from 0
+    #[wasm_bindgen]
+    pub fn rate(&self, epoch: usize) -> f32 {
+        if epoch < self.warmup_epochs {
+            return self.base_rate * (epoch + 1) as f32 / (self.warmup_epochs + 1) as f32;
+        }
+        let t = epoch - self.warmup_epochs;
+        match self.kind {
+            ScheduleKind::Constant => self.base_rate,
+            ScheduleKind::StepDecay => self.base_rate * power(self.gamma, t / self.step_size),
+            ScheduleKind::Exponential => self.base_rate * power(self.gamma, t),
+            ScheduleKind::Cosine => {
+                let progress = t.min(self.period) as f64 / self.period as f64;
+                let cosine = (1.0 + (std::f64::consts::PI * progress).cos()) / 2.0;
+                (self.min_rate as f64 + (self.base_rate - self.min_rate) as f64 * cosine) as f32
+            }
+        }
+    }
+}
+
+impl LearningRateSchedule {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !self.base_rate.is_finite() || self.base_rate <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("base learning rate must be positive and finite".to_string()));
+        }
+        match self.kind {
+            ScheduleKind::StepDecay if self.step_size == 0 => {
+                Err(NeuralError::InvalidConfiguration("step decay needs a non-zero step size".to_string()))
+            }
+            ScheduleKind::StepDecay | ScheduleKind::Exponential if !(self.gamma > 0.0 && self.gamma <= 1.0) => {
+                Err(NeuralError::InvalidConfiguration("decay gamma must lie in (0, 1]".to_string()))
+            }
+            ScheduleKind::Cosine if self.period == 0 => {
+                Err(NeuralError::InvalidConfiguration("cosine schedule needs a non-zero period".to_string()))
+            }
+            // A zero floor would end training with steps the optimizer rejects
+            ScheduleKind::Cosine if !(self.min_rate > 0.0 && self.min_rate <= self.base_rate) => {
+                Err(NeuralError::InvalidConfiguration("cosine min_rate must lie in (0, base_rate]".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// gamma^n in f64, so long runs decay smoothly instead of flushing to zero early
+fn power(gamma: f32, n: usize) -> f32 {
+    (gamma as f64).powf(n as f64) as f32
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EarlyStopping {
+    patience: usize,
+    min_delta: f32,
+    best_loss: f32,
+    best_epoch: usize,
+    epochs_seen: usize,
+    stale_epochs: usize,
+}
+
+#[wasm_bindgen]
+impl EarlyStopping {
+    #[wasm_bindgen(constructor)]
+    pub fn new(patience: usize, min_delta: f32) -> Result<EarlyStopping, NeuralError> {
+        if patience == 0 {
+            return Err(NeuralError::InvalidConfiguration("patience must be at least 1".to_string()));
+        }
+        if !min_delta.is_finite() || min_delta < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("min_delta must be finite and non-negative".to_string()));
+        }
+        Ok(EarlyStopping { patience, min_delta, best_loss: f32::INFINITY, best_epoch: 0, epochs_seen: 0, stale_epochs: 0 })
+    }
+
+    // Record an epoch's loss; true once training should stop
+    #[wasm_bindgen]
+    pub fn observe(&mut self, loss: f32) -> bool {
+        if loss < self.best_loss - self.min_delta {
+            self.best_loss = loss;
+            self.best_epoch = self.epochs_seen;
+            self.stale_epochs = 0;
+        } else {
+            self.stale_epochs += 1;
+        }
+        self.epochs_seen += 1;
+        self.should_stop()
+    }
+
+    #[wasm_bindgen]
+    pub fn should_stop(&self) -> bool {
+        self.stale_epochs >= self.patience
+    }
+
+    // Whether the last observed epoch set a new best
+    #[wasm_bindgen]
+    pub fn improved(&self) -> bool {
+        self.epochs_seen > 0 && self.stale_epochs == 0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn best_loss(&self) -> f32 {
+        self.best_loss
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn best_epoch(&self) -> usize {
+        self.best_epoch
+    }
+
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        *self = EarlyStopping { best_loss: f32::INFINITY, best_epoch: 0, epochs_seen: 0, stale_epochs: 0, ..*self };
+    }
+}
@@ -0,0 +1,119 @@
+// Reusable intermediate buffers keyed by shape
+//
+// A forward pass needs a buffer for every layer's output. The ScratchAllocator
+// keeps the buffers of finished passes on free lists keyed by (rows, columns), so
+// the next pass of the same network takes them back instead of allocating: after
+// its first pass a network runs without new allocations apart from the outputs
+// handed to the caller. Buffers come back with stale contents, which is fine
+// for kernels that overwrite their outputs. The cache holds at most a couple of
+// buffers per shape in use; clear() releases all of them.
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ScratchAllocator {
+    free: BTreeMap<(usize, usize), Vec<Vec<f32>>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ScratchAllocator {
+    pub(crate) fn new() -> ScratchAllocator {
+        ScratchAllocator::default()
+    }
+
+    // A rows × columns buffer with unspecified contents
+    pub(crate) fn take(&mut self, shape: (usize, usize)) -> Vec<f32> {
+        match self.free.get_mut(&shape).and_then(Vec::pop) {
+            Some(buffer) => {
+                self.hits += 1;
+                buffer
+            }
+            None => {
+                self.misses += 1;
+                vec![0.0; shape.0 * shape.1]
+            }
+        }
+    }
+
+    // Return a buffer taken with `shape` for later passes
+    pub(crate) fn give(&mut self, shape: (usize, usize), buffer: Vec<f32>) {
+        debug_assert_eq!(buffer.len(), shape.0 * shape.1);
+        self.free.entry(shape).or_default().push(buffer);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.free.clear();
+    }
+
+    pub(crate) fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    // Carry reuse counts over from a runtime snapshot
+    pub(crate) fn set_stats(&mut self, hits: u64, misses: u64) {
+        self.hits = hits;
+        self.misses = misses;
+    }
+
+    pub(crate) fn stats(&self) -> ScratchStats {
+        let buffers = self.free.values().map(Vec::len).sum();
+        let bytes = self.free.values().flatten().map(|buffer| buffer.capacity() * std::mem::size_of::<f32>()).sum();
+        ScratchStats { hits: self.hits, misses: self.misses, shapes: self.free.len(), buffers, bytes }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScratchStats {
+    hits: u64,
+    misses: u64,
+    shapes: usize,
+    buffers: usize,
+    bytes: usize,
+}
+
+#[wasm_bindgen]
+impl ScratchStats {
+    // Requests served from the cache
+    #[wasm_bindgen(getter)]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    // Requests that had to allocate
+    #[wasm_bindgen(getter)]
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    // hits / (hits + misses); 0 before any request
+    #[wasm_bindgen(getter)]
+    pub fn hit_rate(&self) -> f64 {
+        let requests = self.hits + self.misses;
+        if requests == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / requests as f64
+    }
+
+    // Distinct shapes with cached buffers
+    #[wasm_bindgen(getter)]
+    pub fn shapes(&self) -> usize {
+        self.shapes
+    }
+
+    // Buffers waiting to be reused and the bytes they hold
+    #[wasm_bindgen(getter)]
+    pub fn cached_buffers(&self) -> usize {
+        self.buffers
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cached_bytes(&self) -> usize {
+        self.bytes
+    }
+}
@@ -0,0 +1,171 @@
+// Input sanitization policy for the runtime's entry points
+//
+// Every NeuralRuntime entry point that takes float data from the caller runs it
+// through the runtime's SecurityPolicy before any kernel sees it:
+//   max_length     longest input vector (0 = unlimited); batched calls apply it to
+//                  each sample and matmul to each matrix row
+//   min_value, max_value   accepted range of finite values
+//   non_finite     what to do with NaN and ±Infinity
+//   out_of_bounds  what to do with finite values outside the range
+// Each action is one of
+//   Reject  fail with NonFiniteInput / ValueOutOfBounds naming the first offending
+//           index; in-place calls leave their data untouched
+//   Clamp   pull the value into range: ±Infinity to the nearer bound, NaN to 0
+//           clamped into range
+//   Zero    replace the value with 0
+// A slice longer than max_length is always rejected with InputTooLarge.
+//
+// Entry points that cannot throw (process_spike_train, calculate_mesh_efficiency,
+// mesh_efficiency_report) keep their JS signatures: a rejected input is logged under
+// the "security" target and the call returns its neutral result (a rate of 0, no
+// report).
+//
+// The default policy is the one the runtime always enforced: at most 10000
+// elements, values within ±1000, and both kinds of violation rejected.
+
+use std::borrow::Cow;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::logging::{log_event, LogLevel};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationAction {
+    Reject = 0,
+    Clamp = 1,
+    Zero = 2,
+}
+
+impl ViolationAction {
+    pub(crate) fn from_u8(value: u8) -> Option<ViolationAction> {
+        match value {
+            0 => Some(ViolationAction::Reject),
+            1 => Some(ViolationAction::Clamp),
+            2 => Some(ViolationAction::Zero),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecurityPolicy {
+    // Longest input vector, per sample for batched calls (0 = unlimited)
+    pub max_length: usize,
+    pub min_value: f32,
+    pub max_value: f32,
+    // NaN and ±Infinity
+    pub non_finite: ViolationAction,
+    // Finite values outside [min_value, max_value]
+    pub out_of_bounds: ViolationAction,
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        SecurityPolicy::new()
+    }
+}
+
+#[wasm_bindgen]
+impl SecurityPolicy {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SecurityPolicy {
+        SecurityPolicy {
+            max_length: 10000,
+            min_value: -1000.0,
+            max_value: 1000.0,
+            non_finite: ViolationAction::Reject,
+            out_of_bounds: ViolationAction::Reject,
+        }
+    }
+}
+
+impl SecurityPolicy {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !(self.min_value.is_finite() && self.max_value.is_finite() && self.min_value <= self.max_value) {
+            return Err(NeuralError::InvalidConfiguration("value bounds must be finite with min_value <= max_value".to_string()));
+        }
+        Ok(())
+    }
+
+    // Check `values`, made of rows of `row_len`, and return how many values need
+    // repairing; nothing is modified
+    fn check(&self, values: &[f32], row_len: usize) -> NeuralResult<usize> {
+        if self.max_length > 0 && row_len > self.max_length {
+            return Err(NeuralError::InputTooLarge { len: row_len, max: self.max_length });
+        }
+        let mut repairs = 0;
+        for (index, &value) in values.iter().enumerate() {
+            if !value.is_finite() {
+                if self.non_finite == ViolationAction::Reject {
+                    return Err(NeuralError::NonFiniteInput { index });
+                }
+                repairs += 1;
+            } else if value < self.min_value || value > self.max_value {
+                if self.out_of_bounds == ViolationAction::Reject {
+                    return Err(NeuralError::ValueOutOfBounds { index, value, min: self.min_value, max: self.max_value });
+                }
+                repairs += 1;
+            }
+        }
+        Ok(repairs)
+    }
+
+    fn repair(&self, values: &mut [f32]) {
+        for value in values.iter_mut() {
+            let action = if !value.is_finite() {
+                self.non_finite
+            } else if *value < self.min_value || *value > self.max_value {
+                self.out_of_bounds
+            } else {
+                continue;
+            };
+            // Reject never gets here: check() has already failed
+            *value = match action {
+                ViolationAction::Zero => 0.0,
+                _ if value.is_nan() => 0.0f32.clamp(self.min_value, self.max_value),
+                _ => value.clamp(self.min_value, self.max_value),
+            };
+        }
+    }
+
+    // Enforce the policy on a single input vector in place
+    pub(crate) fn apply(&self, values: &mut [f32]) -> NeuralResult<()> {
+        self.apply_rows(values, values.len())
+    }
+
+    // Enforce the policy in place on `values` made of rows of `row_len`
+    pub(crate) fn apply_rows(&self, values: &mut [f32], row_len: usize) -> NeuralResult<()> {
+        let repairs = self.check(values, row_len)?;
+        if repairs > 0 {
+            self.repair(values);
+            log_event!(LogLevel::Debug, "security", "replaced {} of {} input values", repairs, values.len());
+        }
+        Ok(())
+    }
+
+    // The policy applied to read-only input: borrowed when nothing needs repairing
+    pub(crate) fn sanitize<'a>(&self, values: &'a [f32], row_len: usize) -> NeuralResult<Cow<'a, [f32]>> {
+        let repairs = self.check(values, row_len)?;
+        if repairs == 0 {
+            return Ok(Cow::Borrowed(values));
+        }
+        let mut repaired = values.to_vec();
+        self.repair(&mut repaired);
+        log_event!(LogLevel::Debug, "security", "replaced {} of {} input values", repairs, values.len());
+        Ok(Cow::Owned(repaired))
+    }
+
+    // sanitize for entry points that cannot throw; a rejection is logged and yields None
+    pub(crate) fn sanitize_or_log<'a>(&self, values: &'a [f32], entry_point: &str) -> Option<Cow<'a, [f32]>> {
+        match self.sanitize(values, values.len()) {
+            Ok(values) => Some(values),
+            Err(err) => {
+                log_event!(LogLevel::Warn, "security", "{} rejected its input: {}", entry_point, err);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+// Compact binary weight format
+//
+// Layout (all integers and floats little-endian):
+//   magic       b"SASW"
+//   version     u16
+//   encoding    u8   (0 = f32, 1 = 8-bit affine quantized)
+//   reserved    u8
+//   input_size  u32
+//   layer_count u32
+//   layer_count × { inputs u32, outputs u32, activation u8, kind u8, reserved [u8; 2],
+//                   Conv1d only: in_channels u32, out_channels u32, kernel u32, stride u32, padding u32,
+//                   Dropout only: rate f32,
+//                   BatchNorm only: epsilon f32, momentum f32,
+//                   LayerNorm only: epsilon f32 }
+//   layer_count × { weights tensor, biases tensor, BatchNorm only: running mean tensor, running variance tensor }
+//   has_preprocessor u8, reserved [u8; 3], preprocessor    (version 3 and later)
+//
+// `kind` is a LayerKind (0 = dense); version 1 blobs hold zero there. Recurrent
+// layers store their gate matrix and gate biases as the two tensors, Conv1d layers
+// their filters and per-channel biases, normalization layers γ and β. Dropout
+// layers store two empty tensors.
+//
+// f32 tensors are raw values. Quantized tensors store `min f32, scale f32`
+// followed by one byte per value, decoded as `min + byte * scale`. Preprocessor
+// statistics are always f32; see Preprocessor::encode for their layout.
+
+use crate::activation::ActivationKind;
+use crate::error::{NeuralError, NeuralResult};
+use crate::conv::Conv1dGeometry;
+use crate::network::{Layer, LayerKind, LayerShape};
+use crate::normalization::{NormKind, NormLayer, RegularizerConfig};
+use crate::preprocess::Preprocessor;
+
+pub const WEIGHTS_MAGIC: &[u8; 4] = b"SASW";
+pub const WEIGHTS_VERSION: u16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightEncoding {
+    F32 = 0,
+    Quantized8 = 1,
+}
+
+// Architecture plus parameters decoded from a weight blob
+pub struct DecodedWeights {
+    pub input_size: usize,
+    pub layers: Vec<Layer>,
+    pub preprocessor: Option<Preprocessor>,
+}
+
+pub fn encode_weights(input_size: usize, layers: &[Layer], preprocessor: Option<&Preprocessor>, encoding: WeightEncoding) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.bytes(WEIGHTS_MAGIC);
+    writer.u16(WEIGHTS_VERSION);
+    writer.u8(encoding as u8);
+    writer.u8(0);
+    writer.u32(input_size as u32);
+    writer.u32(layers.len() as u32);
+
+    for layer in layers {
+        let shape = layer.shape();
+        writer.u32(shape.inputs as u32);
+        writer.u32(shape.outputs as u32);
+        writer.u8(shape.activation as u8);
+        writer.u8(shape.kind as u8);
+        writer.bytes(&[0; 2]);
+        if let Some(geometry) = shape.conv {
+            for value in [geometry.in_channels, geometry.out_channels, geometry.kernel, geometry.stride, geometry.padding] {
+                writer.u32(value as u32);
+            }
+        }
+        match shape.regularizer {
+            Some(RegularizerConfig::Dropout { rate }) => writer.f32(rate),
+            Some(RegularizerConfig::BatchNorm { epsilon, momentum }) => {
+                writer.f32(epsilon);
+                writer.f32(momentum);
+            }
+            Some(RegularizerConfig::LayerNorm { epsilon }) => writer.f32(epsilon),
+            None => {}
+        }
+    }
+
+    for layer in layers {
+        let weights = layer.dense_weights();
+        let mut tensors = vec![&weights[..], layer.biases()];
+        if let Layer::Norm(norm @ NormLayer { kind: NormKind::Batch, .. }) = layer {
+            tensors.extend([&norm.running_mean[..], &norm.running_var[..]]);
+        }
+        for tensor in tensors {
+            match encoding {
+                WeightEncoding::F32 => writer.f32_slice(tensor),
+                WeightEncoding::Quantized8 => write_quantized(&mut writer, tensor),
+            }
+        }
+    }
+
+    writer.u8(preprocessor.is_some() as u8);
+    writer.bytes(&[0; 3]);
+    if let Some(preprocessor) = preprocessor {
+        preprocessor.encode(&mut writer);
+    }
+
+    writer.finish()
+}
+
+// Format version of a blob that starts with the SASW header
+pub(crate) fn weights_version(bytes: &[u8]) -> Option<u16> {
+    match bytes {
+        [b'S', b'A', b'S', b'W', low, high, ..] => Some(u16::from_le_bytes([*low, *high])),
+        _ => None,
+    }
+}
+
+pub fn decode_weights(bytes: &[u8]) -> NeuralResult<DecodedWeights> {
+    let mut reader = ByteReader::new(bytes);
+
+    if reader.bytes(4)? != WEIGHTS_MAGIC {
+        return Err(NeuralError::InvalidFormat("missing SASW header".to_string()));
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > WEIGHTS_VERSION {
+        return Err(NeuralError::InvalidFormat(format!("unsupported weight format version {}", version)));
+    }
+    let encoding = match reader.u8()? {
+        0 => WeightEncoding::F32,
+        1 => WeightEncoding::Quantized8,
+        other => return Err(NeuralError::InvalidFormat(format!("unknown weight encoding {}", other))),
+    };
+    reader.u8()?;
+
+    let input_size = reader.u32()? as usize;
+    let layer_count = reader.u32()? as usize;
+
+    let mut shapes = Vec::with_capacity(layer_count.min(1024));
+    let mut expected_inputs = input_size;
+    for index in 0..layer_count {
+        let inputs = reader.u32()? as usize;
+        let outputs = reader.u32()? as usize;
+        let activation = reader.u8()?;
+        let kind = reader.u8()?;
+        reader.bytes(2)?;
+
+        let activation = ActivationKind::from_u8(activation)
+            .ok_or_else(|| NeuralError::InvalidFormat(format!("layer {} has unknown activation {}", index, activation)))?;
+        let kind = LayerKind::from_u8(kind)
+            .ok_or_else(|| NeuralError::InvalidFormat(format!("layer {} has unknown kind {}", index, kind)))?;
+        let conv = match kind {
+            LayerKind::Conv1d => Some(Conv1dGeometry {
+                in_channels: reader.u32()? as usize,
+                out_channels: reader.u32()? as usize,
+                kernel: reader.u32()? as usize,
+                stride: reader.u32()? as usize,
+                padding: reader.u32()? as usize,
+            }),
+            _ => None,
+        };
+        let regularizer = match kind {
+            LayerKind::Dropout => Some(RegularizerConfig::Dropout { rate: reader.f32()? }),
+            LayerKind::BatchNorm => Some(RegularizerConfig::BatchNorm { epsilon: reader.f32()?, momentum: reader.f32()? }),
+            LayerKind::LayerNorm => Some(RegularizerConfig::LayerNorm { epsilon: reader.f32()? }),
+            _ => None,
+        };
+
+        let shape = LayerShape { kind, inputs, outputs, activation, conv, regularizer };
+        if inputs != expected_inputs || outputs == 0 || shape.parameter_counts().is_none() {
+            return Err(NeuralError::InvalidFormat(format!("layer {} has inconsistent shape {}x{}", index, outputs, inputs)));
+        }
+        shapes.push(shape);
+        expected_inputs = outputs;
+    }
+
+    let mut layers = Vec::with_capacity(shapes.len());
+    for shape in shapes {
+        let (weight_count, bias_count) = shape
+            .parameter_counts()
+            .ok_or_else(|| NeuralError::InvalidFormat("layer shape overflows".to_string()))?;
+        let weights = read_tensor(&mut reader, encoding, weight_count)?;
+        let biases = read_tensor(&mut reader, encoding, bias_count)?;
+        let mut layer = Layer::with_parameters(&shape, &weights, &biases)?;
+        if let Layer::Norm(norm @ NormLayer { kind: NormKind::Batch, .. }) = &mut layer {
+            let count = norm.running_mean.len();
+            norm.running_mean = read_tensor(&mut reader, encoding, count)?;
+            norm.running_var = read_tensor(&mut reader, encoding, count)?;
+            if norm.running_var.iter().any(|&var| !(var >= 0.0 && var.is_finite())) {
+                return Err(NeuralError::InvalidFormat("batch norm running variance must be non-negative".to_string()));
+            }
+        }
+        layers.push(layer);
+    }
+
+    let preprocessor = if version >= 3 { read_preprocessor(&mut reader, input_size)? } else { None };
+
+    if !reader.is_empty() {
+        return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+    }
+
+    Ok(DecodedWeights { input_size, layers, preprocessor })
+}
+
+fn read_preprocessor(reader: &mut ByteReader, input_size: usize) -> NeuralResult<Option<Preprocessor>> {
+    let present = reader.u8()? != 0;
+    reader.bytes(3)?;
+    if !present {
+        return Ok(None);
+    }
+    let preprocessor = Preprocessor::decode(reader)?;
+    if !preprocessor.is_fitted() || preprocessor.output_width() != input_size {
+        return Err(NeuralError::InvalidFormat("preprocessor does not produce the network's inputs".to_string()));
+    }
+    Ok(Some(preprocessor))
+}
+
+fn write_quantized(writer: &mut ByteWriter, values: &[f32]) {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let (min, scale) = if values.is_empty() || max <= min {
+        (if values.is_empty() { 0.0 } else { min }, 0.0)
+    } else {
+        (min, (max - min) / 255.0)
+    };
+
+    writer.f32(min);
+    writer.f32(scale);
+    for &value in values {
+        let byte = if scale > 0.0 { ((value - min) / scale).round().clamp(0.0, 255.0) as u8 } else { 0 };
+        writer.u8(byte);
+    }
+}
+
+fn read_tensor(reader: &mut ByteReader, encoding: WeightEncoding, count: usize) -> NeuralResult<Vec<f32>> {
+    match encoding {
+        WeightEncoding::F32 => reader.f32_vec(count),
+        WeightEncoding::Quantized8 => {
+            let min = reader.f32()?;
+            let scale = reader.f32()?;
+            Ok(reader.bytes(count)?.iter().map(|&byte| min + byte as f32 * scale).collect())
+        }
+    }
+}
+
+// Little-endian byte sink shared by the binary formats
+pub(crate) struct ByteWriter {
+    buffer: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub(crate) fn new() -> ByteWriter {
+        ByteWriter { buffer: Vec::new() }
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.u64(value.to_bits());
+    }
+
+    pub(crate) fn f32(&mut self, value: f32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn f32_slice(&mut self, values: &[f32]) {
+        self.buffer.reserve(values.len() * 4);
+        for &value in values {
+            self.f32(value);
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+// Bounds-checked little-endian reader; every read fails cleanly on truncated input
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes, offset: 0 }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> NeuralResult<&'a [u8]> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(|| {
+            NeuralError::InvalidFormat(format!("unexpected end of data at offset {}", self.offset))
+        })?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn u8(&mut self) -> NeuralResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> NeuralResult<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> NeuralResult<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> NeuralResult<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn f64(&mut self) -> NeuralResult<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    pub(crate) fn f32(&mut self) -> NeuralResult<f32> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn f32_vec(&mut self, count: usize) -> NeuralResult<Vec<f32>> {
+        let len = count
+            .checked_mul(4)
+            .ok_or_else(|| NeuralError::InvalidFormat("tensor length overflows".to_string()))?;
+        Ok(self
+            .bytes(len)?
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    fn array<const N: usize>(&mut self) -> NeuralResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+}
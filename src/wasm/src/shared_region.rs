@@ -0,0 +1,206 @@
+// Tensors other threads read in place from a SharedArrayBuffer-compatible region
+//
+// A SharedTensorRegion is one fixed allocation of 32-bit words in WASM linear
+// memory that never moves: a header, then the data of every tensor allocated in
+// it. When the module is built with atomics and shared memory (as the `threads`
+// feature needs), linear memory is a SharedArrayBuffer, so workers and the render
+// thread can lay Int32Array/Float32Array views over the region and read weights or
+// activations without copying; shared_memory() tells whether that is the case.
+// Without shared memory the region still works, but only on the owning thread.
+//
+// Every tensor is guarded by a sequence word (a seqlock). write() makes it odd,
+// stores the values, then makes it even again, 2 higher than before. A reader loads
+// the sequence with Atomics.load, reads the values, and loads it again; the read
+// is consistent if both loads are equal and even, and is retried otherwise. There
+// is one writer, the region's owner.
+//
+// Layout in 32-bit words, byte offsets from ptr():
+//   0  magic "SAST" (little-endian)   1  version   2  tensor_count   3  max_tensors
+//   4 + 4·t  tensor t: sequence, data offset (words), len (floats), reserved
+//   data from 4 + 4·max_tensors, each tensor's floats stored as raw f32 bits
+
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+
+const REGION_MAGIC: u32 = u32::from_le_bytes(*b"SAST");
+const REGION_VERSION: u32 = 1;
+const HEADER_WORDS: usize = 4;
+const TENSOR_WORDS: usize = 4;
+
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct SharedTensorRegion {
+    words: Box<[AtomicU32]>,
+    max_tensors: usize,
+    // Next free data word
+    next: usize,
+}
+
+#[wasm_bindgen]
+impl SharedTensorRegion {
+    // Room for `capacity` floats in at most `max_tensors` tensors, allocated up front
+    #[wasm_bindgen(constructor)]
+    pub fn new(capacity: usize, max_tensors: usize) -> Result<SharedTensorRegion, NeuralError> {
+        if capacity == 0 || max_tensors == 0 {
+            return Err(NeuralError::InvalidConfiguration("region capacity and tensor count must be non-zero".to_string()));
+        }
+        let data_start = max_tensors
+            .checked_mul(TENSOR_WORDS)
+            .and_then(|words| words.checked_add(HEADER_WORDS))
+            .filter(|&words| words <= u32::MAX as usize)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("too many tensors for one region".to_string()))?;
+        let total = data_start
+            .checked_add(capacity)
+            .filter(|&words| words <= u32::MAX as usize)
+            .ok_or_else(|| NeuralError::InvalidConfiguration("region is too large".to_string()))?;
+        let words: Box<[AtomicU32]> = (0..total).map(|_| AtomicU32::new(0)).collect();
+        words[0].store(REGION_MAGIC, Ordering::Relaxed);
+        words[1].store(REGION_VERSION, Ordering::Relaxed);
+        words[3].store(max_tensors as u32, Ordering::Relaxed);
+        Ok(SharedTensorRegion { words, max_tensors, next: data_start })
+    }
+
+    // Reserve a tensor of `len` floats, all 0; returns its index
+    #[wasm_bindgen]
+    pub fn allocate(&mut self, len: usize) -> Result<u32, NeuralError> {
+        let tensor = self.tensor_count();
+        if tensor == self.max_tensors {
+            return Err(NeuralError::InvalidConfiguration(format!("region already holds its {} tensors", self.max_tensors)));
+        }
+        let available = self.words.len() - self.next;
+        if len == 0 || len > available {
+            return Err(NeuralError::MemoryLimitExceeded {
+                requested: len * 4,
+                in_use: (self.next - self.data_start()) * 4,
+                limit: (self.words.len() - self.data_start()) * 4,
+            });
+        }
+        let entry = HEADER_WORDS + tensor * TENSOR_WORDS;
+        self.words[entry + 1].store(self.next as u32, Ordering::Relaxed);
+        self.words[entry + 2].store(len as u32, Ordering::Relaxed);
+        self.next += len;
+        // Publishes the entry to readers that see the new count
+        self.words[2].store(tensor as u32 + 1, Ordering::Release);
+        Ok(tensor as u32)
+    }
+
+    // Replace a tensor's values under its seqlock
+    #[wasm_bindgen]
+    pub fn write(&mut self, tensor: u32, values: &[f32]) -> Result<(), NeuralError> {
+        let (offset, len) = self.tensor(tensor)?;
+        if values.len() != len {
+            return Err(NeuralError::DimensionMismatch { expected: len, actual: values.len() });
+        }
+        let sequence = &self.words[HEADER_WORDS + tensor as usize * TENSOR_WORDS];
+        let start = sequence.load(Ordering::Relaxed);
+        sequence.store(start.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (word, value) in self.words[offset..offset + len].iter().zip(values) {
+            word.store(value.to_bits(), Ordering::Relaxed);
+        }
+        sequence.store(start.wrapping_add(2), Ordering::Release);
+        Ok(())
+    }
+
+    // Publish a network's flat parameters (get_parameters order) into `tensor`
+    #[wasm_bindgen]
+    pub fn write_parameters(&mut self, tensor: u32, network: &NeuralNetwork) -> Result<(), NeuralError> {
+        self.write(tensor, &network.get_parameters())
+    }
+
+    // Consistent copy of a tensor, retrying while a write is in progress
+    #[wasm_bindgen]
+    pub fn read(&self, tensor: u32) -> Result<Vec<f32>, NeuralError> {
+        let (offset, len) = self.tensor(tensor)?;
+        let sequence = &self.words[HEADER_WORDS + tensor as usize * TENSOR_WORDS];
+        let mut values = vec![0.0f32; len];
+        loop {
+            let before = sequence.load(Ordering::Acquire);
+            if before.is_multiple_of(2) {
+                for (value, word) in values.iter_mut().zip(&self.words[offset..offset + len]) {
+                    *value = f32::from_bits(word.load(Ordering::Relaxed));
+                }
+                fence(Ordering::Acquire);
+                if sequence.load(Ordering::Relaxed) == before {
+                    return Ok(values);
+                }
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    // Even between writes; each write adds 2
+    #[wasm_bindgen]
+    pub fn sequence(&self, tensor: u32) -> Result<u32, NeuralError> {
+        self.tensor(tensor)?;
+        Ok(self.words[HEADER_WORDS + tensor as usize * TENSOR_WORDS].load(Ordering::Acquire))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tensor_count(&self) -> usize {
+        self.words[2].load(Ordering::Relaxed) as usize
+    }
+
+    #[wasm_bindgen]
+    pub fn tensor_len(&self, tensor: u32) -> Result<usize, NeuralError> {
+        Ok(self.tensor(tensor)?.1)
+    }
+
+    // Byte address of the region in linear memory, stable for its lifetime
+    #[wasm_bindgen]
+    pub fn ptr(&self) -> usize {
+        self.words.as_ptr() as usize
+    }
+
+    #[wasm_bindgen]
+    pub fn byte_len(&self) -> usize {
+        self.words.len() * 4
+    }
+
+    // Byte address of a tensor's first float
+    #[wasm_bindgen]
+    pub fn tensor_ptr(&self, tensor: u32) -> Result<usize, NeuralError> {
+        Ok(self.ptr() + self.tensor(tensor)?.0 * 4)
+    }
+
+    // Byte address of a tensor's sequence word, for Atomics.load on an Int32Array
+    #[wasm_bindgen]
+    pub fn sequence_ptr(&self, tensor: u32) -> Result<usize, NeuralError> {
+        self.tensor(tensor)?;
+        Ok(self.ptr() + (HEADER_WORDS + tensor as usize * TENSOR_WORDS) * 4)
+    }
+
+    // Whether linear memory is a SharedArrayBuffer other threads can map
+    #[wasm_bindgen]
+    pub fn shared_memory() -> bool {
+        #[cfg(js_host)]
+        {
+            wasm_bindgen::memory()
+                .dyn_into::<js_sys::WebAssembly::Memory>()
+                .is_ok_and(|memory| memory.buffer().is_instance_of::<js_sys::SharedArrayBuffer>())
+        }
+        #[cfg(not(js_host))]
+        {
+            false
+        }
+    }
+}
+
+impl SharedTensorRegion {
+    fn data_start(&self) -> usize {
+        HEADER_WORDS + self.max_tensors * TENSOR_WORDS
+    }
+
+    // (data offset in words, len) of an allocated tensor
+    fn tensor(&self, tensor: u32) -> NeuralResult<(usize, usize)> {
+        let count = self.tensor_count();
+        if tensor as usize >= count {
+            return Err(NeuralError::IndexOutOfRange { index: tensor as usize, len: count });
+        }
+        let entry = HEADER_WORDS + tensor as usize * TENSOR_WORDS;
+        Ok((self.words[entry + 1].load(Ordering::Relaxed) as usize, self.words[entry + 2].load(Ordering::Relaxed) as usize))
+    }
+}
@@ -0,0 +1,61 @@
+// Bounds-checked loads and stores for the simd128 kernels
+//
+// Kernels walk their slices with chunks_exact(N) and move whole chunks in and out
+// of v128 registers through these helpers, the only code that casts slice pointers.
+// wasm memory accesses have no alignment requirement (v128_load and v128_store emit
+// alignment hint 1), so any subslice is valid as long as all 16 bytes are in
+// bounds; the helpers slice to exactly one register first, which panics otherwise.
+
+use std::arch::wasm32::*;
+
+// Element types that pack into a v128, with how many fit
+pub(crate) trait Lane: Copy {
+    const LANES: usize;
+}
+
+impl Lane for f32 {
+    const LANES: usize = 4;
+}
+
+impl Lane for u16 {
+    const LANES: usize = 8;
+}
+
+impl Lane for i8 {
+    const LANES: usize = 16;
+}
+
+// 16-byte aligned storage, for spilling a register to memory
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C, align(16))]
+pub(crate) struct Aligned<T>(pub(crate) T);
+
+// Load the first register's worth of `values`
+#[inline]
+pub(crate) fn load<T: Lane>(values: &[T]) -> v128 {
+    let lanes = &values[..T::LANES];
+    // Safety: `lanes` spans exactly 16 readable bytes and the load is unaligned
+    unsafe { v128_load(lanes.as_ptr() as *const v128) }
+}
+
+// Overwrite the first register's worth of `values`
+#[inline]
+pub(crate) fn store<T: Lane>(values: &mut [T], value: v128) {
+    let lanes = &mut values[..T::LANES];
+    // Safety: `lanes` spans exactly 16 writable bytes and the store is unaligned
+    unsafe { v128_store(lanes.as_mut_ptr() as *mut v128, value) }
+}
+
+#[inline]
+pub(crate) fn f32_lanes(value: v128) -> [f32; 4] {
+    let mut lanes = Aligned([0.0; 4]);
+    store(&mut lanes.0, value);
+    lanes.0
+}
+
+// Sum of the four lanes in lane order
+#[inline]
+pub(crate) fn f32_sum(value: v128) -> f32 {
+    let [a, b, c, d] = f32_lanes(value);
+    a + b + c + d
+}
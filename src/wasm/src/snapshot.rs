@@ -0,0 +1,768 @@
+// Snapshots of a runtime and the networks and mesh graphs it works with
+//
+// NeuralRuntime.snapshot() captures the runtime's own state: its RNG, random source
+// and activation accuracy, the connection optimizer with its parameters and
+// evolving state (e.g. the annealing temperature), efficiency weights, budget, the
+// metrics (operation and spike counters, per-kernel profiles, scratch reuse counts)
+// and every pool buffer under its handle. restore() loads that into a runtime,
+// which is how an agent moves between tabs or workers or comes back after a crash.
+// The backend, GPU device, thread pool size beyond what the host offers, tracing
+// and JS callbacks belong to the host and are left as they are; pool buffers come
+// back at new addresses, so memory_epoch() changes and JS must refetch pointers.
+//
+// Networks and mesh graphs live outside the runtime, so a RuntimeSnapshot bundles
+// them by name with the runtime state. A network is stored as its SASW weight blob
+// (architecture, parameters, preprocessor) plus the execution state a replay trace
+// records: precision and output mode, initializer and RNG state, recurrent hidden
+// state, the gradient optimizer with its moments, loss, training settings, arithmetic
+// and LoRA adapters.
+// Networks using a JavaScript loss cannot be captured.
+//
+// Snapshots written by older versions are migrated on load rather than rejected:
+// sections a version predates get their defaults, older network execution states
+// and weight blobs are read through their own version rules, and extension
+// sections this version does not know are skipped. compatibility_report() lists
+// every such step, with what was filled in under "migrated" and what was lost
+// under "dropped".
+//
+// Layout (all integers and floats little-endian):
+//   magic          b"SASN"
+//   version        u16
+//   state_version  u16  (SAST trace version of the network execution states)
+//   writer_len u16, writer (crate version that wrote it)          (version 2 and later)
+//   runtime_len u32, runtime state
+//   network_count u32, network_count × { name_len u16, name, weights_len u32,
+//                                        SASW weight blob, execution state }
+//   mesh_count u32, mesh_count × { name_len u16, name, mesh graph }
+//   extension_count u32, extension_count × { tag u16, len u32, bytes } (version 2 and later)
+// Extensions hold optional sections that older readers can skip. Tags:
+//   1  security policy: max_length u64, min_value f32, max_value f32, non_finite u8,
+//      out_of_bounds u8, reserved [u8; 2]
+// A snapshot without a security policy leaves the runtime's own in place.
+// Runtime state:
+//   rng_state u64 × 4, random_source u8, activation_accuracy u8, reserved [u8; 2],
+//   thread_count u32
+//   optimizer kind u8, reserved [u8; 3], jitter, learning_rate, decay, prune_fraction,
+//             temperature, cooling f32, state_len u32, f32[state_len]
+//   efficiency energy, throughput, connectivity, spike_cost, synapse_cost, active_weight f32
+//   metrics   operations u32, spikes u64, spike_window_ms f64, last_spike_rate f32,
+//             scratch_hits u64, scratch_misses u64, profile (Profiler::encode)
+//   budget    has_budget u8, reserved [u8; 3], tick_ms f64, memory_bytes u64,
+//             auto_downshift u8, reserved [u8; 3], recovery_ticks u32, reduced_batch u32
+//   memory    pool buffers (PoolAllocator::encode)
+
+use wasm_bindgen::prelude::*;
+
+use crate::activation::ActivationAccuracy;
+use crate::allocator::PoolAllocator;
+use crate::budget::{BudgetConfig, BudgetGuard};
+use crate::efficiency::EfficiencyWeights;
+use crate::error::{NeuralError, NeuralResult};
+use crate::integrity::IntegrityCheck;
+use crate::logging::push_json_string;
+use crate::mesh::MeshGraph;
+use crate::network::NeuralNetwork;
+use crate::optimizer::{self, ConnectionOptimizer, OptimizerKind, OptimizerParams};
+use crate::profiler::Profiler;
+use crate::replay::{self, TRACE_VERSION};
+use crate::rng::{Rng, SecureRng};
+use crate::security::{SecurityPolicy, ViolationAction};
+use crate::serialization::{self, ByteReader, ByteWriter, WEIGHTS_VERSION};
+use crate::NeuralRuntime;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"SASN";
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+const EXTENSION_SECURITY_POLICY: u16 = 1;
+
+// Recorded in every snapshot for compatibility reports
+const WRITER: &str = concat!("neural-wasm-runtime ", env!("CARGO_PKG_VERSION"));
+
+// How a snapshot was brought up to the current format
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompatibilityReport {
+    source_version: u16,
+    writer: Option<String>,
+    migrated: Vec<String>,
+    dropped: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl CompatibilityReport {
+    // Snapshot format version that was read
+    #[wasm_bindgen(getter)]
+    pub fn source_version(&self) -> u16 {
+        self.source_version
+    }
+
+    // Crate version that wrote the snapshot, undefined before format version 2
+    #[wasm_bindgen(getter)]
+    pub fn writer(&self) -> Option<String> {
+        self.writer.clone()
+    }
+
+    // Fields filled in with defaults or converted from older layouts
+    #[wasm_bindgen]
+    pub fn migrated(&self) -> Vec<String> {
+        self.migrated.clone()
+    }
+
+    // Data in the snapshot this version could not use
+    #[wasm_bindgen]
+    pub fn dropped(&self) -> Vec<String> {
+        self.dropped.clone()
+    }
+
+    // Nothing was dropped, though fields may have been defaulted
+    #[wasm_bindgen(getter)]
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty()
+    }
+
+    // {"source_version", "writer", "migrated": [..], "dropped": [..]}
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"source_version\":{},\"writer\":", self.source_version);
+        match &self.writer {
+            Some(writer) => push_json_string(&mut out, writer),
+            None => out.push_str("null"),
+        }
+        for (key, entries) in [("migrated", &self.migrated), ("dropped", &self.dropped)] {
+            out.push_str(&format!(",\"{}\":[", key));
+            for (position, entry) in entries.iter().enumerate() {
+                if position > 0 {
+                    out.push(',');
+                }
+                push_json_string(&mut out, entry);
+            }
+            out.push(']');
+        }
+        out.push('}');
+        out
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct RuntimeSnapshot {
+    // Encoded runtime state, validated when the snapshot was built
+    runtime: Vec<u8>,
+    // None for snapshots written before the policy was recorded
+    security: Option<SecurityPolicy>,
+    networks: Vec<(String, NeuralNetwork)>,
+    meshes: Vec<(String, MeshGraph)>,
+    report: CompatibilityReport,
+}
+
+#[wasm_bindgen]
+impl RuntimeSnapshot {
+    // The runtime's state now, with no networks or meshes yet
+    #[wasm_bindgen]
+    pub fn capture(runtime: &NeuralRuntime) -> RuntimeSnapshot {
+        RuntimeSnapshot {
+            runtime: encode_runtime(runtime),
+            security: Some(runtime.security),
+            networks: Vec::new(),
+            meshes: Vec::new(),
+            report: CompatibilityReport::current(),
+        }
+    }
+
+    // from_bytes for snapshots from untrusted sources: the snapshot must pass `check`
+    // (see integrity.rs) before it is parsed
+    #[wasm_bindgen]
+    pub fn from_bytes_verified(bytes: &[u8], check: &IntegrityCheck) -> Result<RuntimeSnapshot, NeuralError> {
+        check.verify(bytes)?;
+        RuntimeSnapshot::from_bytes(bytes)
+    }
+
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<RuntimeSnapshot, NeuralError> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.bytes(4)? != SNAPSHOT_MAGIC {
+            return Err(NeuralError::InvalidFormat("missing SASN header".to_string()));
+        }
+        let version = reader.u16()?;
+        if version == 0 || version > SNAPSHOT_VERSION {
+            return Err(NeuralError::InvalidFormat(format!("unsupported snapshot format version {}", version)));
+        }
+        let state_version = reader.u16()?;
+        if state_version == 0 || state_version > TRACE_VERSION {
+            return Err(NeuralError::InvalidFormat(format!("unsupported execution state version {}", state_version)));
+        }
+        let mut report = CompatibilityReport { source_version: version, ..CompatibilityReport::default() };
+        if version >= 2 {
+            let len = reader.u16()? as usize;
+            let writer = std::str::from_utf8(reader.bytes(len)?).map_err(|_| NeuralError::InvalidFormat("writer is not UTF-8".to_string()))?;
+            report.writer = Some(writer.to_string());
+        } else {
+            report.migrated.push("writer version unknown (format version 1)".to_string());
+        }
+        let runtime_len = reader.u32()? as usize;
+        let runtime = reader.bytes(runtime_len)?.to_vec();
+        RuntimeState::decode(&runtime)?;
+
+        let mut snapshot =
+            RuntimeSnapshot { runtime, security: None, networks: Vec::new(), meshes: Vec::new(), report: CompatibilityReport::default() };
+        for _ in 0..reader.u32()? {
+            let name = read_name(&mut reader)?;
+            let weights_len = reader.u32()? as usize;
+            let weights = reader.bytes(weights_len)?;
+            let mut network = NeuralNetwork::from_weights(weights)?;
+            if let Some(weights_version) = serialization::weights_version(weights).filter(|&v| v < WEIGHTS_VERSION) {
+                report.migrated.push(format!("network {:?}: weights read from SASW version {}", name, weights_version));
+            }
+            network.restore_execution_state(replay::read_execution_state(&mut reader, state_version)?)?;
+            for defaulted in replay::defaulted_state(state_version) {
+                report.migrated.push(format!("network {:?}: {}", name, defaulted));
+            }
+            insert(&mut snapshot.networks, name, network);
+        }
+        for _ in 0..reader.u32()? {
+            let name = read_name(&mut reader)?;
+            insert(&mut snapshot.meshes, name, MeshGraph::decode(&mut reader)?);
+        }
+        if version >= 2 {
+            for _ in 0..reader.u32()? {
+                let tag = reader.u16()?;
+                let len = reader.u32()? as usize;
+                let section = reader.bytes(len)?;
+                match tag {
+                    EXTENSION_SECURITY_POLICY => snapshot.security = Some(decode_security(section)?),
+                    _ => report.dropped.push(format!("extension section {} ({} bytes)", tag, len)),
+                }
+            }
+        }
+        if snapshot.security.is_none() {
+            report.migrated.push("security policy not recorded; the runtime keeps its own".to_string());
+        }
+        if !reader.is_empty() {
+            return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+        }
+        snapshot.report = report;
+        Ok(snapshot)
+    }
+
+    // Store a copy of `network` under `name`, replacing any network of that name
+    #[wasm_bindgen]
+    pub fn add_network(&mut self, name: &str, network: &NeuralNetwork) -> Result<(), NeuralError> {
+        check_name(name)?;
+        if network.loss_function().is_none() {
+            return Err(NeuralError::InvalidConfiguration("networks with a JavaScript loss cannot be captured".to_string()));
+        }
+        insert(&mut self.networks, name.to_string(), network.clone());
+        Ok(())
+    }
+
+    // Store a copy of `mesh` under `name`, replacing any mesh of that name
+    #[wasm_bindgen]
+    pub fn add_mesh(&mut self, name: &str, mesh: &MeshGraph) -> Result<(), NeuralError> {
+        check_name(name)?;
+        insert(&mut self.meshes, name.to_string(), mesh.clone());
+        Ok(())
+    }
+
+    // Names in the order they were first added
+    #[wasm_bindgen]
+    pub fn network_names(&self) -> Vec<String> {
+        self.networks.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn mesh_names(&self) -> Vec<String> {
+        self.meshes.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn network(&self, name: &str) -> Result<NeuralNetwork, NeuralError> {
+        find(&self.networks, name).cloned().ok_or_else(|| NeuralError::InvalidConfiguration(format!("no network named {:?}", name)))
+    }
+
+    #[wasm_bindgen]
+    pub fn mesh(&self, name: &str) -> Result<MeshGraph, NeuralError> {
+        find(&self.meshes, name).cloned().ok_or_else(|| NeuralError::InvalidConfiguration(format!("no mesh named {:?}", name)))
+    }
+
+    // How this snapshot was migrated when it was read; empty for a captured one
+    #[wasm_bindgen]
+    pub fn compatibility_report(&self) -> CompatibilityReport {
+        self.report.clone()
+    }
+
+    // Load the captured runtime state into `runtime`
+    #[wasm_bindgen]
+    pub fn restore_runtime(&self, runtime: &mut NeuralRuntime) -> Result<(), NeuralError> {
+        RuntimeState::decode(&self.runtime)?.apply(runtime);
+        if let Some(policy) = self.security {
+            runtime.security = policy;
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NeuralError> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(SNAPSHOT_MAGIC);
+        writer.u16(SNAPSHOT_VERSION);
+        writer.u16(TRACE_VERSION);
+        write_name(&mut writer, WRITER);
+        writer.u32(self.runtime.len() as u32);
+        writer.bytes(&self.runtime);
+        writer.u32(self.networks.len() as u32);
+        for (name, network) in &self.networks {
+            write_name(&mut writer, name);
+            let weights = network.export_weights();
+            writer.u32(weights.len() as u32);
+            writer.bytes(&weights);
+            let state = network.execution_state();
+            if state.loss.is_none() {
+                return Err(NeuralError::InvalidConfiguration(format!("network {:?} uses a JavaScript loss", name)));
+            }
+            replay::write_execution_state(&mut writer, &state);
+        }
+        writer.u32(self.meshes.len() as u32);
+        for (name, mesh) in &self.meshes {
+            write_name(&mut writer, name);
+            mesh.encode(&mut writer);
+        }
+        let extensions: Vec<(u16, Vec<u8>)> = self.security.iter().map(|policy| (EXTENSION_SECURITY_POLICY, encode_security(policy))).collect();
+        writer.u32(extensions.len() as u32);
+        for (tag, section) in extensions {
+            writer.u16(tag);
+            writer.u32(section.len() as u32);
+            writer.bytes(&section);
+        }
+        Ok(writer.finish())
+    }
+}
+
+impl CompatibilityReport {
+    fn current() -> CompatibilityReport {
+        CompatibilityReport { source_version: SNAPSHOT_VERSION, writer: Some(WRITER.to_string()), ..CompatibilityReport::default() }
+    }
+}
+
+// The runtime state section of a snapshot
+fn encode_runtime(runtime: &NeuralRuntime) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    for word in runtime.rng.state() {
+        writer.u64(word);
+    }
+    writer.bytes(&[runtime.secure_rng.is_some() as u8, runtime.activation_accuracy as u8, 0, 0]);
+    writer.u32(runtime.thread_count as u32);
+
+    let params = runtime.optimizer.params();
+    writer.bytes(&[runtime.optimizer.kind() as u8, 0, 0, 0]);
+    for value in [params.jitter, params.learning_rate, params.decay, params.prune_fraction, params.temperature, params.cooling] {
+        writer.f32(value);
+    }
+    let state = runtime.optimizer.state();
+    writer.u32(state.len() as u32);
+    writer.f32_slice(&state);
+
+    let weights = &runtime.efficiency_weights;
+    for value in [weights.energy, weights.throughput, weights.connectivity, weights.spike_cost, weights.synapse_cost, weights.active_weight] {
+        writer.f32(value);
+    }
+
+    writer.u32(runtime.operations_count);
+    writer.u64(runtime.spikes_total);
+    writer.f64(runtime.spike_window_ms);
+    writer.f32(runtime.last_spike_rate);
+    let scratch = runtime.scratch.stats();
+    writer.u64(scratch.hits());
+    writer.u64(scratch.misses());
+    runtime.profiler.encode(&mut writer);
+
+    let budget = runtime.budget.as_ref().map(BudgetGuard::config);
+    writer.bytes(&[budget.is_some() as u8, 0, 0, 0]);
+    let budget = budget.unwrap_or_default();
+    writer.f64(budget.tick_ms);
+    writer.u64(budget.memory_bytes as u64);
+    writer.bytes(&[budget.auto_downshift as u8, 0, 0, 0]);
+    writer.u32(budget.recovery_ticks);
+    writer.u32(budget.reduced_batch as u32);
+
+    runtime.memory_pool.encode(&mut writer);
+    writer.finish()
+}
+
+fn encode_security(policy: &SecurityPolicy) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    writer.u64(policy.max_length as u64);
+    writer.f32(policy.min_value);
+    writer.f32(policy.max_value);
+    writer.bytes(&[policy.non_finite as u8, policy.out_of_bounds as u8, 0, 0]);
+    writer.finish()
+}
+
+fn decode_security(bytes: &[u8]) -> NeuralResult<SecurityPolicy> {
+    let mut reader = ByteReader::new(bytes);
+    let max_length = usize::try_from(reader.u64()?).unwrap_or(usize::MAX);
+    let (min_value, max_value) = (reader.f32()?, reader.f32()?);
+    let actions = reader.bytes(4)?;
+    let action = |value: u8| {
+        ViolationAction::from_u8(value).ok_or_else(|| NeuralError::InvalidFormat(format!("unknown violation action {}", value)))
+    };
+    let policy = SecurityPolicy { max_length, min_value, max_value, non_finite: action(actions[0])?, out_of_bounds: action(actions[1])? };
+    policy.validate().map_err(|err| NeuralError::InvalidFormat(format!("invalid security policy: {}", err)))?;
+    Ok(policy)
+}
+
+// Runtime state decoded in full before any of it is applied
+struct RuntimeState {
+    rng: Rng,
+    secure: bool,
+    activation_accuracy: ActivationAccuracy,
+    thread_count: usize,
+    optimizer: Box<dyn ConnectionOptimizer>,
+    efficiency_weights: EfficiencyWeights,
+    operations_count: u32,
+    spikes_total: u64,
+    spike_window_ms: f64,
+    last_spike_rate: f32,
+    scratch_hits: u64,
+    scratch_misses: u64,
+    profiler: Profiler,
+    budget: Option<BudgetGuard>,
+    memory_pool: PoolAllocator,
+}
+
+impl RuntimeState {
+    fn decode(bytes: &[u8]) -> NeuralResult<RuntimeState> {
+        let invalid = |err: NeuralError| NeuralError::InvalidFormat(format!("invalid runtime state: {}", err));
+        let mut reader = ByteReader::new(bytes);
+        let rng = Rng::from_state([reader.u64()?, reader.u64()?, reader.u64()?, reader.u64()?]);
+        let flags = reader.bytes(4)?;
+        let secure = flags[0] != 0;
+        let activation_accuracy = match flags[1] {
+            0 => ActivationAccuracy::Fast,
+            1 => ActivationAccuracy::Accurate,
+            other => return Err(NeuralError::InvalidFormat(format!("unknown activation accuracy {}", other))),
+        };
+        let thread_count = reader.u32()? as usize;
+
+        let kind = match reader.bytes(4)?[0] {
+            0 => OptimizerKind::Jitter,
+            1 => OptimizerKind::GradientPruning,
+            2 => OptimizerKind::Hebbian,
+            3 => OptimizerKind::WeightDecay,
+            4 => OptimizerKind::Annealing,
+            other => return Err(NeuralError::InvalidFormat(format!("unknown optimizer kind {}", other))),
+        };
+        let params = OptimizerParams {
+            jitter: reader.f32()?,
+            learning_rate: reader.f32()?,
+            decay: reader.f32()?,
+            prune_fraction: reader.f32()?,
+            temperature: reader.f32()?,
+            cooling: reader.f32()?,
+        };
+        let mut optimizer = optimizer::create(kind, &params).map_err(invalid)?;
+        let state_len = reader.u32()? as usize;
+        optimizer.restore_state(&reader.f32_vec(state_len)?);
+
+        let efficiency_weights = EfficiencyWeights {
+            energy: reader.f32()?,
+            throughput: reader.f32()?,
+            connectivity: reader.f32()?,
+            spike_cost: reader.f32()?,
+            synapse_cost: reader.f32()?,
+            active_weight: reader.f32()?,
+        };
+        efficiency_weights.validate().map_err(invalid)?;
+
+        let operations_count = reader.u32()?;
+        let spikes_total = reader.u64()?;
+        let spike_window_ms = reader.f64()?;
+        let last_spike_rate = reader.f32()?;
+        let scratch_hits = reader.u64()?;
+        let scratch_misses = reader.u64()?;
+        let profiler = Profiler::decode(&mut reader)?;
+
+        let has_budget = reader.bytes(4)?[0] != 0;
+        let mut config = BudgetConfig::new(reader.f64()?, reader.u64()? as usize);
+        config.auto_downshift = reader.bytes(4)?[0] != 0;
+        config.recovery_ticks = reader.u32()?;
+        config.reduced_batch = reader.u32()? as usize;
+        let budget = match has_budget {
+            true => Some(BudgetGuard::new(config).map_err(invalid)?),
+            false => None,
+        };
+
+        let memory_pool = PoolAllocator::decode(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(NeuralError::InvalidFormat("trailing bytes after runtime state".to_string()));
+        }
+        Ok(RuntimeState {
+            rng,
+            secure,
+            activation_accuracy,
+            thread_count,
+            optimizer,
+            efficiency_weights,
+            operations_count,
+            spikes_total,
+            spike_window_ms,
+            last_spike_rate,
+            scratch_hits,
+            scratch_misses,
+            profiler,
+            budget,
+            memory_pool,
+        })
+    }
+
+    fn apply(self, runtime: &mut NeuralRuntime) {
+        runtime.rng = self.rng;
+        runtime.secure_rng = self.secure.then(SecureRng::new);
+        runtime.activation_accuracy = self.activation_accuracy;
+        runtime.set_thread_count(self.thread_count);
+        runtime.optimizer = self.optimizer;
+        runtime.efficiency_weights = self.efficiency_weights;
+        runtime.operations_count = self.operations_count;
+        runtime.spikes_total = self.spikes_total;
+        runtime.spike_window_ms = self.spike_window_ms;
+        runtime.last_spike_rate = self.last_spike_rate;
+        runtime.scratch.clear();
+        runtime.scratch.set_stats(self.scratch_hits, self.scratch_misses);
+        runtime.profiler = self.profiler;
+        runtime.budget = self.budget;
+        runtime.memory_pool = self.memory_pool;
+    }
+}
+
+fn check_name(name: &str) -> NeuralResult<()> {
+    if name.is_empty() || name.len() > u16::MAX as usize {
+        return Err(NeuralError::InvalidConfiguration("snapshot entry names must be 1 to 65535 bytes".to_string()));
+    }
+    Ok(())
+}
+
+fn write_name(writer: &mut ByteWriter, name: &str) {
+    writer.u16(name.len() as u16);
+    writer.bytes(name.as_bytes());
+}
+
+fn read_name(reader: &mut ByteReader) -> NeuralResult<String> {
+    let len = reader.u16()? as usize;
+    let name = std::str::from_utf8(reader.bytes(len)?).map_err(|_| NeuralError::InvalidFormat("entry name is not UTF-8".to_string()))?;
+    check_name(name).map_err(|_| NeuralError::InvalidFormat("empty entry name".to_string()))?;
+    Ok(name.to_string())
+}
+
+fn insert<T>(entries: &mut Vec<(String, T)>, name: String, value: T) {
+    match entries.iter_mut().find(|(existing, _)| *existing == name) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((name, value)),
+    }
+}
+
+fn find<'a, T>(entries: &'a [(String, T)], name: &str) -> Option<&'a T> {
+    entries.iter().find(|(existing, _)| existing == name).map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::ActivationKind;
+    use crate::gradient_optimizer::{GradientOptimizerConfig, GradientOptimizerKind};
+    use crate::fixed_point::Arithmetic;
+    use crate::initializer::{InitDistribution, InitScheme};
+    use crate::loss::LossKind;
+
+    fn batch() -> (Vec<f32>, Vec<f32>) {
+        let inputs = (0..16).map(|i| (i as f32 * 0.37).sin()).collect();
+        let targets = (0..8).map(|i| (i as f32 * 0.71).cos()).collect();
+        (inputs, targets)
+    }
+
+    #[test]
+    fn adapted_network_round_trips() {
+        let mut network = NeuralNetwork::with_initializer(4, InitScheme::He, InitDistribution::Uniform, 11).unwrap();
+        network.add_layer(8, ActivationKind::ReLU).unwrap();
+        network.add_layer(2, ActivationKind::Linear).unwrap();
+        network.set_optimizer(&GradientOptimizerConfig::new(GradientOptimizerKind::Adam)).unwrap();
+        network.add_adapter(0, 2, 4.0).unwrap();
+        let (inputs, targets) = batch();
+        for _ in 0..5 {
+            network.train_batch(&inputs, &targets, 4, 0.01).unwrap();
+        }
+
+        let mut snapshot = RuntimeSnapshot::capture(&NeuralRuntime::new());
+        snapshot.add_network("agent", &network).unwrap();
+        let loaded = RuntimeSnapshot::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+        let mut restored = loaded.network("agent").unwrap();
+        assert_eq!(restored.adapter_parameter_count(), network.adapter_parameter_count());
+        assert_eq!(restored.export_adapters(), network.export_adapters());
+        assert_eq!(restored.forward_batch(&inputs, 4).unwrap(), network.forward_batch(&inputs, 4).unwrap());
+
+        // Adam resumes from the same moments
+        for _ in 0..3 {
+            network.train_batch(&inputs, &targets, 4, 0.01).unwrap();
+            restored.train_batch(&inputs, &targets, 4, 0.01).unwrap();
+        }
+        assert_eq!(restored.export_adapters(), network.export_adapters());
+        assert_eq!(restored.get_parameters(), network.get_parameters());
+    }
+
+    // Fixtures written by the crate while each format version was current: a network
+    // 3 → 4 tanh → 2 linear, Xavier uniform with seed 7, trained for three
+    // train_batch steps (with Adam from execution state version 2, Huber loss from 3
+    // and max_gradient_norm 5 from 4; version 6 then switches to FixedQ16), and
+    // recording started so the traces hold no operations
+    const EXECUTION_STATES: [&[u8]; 6] = [
+        include_bytes!("../tests/fixtures/execution_state_v1.sast"),
+        include_bytes!("../tests/fixtures/execution_state_v2.sast"),
+        include_bytes!("../tests/fixtures/execution_state_v3.sast"),
+        include_bytes!("../tests/fixtures/execution_state_v4.sast"),
+        include_bytes!("../tests/fixtures/execution_state_v5.sast"),
+        include_bytes!("../tests/fixtures/execution_state_v6.sast"),
+    ];
+
+    // Output bits of forward([0.5, -1.0, 0.25]) when each fixture was written
+    const FIXTURE_OUTPUTS: [[u32; 2]; 6] = [
+        [1_057_857_101, 3_183_085_273],
+        [1_054_177_462, 3_173_752_788],
+        [1_057_624_163, 3_196_482_936],
+        [1_057_624_163, 3_196_482_936],
+        [1_057_624_163, 3_196_482_936],
+        [1_057_624_064, 3_196_483_072],
+    ];
+
+    // What each execution state version predates, oldest first
+    const STATE_DEFAULTS: [&str; 6] = [
+        "gradient optimizer reset to SGD without moments",
+        "loss set to mean squared error",
+        "training safety set to its defaults",
+        "training precision set to f32 with a fresh loss scale",
+        "inference arithmetic set to f32",
+        "no LoRA adapters attached",
+    ];
+
+    const SECURITY_DEFAULT: &str = "security policy not recorded; the runtime keeps its own";
+
+    // A format 2 snapshot holding the weights and execution state of a trace with no
+    // operations as network "agent"
+    fn snapshot_from_trace(trace: &[u8]) -> Vec<u8> {
+        assert_eq!(trace[trace.len() - 4..], [0; 4], "trace holds operations");
+        let runtime = encode_runtime(&NeuralRuntime::new());
+        let mut writer = ByteWriter::new();
+        writer.bytes(SNAPSHOT_MAGIC);
+        writer.u16(2);
+        writer.u16(u16::from_le_bytes([trace[4], trace[5]]));
+        write_name(&mut writer, "fixture");
+        writer.u32(runtime.len() as u32);
+        writer.bytes(&runtime);
+        writer.u32(1);
+        write_name(&mut writer, "agent");
+        writer.bytes(&trace[8..trace.len() - 4]);
+        writer.u32(0);
+        writer.u32(0);
+        writer.finish()
+    }
+
+    fn network_migrations(weights_version: Option<u16>, state_version: u16) -> Vec<String> {
+        let weights = weights_version.map(|version| format!("weights read from SASW version {}", version));
+        let state = STATE_DEFAULTS[state_version as usize - 1..].iter().map(|entry| entry.to_string());
+        weights.into_iter().chain(state).map(|entry| format!("network \"agent\": {}", entry)).collect()
+    }
+
+    // The migrated network gives the fixture's outputs and keeps every setting its
+    // version recorded
+    fn check_fixture_network(network: &NeuralNetwork, state_version: u16) {
+        let outputs: Vec<u32> = network.forward(&[0.5, -1.0, 0.25]).unwrap().iter().map(|value| value.to_bits()).collect();
+        assert_eq!(outputs, FIXTURE_OUTPUTS[state_version as usize - 1], "execution state version {}", state_version);
+        let optimizer = network.optimizer().kind;
+        assert_eq!(optimizer, if state_version >= 2 { GradientOptimizerKind::Adam } else { GradientOptimizerKind::Sgd });
+        let loss = network.loss_function().unwrap().kind;
+        assert_eq!(loss, if state_version >= 3 { LossKind::Huber } else { LossKind::MeanSquaredError });
+        assert_eq!(network.training_safety().max_gradient_norm, if state_version >= 4 { 5.0 } else { 0.0 });
+        assert_eq!(network.arithmetic(), if state_version >= 6 { Arithmetic::FixedQ16 } else { Arithmetic::F32 });
+        assert_eq!(network.adapter_parameter_count(), 0);
+    }
+
+    #[test]
+    fn migrates_every_execution_state_version() {
+        for (index, trace) in EXECUTION_STATES.iter().enumerate() {
+            let state_version = index as u16 + 1;
+            let snapshot = RuntimeSnapshot::from_bytes(&snapshot_from_trace(trace)).unwrap();
+            let report = snapshot.compatibility_report();
+            assert_eq!(report.source_version(), 2);
+            assert_eq!(report.writer().as_deref(), Some("fixture"));
+            let weights_version = (state_version < 5).then_some(2);
+            let mut expected = network_migrations(weights_version, state_version);
+            expected.push(SECURITY_DEFAULT.to_string());
+            assert_eq!(report.migrated(), expected, "execution state version {}", state_version);
+            assert!(report.is_lossless());
+            check_fixture_network(&snapshot.network("agent").unwrap(), state_version);
+
+            // Traces go through the same migration
+            let replayed = NeuralNetwork::replay(trace).unwrap();
+            assert_eq!(replayed.first_divergence(), -1);
+        }
+    }
+
+    #[test]
+    fn migrates_format_1_snapshots() {
+        let bytes = include_bytes!("../tests/fixtures/snapshot_v1.sasn");
+        let snapshot = RuntimeSnapshot::from_bytes(bytes).unwrap();
+        let report = snapshot.compatibility_report();
+        assert_eq!(report.source_version(), 1);
+        assert_eq!(report.writer(), None);
+        let mut expected = vec!["writer version unknown (format version 1)".to_string()];
+        expected.extend(network_migrations(None, 5));
+        expected.push(SECURITY_DEFAULT.to_string());
+        assert_eq!(report.migrated(), expected);
+        assert!(report.dropped().is_empty());
+        check_fixture_network(&snapshot.network("agent").unwrap(), 5);
+
+        // The runtime keeps its own policy
+        let mut runtime = NeuralRuntime::new();
+        let mut policy = SecurityPolicy::new();
+        policy.max_length = 7;
+        runtime.set_security_policy(&policy).unwrap();
+        assert_eq!(runtime.restore(bytes).unwrap(), report);
+        assert_eq!(runtime.security_policy(), policy);
+    }
+
+    #[test]
+    fn migrates_format_2_snapshots() {
+        let fixtures: [(&[u8], u16); 2] = [
+            (include_bytes!("../tests/fixtures/snapshot_v2_state_v5.sasn"), 5),
+            (include_bytes!("../tests/fixtures/snapshot_v2_state_v6.sasn"), 6),
+        ];
+        for (bytes, state_version) in fixtures {
+            let snapshot = RuntimeSnapshot::from_bytes(bytes).unwrap();
+            let report = snapshot.compatibility_report();
+            assert_eq!(report.source_version(), 2);
+            assert!(report.writer().unwrap().starts_with("neural-wasm-runtime "));
+            assert_eq!(report.migrated(), network_migrations(None, state_version));
+            assert!(report.is_lossless());
+            check_fixture_network(&snapshot.network("agent").unwrap(), state_version);
+            assert_eq!(snapshot.security, Some(SecurityPolicy::new()));
+
+            // Written back out, the snapshot is current and migrates nothing
+            let current = RuntimeSnapshot::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+            assert_eq!(current.compatibility_report(), CompatibilityReport::current());
+            check_fixture_network(&current.network("agent").unwrap(), state_version);
+        }
+    }
+
+    #[test]
+    fn reports_unknown_extensions_as_dropped() {
+        let mut bytes = RuntimeSnapshot::capture(&NeuralRuntime::new()).to_bytes().unwrap();
+        // The snapshot ends with extension_count and the security policy section
+        // (tag, len and 20 bytes); count one more and append an unknown tag
+        let count_at = bytes.len() - 26 - 4;
+        bytes[count_at] += 1;
+        bytes.extend_from_slice(&99u16.to_le_bytes());
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3]);
+        let report = RuntimeSnapshot::from_bytes(&bytes).unwrap().compatibility_report();
+        assert_eq!(report.dropped(), ["extension section 99 (3 bytes)"]);
+        assert!(!report.is_lossless());
+        assert!(report.migrated().is_empty());
+    }
+}
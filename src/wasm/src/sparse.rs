@@ -0,0 +1,346 @@
+// Sparse matrices in coordinate (COO) and compressed sparse row (CSR) form
+//
+// COO holds (row, col, value) triplets in any order and is the easy format to build
+// connectivity in; CSR is the compute format. Row r's non-zeros are
+// values[row_offsets[r]..row_offsets[r + 1]], at the columns held in the same range
+// of col_indices, sorted by column. Indices are u32, which covers any matrix that
+// fits in wasm32 memory. A non-zero costs 8 bytes against 4 for a dense f32, so CSR
+// only saves memory once more than half of the entries are zero, but a multiply
+// always costs time proportional to the non-zeros alone.
+//
+// The SIMD matrix-vector kernel gathers four x values per step into one register
+// and multiplies them against four contiguous stored values.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::checked;
+use crate::error::{NeuralError, NeuralResult};
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CooMatrix {
+    pub(crate) rows: usize,
+    pub(crate) cols: usize,
+    pub(crate) row_indices: Vec<u32>,
+    pub(crate) col_indices: Vec<u32>,
+    pub(crate) values: Vec<f32>,
+}
+
+impl CooMatrix {
+    pub(crate) fn from_triplets(
+        rows: usize,
+        cols: usize,
+        row_indices: &[u32],
+        col_indices: &[u32],
+        values: &[f32],
+    ) -> NeuralResult<CooMatrix> {
+        if row_indices.len() != values.len() {
+            return Err(NeuralError::DimensionMismatch { expected: values.len(), actual: row_indices.len() });
+        }
+        if col_indices.len() != values.len() {
+            return Err(NeuralError::DimensionMismatch { expected: values.len(), actual: col_indices.len() });
+        }
+        check_dimensions(rows, cols)?;
+        if let Some(&row) = row_indices.iter().find(|&&row| row as usize >= rows) {
+            return Err(NeuralError::IndexOutOfRange { index: row as usize, len: rows });
+        }
+        if let Some(&col) = col_indices.iter().find(|&&col| col as usize >= cols) {
+            return Err(NeuralError::IndexOutOfRange { index: col as usize, len: cols });
+        }
+        if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(CooMatrix {
+            rows,
+            cols,
+            row_indices: row_indices.to_vec(),
+            col_indices: col_indices.to_vec(),
+            values: values.to_vec(),
+        })
+    }
+
+    // Triplets for every non-zero of a row-major [rows][cols] matrix, in row order
+    pub(crate) fn from_dense(dense: &[f32], rows: usize, cols: usize) -> CooMatrix {
+        let mut coo = CooMatrix { rows, cols, row_indices: Vec::new(), col_indices: Vec::new(), values: Vec::new() };
+        for (row, values) in dense.chunks_exact(cols.max(1)).take(rows).enumerate() {
+            for (col, &value) in values.iter().enumerate() {
+                if value != 0.0 {
+                    coo.row_indices.push(row as u32);
+                    coo.col_indices.push(col as u32);
+                    coo.values.push(value);
+                }
+            }
+        }
+        coo
+    }
+
+    // Sort by (row, col) and sum duplicate entries; entries that cancel stay stored
+    pub(crate) fn to_csr(&self) -> CsrMatrix {
+        let mut order: Vec<usize> = (0..self.values.len()).collect();
+        order.sort_by_key(|&index| (self.row_indices[index], self.col_indices[index]));
+
+        let mut row_offsets = vec![0u32; self.rows + 1];
+        let mut col_indices: Vec<u32> = Vec::with_capacity(order.len());
+        let mut values: Vec<f32> = Vec::with_capacity(order.len());
+        let mut last: Option<(u32, u32)> = None;
+        for index in order {
+            let entry = (self.row_indices[index], self.col_indices[index]);
+            match values.last_mut() {
+                Some(value) if last == Some(entry) => *value += self.values[index],
+                _ => {
+                    col_indices.push(entry.1);
+                    values.push(self.values[index]);
+                    row_offsets[entry.0 as usize + 1] += 1;
+                }
+            }
+            last = Some(entry);
+        }
+        // Per-row counts to running offsets
+        for row in 0..self.rows {
+            row_offsets[row + 1] += row_offsets[row];
+        }
+        CsrMatrix { rows: self.rows, cols: self.cols, row_offsets, col_indices, values }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CsrMatrix {
+    pub(crate) rows: usize,
+    pub(crate) cols: usize,
+    pub(crate) row_offsets: Vec<u32>,
+    pub(crate) col_indices: Vec<u32>,
+    pub(crate) values: Vec<f32>,
+}
+
+impl CsrMatrix {
+    // Keep every non-zero of a row-major [rows][cols] matrix
+    pub(crate) fn from_dense(dense: &[f32], rows: usize, cols: usize) -> CsrMatrix {
+        let mut row_offsets = Vec::with_capacity(rows + 1);
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        row_offsets.push(0);
+        for row in dense.chunks_exact(cols.max(1)).take(rows) {
+            for (col, &value) in row.iter().enumerate() {
+                if value != 0.0 {
+                    col_indices.push(col as u32);
+                    values.push(value);
+                }
+            }
+            row_offsets.push(values.len() as u32);
+        }
+        // Zero-width matrices have no rows to iterate but still need one offset per row
+        row_offsets.resize(rows + 1, values.len() as u32);
+        CsrMatrix { rows, cols, row_offsets, col_indices, values }
+    }
+
+    pub(crate) fn to_dense(&self) -> Vec<f32> {
+        let mut dense = vec![0.0; self.rows * self.cols];
+        for row in 0..self.rows {
+            let (cols, values) = self.row(row);
+            for (&col, &value) in cols.iter().zip(values) {
+                dense[row * self.cols + col as usize] = value;
+            }
+        }
+        dense
+    }
+
+    pub(crate) fn to_coo(&self) -> CooMatrix {
+        let row_indices = (0..self.rows)
+            .flat_map(|row| std::iter::repeat_n(row as u32, self.row(row).0.len()))
+            .collect();
+        CooMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            row_indices,
+            col_indices: self.col_indices.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    pub(crate) fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn byte_len(&self) -> usize {
+        std::mem::size_of_val(self.row_offsets.as_slice())
+            + std::mem::size_of_val(self.col_indices.as_slice())
+            + std::mem::size_of_val(self.values.as_slice())
+    }
+
+    // Column indices and values of one row's non-zeros
+    pub(crate) fn row(&self, row: usize) -> (&[u32], &[f32]) {
+        let range = self.row_offsets[row] as usize..self.row_offsets[row + 1] as usize;
+        (&self.col_indices[range.clone()], &self.values[range])
+    }
+
+    // y[rows] = A · x[cols], touching only the stored non-zeros
+    pub(crate) fn matvec_into(&self, x: &[f32], y: &mut [f32], simd: bool) -> NeuralResult<()> {
+        if x.len() != self.cols {
+            return Err(NeuralError::DimensionMismatch { expected: self.cols, actual: x.len() });
+        }
+        if y.len() != self.rows {
+            return Err(NeuralError::DimensionMismatch { expected: self.rows, actual: y.len() });
+        }
+        for (row, out) in y.iter_mut().enumerate() {
+            let (cols, values) = self.row(row);
+            *out = simd_dispatch!(simd && values.len() >= 4, simd_sparse_dot(cols, values, x), scalar_sparse_dot(cols, values, x));
+        }
+        Ok(())
+    }
+}
+
+fn check_dimensions(rows: usize, cols: usize) -> NeuralResult<()> {
+    if u32::try_from(rows).is_err() || u32::try_from(cols).is_err() {
+        return Err(NeuralError::InvalidConfiguration("sparse matrix dimensions exceed u32".to_string()));
+    }
+    Ok(())
+}
+
+fn scalar_sparse_dot(cols: &[u32], values: &[f32], x: &[f32]) -> f32 {
+    cols.iter().zip(values).map(|(&col, value)| value * x[col as usize]).sum()
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_sparse_dot(cols: &[u32], values: &[f32], x: &[f32]) -> f32 {
+    let col_chunks = cols.chunks_exact(4);
+    let value_chunks = values.chunks_exact(4);
+    let tail = scalar_sparse_dot(col_chunks.remainder(), value_chunks.remainder(), x);
+    let mut acc = f32x4_splat(0.0);
+
+    for (col, value) in col_chunks.zip(value_chunks) {
+        let gathered = f32x4(x[col[0] as usize], x[col[1] as usize], x[col[2] as usize], x[col[3] as usize]);
+        acc = f32x4_add(acc, f32x4_mul(simd::load(value), gathered));
+    }
+
+    simd::f32_sum(acc) + tail
+}
+
+// CSR matrix for JavaScript, e.g. synaptic mesh connectivity
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SparseMatrix {
+    matrix: CsrMatrix,
+}
+
+#[wasm_bindgen]
+impl SparseMatrix {
+    // Non-zeros of a row-major [rows × cols] matrix
+    #[wasm_bindgen]
+    pub fn from_dense(dense: &[f32], rows: usize, cols: usize) -> Result<SparseMatrix, NeuralError> {
+        check_dimensions(rows, cols)?;
+        let expected = checked::elements(rows, cols)?;
+        if dense.len() != expected {
+            return Err(NeuralError::DimensionMismatch { expected, actual: dense.len() });
+        }
+        if let Some(index) = dense.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        Ok(SparseMatrix { matrix: CooMatrix::from_dense(dense, rows, cols).to_csr() })
+    }
+
+    // COO triplets in any order; duplicate (row, col) entries are summed
+    #[wasm_bindgen]
+    pub fn from_triplets(
+        rows: usize,
+        cols: usize,
+        row_indices: &[u32],
+        col_indices: &[u32],
+        values: &[f32],
+    ) -> Result<SparseMatrix, NeuralError> {
+        let coo = CooMatrix::from_triplets(rows, cols, row_indices, col_indices, values)?;
+        Ok(SparseMatrix { matrix: coo.to_csr() })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.matrix.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.matrix.cols
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nnz(&self) -> usize {
+        self.matrix.nnz()
+    }
+
+    // Stored entries as a fraction of rows × cols
+    #[wasm_bindgen(getter)]
+    pub fn density(&self) -> f64 {
+        let size = self.matrix.rows * self.matrix.cols;
+        if size == 0 {
+            0.0
+        } else {
+            self.matrix.nnz() as f64 / size as f64
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn byte_len(&self) -> usize {
+        self.matrix.byte_len()
+    }
+
+    // y = A · x
+    #[wasm_bindgen]
+    pub fn matvec(&self, x: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        let mut y = vec![0.0; self.matrix.rows];
+        self.matrix.matvec_into(x, &mut y, crate::check_simd_support())?;
+        Ok(y)
+    }
+
+    #[wasm_bindgen]
+    pub fn to_dense(&self) -> Vec<f32> {
+        self.matrix.to_dense()
+    }
+
+    // CSR arrays, e.g. for upload to a GPU buffer
+    #[wasm_bindgen]
+    pub fn row_offsets(&self) -> Vec<u32> {
+        self.matrix.row_offsets.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn col_indices(&self) -> Vec<u32> {
+        self.matrix.col_indices.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn values(&self) -> Vec<f32> {
+        self.matrix.values.clone()
+    }
+
+    // Row index of every stored entry, pairing with col_indices() and values() as COO
+    #[wasm_bindgen]
+    pub fn row_indices(&self) -> Vec<u32> {
+        self.matrix.to_coo().row_indices
+    }
+}
+
+impl SparseMatrix {
+    pub(crate) fn csr(&self) -> &CsrMatrix {
+        &self.matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_dense_rejects_huge_dimensions_without_overflowing() {
+        let largest = u32::MAX as usize;
+        // rows × cols overflows a 32-bit usize and is far beyond `dense` on 64-bit
+        assert!(matches!(
+            SparseMatrix::from_dense(&[], largest, largest),
+            Err(NeuralError::InvalidConfiguration(_) | NeuralError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(SparseMatrix::from_dense(&[], largest + 1, 1), Err(NeuralError::InvalidConfiguration(_))));
+        assert!(matches!(SparseMatrix::from_dense(&[1.0; 5], 2, 3), Err(NeuralError::DimensionMismatch { expected: 6, actual: 5 })));
+    }
+}
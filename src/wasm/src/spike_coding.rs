@@ -0,0 +1,194 @@
+// Spike-train encoders and decoders between real values and the spiking network
+//
+// Each value is first scaled to x ∈ [0, 1] by [min_value, max_value] (and clamped);
+// a spike train covers one window of `window_ms`.
+//   Rate        one channel per value firing as a Poisson process at x · max_rate_hz,
+//               sampled in bins of dt_ms; decoded as count / (max_rate · window)
+//   Latency     one channel per value firing once at (1 - x) · window, so larger
+//               values fire earlier and x = 0 stays silent; decoded from the first
+//               spike, silence meaning x = 0
+//   Population  population_size channels per value with preferred values spread
+//               evenly over [0, 1], each a Poisson process at
+//               max_rate_hz · exp(-(x - preferred)² / 2σ²), σ = tuning_width;
+//               decoded as the spike-count-weighted mean of the preferred values
+// Channels of value i are i (or i·population_size..(i + 1)·population_size), so
+// SpikingNetwork::inject can feed them to consecutive input neurons and
+// SpikeEvents::window can cut a population's output back out for decoding.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::rng::Rng;
+use crate::spiking::SpikeEvents;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpikeCoding {
+    Rate = 0,
+    Latency = 1,
+    Population = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpikeCoder {
+    pub coding: SpikeCoding,
+    pub min_value: f32,
+    pub max_value: f32,
+    pub window_ms: f32,
+    // Poisson bin width
+    pub dt_ms: f32,
+    pub max_rate_hz: f32,
+    pub population_size: usize,
+    // Tuning curve σ as a fraction of the value range
+    pub tuning_width: f32,
+}
+
+#[wasm_bindgen]
+impl SpikeCoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(coding: SpikeCoding) -> SpikeCoder {
+        SpikeCoder {
+            coding,
+            min_value: 0.0,
+            max_value: 1.0,
+            window_ms: 100.0,
+            dt_ms: 1.0,
+            max_rate_hz: 100.0,
+            population_size: 8,
+            tuning_width: 0.15,
+        }
+    }
+
+    // Spike channels used for `value_count` values
+    #[wasm_bindgen]
+    pub fn channels(&self, value_count: usize) -> usize {
+        match self.coding {
+            SpikeCoding::Population => value_count * self.population_size,
+            _ => value_count,
+        }
+    }
+
+    // One window of spikes, times from 0, for `values`
+    #[wasm_bindgen]
+    pub fn encode(&self, values: &[f32], seed: u64) -> Result<SpikeEvents, NeuralError> {
+        self.validate()?;
+        if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        let scaled: Vec<f32> = values.iter().map(|&value| self.scale(value)).collect();
+        let mut spikes = SpikeEvents::default();
+        match self.coding {
+            SpikeCoding::Rate => {
+                let rates: Vec<f32> = scaled.iter().map(|x| x * self.max_rate_hz).collect();
+                self.poisson(&rates, seed, &mut spikes);
+            }
+            SpikeCoding::Latency => {
+                let mut firing: Vec<(f32, u32)> = scaled
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &x)| x > 0.0)
+                    .map(|(channel, x)| ((1.0 - x) * self.window_ms, channel as u32))
+                    .collect();
+                firing.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for (time, channel) in firing {
+                    spikes.neurons.push(channel);
+                    spikes.times_ms.push(time);
+                }
+            }
+            SpikeCoding::Population => {
+                let preferred = self.preferred_values();
+                let two_sigma_sq = 2.0 * self.tuning_width * self.tuning_width;
+                let rates: Vec<f32> = scaled
+                    .iter()
+                    .flat_map(|&x| preferred.iter().map(move |p| self.max_rate_hz * (-(x - p) * (x - p) / two_sigma_sq).exp()))
+                    .collect();
+                self.poisson(&rates, seed, &mut spikes);
+            }
+        }
+        Ok(spikes)
+    }
+
+    // Recover `value_count` values from one window of spikes timed from 0
+    #[wasm_bindgen]
+    pub fn decode(&self, spikes: &SpikeEvents, value_count: usize) -> Result<Vec<f32>, NeuralError> {
+        self.validate()?;
+        let channels = self.channels(value_count);
+        if let Some(&neuron) = spikes.neurons.iter().find(|&&neuron| neuron as usize >= channels) {
+            return Err(NeuralError::IndexOutOfRange { index: neuron as usize, len: channels });
+        }
+        let mut counts = vec![0.0f32; channels];
+        let mut first_times = vec![f32::INFINITY; channels];
+        for (&neuron, &time) in spikes.neurons.iter().zip(&spikes.times_ms) {
+            if (0.0..self.window_ms).contains(&time) {
+                counts[neuron as usize] += 1.0;
+                first_times[neuron as usize] = first_times[neuron as usize].min(time);
+            }
+        }
+        let scaled: Vec<f32> = match self.coding {
+            SpikeCoding::Rate => {
+                let full = self.max_rate_hz * self.window_ms / 1000.0;
+                counts.iter().map(|count| (count / full).min(1.0)).collect()
+            }
+            SpikeCoding::Latency => {
+                first_times.iter().map(|&time| if time.is_finite() { 1.0 - time / self.window_ms } else { 0.0 }).collect()
+            }
+            SpikeCoding::Population => {
+                let preferred = self.preferred_values();
+                counts
+                    .chunks_exact(self.population_size)
+                    .map(|population| {
+                        let total: f32 = population.iter().sum();
+                        if total == 0.0 {
+                            return 0.0;
+                        }
+                        population.iter().zip(&preferred).map(|(count, p)| count * p).sum::<f32>() / total
+                    })
+                    .collect()
+            }
+        };
+        Ok(scaled.iter().map(|x| self.min_value + x * (self.max_value - self.min_value)).collect())
+    }
+}
+
+impl SpikeCoder {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !self.min_value.is_finite() || !self.max_value.is_finite() || self.min_value >= self.max_value {
+            return Err(NeuralError::InvalidConfiguration("value range must be finite with min_value < max_value".to_string()));
+        }
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(self.window_ms) || !positive(self.dt_ms) || !positive(self.max_rate_hz) {
+            return Err(NeuralError::InvalidConfiguration("window, bin width and maximum rate must be positive".to_string()));
+        }
+        if self.max_rate_hz * self.dt_ms / 1000.0 > 1.0 {
+            return Err(NeuralError::InvalidConfiguration("max_rate_hz exceeds one spike per bin".to_string()));
+        }
+        if self.coding == SpikeCoding::Population && (self.population_size < 2 || !positive(self.tuning_width)) {
+            return Err(NeuralError::InvalidConfiguration("population coding needs at least two channels and a positive width".to_string()));
+        }
+        Ok(())
+    }
+
+    fn scale(&self, value: f32) -> f32 {
+        ((value - self.min_value) / (self.max_value - self.min_value)).clamp(0.0, 1.0)
+    }
+
+    fn preferred_values(&self) -> Vec<f32> {
+        (0..self.population_size).map(|index| index as f32 / (self.population_size - 1) as f32).collect()
+    }
+
+    // Bernoulli draws per bin at each channel's rate, spikes at bin starts
+    fn poisson(&self, rates_hz: &[f32], seed: u64, spikes: &mut SpikeEvents) {
+        let mut rng = Rng::new(seed);
+        let bins = (self.window_ms / self.dt_ms).ceil() as usize;
+        for bin in 0..bins {
+            let time = bin as f32 * self.dt_ms;
+            for (channel, rate) in rates_hz.iter().enumerate() {
+                if rng.next_f32() < rate * self.dt_ms / 1000.0 {
+                    spikes.neurons.push(channel as u32);
+                    spikes.times_ms.push(time);
+                }
+            }
+        }
+    }
+}
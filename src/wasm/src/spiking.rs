@@ -0,0 +1,827 @@
+// Spiking neural network simulation with leaky integrate-and-fire neurons
+//
+// Membrane potentials integrate with forward Euler:
+//   dv/dt = (-(v - v_rest) + R·I) / tau_m
+// A neuron whose potential reaches threshold emits a spike, resets, and ignores
+// input for the refractory period. Each spike travels along the neuron's outgoing
+// synapses and is delivered after the synapse delay as an instantaneous jump of
+// `weight` in the target's potential. Times are in milliseconds. Izhikevich and
+// AdEx neurons (neuron_model.rs) can replace LIF for the whole network.
+// With STDP enabled, synapse weights also adapt online from decaying spike traces.
+// Rate-based Hebbian rules (plasticity.rs) can be applied to every synapse at once
+// from per-neuron activity, e.g. firing rates over a window. Homeostasis
+// (homeostasis.rs) can hold each population's firing rates within a target band.
+//
+// Spikes in flight wait in a time-ordered event queue (event_queue.rs). step()
+// advances every neuron by a fixed dt; run_event_driven() instead jumps from event
+// to event, solving the LIF equation exactly in between:
+//   v(t) = v_inf + (v(t0) - v_inf) · exp(-(t - t0) / tau_m),  v_inf = v_rest + R·I
+// A neuron is only touched when a spike reaches it or when its input current is
+// due to carry it to threshold, at
+//   t0 + tau_m · ln((v(t0) - v_inf) / (v_threshold - v_inf))  (only if v_inf > v_threshold)
+// so sparsely active networks cost O(spikes · log events) rather than
+// O(neurons · steps).
+//
+// With recording enabled the network keeps a ring of its latest spikes, readable
+// as a dense raster or a rate histogram (raster.rs).
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::event_queue::EventQueue;
+use crate::homeostasis::{Homeostasis, HomeostasisParams};
+use crate::neuron_model::{AdExParams, IzhikevichParams, Model, NeuronModel};
+use crate::plasticity::{self, HebbianRule, StdpParams, StdpTraces};
+use crate::raster::{self, SpikeLog};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LifParams {
+    pub tau_m: f32,
+    pub v_rest: f32,
+    pub v_reset: f32,
+    pub v_threshold: f32,
+    pub refractory_ms: f32,
+    pub resistance: f32,
+}
+
+impl Default for LifParams {
+    fn default() -> Self {
+        LifParams {
+            tau_m: 20.0,
+            v_rest: -65.0,
+            v_reset: -70.0,
+            v_threshold: -50.0,
+            refractory_ms: 2.0,
+            resistance: 1.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl LifParams {
+    // Cortical-style defaults (mV, ms)
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> LifParams {
+        LifParams::default()
+    }
+}
+
+impl LifParams {
+    pub(crate) fn validate(&self) -> NeuralResult<()> {
+        let values = [self.tau_m, self.v_rest, self.v_reset, self.v_threshold, self.refractory_ms, self.resistance];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(NeuralError::InvalidConfiguration("LIF parameters must be finite".to_string()));
+        }
+        if self.tau_m <= 0.0 || self.refractory_ms < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("tau_m must be positive and refractory_ms non-negative".to_string()));
+        }
+        if self.v_reset >= self.v_threshold {
+            return Err(NeuralError::InvalidConfiguration("v_reset must be below v_threshold".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Synapse {
+    pub(crate) pre: usize,
+    pub(crate) post: usize,
+    pub(crate) weight: f32,
+    pub(crate) delay_ms: f32,
+}
+
+// Spike on its way to a neuron: along a synapse, whose weight is read on arrival,
+// or injected from outside with a fixed voltage jump
+#[derive(Debug, Clone, Copy)]
+enum Arrival {
+    Synapse(usize),
+    External { neuron: usize, voltage: f32 },
+}
+
+// Scheduled visit to a neuron during an event-driven run: `fire` marks a predicted
+// threshold crossing, otherwise the end of its refractory period. Stale once the
+// neuron's version has moved on.
+#[derive(Debug, Clone, Copy)]
+struct Wake {
+    neuron: usize,
+    version: u32,
+    fire: bool,
+}
+
+// Per-neuron clocks of an event-driven run; each potential is current as of `updated`
+struct EventClock {
+    params: LifParams,
+    updated: Vec<f32>,
+    refractory_until: Vec<f32>,
+    versions: Vec<u32>,
+    wakes: EventQueue<Wake>,
+    spikes: SpikeEvents,
+}
+
+// Spikes as (neuron, time in ms) pairs in time order, from an event-driven run or
+// a spike encoder (spike_coding.rs)
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpikeEvents {
+    pub(crate) neurons: Vec<u32>,
+    pub(crate) times_ms: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl SpikeEvents {
+    // Spikes recorded elsewhere; they are sorted by time
+    #[wasm_bindgen(constructor)]
+    pub fn new(neurons: &[u32], times: &[f32]) -> Result<SpikeEvents, NeuralError> {
+        if neurons.len() != times.len() {
+            return Err(NeuralError::DimensionMismatch { expected: neurons.len(), actual: times.len() });
+        }
+        if let Some(index) = times.iter().position(|time| !time.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        let mut order: Vec<usize> = (0..neurons.len()).collect();
+        order.sort_by(|&a, &b| times[a].total_cmp(&times[b]));
+        Ok(SpikeEvents { neurons: order.iter().map(|&i| neurons[i]).collect(), times_ms: order.iter().map(|&i| times[i]).collect() })
+    }
+
+    // Spikes of neurons first..first + count within [start_ms, end_ms), renumbered
+    // from 0 and timed from start_ms, e.g. to decode one population's output
+    #[wasm_bindgen]
+    pub fn window(&self, first: u32, count: u32, start_ms: f32, end_ms: f32) -> SpikeEvents {
+        let mut selected = SpikeEvents::default();
+        for (&neuron, &time) in self.neurons.iter().zip(&self.times_ms) {
+            if neuron >= first && neuron - first < count && time >= start_ms && time < end_ms {
+                selected.neurons.push(neuron - first);
+                selected.times_ms.push(time - start_ms);
+            }
+        }
+        selected
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn neurons(&self) -> Vec<u32> {
+        self.neurons.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn times(&self) -> Vec<f32> {
+        self.times_ms.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.neurons.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.neurons.is_empty()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SpikingNetwork {
+    model: Model,
+    potentials: Vec<f32>,
+    // Izhikevich u or AdEx w per neuron; unused by LIF
+    recovery: Vec<f32>,
+    refractory_remaining: Vec<f32>,
+    input_currents: Vec<f32>,
+    spike_counts: Vec<u32>,
+    last_spike_ms: Vec<f32>,
+    synapses: Vec<Synapse>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+    // Spikes in flight, keyed by arrival time
+    pending: EventQueue<Arrival>,
+    stdp: Option<StdpTraces>,
+    homeostasis: Option<Homeostasis>,
+    spike_log: Option<SpikeLog>,
+    time_ms: f32,
+}
+
+#[wasm_bindgen]
+impl SpikingNetwork {
+    #[wasm_bindgen(constructor)]
+    pub fn new(neuron_count: usize, params: &LifParams) -> Result<SpikingNetwork, NeuralError> {
+        params.validate()?;
+        SpikingNetwork::with_model(neuron_count, Model::Lif(*params))
+    }
+
+    #[wasm_bindgen]
+    pub fn with_izhikevich(neuron_count: usize, params: &IzhikevichParams) -> Result<SpikingNetwork, NeuralError> {
+        params.validate()?;
+        SpikingNetwork::with_model(neuron_count, Model::Izhikevich(*params))
+    }
+
+    #[wasm_bindgen]
+    pub fn with_adex(neuron_count: usize, params: &AdExParams) -> Result<SpikingNetwork, NeuralError> {
+        params.validate()?;
+        SpikingNetwork::with_model(neuron_count, Model::AdEx(*params))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn model(&self) -> NeuronModel {
+        self.model.kind()
+    }
+
+    // Add a synapse and return its index
+    #[wasm_bindgen]
+    pub fn connect(&mut self, pre: usize, post: usize, weight: f32, delay_ms: f32) -> Result<usize, NeuralError> {
+        self.check_neuron(pre)?;
+        self.check_neuron(post)?;
+        if !weight.is_finite() || !delay_ms.is_finite() || delay_ms < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("synapse weight must be finite and delay non-negative".to_string()));
+        }
+
+        self.synapses.push(Synapse { pre, post, weight, delay_ms });
+        let index = self.synapses.len() - 1;
+        self.outgoing[pre].push(index);
+        self.incoming[post].push(index);
+        Ok(index)
+    }
+
+    // Constant external current driving a neuron until changed
+    #[wasm_bindgen]
+    pub fn set_input_current(&mut self, neuron: usize, current: f32) -> Result<(), NeuralError> {
+        self.check_neuron(neuron)?;
+        if !current.is_finite() {
+            return Err(NeuralError::NonFiniteInput { index: neuron });
+        }
+        self.input_currents[neuron] = current;
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_input_currents(&mut self, currents: &[f32]) -> Result<(), NeuralError> {
+        if currents.len() != self.potentials.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.potentials.len(), actual: currents.len() });
+        }
+        if let Some(index) = currents.iter().position(|current| !current.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+        self.input_currents.copy_from_slice(currents);
+        Ok(())
+    }
+
+    // Advance the simulation by `dt` ms and return the indices of neurons that spiked
+    #[wasm_bindgen]
+    pub fn step(&mut self, dt: f32) -> Result<Vec<u32>, NeuralError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("time step must be positive".to_string()));
+        }
+        self.time_ms += dt;
+        if let Some(traces) = self.stdp.as_mut() {
+            traces.decay(dt);
+        }
+        self.deliver_pending();
+
+        let model = self.model;
+        let mut fired = Vec::new();
+        for neuron in 0..self.potentials.len() {
+            if self.refractory_remaining[neuron] > 0.0 {
+                self.refractory_remaining[neuron] = (self.refractory_remaining[neuron] - dt).max(0.0);
+                self.potentials[neuron] = model.reset_potential();
+                continue;
+            }
+
+            let offset = self.homeostasis.as_ref().map_or(0.0, |homeostasis| homeostasis.threshold_offsets[neuron]);
+            let update = model.integrate(self.potentials[neuron], self.recovery[neuron], self.input_currents[neuron], offset, dt);
+            self.potentials[neuron] = update.v;
+            self.recovery[neuron] = update.recovery;
+            if update.fired {
+                self.refractory_remaining[neuron] = model.refractory_ms();
+                fired.push(neuron as u32);
+            }
+        }
+
+        for &neuron in &fired {
+            self.apply_online_stdp(neuron as usize);
+            self.emit_spike(neuron as usize);
+        }
+        self.regulate(dt, &fired);
+        Ok(fired)
+    }
+
+    // Advance by `duration_ms` from event to event rather than in fixed steps.
+    // Input currents are held for the whole run. Needs LIF neurons without online
+    // STDP or homeostasis, whose dynamics have no closed form between events.
+    #[wasm_bindgen]
+    pub fn run_event_driven(&mut self, duration_ms: f32) -> Result<SpikeEvents, NeuralError> {
+        if !duration_ms.is_finite() || duration_ms <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("duration must be positive".to_string()));
+        }
+        let Model::Lif(params) = self.model else {
+            return Err(NeuralError::InvalidConfiguration("event-driven simulation needs LIF neurons".to_string()));
+        };
+        if self.stdp.is_some() || self.homeostasis.is_some() {
+            return Err(NeuralError::InvalidConfiguration(
+                "event-driven simulation does not support online STDP or homeostasis".to_string(),
+            ));
+        }
+
+        let start = self.time_ms;
+        let end = start + duration_ms;
+        let neuron_count = self.potentials.len();
+        let mut clock = EventClock {
+            params,
+            updated: vec![start; neuron_count],
+            refractory_until: self.refractory_remaining.iter().map(|remaining| start + remaining).collect(),
+            versions: vec![0; neuron_count],
+            wakes: EventQueue::new(),
+            spikes: SpikeEvents::default(),
+        };
+        for neuron in 0..neuron_count {
+            if clock.refractory_until[neuron] > start {
+                clock.wakes.push(clock.refractory_until[neuron], Wake { neuron, version: 0, fire: false });
+            } else {
+                self.predict(&mut clock, neuron, start);
+            }
+        }
+
+        loop {
+            let wake_time = clock.wakes.next_time().filter(|&time| time <= end);
+            let delivery_time = self.pending.next_time().filter(|&time| time <= end);
+            match (delivery_time, wake_time) {
+                (Some(delivery), wake) if wake.is_none_or(|wake| delivery <= wake) => {
+                    if let Some((time, arrival)) = self.pending.pop_due(delivery) {
+                        self.deliver_event(&mut clock, arrival, time);
+                    }
+                }
+                (_, Some(wake)) => {
+                    if let Some((time, event)) = clock.wakes.pop_due(wake) {
+                        if event.version != clock.versions[event.neuron] {
+                            continue;
+                        }
+                        self.evolve(&mut clock, event.neuron, time);
+                        if event.fire {
+                            self.fire(&mut clock, event.neuron, time);
+                        } else {
+                            self.predict(&mut clock, event.neuron, time);
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        for neuron in 0..neuron_count {
+            self.evolve(&mut clock, neuron, end);
+            self.refractory_remaining[neuron] = (clock.refractory_until[neuron] - end).max(0.0);
+        }
+        self.time_ms = end;
+        Ok(clock.spikes)
+    }
+
+    // Apply an immediate voltage jump, e.g. to stimulate a neuron from outside
+    #[wasm_bindgen]
+    pub fn stimulate(&mut self, neuron: usize, voltage: f32) -> Result<(), NeuralError> {
+        self.check_neuron(neuron)?;
+        if !voltage.is_finite() {
+            return Err(NeuralError::NonFiniteInput { index: neuron });
+        }
+        if self.refractory_remaining[neuron] <= 0.0 {
+            self.potentials[neuron] += voltage;
+        }
+        Ok(())
+    }
+
+    // Schedule each spike of `spikes` as a jump of `voltage` in neuron first + n,
+    // timed from now; encoded inputs (spike_coding.rs) feed the network this way
+    #[wasm_bindgen]
+    pub fn inject(&mut self, spikes: &SpikeEvents, first: usize, voltage: f32) -> Result<(), NeuralError> {
+        if !voltage.is_finite() {
+            return Err(NeuralError::InvalidConfiguration("injected voltage must be finite".to_string()));
+        }
+        if let Some(&neuron) = spikes.neurons.iter().max() {
+            self.check_neuron(first.saturating_add(neuron as usize))?;
+        }
+        if spikes.times_ms.iter().any(|&time| time < 0.0) {
+            return Err(NeuralError::InvalidConfiguration("injected spike times must be non-negative".to_string()));
+        }
+        for (&neuron, &time) in spikes.neurons.iter().zip(&spikes.times_ms) {
+            self.pending.push(self.time_ms + time, Arrival::External { neuron: first + neuron as usize, voltage });
+        }
+        Ok(())
+    }
+
+    // Enable online STDP: every spike updates the neuron's incoming and outgoing synapses
+    #[wasm_bindgen]
+    pub fn set_stdp(&mut self, params: &StdpParams) -> Result<(), NeuralError> {
+        params.validate()?;
+        self.stdp = Some(StdpTraces::new(*params, self.potentials.len()));
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn disable_stdp(&mut self) {
+        self.stdp = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn stdp_enabled(&self) -> bool {
+        self.stdp.is_some()
+    }
+
+    // Batch STDP from recorded spike history: each array holds one spike time (ms) per neuron,
+    // NaN or negative meaning "no spike". Every synapse whose pre and post neurons both spiked
+    // gets the pair update for t_post[post] - t_pre[pre]. Returns how many synapses changed.
+    #[wasm_bindgen]
+    pub fn apply_stdp(&mut self, pre_spikes: &[f32], post_spikes: &[f32], params: &StdpParams) -> Result<usize, NeuralError> {
+        params.validate()?;
+        for times in [pre_spikes, post_spikes] {
+            if times.len() != self.potentials.len() {
+                return Err(NeuralError::DimensionMismatch { expected: self.potentials.len(), actual: times.len() });
+            }
+        }
+
+        let spiked = |time: f32| time.is_finite() && time >= 0.0;
+        let mut updated = 0;
+        for synapse in self.synapses.iter_mut() {
+            let (t_pre, t_post) = (pre_spikes[synapse.pre], post_spikes[synapse.post]);
+            if spiked(t_pre) && spiked(t_post) {
+                synapse.weight = params.clip(synapse.weight + params.pair_update(t_pre, t_post));
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    // Update every synapse with `rule` from one activity value per neuron, the
+    // synapse's pre neuron giving x and its post neuron y
+    #[wasm_bindgen]
+    pub fn apply_hebbian(&mut self, rule: HebbianRule, activity: &[f32], learning_rate: f32) -> Result<(), NeuralError> {
+        if activity.len() != self.potentials.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.potentials.len(), actual: activity.len() });
+        }
+        plasticity::check_hebbian_inputs(&[activity], learning_rate)?;
+        // Gather into contiguous arrays so the update runs on the SIMD kernel
+        let mut weights: Vec<f32> = self.synapses.iter().map(|synapse| synapse.weight).collect();
+        let pre: Vec<f32> = self.synapses.iter().map(|synapse| activity[synapse.pre]).collect();
+        let post: Vec<f32> = self.synapses.iter().map(|synapse| activity[synapse.post]).collect();
+        plasticity::hebbian_pairs(rule, &mut weights, &pre, &post, learning_rate, crate::check_simd_support());
+        for (synapse, weight) in self.synapses.iter_mut().zip(weights) {
+            synapse.weight = weight;
+        }
+        Ok(())
+    }
+
+    // Regulate neurons first..first + count as one population. The threshold shift
+    // must stay below v_threshold - v_reset (LIF) or v_peak - v_threshold (AdEx) so
+    // a neuron can always fire.
+    #[wasm_bindgen]
+    pub fn set_homeostasis(&mut self, first: usize, count: usize, params: &HomeostasisParams) -> Result<(), NeuralError> {
+        params.validate()?;
+        if params.max_threshold_shift >= self.model.threshold_headroom() {
+            return Err(NeuralError::InvalidConfiguration("max_threshold_shift leaves no room to fire".to_string()));
+        }
+        self.check_population(first, count)?;
+        let neuron_count = self.potentials.len();
+        self.homeostasis.get_or_insert_with(|| Homeostasis::new(neuron_count)).assign(first, count, Some(*params));
+        Ok(())
+    }
+
+    // Stop regulating neurons first..first + count and restore their thresholds
+    #[wasm_bindgen]
+    pub fn clear_homeostasis(&mut self, first: usize, count: usize) -> Result<(), NeuralError> {
+        self.check_population(first, count)?;
+        if let Some(homeostasis) = self.homeostasis.as_mut() {
+            homeostasis.assign(first, count, None);
+            if homeostasis.params.iter().all(Option::is_none) {
+                self.homeostasis = None;
+            }
+        }
+        Ok(())
+    }
+
+    // Estimated firing rate (Hz) of each regulated neuron, 0 for the others
+    #[wasm_bindgen]
+    pub fn firing_rates(&self) -> Vec<f32> {
+        self.homeostasis.as_ref().map_or_else(|| vec![0.0; self.potentials.len()], |homeostasis| homeostasis.rates.clone())
+    }
+
+    // Homeostatic shift (mV) added to each neuron's threshold
+    #[wasm_bindgen]
+    pub fn threshold_offsets(&self) -> Vec<f32> {
+        self.homeostasis.as_ref().map_or_else(|| vec![0.0; self.potentials.len()], |homeostasis| homeostasis.threshold_offsets.clone())
+    }
+
+    // Time of each neuron's most recent spike, NaN if it has not fired
+    #[wasm_bindgen]
+    pub fn last_spike_times(&self) -> Vec<f32> {
+        self.last_spike_ms.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn synapse_weight(&self, synapse: usize) -> Result<f32, NeuralError> {
+        self.synapses
+            .get(synapse)
+            .map(|synapse| synapse.weight)
+            .ok_or(NeuralError::IndexOutOfRange { index: synapse, len: self.synapses.len() })
+    }
+
+    #[wasm_bindgen]
+    pub fn membrane_potentials(&self) -> Vec<f32> {
+        self.potentials.clone()
+    }
+
+    // Izhikevich u or AdEx adaptation current w of each neuron; zeros for LIF
+    #[wasm_bindgen]
+    pub fn recovery_variables(&self) -> Vec<f32> {
+        self.recovery.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn spike_counts(&self) -> Vec<u32> {
+        self.spike_counts.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn synapse_weights(&self) -> Vec<f32> {
+        self.synapses.iter().map(|synapse| synapse.weight).collect()
+    }
+
+    // Flattened [pre0, post0, pre1, post1, ...] in synapse index order
+    #[wasm_bindgen]
+    pub fn synapse_endpoints(&self) -> Vec<u32> {
+        self.synapses.iter().flat_map(|synapse| [synapse.pre as u32, synapse.post as u32]).collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn time(&self) -> f32 {
+        self.time_ms
+    }
+
+    #[wasm_bindgen]
+    pub fn neuron_count(&self) -> usize {
+        self.potentials.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn synapse_count(&self) -> usize {
+        self.synapses.len()
+    }
+
+    // Keep the latest `capacity` spikes for rasters and histograms; 0 stops recording
+    #[wasm_bindgen]
+    pub fn record_spikes(&mut self, capacity: usize) {
+        self.spike_log = (capacity > 0).then(|| SpikeLog::new(capacity));
+    }
+
+    #[wasm_bindgen]
+    pub fn recorded_spike_count(&self) -> usize {
+        self.spike_log.as_ref().map_or(0, SpikeLog::len)
+    }
+
+    #[wasm_bindgen]
+    pub fn recorded_spikes(&self) -> SpikeEvents {
+        let mut events = SpikeEvents::default();
+        for &(neuron, time) in self.spike_log.iter().flat_map(SpikeLog::spikes) {
+            events.neurons.push(neuron);
+            events.times_ms.push(time);
+        }
+        events
+    }
+
+    // Spike counts of the last `window_ms` in bins of `bin_ms`, bins × neurons row-major
+    #[wasm_bindgen]
+    pub fn get_spike_raster(&self, window_ms: f32, bin_ms: f32) -> Result<Vec<u8>, NeuralError> {
+        raster::check_window(window_ms, bin_ms)?;
+        Ok(self.spike_log()?.raster(self.potentials.len(), self.time_ms, window_ms, bin_ms))
+    }
+
+    // Mean firing rate (Hz) of neurons first..first + count in each bin of the last `window_ms`
+    #[wasm_bindgen]
+    pub fn rate_histogram(&self, first: usize, count: usize, window_ms: f32, bin_ms: f32) -> Result<Vec<f32>, NeuralError> {
+        raster::check_window(window_ms, bin_ms)?;
+        self.check_population(first, count)?;
+        Ok(self.spike_log()?.histogram(first, count, self.time_ms, window_ms, bin_ms))
+    }
+
+    // Spikes emitted but not yet delivered
+    #[wasm_bindgen]
+    pub fn pending_spike_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Return every neuron to rest and drop in-flight spikes; synapses and
+    // homeostatic threshold shifts are kept
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        let (v_rest, recovery_rest) = self.model.rest();
+        self.potentials.fill(v_rest);
+        self.recovery.fill(recovery_rest);
+        self.refractory_remaining.fill(0.0);
+        self.spike_counts.fill(0);
+        self.last_spike_ms.fill(f32::NAN);
+        self.pending.clear();
+        if let Some(log) = self.spike_log.as_mut() {
+            log.clear();
+        }
+        if let Some(traces) = self.stdp.as_mut() {
+            traces.reset();
+        }
+        if let Some(homeostasis) = self.homeostasis.as_mut() {
+            homeostasis.reset_rates();
+        }
+        self.time_ms = 0.0;
+    }
+}
+
+impl SpikingNetwork {
+    // Bytes reserved by neuron state, synapses and in-flight spikes
+    pub(crate) fn memory_bytes(&self) -> usize {
+        let per_neuron = 5 * std::mem::size_of::<f32>() + std::mem::size_of::<u32>();
+        let adjacency: usize = self.outgoing.iter().chain(&self.incoming).map(|list| list.capacity() * std::mem::size_of::<usize>()).sum();
+        let homeostasis = self.homeostasis.as_ref().map_or(0, |homeostasis| {
+            homeostasis.params.len() * (std::mem::size_of::<Option<HomeostasisParams>>() + 3 * std::mem::size_of::<f32>())
+        });
+        self.potentials.len() * per_neuron
+            + homeostasis
+            + self.synapses.capacity() * std::mem::size_of::<Synapse>()
+            + self.pending.memory_bytes()
+            + self.spike_log.as_ref().map_or(0, SpikeLog::memory_bytes)
+            + adjacency
+    }
+
+    fn with_model(neuron_count: usize, model: Model) -> NeuralResult<SpikingNetwork> {
+        if neuron_count == 0 {
+            return Err(NeuralError::InvalidConfiguration("neuron count must be non-zero".to_string()));
+        }
+        let (v_rest, recovery_rest) = model.rest();
+        Ok(SpikingNetwork {
+            model,
+            potentials: vec![v_rest; neuron_count],
+            recovery: vec![recovery_rest; neuron_count],
+            refractory_remaining: vec![0.0; neuron_count],
+            input_currents: vec![0.0; neuron_count],
+            spike_counts: vec![0; neuron_count],
+            last_spike_ms: vec![f32::NAN; neuron_count],
+            synapses: Vec::new(),
+            outgoing: vec![Vec::new(); neuron_count],
+            incoming: vec![Vec::new(); neuron_count],
+            pending: EventQueue::new(),
+            stdp: None,
+            homeostasis: None,
+            spike_log: None,
+            time_ms: 0.0,
+        })
+    }
+
+    // Queue a jump of `voltage` in `neuron` at absolute time `arrival_ms`; one
+    // already due lands on the next step
+    pub(crate) fn schedule_external(&mut self, neuron: usize, arrival_ms: f32, voltage: f32) -> NeuralResult<()> {
+        self.check_neuron(neuron)?;
+        self.pending.push(arrival_ms, Arrival::External { neuron, voltage });
+        Ok(())
+    }
+
+    fn spike_log(&self) -> NeuralResult<&SpikeLog> {
+        self.spike_log.as_ref().ok_or_else(|| NeuralError::InvalidConfiguration("spike recording is off; call record_spikes first".to_string()))
+    }
+
+    fn check_population(&self, first: usize, count: usize) -> NeuralResult<()> {
+        match first.checked_add(count) {
+            Some(end) if count > 0 && end <= self.potentials.len() => Ok(()),
+            _ => Err(NeuralError::IndexOutOfRange { index: first.saturating_add(count.max(1)) - 1, len: self.potentials.len() }),
+        }
+    }
+
+    // Update rate estimates, then scale the incoming synapses of out-of-band neurons
+    fn regulate(&mut self, dt: f32, fired: &[u32]) {
+        let Some(homeostasis) = self.homeostasis.as_mut() else {
+            return;
+        };
+        homeostasis.record(dt, fired);
+        homeostasis.regulate(dt);
+        for (neuron, &factor) in homeostasis.scaling.iter().enumerate() {
+            if factor == 1.0 {
+                continue;
+            }
+            for &index in &self.incoming[neuron] {
+                let synapse = &mut self.synapses[index];
+                if synapse.weight > 0.0 {
+                    synapse.weight *= factor;
+                } else if factor > 0.0 {
+                    synapse.weight /= factor;
+                }
+            }
+        }
+    }
+
+    fn check_neuron(&self, index: usize) -> NeuralResult<()> {
+        if index >= self.potentials.len() {
+            return Err(NeuralError::IndexOutOfRange { index, len: self.potentials.len() });
+        }
+        Ok(())
+    }
+
+    fn emit_spike(&mut self, neuron: usize) {
+        self.spike_counts[neuron] += 1;
+        self.last_spike_ms[neuron] = self.time_ms;
+        if let Some(log) = self.spike_log.as_mut() {
+            log.record(neuron, self.time_ms);
+        }
+        for &synapse in &self.outgoing[neuron] {
+            self.pending.push(self.time_ms + self.synapses[synapse].delay_ms, Arrival::Synapse(synapse));
+        }
+    }
+
+    // Potentiate incoming synapses by their pre traces, depress outgoing ones by their post traces
+    fn apply_online_stdp(&mut self, neuron: usize) {
+        let Some(traces) = self.stdp.as_mut() else {
+            return;
+        };
+        let params = traces.params;
+
+        for &index in &self.incoming[neuron] {
+            let synapse = &mut self.synapses[index];
+            synapse.weight = params.clip(synapse.weight + params.a_plus * traces.pre[synapse.pre]);
+        }
+        for &index in &self.outgoing[neuron] {
+            let synapse = &mut self.synapses[index];
+            synapse.weight = params.clip(synapse.weight - params.a_minus * traces.post[synapse.post]);
+        }
+        traces.pre[neuron] += 1.0;
+        traces.post[neuron] += 1.0;
+    }
+
+    // Deliver every spike whose arrival time has been reached
+    fn deliver_pending(&mut self) {
+        while let Some((_, arrival)) = self.pending.pop_due(self.time_ms) {
+            let (post, weight) = self.target(arrival);
+            if self.refractory_remaining[post] <= 0.0 {
+                self.potentials[post] += weight;
+            }
+        }
+    }
+
+    // Neuron an arrival reaches and the jump it causes
+    fn target(&self, arrival: Arrival) -> (usize, f32) {
+        match arrival {
+            Arrival::Synapse(index) => (self.synapses[index].post, self.synapses[index].weight),
+            Arrival::External { neuron, voltage } => (neuron, voltage),
+        }
+    }
+
+    // Bring a neuron's potential forward to `t` under its constant input current
+    fn evolve(&mut self, clock: &mut EventClock, neuron: usize, t: f32) {
+        let mut from = clock.updated[neuron];
+        if t <= from {
+            return;
+        }
+        let params = clock.params;
+        if clock.refractory_until[neuron] > from {
+            self.potentials[neuron] = params.v_reset;
+            from = clock.refractory_until[neuron].min(t);
+        }
+        let v_inf = params.v_rest + params.resistance * self.input_currents[neuron];
+        self.potentials[neuron] = v_inf + (self.potentials[neuron] - v_inf) * (-(t - from) / params.tau_m).exp();
+        clock.updated[neuron] = t;
+    }
+
+    // Fire now if at threshold, else schedule the crossing the input current will cause
+    fn predict(&mut self, clock: &mut EventClock, neuron: usize, t: f32) {
+        let params = clock.params;
+        let v = self.potentials[neuron];
+        if v >= params.v_threshold {
+            self.fire(clock, neuron, t);
+            return;
+        }
+        let v_inf = params.v_rest + params.resistance * self.input_currents[neuron];
+        if v_inf > params.v_threshold {
+            let delay = params.tau_m * ((v - v_inf) / (params.v_threshold - v_inf)).ln();
+            clock.wakes.push(t + delay, Wake { neuron, version: clock.versions[neuron], fire: true });
+        }
+    }
+
+    fn fire(&mut self, clock: &mut EventClock, neuron: usize, t: f32) {
+        let params = clock.params;
+        self.potentials[neuron] = params.v_reset;
+        clock.updated[neuron] = t;
+        clock.refractory_until[neuron] = t + params.refractory_ms;
+        clock.versions[neuron] = clock.versions[neuron].wrapping_add(1);
+        clock.spikes.neurons.push(neuron as u32);
+        clock.spikes.times_ms.push(t);
+        self.time_ms = t;
+        self.emit_spike(neuron);
+        if params.refractory_ms > 0.0 {
+            clock.wakes.push(clock.refractory_until[neuron], Wake { neuron, version: clock.versions[neuron], fire: false });
+        } else {
+            self.predict(clock, neuron, t);
+        }
+    }
+
+    fn deliver_event(&mut self, clock: &mut EventClock, arrival: Arrival, t: f32) {
+        let (post, weight) = self.target(arrival);
+        self.evolve(clock, post, t);
+        // Refractory, or already fired at this instant with no refractory period
+        if clock.refractory_until[post] > t || self.last_spike_ms[post] == t {
+            return;
+        }
+        self.potentials[post] += weight;
+        clock.versions[post] = clock.versions[post].wrapping_add(1);
+        self.predict(clock, post, t);
+    }
+}
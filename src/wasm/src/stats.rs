@@ -0,0 +1,222 @@
+// Statistical reductions over f32 buffers, vectorized with simd128
+//
+// Sums are Kahan-compensated: each lane carries the low-order bits its additions
+// rounded away and feeds them back into the next one, so the error stays near one
+// rounding step instead of growing with the buffer length. Variance uses two passes
+// (mean first, then squared deviations), which avoids the cancellation of the
+// E[x²] - E[x]² shortcut.
+//
+// The SIMD and scalar paths compensate per lane and per element respectively, so
+// their results can differ in the last bit. Callers pass finite values; NaN inputs
+// give unspecified results.
+
+use wasm_bindgen::prelude::*;
+#[cfg(target_feature = "simd128")]
+use std::arch::wasm32::*;
+
+use crate::activation::check_finite;
+use crate::error::NeuralError;
+use crate::features::simd_dispatch;
+#[cfg(target_feature = "simd128")]
+use crate::simd;
+
+// Running compensated sum
+#[derive(Debug, Clone, Copy, Default)]
+struct Kahan {
+    sum: f32,
+    compensation: f32,
+}
+
+impl Kahan {
+    #[inline]
+    fn add(&mut self, value: f32) {
+        let y = value - self.compensation;
+        let t = self.sum + y;
+        self.compensation = (t - self.sum) - y;
+        self.sum = t;
+    }
+}
+
+pub fn sum(values: &[f32], simd: bool) -> f32 {
+    compensated_sum(values, simd, 0.0, 1.0, false)
+}
+
+pub fn mean(values: &[f32], simd: bool) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(sum(values, simd) / values.len() as f32)
+}
+
+// Population variance Σ(x - mean)² / n about a mean from a previous pass
+pub fn variance(values: &[f32], mean: f32, simd: bool) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(compensated_sum(values, simd, mean, 1.0, true) / values.len() as f32)
+}
+
+pub fn min_max(values: &[f32], simd: bool) -> Option<(f32, f32)> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(simd_dispatch!(simd && values.len() >= 4, simd_min_max(values), scalar_min_max(values)))
+}
+
+// Euclidean norm. Squares that overflow or underflow f32 are rescaled by the
+// largest magnitude first, so the result is finite whenever the norm is.
+pub fn norm(values: &[f32], simd: bool) -> f32 {
+    let squares = compensated_sum(values, simd, 0.0, 1.0, true);
+    if squares.is_normal() {
+        return squares.sqrt();
+    }
+    let largest = match min_max(values, simd) {
+        Some((min, max)) => min.abs().max(max.abs()),
+        None => return 0.0,
+    };
+    if largest == 0.0 || !largest.is_finite() {
+        return largest;
+    }
+    compensated_sum(values, simd, 0.0, 1.0 / largest, true).sqrt() * largest
+}
+
+// Σ f(x) with f(x) = ((x - offset) · scale)², or (x - offset) · scale when `square` is off
+fn compensated_sum(values: &[f32], simd: bool, offset: f32, scale: f32, square: bool) -> f32 {
+    simd_dispatch!(
+        simd && values.len() >= 4,
+        simd_compensated_sum(values, offset, scale, square),
+        scalar_compensated_sum(values, offset, scale, square)
+    )
+}
+
+#[inline]
+fn term(value: f32, offset: f32, scale: f32, square: bool) -> f32 {
+    let scaled = (value - offset) * scale;
+    if square {
+        scaled * scaled
+    } else {
+        scaled
+    }
+}
+
+fn scalar_compensated_sum(values: &[f32], offset: f32, scale: f32, square: bool) -> f32 {
+    let mut acc = Kahan::default();
+    for &value in values {
+        acc.add(term(value, offset, scale, square));
+    }
+    acc.sum
+}
+
+// Four independent Kahan accumulators, merged with a scalar one at the end
+#[cfg(target_feature = "simd128")]
+fn simd_compensated_sum(values: &[f32], offset: f32, scale: f32, square: bool) -> f32 {
+    let chunks = values.chunks_exact(4);
+    let tail = chunks.remainder();
+    let offset_vec = f32x4_splat(offset);
+    let scale_vec = f32x4_splat(scale);
+    let mut sum = f32x4_splat(0.0);
+    let mut compensation = f32x4_splat(0.0);
+
+    for chunk in chunks {
+        let mut x = f32x4_mul(f32x4_sub(simd::load(chunk), offset_vec), scale_vec);
+        if square {
+            x = f32x4_mul(x, x);
+        }
+        let y = f32x4_sub(x, compensation);
+        let t = f32x4_add(sum, y);
+        compensation = f32x4_sub(f32x4_sub(t, sum), y);
+        sum = t;
+    }
+
+    let mut acc = Kahan::default();
+    for (lane_sum, lane_compensation) in simd::f32_lanes(sum).into_iter().zip(simd::f32_lanes(compensation)) {
+        acc.add(lane_sum);
+        acc.add(-lane_compensation);
+    }
+    for &value in tail {
+        acc.add(term(value, offset, scale, square));
+    }
+    acc.sum
+}
+
+fn scalar_min_max(values: &[f32]) -> (f32, f32) {
+    values.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &x| (min.min(x), max.max(x)))
+}
+
+#[cfg(target_feature = "simd128")]
+fn simd_min_max(values: &[f32]) -> (f32, f32) {
+    let chunks = values.chunks_exact(4);
+    let (mut min, mut max) = scalar_min_max(chunks.remainder());
+    let mut min_vec = f32x4_splat(f32::INFINITY);
+    let mut max_vec = f32x4_splat(f32::NEG_INFINITY);
+
+    for chunk in chunks {
+        let x = simd::load(chunk);
+        min_vec = f32x4_pmin(min_vec, x);
+        max_vec = f32x4_pmax(max_vec, x);
+    }
+
+    for lane in simd::f32_lanes(min_vec) {
+        min = min.min(lane);
+    }
+    for lane in simd::f32_lanes(max_vec) {
+        max = max.max(lane);
+    }
+    (min, max)
+}
+
+// Everything telemetry usually wants from a buffer
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub sum: f32,
+    pub mean: f32,
+    // Population variance
+    pub variance: f32,
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+    pub l2_norm: f32,
+}
+
+impl Summary {
+    pub fn of(values: &[f32], simd: bool) -> Option<Summary> {
+        let (min, max) = min_max(values, simd)?;
+        let sum = sum(values, simd);
+        let mean = sum / values.len() as f32;
+        let variance = variance(values, mean, simd)?;
+        Some(Summary {
+            count: values.len(),
+            sum,
+            mean,
+            variance,
+            std_dev: variance.sqrt(),
+            min,
+            max,
+            l2_norm: norm(values, simd),
+        })
+    }
+}
+
+// Export for JavaScript: count, sum, mean, variance, min, max and norm of a buffer
+#[wasm_bindgen]
+pub fn summarize(values: &[f32]) -> Result<Summary, NeuralError> {
+    check_finite(values)?;
+    Summary::of(values, crate::check_simd_support())
+        .ok_or_else(|| NeuralError::InvalidConfiguration("statistics of an empty slice".to_string()))
+}
+
+// Compensated sum, for accumulating long buffers without drift
+#[wasm_bindgen]
+pub fn kahan_sum(values: &[f32]) -> Result<f32, NeuralError> {
+    check_finite(values)?;
+    Ok(sum(values, crate::check_simd_support()))
+}
+
+#[wasm_bindgen]
+pub fn l2_norm(values: &[f32]) -> Result<f32, NeuralError> {
+    check_finite(values)?;
+    Ok(norm(values, crate::check_simd_support()))
+}
+
@@ -0,0 +1,124 @@
+// Streaming inference over continuous sample streams
+//
+// Samples arrive in arbitrary chunks and are written into a ring buffer holding
+// exactly one network input window. Once the buffer is full, the network runs on
+// the most recent window every `hop` frames. Multi-channel streams are
+// interleaved frame by frame (c0 c1 .. c0 c1 ..).
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+
+#[wasm_bindgen]
+pub struct StreamProcessor {
+    network: NeuralNetwork,
+    channels: usize,
+    hop_samples: usize,
+    ring: Vec<f32>,
+    write_pos: usize,
+    filled: usize,
+    // Samples still to arrive before the next window is emitted
+    until_next: usize,
+    window: Vec<f32>,
+    windows_emitted: u32,
+}
+
+#[wasm_bindgen]
+impl StreamProcessor {
+    // Window length is the network's input size; `hop` is the number of frames
+    // between consecutive windows (1 = fully overlapping, window frames = disjoint)
+    #[wasm_bindgen(constructor)]
+    pub fn new(network: &NeuralNetwork, channels: usize, hop: usize) -> Result<StreamProcessor, NeuralError> {
+        let window_len = network.input_size();
+        if channels == 0 || !window_len.is_multiple_of(channels) {
+            return Err(NeuralError::InvalidConfiguration("network input size must be a multiple of the channel count".to_string()));
+        }
+        let window_frames = window_len / channels;
+        if hop == 0 || hop > window_frames {
+            return Err(NeuralError::InvalidConfiguration(format!("hop must be between 1 and {} frames", window_frames)));
+        }
+
+        Ok(StreamProcessor {
+            network: network.clone(),
+            channels,
+            hop_samples: hop * channels,
+            ring: vec![0.0; window_len],
+            write_pos: 0,
+            filled: 0,
+            until_next: window_len,
+            window: vec![0.0; window_len],
+            windows_emitted: 0,
+        })
+    }
+
+    // Append samples and return the outputs of every window completed by them,
+    // concatenated in order ([windows × output_size]; empty if none completed)
+    #[wasm_bindgen]
+    pub fn push_samples(&mut self, samples: &[f32]) -> Result<Vec<f32>, NeuralError> {
+        // Validate first so a rejected chunk leaves the stream untouched
+        if let Some(index) = samples.iter().position(|x| !x.is_finite()) {
+            return Err(NeuralError::NonFiniteInput { index });
+        }
+
+        let mut outputs = Vec::new();
+        for &sample in samples {
+            self.ring[self.write_pos] = sample;
+            self.write_pos = (self.write_pos + 1) % self.ring.len();
+            self.filled = (self.filled + 1).min(self.ring.len());
+            self.until_next -= 1;
+
+            if self.until_next == 0 {
+                outputs.extend(self.emit()?);
+                self.until_next = self.hop_samples;
+            }
+        }
+        Ok(outputs)
+    }
+
+    // Drop buffered samples; the next window needs a full window of new input
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.ring.fill(0.0);
+        self.write_pos = 0;
+        self.filled = 0;
+        self.until_next = self.ring.len();
+    }
+
+    // Samples currently held, up to one window
+    #[wasm_bindgen]
+    pub fn buffered(&self) -> usize {
+        self.filled
+    }
+
+    #[wasm_bindgen]
+    pub fn windows_emitted(&self) -> u32 {
+        self.windows_emitted
+    }
+
+    #[wasm_bindgen]
+    pub fn window_size(&self) -> usize {
+        self.ring.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn output_size(&self) -> usize {
+        self.network.output_size()
+    }
+
+    #[wasm_bindgen]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+}
+
+impl StreamProcessor {
+    // Unroll the ring oldest-first and run the network on it
+    fn emit(&mut self) -> NeuralResult<Vec<f32>> {
+        let (newer, older) = self.ring.split_at(self.write_pos);
+        self.window[..older.len()].copy_from_slice(older);
+        self.window[older.len()..].copy_from_slice(newer);
+        self.windows_emitted = self.windows_emitted.wrapping_add(1);
+        self.network.forward(&self.window)
+    }
+}
@@ -0,0 +1,338 @@
+// Priority scheduling of per-agent forward and training jobs
+//
+// Jobs are queued against AgentPool agents with a priority and a deadline, and
+// run() starts them highest effective priority first, where
+//   effective = priority + aging_rate · ms waited
+// so an agent with low-priority work climbs the queue the longer it waits rather
+// than being starved by busier ones. Ties go to the earliest deadline, then to the
+// oldest job. A job whose deadline has passed when its turn comes is dropped as
+// expired instead of running late.
+//
+// An agent's jobs always run one after another in queue order, as training
+// changes its network. With the `threads` feature and more than one thread, the
+// jobs of each agent form a lane and lanes are spread over the rayon pool, whose
+// work stealing keeps every worker busy however uneven the lanes are; lanes are
+// handed out in the order of their best job. Otherwise jobs run one at a time in
+// queue order. A run stops starting jobs once its time budget is spent (after at
+// least one job), leaving the rest queued, still aging, for the next run.
+// Timing uses wall_time_ms(), which every worker reads on the same timeline.
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::agent_pool::{unknown_agent, AgentPool};
+use crate::clock::wall_time_ms;
+use crate::error::{NeuralError, NeuralResult};
+use crate::network::NeuralNetwork;
+use crate::parallel;
+
+// Priority gained per millisecond of waiting: one level every 100 ms
+const DEFAULT_AGING_RATE: f64 = 0.01;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued = 0,
+    Done = 1,
+    Failed = 2,
+    // Deadline passed before the job started
+    Expired = 3,
+    // Never submitted, cancelled, or its result already taken
+    Unknown = 4,
+}
+
+#[derive(Debug, Clone)]
+enum Work {
+    Forward,
+    Train { targets: Vec<f32>, learning_rate: f32 },
+}
+
+#[derive(Debug, Clone)]
+struct Job {
+    id: u32,
+    agent: u32,
+    work: Work,
+    inputs: Vec<f32>,
+    priority: i32,
+    submitted_ms: f64,
+    // Absolute, in wall_time_ms(); infinite for none
+    deadline_ms: f64,
+}
+
+impl Job {
+    fn effective_priority(&self, now: f64, aging_rate: f64) -> f64 {
+        self.priority as f64 + aging_rate * (now - self.submitted_ms).max(0.0)
+    }
+
+    fn execute(&self, network: &mut NeuralNetwork, now: f64) -> Outcome {
+        if now > self.deadline_ms {
+            return Outcome::Expired { deadline_ms: self.deadline_ms - self.submitted_ms, elapsed_ms: now - self.submitted_ms };
+        }
+        let result = match &self.work {
+            Work::Forward => network.forward(&self.inputs),
+            Work::Train { targets, learning_rate } => {
+                let batch_size = self.inputs.len() / network.input_size().max(1);
+                network.train_batch(&self.inputs, targets, batch_size, *learning_rate).map(|loss| vec![loss])
+            }
+        };
+        match result {
+            Ok(values) => Outcome::Done(values),
+            Err(err) => Outcome::Failed(err),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Outcome {
+    Done(Vec<f32>),
+    Failed(NeuralError),
+    Expired { deadline_ms: f64, elapsed_ms: f64 },
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SwarmExecutor {
+    queue: Vec<Job>,
+    // Finished jobs, kept until their result is taken
+    outcomes: BTreeMap<u32, Outcome>,
+    next_id: u32,
+    threads: usize,
+    aging_rate: f64,
+    completed: u32,
+    failed: u32,
+    expired: u32,
+}
+
+#[wasm_bindgen]
+impl SwarmExecutor {
+    // `threads` is clamped to what the pool can run; 1 without the `threads` feature
+    #[wasm_bindgen(constructor)]
+    pub fn new(threads: usize) -> SwarmExecutor {
+        SwarmExecutor {
+            queue: Vec::new(),
+            outcomes: BTreeMap::new(),
+            next_id: 1,
+            threads: threads.clamp(1, parallel::available_threads()),
+            aging_rate: DEFAULT_AGING_RATE,
+            completed: 0,
+            failed: 0,
+            expired: 0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // Returns the thread count actually used
+    #[wasm_bindgen]
+    pub fn set_threads(&mut self, threads: usize) -> usize {
+        self.threads = threads.clamp(1, parallel::available_threads());
+        self.threads
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn aging_rate(&self) -> f64 {
+        self.aging_rate
+    }
+
+    // Priority a job gains per millisecond in the queue; 0 disables aging
+    #[wasm_bindgen]
+    pub fn set_aging_rate(&mut self, rate: f64) -> Result<(), NeuralError> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("aging rate must be finite and non-negative".to_string()));
+        }
+        self.aging_rate = rate;
+        Ok(())
+    }
+
+    // Queue agent.forward(inputs); the job must start within `deadline_ms` of now
+    // (Infinity for no deadline). Returns the job ID.
+    #[wasm_bindgen]
+    pub fn submit_forward(&mut self, agent: u32, inputs: Vec<f32>, priority: i32, deadline_ms: f64) -> Result<u32, NeuralError> {
+        self.submit(agent, Work::Forward, inputs, priority, deadline_ms)
+    }
+
+    // Queue one training step on a row-major batch; the result is [loss]
+    #[wasm_bindgen]
+    pub fn submit_train(
+        &mut self,
+        agent: u32,
+        inputs: Vec<f32>,
+        targets: Vec<f32>,
+        learning_rate: f32,
+        priority: i32,
+        deadline_ms: f64,
+    ) -> Result<u32, NeuralError> {
+        self.submit(agent, Work::Train { targets, learning_rate }, inputs, priority, deadline_ms)
+    }
+
+    // Drop a queued job; false if it is not queued
+    #[wasm_bindgen]
+    pub fn cancel(&mut self, job: u32) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|queued| queued.id != job);
+        self.queue.len() < before
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pending_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    // Jobs queued for one agent
+    #[wasm_bindgen]
+    pub fn pending_for(&self, agent: u32) -> usize {
+        self.queue.iter().filter(|job| job.agent == agent).count()
+    }
+
+    // Run queued jobs against `pool` for up to `budget_ms` (Infinity to drain the
+    // queue). Returns how many jobs finished, successfully or not; expired jobs
+    // are not counted.
+    #[wasm_bindgen]
+    pub fn run(&mut self, pool: &mut AgentPool, budget_ms: f64) -> Result<u32, NeuralError> {
+        if budget_ms.is_nan() || budget_ms <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("time budget must be positive".to_string()));
+        }
+        let start = wall_time_ms();
+        let stop = start + budget_ms;
+        let aging_rate = self.aging_rate;
+        let mut jobs = std::mem::take(&mut self.queue);
+        jobs.sort_by(|a, b| {
+            b.effective_priority(start, aging_rate)
+                .total_cmp(&a.effective_priority(start, aging_rate))
+                .then(a.deadline_ms.total_cmp(&b.deadline_ms))
+                .then(a.id.cmp(&b.id))
+        });
+        let mut networks: BTreeMap<u32, &mut NeuralNetwork> = pool.networks_mut().collect();
+
+        let mut finished = 0;
+        if self.threads > 1 {
+            let mut lanes: Vec<(&mut NeuralNetwork, Vec<Job>)> = Vec::new();
+            let mut lane_of: BTreeMap<u32, usize> = BTreeMap::new();
+            for job in jobs {
+                if let Some(&lane) = lane_of.get(&job.agent) {
+                    lanes[lane].1.push(job);
+                } else if let Some(network) = networks.remove(&job.agent) {
+                    lane_of.insert(job.agent, lanes.len());
+                    lanes.push((network, vec![job]));
+                } else {
+                    finished += self.record(job.id, Outcome::Failed(unknown_agent(job.agent)));
+                }
+            }
+            let results = parallel::map_tasks(lanes, |lane, (network, jobs)| run_lane(network, jobs, stop, lane == 0));
+            for (outcomes, left) in results {
+                for (job, outcome) in outcomes {
+                    finished += self.record(job, outcome);
+                }
+                self.queue.extend(left);
+            }
+        } else {
+            let mut started = false;
+            for job in jobs {
+                let now = wall_time_ms();
+                if started && now >= stop {
+                    self.queue.push(job);
+                    continue;
+                }
+                let outcome = match networks.get_mut(&job.agent) {
+                    Some(network) => job.execute(network, now),
+                    None => Outcome::Failed(unknown_agent(job.agent)),
+                };
+                started = true;
+                finished += self.record(job.id, outcome);
+            }
+        }
+        Ok(finished)
+    }
+
+    #[wasm_bindgen]
+    pub fn status(&self, job: u32) -> JobStatus {
+        match self.outcomes.get(&job) {
+            Some(Outcome::Done(_)) => JobStatus::Done,
+            Some(Outcome::Failed(_)) => JobStatus::Failed,
+            Some(Outcome::Expired { .. }) => JobStatus::Expired,
+            None if self.queue.iter().any(|queued| queued.id == job) => JobStatus::Queued,
+            None => JobStatus::Unknown,
+        }
+    }
+
+    // Output of a finished job, removing it: the forward outputs, or [loss] for
+    // training. Throws the job's error if it failed and DeadlineExceeded if it expired.
+    #[wasm_bindgen]
+    pub fn take_result(&mut self, job: u32) -> Result<Vec<f32>, NeuralError> {
+        match self.outcomes.remove(&job) {
+            Some(Outcome::Done(values)) => Ok(values),
+            Some(Outcome::Failed(err)) => Err(err),
+            Some(Outcome::Expired { deadline_ms, elapsed_ms }) => Err(NeuralError::DeadlineExceeded { deadline_ms, elapsed_ms }),
+            None => Err(NeuralError::InvalidConfiguration(format!("job {} has no result", job))),
+        }
+    }
+
+    // Totals since the executor was created
+    #[wasm_bindgen(getter)]
+    pub fn completed_count(&self) -> u32 {
+        self.completed
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn failed_count(&self) -> u32 {
+        self.failed
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn expired_count(&self) -> u32 {
+        self.expired
+    }
+}
+
+impl SwarmExecutor {
+    fn submit(&mut self, agent: u32, work: Work, inputs: Vec<f32>, priority: i32, deadline_ms: f64) -> NeuralResult<u32> {
+        if deadline_ms.is_nan() || deadline_ms <= 0.0 {
+            return Err(NeuralError::InvalidConfiguration("deadline must be positive".to_string()));
+        }
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or_else(|| NeuralError::InvalidConfiguration("job IDs exhausted".to_string()))?;
+        let submitted_ms = wall_time_ms();
+        self.queue.push(Job { id, agent, work, inputs, priority, submitted_ms, deadline_ms: submitted_ms + deadline_ms });
+        Ok(id)
+    }
+
+    // 1 if the outcome counts as finished
+    fn record(&mut self, job: u32, outcome: Outcome) -> u32 {
+        let finished = match outcome {
+            Outcome::Done(_) => {
+                self.completed += 1;
+                1
+            }
+            Outcome::Failed(_) => {
+                self.failed += 1;
+                1
+            }
+            Outcome::Expired { .. } => {
+                self.expired += 1;
+                0
+            }
+        };
+        self.outcomes.insert(job, outcome);
+        finished
+    }
+}
+
+// One agent's jobs in order on a pool worker; returns their outcomes and the jobs
+// left once `stop` passed. The first lane always starts its first job.
+fn run_lane(network: &mut NeuralNetwork, jobs: Vec<Job>, stop: f64, first: bool) -> (Vec<(u32, Outcome)>, Vec<Job>) {
+    let mut outcomes = Vec::with_capacity(jobs.len());
+    let mut left = Vec::new();
+    for job in jobs {
+        let now = wall_time_ms();
+        if now >= stop && !(first && outcomes.is_empty()) {
+            left.push(job);
+            continue;
+        }
+        outcomes.push((job.id, job.execute(network, now)));
+    }
+    (outcomes, left)
+}
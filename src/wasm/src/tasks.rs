@@ -0,0 +1,94 @@
+// Cooperative scheduling for long-running work exposed as JS Promises
+//
+// Async tasks check a shared CancellationToken between units of work and hand
+// control back to the event loop (via a zero-delay setTimeout) whenever they
+// have run for longer than their time slice, so rendering and input keep going.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::clock::Clock;
+use crate::error::{NeuralError, NeuralResult};
+
+// Default time slice before yielding; keeps a 60Hz frame budget free for the page
+pub const DEFAULT_SLICE_MS: f64 = 8.0;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Rc<Cell<bool>>,
+}
+
+#[wasm_bindgen]
+impl CancellationToken {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    // Ask every task holding this token to stop at its next checkpoint
+    #[wasm_bindgen]
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    #[wasm_bindgen]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+}
+
+impl CancellationToken {
+    pub fn check(&self) -> NeuralResult<()> {
+        if self.is_cancelled() {
+            return Err(NeuralError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+// Tracks how long the current slice has run and yields once it is used up
+pub struct Yielder {
+    clock: Clock,
+    slice_ms: f64,
+    slice_start: f64,
+}
+
+impl Yielder {
+    pub fn new(slice_ms: f64) -> Yielder {
+        let clock = Clock::new();
+        let slice_start = clock.now_ms();
+        Yielder { clock, slice_ms, slice_start }
+    }
+
+    pub async fn checkpoint(&mut self, token: &CancellationToken) -> NeuralResult<()> {
+        token.check()?;
+        if self.clock.now_ms() - self.slice_start >= self.slice_ms {
+            yield_now().await;
+            self.slice_start = self.clock.now_ms();
+            // Cancellation usually arrives from a UI event handled during the yield
+            token.check()?;
+        }
+        Ok(())
+    }
+}
+
+// Resolve on the next macrotask so the browser can render and process input
+#[cfg(js_host)]
+pub async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|value| value.dyn_into::<js_sys::Function>().ok());
+        let scheduled = set_timeout.is_some_and(|set_timeout| set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(0)).is_ok());
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(not(js_host))]
+pub async fn yield_now() {}
@@ -0,0 +1,290 @@
+// Tensors: shaped, strided views over f32 storage
+//
+// A Tensor pairs storage with a layout: an offset, a shape and a stride per
+// dimension, all in elements, so element [i₀, i₁, …] lives at
+//   offset + Σ iₖ · strideₖ
+// Storage is either an immutable buffer shared by every view taken from it, or a
+// NeuralRuntime pool buffer (alloc_buffer) named by its handle, read through the
+// runtime that owns it. reshape(), slice(), select() and transpose() only build a
+// new layout over the same storage, so kernels can take batches, rows or columns
+// of a tensor without copying; contiguous views are passed to them as plain
+// slices and only strided ones are gathered first.
+
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::allocator::PoolAllocator;
+use crate::error::{NeuralError, NeuralResult};
+
+// Element type of tensor storage
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DType {
+    F32 = 0,
+}
+
+#[derive(Debug, Clone)]
+enum Storage {
+    Owned(Rc<[f32]>),
+    Pool(u32),
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Tensor {
+    storage: Storage,
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl Tensor {
+    // Tensor over a copy of `data`, whose length must be the product of `shape`
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: Vec<f32>, shape: &[u32]) -> Result<Tensor, NeuralError> {
+        let shape = to_shape(shape);
+        let len = element_count(&shape)?;
+        if data.len() != len {
+            return Err(NeuralError::DimensionMismatch { expected: len, actual: data.len() });
+        }
+        Ok(Tensor::contiguous(Storage::Owned(data.into()), shape))
+    }
+
+    #[wasm_bindgen]
+    pub fn zeros(shape: &[u32]) -> Result<Tensor, NeuralError> {
+        let shape = to_shape(shape);
+        let len = element_count(&shape)?;
+        Ok(Tensor::contiguous(Storage::Owned(vec![0.0; len].into()), shape))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dtype(&self) -> DType {
+        DType::F32
+    }
+
+    #[wasm_bindgen]
+    pub fn shape(&self) -> Vec<u32> {
+        self.shape.iter().map(|&dim| dim as u32).collect()
+    }
+
+    // Elements between neighbours along each dimension
+    #[wasm_bindgen]
+    pub fn strides(&self) -> Vec<u32> {
+        self.strides.iter().map(|&stride| stride as u32).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    // Number of elements
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Handle of the pool buffer behind the tensor, if any
+    #[wasm_bindgen(getter)]
+    pub fn buffer(&self) -> Option<u32> {
+        match self.storage {
+            Storage::Pool(handle) => Some(handle),
+            Storage::Owned(_) => None,
+        }
+    }
+
+    // Whether the elements sit in row-major order with no gaps
+    #[wasm_bindgen]
+    pub fn is_contiguous(&self) -> bool {
+        let mut expected = 1;
+        for (&dim, &stride) in self.shape.iter().zip(&self.strides).rev() {
+            if dim > 1 && stride != expected {
+                return false;
+            }
+            expected *= dim;
+        }
+        true
+    }
+
+    // Same elements under a new shape; the view must be contiguous
+    #[wasm_bindgen]
+    pub fn reshape(&self, shape: &[u32]) -> Result<Tensor, NeuralError> {
+        let shape = to_shape(shape);
+        let len = element_count(&shape)?;
+        if len != self.len() {
+            return Err(NeuralError::DimensionMismatch { expected: self.len(), actual: len });
+        }
+        if !self.is_contiguous() {
+            return Err(NeuralError::InvalidConfiguration("only contiguous tensors can be reshaped".to_string()));
+        }
+        Ok(Tensor { offset: self.offset, ..Tensor::contiguous(self.storage.clone(), shape) })
+    }
+
+    // Indices [start, end) along `axis`
+    #[wasm_bindgen]
+    pub fn slice(&self, axis: usize, start: usize, end: usize) -> Result<Tensor, NeuralError> {
+        let dim = self.dim(axis)?;
+        if start > end || end > dim {
+            return Err(NeuralError::IndexOutOfRange { index: end.max(start), len: dim });
+        }
+        let mut view = self.clone();
+        if end > start {
+            view.offset += start * self.strides[axis];
+        }
+        view.shape[axis] = end - start;
+        Ok(view)
+    }
+
+    // Index `index` along `axis`, dropping that dimension
+    #[wasm_bindgen]
+    pub fn select(&self, axis: usize, index: usize) -> Result<Tensor, NeuralError> {
+        let dim = self.dim(axis)?;
+        if index >= dim {
+            return Err(NeuralError::IndexOutOfRange { index, len: dim });
+        }
+        let mut view = self.clone();
+        view.offset += index * self.strides[axis];
+        view.shape.remove(axis);
+        view.strides.remove(axis);
+        Ok(view)
+    }
+
+    // Swap two dimensions
+    #[wasm_bindgen]
+    pub fn transpose(&self, first: usize, second: usize) -> Result<Tensor, NeuralError> {
+        self.dim(first)?;
+        self.dim(second)?;
+        let mut view = self.clone();
+        view.shape.swap(first, second);
+        view.strides.swap(first, second);
+        Ok(view)
+    }
+
+    // Elements in row-major order; pool-backed tensors are read with NeuralRuntime.tensor_data
+    #[wasm_bindgen]
+    pub fn to_vec(&self) -> Result<Vec<f32>, NeuralError> {
+        Ok(self.gather(self.owned()?)?.into_owned())
+    }
+
+    // Element at `indices`, one per dimension
+    #[wasm_bindgen]
+    pub fn get(&self, indices: &[u32]) -> Result<f32, NeuralError> {
+        let data = self.owned()?;
+        if indices.len() != self.rank() {
+            return Err(NeuralError::DimensionMismatch { expected: self.rank(), actual: indices.len() });
+        }
+        let mut position = self.offset;
+        for ((&index, &dim), &stride) in indices.iter().zip(&self.shape).zip(&self.strides) {
+            if index as usize >= dim {
+                return Err(NeuralError::IndexOutOfRange { index: index as usize, len: dim });
+            }
+            position += index as usize * stride;
+        }
+        Ok(data[position])
+    }
+}
+
+impl Tensor {
+    fn contiguous(storage: Storage, shape: Vec<usize>) -> Tensor {
+        let mut strides = vec![1; shape.len()];
+        for axis in (0..shape.len().saturating_sub(1)).rev() {
+            strides[axis] = strides[axis + 1] * shape[axis + 1];
+        }
+        Tensor { storage, offset: 0, shape, strides }
+    }
+
+    // Contiguous view of pool buffer `handle`, whose length must cover the shape
+    pub(crate) fn from_pool(pool: &PoolAllocator, handle: u32, shape: &[u32]) -> NeuralResult<Tensor> {
+        let shape = to_shape(shape);
+        let len = element_count(&shape)?;
+        let available = pool.get(handle)?.len();
+        if len > available {
+            return Err(NeuralError::DimensionMismatch { expected: len, actual: available });
+        }
+        Ok(Tensor::contiguous(Storage::Pool(handle), shape))
+    }
+
+    pub(crate) fn from_vec(data: Vec<f32>, shape: Vec<usize>) -> Tensor {
+        debug_assert_eq!(data.len(), shape.iter().product::<usize>());
+        Tensor::contiguous(Storage::Owned(data.into()), shape)
+    }
+
+    pub(crate) fn dims(&self) -> &[usize] {
+        &self.shape
+    }
+
+    // The tensor's elements in row-major order: borrowed when the view is
+    // contiguous, gathered otherwise
+    pub(crate) fn data<'a>(&'a self, pool: &'a PoolAllocator) -> NeuralResult<Cow<'a, [f32]>> {
+        match &self.storage {
+            Storage::Owned(data) => self.gather(data),
+            Storage::Pool(handle) => self.gather(pool.get(*handle)?),
+        }
+    }
+
+    fn gather<'a>(&self, data: &'a [f32]) -> NeuralResult<Cow<'a, [f32]>> {
+        let len = self.len();
+        if len == 0 {
+            return Ok(Cow::Borrowed(&[]));
+        }
+        let last = self.offset + self.shape.iter().zip(&self.strides).map(|(&dim, &stride)| (dim - 1) * stride).sum::<usize>();
+        if last >= data.len() {
+            // A pool buffer shrank or was reallocated under the view
+            return Err(NeuralError::IndexOutOfRange { index: last, len: data.len() });
+        }
+        if self.is_contiguous() {
+            return Ok(Cow::Borrowed(&data[self.offset..self.offset + len]));
+        }
+        let mut values = Vec::with_capacity(len);
+        let mut index = vec![0usize; self.rank()];
+        for _ in 0..len {
+            let position = self.offset + index.iter().zip(&self.strides).map(|(&i, &stride)| i * stride).sum::<usize>();
+            values.push(data[position]);
+            for axis in (0..index.len()).rev() {
+                index[axis] += 1;
+                if index[axis] < self.shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+        Ok(Cow::Owned(values))
+    }
+
+    fn owned(&self) -> NeuralResult<&[f32]> {
+        match &self.storage {
+            Storage::Owned(data) => Ok(data),
+            Storage::Pool(handle) => {
+                Err(NeuralError::InvalidConfiguration(format!("tensor views pool buffer {}; read it through its runtime", handle)))
+            }
+        }
+    }
+
+    fn dim(&self, axis: usize) -> NeuralResult<usize> {
+        self.shape.get(axis).copied().ok_or(NeuralError::IndexOutOfRange { index: axis, len: self.rank() })
+    }
+}
+
+fn to_shape(shape: &[u32]) -> Vec<usize> {
+    shape.iter().map(|&dim| dim as usize).collect()
+}
+
+fn element_count(shape: &[usize]) -> NeuralResult<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |count, &dim| count.checked_mul(dim))
+        .ok_or_else(|| NeuralError::InvalidConfiguration("tensor shape overflows".to_string()))
+}
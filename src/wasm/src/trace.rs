@@ -0,0 +1,224 @@
+// Kernel trace spans in the OpenTelemetry data model
+//
+// With tracing enabled, every runtime kernel becomes a span: a 128-bit trace id
+// shared by the runtime's spans, a random 64-bit span id, the parent span id and
+// start/end times in Unix nanoseconds. set_trace_parent takes a W3C traceparent
+// header ("00-<trace id>-<parent span id>-<flags>"), so kernel spans join the
+// caller's distributed trace as children of its current span; without one they
+// hang off a fresh trace id as roots.
+//
+// Spans reach the host two ways:
+//   - a listener set with set_trace_listener is called with a JSON event when a
+//     kernel starts and when it ends, using OTLP/JSON field names (ids in hex,
+//     nanosecond times as decimal strings, dims and bytes as attributes);
+//   - finished spans are buffered, up to the capacity given to enable_tracing, and
+//     drained as a binary log by take_trace_log. Spans past the capacity are
+//     dropped and counted.
+// Kernels that fail emit a start event but no end event and are not buffered.
+//
+// Binary log layout (little-endian):
+//   magic "SATR", version u16, reserved u16, span_count u32, then per span
+//   trace_id [u8; 16], span_id [u8; 8], parent_span_id [u8; 8] (zero for a root),
+//   start_unix_nano u64, duration_nano u64, bytes u64, name_len u8, name,
+//   dim_count u8, dim_count × u32
+// Ids are stored in their hex byte order, i.e. big-endian.
+
+use wasm_bindgen::prelude::*;
+
+use crate::clock::wall_time_ms;
+use crate::error::{NeuralError, NeuralResult};
+use crate::logging::push_json_string;
+use crate::rng::{Rng, SecureRng};
+use crate::serialization::ByteWriter;
+
+pub const TRACE_MAGIC: &[u8; 4] = b"SATR";
+pub const TRACE_VERSION: u16 = 1;
+
+// A started span, carried by the kernel until it finishes
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OpenSpan {
+    span_id: u64,
+    start_unix_ms: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    name: &'static str,
+    span_id: u64,
+    start_unix_ns: u64,
+    duration_ns: u64,
+    bytes: u64,
+    dims: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Tracer {
+    trace_id: u128,
+    // 0 when spans are roots
+    parent_span_id: u64,
+    ids: Rng,
+    listener: Option<js_sys::Function>,
+    spans: Vec<Span>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl Tracer {
+    pub(crate) fn new(capacity: usize) -> NeuralResult<Tracer> {
+        if capacity == 0 {
+            return Err(NeuralError::InvalidConfiguration("trace buffer capacity must be non-zero".to_string()));
+        }
+        let mut ids = Rng::new(SecureRng::new().next_u64());
+        let trace_id = random_trace_id(&mut ids);
+        Ok(Tracer { trace_id, parent_span_id: 0, ids, listener: None, spans: Vec::new(), capacity, dropped: 0 })
+    }
+
+    // Join the trace in a W3C traceparent header, or start a fresh root trace with None
+    pub(crate) fn set_parent(&mut self, traceparent: Option<&str>) -> NeuralResult<()> {
+        match traceparent {
+            Some(header) => {
+                let (trace_id, parent_span_id) = parse_traceparent(header)?;
+                self.trace_id = trace_id;
+                self.parent_span_id = parent_span_id;
+            }
+            None => {
+                self.trace_id = random_trace_id(&mut self.ids);
+                self.parent_span_id = 0;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn trace_id(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    pub(crate) fn set_listener(&mut self, listener: Option<js_sys::Function>) {
+        self.listener = listener;
+    }
+
+    pub(crate) fn start(&mut self, name: &'static str) -> OpenSpan {
+        let span = OpenSpan { span_id: nonzero(self.ids.next_u64()), start_unix_ms: wall_time_ms() };
+        if self.listener.is_some() {
+            let mut json = self.event_json("start", name, span.span_id);
+            json.push_str(&format!(",\"startTimeUnixNano\":\"{}\"}}", unix_nanos(span.start_unix_ms)));
+            self.notify(&json);
+        }
+        span
+    }
+
+    pub(crate) fn finish(&mut self, name: &'static str, open: OpenSpan, elapsed_ms: f64, bytes: usize, dims: &[usize]) {
+        let span = Span {
+            name,
+            span_id: open.span_id,
+            start_unix_ns: unix_nanos(open.start_unix_ms),
+            duration_ns: (elapsed_ms.max(0.0) * 1e6) as u64,
+            bytes: bytes as u64,
+            dims: dims.iter().map(|&dim| dim.min(u32::MAX as usize) as u32).collect(),
+        };
+        if self.listener.is_some() {
+            let mut json = self.event_json("end", name, span.span_id);
+            let dims: Vec<String> = span.dims.iter().map(u32::to_string).collect();
+            json.push_str(&format!(
+                ",\"startTimeUnixNano\":\"{}\",\"endTimeUnixNano\":\"{}\",\"durationMs\":{},\"attributes\":{{\"kernel.bytes\":{},\"kernel.dims\":[{}]}}}}",
+                span.start_unix_ns,
+                span.start_unix_ns + span.duration_ns,
+                elapsed_ms.max(0.0),
+                span.bytes,
+                dims.join(",")
+            ));
+            self.notify(&json);
+        }
+        if self.spans.len() < self.capacity {
+            self.spans.push(span);
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    pub(crate) fn pending(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // Drain the buffered spans as a binary log
+    pub(crate) fn take_log(&mut self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(TRACE_MAGIC);
+        writer.u16(TRACE_VERSION);
+        writer.u16(0);
+        writer.u32(self.spans.len() as u32);
+        for span in self.spans.drain(..) {
+            writer.bytes(&self.trace_id.to_be_bytes());
+            writer.bytes(&span.span_id.to_be_bytes());
+            writer.bytes(&self.parent_span_id.to_be_bytes());
+            writer.u64(span.start_unix_ns);
+            writer.u64(span.duration_ns);
+            writer.u64(span.bytes);
+            writer.u8(span.name.len() as u8);
+            writer.bytes(span.name.as_bytes());
+            writer.u8(span.dims.len().min(u8::MAX as usize) as u8);
+            for &dim in span.dims.iter().take(u8::MAX as usize) {
+                writer.u32(dim);
+            }
+        }
+        writer.finish()
+    }
+
+    // {"event", "traceId", "spanId", "parentSpanId", "name" without the closing brace
+    fn event_json(&self, event: &str, name: &str, span_id: u64) -> String {
+        let mut json = format!("{{\"event\":\"{}\",\"traceId\":\"{}\",\"spanId\":\"{:016x}\",\"parentSpanId\":", event, self.trace_id(), span_id);
+        match self.parent_span_id {
+            0 => json.push_str("null"),
+            parent => json.push_str(&format!("\"{:016x}\"", parent)),
+        }
+        json.push_str(",\"name\":");
+        push_json_string(&mut json, name);
+        json
+    }
+
+    fn notify(&self, json: &str) {
+        if let Some(listener) = &self.listener {
+            // A failing listener must not fail the kernel
+            let _ = listener.call1(&JsValue::NULL, &JsValue::from_str(json));
+        }
+    }
+}
+
+// All-zero ids are invalid in W3C trace context
+fn nonzero(id: u64) -> u64 {
+    id.max(1)
+}
+
+fn random_trace_id(rng: &mut Rng) -> u128 {
+    (((rng.next_u64() as u128) << 64) | rng.next_u64() as u128).max(1)
+}
+
+fn unix_nanos(unix_ms: f64) -> u64 {
+    (unix_ms.max(0.0) * 1e6) as u64
+}
+
+// "00-<32 hex trace id>-<16 hex parent id>-<2 hex flags>"; later versions may append fields
+fn parse_traceparent(header: &str) -> NeuralResult<(u128, u64)> {
+    let invalid = || NeuralError::InvalidFormat(format!("invalid traceparent {:?}", header));
+    let mut fields = header.trim().split('-');
+    let (Some(version), Some(trace_id), Some(parent_id), Some(flags)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+        return Err(invalid());
+    };
+    let is_hex = |field: &str, len: usize| field.len() == len && field.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    if !is_hex(version, 2) || version == "ff" || !is_hex(trace_id, 32) || !is_hex(parent_id, 16) || !is_hex(flags, 2) {
+        return Err(invalid());
+    }
+    if version == "00" && fields.next().is_some() {
+        return Err(invalid());
+    }
+    let trace_id = u128::from_str_radix(trace_id, 16).map_err(|_| invalid())?;
+    let parent_id = u64::from_str_radix(parent_id, 16).map_err(|_| invalid())?;
+    if trace_id == 0 || parent_id == 0 {
+        return Err(invalid());
+    }
+    Ok((trace_id, parent_id))
+}
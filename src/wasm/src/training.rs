@@ -0,0 +1,730 @@
+// Supervised training by mini-batch gradient descent
+//
+// Each sample is scored by the network's loss (loss.rs, mean squared error by
+// default), averaged over the batch. Gradients are accumulated for the whole batch and applied once; the two
+// halves are also available separately so agents can ship gradients (gradients.rs).
+// The forward pass runs layer by layer over the whole batch, since batch
+// normalization needs every sample's activations before it can normalize any.
+//
+// NeuralNetwork.fit runs whole epochs under FitOptions: the learning rate follows
+// a schedule, early stopping may end the run, and an optional JS callback receives
+// (epoch, loss, learning_rate) after every epoch and can stop training by
+// returning false.
+//
+// TrainingSafety guards every optimizer step of train_batch and apply_gradients.
+// Gradients whose global L2 norm (over every layer's weights and biases) exceeds
+// max_gradient_norm are rescaled to that norm. A step whose loss, gradients or
+// updated parameters are not finite is rolled back, restoring the layers and the
+// optimizer's moments to where they were before it, and reported as a
+// TrainingFault: logged as a JSON warning under the "training" target, kept as the
+// network's last_training_fault and returned as a NonFiniteTraining error.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::gradient_optimizer::OptimizerState;
+use crate::linalg;
+use crate::logging::{log_event, LogLevel};
+use crate::loss::Loss;
+use crate::network::{DenseLayer, Layer, LayerKind, NeuralNetwork, WeightStorage};
+use crate::normalization::NormCache;
+use crate::rng::Rng;
+use crate::scheduler::{EarlyStopping, LearningRateSchedule};
+
+// Result of an asynchronous training run
+#[wasm_bindgen]
+pub struct TrainingOutcome {
+    network: NeuralNetwork,
+    losses: Vec<f32>,
+    report: Option<FitReport>,
+}
+
+#[wasm_bindgen]
+impl TrainingOutcome {
+    #[wasm_bindgen(getter)]
+    pub fn network(&self) -> NeuralNetwork {
+        self.network.clone()
+    }
+
+    // Mean loss of each completed epoch
+    #[wasm_bindgen(getter)]
+    pub fn losses(&self) -> Vec<f32> {
+        self.losses.clone()
+    }
+
+    // Schedule and early-stopping details; only set by fit_async
+    #[wasm_bindgen(getter)]
+    pub fn report(&self) -> Option<FitReport> {
+        self.report.clone()
+    }
+}
+
+impl TrainingOutcome {
+    pub fn new(network: NeuralNetwork, losses: Vec<f32>) -> TrainingOutcome {
+        TrainingOutcome { network, losses, report: None }
+    }
+
+    pub fn with_report(network: NeuralNetwork, report: FitReport) -> TrainingOutcome {
+        TrainingOutcome { network, losses: report.losses.clone(), report: Some(report) }
+    }
+}
+
+// How NeuralNetwork.fit trains: epochs, batch size, learning-rate schedule and
+// optional early stopping
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitOptions {
+    pub epochs: usize,
+    pub batch_size: usize,
+    // When the run ends, return to the weights of the best epoch
+    pub restore_best: bool,
+    // Reshuffle the Dataset before every epoch (fit_dataset only)
+    pub shuffle: bool,
+    schedule: LearningRateSchedule,
+    early_stopping: Option<EarlyStopping>,
+}
+
+#[wasm_bindgen]
+impl FitOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(epochs: usize, batch_size: usize, schedule: &LearningRateSchedule) -> FitOptions {
+        FitOptions { epochs, batch_size, restore_best: false, shuffle: false, schedule: *schedule, early_stopping: None }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_schedule(&mut self, schedule: &LearningRateSchedule) {
+        self.schedule = *schedule;
+    }
+
+    #[wasm_bindgen]
+    pub fn schedule(&self) -> LearningRateSchedule {
+        self.schedule
+    }
+
+    // A fresh copy of `stopping` watches every run; pass undefined to train all epochs
+    #[wasm_bindgen]
+    pub fn set_early_stopping(&mut self, stopping: Option<EarlyStopping>) {
+        self.early_stopping = stopping.map(|mut stopping| {
+            stopping.reset();
+            stopping
+        });
+    }
+}
+
+impl FitOptions {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if self.epochs == 0 {
+            return Err(NeuralError::InvalidConfiguration("epochs must be non-zero".to_string()));
+        }
+        if self.batch_size == 0 {
+            return Err(NeuralError::InvalidConfiguration("batch size must be non-zero".to_string()));
+        }
+        self.schedule.validate()
+    }
+}
+
+// Gradient clipping and non-finite rollback for each training step
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainingSafety {
+    // Rescale gradients whose global norm exceeds this; 0 disables clipping
+    pub max_gradient_norm: f32,
+    // Undo steps that produce a non-finite loss, gradient or parameter
+    pub rollback_non_finite: bool,
+}
+
+impl Default for TrainingSafety {
+    fn default() -> Self {
+        TrainingSafety { max_gradient_norm: 0.0, rollback_non_finite: true }
+    }
+}
+
+#[wasm_bindgen]
+impl TrainingSafety {
+    // No clipping, rollback on
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TrainingSafety {
+        TrainingSafety::default()
+    }
+}
+
+impl TrainingSafety {
+    pub fn validate(&self) -> NeuralResult<()> {
+        if !self.max_gradient_norm.is_finite() || self.max_gradient_norm < 0.0 {
+            return Err(NeuralError::InvalidConfiguration("maximum gradient norm must be finite and non-negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+// Where a rolled-back step first went non-finite
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultStage {
+    Loss = 0,
+    Gradients = 1,
+    Parameters = 2,
+}
+
+impl FaultStage {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            FaultStage::Loss => "loss",
+            FaultStage::Gradients => "gradients",
+            FaultStage::Parameters => "parameters",
+        }
+    }
+}
+
+// A training step that was rolled back
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainingFault {
+    stage: FaultStage,
+    layer: Option<usize>,
+    step: u64,
+    loss: f32,
+    gradient_norm: f32,
+}
+
+#[wasm_bindgen]
+impl TrainingFault {
+    #[wasm_bindgen(getter)]
+    pub fn stage(&self) -> FaultStage {
+        self.stage
+    }
+
+    // First layer with a non-finite gradient or parameter; undefined for the loss
+    #[wasm_bindgen(getter)]
+    pub fn layer(&self) -> Option<usize> {
+        self.layer
+    }
+
+    // Optimizer step that was attempted, counting from 1
+    #[wasm_bindgen(getter)]
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn loss(&self) -> f32 {
+        self.loss
+    }
+
+    // Global gradient norm before clipping
+    #[wasm_bindgen(getter)]
+    pub fn gradient_norm(&self) -> f32 {
+        self.gradient_norm
+    }
+
+    // {"event", "stage", "layer", "step", "loss", "gradient_norm"}; non-finite numbers are null
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"event\":\"training_rollback\",\"stage\":\"{}\",\"layer\":{},\"step\":{},\"loss\":{},\"gradient_norm\":{}}}",
+            self.stage.name(),
+            self.layer.map_or("null".to_string(), |layer| layer.to_string()),
+            self.step,
+            json_number(self.loss),
+            json_number(self.gradient_norm)
+        )
+    }
+}
+
+impl TrainingFault {
+    pub(crate) fn error(&self) -> NeuralError {
+        NeuralError::NonFiniteTraining { stage: self.stage.name().to_string(), layer: self.layer }
+    }
+}
+
+// What happened during NeuralNetwork.fit
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitReport {
+    losses: Vec<f32>,
+    learning_rates: Vec<f32>,
+    stopped_early: bool,
+    best_epoch: usize,
+    best_loss: f32,
+    restored_best: bool,
+}
+
+#[wasm_bindgen]
+impl FitReport {
+    // Mean loss of each completed epoch
+    #[wasm_bindgen(getter)]
+    pub fn losses(&self) -> Vec<f32> {
+        self.losses.clone()
+    }
+
+    // Learning rate each completed epoch used
+    #[wasm_bindgen(getter)]
+    pub fn learning_rates(&self) -> Vec<f32> {
+        self.learning_rates.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn epochs_run(&self) -> usize {
+        self.losses.len()
+    }
+
+    // Ended before the last epoch, by early stopping or the callback
+    #[wasm_bindgen(getter)]
+    pub fn stopped_early(&self) -> bool {
+        self.stopped_early
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn best_epoch(&self) -> usize {
+        self.best_epoch
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn best_loss(&self) -> f32 {
+        self.best_loss
+    }
+
+    // The weights were rolled back to best_epoch
+    #[wasm_bindgen(getter)]
+    pub fn restored_best(&self) -> bool {
+        self.restored_best
+    }
+}
+
+// Epoch bookkeeping shared by the synchronous and asynchronous fit loops
+pub(crate) struct FitProgress {
+    options: FitOptions,
+    losses: Vec<f32>,
+    learning_rates: Vec<f32>,
+    best_epoch: usize,
+    best_loss: f32,
+    halted: bool,
+}
+
+impl FitProgress {
+    pub(crate) fn new(options: &FitOptions) -> NeuralResult<FitProgress> {
+        options.validate()?;
+        Ok(FitProgress {
+            options: *options,
+            losses: Vec::with_capacity(options.epochs),
+            learning_rates: Vec::with_capacity(options.epochs),
+            best_epoch: 0,
+            best_loss: f32::INFINITY,
+            halted: false,
+        })
+    }
+
+    // Learning rate for the next epoch, or None once the run is over
+    pub(crate) fn next_rate(&self) -> Option<f32> {
+        let epoch = self.losses.len();
+        let stopped = self.options.early_stopping.is_some_and(|stopping| stopping.should_stop());
+        if epoch >= self.options.epochs || stopped || self.halted {
+            return None;
+        }
+        Some(self.options.schedule.rate(epoch))
+    }
+
+    // Log a finished epoch and tell the callback; true if it set a new best loss
+    pub(crate) fn record(&mut self, loss: f32, rate: f32, on_epoch: Option<&js_sys::Function>) -> NeuralResult<bool> {
+        let epoch = self.losses.len();
+        self.losses.push(loss);
+        self.learning_rates.push(rate);
+        let improved = match self.options.early_stopping.as_mut() {
+            Some(stopping) => {
+                stopping.observe(loss);
+                stopping.improved()
+            }
+            None => loss < self.best_loss,
+        };
+        if improved {
+            self.best_epoch = epoch;
+            self.best_loss = loss;
+        }
+        if let Some(callback) = on_epoch {
+            let verdict = callback
+                .call3(&JsValue::NULL, &JsValue::from(epoch as u32), &JsValue::from(loss), &JsValue::from(rate))
+                .map_err(|err| {
+                    let reason = err.as_string().unwrap_or_else(|| "exception".to_string());
+                    NeuralError::InvalidConfiguration(format!("epoch callback threw: {}", reason))
+                })?;
+            self.halted = verdict.as_bool() == Some(false);
+        }
+        Ok(improved)
+    }
+
+    pub(crate) fn restore_best(&self) -> bool {
+        self.options.restore_best
+    }
+
+    pub(crate) fn epochs_run(&self) -> usize {
+        self.losses.len()
+    }
+
+    pub(crate) fn best_epoch(&self) -> usize {
+        self.best_epoch
+    }
+
+    pub(crate) fn finish(self, restored_best: bool) -> FitReport {
+        FitReport {
+            stopped_early: self.losses.len() < self.options.epochs,
+            losses: self.losses,
+            learning_rates: self.learning_rates,
+            best_epoch: self.best_epoch,
+            best_loss: self.best_loss,
+            restored_best,
+        }
+    }
+}
+
+// Per-layer gradient accumulators, shaped like the layer's parameters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LayerGradients {
+    pub(crate) weights: Vec<f32>,
+    pub(crate) biases: Vec<f32>,
+}
+
+// What one layer's backward pass needs from the training forward pass
+enum LayerCache {
+    // Pre-activations of every sample
+    Dense(Vec<f32>),
+    Dropout(Vec<f32>),
+    Norm(NormCache),
+}
+
+// Gradients of the mean batch loss for every layer, and that loss, without
+// touching the weights. The forward pass runs in training mode: dropout draws
+// masks from `rng` and batch norm updates its running statistics.
+pub(crate) fn compute_gradients(
+    layers: &mut [Layer],
+    inputs: &[f32],
+    targets: &[f32],
+    batch_size: usize,
+    simd: bool,
+    rng: &mut Rng,
+    loss: &Loss,
+) -> NeuralResult<(Vec<LayerGradients>, f32)> {
+    let output_size = check_batch(layers, inputs, targets, batch_size)?;
+
+    // Forward pass over the whole batch, keeping every layer's output
+    let mut outputs: Vec<Vec<f32>> = Vec::with_capacity(layers.len() + 1);
+    outputs.push(inputs.to_vec());
+    let mut caches = Vec::with_capacity(layers.len());
+    for layer in layers.iter_mut() {
+        let input = &outputs[outputs.len() - 1];
+        let (output, cache) = match layer {
+            Layer::Dense(dense) => {
+                let (pre, post) = dense_forward(dense, input, simd)?;
+                (post, LayerCache::Dense(pre))
+            }
+            Layer::Dropout(dropout) => {
+                let (output, mask) = dropout.forward_train(input, rng);
+                (output, LayerCache::Dropout(mask))
+            }
+            Layer::Norm(norm) => {
+                let (output, cache) = norm.forward_train(input, batch_size, simd)?;
+                (output, LayerCache::Norm(cache))
+            }
+            _ => return Err(untrainable()),
+        };
+        caches.push(cache);
+        outputs.push(output);
+    }
+
+    let prediction = &outputs[layers.len()];
+    let mut total_loss = 0.0;
+    let mut grad = vec![0.0; prediction.len()];
+    for ((predicted, target), sample_grad) in
+        prediction.chunks_exact(output_size).zip(targets.chunks_exact(output_size)).zip(grad.chunks_exact_mut(output_size))
+    {
+        total_loss += loss.sample(predicted, target, sample_grad)?;
+    }
+
+    // Normalization layers always fill full-size gradients; frozen ones are emptied
+    // once the pass is done
+    let adapted = has_adapters(layers);
+    let mut gradients: Vec<LayerGradients> = layers
+        .iter()
+        .map(|layer| {
+            let (weights, biases) = match layer {
+                Layer::Norm(_) => (layer.weight_count(), layer.biases().len()),
+                _ => trainable_shape(layer, adapted),
+            };
+            LayerGradients { weights: vec![0.0; weights], biases: vec![0.0; biases] }
+        })
+        .collect();
+    for (index, (layer, cache)) in layers.iter().zip(&caches).enumerate().rev() {
+        let gradient = &mut gradients[index];
+        grad = match (layer, cache) {
+            (Layer::Dense(dense), LayerCache::Dense(pre)) => {
+                dense_backward(dense, (pre, &outputs[index + 1]), &outputs[index], grad, gradient, index > 0, simd)?
+            }
+            (Layer::Dropout(_), LayerCache::Dropout(mask)) => {
+                for (delta, factor) in grad.iter_mut().zip(mask) {
+                    *delta *= factor;
+                }
+                grad
+            }
+            (Layer::Norm(norm), LayerCache::Norm(cache)) => norm.backward(cache, &grad, batch_size, gradient),
+            _ => unreachable!("caches are built from the same layers"),
+        };
+    }
+
+    let scale = 1.0 / batch_size as f32;
+    for (layer, gradient) in layers.iter().zip(gradients.iter_mut()) {
+        let (weights, biases) = trainable_shape(layer, adapted);
+        gradient.weights.truncate(weights);
+        gradient.biases.truncate(biases);
+        for grad in gradient.weights.iter_mut().chain(gradient.biases.iter_mut()) {
+            *grad *= scale;
+        }
+    }
+    Ok((gradients, total_loss * scale))
+}
+
+// One optimizer step with `learning_rate` as the step size; see gradient_optimizer.rs
+pub(crate) fn apply_gradients(
+    layers: &mut [Layer],
+    gradients: &[LayerGradients],
+    learning_rate: f32,
+    optimizer: &mut OptimizerState,
+) -> NeuralResult<()> {
+    check_learning_rate(learning_rate)?;
+    check_trainable(layers)?;
+    if gradients.len() != layers.len() {
+        return Err(NeuralError::DimensionMismatch { expected: layers.len(), actual: gradients.len() });
+    }
+    let adapted = has_adapters(layers);
+    for (layer, gradient) in layers.iter().zip(gradients) {
+        let (weights, biases) = trainable_shape(layer, adapted);
+        if gradient.weights.len() != weights {
+            return Err(NeuralError::DimensionMismatch { expected: weights, actual: gradient.weights.len() });
+        }
+        if gradient.biases.len() != biases {
+            return Err(NeuralError::DimensionMismatch { expected: biases, actual: gradient.biases.len() });
+        }
+    }
+
+    // Weights then biases of every layer, the order the optimizer's moments follow
+    let mut tensors: Vec<(&mut [f32], &[f32])> = Vec::with_capacity(2 * layers.len());
+    for (layer, gradient) in layers.iter_mut().zip(gradients) {
+        let (weights, biases): (&mut [f32], &mut [f32]) = match layer {
+            Layer::Dense(DenseLayer { adapter: Some(adapter), .. }) => (&mut adapter.parameters, &mut []),
+            _ if adapted => (&mut [], &mut []),
+            Layer::Dense(DenseLayer { weights: WeightStorage::F32(weights), biases, .. }) => (weights, biases),
+            Layer::Norm(norm) => (&mut norm.gamma, &mut norm.beta),
+            _ => (&mut [], &mut []),
+        };
+        tensors.push((weights, &gradient.weights));
+        tensors.push((biases, &gradient.biases));
+    }
+    optimizer.step(tensors, learning_rate)
+}
+
+// Global L2 norm of every gradient, accumulated in f64; NaN or infinite if any gradient is
+pub(crate) fn gradient_norm(gradients: &[LayerGradients]) -> f32 {
+    let squares: f64 = gradients
+        .iter()
+        .flat_map(|gradient| gradient.weights.iter().chain(&gradient.biases))
+        .map(|&grad| grad as f64 * grad as f64)
+        .sum();
+    squares.sqrt() as f32
+}
+
+// Scale the gradients down to `max_norm` when their global norm `norm` exceeds it
+pub(crate) fn clip_gradients(gradients: &mut [LayerGradients], norm: f32, max_norm: f32) {
+    if max_norm <= 0.0 || !norm.is_finite() || norm <= max_norm {
+        return;
+    }
+    let scale = max_norm / norm;
+    for gradient in gradients.iter_mut() {
+        for grad in gradient.weights.iter_mut().chain(gradient.biases.iter_mut()) {
+            *grad *= scale;
+        }
+    }
+}
+
+// A step's safety net: clip, check the loss and gradients, apply, check the
+// parameters. With rollback on, `backup` holds the layers and optimizer from before
+// the step and a non-finite value restores them and reports a fault. Returns the
+// gradient norm before clipping.
+pub(crate) fn guarded_step(
+    layers: &mut Vec<Layer>,
+    optimizer: &mut OptimizerState,
+    (gradients, loss): (&mut [LayerGradients], f32),
+    learning_rate: f32,
+    safety: &TrainingSafety,
+    backup: Option<(Vec<Layer>, OptimizerState)>,
+) -> NeuralResult<(f32, Option<TrainingFault>)> {
+    let norm = gradient_norm(gradients);
+    let mut fault = None;
+    if backup.is_some() {
+        if !loss.is_finite() {
+            fault = Some((FaultStage::Loss, None));
+        } else if let Some(layer) = gradients.iter().position(|g| g.weights.iter().chain(&g.biases).any(|x| !x.is_finite())) {
+            fault = Some((FaultStage::Gradients, Some(layer)));
+        }
+    }
+    if fault.is_none() {
+        clip_gradients(gradients, norm, safety.max_gradient_norm);
+        apply_gradients(layers, gradients, learning_rate, optimizer)?;
+        if backup.is_some() {
+            let layer = layers.iter().position(|layer| !parameters_finite(layer));
+            fault = layer.map(|layer| (FaultStage::Parameters, Some(layer)));
+        }
+    }
+    let (Some((stage, layer)), Some((saved_layers, saved_optimizer))) = (fault, backup) else {
+        return Ok((norm, None));
+    };
+    *layers = saved_layers;
+    *optimizer = saved_optimizer;
+    let fault = TrainingFault { stage, layer, step: optimizer.step_count() + 1, loss, gradient_norm: norm };
+    log_event!(LogLevel::Warn, "training", "{}", fault.to_json());
+    Ok((norm, Some(fault)))
+}
+
+pub(crate) fn check_learning_rate(learning_rate: f32) -> NeuralResult<()> {
+    if !learning_rate.is_finite() || learning_rate <= 0.0 {
+        return Err(NeuralError::InvalidConfiguration("learning rate must be positive and finite".to_string()));
+    }
+    Ok(())
+}
+
+// Trainable layers only; batch norm's running statistics count as parameters
+fn parameters_finite(layer: &Layer) -> bool {
+    let finite = |values: &[f32]| values.iter().all(|x| x.is_finite());
+    match layer {
+        Layer::Dense(DenseLayer { adapter: Some(adapter), .. }) => finite(&adapter.parameters),
+        Layer::Dense(DenseLayer { weights: WeightStorage::F32(weights), biases, .. }) => finite(weights) && finite(biases),
+        Layer::Norm(norm) => finite(&norm.gamma) && finite(&norm.beta) && finite(&norm.running_mean) && finite(&norm.running_var),
+        _ => true,
+    }
+}
+
+pub(crate) fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+// Validate a training batch against the layers; returns the output size
+pub(crate) fn check_batch(layers: &[Layer], inputs: &[f32], targets: &[f32], batch_size: usize) -> NeuralResult<usize> {
+    check_trainable(layers)?;
+    let (Some(first), Some(last)) = (layers.first(), layers.last()) else {
+        return Err(NeuralError::InvalidConfiguration("network has no layers to train".to_string()));
+    };
+    let (input_size, output_size) = (first.inputs(), last.outputs());
+    if inputs.len() != batch_size * input_size {
+        return Err(NeuralError::DimensionMismatch { expected: batch_size * input_size, actual: inputs.len() });
+    }
+    if targets.len() != batch_size * output_size {
+        return Err(NeuralError::DimensionMismatch { expected: batch_size * output_size, actual: targets.len() });
+    }
+    if batch_size < 2 && layers.iter().any(|layer| layer.kind() == LayerKind::BatchNorm) {
+        return Err(NeuralError::InvalidConfiguration("batch normalization needs at least two samples per training batch".to_string()));
+    }
+    Ok(output_size)
+}
+
+// Whether any dense layer carries a LoRA adapter, which freezes everything else
+pub(crate) fn has_adapters(layers: &[Layer]) -> bool {
+    layers.iter().any(|layer| matches!(layer, Layer::Dense(DenseLayer { adapter: Some(_), .. })))
+}
+
+// Lengths of a layer's (weight, bias) gradients: its adapter's parameters when it
+// has one, nothing for the frozen layers of an adapted network
+pub(crate) fn trainable_shape(layer: &Layer, adapted: bool) -> (usize, usize) {
+    match layer {
+        Layer::Dense(DenseLayer { adapter: Some(adapter), .. }) => (adapter.parameters.len(), 0),
+        _ if adapted => (0, 0),
+        _ => (layer.weight_count(), layer.biases().len()),
+    }
+}
+
+fn check_trainable(layers: &[Layer]) -> NeuralResult<()> {
+    for layer in layers {
+        match layer {
+            Layer::Dense(dense) => {
+                f32_weights(dense)?;
+            }
+            Layer::Dropout(_) | Layer::Norm(_) => {}
+            _ => return Err(untrainable()),
+        }
+    }
+    Ok(())
+}
+
+fn untrainable() -> NeuralError {
+    NeuralError::InvalidConfiguration("training supports dense, dropout and normalization layers only".to_string())
+}
+
+pub(crate) fn f32_weights(layer: &DenseLayer) -> NeuralResult<&[f32]> {
+    match &layer.weights {
+        WeightStorage::F32(weights) => Ok(weights),
+        _ => Err(NeuralError::InvalidConfiguration("training requires dense f32 weights".to_string())),
+    }
+}
+
+// Pre-activations and outputs of a dense layer for every sample in the batch
+fn dense_forward(layer: &DenseLayer, inputs: &[f32], simd: bool) -> NeuralResult<(Vec<f32>, Vec<f32>)> {
+    let weights = f32_weights(layer)?;
+    let mut pre = vec![0.0; inputs.len() / layer.inputs * layer.outputs];
+    for (input, row) in inputs.chunks_exact(layer.inputs).zip(pre.chunks_exact_mut(layer.outputs)) {
+        linalg::matvec_into(weights, input, row, layer.outputs, layer.inputs, simd)?;
+        if let Some(adapter) = &layer.adapter {
+            adapter.add_into(input, row, simd)?;
+        }
+        for (value, bias) in row.iter_mut().zip(&layer.biases) {
+            *value += bias;
+        }
+    }
+    let mut post = pre.clone();
+    for row in post.chunks_exact_mut(layer.outputs) {
+        layer.activation.apply_slice(row, simd);
+    }
+    Ok((pre, post))
+}
+
+// Accumulate the layer's weight and bias gradients (its adapter's, when it has one)
+// sample by sample and return dL/d(input), which is left empty for the first layer
+// (`upstream` off). Frozen layers come with empty gradients and only pass dL/d(input) on.
+fn dense_backward(
+    layer: &DenseLayer,
+    (pre_activations, outputs): (&[f32], &[f32]),
+    inputs: &[f32],
+    mut grad: Vec<f32>,
+    gradient: &mut LayerGradients,
+    upstream: bool,
+    simd: bool,
+) -> NeuralResult<Vec<f32>> {
+    let weights = f32_weights(layer)?;
+    let mut previous_grad = if upstream { vec![0.0; inputs.len()] } else { Vec::new() };
+    for (sample, delta) in grad.chunks_exact_mut(layer.outputs).enumerate() {
+        let span = sample * layer.outputs..(sample + 1) * layer.outputs;
+        layer.activation.backprop_slice(&pre_activations[span.clone()], &outputs[span], delta);
+
+        let previous = &inputs[sample * layer.inputs..(sample + 1) * layer.inputs];
+        let mut up = upstream.then(|| &mut previous_grad[sample * layer.inputs..(sample + 1) * layer.inputs]);
+        match &layer.adapter {
+            Some(adapter) => adapter.backward(previous, delta, &mut gradient.weights, up.as_deref_mut(), simd)?,
+            None => {
+                for ((row, bias_grad), d) in gradient.weights.chunks_exact_mut(layer.inputs).zip(gradient.biases.iter_mut()).zip(delta.iter()) {
+                    *bias_grad += d;
+                    for (weight_grad, x) in row.iter_mut().zip(previous) {
+                        *weight_grad += d * x;
+                    }
+                }
+            }
+        }
+
+        if let Some(up) = up {
+            for (row, d) in weights.chunks_exact(layer.inputs).zip(delta.iter()) {
+                for (value, weight) in up.iter_mut().zip(row) {
+                    *value += d * weight;
+                }
+            }
+        }
+    }
+    Ok(previous_grad)
+}
@@ -0,0 +1,201 @@
+// WebGPU compute backend (cargo feature `webgpu`)
+//
+// WebGPU readback is asynchronous, so GPU work is reached through the
+// Promise-returning entry points. The synchronous `Backend` methods run on the
+// best CPU kernels, which also win for small problems where upload and readback
+// dominate.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    gpu_buffer_usage, gpu_map_mode, Gpu, GpuAutoLayoutMode, GpuBindGroupDescriptor, GpuBindGroupEntry, GpuBuffer,
+    GpuBufferDescriptor, GpuComputePipeline, GpuComputePipelineDescriptor, GpuDevice, GpuProgrammableStage,
+    GpuShaderModuleDescriptor,
+};
+
+#[cfg(not(web_sys_unstable_apis))]
+compile_error!("the `webgpu` feature requires RUSTFLAGS=\"--cfg=web_sys_unstable_apis\"");
+
+use crate::activation::{ActivationAccuracy, ActivationKind};
+use crate::backend::{Backend, BackendKind};
+use crate::error::{NeuralError, NeuralResult};
+use crate::linalg;
+
+// Below this many multiply-adds the CPU finishes before a GPU round trip would
+pub const GPU_MIN_WORK: usize = 64 * 64 * 64;
+
+const WORKGROUP_SIZE: usize = 8;
+
+const MATMUL_SHADER: &str = r#"
+struct Dims {
+    m: u32,
+    n: u32,
+    k: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(0) var<storage, read> a: array<f32>;
+@group(0) @binding(1) var<storage, read> b: array<f32>;
+@group(0) @binding(2) var<storage, read_write> c: array<f32>;
+@group(0) @binding(3) var<uniform> dims: Dims;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let row = id.y;
+    let col = id.x;
+    if (row >= dims.m || col >= dims.n) {
+        return;
+    }
+    var sum = 0.0;
+    for (var p = 0u; p < dims.k; p = p + 1u) {
+        sum = sum + a[row * dims.k + p] * b[p * dims.n + col];
+    }
+    c[row * dims.n + col] = sum;
+}
+"#;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct GpuContext {
+    device: GpuDevice,
+    matmul_pipeline: GpuComputePipeline,
+    simd_fallback: bool,
+}
+
+#[wasm_bindgen]
+impl GpuContext {
+    // Acquire an adapter and device from `navigator.gpu`
+    #[wasm_bindgen]
+    pub async fn request() -> Result<GpuContext, NeuralError> {
+        let gpu: Gpu = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("navigator"))
+            .and_then(|navigator| js_sys::Reflect::get(&navigator, &JsValue::from_str("gpu")))
+            .ok()
+            .and_then(|gpu| gpu.dyn_into::<Gpu>().ok())
+            .ok_or_else(|| NeuralError::Unavailable("navigator.gpu".to_string()))?;
+
+        let adapter = JsFuture::from(gpu.request_adapter())
+            .await
+            .map_err(|err| gpu_error("requestAdapter", err))?
+            .into_option()
+            .ok_or_else(|| NeuralError::Unavailable("WebGPU adapter".to_string()))?;
+        let device = JsFuture::from(adapter.request_device()).await.map_err(|err| gpu_error("requestDevice", err))?;
+
+        let module = device.create_shader_module(&GpuShaderModuleDescriptor::new(MATMUL_SHADER));
+        let stage = GpuProgrammableStage::new(&module);
+        stage.set_entry_point("main");
+        let matmul_pipeline = device
+            .create_compute_pipeline(&GpuComputePipelineDescriptor::new_with_gpu_auto_layout_mode(GpuAutoLayoutMode::Auto, &stage));
+
+        Ok(GpuContext { device, matmul_pipeline, simd_fallback: crate::features::simd_available() })
+    }
+
+    // Promise resolving to C[m×n] = A[m×k] · B[k×n] as a Float32Array
+    #[wasm_bindgen]
+    pub fn matmul(&self, a: Vec<f32>, b: Vec<f32>, m: usize, n: usize, k: usize) -> js_sys::Promise {
+        let context = self.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let c = context.matmul_on_device(&a, &b, m, n, k).await?;
+            Ok(js_sys::Float32Array::from(&c[..]).into())
+        })
+    }
+}
+
+impl GpuContext {
+    pub async fn matmul_on_device(&self, a: &[f32], b: &[f32], m: usize, n: usize, k: usize) -> NeuralResult<Vec<f32>> {
+        check_len(a.len(), m * k)?;
+        check_len(b.len(), k * n)?;
+        if m == 0 || n == 0 {
+            return Ok(Vec::new());
+        }
+
+        let storage = gpu_buffer_usage::STORAGE | gpu_buffer_usage::COPY_DST;
+        let a_buffer = self.upload(f32_bytes(a), storage)?;
+        let b_buffer = self.upload(f32_bytes(b), storage)?;
+        let dims = [m as u32, n as u32, k as u32, 0];
+        let dims_bytes: Vec<u8> = dims.iter().flat_map(|value| value.to_le_bytes()).collect();
+        let dims_buffer = self.upload(&dims_bytes, gpu_buffer_usage::UNIFORM | gpu_buffer_usage::COPY_DST)?;
+
+        let output_bytes = (m * n * std::mem::size_of::<f32>()) as u32;
+        let c_buffer = self.buffer(output_bytes, gpu_buffer_usage::STORAGE | gpu_buffer_usage::COPY_SRC)?;
+        let readback = self.buffer(output_bytes, gpu_buffer_usage::MAP_READ | gpu_buffer_usage::COPY_DST)?;
+
+        let entries = [
+            GpuBindGroupEntry::new_with_gpu_buffer(0, &a_buffer),
+            GpuBindGroupEntry::new_with_gpu_buffer(1, &b_buffer),
+            GpuBindGroupEntry::new_with_gpu_buffer(2, &c_buffer),
+            GpuBindGroupEntry::new_with_gpu_buffer(3, &dims_buffer),
+        ];
+        let layout = self.matmul_pipeline.get_bind_group_layout(0);
+        let bind_group = self.device.create_bind_group(&GpuBindGroupDescriptor::new(&entries, &layout));
+
+        let encoder = self.device.create_command_encoder();
+        let pass = encoder.begin_compute_pass();
+        pass.set_pipeline(&self.matmul_pipeline);
+        pass.set_bind_group(0, Some(&bind_group));
+        pass.dispatch_workgroups_with_workgroup_count_y(n.div_ceil(WORKGROUP_SIZE) as u32, m.div_ceil(WORKGROUP_SIZE) as u32);
+        pass.end();
+        encoder
+            .copy_buffer_to_buffer_with_u32_and_u32_and_u32(&c_buffer, 0, &readback, 0, output_bytes)
+            .map_err(|err| gpu_error("copyBufferToBuffer", err))?;
+        self.device.queue().submit(&[encoder.finish()]);
+
+        JsFuture::from(readback.map_async(gpu_map_mode::READ)).await.map_err(|err| gpu_error("mapAsync", err))?;
+        let mapped = readback.get_mapped_range().map_err(|err| gpu_error("getMappedRange", err))?;
+        let c = js_sys::Float32Array::new(&mapped).to_vec();
+        readback.unmap();
+
+        for buffer in [a_buffer, b_buffer, dims_buffer, c_buffer, readback] {
+            buffer.destroy();
+        }
+        Ok(c)
+    }
+
+    // Storage bindings must be non-empty and 4-byte sized
+    fn buffer(&self, size: u32, usage: u32) -> NeuralResult<GpuBuffer> {
+        self.device
+            .create_buffer(&GpuBufferDescriptor::new(size.max(4).next_multiple_of(4), usage))
+            .map_err(|err| gpu_error("createBuffer", err))
+    }
+
+    fn upload(&self, bytes: &[u8], usage: u32) -> NeuralResult<GpuBuffer> {
+        let buffer = self.buffer(bytes.len() as u32, usage)?;
+        if !bytes.is_empty() {
+            self.device
+                .queue()
+                .write_buffer_with_u32_and_u8_slice(&buffer, 0, bytes)
+                .map_err(|err| gpu_error("writeBuffer", err))?;
+        }
+        Ok(buffer)
+    }
+}
+
+impl Backend for GpuContext {
+    fn kind(&self) -> BackendKind {
+        BackendKind::WebGpu
+    }
+
+    fn matmul(&self, a: &[f32], b: &[f32], c: &mut [f32], m: usize, n: usize, k: usize) -> NeuralResult<()> {
+        linalg::matmul_into(a, b, c, m, n, k, self.simd_fallback)
+    }
+
+    fn activate(&self, values: &mut [f32], kind: ActivationKind, accuracy: ActivationAccuracy) {
+        kind.apply_slice_with(values, self.simd_fallback, accuracy);
+    }
+}
+
+fn f32_bytes(values: &[f32]) -> &[u8] {
+    // f32 has no padding or invalid bit patterns, so viewing it as bytes is sound
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values)) }
+}
+
+fn check_len(actual: usize, expected: usize) -> NeuralResult<()> {
+    if actual != expected {
+        return Err(NeuralError::DimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+fn gpu_error(operation: &str, err: JsValue) -> NeuralError {
+    NeuralError::Unavailable(format!("WebGPU {} failed: {:?}", operation, err))
+}
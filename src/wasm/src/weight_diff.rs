@@ -0,0 +1,197 @@
+// Differences between two weight sets
+//
+// diff_weights(a, b) compares two SASW blobs (export_weights) of the same
+// architecture. Per layer it reports the L2 and L∞ distance of b from a over
+// weights and biases, for drift monitoring, and it encodes a sparse delta that
+// turns a's parameters into b's: only runs of entries whose bits changed are
+// stored, carrying b's values so the result is exact. An agent holding a applies
+// it with NeuralNetwork.apply_weight_delta instead of downloading all of b. The
+// delta names the parameters it applies to and the ones it produces by SHA-256, so
+// a delta against another base, or a corrupted one, is rejected. Only parameters
+// change: the receiving network keeps its own preprocessor and layer precisions
+// (reduced-precision layers round the new values as set_parameters does).
+//
+// Parameters are flattened as in get_parameters: each layer's weights, then its
+// biases, in layer order. Runs separated by at most MAX_GAP unchanged entries are
+// merged, since a run header costs as much as two values.
+//
+// Delta layout (all integers and floats little-endian):
+//   magic            b"SASP"
+//   version          u16
+//   reserved         u16
+//   parameter_count  u32
+//   base_digest      [u8; 32]  (SHA-256 of the base parameters as f32 bytes)
+//   target_digest    [u8; 32]  (the same for the parameters the delta produces)
+//   run_count u32, run_count × { start u32, len u32, f32[len] }
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{NeuralError, NeuralResult};
+use crate::integrity::{sha256_digest, SHA256_LEN};
+use crate::network::Layer;
+use crate::serialization::{self, ByteReader, ByteWriter};
+
+pub const DELTA_MAGIC: &[u8; 4] = b"SASP";
+pub const DELTA_VERSION: u16 = 1;
+const MAX_GAP: usize = 2;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightDiff {
+    l2: Vec<f32>,
+    linf: Vec<f32>,
+    changed: usize,
+    delta: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl WeightDiff {
+    // Per-layer L2 distance over weights and biases
+    #[wasm_bindgen(getter)]
+    pub fn l2(&self) -> Vec<f32> {
+        self.l2.clone()
+    }
+
+    // Per-layer largest absolute change
+    #[wasm_bindgen(getter)]
+    pub fn linf(&self) -> Vec<f32> {
+        self.linf.clone()
+    }
+
+    // L2 distance over all parameters
+    #[wasm_bindgen(getter)]
+    pub fn total_l2(&self) -> f32 {
+        self.l2.iter().map(|distance| distance * distance).sum::<f32>().sqrt()
+    }
+
+    // Parameters whose bits differ
+    #[wasm_bindgen(getter)]
+    pub fn changed(&self) -> usize {
+        self.changed
+    }
+
+    // Sparse delta from a to b, for NeuralNetwork.apply_weight_delta
+    #[wasm_bindgen]
+    pub fn delta(&self) -> Vec<u8> {
+        self.delta.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn diff_weights(a: &[u8], b: &[u8]) -> Result<WeightDiff, NeuralError> {
+    let (a, b) = (serialization::decode_weights(a)?, serialization::decode_weights(b)?);
+    if a.input_size != b.input_size {
+        return Err(NeuralError::DimensionMismatch { expected: a.input_size, actual: b.input_size });
+    }
+    if a.layers.len() != b.layers.len() {
+        return Err(NeuralError::DimensionMismatch { expected: a.layers.len(), actual: b.layers.len() });
+    }
+    let (mut l2, mut linf) = (Vec::with_capacity(a.layers.len()), Vec::with_capacity(a.layers.len()));
+    for (index, (old, new)) in a.layers.iter().zip(&b.layers).enumerate() {
+        if !same_shape(old, new) {
+            return Err(NeuralError::InvalidConfiguration(format!("weight sets differ in the architecture of layer {}", index)));
+        }
+        let (old, new) = (layer_parameters(old), layer_parameters(new));
+        let mut squares = 0.0f64;
+        let mut largest = 0.0f32;
+        for (x, y) in old.iter().zip(&new) {
+            let change = y - x;
+            squares += change as f64 * change as f64;
+            largest = largest.max(change.abs());
+        }
+        l2.push(squares.sqrt() as f32);
+        linf.push(largest);
+    }
+
+    let base: Vec<f32> = a.layers.iter().flat_map(layer_parameters).collect();
+    let target: Vec<f32> = b.layers.iter().flat_map(layer_parameters).collect();
+    let changed = base.iter().zip(&target).filter(|(x, y)| x.to_bits() != y.to_bits()).count();
+    Ok(WeightDiff { l2, linf, changed, delta: encode_delta(&base, &target) })
+}
+
+// `base` with a delta from diff_weights applied
+pub(crate) fn apply_delta(base: &[f32], delta: &[u8]) -> NeuralResult<Vec<f32>> {
+    let mut reader = ByteReader::new(delta);
+    if reader.bytes(4)? != DELTA_MAGIC {
+        return Err(NeuralError::InvalidFormat("missing SASP header".to_string()));
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > DELTA_VERSION {
+        return Err(NeuralError::InvalidFormat(format!("unsupported weight delta version {}", version)));
+    }
+    reader.u16()?;
+    let parameter_count = reader.u32()? as usize;
+    if parameter_count != base.len() {
+        return Err(NeuralError::DimensionMismatch { expected: base.len(), actual: parameter_count });
+    }
+    if reader.bytes(SHA256_LEN)? != parameters_digest(base) {
+        return Err(NeuralError::InvalidConfiguration("weight delta was made against different parameters".to_string()));
+    }
+    let target_digest = reader.bytes(SHA256_LEN)?;
+    let mut parameters = base.to_vec();
+    for _ in 0..reader.u32()? {
+        let start = reader.u32()? as usize;
+        let len = reader.u32()? as usize;
+        let target = start
+            .checked_add(len)
+            .and_then(|end| parameters.get_mut(start..end))
+            .ok_or_else(|| NeuralError::InvalidFormat("delta run lies outside the parameters".to_string()))?;
+        target.copy_from_slice(&reader.f32_vec(len)?);
+    }
+    if !reader.is_empty() {
+        return Err(NeuralError::InvalidFormat("trailing bytes after payload".to_string()));
+    }
+    if parameters_digest(&parameters) != target_digest {
+        return Err(NeuralError::InvalidFormat("weight delta digest mismatch".to_string()));
+    }
+    Ok(parameters)
+}
+
+fn encode_delta(base: &[f32], target: &[f32]) -> Vec<u8> {
+    // Runs of changed entries, merged across short unchanged gaps
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (index, (x, y)) in base.iter().zip(target).enumerate() {
+        if x.to_bits() == y.to_bits() {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((start, len)) if index - (*start + *len) <= MAX_GAP => *len = index + 1 - *start,
+            _ => runs.push((index, 1)),
+        }
+    }
+
+    let mut writer = ByteWriter::new();
+    writer.bytes(DELTA_MAGIC);
+    writer.u16(DELTA_VERSION);
+    writer.u16(0);
+    writer.u32(base.len() as u32);
+    writer.bytes(&parameters_digest(base));
+    writer.bytes(&parameters_digest(target));
+    writer.u32(runs.len() as u32);
+    for (start, len) in runs {
+        writer.u32(start as u32);
+        writer.u32(len as u32);
+        writer.f32_slice(&target[start..start + len]);
+    }
+    writer.finish()
+}
+
+fn same_shape(a: &Layer, b: &Layer) -> bool {
+    a.kind() == b.kind()
+        && a.inputs() == b.inputs()
+        && a.outputs() == b.outputs()
+        && a.weight_count() == b.weight_count()
+        && a.biases().len() == b.biases().len()
+}
+
+fn layer_parameters(layer: &Layer) -> Vec<f32> {
+    let mut parameters = layer.dense_weights().into_owned();
+    parameters.extend_from_slice(layer.biases());
+    parameters
+}
+
+fn parameters_digest(parameters: &[f32]) -> [u8; SHA256_LEN] {
+    let mut writer = ByteWriter::new();
+    writer.f32_slice(parameters);
+    sha256_digest(&writer.finish())
+}